@@ -7,13 +7,16 @@ use oasis_core::config::OasisConfig;
 use oasis_core::cursor::CursorState;
 use oasis_core::dashboard::DashboardState;
 use oasis_core::net::{RemoteClient, RemoteListener, RustlsTlsProvider, StdNetworkBackend};
+use oasis_core::notification::NotificationCenter;
 use oasis_core::osk::OskState;
 use oasis_core::platform::DesktopPlatform;
-use oasis_core::skin::Skin;
+use oasis_core::skin::{CorruptedEffect, Skin, SkinWatcher};
+use oasis_core::skin_assets::SkinAssets;
 use oasis_core::startmenu::StartMenuState;
 use oasis_core::statusbar::StatusBar;
-use oasis_core::terminal::CommandRegistry;
+use oasis_core::terminal::{CommandRegistry, Completer};
 use oasis_core::transition;
+use oasis_core::wallpaper::WallpaperImage;
 use oasis_core::wm::manager::WindowManager;
 
 /// The UI modes the app supports.
@@ -32,6 +35,12 @@ pub enum Mode {
 pub struct AppState {
     pub config: OasisConfig,
     pub skin: Skin,
+    /// Set while `skin watch` is on; reloads the skin when its files change.
+    pub skin_watcher: Option<SkinWatcher>,
+    /// Set by `skin reload` until the reload is applied.
+    pub skin_reload_pending: bool,
+    /// Glitch effect of skins with `corrupted.toml`, applied each frame.
+    pub skin_effect: Option<CorruptedEffect>,
     pub active_theme: ActiveTheme,
     pub browser_config: BrowserConfig,
     pub platform: DesktopPlatform,
@@ -42,20 +51,40 @@ pub struct AppState {
     pub cmd_reg: CommandRegistry,
     pub cwd: String,
     pub input_buf: String,
+    /// Tab completion of `input_buf`.
+    pub completer: Completer,
     pub output_lines: Vec<String>,
     pub osk: Option<OskState>,
     pub app_runner: Option<AppRunner>,
     pub wm: WindowManager,
     pub open_runners: Vec<(String, AppRunner)>,
     pub browser: Option<BrowserWidget>,
+    /// Id of the WM dialog asking whether to save the browser's pending
+    /// download, while it is open.
+    pub download_dialog: Option<String>,
+    /// Cancel is held in Desktop mode, so L/R switch windows.
+    pub cancel_held: bool,
+    /// L/R switched windows while Cancel was held; releasing it then
+    /// leaves the active window open.
+    pub cancel_chorded: bool,
     pub net_backend: StdNetworkBackend,
     pub listener: Option<RemoteListener>,
     pub remote_client: Option<RemoteClient>,
     pub tls_provider: RustlsTlsProvider,
     pub mouse_cursor: CursorState,
+    /// Toasts posted by `notify`, browser downloads and the like.
+    pub notifications: NotificationCenter,
     pub mode: Mode,
     pub bg_color: Color,
     pub active_transition: Option<transition::TransitionState>,
+    /// The skin's wallpaper image, drawn instead of the generated
+    /// wallpaper when set.
+    pub wallpaper_image: Option<WallpaperImage>,
+    /// Set when the skin changed and the wallpaper (and the skin's
+    /// assets) must be rebuilt.
+    pub wallpaper_dirty: bool,
+    /// The nine-patches uploaded from the skin's assets.
+    pub skin_assets: SkinAssets,
     pub frame_counter: u64,
 }
 
@@ -1,14 +1,19 @@
-use oasis_core::active_theme::ActiveTheme;
+use oasis_core::active_theme::{ActiveTheme, wm_color_mut};
+use oasis_core::backend::Color;
 use oasis_core::browser::BrowserConfig;
 use oasis_core::dashboard::{DashboardConfig, DashboardState, discover_apps};
 use oasis_core::net::{ListenerConfig, RemoteClient, RemoteListener};
+use oasis_core::notification::{Notification, NotificationTheme};
 use oasis_core::sdi::SdiRegistry;
-use oasis_core::skin::{Skin, resolve_skin};
+use oasis_core::skin::theme::format_hex_color;
+use oasis_core::skin::{CorruptedEffect, Skin, SkinEffect, SkinWatcher, resolve_skin};
 use oasis_core::startmenu::StartMenuState;
-use oasis_core::terminal::{CommandOutput, Environment};
-use oasis_core::vfs::MemoryVfs;
+use oasis_core::terminal::{CommandOutput, ENFORCE_PERMISSIONS, Environment, format_json};
+use oasis_core::ui::icon::Icon;
+use oasis_core::vfs::{MemoryVfs, Vfs};
+use oasis_core::wm::manager::WmEvent;
 
-use crate::app_state::AppState;
+use crate::app_state::{AppState, Mode};
 use crate::terminal_sdi;
 
 /// Process a local terminal command result. Returns a pending skin swap name
@@ -29,6 +34,11 @@ pub fn process_command_output(
                 state.output_lines.push(row.join(" | "));
             }
         },
+        Ok(CommandOutput::Json(value)) => {
+            for l in format_json(&value).lines() {
+                state.output_lines.push(l.to_string());
+            }
+        },
         Ok(CommandOutput::Clear) => state.output_lines.clear(),
         Ok(CommandOutput::None) => {},
         Ok(CommandOutput::ListenToggle { port }) => {
@@ -98,9 +108,35 @@ pub fn process_command_output(
             };
             state.output_lines.push(format!("Browser sandbox: {st}"));
         },
+        Ok(CommandOutput::BrowserCache { clear }) => {
+            let report = browser_cache_report(state.browser.as_mut(), clear);
+            state.output_lines.push(report);
+        },
         Ok(CommandOutput::SkinSwap { name }) => {
             return Some(name);
         },
+        Ok(CommandOutput::SkinWatch { enable }) => {
+            let enable = enable.unwrap_or(state.skin_watcher.is_none());
+            let msg = set_skin_watch(state, enable);
+            state.output_lines.push(msg);
+        },
+        Ok(CommandOutput::SkinReload) => {
+            state.skin_reload_pending = true;
+        },
+        Ok(CommandOutput::SkinIntensity { intensity }) => {
+            let msg = match state.skin_effect.as_mut() {
+                Some(effect) => {
+                    effect.set_intensity(intensity);
+                    format!("Glitch intensity: {intensity:.2}")
+                },
+                None => format!("Skin '{}' has no glitch effect.", state.skin.manifest.name),
+            };
+            state.output_lines.push(msg);
+        },
+        Ok(CommandOutput::ThemeColor { key, color }) => {
+            let msg = apply_theme_color(state, &key, color);
+            state.output_lines.push(msg);
+        },
         Ok(CommandOutput::Multi(outputs)) => {
             let mut skin_swap = None;
             for output in outputs {
@@ -111,6 +147,9 @@ pub fn process_command_output(
             }
             return skin_swap;
         },
+        Ok(CommandOutput::Status { output, .. }) => {
+            return process_command_output(Ok(*output), state);
+        },
         Err(e) => {
             state.output_lines.push(format!("error: {e}"));
         },
@@ -122,24 +161,11 @@ pub fn process_command_output(
 pub fn apply_skin_swap(name: &str, state: &mut AppState, sdi: &mut SdiRegistry, vfs: &MemoryVfs) {
     match resolve_skin(name) {
         Ok(new_skin) => {
-            let swapped = Skin::swap(&state.skin, new_skin, sdi);
-            state.active_theme = ActiveTheme::from_skin(&swapped.theme);
-            state.browser_config = BrowserConfig::from_skin_theme(&swapped.theme);
-            state.wm.set_theme(swapped.theme.build_wm_theme());
-            let dash_config =
-                DashboardConfig::from_features(&swapped.features, &state.active_theme);
-            let apps = discover_apps(vfs, "/apps", Some("OASISOS")).unwrap_or_default();
-            state.dashboard = DashboardState::new(dash_config, apps);
-            state.bottom_bar.total_pages = state.dashboard.page_count();
-            state.bottom_bar.current_page = 0;
-            state.start_menu = StartMenuState::new_with_theme(
-                StartMenuState::default_items(),
-                &state.active_theme,
-            );
+            state.skin = Skin::swap(&state.skin, new_skin, sdi);
+            refresh_skin_state(state, vfs);
             state
                 .output_lines
-                .push(format!("Switched to skin: {}", swapped.manifest.name));
-            state.skin = swapped;
+                .push(format!("Switched to skin: {}", state.skin.manifest.name));
         },
         Err(e) => {
             state.output_lines.push(format!("Skin error: {e}"));
@@ -147,6 +173,150 @@ pub fn apply_skin_swap(name: &str, state: &mut AppState, sdi: &mut SdiRegistry,
     }
 }
 
+/// Rebuild everything derived from `state.skin` (theme, browser and WM
+/// colors, dashboard, start menu, wallpaper) after it changed.
+fn refresh_skin_state(state: &mut AppState, vfs: &MemoryVfs) {
+    let skin = &state.skin;
+    state.active_theme = ActiveTheme::from_skin(&skin.theme);
+    state.browser_config = BrowserConfig::from_skin_theme(&skin.theme);
+    state.wm.set_theme(skin.theme.build_wm_theme());
+    state.wm.set_dialog_theme(skin.theme.to_ui_theme());
+    state
+        .notifications
+        .set_theme(NotificationTheme::from_skin_theme(&skin.theme));
+    let dash_config = DashboardConfig::from_features(&skin.features, &state.active_theme);
+    let apps = discover_apps(vfs, "/apps", Some("OASISOS")).unwrap_or_default();
+    state.dashboard = DashboardState::new(dash_config, apps);
+    state.bottom_bar.total_pages = state.dashboard.page_count();
+    state.bottom_bar.current_page = 0;
+    state.start_menu =
+        StartMenuState::new_with_theme(StartMenuState::default_items(), &state.active_theme);
+    state.skin_effect = skin.corrupted_modifiers.clone().map(CorruptedEffect::new);
+    state.wallpaper_dirty = true;
+}
+
+/// Report or change a color of the live theme, for `theme get/set`.
+/// Returns a status line for the terminal.
+fn apply_theme_color(state: &mut AppState, key: &str, color: Option<Color>) -> String {
+    let mut wm_theme = state.wm.theme().clone();
+    let Some(slot) = state
+        .active_theme
+        .color_mut(key)
+        .or_else(|| wm_color_mut(&mut wm_theme, key))
+    else {
+        return format!("Unknown theme color: {key}");
+    };
+    let Some(color) = color else {
+        return format!("{key} = {}", format_hex_color(*slot));
+    };
+    *slot = color;
+    state.wm.set_theme(wm_theme);
+    // The start menu keeps its own copy of the theme.
+    state.start_menu =
+        StartMenuState::new_with_theme(StartMenuState::default_items(), &state.active_theme);
+    if key == "wallpaper_fallback" {
+        state.wallpaper_dirty = true;
+    }
+    format!("{key} = {}", format_hex_color(color))
+}
+
+/// Start or stop watching the active skin's directory. Returns a status
+/// line for the terminal.
+fn set_skin_watch(state: &mut AppState, enable: bool) -> String {
+    if !enable {
+        state.skin_watcher = None;
+        return "Skin watch off.".to_string();
+    }
+    state.skin_watcher = SkinWatcher::new(&state.skin);
+    match &state.skin_watcher {
+        Some(watcher) => format!("Watching {} for changes.", watcher.dir().display()),
+        None => format!(
+            "Skin '{}' is built in; only skins loaded from a directory can be watched.",
+            state.skin.manifest.name
+        ),
+    }
+}
+
+/// Reload the skin if `skin watch` is on and its files changed. Call
+/// about once a second.
+///
+/// A skin that fails to parse is reported in the terminal and the
+/// current one is kept; the next edit is picked up again.
+pub fn poll_skin_watcher(state: &mut AppState, sdi: &mut SdiRegistry, vfs: &MemoryVfs) {
+    let Some(watcher) = &mut state.skin_watcher else {
+        return;
+    };
+    // Follow skin swaps made since watching started.
+    if state.skin.source_dir.as_deref() != Some(watcher.dir()) {
+        let msg = set_skin_watch(state, true);
+        state.output_lines.push(msg);
+        trim_output(&mut state.output_lines);
+        return;
+    }
+    let old_objects: Vec<String> = state.skin.layout.objects.keys().cloned().collect();
+    let result = state.skin.reload_if_changed(watcher);
+    finish_skin_reload(state, sdi, vfs, &old_objects, result);
+}
+
+/// Reload the skin now, for `skin reload`.
+pub fn apply_skin_reload(state: &mut AppState, sdi: &mut SdiRegistry, vfs: &MemoryVfs) {
+    state.skin_reload_pending = false;
+    let old_objects: Vec<String> = state.skin.layout.objects.keys().cloned().collect();
+    let result = state.skin.reload().map(|_| true);
+    if result.is_ok()
+        && let Some(watcher) = &mut state.skin_watcher
+    {
+        watcher.rearm();
+    }
+    finish_skin_reload(state, sdi, vfs, &old_objects, result);
+}
+
+/// Rebuild the scene after a reload that replaced a skin with layout
+/// objects `old_objects`, or report why it failed.
+fn finish_skin_reload(
+    state: &mut AppState,
+    sdi: &mut SdiRegistry,
+    vfs: &MemoryVfs,
+    old_objects: &[String],
+    result: oasis_core::error::Result<bool>,
+) {
+    match result {
+        Ok(false) => return,
+        Ok(true) => {
+            for name in old_objects {
+                let _ = sdi.destroy(name);
+            }
+            state.skin.apply_layout(sdi);
+            refresh_skin_state(state, vfs);
+            log::info!("Reloaded skin: {}", state.skin.manifest.name);
+            state
+                .output_lines
+                .push(format!("Reloaded skin: {}", state.skin.manifest.name));
+        },
+        Err(e) => {
+            log::warn!("Skin reload failed, keeping current skin: {e}");
+            state
+                .output_lines
+                .push(format!("Skin reload failed, keeping current skin: {e}"));
+        },
+    }
+    trim_output(&mut state.output_lines);
+}
+
+/// Report (after optionally clearing) the browser resource cache.
+fn browser_cache_report(
+    browser: Option<&mut oasis_core::browser::BrowserWidget>,
+    clear: bool,
+) -> String {
+    let Some(bw) = browser else {
+        return "Browser is not open.".to_string();
+    };
+    if clear {
+        bw.clear_cache();
+    }
+    format!("Browser cache: {}", bw.cache_stats())
+}
+
 /// Format a remote command result as a response string, applying side effects
 /// (browser sandbox, skin swap) as needed.
 fn format_remote_response(
@@ -168,6 +338,7 @@ fn format_remote_response(
             }
             out
         },
+        Ok(CommandOutput::Json(value)) => format_json(&value),
         Ok(CommandOutput::Clear) => "OK".to_string(),
         Ok(CommandOutput::None) => "OK".to_string(),
         Ok(CommandOutput::ListenToggle { .. }) | Ok(CommandOutput::RemoteConnect { .. }) => {
@@ -184,12 +355,26 @@ fn format_remote_response(
             };
             format!("Browser sandbox: {st}")
         },
+        Ok(CommandOutput::BrowserCache { clear }) => browser_cache_report(browser.as_mut(), clear),
+        Ok(CommandOutput::SkinWatch { .. }) => {
+            "Skin watch is only available from the local terminal.".to_string()
+        },
+        Ok(CommandOutput::SkinReload) => {
+            "Skin reload is only available from the local terminal.".to_string()
+        },
+        Ok(CommandOutput::SkinIntensity { .. }) => {
+            "Skin intensity is only available from the local terminal.".to_string()
+        },
+        Ok(CommandOutput::ThemeColor { .. }) => {
+            "Theme colors are only available from the local terminal.".to_string()
+        },
         Ok(CommandOutput::SkinSwap { name }) => match resolve_skin(&name) {
             Ok(new_skin) => {
                 let swapped = Skin::swap(skin, new_skin, sdi);
                 *active_theme = ActiveTheme::from_skin(&swapped.theme);
                 *browser_config = BrowserConfig::from_skin_theme(&swapped.theme);
                 wm.set_theme(swapped.theme.build_wm_theme());
+                wm.set_dialog_theme(swapped.theme.to_ui_theme());
                 let msg = format!("Switched to skin: {}", swapped.manifest.name);
                 *skin = swapped;
                 msg
@@ -214,6 +399,15 @@ fn format_remote_response(
             }
             parts.join("\n")
         },
+        Ok(CommandOutput::Status { output, .. }) => format_remote_response(
+            Ok(*output),
+            browser,
+            skin,
+            active_theme,
+            browser_config,
+            wm,
+            sdi,
+        ),
         Err(e) => format!("error: {e}"),
     }
 }
@@ -233,6 +427,8 @@ pub fn poll_remote_listener(state: &mut AppState, sdi: &mut SdiRegistry, vfs: &m
         ref mut active_theme,
         ref mut browser_config,
         ref mut wm,
+        ref mut wallpaper_dirty,
+        ref mut skin_effect,
         ..
     } = *state;
 
@@ -250,11 +446,17 @@ pub fn poll_remote_listener(state: &mut AppState, sdi: &mut SdiRegistry, vfs: &m
             network: None,
             tls: Some(tls_provider),
             stdin: None,
+            enforce_permissions: ENFORCE_PERMISSIONS,
         };
         let result = cmd_reg.execute(&cmd_line, &mut env);
         *cwd = env.cwd;
+        let swap = matches!(result, Ok(CommandOutput::SkinSwap { .. }));
         let response =
             format_remote_response(result, browser, skin, active_theme, browser_config, wm, sdi);
+        if swap {
+            *wallpaper_dirty = true;
+            *skin_effect = skin.corrupted_modifiers.clone().map(CorruptedEffect::new);
+        }
         let _ = l.send_response(conn_idx, &response);
     }
 }
@@ -277,6 +479,115 @@ pub fn poll_remote_client(state: &mut AppState) {
     trim_output(&mut state.output_lines);
 }
 
+/// Advance the terminal's background jobs and print what they output.
+pub fn poll_jobs(state: &mut AppState, vfs: &mut MemoryVfs) {
+    if !state.cmd_reg.has_jobs() {
+        return;
+    }
+    let mut env = Environment {
+        cwd: state.cwd.clone(),
+        vfs,
+        power: Some(&state.platform),
+        time: Some(&state.platform),
+        usb: Some(&state.platform),
+        network: None,
+        tls: Some(&state.tls_provider),
+        stdin: None,
+        enforce_permissions: ENFORCE_PERMISSIONS,
+    };
+    state.cmd_reg.poll_jobs(&mut env);
+    let lines = state.cmd_reg.take_job_output();
+    if !lines.is_empty() {
+        state.output_lines.extend(lines);
+        trim_output(&mut state.output_lines);
+    }
+}
+
+/// Run the terminal's scheduled commands that are due at `now_ms`
+/// (milliseconds since startup). Their output goes to `schedule log`.
+pub fn poll_schedule(state: &mut AppState, vfs: &mut MemoryVfs, now_ms: u64) {
+    if !state.cmd_reg.has_schedule() {
+        return;
+    }
+    let mut env = Environment {
+        cwd: state.cwd.clone(),
+        vfs,
+        power: Some(&state.platform),
+        time: Some(&state.platform),
+        usb: Some(&state.platform),
+        network: None,
+        tls: Some(&state.tls_provider),
+        stdin: None,
+        enforce_permissions: ENFORCE_PERMISSIONS,
+    };
+    state.cmd_reg.run_schedule(now_ms, &mut env);
+}
+
+/// Carry out a window request left in `/var/wm/request` by the `wm`
+/// terminal command (e.g. `wm snap terminal left`).
+pub fn poll_wm_request(state: &mut AppState, sdi: &mut SdiRegistry, vfs: &mut MemoryVfs) {
+    const REQUEST_PATH: &str = "/var/wm/request";
+    let Ok(data) = vfs.read(REQUEST_PATH) else {
+        return;
+    };
+    let _ = vfs.remove(REQUEST_PATH);
+    let request = String::from_utf8_lossy(&data);
+    match state.wm.apply_request(&request, sdi) {
+        Ok(WmEvent::WindowClosed(id)) => {
+            state.open_runners.retain(|(rid, _)| *rid != id);
+            if id == "browser" {
+                state.browser = None;
+            }
+            if state.wm.window_count() == 0 && state.mode == Mode::Desktop {
+                state.mode = Mode::Dashboard;
+            }
+        },
+        // `wm move` grabs a window; its keys are handled in Desktop mode.
+        Ok(WmEvent::WindowFocused(_)) if state.wm.keyboard_grab().is_some() => {
+            state.mode = Mode::Desktop;
+        },
+        Ok(_) => {},
+        Err(e) => {
+            state.output_lines.push(format!("wm: {e}"));
+            trim_output(&mut state.output_lines);
+        },
+    }
+}
+
+/// Post the notification left in `/var/notify/message` by the `notify`
+/// command and announce finished browser downloads. The history is
+/// written to `/var/notify/history` for `notify --history`.
+pub fn poll_notifications(state: &mut AppState, vfs: &mut MemoryVfs) {
+    const MESSAGE_PATH: &str = "/var/notify/message";
+    let mut posted = false;
+    if let Ok(data) = vfs.read(MESSAGE_PATH) {
+        let _ = vfs.remove(MESSAGE_PATH);
+        let message = String::from_utf8_lossy(&data);
+        state
+            .notifications
+            .post(Notification::new("Terminal", message.trim()));
+        posted = true;
+    }
+    if let Some(path) = state
+        .browser
+        .as_mut()
+        .and_then(|bw| bw.take_saved_download())
+    {
+        let mut notification = Notification::new("Download complete", path);
+        notification.icon = Some(Icon::Download);
+        state.notifications.post(notification);
+        posted = true;
+    }
+    if posted
+        && let Err(e) = vfs.write(
+            "/var/notify/history",
+            state.notifications.history_text().as_bytes(),
+        )
+    {
+        log::warn!("Failed to save notification history: {e}");
+    }
+}
+
 /// Truncate output lines to `MAX_OUTPUT_LINES`.
 pub fn trim_output(output_lines: &mut Vec<String>) {
     while output_lines.len() > terminal_sdi::MAX_OUTPUT_LINES {
@@ -1,12 +1,14 @@
 use oasis_core::apps::{AppAction, AppRunner};
 use oasis_core::bottombar::MediaTab;
+use oasis_core::browser::download;
 use oasis_core::input::{Button, InputEvent, Trigger};
 use oasis_core::osk::{OskConfig, OskState};
 use oasis_core::sdi::SdiRegistry;
 use oasis_core::startmenu::StartMenuAction;
-use oasis_core::terminal::Environment;
+use oasis_core::terminal::{ENFORCE_PERMISSIONS, Environment};
 use oasis_core::transition;
 use oasis_core::vfs::MemoryVfs;
+use oasis_core::wm::Dialog;
 use oasis_core::wm::manager::WmEvent;
 
 use crate::app_state::{AppState, Mode};
@@ -58,8 +60,20 @@ pub fn handle_desktop_input(
     event: &InputEvent,
     state: &mut AppState,
     sdi: &mut SdiRegistry,
-    vfs: &MemoryVfs,
+    vfs: &mut MemoryVfs,
 ) -> InputResult {
+    // A keyboard move/resize (`wm move`) takes all input until it ends.
+    if state.wm.keyboard_grab().is_some() && *event != InputEvent::Quit {
+        state.wm.handle_input(event, sdi);
+        return InputResult::Continue;
+    }
+    // So does a modal dialog, until a button is chosen or it is dismissed.
+    if state.wm.modal_dialog().is_some() && *event != InputEvent::Quit {
+        if let WmEvent::DialogResult(id, index) = state.wm.handle_input(event, sdi) {
+            handle_dialog_result(&id, index, state, vfs);
+        }
+        return InputResult::Continue;
+    }
     match event {
         InputEvent::Quit => return InputResult::Quit,
         InputEvent::PointerClick { x, y } => {
@@ -103,7 +117,18 @@ pub fn handle_desktop_input(
                 .wm
                 .handle_input(&InputEvent::PointerRelease { x: *x, y: *y }, sdi);
         },
-        InputEvent::ButtonPress(Button::Cancel) => {
+        // Cancel closes the active window when released, unless L/R were
+        // pressed while it was held to switch windows.
+        InputEvent::ButtonPress(Button::Cancel) if !state.cancel_held => {
+            state.cancel_held = true;
+            state.cancel_chorded = false;
+        },
+        // Repeats while Cancel is held must not reach the windows below.
+        InputEvent::ButtonPress(Button::Cancel) => {},
+        InputEvent::ButtonRelease(Button::Cancel) => {
+            if !std::mem::take(&mut state.cancel_held) || state.cancel_chorded {
+                return InputResult::Continue;
+            }
             if let Some(active_id) = state.wm.active_window().map(|s| s.to_string()) {
                 let _ = state.wm.close_window(&active_id, sdi);
                 state.open_runners.retain(|(rid, _)| *rid != active_id);
@@ -127,11 +152,17 @@ pub fn handle_desktop_input(
                 bw.handle_input(&InputEvent::TextInput(*ch), vfs);
             }
         },
-        InputEvent::Backspace => {
+        // With Cancel held, the triggers switch between open windows (R
+        // forward, L back).
+        InputEvent::TriggerPress(trigger) if state.cancel_held => {
+            state.wm.cycle_focus(*trigger == Trigger::Right, sdi);
+            state.cancel_chorded = true;
+        },
+        InputEvent::Backspace | InputEvent::Paste(_) | InputEvent::EditKey { .. } => {
             if state.wm.active_window() == Some("browser")
                 && let Some(ref mut bw) = state.browser
             {
-                bw.handle_input(&InputEvent::Backspace, vfs);
+                bw.handle_input(event, vfs);
             }
         },
         InputEvent::ButtonPress(btn) => {
@@ -166,6 +197,48 @@ pub fn handle_desktop_input(
     InputResult::Continue
 }
 
+/// Index of the Save button of the download dialog.
+const SAVE_BUTTON: usize = 0;
+
+/// Open a dialog asking whether to save the browser's pending download,
+/// unless one is already open.
+pub fn prompt_pending_download(state: &mut AppState, sdi: &mut SdiRegistry) {
+    if let Some(id) = &state.download_dialog
+        && state.wm.get_window(id).is_some()
+    {
+        return;
+    }
+    let Some(resp) = state.browser.as_ref().and_then(|bw| bw.pending_download()) else {
+        return;
+    };
+    let message = format!(
+        "Save {} ({}) to the downloads folder?",
+        download::file_name_for(&resp.url),
+        download::format_size(resp.body.len()),
+    );
+    let dialog = Dialog::new("Download", message, &["Save", "Cancel"]);
+    match state.wm.show_dialog(dialog, sdi) {
+        Ok(id) => state.download_dialog = Some(id),
+        Err(e) => log::warn!("cannot ask to save the download: {e}"),
+    }
+}
+
+/// Act on the button chosen in the dialog `id`.
+fn handle_dialog_result(id: &str, index: usize, state: &mut AppState, vfs: &mut MemoryVfs) {
+    if state.download_dialog.as_deref() != Some(id) {
+        return;
+    }
+    state.download_dialog = None;
+    if let Some(bw) = state.browser.as_mut() {
+        if index == SAVE_BUTTON {
+            bw.confirm_download(vfs);
+        } else {
+            bw.cancel_download();
+        }
+        state.wm.mark_dirty("browser");
+    }
+}
+
 /// Handle input in App (fullscreen) mode.
 pub fn handle_app_input(
     event: &InputEvent,
@@ -202,6 +275,18 @@ pub fn handle_default_input(
     sdi: &mut SdiRegistry,
     vfs: &mut MemoryVfs,
 ) -> InputResult {
+    // Any other edit of the input line ends a completion in progress.
+    if state.mode == Mode::Terminal
+        && matches!(
+            event,
+            InputEvent::TextInput(_)
+                | InputEvent::Backspace
+                | InputEvent::Paste(_)
+                | InputEvent::ButtonPress(_)
+        )
+    {
+        state.completer.reset();
+    }
     match event {
         InputEvent::Quit => return InputResult::Quit,
         InputEvent::ButtonPress(Button::Cancel) if state.mode == Mode::Dashboard => {
@@ -377,6 +462,13 @@ pub fn handle_default_input(
         InputEvent::Backspace if state.mode == Mode::Terminal => {
             state.input_buf.pop();
         },
+        InputEvent::Tab if state.mode == Mode::Terminal => {
+            let completion =
+                state
+                    .completer
+                    .complete(&state.input_buf, &state.cmd_reg, vfs, &state.cwd);
+            state.input_buf = completion.line;
+        },
         InputEvent::ButtonPress(Button::Confirm) if state.mode == Mode::Terminal => {
             let line = state.input_buf.clone();
             state.input_buf.clear();
@@ -393,6 +485,7 @@ pub fn handle_default_input(
                         network: None,
                         tls: Some(&state.tls_provider),
                         stdin: None,
+                        enforce_permissions: ENFORCE_PERMISSIONS,
                     };
                     let result = state.cmd_reg.execute(&line, &mut env);
                     state.cwd = env.cwd;
@@ -401,12 +494,25 @@ pub fn handle_default_input(
                 if let Some(name) = pending_skin_swap {
                     commands::apply_skin_swap(&name, state, sdi, vfs);
                 }
+                if state.skin_reload_pending {
+                    commands::apply_skin_reload(state, sdi, vfs);
+                }
             }
             commands::trim_output(&mut state.output_lines);
         },
         InputEvent::ButtonPress(Button::Square) if state.mode == Mode::Terminal => {
             state.input_buf.pop();
         },
+        InputEvent::ButtonPress(Button::Up) if state.mode == Mode::Terminal => {
+            if let Some(line) = state.cmd_reg.history_prev(&state.input_buf) {
+                state.input_buf = line;
+            }
+        },
+        InputEvent::ButtonPress(Button::Down) if state.mode == Mode::Terminal => {
+            if let Some(line) = state.cmd_reg.history_next() {
+                state.input_buf = line;
+            }
+        },
         InputEvent::ButtonPress(Button::Cancel) if state.mode == Mode::Terminal => {
             terminal_sdi::set_terminal_visible(sdi, false);
             state.mode = Mode::Dashboard;
@@ -8,7 +8,7 @@ use oasis_core::vfs::MemoryVfs;
 use oasis_core::wm::manager::WindowManager;
 use oasis_core::wm::window::{WindowConfig, WindowType};
 
-use crate::app_state::Mode;
+use crate::app_state::{AppState, Mode};
 
 /// Result of launching an app.
 pub enum LaunchResult {
@@ -16,6 +16,9 @@ pub enum LaunchResult {
     Desktop,
 }
 
+/// File the Desktop window layout is kept in, in the working directory.
+pub const LAYOUT_FILE: &str = "layout.toml";
+
 /// Launch an app as a floating window (Browser, generic app, or Terminal).
 ///
 /// Returns the mode to switch to. Caller must set `state.mode` accordingly.
@@ -34,50 +37,106 @@ pub fn launch_app_window(
         return LaunchResult::Terminal;
     }
 
-    if app.title == "Browser" {
-        let win_id = "browser";
-        if wm.get_window(win_id).is_some() {
-            let _ = wm.focus_window(win_id, sdi);
-        } else {
-            let wc = WindowConfig {
-                id: win_id.to_string(),
-                title: "Browser".to_string(),
-                x: None,
-                y: None,
-                width: 380,
-                height: 220,
-                window_type: WindowType::AppWindow,
-            };
-            let _ = wm.create_window(&wc, sdi);
-            let mut bw = BrowserWidget::new(browser_config.clone());
-            bw.set_tls_provider(Box::new(tls_provider.clone()));
-            bw.set_window(0, 0, 380, 220);
-            let home = bw.config.features.home_url.clone();
-            bw.navigate_vfs(&home, vfs);
-            *browser = Some(bw);
-        }
-        return LaunchResult::Desktop;
-    }
-
-    let win_id = app.title.to_lowercase().replace(' ', "_");
+    let win_id = app_window_id(app);
     if wm.get_window(&win_id).is_some() {
         let _ = wm.focus_window(&win_id, sdi);
     } else {
-        let wc = WindowConfig {
-            id: win_id.clone(),
-            title: app.title.clone(),
-            x: None,
-            y: None,
-            width: 380,
-            height: 220,
-            window_type: WindowType::AppWindow,
-        };
+        let wc = start_app(
+            app,
+            open_runners,
+            browser,
+            browser_config,
+            vfs,
+            tls_provider,
+        );
         let _ = wm.create_window(&wc, sdi);
-        open_runners.push((win_id, AppRunner::launch(app, vfs)));
     }
     LaunchResult::Desktop
 }
 
+/// Id of the window an app opens in.
+fn app_window_id(app: &AppEntry) -> String {
+    app.title.to_lowercase().replace(' ', "_")
+}
+
+/// Start the content of a windowed app and return the config of its
+/// window.
+fn start_app(
+    app: &AppEntry,
+    open_runners: &mut Vec<(String, AppRunner)>,
+    browser: &mut Option<BrowserWidget>,
+    browser_config: &BrowserConfig,
+    vfs: &MemoryVfs,
+    tls_provider: &RustlsTlsProvider,
+) -> WindowConfig {
+    let win_id = app_window_id(app);
+    if app.title == "Browser" {
+        let mut bw = BrowserWidget::with_vfs(browser_config.clone(), vfs);
+        bw.set_tls_provider(Box::new(tls_provider.clone()));
+        bw.set_window(0, 0, 380, 220);
+        let home = bw.config.features.home_url.clone();
+        bw.navigate_vfs(&home, vfs);
+        *browser = Some(bw);
+    } else {
+        open_runners.push((win_id.clone(), AppRunner::launch(app, vfs)));
+    }
+    WindowConfig {
+        id: win_id,
+        title: app.title.clone(),
+        x: None,
+        y: None,
+        width: 380,
+        height: 220,
+        window_type: WindowType::AppWindow,
+    }
+}
+
+/// Reopen the windows saved in [`LAYOUT_FILE`], if there is one, and
+/// switch to Desktop mode when any were opened.
+pub fn restore_layout(state: &mut AppState, sdi: &mut SdiRegistry, vfs: &MemoryVfs) {
+    let Ok(layout) = std::fs::read_to_string(LAYOUT_FILE) else {
+        return;
+    };
+    let apps = &state.dashboard.apps;
+    let result = state.wm.restore_layout(&layout, sdi, |id| {
+        let app = apps
+            .iter()
+            .find(|app| app.title != "Terminal" && app_window_id(app) == id)?;
+        Some(start_app(
+            app,
+            &mut state.open_runners,
+            &mut state.browser,
+            &state.browser_config,
+            vfs,
+            &state.tls_provider,
+        ))
+    });
+    match result {
+        Ok(0) => {},
+        Ok(count) => {
+            log::info!("Restored {count} windows from {LAYOUT_FILE}");
+            state.mode = Mode::Desktop;
+        },
+        Err(e) => log::warn!("Failed to restore {LAYOUT_FILE}: {e}"),
+    }
+}
+
+/// Save the Desktop window layout to [`LAYOUT_FILE`], or remove the file
+/// when no windows are open.
+pub fn save_layout(wm: &WindowManager) {
+    let result = if wm.window_count() == 0 {
+        match std::fs::remove_file(LAYOUT_FILE) {
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+            other => other,
+        }
+    } else {
+        std::fs::write(LAYOUT_FILE, wm.serialize_layout())
+    };
+    if let Err(e) = result {
+        log::warn!("Failed to save {LAYOUT_FILE}: {e}");
+    }
+}
+
 /// Create a fade-in transition.
 pub fn make_transition(w: u32, h: u32, fade_frames: u32) -> transition::TransitionState {
     transition::fade_in_custom(w, h, fade_frames)
@@ -11,9 +11,13 @@ mod commands;
 mod input;
 mod launch;
 mod render;
+mod skin_assets;
+mod skin_wallpaper;
 mod terminal_sdi;
 mod vfs_setup;
 
+use std::time::Instant;
+
 use anyhow::Result;
 
 use app_state::{AppState, Mode};
@@ -22,22 +26,27 @@ use oasis_core::active_theme::ActiveTheme;
 use oasis_core::backend::{Color, InputBackend, SdiBackend};
 use oasis_core::bottombar::BottomBar;
 use oasis_core::browser::BrowserConfig;
+use oasis_core::clipboard::Clipboard;
 use oasis_core::config::OasisConfig;
 use oasis_core::cursor::{self, CursorState};
 use oasis_core::dashboard::{DashboardConfig, DashboardState, discover_apps};
+use oasis_core::input::{Button, InputEvent};
 use oasis_core::net::{RustlsTlsProvider, StdNetworkBackend};
+use oasis_core::notification::{NotificationCenter, NotificationTheme};
 use oasis_core::platform::DesktopPlatform;
 use oasis_core::platform::{PowerService, TimeService};
 use oasis_core::sdi::SdiRegistry;
-use oasis_core::skin::resolve_skin;
+use oasis_core::skin::{CorruptedEffect, SkinEffect, resolve_skin};
+use oasis_core::skin_assets::SkinAssets;
 use oasis_core::startmenu::StartMenuState;
 use oasis_core::statusbar::StatusBar;
 use oasis_core::terminal::{
-    CommandRegistry, register_agent_commands, register_builtins, register_plugin_commands,
+    CommandRegistry, Completer, ENFORCE_PERMISSIONS, Environment, register_agent_commands,
+    register_builtins, register_plugin_commands,
 };
 use oasis_core::transition;
 use oasis_core::vfs::MemoryVfs;
-use oasis_core::wallpaper;
+use oasis_core::wm::NOTIFICATION_Z;
 use oasis_core::wm::manager::WindowManager;
 
 fn main() -> Result<()> {
@@ -50,12 +59,15 @@ fn main() -> Result<()> {
         config.screen_height,
     );
 
-    let mut backend = SdlBackend::new(
+    let mut backend = SdlBackend::with_scaling(
         &config.window_title,
         config.screen_width,
         config.screen_height,
+        config.window_scale,
+        config.window_scaling,
     )?;
     backend.init(config.screen_width, config.screen_height)?;
+    backend.set_gamepad(&config.gamepad);
 
     // Resolve skin from CLI arg, OASIS_SKIN env var, or config.
     let skin_name = std::env::args()
@@ -100,20 +112,53 @@ fn main() -> Result<()> {
     // Set up command interpreter.
     let mut cmd_reg = CommandRegistry::new();
     register_builtins(&mut cmd_reg);
-    // Register additional command modules (script, transfer, update, plugin, agent, browser).
-    oasis_core::script::register_script_commands(&mut cmd_reg);
+    // Register additional command modules (transfer, update, plugin, agent, browser).
     oasis_core::transfer::register_transfer_commands(&mut cmd_reg);
     oasis_core::update::register_update_commands(&mut cmd_reg);
     register_plugin_commands(&mut cmd_reg);
     register_agent_commands(&mut cmd_reg);
     oasis_core::browser::commands::register_browser_commands(&mut cmd_reg);
+    if let Err(e) = cmd_reg.load_history(&vfs) {
+        log::warn!("Failed to load command history: {e}");
+    }
+    if let Err(e) = cmd_reg.load_schedule(&vfs) {
+        log::warn!("Failed to load command schedule: {e}");
+    }
+    let mut profile_env = Environment {
+        cwd: "/".to_string(),
+        vfs: &mut vfs,
+        power: Some(&platform),
+        time: Some(&platform),
+        usb: Some(&platform),
+        network: None,
+        tls: None,
+        stdin: None,
+        enforce_permissions: ENFORCE_PERMISSIONS,
+    };
+    if let Err(e) = cmd_reg.run_profile(&mut profile_env) {
+        log::warn!("Failed to run shell profile: {e}");
+    }
+    let mut output_lines = vec![
+        "OASIS_OS v0.1.0 -- Type 'help' for commands".to_string(),
+        "F1=terminal  F2=on-screen keyboard  Escape=quit".to_string(),
+        String::new(),
+    ];
+    match cmd_reg.run_rc(&mut profile_env) {
+        Ok(lines) => output_lines.extend(lines),
+        Err(e) => {
+            log::warn!("Failed to run {}: {e}", oasis_core::terminal::RC_PATH);
+            output_lines.push(format!("error: {e}"));
+        },
+    }
 
     // Window manager state (Desktop mode).
-    let wm = WindowManager::with_theme(
+    let mut wm = WindowManager::with_theme(
         config.screen_width,
         config.screen_height,
         skin.theme.build_wm_theme(),
     );
+    wm.set_dialog_theme(skin.theme.to_ui_theme());
+    wm.set_content_caching(config.cache_window_content);
 
     // Boot transition: fade in from black.
     let fade_frames = skin.features.transition_fade_frames.unwrap_or(15);
@@ -124,13 +169,17 @@ fn main() -> Result<()> {
     ));
 
     let mouse_cursor = CursorState::new(config.screen_width, config.screen_height);
+    let notifications = NotificationCenter::new(NotificationTheme::from_skin_theme(&skin.theme));
 
     let start_menu = StartMenuState::new_with_theme(StartMenuState::default_items(), &active_theme);
 
     // Assemble application state.
     let mut state = AppState {
         config,
+        skin_effect: skin.corrupted_modifiers.clone().map(CorruptedEffect::new),
         skin,
+        skin_watcher: None,
+        skin_reload_pending: false,
         active_theme,
         browser_config,
         platform,
@@ -141,24 +190,28 @@ fn main() -> Result<()> {
         cmd_reg,
         cwd: "/".to_string(),
         input_buf: String::new(),
-        output_lines: vec![
-            "OASIS_OS v0.1.0 -- Type 'help' for commands".to_string(),
-            "F1=terminal  F2=on-screen keyboard  Escape=quit".to_string(),
-            String::new(),
-        ],
+        completer: Completer::new(),
+        output_lines,
         osk: None,
         app_runner: None,
         wm,
         open_runners: Vec::new(),
         browser: None,
+        download_dialog: None,
+        cancel_held: false,
+        cancel_chorded: false,
         net_backend: StdNetworkBackend::new(),
         listener: None,
         remote_client: None,
         tls_provider: RustlsTlsProvider::new(),
         mouse_cursor,
+        notifications,
         mode: Mode::Dashboard,
         bg_color: Color::rgb(10, 10, 18),
         active_transition,
+        wallpaper_image: None,
+        wallpaper_dirty: false,
+        skin_assets: SkinAssets::default(),
         frame_counter: 0,
     };
 
@@ -166,26 +219,10 @@ fn main() -> Result<()> {
     let mut sdi = SdiRegistry::new();
     state.skin.apply_layout(&mut sdi);
 
-    // -- Wallpaper: generate from skin config and load as texture --
-    let wallpaper_tex = {
-        let wp_data = wallpaper::generate_from_config(
-            state.config.screen_width,
-            state.config.screen_height,
-            &state.active_theme,
-        );
-        backend.load_texture(
-            state.config.screen_width,
-            state.config.screen_height,
-            &wp_data,
-        )?
-    };
-    terminal_sdi::setup_wallpaper(
-        &mut sdi,
-        wallpaper_tex,
-        state.config.screen_width,
-        state.config.screen_height,
-    );
+    // -- Wallpaper: generate from skin config (plus the skin's image) --
+    skin_wallpaper::load_wallpaper(&mut state, &mut sdi, &mut backend)?;
     log::info!("Wallpaper loaded");
+    skin_assets::load_assets(&mut state, &mut sdi, &mut backend)?;
 
     // -- Mouse cursor: generate procedural arrow and load as texture --
     {
@@ -199,23 +236,53 @@ fn main() -> Result<()> {
     }
     log::info!("Mouse cursor loaded");
 
+    // Reopen the Desktop windows from the last session.
+    launch::restore_layout(&mut state, &mut sdi, &vfs);
+
+    let started = Instant::now();
+    let mut last_frame = started;
     'running: loop {
         state.frame_counter += 1;
+        let now = Instant::now();
+        let dt_ms = now.duration_since(last_frame).as_millis().min(1000) as u32;
+        last_frame = now;
 
         // Update system info every ~60 frames (~1s at 60fps).
         if state.frame_counter.is_multiple_of(60) {
             let time = state.platform.now().ok();
             let power = state.platform.power_info().ok();
             state.status_bar.update_info(time.as_ref(), power.as_ref());
+            commands::poll_skin_watcher(&mut state, &mut sdi, &vfs);
         }
 
         let events = backend.poll_events();
         for event in &events {
+            // Tab completes in the terminal and stands in for Square
+            // everywhere else.
+            let event = match event {
+                InputEvent::Tab if state.mode != Mode::Terminal => {
+                    &InputEvent::ButtonPress(Button::Square)
+                },
+                other => other,
+            };
+            // The window was resized with native scaling: the screen
+            // itself changed size.
+            if let InputEvent::Resized { w, h } = *event {
+                state.config.screen_width = w;
+                state.config.screen_height = h;
+                state.wm.set_screen_size(w, h, &mut sdi);
+                state.wallpaper_dirty = true;
+                continue;
+            }
             state.mouse_cursor.handle_input(event);
+            // A click or Select on a toast dismisses it and goes no further.
+            if state.notifications.handle_input(event) {
+                continue;
+            }
 
             let result = match state.mode {
                 Mode::Osk => input::handle_osk_input(event, &mut state, &mut sdi),
-                Mode::Desktop => input::handle_desktop_input(event, &mut state, &mut sdi, &vfs),
+                Mode::Desktop => input::handle_desktop_input(event, &mut state, &mut sdi, &mut vfs),
                 Mode::App => input::handle_app_input(event, &mut state, &mut sdi, &vfs),
                 _ => input::handle_default_input(event, &mut state, &mut sdi, &mut vfs),
             };
@@ -224,21 +291,98 @@ fn main() -> Result<()> {
             }
         }
 
+        // Ask in a dialog whether to save what the browser downloaded.
+        if state.mode == Mode::Desktop {
+            input::prompt_pending_download(&mut state, &mut sdi);
+        }
+
+        // Text cut or copied in the browser's URL bar.
+        if let Some(text) = state.browser.as_mut().and_then(|bw| bw.take_copied_text()) {
+            Clipboard::copy(&text, &mut backend);
+        }
+
         // Poll remote listener for incoming commands.
         commands::poll_remote_listener(&mut state, &mut sdi, &mut vfs);
 
         // Poll remote client for received data.
         commands::poll_remote_client(&mut state);
 
+        // Advance background jobs started with `&`.
+        commands::poll_jobs(&mut state, &mut vfs);
+
+        // Apply window requests queued by the `wm` command.
+        commands::poll_wm_request(&mut state, &mut sdi, &mut vfs);
+
+        // Advance window animations (minimizing).
+        state.wm.tick(dt_ms, &mut sdi);
+
+        // Let the browser page coast with smooth scrolling. Window
+        // content is cached by the WM: repaint the browser when it may
+        // have changed, and the apps, which can animate, every frame.
+        if let Some(bw) = state.browser.as_mut() {
+            // Write through history recorded by navigation that came
+            // from elsewhere than input, such as the home page on launch.
+            bw.persist(&mut vfs);
+            let scrolling = bw.tick(dt_ms);
+            if scrolling || bw.needs_repaint() || !events.is_empty() {
+                state.wm.mark_dirty("browser");
+            }
+        }
+        for (id, _) in &state.open_runners {
+            state.wm.mark_dirty(id);
+        }
+
+        // Post queued notifications and expire old toasts.
+        commands::poll_notifications(&mut state, &mut vfs);
+        state.notifications.tick(dt_ms);
+
+        // Run scheduled commands that are due.
+        let now_ms = now.duration_since(started).as_millis() as u64;
+        commands::poll_schedule(&mut state, &mut vfs, now_ms);
+
+        // Rebuild the wallpaper and assets after a skin switch or reload.
+        if state.wallpaper_dirty {
+            skin_wallpaper::load_wallpaper(&mut state, &mut sdi, &mut backend)?;
+            skin_assets::load_assets(&mut state, &mut sdi, &mut backend)?;
+        }
+
         // Update SDI scene graph for the active mode.
         render::update_sdi(&mut state, &mut sdi);
 
+        // The glitch effect only distorts what is drawn this frame; the
+        // undistorted scene is put back afterwards.
+        let clean_scene = state.skin_effect.as_mut().map(|effect| {
+            let scene = sdi.clone();
+            effect.tick(dt_ms);
+            effect.apply(&mut sdi);
+            scene
+        });
+
         // -- Render --
         backend.clear(state.bg_color)?;
+        if let Some(ref image) = state.wallpaper_image {
+            image.draw(
+                &mut backend,
+                state.config.screen_width,
+                state.config.screen_height,
+            )?;
+        }
+        // Toasts go between the status bar and the bottom bar, in the
+        // WM's notification layer.
+        let toast_y = state.active_theme.statusbar_height;
+        let toast_h = state
+            .config
+            .screen_height
+            .saturating_sub(toast_y + state.active_theme.bottombar_height);
+        let screen_w = state.config.screen_width;
+        let notifications = &mut state.notifications;
+        let mut draw_toasts =
+            |be: &mut dyn SdiBackend| notifications.draw(be, 0, toast_y as i32, screen_w, toast_h);
         if state.mode == Mode::Desktop && state.wm.window_count() > 0 {
-            state
-                .wm
-                .draw_with_clips(&mut sdi, &mut backend, |window_id, cx, cy, cw, ch, be| {
+            state.wm.draw_with_overlay(
+                &mut sdi,
+                &mut backend,
+                |window_id, cx, cy, cw, ch, be| {
                     if window_id == "browser" {
                         if let Some(ref mut bw) = state.browser {
                             bw.set_window(cx, cy, cw, ch);
@@ -253,9 +397,16 @@ fn main() -> Result<()> {
                     } else {
                         Ok(())
                     }
-                })?;
+                },
+                draw_toasts,
+            )?;
         } else {
-            sdi.draw(&mut backend)?;
+            sdi.draw_below(&mut backend, NOTIFICATION_Z)?;
+            draw_toasts(&mut backend)?;
+            sdi.draw_overlay_from(&mut backend, NOTIFICATION_Z)?;
+        }
+        if let Some(scene) = clean_scene {
+            sdi = scene;
         }
 
         // Draw transition overlay if active.
@@ -270,6 +421,10 @@ fn main() -> Result<()> {
         backend.swap_buffers()?;
     }
 
+    if let Err(e) = state.cmd_reg.save_history(&mut vfs) {
+        log::warn!("Failed to save command history: {e}");
+    }
+    launch::save_layout(&state.wm);
     backend.shutdown()?;
     log::info!("OASIS_OS shut down cleanly");
     Ok(())
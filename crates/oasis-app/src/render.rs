@@ -42,9 +42,11 @@ pub fn update_sdi(state: &mut AppState, sdi: &mut SdiRegistry) {
             state.start_menu.close();
             state.start_menu.hide_sdi(sdi);
             terminal_sdi::hide_media_page(sdi);
+            let candidates = state.completer.candidates().join("  ");
             terminal_sdi::setup_terminal_objects(
                 sdi,
                 &state.output_lines,
+                (!candidates.is_empty()).then_some(candidates.as_str()),
                 &state.cwd,
                 &state.input_buf,
             );
@@ -89,9 +91,10 @@ pub fn update_sdi(state: &mut AppState, sdi: &mut SdiRegistry) {
     // Update cursor SDI position (always on top).
     state.mouse_cursor.update_sdi(sdi);
 
-    // Ensure wallpaper is visible and at lowest z.
+    // The generated wallpaper sits at the lowest z, unless the skin's
+    // wallpaper image is drawn in its place.
     if let Ok(obj) = sdi.get_mut("wallpaper") {
-        obj.visible = true;
+        obj.visible = state.wallpaper_image.is_none();
     }
 }
 
@@ -0,0 +1,32 @@
+//! Skin image assets.
+//!
+//! The skin's `[assets]` are decoded and uploaded by
+//! [`SkinAssets::load`]; this swaps them in for the window manager. Without
+//! an asset, that part of the window chrome is drawn flat in the theme's
+//! colors.
+
+use anyhow::Result;
+
+use oasis_core::backend::SdiBackend;
+use oasis_core::sdi::SdiRegistry;
+use oasis_core::skin_assets::SkinAssets;
+
+use crate::app_state::AppState;
+
+/// (Re)load the current skin's assets, freeing the textures of the
+/// previous ones.
+pub fn load_assets(
+    state: &mut AppState,
+    sdi: &mut SdiRegistry,
+    backend: &mut dyn SdiBackend,
+) -> Result<()> {
+    let assets = SkinAssets::load(&state.skin, backend)?;
+    let old = std::mem::replace(&mut state.skin_assets, assets);
+    // Windows opened under the previous skin switch too.
+    assets.replace(&old, sdi);
+    old.destroy(backend)?;
+    let mut wm_theme = state.wm.theme().clone();
+    assets.apply(&mut wm_theme);
+    state.wm.set_theme(wm_theme);
+    Ok(())
+}
@@ -0,0 +1,65 @@
+//! Skin wallpaper loading.
+//!
+//! Every skin gets a generated wallpaper behind the SDI scene. Skins
+//! with a `[wallpaper] image` also get that image decoded here and drawn
+//! by [`WallpaperImage`] in place of the generated one.
+
+use anyhow::Result;
+
+use oasis_core::backend::SdiBackend;
+use oasis_core::browser::image::decode_image;
+use oasis_core::sdi::SdiRegistry;
+use oasis_core::wallpaper::{self, WallpaperImage, WallpaperMode};
+
+use crate::app_state::AppState;
+use crate::terminal_sdi;
+
+/// (Re)build the wallpaper for the current skin, freeing the textures of
+/// the previous one.
+pub fn load_wallpaper(
+    state: &mut AppState,
+    sdi: &mut SdiRegistry,
+    backend: &mut dyn SdiBackend,
+) -> Result<()> {
+    state.wallpaper_dirty = false;
+    let (w, h) = (state.config.screen_width, state.config.screen_height);
+    let at = &state.active_theme;
+
+    let data = wallpaper::generate_from_config(w, h, at);
+    let tex = backend.load_texture(w, h, &data)?;
+    if let Ok(obj) = sdi.get_mut("wallpaper") {
+        if let Some(old) = obj.texture.replace(tex) {
+            backend.destroy_texture(old)?;
+        }
+    } else {
+        terminal_sdi::setup_wallpaper(sdi, tex, w, h);
+    }
+
+    if let Some(old) = state.wallpaper_image.take() {
+        old.destroy(backend)?;
+    }
+    let Some(bytes) = &state.skin.wallpaper_image else {
+        return Ok(());
+    };
+    let Some(image) = decode_image(bytes) else {
+        log::warn!("Skin wallpaper is not a PNG, BMP or GIF image -- using generated wallpaper");
+        return Ok(());
+    };
+    let mode = WallpaperMode::from_name(&at.wallpaper_mode).unwrap_or_else(|| {
+        log::warn!(
+            "Unknown wallpaper mode '{}' -- stretching",
+            at.wallpaper_mode
+        );
+        WallpaperMode::Stretch
+    });
+    state.wallpaper_image = Some(WallpaperImage::load(
+        backend,
+        image.width,
+        image.height,
+        &image.pixels,
+        mode,
+        at.wallpaper_fallback,
+    )?);
+    log::info!("Wallpaper image loaded ({}x{})", image.width, image.height);
+    Ok(())
+}
@@ -74,6 +74,8 @@ pub fn populate_demo_vfs(vfs: &mut MemoryVfs) {
 
     vfs.mkdir("/var").unwrap();
     vfs.mkdir("/var/audio").unwrap();
+    vfs.mkdir("/var/wm").unwrap();
+    vfs.mkdir("/var/notify").unwrap();
 }
 
 /// Try to load real sample files from the `samples/` directory on disk.
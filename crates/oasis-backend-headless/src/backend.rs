@@ -0,0 +1,474 @@
+//! Headless rendering backend.
+//!
+//! Draws with the UE5 backend's software rasterizer -- shapes, bitmap font
+//! text, alpha-blended texture blits, clip and translate stacks -- into an
+//! RGBA framebuffer that tests can inspect and dump as PNG.
+
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::Path;
+
+use oasis_backend_ue5::Ue5Backend;
+use oasis_core::backend::{Color, SdiBackend, TextureId};
+use oasis_core::error::{OasisError, Result};
+
+/// Software framebuffer backend for running OASIS_OS without a window.
+pub struct HeadlessBackend {
+    fb: Ue5Backend,
+    frames: u64,
+}
+
+impl HeadlessBackend {
+    /// Create a new backend with the given resolution.
+    pub fn new(width: u32, height: u32) -> Self {
+        Self {
+            fb: Ue5Backend::new(width, height),
+            frames: 0,
+        }
+    }
+
+    /// The RGBA framebuffer, row by row.
+    pub fn pixels(&self) -> &[u8] {
+        self.fb.buffer()
+    }
+
+    /// Framebuffer dimensions.
+    pub fn dimensions(&self) -> (u32, u32) {
+        self.fb.dimensions()
+    }
+
+    /// Number of frames presented with `swap_buffers`.
+    pub fn frame_count(&self) -> u64 {
+        self.frames
+    }
+
+    /// The color at (`x`, `y`), or `None` outside the framebuffer.
+    pub fn pixel(&self, x: i32, y: i32) -> Option<Color> {
+        let (w, h) = self.dimensions();
+        if x < 0 || y < 0 || x as u32 >= w || y as u32 >= h {
+            return None;
+        }
+        let i = ((y as u32 * w + x as u32) * 4) as usize;
+        let p = &self.pixels()[i..i + 4];
+        Some(Color::rgba(p[0], p[1], p[2], p[3]))
+    }
+
+    /// Number of pixels whose RGB is `color`'s.
+    pub fn count_color(&self, color: Color) -> usize {
+        self.pixels()
+            .chunks_exact(4)
+            .filter(|p| p[..3] == [color.r, color.g, color.b])
+            .count()
+    }
+
+    /// Where `text` was drawn with `draw_text` at `font_size` in `color`:
+    /// the position of the first match, scanning row by row. Only the
+    /// glyphs' own pixels are compared, so any background matches.
+    pub fn find_text(&self, text: &str, font_size: u16, color: Color) -> Option<(i32, i32)> {
+        let ink = glyph_pixels(text, font_size);
+        let (max_dx, max_dy) = ink
+            .iter()
+            .fold((0, 0), |(mx, my), &(dx, dy)| (mx.max(dx), my.max(dy)));
+        if ink.is_empty() {
+            return None;
+        }
+        let (w, h) = self.dimensions();
+        let rgb = [color.r, color.g, color.b];
+        let pixels = self.pixels();
+        for y in 0..h.saturating_sub(max_dy) {
+            for x in 0..w.saturating_sub(max_dx) {
+                let matches = ink.iter().all(|&(dx, dy)| {
+                    let i = (((y + dy) * w + x + dx) * 4) as usize;
+                    pixels[i..i + 3] == rgb
+                });
+                if matches {
+                    return Some((x as i32, y as i32));
+                }
+            }
+        }
+        None
+    }
+
+    /// Write the framebuffer to `path` as an RGBA PNG.
+    pub fn dump_png(&self, path: impl AsRef<Path>) -> Result<()> {
+        let (w, h) = self.dimensions();
+        let writer = BufWriter::new(File::create(path)?);
+        let mut encoder = png::Encoder::new(writer, w, h);
+        encoder.set_color(png::ColorType::Rgba);
+        encoder.set_depth(png::BitDepth::Eight);
+        encoder
+            .write_header()
+            .and_then(|mut writer| writer.write_image_data(self.pixels()))
+            .map_err(|e| OasisError::Backend(format!("PNG encoding failed: {e}")))
+    }
+}
+
+/// The offsets of the pixels `draw_text` sets for `text`, relative to
+/// where it is drawn.
+fn glyph_pixels(text: &str, font_size: u16) -> Vec<(u32, u32)> {
+    let (w, h) = {
+        let probe = Ue5Backend::new(0, 0);
+        let pad = font_size as u32;
+        (
+            probe.measure_text(text, font_size) + pad,
+            probe.measure_text_height(font_size) + pad,
+        )
+    };
+    let mut scratch = Ue5Backend::new(w, h);
+    // A fresh framebuffer is transparent; text sets opaque pixels.
+    if scratch
+        .draw_text(text, 0, 0, font_size, Color::rgb(255, 255, 255))
+        .is_err()
+    {
+        return Vec::new();
+    }
+    let mut ink = Vec::new();
+    for (i, p) in scratch.buffer().chunks_exact(4).enumerate() {
+        if p[3] != 0 {
+            ink.push((i as u32 % w, i as u32 / w));
+        }
+    }
+    ink
+}
+
+impl SdiBackend for HeadlessBackend {
+    fn init(&mut self, width: u32, height: u32) -> Result<()> {
+        self.frames = 0;
+        self.fb.init(width, height)
+    }
+
+    fn clear(&mut self, color: Color) -> Result<()> {
+        self.fb.clear(color)
+    }
+
+    fn fill_rect(&mut self, x: i32, y: i32, w: u32, h: u32, color: Color) -> Result<()> {
+        self.fb.fill_rect(x, y, w, h, color)
+    }
+
+    fn draw_text(
+        &mut self,
+        text: &str,
+        x: i32,
+        y: i32,
+        font_size: u16,
+        color: Color,
+    ) -> Result<()> {
+        self.fb.draw_text(text, x, y, font_size, color)
+    }
+
+    fn blit(&mut self, tex: TextureId, x: i32, y: i32, w: u32, h: u32) -> Result<()> {
+        self.fb.blit(tex, x, y, w, h)
+    }
+
+    fn swap_buffers(&mut self) -> Result<()> {
+        self.frames += 1;
+        self.fb.swap_buffers()
+    }
+
+    fn load_texture(&mut self, width: u32, height: u32, rgba_data: &[u8]) -> Result<TextureId> {
+        self.fb.load_texture(width, height, rgba_data)
+    }
+
+    fn destroy_texture(&mut self, tex: TextureId) -> Result<()> {
+        self.fb.destroy_texture(tex)
+    }
+
+    fn set_clip_rect(&mut self, x: i32, y: i32, w: u32, h: u32) -> Result<()> {
+        self.fb.set_clip_rect(x, y, w, h)
+    }
+
+    fn reset_clip_rect(&mut self) -> Result<()> {
+        self.fb.reset_clip_rect()
+    }
+
+    fn measure_text(&self, text: &str, font_size: u16) -> u32 {
+        self.fb.measure_text(text, font_size)
+    }
+
+    fn read_pixels(&self, x: i32, y: i32, w: u32, h: u32) -> Result<Vec<u8>> {
+        self.fb.read_pixels(x, y, w, h)
+    }
+
+    fn shutdown(&mut self) -> Result<()> {
+        self.fb.shutdown()
+    }
+
+    // -------------------------------------------------------------------
+    // Extended: Shape Primitives
+    // -------------------------------------------------------------------
+
+    fn fill_rounded_rect(
+        &mut self,
+        x: i32,
+        y: i32,
+        w: u32,
+        h: u32,
+        radius: u16,
+        color: Color,
+    ) -> Result<()> {
+        self.fb.fill_rounded_rect(x, y, w, h, radius, color)
+    }
+
+    fn stroke_rect(
+        &mut self,
+        x: i32,
+        y: i32,
+        w: u32,
+        h: u32,
+        stroke_width: u16,
+        color: Color,
+    ) -> Result<()> {
+        self.fb.stroke_rect(x, y, w, h, stroke_width, color)
+    }
+
+    fn draw_line(
+        &mut self,
+        x1: i32,
+        y1: i32,
+        x2: i32,
+        y2: i32,
+        width: u16,
+        color: Color,
+    ) -> Result<()> {
+        self.fb.draw_line(x1, y1, x2, y2, width, color)
+    }
+
+    fn fill_circle(&mut self, cx: i32, cy: i32, radius: u16, color: Color) -> Result<()> {
+        self.fb.fill_circle(cx, cy, radius, color)
+    }
+
+    fn stroke_circle(
+        &mut self,
+        cx: i32,
+        cy: i32,
+        radius: u16,
+        stroke_width: u16,
+        color: Color,
+    ) -> Result<()> {
+        self.fb.stroke_circle(cx, cy, radius, stroke_width, color)
+    }
+
+    fn fill_triangle(
+        &mut self,
+        x1: i32,
+        y1: i32,
+        x2: i32,
+        y2: i32,
+        x3: i32,
+        y3: i32,
+        color: Color,
+    ) -> Result<()> {
+        self.fb.fill_triangle(x1, y1, x2, y2, x3, y3, color)
+    }
+
+    // -------------------------------------------------------------------
+    // Extended: Gradients
+    // -------------------------------------------------------------------
+
+    fn fill_rect_gradient_v(
+        &mut self,
+        x: i32,
+        y: i32,
+        w: u32,
+        h: u32,
+        top_color: Color,
+        bottom_color: Color,
+    ) -> Result<()> {
+        self.fb
+            .fill_rect_gradient_v(x, y, w, h, top_color, bottom_color)
+    }
+
+    fn fill_rect_gradient_h(
+        &mut self,
+        x: i32,
+        y: i32,
+        w: u32,
+        h: u32,
+        left_color: Color,
+        right_color: Color,
+    ) -> Result<()> {
+        self.fb
+            .fill_rect_gradient_h(x, y, w, h, left_color, right_color)
+    }
+
+    fn fill_rect_gradient_4(
+        &mut self,
+        x: i32,
+        y: i32,
+        w: u32,
+        h: u32,
+        top_left: Color,
+        top_right: Color,
+        bottom_left: Color,
+        bottom_right: Color,
+    ) -> Result<()> {
+        self.fb
+            .fill_rect_gradient_4(x, y, w, h, top_left, top_right, bottom_left, bottom_right)
+    }
+
+    fn dim_screen(&mut self, alpha: u8) -> Result<()> {
+        self.fb.dim_screen(alpha)
+    }
+
+    // -------------------------------------------------------------------
+    // Extended: Text
+    // -------------------------------------------------------------------
+
+    fn measure_text_height(&self, font_size: u16) -> u32 {
+        self.fb.measure_text_height(font_size)
+    }
+
+    fn font_ascent(&self, font_size: u16) -> u32 {
+        self.fb.font_ascent(font_size)
+    }
+
+    // -------------------------------------------------------------------
+    // Extended: Textures
+    // -------------------------------------------------------------------
+
+    fn blit_sub(
+        &mut self,
+        tex: TextureId,
+        src_x: u32,
+        src_y: u32,
+        src_w: u32,
+        src_h: u32,
+        dst_x: i32,
+        dst_y: i32,
+        dst_w: u32,
+        dst_h: u32,
+    ) -> Result<()> {
+        self.fb
+            .blit_sub(tex, src_x, src_y, src_w, src_h, dst_x, dst_y, dst_w, dst_h)
+    }
+
+    fn blit_tinted(
+        &mut self,
+        tex: TextureId,
+        x: i32,
+        y: i32,
+        w: u32,
+        h: u32,
+        tint: Color,
+    ) -> Result<()> {
+        self.fb.blit_tinted(tex, x, y, w, h, tint)
+    }
+
+    fn blit_sub_tinted(
+        &mut self,
+        tex: TextureId,
+        src_x: u32,
+        src_y: u32,
+        src_w: u32,
+        src_h: u32,
+        dst_x: i32,
+        dst_y: i32,
+        dst_w: u32,
+        dst_h: u32,
+        tint: Color,
+    ) -> Result<()> {
+        self.fb.blit_sub_tinted(
+            tex, src_x, src_y, src_w, src_h, dst_x, dst_y, dst_w, dst_h, tint,
+        )
+    }
+
+    fn blit_flipped(
+        &mut self,
+        tex: TextureId,
+        x: i32,
+        y: i32,
+        w: u32,
+        h: u32,
+        flip_h: bool,
+        flip_v: bool,
+    ) -> Result<()> {
+        self.fb.blit_flipped(tex, x, y, w, h, flip_h, flip_v)
+    }
+
+    // -------------------------------------------------------------------
+    // Extended: Clip & Transform Stacks
+    // -------------------------------------------------------------------
+
+    fn push_clip_rect(&mut self, x: i32, y: i32, w: u32, h: u32) -> Result<()> {
+        self.fb.push_clip_rect(x, y, w, h)
+    }
+
+    fn pop_clip_rect(&mut self) -> Result<()> {
+        self.fb.pop_clip_rect()
+    }
+
+    fn current_clip_rect(&self) -> Option<(i32, i32, u32, u32)> {
+        self.fb.current_clip_rect()
+    }
+
+    fn push_translate(&mut self, dx: i32, dy: i32) -> Result<()> {
+        self.fb.push_translate(dx, dy)
+    }
+
+    fn pop_translate(&mut self) -> Result<()> {
+        self.fb.pop_translate()
+    }
+
+    fn current_translate(&self) -> (i32, i32) {
+        self.fb.current_translate()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn frame_count_counts_swaps() {
+        let mut backend = HeadlessBackend::new(8, 8);
+        assert_eq!(backend.frame_count(), 0);
+        backend.swap_buffers().unwrap();
+        backend.swap_buffers().unwrap();
+        assert_eq!(backend.frame_count(), 2);
+    }
+
+    #[test]
+    fn draws_and_reads_back_real_pixels() {
+        let mut backend = HeadlessBackend::new(16, 16);
+        backend.clear(Color::rgb(0, 0, 0)).unwrap();
+        backend
+            .fill_rect(2, 2, 4, 4, Color::rgb(200, 0, 0))
+            .unwrap();
+        backend.push_translate(8, 8).unwrap();
+        backend
+            .fill_rect(0, 0, 2, 2, Color::rgb(0, 200, 0))
+            .unwrap();
+        backend.pop_translate().unwrap();
+
+        assert_eq!(backend.pixel(3, 3), Some(Color::rgb(200, 0, 0)));
+        assert_eq!(backend.pixel(9, 9), Some(Color::rgb(0, 200, 0)));
+        assert_eq!(backend.pixel(16, 0), None);
+        assert_eq!(backend.count_color(Color::rgb(200, 0, 0)), 16);
+        let px = backend.read_pixels(8, 8, 1, 1).unwrap();
+        assert_eq!(px, vec![0, 200, 0, 255]);
+    }
+
+    #[test]
+    fn find_text_locates_drawn_text() {
+        let mut backend = HeadlessBackend::new(120, 40);
+        let ink = Color::rgb(230, 230, 230);
+        backend.clear(Color::rgb(20, 20, 40)).unwrap();
+        assert_eq!(backend.find_text("hello", 8, ink), None);
+
+        backend.draw_text("hello", 30, 12, 8, ink).unwrap();
+        assert_eq!(backend.find_text("hello", 8, ink), Some((30, 12)));
+        assert_eq!(backend.find_text("help", 8, ink), None);
+        assert_eq!(backend.find_text("hello", 8, Color::rgb(255, 0, 0)), None);
+    }
+
+    #[test]
+    fn dump_png_writes_the_framebuffer() {
+        let mut backend = HeadlessBackend::new(4, 3);
+        backend.clear(Color::rgb(1, 2, 3)).unwrap();
+        let path = std::env::temp_dir().join(format!("oasis-headless-{}.png", std::process::id()));
+        backend.dump_png(&path).unwrap();
+        let bytes = std::fs::read(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(&bytes[..8], b"\x89PNG\r\n\x1a\n");
+    }
+}
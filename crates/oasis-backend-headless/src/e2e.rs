@@ -0,0 +1,195 @@
+//! End-to-end test: boot the dashboard headless, open the terminal from
+//! it with scripted input, run a command and find its output on screen.
+//! The terminal is drawn with the same SDI objects as the desktop app's.
+
+use oasis_core::active_theme::ActiveTheme;
+use oasis_core::backend::{Color, InputBackend, SdiBackend};
+use oasis_core::dashboard::{DashboardConfig, DashboardState, discover_apps};
+use oasis_core::input::{Button, InputEvent};
+use oasis_core::sdi::SdiRegistry;
+use oasis_core::skin::resolve_skin;
+use oasis_core::terminal::sdi::setup_terminal_objects;
+use oasis_core::terminal::{CommandOutput, CommandRegistry, Environment, register_builtins};
+use oasis_core::vfs::{MemoryVfs, Vfs};
+
+use crate::{HeadlessBackend, ScriptedInput};
+
+const W: u32 = 480;
+const H: u32 = 272;
+const BG: Color = Color::rgb(10, 10, 18);
+
+/// A minimal OS loop: the dashboard, and the terminal opened from it.
+struct HeadlessOs {
+    backend: HeadlessBackend,
+    input: ScriptedInput,
+    sdi: SdiRegistry,
+    theme: ActiveTheme,
+    dashboard: DashboardState,
+    vfs: MemoryVfs,
+    commands: CommandRegistry,
+    /// Output lines and the input line, once the terminal is open.
+    terminal: Option<(Vec<String>, String)>,
+}
+
+impl HeadlessOs {
+    fn boot() -> Self {
+        let skin = resolve_skin("classic").unwrap();
+        let theme = ActiveTheme::from_skin(&skin.theme);
+        let mut vfs = MemoryVfs::new();
+        vfs.mkdir("/apps").unwrap();
+        for app in ["Browser", "Files", "Terminal"] {
+            vfs.mkdir(&format!("/apps/{app}")).unwrap();
+        }
+        let apps = discover_apps(&vfs, "/apps", None).unwrap();
+        let mut sdi = SdiRegistry::new();
+        skin.apply_layout(&mut sdi);
+        let dashboard =
+            DashboardState::new(DashboardConfig::from_features(&skin.features, &theme), apps);
+        let mut commands = CommandRegistry::new();
+        register_builtins(&mut commands);
+
+        let mut backend = HeadlessBackend::new(W, H);
+        backend.init(W, H).unwrap();
+        Self {
+            backend,
+            input: ScriptedInput::new(),
+            sdi,
+            theme,
+            dashboard,
+            vfs,
+            commands,
+            terminal: None,
+        }
+    }
+
+    /// Run frames until the input script is used up.
+    fn run_script(&mut self) {
+        while !self.input.is_done() {
+            self.frame();
+        }
+    }
+
+    fn frame(&mut self) {
+        for event in self.input.poll_events() {
+            self.handle(event);
+        }
+        match &self.terminal {
+            Some((lines, input)) => {
+                self.dashboard.hide_sdi(&mut self.sdi);
+                setup_terminal_objects(&mut self.sdi, lines, None, "/", input);
+            },
+            None => self.dashboard.update_sdi(&mut self.sdi, &self.theme),
+        }
+
+        self.backend.clear(BG).unwrap();
+        self.sdi.draw(&mut self.backend).unwrap();
+        self.backend.swap_buffers().unwrap();
+    }
+
+    fn handle(&mut self, event: InputEvent) {
+        match (&mut self.terminal, event) {
+            (Some((_, input)), InputEvent::TextInput(c)) => input.push(c),
+            (Some((_, input)), InputEvent::Backspace) => {
+                input.pop();
+            },
+            (Some(_), InputEvent::ButtonPress(Button::Confirm)) => self.run_input(),
+            (None, InputEvent::ButtonPress(Button::Confirm))
+                if self
+                    .dashboard
+                    .selected_app()
+                    .is_some_and(|a| a.title == "Terminal") =>
+            {
+                self.open_terminal();
+            },
+            (None, InputEvent::ButtonPress(button)) => self.dashboard.handle_input(&button),
+            _ => {},
+        }
+    }
+
+    fn open_terminal(&mut self) {
+        self.terminal = Some((Vec::new(), String::new()));
+    }
+
+    /// Where the terminal's output lines are drawn, their font size and
+    /// color, and the output area's color.
+    fn terminal_style(&self) -> ((i32, i32, u32, u32), u16, Color, Color) {
+        let area = self.sdi.get("terminal_bg").unwrap();
+        let line = self.sdi.get("term_line_0").unwrap();
+        (
+            (area.x, area.y, area.w, area.h),
+            line.font_size,
+            line.text_color,
+            area.color,
+        )
+    }
+
+    fn run_input(&mut self) {
+        let Some((lines, input)) = &mut self.terminal else {
+            return;
+        };
+        let line = std::mem::take(input);
+        lines.push(format!("> {line}"));
+        let mut env = Environment {
+            cwd: "/".to_string(),
+            vfs: &mut self.vfs,
+            power: None,
+            time: None,
+            usb: None,
+            network: None,
+            tls: None,
+            stdin: None,
+            enforce_permissions: false,
+        };
+        match self.commands.execute(&line, &mut env) {
+            Ok(CommandOutput::Text(text)) => lines.extend(text.lines().map(str::to_string)),
+            Ok(_) => {},
+            Err(e) => lines.push(format!("error: {e}")),
+        }
+    }
+}
+
+#[test]
+fn dashboard_opens_terminal_and_runs_a_command() {
+    let mut os = HeadlessOs::boot();
+    os.frame();
+    // The dashboard draws something over the background.
+    assert!(os.backend.count_color(BG) < (W * H) as usize);
+    assert!(!os.sdi.contains("terminal_bg"));
+
+    let terminal_index = os
+        .dashboard
+        .current_page_apps()
+        .iter()
+        .position(|a| a.title == "Terminal")
+        .unwrap();
+    for _ in 0..terminal_index {
+        os.input.press(Button::Right);
+    }
+    os.input.press(Button::Confirm);
+    os.run_script();
+    let ((ax, ay, aw, ah), font, text, area) = os.terminal_style();
+    assert!(os.backend.count_color(area) > 0);
+
+    os.input
+        .type_text("expr 6 + 7")
+        .press(Button::Confirm)
+        .idle(1);
+    os.run_script();
+
+    let (x, y) = os
+        .backend
+        .find_text("13", font, text)
+        .expect("command output on screen");
+    assert!(
+        x >= ax && y >= ay && x < ax + aw as i32 && y < ay + ah as i32,
+        "({x}, {y}) is outside the terminal"
+    );
+    let (_, command_y) = os
+        .backend
+        .find_text("> expr 6 + 7", font, text)
+        .expect("command echoed on screen");
+    assert!(y > command_y, "output is not below the command");
+    // One boot frame, two per press, one typing and one idle.
+    let presses = terminal_index as u64 + 2;
+    assert_eq!(os.backend.frame_count(), 1 + 2 * presses + 2);
+}
@@ -0,0 +1,103 @@
+//! Scripted input backend.
+//!
+//! Holds a queue of frames, each a list of `InputEvent`s. Every poll
+//! delivers the next frame, so a test drives the OS one frame at a time.
+
+use std::collections::VecDeque;
+
+use oasis_core::backend::InputBackend;
+use oasis_core::input::{Button, InputEvent};
+
+/// Input backend that replays a script of per-frame events.
+///
+/// Once the script runs out, polls return no events.
+#[derive(Debug, Default)]
+pub struct ScriptedInput {
+    frames: VecDeque<Vec<InputEvent>>,
+}
+
+impl ScriptedInput {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Queue one frame delivering `events`.
+    pub fn frame(&mut self, events: impl IntoIterator<Item = InputEvent>) -> &mut Self {
+        self.frames.push_back(events.into_iter().collect());
+        self
+    }
+
+    /// Queue `frames` frames without input.
+    pub fn idle(&mut self, frames: usize) -> &mut Self {
+        for _ in 0..frames {
+            self.frames.push_back(Vec::new());
+        }
+        self
+    }
+
+    /// Queue a press of `button`: pressed in one frame, released in the next.
+    pub fn press(&mut self, button: Button) -> &mut Self {
+        self.frame([InputEvent::ButtonPress(button)])
+            .frame([InputEvent::ButtonRelease(button)])
+    }
+
+    /// Queue one frame typing `text`.
+    pub fn type_text(&mut self, text: &str) -> &mut Self {
+        self.frame(text.chars().map(InputEvent::TextInput))
+    }
+
+    /// Number of frames left in the script.
+    pub fn remaining(&self) -> usize {
+        self.frames.len()
+    }
+
+    /// Whether the whole script has been delivered.
+    pub fn is_done(&self) -> bool {
+        self.frames.is_empty()
+    }
+}
+
+impl InputBackend for ScriptedInput {
+    fn poll_events(&mut self) -> Vec<InputEvent> {
+        self.frames.pop_front().unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn frames_are_delivered_one_poll_at_a_time() {
+        let mut input = ScriptedInput::new();
+        input
+            .frame([InputEvent::FocusGained])
+            .idle(1)
+            .type_text("ok");
+        assert_eq!(input.remaining(), 3);
+
+        assert_eq!(input.poll_events(), vec![InputEvent::FocusGained]);
+        assert!(input.poll_events().is_empty());
+        assert_eq!(
+            input.poll_events(),
+            vec![InputEvent::TextInput('o'), InputEvent::TextInput('k')]
+        );
+        assert!(input.is_done());
+        assert!(input.poll_events().is_empty());
+    }
+
+    #[test]
+    fn press_releases_on_the_next_frame() {
+        let mut input = ScriptedInput::new();
+        input.press(Button::Confirm);
+        assert_eq!(
+            input.poll_events(),
+            vec![InputEvent::ButtonPress(Button::Confirm)]
+        );
+        assert_eq!(
+            input.poll_events(),
+            vec![InputEvent::ButtonRelease(Button::Confirm)]
+        );
+        assert!(input.is_done());
+    }
+}
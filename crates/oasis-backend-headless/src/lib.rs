@@ -0,0 +1,17 @@
+//! Headless backend for OASIS_OS.
+//!
+//! Runs the whole OS without a window, for integration tests and CI
+//! screenshots. `HeadlessBackend` implements `SdiBackend` on the UE5
+//! backend's software RGBA framebuffer and adds frame counting, pixel
+//! queries, text search and PNG dumps. `ScriptedInput` implements
+//! `InputBackend` from a queue of per-frame events, so a test can feed
+//! button presses frame by frame.
+
+mod backend;
+mod input;
+
+#[cfg(test)]
+mod e2e;
+
+pub use backend::HeadlessBackend;
+pub use input::ScriptedInput;
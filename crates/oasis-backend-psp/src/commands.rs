@@ -46,6 +46,8 @@ pub fn execute_command(cmd: &str, config: &mut psp::config::Config) -> Vec<Strin
             String::from("  play PATH  - Play MP3 file"),
             String::from("  pause/resume/stop - Audio control"),
             String::from("  umd        - UMD disc info"),
+            String::from("  wm snap ID REGION - Snap a window"),
+            String::from("             (left/right/top/bottom/max/restore)"),
             String::from("  save/load  - Terminal history"),
             String::from("  clear      - Clear terminal"),
             String::new(),
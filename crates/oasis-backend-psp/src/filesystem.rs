@@ -4,6 +4,7 @@
 //! JPEG decoding uses `psp::image::decode_jpeg()` hardware decoder.
 
 /// A single entry from a directory listing.
+#[derive(Clone)]
 pub struct FileEntry {
     /// File or directory name (ASCII, up to 255 chars).
     pub name: String,
@@ -68,6 +68,12 @@ impl SystemFont {
         self.renderer.as_ref().map_or(0.0, |r| r.measure_text(text))
     }
 
+    /// Advance width of a single glyph in pixels.
+    pub fn glyph_advance(&self, ch: char) -> f32 {
+        let mut buf = [0u8; 4];
+        self.measure_text(ch.encode_utf8(&mut buf))
+    }
+
     /// Get the line height in pixels.
     pub fn line_height(&self) -> f32 {
         self.renderer.as_ref().map_or(12.0, |r| r.line_height())
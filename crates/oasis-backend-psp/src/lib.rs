@@ -3,6 +3,8 @@
 //! Hardware-accelerated rendering via the PSP Graphics Engine (sceGu/sceGum).
 //! All rectangles, textures, and text are drawn as GU `Sprites` primitives,
 //! offloading work from the 333MHz MIPS CPU to the dedicated GE hardware.
+//! Rounded rects and circles are batched sprite spans, lines use GU
+//! `Lines`, and gradients are Gouraud-shaded triangles.
 //!
 //! Controller input via `sceCtrlPeekBufferPositive` with edge detection for
 //! press/release events.
@@ -26,6 +28,7 @@ pub mod power;
 pub mod procedural;
 pub mod render;
 pub mod sfx;
+pub mod shapes;
 pub mod status;
 pub mod textures;
 pub mod threading;
@@ -39,7 +42,9 @@ pub use audio::PspAudioBackend;
 pub use filesystem::{FileEntry, decode_jpeg, format_size, list_directory, read_file};
 pub use network::{PspNetworkBackend, PspNetworkService};
 pub use tls::PspTlsProvider;
-pub use power::{check_power_resumed, power_tick, register_power_callback, set_clock};
+pub use power::{
+    check_power_resumed, check_power_suspending, power_tick, register_power_callback, set_clock,
+};
 #[cfg(feature = "kernel-exception")]
 pub use power::register_exception_handler;
 pub use procedural::{
@@ -58,8 +63,18 @@ pub use oasis_core::backend::{Color, SdiBackend, TextureId};
 pub use oasis_core::error::{OasisError, Result as OasisResult};
 pub use oasis_core::input::{Button, InputEvent, Trigger};
 pub use oasis_core::sdi::SdiRegistry;
+pub use oasis_core::skin::Skin;
+pub use oasis_core::skin_assets::SkinAssets;
+pub use oasis_core::ui::layout::HAlign;
+pub use oasis_core::ui::dialog::{Dialog, DialogResult};
+pub use oasis_core::ui::spinner::Spinner;
+pub use oasis_core::ui::table_view::{Column, ColumnWidth, SortKey, TableView};
+pub use oasis_core::ui::widget::Widget;
+pub use oasis_core::ui::{DrawContext, Theme};
 pub use oasis_core::wm::manager::{WindowManager, WmEvent};
-pub use oasis_core::wm::window::{WindowConfig, WindowType, WmTheme};
+pub use oasis_core::wm::taskbar::TaskbarLayout;
+pub use oasis_core::wm::window::{WindowConfig, WindowState, WindowType, WmTheme};
+pub use oasis_core::wm::{DragPayload, past_threshold};
 
 // ---------------------------------------------------------------------------
 // Imports
@@ -71,7 +86,7 @@ use std::ptr;
 
 use psp::sys::{
     self, BlendFactor, BlendOp, DisplayPixelFormat, GuContextType, GuState, GuSyncBehavior,
-    GuSyncMode, MatrixMode, TextureColorComponent, TextureEffect, TextureFilter,
+    GuSyncMode, MatrixMode, ShadingModel, TextureColorComponent, TextureEffect, TextureFilter,
     TexturePixelFormat,
 };
 use psp::vram_alloc::get_vram_allocator;
@@ -142,6 +157,11 @@ pub struct PspBackend {
     /// When true, `draw_text_inner` skips the system font and uses the
     /// 8x8 bitmap font. Set before drawing content that needs smaller text.
     pub force_bitmap_font: bool,
+    /// Uncached VRAM address of the framebuffer being drawn (null until
+    /// `init()`). Swapped with `disp_fb` on every buffer swap.
+    pub(crate) draw_fb: *mut u8,
+    /// Uncached VRAM address of the framebuffer on screen.
+    pub(crate) disp_fb: *mut u8,
 }
 
 impl PspBackend {
@@ -158,6 +178,8 @@ impl PspBackend {
             system_font: None,
             volatile_alloc: None,
             force_bitmap_font: false,
+            draw_fb: ptr::null_mut(),
+            disp_fb: ptr::null_mut(),
         }
     }
 
@@ -184,6 +206,8 @@ impl PspBackend {
 
             let fbp0_zero = fbp0.as_mut_ptr_from_zero() as *mut c_void;
             let fbp1_zero = fbp1.as_mut_ptr_from_zero() as *mut c_void;
+            self.draw_fb = fbp0.as_mut_ptr_direct_to_vram();
+            self.disp_fb = fbp1.as_mut_ptr_direct_to_vram();
 
             // Font atlas in RAM (16-byte aligned).
             let atlas_size = (render::FONT_ATLAS_W * render::FONT_ATLAS_H * 4) as usize;
@@ -225,6 +249,9 @@ impl PspBackend {
                 0,
             );
 
+            // Smooth shading, so vertex colors interpolate into gradients.
+            sys::sceGuShadeModel(ShadingModel::Smooth);
+
             // Texture state.
             sys::sceGuEnable(GuState::Texture2D);
             sys::sceGuTexFunc(TextureEffect::Modulate, TextureColorComponent::Rgba);
@@ -319,6 +346,63 @@ impl PspBackend {
             sys::sceGuSwapBuffers();
             sys::sceGuStart(GuContextType::Direct, &raw mut DISPLAY_LIST as *mut c_void);
         }
+        ptr::swap(&mut self.draw_fb, &mut self.disp_fb);
+    }
+
+    /// Read back a rect of the frame being drawn as RGBA bytes.
+    ///
+    /// The rect is clamped to the screen, so the result holds fewer than
+    /// `w * h` pixels when it extends past an edge. Commands queued so
+    /// far are flushed first so the framebuffer holds everything drawn
+    /// this frame; the display list is then reopened and drawing can
+    /// continue.
+    pub fn read_pixels_inner(&self, x: i32, y: i32, w: u32, h: u32) -> OasisResult<Vec<u8>> {
+        if self.draw_fb.is_null() {
+            return Err(OasisError::Backend("PSP display not initialized".into()));
+        }
+        let clamp = |v: i64, max: u32| v.clamp(0, i64::from(max)) as usize;
+        let (x0, x1) = (
+            clamp(i64::from(x), SCREEN_WIDTH),
+            clamp(i64::from(x) + i64::from(w), SCREEN_WIDTH),
+        );
+        let (y0, y1) = (
+            clamp(i64::from(y), SCREEN_HEIGHT),
+            clamp(i64::from(y) + i64::from(h), SCREEN_HEIGHT),
+        );
+        let mut out = Vec::with_capacity((x1 - x0) * (y1 - y0) * 4);
+        if x0 == x1 || y0 == y1 {
+            return Ok(out);
+        }
+
+        // SAFETY: GU frame lifecycle calls as in swap_buffers_inner.
+        // `draw_fb` points at a BUF_WIDTH x SCREEN_HEIGHT Psm8888
+        // framebuffer allocated in init(), and the clamped rect lies
+        // within its visible 480-pixel part of each row. The GE writes
+        // VRAM behind the CPU cache, so the pixels are read through the
+        // uncached mirror rather than the cached `draw_fb` address.
+        unsafe {
+            sys::sceGuFinish();
+            sys::sceGuSync(GuSyncMode::Finish, GuSyncBehavior::Wait);
+            let fb =
+                psp::cache::UncachedPtr::from_cached_addr(self.draw_fb).as_ptr() as *const u32;
+            for row in y0..y1 {
+                let line = fb.add(row * BUF_WIDTH as usize);
+                for col in x0..x1 {
+                    // Psm8888 is ABGR with red in the low byte. The
+                    // alpha channel holds blending leftovers, so the
+                    // read-back is made opaque.
+                    let abgr = ptr::read_volatile(line.add(col));
+                    out.extend_from_slice(&[
+                        abgr as u8,
+                        (abgr >> 8) as u8,
+                        (abgr >> 16) as u8,
+                        0xFF,
+                    ]);
+                }
+            }
+            sys::sceGuStart(GuContextType::Direct, &raw mut DISPLAY_LIST as *mut c_void);
+        }
+        Ok(out)
     }
 
     /// Current cursor position (for rendering the cursor sprite).
@@ -366,6 +450,20 @@ impl SdiBackend for PspBackend {
         Ok(())
     }
 
+    fn blit_rotated(
+        &mut self,
+        tex: TextureId,
+        x: i32,
+        y: i32,
+        w: u32,
+        h: u32,
+        angle_degrees: f32,
+        center: Option<(i32, i32)>,
+    ) -> OasisResult<()> {
+        self.blit_rotated_inner(tex, x, y, w, h, angle_degrees, center);
+        Ok(())
+    }
+
     fn fill_rect(&mut self, x: i32, y: i32, w: u32, h: u32, color: Color) -> OasisResult<()> {
         self.fill_rect_inner(x, y, w, h, color);
         Ok(())
@@ -413,20 +511,166 @@ impl SdiBackend for PspBackend {
         Ok(())
     }
 
-    fn measure_text(&self, text: &str, _font_size: u16) -> u32 {
-        // PSP bitmap font is fixed 8×8 (no scaling support yet).
-        text.len() as u32 * 8
+    fn measure_text(&self, text: &str, font_size: u16) -> u32 {
+        text.chars().map(|ch| self.glyph_advance(ch, font_size)).sum()
+    }
+
+    fn glyph_advance(&self, ch: char, font_size: u16) -> u32 {
+        // The system font is proportional and drawn at a fixed size.
+        if !self.force_bitmap_font
+            && let Some(sf) = &self.system_font
+        {
+            return sf.glyph_advance(ch).round() as u32;
+        }
+        // Bitmap fallback: monospace 8x8 glyphs, scaled like draw_text.
+        let scale = if font_size >= 8 { font_size / 8 } else { 1 };
+        crate::font::GLYPH_WIDTH * scale as u32
     }
 
-    fn read_pixels(&self, _x: i32, _y: i32, _w: u32, _h: u32) -> OasisResult<Vec<u8>> {
-        Err(OasisError::Backend(
-            "read_pixels not supported on PSP".into(),
-        ))
+    fn read_pixels(&self, x: i32, y: i32, w: u32, h: u32) -> OasisResult<Vec<u8>> {
+        self.read_pixels_inner(x, y, w, h)
     }
 
     fn shutdown(&mut self) -> OasisResult<()> {
         Ok(())
     }
+
+    // -------------------------------------------------------------------
+    // Extended: Shape Primitives
+    // -------------------------------------------------------------------
+
+    fn fill_rounded_rect(
+        &mut self,
+        x: i32,
+        y: i32,
+        w: u32,
+        h: u32,
+        radius: u16,
+        color: Color,
+    ) -> OasisResult<()> {
+        self.fill_rounded_rect_inner(x, y, w, h, radius, color);
+        Ok(())
+    }
+
+    fn stroke_rounded_rect(
+        &mut self,
+        x: i32,
+        y: i32,
+        w: u32,
+        h: u32,
+        radius: u16,
+        stroke_width: u16,
+        color: Color,
+    ) -> OasisResult<()> {
+        self.stroke_rounded_rect_inner(x, y, w, h, radius, stroke_width, color);
+        Ok(())
+    }
+
+    fn draw_line(
+        &mut self,
+        x1: i32,
+        y1: i32,
+        x2: i32,
+        y2: i32,
+        width: u16,
+        color: Color,
+    ) -> OasisResult<()> {
+        self.draw_line_inner(x1, y1, x2, y2, width, color);
+        Ok(())
+    }
+
+    fn fill_circle(&mut self, cx: i32, cy: i32, radius: u16, color: Color) -> OasisResult<()> {
+        self.fill_circle_inner(cx, cy, radius, color);
+        Ok(())
+    }
+
+    fn stroke_circle(
+        &mut self,
+        cx: i32,
+        cy: i32,
+        radius: u16,
+        stroke_width: u16,
+        color: Color,
+    ) -> OasisResult<()> {
+        self.stroke_circle_inner(cx, cy, radius, stroke_width, color);
+        Ok(())
+    }
+
+    fn fill_triangle(
+        &mut self,
+        x1: i32,
+        y1: i32,
+        x2: i32,
+        y2: i32,
+        x3: i32,
+        y3: i32,
+        color: Color,
+    ) -> OasisResult<()> {
+        self.fill_triangle_inner(x1, y1, x2, y2, x3, y3, color);
+        Ok(())
+    }
+
+    // -------------------------------------------------------------------
+    // Extended: Gradient Fills
+    // -------------------------------------------------------------------
+
+    fn fill_rect_gradient_v(
+        &mut self,
+        x: i32,
+        y: i32,
+        w: u32,
+        h: u32,
+        top_color: Color,
+        bottom_color: Color,
+    ) -> OasisResult<()> {
+        let corners = [top_color, top_color, bottom_color, bottom_color];
+        self.fill_rect_gradient_inner(x, y, w, h, corners);
+        Ok(())
+    }
+
+    fn fill_rect_gradient_h(
+        &mut self,
+        x: i32,
+        y: i32,
+        w: u32,
+        h: u32,
+        left_color: Color,
+        right_color: Color,
+    ) -> OasisResult<()> {
+        let corners = [left_color, right_color, left_color, right_color];
+        self.fill_rect_gradient_inner(x, y, w, h, corners);
+        Ok(())
+    }
+
+    fn fill_rect_gradient_4(
+        &mut self,
+        x: i32,
+        y: i32,
+        w: u32,
+        h: u32,
+        top_left: Color,
+        top_right: Color,
+        bottom_left: Color,
+        bottom_right: Color,
+    ) -> OasisResult<()> {
+        let corners = [top_left, top_right, bottom_left, bottom_right];
+        self.fill_rect_gradient_inner(x, y, w, h, corners);
+        Ok(())
+    }
+
+    fn fill_rounded_rect_gradient_v(
+        &mut self,
+        x: i32,
+        y: i32,
+        w: u32,
+        h: u32,
+        radius: u16,
+        top_color: Color,
+        bottom_color: Color,
+    ) -> OasisResult<()> {
+        self.fill_rounded_rect_gradient_v_inner(x, y, w, h, radius, top_color, bottom_color);
+        Ok(())
+    }
 }
 
 // ---------------------------------------------------------------------------
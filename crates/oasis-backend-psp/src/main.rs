@@ -11,10 +11,11 @@
 #![no_main]
 
 use oasis_backend_psp::{
-    AudioCmd, AudioHandle, Button, CURSOR_H, CURSOR_W, Color, FileEntry, InputEvent, IoCmd,
-    IoResponse, PspBackend, SCREEN_HEIGHT, SCREEN_WIDTH, SdiBackend, SdiRegistry, SfxId,
-    StatusBarInfo, SystemInfo, TextureId, Trigger, WindowConfig, WindowManager, WindowType,
-    WmEvent,
+    AudioCmd, AudioHandle, Button, CURSOR_H, CURSOR_W, Color, Column, ColumnWidth, Dialog,
+    DialogResult, DragPayload, DrawContext, FileEntry, HAlign, InputEvent, IoCmd, IoResponse, PspBackend, SCREEN_HEIGHT,
+    SCREEN_WIDTH, SdiBackend, SdiRegistry, SfxId, Skin, SkinAssets, SortKey, Spinner, StatusBarInfo, SystemInfo,
+    TableView, TaskbarLayout, TextureId, Theme, Trigger, Widget, WindowConfig, WindowManager,
+    WindowState, WindowType, WmEvent,
 };
 
 mod commands;
@@ -88,6 +89,15 @@ const BOTTOM_UPPER_Y: i32 = BOTTOMBAR_Y;
 const BOTTOM_UPPER_H: u32 = 16;
 const BOTTOM_LOWER_Y: i32 = BOTTOMBAR_Y + BOTTOM_UPPER_H as i32;
 
+/// Desktop mode window entries in the bottom bar lower row.
+const DESKTOP_TASKBAR: TaskbarLayout = TaskbarLayout {
+    x: 24,
+    y: BOTTOM_LOWER_Y + 2,
+    slot_w: 76,
+    slot_h: 12,
+    gap: 4,
+};
+
 // Font metrics.
 const CHAR_W: i32 = 8;
 
@@ -123,6 +133,19 @@ const CURSOR_PAD: i32 = 3;
 // Persistent configuration path on Memory Stick.
 const CONFIG_PATH: &str = "ms0:/PSP/GAME/OASISOS/config.rcfg";
 
+// Desktop window layout. HOME exits from the firmware's exit callback
+// without coming back to the main loop, so it is saved when leaving
+// Desktop mode and before a suspend.
+const LAYOUT_PATH: &str = "ms0:/PSP/GAME/OASISOS/layout.toml";
+
+// Optional wallpaper image; replaces the generated gradient when present.
+// Used when the skin does not name a `[wallpaper] image` of its own.
+const DEFAULT_WALLPAPER_PATH: &str = "ms0:/PSP/GAME/OASISOS/wallpaper.jpg";
+
+// Optional skin. Its `[assets]` nine-patches draw the Desktop window
+// frames and titlebar buttons.
+const SKIN_DIR: &str = "ms0:/PSP/GAME/OASISOS/skin";
+
 // Colors -- bar backgrounds (green-tinted opaque, matching PSIX reference).
 const STATUSBAR_BG: Color = Color::rgba(30, 80, 30, 200);
 const BAR_BG: Color = Color::rgba(30, 80, 30, 200);
@@ -185,6 +208,8 @@ const TERM_INPUT_Y: i32 = BOTTOMBAR_Y - 14;
 const FM_VISIBLE_ROWS: usize = 18;
 const FM_ROW_H: i32 = 10;
 const FM_START_Y: i32 = CONTENT_TOP as i32 + 14;
+/// Top of the file tables in the windowed file manager, below the path line.
+const FM_WIN_TABLE_Y: i32 = 12;
 
 // ---------------------------------------------------------------------------
 // App entries (matching oasis-core FALLBACK_COLORS)
@@ -482,7 +507,7 @@ fn psp_main() {
         WALLPAPER_TEX_W,
         WALLPAPER_TEX_H,
     );
-    let wallpaper_tex = backend
+    let mut wallpaper_tex = backend
         .load_texture_inner(WALLPAPER_TEX_W, WALLPAPER_TEX_H, &wallpaper_data)
         .unwrap_or(TextureId(0));
 
@@ -494,14 +519,32 @@ fn psp_main() {
     show_boot_screen(&mut backend, "Setting up UI...", 60);
 
     // -- Window Manager (Desktop mode) --
-    let psp_theme = oasis_backend_psp::psp_wm_theme();
+    let mut psp_theme = oasis_backend_psp::psp_wm_theme();
+    let skin = load_skin();
+    if let Some(skin) = &skin {
+        match SkinAssets::load(skin, &mut backend) {
+            Ok(assets) => assets.apply(&mut psp_theme),
+            Err(e) => psp::dprintln!("OASIS_OS: cannot load skin assets: {}", e),
+        }
+    }
     let mut wm = WindowManager::with_theme(SCREEN_WIDTH, SCREEN_HEIGHT, psp_theme);
+    wm.set_taskbar_layout(Some(DESKTOP_TASKBAR));
     let mut sdi = SdiRegistry::new();
 
     // -- App mode --
     let mut app_mode = AppMode::Classic;
     let mut classic_view = ClassicView::Dashboard;
 
+    // Reopen the Desktop windows from the last session.
+    if let Ok(data) = psp::io::read_to_vec(LAYOUT_PATH) {
+        let layout = String::from_utf8_lossy(&data);
+        match wm.restore_layout(&layout, &mut sdi, restored_window_config) {
+            Ok(0) => {},
+            Ok(_) => app_mode = AppMode::Desktop,
+            Err(e) => psp::dprintln!("OASIS_OS: cannot restore window layout: {}", e),
+        }
+    }
+
     let mut selected: usize = 0;
     let page: usize = 0;
     let mut top_tab = TopTab::Mso;
@@ -542,17 +585,23 @@ fn psp_main() {
     let mut fm_entries: Vec<FileEntry> = Vec::new();
     let mut fm_selected: usize = 0;
     let mut fm_scroll: usize = 0;
+    // Column and direction the panel is sorted by (see `filemgr_columns`).
+    let mut fm_sort: Option<(usize, bool)> = None;
     let mut fm_loaded = false;
 
     let mut fm2_path = String::from("ms0:/");
     let mut fm2_entries: Vec<FileEntry> = Vec::new();
     let mut fm2_selected: usize = 0;
     let mut fm2_scroll: usize = 0;
+    let mut fm2_sort: Option<(usize, bool)> = None;
     let mut fm2_loaded = false;
 
     // 0 = left panel, 1 = right panel.
     let mut fm_active_panel: usize = 0;
 
+    // File waiting for its delete to be confirmed, with the dialog asking.
+    let mut fm_delete: Option<(Dialog, String)> = None;
+
     // UMD drive state.
     let mut umd_activated = false;
 
@@ -581,10 +630,24 @@ fn psp_main() {
     // Single background worker thread handles both audio and file I/O.
     let (audio, io) = oasis_backend_psp::spawn_workers();
     let mut pv_loading = false; // true while waiting for async texture load
+
+    // Decode the wallpaper image on the I/O thread so boot isn't held up;
+    // the gradient stays until it arrives (or forever, if there is none).
+    let wallpaper_path = wallpaper_path(skin.as_ref());
+    io.send(IoCmd::LoadTexture {
+        path: wallpaper_path.clone(),
+        max_w: SCREEN_WIDTH as i32,
+        max_h: SCREEN_HEIGHT as i32,
+    });
     show_boot_screen(&mut backend, "Starting workers...", 80);
 
     // Confirm button held state for pointer simulation.
-    let mut _confirm_held = false;
+    let mut confirm_held = false;
+    // Cancel held in Desktop mode: L/R switch windows instead of tabs.
+    let mut cancel_held = false;
+    // File manager row pressed in Desktop mode: where, and its path. It
+    // becomes a drag once the cursor moves far enough with Confirm held.
+    let mut drag_source: Option<((i32, i32), String)> = None;
 
     // Register power callback for sleep/wake handling (keep handle alive).
     let _power_cb = oasis_backend_psp::register_power_callback();
@@ -603,17 +666,25 @@ fn psp_main() {
         if oasis_backend_psp::check_power_resumed() {
             term_lines.push(String::from("[Power] Resumed from sleep"));
         }
+        if oasis_backend_psp::check_power_suspending() && app_mode == AppMode::Desktop {
+            save_layout(&wm);
+        }
 
         // -- Poll async I/O responses --
         while let Some(resp) = io.try_recv() {
             match resp {
                 IoResponse::TextureReady {
-                    path: _,
+                    path,
                     width,
                     height,
                     rgba,
                 } => {
-                    if pv_loading {
+                    if path == wallpaper_path {
+                        if let Some(tex) = backend.load_texture_inner(width, height, &rgba) {
+                            backend.destroy_texture_inner(wallpaper_tex);
+                            wallpaper_tex = tex;
+                        }
+                    } else if pv_loading {
                         if let Some(old) = pv_tex.take() {
                             backend.destroy_texture_inner(old);
                         }
@@ -624,6 +695,8 @@ fn psp_main() {
                         pv_loading = false;
                     }
                 },
+                // No wallpaper image is fine; keep the gradient.
+                IoResponse::Error { path, .. } if path == wallpaper_path => {},
                 IoResponse::Error { path, msg } => {
                     term_lines.push(format!("I/O error: {} - {}", path, msg));
                     pv_loading = false;
@@ -648,12 +721,59 @@ fn psp_main() {
         for event in &events {
             // -- Desktop mode: bridge analog stick + Confirm to pointer events --
             if app_mode == AppMode::Desktop {
+                // A keyboard move/resize or a dialog takes all input until
+                // it ends.
+                if wm.keyboard_grab().is_some() || wm.modal_dialog().is_some() {
+                    wm.handle_input(event, &mut sdi);
+                    continue;
+                }
                 match event {
                     InputEvent::ButtonPress(Button::Confirm) => {
-                        _confirm_held = true;
+                        confirm_held = true;
                         let (cx, cy) = backend.cursor_pos();
                         let ptr_event = InputEvent::PointerClick { x: cx, y: cy };
                         let wm_event = wm.handle_input(&ptr_event, &mut sdi);
+                        drag_source = match &wm_event {
+                            WmEvent::ContentClick(id, lx, ly) if id == "filemgr" => {
+                                let cw = wm
+                                    .get_window(id)
+                                    .map_or(0, |w| w.content_rect(wm.theme()).2);
+                                let (path, entries, selected, scroll, sort) =
+                                    if *lx < (cw / 2) as i32 {
+                                        (
+                                            &fm_path,
+                                            &mut fm_entries,
+                                            &mut fm_selected,
+                                            &mut fm_scroll,
+                                            &mut fm_sort,
+                                        )
+                                    } else {
+                                        (
+                                            &fm2_path,
+                                            &mut fm2_entries,
+                                            &mut fm2_selected,
+                                            &mut fm2_scroll,
+                                            &mut fm2_sort,
+                                        )
+                                    };
+                                let (px, pw) = filemgr_panel_span(cw, *lx < (cw / 2) as i32);
+                                let clicked = {
+                                    let mut table = filemgr_table(entries, None, *scroll, *sort);
+                                    table
+                                        .click_header(*lx - px, *ly - FM_WIN_TABLE_Y, pw)
+                                        .map(|_| table.sort())
+                                };
+                                if let Some(new_sort) = clicked {
+                                    *sort = new_sort;
+                                    filemgr_sort(entries, selected, scroll, new_sort);
+                                    None
+                                } else {
+                                    filemgr_file_at(path, entries, *scroll, *ly)
+                                        .map(|file| ((cx, cy), file))
+                                }
+                            },
+                            _ => None,
+                        };
                         handle_wm_event(
                             &wm_event,
                             &mut term_lines,
@@ -665,18 +785,59 @@ fn psp_main() {
                         );
                     },
                     InputEvent::ButtonRelease(Button::Confirm) => {
-                        _confirm_held = false;
+                        confirm_held = false;
+                        drag_source = None;
                         let (cx, cy) = backend.cursor_pos();
                         let ptr_event = InputEvent::PointerRelease { x: cx, y: cy };
-                        wm.handle_input(&ptr_event, &mut sdi);
+                        if let WmEvent::Drop {
+                            target_window,
+                            payload,
+                            ..
+                        } = wm.handle_input(&ptr_event, &mut sdi)
+                        {
+                            let name = payload.label().to_string();
+                            let DragPayload::VfsPath(path) = payload;
+                            match target_window.as_str() {
+                                "music" => {
+                                    audio.send(AudioCmd::LoadAndPlay(path));
+                                    term_lines.push(format!("Playing: {}", name));
+                                    mp_file_name = name;
+                                },
+                                "photos" => {
+                                    io.send(IoCmd::LoadTexture {
+                                        path,
+                                        max_w: SCREEN_WIDTH as i32,
+                                        max_h: SCREEN_HEIGHT as i32,
+                                    });
+                                    pv_loading = true;
+                                },
+                                _ => {
+                                    term_lines.push(format!(
+                                        "[WM] {} cannot open {}",
+                                        target_window, name
+                                    ));
+                                },
+                            }
+                        }
                     },
                     InputEvent::CursorMove { x, y } => {
+                        // Pressing a file row and moving away drags the file.
+                        if confirm_held
+                            && wm.item_drag().is_none()
+                            && let Some((start, path)) = &drag_source
+                            && oasis_backend_psp::past_threshold(*start, (*x, *y))
+                        {
+                            let payload = DragPayload::VfsPath(path.clone());
+                            let _ = wm.begin_drag(payload, "filemgr");
+                            drag_source = None;
+                        }
                         // Always forward cursor moves when in Desktop mode.
                         let move_event = InputEvent::CursorMove { x: *x, y: *y };
                         wm.handle_input(&move_event, &mut sdi);
                     },
                     InputEvent::ButtonPress(Button::Select) => {
                         // Toggle back to Classic mode.
+                        save_layout(&wm);
                         app_mode = AppMode::Classic;
                         classic_view = ClassicView::Dashboard;
                     },
@@ -692,6 +853,12 @@ fn psp_main() {
                         // Toggle terminal window.
                         open_app_window(&mut wm, &mut sdi, "terminal", "Terminal");
                     },
+                    InputEvent::ButtonPress(Button::Square) => {
+                        // Move/resize the active window with the D-pad.
+                        if let Some(id) = wm.active_window().map(String::from) {
+                            let _ = wm.begin_keyboard_grab(&id, &mut sdi);
+                        }
+                    },
                     // Dashboard navigation works in Desktop mode too.
                     InputEvent::ButtonPress(Button::Up) => {
                         if selected >= GRID_COLS {
@@ -723,6 +890,13 @@ fn psp_main() {
                         selected = (selected + 1) % page_count.max(1);
                         audio.send(AudioCmd::PlaySfx(SfxId::Click));
                     },
+                    InputEvent::ButtonPress(Button::Cancel) => cancel_held = true,
+                    InputEvent::ButtonRelease(Button::Cancel) => cancel_held = false,
+                    // With Cancel held, the triggers switch between windows.
+                    InputEvent::TriggerPress(trigger) if cancel_held && wm.window_count() > 0 => {
+                        wm.cycle_focus(*trigger == Trigger::Right, &mut sdi);
+                        audio.send(AudioCmd::PlaySfx(SfxId::Click));
+                    },
                     InputEvent::TriggerPress(Trigger::Left) => {
                         top_tab = top_tab.next();
                         audio.send(AudioCmd::PlaySfx(SfxId::Click));
@@ -738,6 +912,31 @@ fn psp_main() {
             }
 
             // -- Classic mode input --
+
+            // A delete confirmation takes all input until it closes.
+            if let Some((dialog, full_path)) = &mut fm_delete
+                && *event != InputEvent::Quit
+            {
+                match dialog.handle_input(event) {
+                    Some(DialogResult::Button(0)) => {
+                        match psp::io::remove_file(full_path.as_str()) {
+                            Ok(()) => {
+                                term_lines.push(format!("Deleted: {}", full_path));
+                                fm_loaded = false;
+                                fm2_loaded = false;
+                            },
+                            Err(e) => {
+                                let _ = psp::dialog::error_dialog(e.0 as u32);
+                            },
+                        }
+                        fm_delete = None;
+                    },
+                    Some(_) => fm_delete = None,
+                    None => {},
+                }
+                continue;
+            }
+
             match event {
                 InputEvent::Quit => return,
 
@@ -980,6 +1179,15 @@ fn psp_main() {
                             audio.send(AudioCmd::Stop);
                             vec!["Stopped.".into()]
                         },
+                        // Window requests, e.g. `wm snap terminal left`: the
+                        // keyboard path for what the pointer does by dragging.
+                        _ if cmd.trim().starts_with("wm ") => {
+                            let request = cmd.trim().strip_prefix("wm ").unwrap().trim();
+                            match wm.apply_request(request, &mut sdi) {
+                                Ok(_) => vec![format!("WM: {}", request)],
+                                Err(e) => vec![format!("wm: {}", e)],
+                            }
+                        },
                         _ => commands::execute_command(&cmd, &mut config),
                     };
                     for line in output {
@@ -1123,36 +1331,24 @@ fn psp_main() {
                 InputEvent::ButtonPress(Button::Square)
                     if classic_view == ClassicView::FileManager =>
                 {
-                    let (path, entries, sel, loaded) = if fm_active_panel == 0 {
-                        (&fm_path, &fm_entries, fm_selected, &mut fm_loaded)
+                    let (path, entries, sel) = if fm_active_panel == 0 {
+                        (&fm_path, &fm_entries, fm_selected)
                     } else {
-                        (&fm2_path, &fm2_entries, fm2_selected, &mut fm2_loaded)
+                        (&fm2_path, &fm2_entries, fm2_selected)
                     };
                     // UMD is read-only, skip delete.
                     if path.starts_with("disc0:") {
                         term_lines.push("UMD is read-only.".into());
                     } else if sel < entries.len() && !entries[sel].is_dir {
                         let name = &entries[sel].name;
+                        let full_path = if path.ends_with('/') {
+                            format!("{}{}", path, name)
+                        } else {
+                            format!("{}/{}", path, name)
+                        };
                         let msg = format!("Delete {}?", name);
-                        match psp::dialog::confirm_dialog(&msg) {
-                            Ok(psp::dialog::DialogResult::Confirm) => {
-                                let full_path = if path.ends_with('/') {
-                                    format!("{}{}", path, name)
-                                } else {
-                                    format!("{}/{}", path, name)
-                                };
-                                match psp::io::remove_file(&full_path) {
-                                    Ok(()) => {
-                                        term_lines.push(format!("Deleted: {}", full_path));
-                                        *loaded = false;
-                                    },
-                                    Err(e) => {
-                                        let _ = psp::dialog::error_dialog(e.0 as u32);
-                                    },
-                                }
-                            },
-                            _ => {}, // Cancelled or closed
-                        }
+                        let dialog = Dialog::new("Delete", msg, &["Delete", "Cancel"]);
+                        fm_delete = Some((dialog, full_path));
                     }
                 },
                 InputEvent::ButtonPress(Button::Triangle)
@@ -1350,12 +1546,19 @@ fn psp_main() {
                     fm_entries = oasis_backend_psp::list_directory(&fm_path);
                     fm_selected = 0;
                     fm_scroll = 0;
+                    filemgr_sort(&mut fm_entries, &mut fm_selected, &mut fm_scroll, fm_sort);
                     fm_loaded = true;
                 }
                 if classic_view == ClassicView::FileManager && !fm2_loaded {
                     fm2_entries = oasis_backend_psp::list_directory(&fm2_path);
                     fm2_selected = 0;
                     fm2_scroll = 0;
+                    filemgr_sort(
+                        &mut fm2_entries,
+                        &mut fm2_selected,
+                        &mut fm2_scroll,
+                        fm2_sort,
+                    );
                     fm2_loaded = true;
                 }
                 if classic_view == ClassicView::PhotoViewer && !pv_loaded && !pv_viewing {
@@ -1431,16 +1634,24 @@ fn psp_main() {
                             &fm_entries,
                             fm_selected,
                             fm_scroll,
+                            fm_sort,
                             &fm2_path,
                             &fm2_entries,
                             fm2_selected,
                             fm2_scroll,
+                            fm2_sort,
                             fm_active_panel,
                         );
                         draw_button_hints(
                             &mut backend,
                             &[("X", "Open"), ("O", "Back"), ("<>", "Panel"), ("^v", "Nav")],
                         );
+                        if let Some((dialog, _)) = &fm_delete {
+                            let theme = Theme::dark();
+                            let mut ctx = DrawContext::new(&mut backend, &theme);
+                            // The PSP backend's drawing calls cannot fail.
+                            let _ = dialog.draw(&mut ctx, 0, 0, SCREEN_WIDTH, SCREEN_HEIGHT);
+                        }
                         backend.force_bitmap_font = false;
                     },
                     ClassicView::PhotoViewer => {
@@ -1449,7 +1660,7 @@ fn psp_main() {
                             draw_photo_view(&mut backend, pv_tex, pv_img_w, pv_img_h);
                             draw_button_hints(&mut backend, &[("O", "Back")]);
                         } else if pv_loading {
-                            draw_loading_indicator(&mut backend, "Decoding image...");
+                            draw_loading_indicator(&mut backend, "Decoding image...", viz_frame);
                         } else {
                             draw_photo_browser(
                                 &mut backend,
@@ -1519,6 +1730,7 @@ fn psp_main() {
                 // Draw WM chrome (frames, titlebars) + clipped content.
                 // Use bitmap font for app content (8px vs 12px system font).
                 backend.force_bitmap_font = true;
+                wm.tick(16, &mut sdi);
                 let _ = wm.draw_with_clips(&mut sdi, &mut backend, |window_id, cx, cy, cw, ch, be| {
                     // Downcast back to PspBackend for direct calls.
                     // Since draw_with_clips passes &mut dyn SdiBackend, we use
@@ -1532,10 +1744,12 @@ fn psp_main() {
                             &fm_entries,
                             fm_selected,
                             fm_scroll,
+                            fm_sort,
                             &fm2_path,
                             &fm2_entries,
                             fm2_selected,
                             fm2_scroll,
+                            fm2_sort,
                             fm_active_panel,
                             cx,
                             cy,
@@ -1673,7 +1887,12 @@ fn open_app_window(wm: &mut WindowManager, sdi: &mut SdiRegistry, app_id: &str,
         let _ = wm.focus_window(app_id, sdi);
         return;
     }
-    let config = WindowConfig {
+    let _ = wm.create_window(&app_window_config(app_id, title), sdi);
+}
+
+/// Config of the floating window an app opens in.
+fn app_window_config(app_id: &str, title: &str) -> WindowConfig {
+    WindowConfig {
         id: app_id.to_string(),
         title: title.to_string(),
         x: None,
@@ -1681,8 +1900,170 @@ fn open_app_window(wm: &mut WindowManager, sdi: &mut SdiRegistry, app_id: &str,
         width: 300,
         height: 180,
         window_type: WindowType::AppWindow,
+    }
+}
+
+/// Window config for reopening `app_id` from a saved layout; `None` for
+/// an unknown app.
+fn restored_window_config(app_id: &str) -> Option<WindowConfig> {
+    let title = match app_id {
+        "terminal" => "Terminal",
+        id => APPS.iter().find(|app| app.id == id)?.title,
+    };
+    Some(app_window_config(app_id, title))
+}
+
+/// The wallpaper image the skin names, or the default one.
+fn wallpaper_path(skin: Option<&Skin>) -> String {
+    let image = skin
+        .and_then(|s| s.theme.wallpaper.as_ref())
+        .and_then(|w| w.image.as_deref());
+    match image {
+        Some(image) => format!("{}/{}", SKIN_DIR, image.trim_start_matches("./")),
+        None => String::from(DEFAULT_WALLPAPER_PATH),
+    }
+}
+
+/// The skin in [`SKIN_DIR`], if there is one: its TOML files and asset
+/// images, read with `psp::io`. Skins that `extends` another are not
+/// resolved here.
+fn load_skin() -> Option<Skin> {
+    let read = |name: &str| psp::io::read_to_vec(&format!("{}/{}", SKIN_DIR, name));
+    let text = |name: &str| {
+        read(name)
+            .map(|bytes| String::from_utf8_lossy(&bytes).into_owned())
+            .unwrap_or_default()
+    };
+    let manifest = text("skin.toml");
+    if manifest.is_empty() {
+        return None;
+    }
+    let mut skin = match Skin::from_toml_full(
+        &manifest,
+        &text("layout.toml"),
+        &text("features.toml"),
+        &text("theme.toml"),
+        &text("strings.toml"),
+    ) {
+        Ok(skin) => skin,
+        Err(e) => {
+            psp::dprintln!("OASIS_OS: cannot load skin: {}", e);
+            return None;
+        },
+    };
+    skin.read_asset_images(|image| {
+        read(image).map_err(|e| std::io::Error::other(format!("{:?}", e)))
+    });
+    Some(skin)
+}
+
+/// Save the Desktop window layout to the Memory Stick.
+fn save_layout(wm: &WindowManager) {
+    if let Err(e) = psp::io::write_bytes(LAYOUT_PATH, wm.serialize_layout().as_bytes()) {
+        psp::dprintln!("OASIS_OS: cannot save window layout: {:?}", e);
+    }
+}
+
+/// Path of the file on the file manager panel row at content-local `ly`,
+/// as laid out by `draw_filemgr_windowed`. Directories are not files.
+fn filemgr_file_at(path: &str, entries: &[FileEntry], scroll: usize, ly: i32) -> Option<String> {
+    let table = filemgr_table(entries, None, scroll, None);
+    let entry = table.list.items[table.row_at(ly - FM_WIN_TABLE_Y)?];
+    if entry.is_dir {
+        return None;
+    }
+    Some(if path.ends_with('/') {
+        format!("{}{}", path, entry.name)
+    } else {
+        format!("{}/{}", path, entry.name)
+    })
+}
+
+/// Columns of a file manager panel: type tag, name and size. Name and size
+/// are sortable.
+fn filemgr_columns<'a>() -> Vec<Column<&'a FileEntry>> {
+    vec![
+        Column::new("", ColumnWidth::Fixed(26), |e: &&FileEntry| {
+            if e.is_dir { "[D]" } else { "[F]" }.to_string()
+        })
+        .with_color(|e| {
+            if e.is_dir {
+                Color::rgb(255, 220, 80)
+            } else {
+                Color::rgb(180, 180, 180)
+            }
+        }),
+        Column::new("Name", ColumnWidth::Weight(1), |e: &&FileEntry| {
+            e.name.clone()
+        })
+        .with_color(|e| {
+            if e.is_dir {
+                Color::rgb(120, 220, 255)
+            } else {
+                Color::WHITE
+            }
+        })
+        .with_sort_key(|e| SortKey::Text(e.name.to_ascii_lowercase())),
+        Column::new("Size", ColumnWidth::Fixed(52), |e: &&FileEntry| {
+            if e.is_dir {
+                String::new()
+            } else {
+                oasis_backend_psp::format_size(e.size)
+            }
+        })
+        .with_align(HAlign::Right)
+        .with_sort_key(|e| SortKey::Number(e.size)),
+    ]
+}
+
+/// A file manager panel as a table scrolled down `scroll` rows. The
+/// entries are already in `sort` order; sorting them again only marks the
+/// header of the sorted column.
+fn filemgr_table(
+    entries: &[FileEntry],
+    selected: Option<usize>,
+    scroll: usize,
+    sort: Option<(usize, bool)>,
+) -> TableView<&FileEntry> {
+    let mut table = TableView::new(filemgr_columns(), entries.iter().collect(), FM_ROW_H as u32);
+    if let Some((column, ascending)) = sort {
+        table.set_sort(column, ascending);
+    }
+    table.list.selected = selected;
+    table.list.scroll_offset = scroll as i32 * FM_ROW_H;
+    table
+}
+
+/// Put a file manager panel's entries in `sort` order, keeping the
+/// selected entry selected and on screen.
+fn filemgr_sort(
+    entries: &mut Vec<FileEntry>,
+    selected: &mut usize,
+    scroll: &mut usize,
+    sort: Option<(usize, bool)>,
+) {
+    let Some((column, ascending)) = sort else {
+        return;
     };
-    let _ = wm.create_window(&config, sdi);
+    let mut table = filemgr_table(entries, Some(*selected), *scroll, None);
+    table.set_sort(column, ascending);
+    *selected = table.list.selected.unwrap_or(0);
+    table
+        .list
+        .scroll_to(*selected, FM_VISIBLE_ROWS as u32 * FM_ROW_H as u32);
+    *scroll = (table.list.scroll_offset / FM_ROW_H) as usize;
+    *entries = table.list.items.into_iter().cloned().collect();
+}
+
+/// Content-local x and width of the left or right windowed file manager
+/// panel in content `cw` wide.
+fn filemgr_panel_span(cw: u32, left: bool) -> (i32, u32) {
+    let half_w = cw / 2;
+    if left {
+        (0, half_w - 1)
+    } else {
+        (half_w as i32 + 1, cw - half_w - 1)
+    }
 }
 
 /// Handle WM events (window closed, desktop click opens apps, etc.).
@@ -1699,6 +2080,9 @@ fn handle_wm_event(
         WmEvent::WindowClosed(id) => {
             term_lines.push(format!("[WM] Window closed: {}", id));
         },
+        WmEvent::WindowMinimized(id) => {
+            term_lines.push(format!("[WM] Window minimized: {}", id));
+        },
         WmEvent::ContentClick(id, lx, ly) => {
             term_lines.push(format!("[WM] Click in {}: ({}, {})", id, lx, ly));
         },
@@ -1709,35 +2093,49 @@ fn handle_wm_event(
                 }
             }
         },
+        WmEvent::TaskbarClick(id) => {
+            // Clicking the active (or a minimized) window's entry toggles
+            // it; any other entry just brings its window to the front.
+            if wm.active_window() == Some(id.as_str())
+                || wm
+                    .get_window(id)
+                    .is_some_and(|w| w.state == WindowState::Minimized)
+            {
+                let _ = wm.toggle_minimize(id, sdi);
+            } else {
+                let _ = wm.focus_window(id, sdi);
+            }
+        },
         _ => {},
     }
 }
 
 /// Draw desktop window tabs in the bottom bar lower row.
 fn draw_desktop_taskbar_row(backend: &mut PspBackend, wm: &WindowManager) {
-    let y = BOTTOM_LOWER_Y + 2;
-
     // L hint.
     backend.draw_text_inner("<L", 4, BOTTOM_LOWER_Y + 4, 8, L_HINT_CLR);
 
     let active_id = wm.active_window();
-    let mut tx = 24i32;
+    let max_chars = (DESKTOP_TASKBAR.slot_w as usize - 4) / CHAR_W as usize;
 
-    for app in APPS {
-        if wm.get_window(app.id).is_some() {
-            let is_active = active_id == Some(app.id);
-            let label_clr = if is_active {
-                Color::WHITE
-            } else {
-                Color::rgb(160, 160, 160)
-            };
-            if is_active {
-                let label_w = (app.title.len() as i32 * 8 + 8) as u32;
-                backend.fill_rect_inner(tx - 2, y, label_w, 12, Color::rgba(60, 90, 160, 140));
-            }
-            backend.draw_text_inner(app.title, tx + 2, y + 1, 8, label_clr);
-            tx += app.title.len() as i32 * 8 + 12;
+    for (i, window) in wm.taskbar_windows().into_iter().enumerate() {
+        let (sx, sy, sw, sh) = DESKTOP_TASKBAR.slot_rect(i);
+        if sx + sw as i32 > SCREEN_WIDTH as i32 - R_HINT_W {
+            break;
         }
+        let is_active = active_id == Some(window.id.as_str());
+        let label_clr = if is_active {
+            Color::WHITE
+        } else if window.state == WindowState::Minimized {
+            Color::rgb(110, 110, 110)
+        } else {
+            Color::rgb(160, 160, 160)
+        };
+        if is_active {
+            backend.fill_rect_inner(sx, sy, sw, sh, Color::rgba(60, 90, 160, 140));
+        }
+        let label: String = window.title.chars().take(max_chars).collect();
+        backend.draw_text_inner(&label, sx + 2, sy + 1, 8, label_clr);
     }
 
     // R hint.
@@ -1796,10 +2194,12 @@ fn draw_filemgr_windowed(
     entries_l: &[FileEntry],
     selected_l: usize,
     scroll_l: usize,
+    sort_l: Option<(usize, bool)>,
     path_r: &str,
     entries_r: &[FileEntry],
     selected_r: usize,
     scroll_r: usize,
+    sort_r: Option<(usize, bool)>,
     active_panel: usize,
     cx: i32,
     cy: i32,
@@ -1830,40 +2230,23 @@ fn draw_filemgr_windowed(
     be.fill_rect(div_x, cy + 12, 1, ch - 12, Color::rgba(100, 200, 255, 80))?;
 
     // Draw each panel.
-    let panels: [(&[FileEntry], usize, usize, i32, u32, bool); 2] = [
-        (entries_l, selected_l, scroll_l, cx, half_w - 1, active_panel == 0),
-        (entries_r, selected_r, scroll_r, div_x + 1, cw - half_w, active_panel == 1),
+    let panels = [
+        (entries_l, selected_l, scroll_l, sort_l, true),
+        (entries_r, selected_r, scroll_r, sort_r, false),
     ];
-    let max_rows = ((ch as i32 - 14) / FM_ROW_H) as usize;
-
-    for &(entries, selected, scroll, px, _pw, is_active) in &panels {
-        let end = (scroll + max_rows).min(entries.len());
-        for i in scroll..end {
-            let entry = &entries[i];
-            let row = (i - scroll) as i32;
-            let y = cy + 14 + row * FM_ROW_H;
-            if i == selected && is_active {
-                be.fill_rect(
-                    px,
-                    y - 1,
-                    half_w,
-                    FM_ROW_H as u32,
-                    Color::rgba(80, 120, 200, 100),
-                )?;
-            }
-            let (prefix, clr) = if entry.is_dir {
-                ("[D]", Color::rgb(255, 220, 80))
-            } else {
-                ("[F]", Color::rgb(180, 180, 180))
-            };
-            be.draw_text(prefix, px + 2, y, 8, clr)?;
-            let name_clr = if entry.is_dir {
-                Color::rgb(120, 220, 255)
-            } else {
-                Color::WHITE
-            };
-            be.draw_text(&entry.name, px + 28, y, 8, name_clr)?;
-        }
+    let theme = Theme::dark();
+    let mut ctx = DrawContext::new(be, &theme);
+    for (entries, selected, scroll, sort, left) in panels {
+        let (px, pw) = filemgr_panel_span(cw, left);
+        let is_active = (active_panel == 0) == left;
+        let table = filemgr_table(entries, is_active.then_some(selected), scroll, sort);
+        table.draw_at(
+            &mut ctx,
+            cx + px,
+            cy + FM_WIN_TABLE_Y,
+            pw,
+            ch.saturating_sub(FM_WIN_TABLE_Y as u32),
+        )?;
     }
     Ok(())
 }
@@ -2168,11 +2551,17 @@ fn draw_sysmon_windowed(
 // Loading indicator
 // ---------------------------------------------------------------------------
 
-fn draw_loading_indicator(backend: &mut PspBackend, msg: &str) {
+fn draw_loading_indicator(backend: &mut PspBackend, msg: &str, frame: u32) {
     let bg = Color::rgba(0, 0, 0, 200);
     backend.fill_rect_inner(0, CONTENT_TOP as i32, SCREEN_WIDTH, CONTENT_H, bg);
     let cx = SCREEN_WIDTH as i32 / 2;
     let cy = CONTENT_TOP as i32 + CONTENT_H as i32 / 2;
+    let theme = Theme::dark();
+    let mut ctx = DrawContext::new(backend, &theme);
+    // The PSP backend's drawing calls cannot fail.
+    let _ = Spinner::new()
+        .with_frame(frame)
+        .draw(&mut ctx, cx - 8, cy - 24, 16, 16);
     let text_x = cx - (msg.len() as i32 * 8) / 2;
     backend.draw_text_inner(msg, text_x, cy, 8, Color::rgb(200, 200, 200));
 }
@@ -2764,10 +3153,12 @@ fn draw_file_manager_dual(
     entries_l: &[FileEntry],
     selected_l: usize,
     scroll_l: usize,
+    sort_l: Option<(usize, bool)>,
     path_r: &str,
     entries_r: &[FileEntry],
     selected_r: usize,
     scroll_r: usize,
+    sort_r: Option<(usize, bool)>,
     active_panel: usize,
 ) {
     let bg = Color::rgba(0, 0, 0, 200);
@@ -2807,71 +3198,33 @@ fn draw_file_manager_dual(
         Color::rgb(100, 200, 255),
     );
 
-    // Draw each panel.
-    let panels: [(&[FileEntry], usize, usize, i32, u32, bool); 2] = [
-        (entries_l, selected_l, scroll_l, 0, half_w - 1, active_panel == 0),
-        (entries_r, selected_r, scroll_r, div_x + 1, half_w, active_panel == 1),
+    // Draw each panel: a header row, then FM_VISIBLE_ROWS of entries.
+    let panels = [
+        (entries_l, selected_l, scroll_l, sort_l, 0, half_w - 1),
+        (entries_r, selected_r, scroll_r, sort_r, div_x + 1, half_w),
     ];
-    // Half the visible rows since panels are narrower but same height.
-    let panel_rows = FM_VISIBLE_ROWS;
+    let table_y = CONTENT_TOP as i32 + 13;
+    let rows_y = table_y + FM_ROW_H;
+    let table_h = (FM_VISIBLE_ROWS as u32 + 1) * FM_ROW_H as u32;
+    let theme = Theme::dark();
+
+    for (entries, selected, scroll, sort, px, pw) in panels {
+        let is_active = (active_panel == 0) == (px == 0);
+        let table = filemgr_table(entries, is_active.then_some(selected), scroll, sort);
+        let mut ctx = DrawContext::new(backend, &theme);
+        // The PSP backend's drawing calls cannot fail.
+        let _ = table.draw_at(&mut ctx, px, table_y, pw, table_h);
 
-    for &(entries, selected, scroll, px, pw, is_active) in &panels {
         if entries.is_empty() {
-            backend.draw_text_inner(
-                "(empty)",
-                px + 4,
-                FM_START_Y,
-                8,
-                Color::rgb(140, 140, 140),
-            );
+            backend.draw_text_inner("(empty)", px + 4, rows_y + 1, 8, Color::rgb(140, 140, 140));
             continue;
         }
 
-        let end = (scroll + panel_rows).min(entries.len());
-        for i in scroll..end {
-            let entry = &entries[i];
-            let row = (i - scroll) as i32;
-            let y = FM_START_Y + row * FM_ROW_H;
-
-            if i == selected && is_active {
-                backend.fill_rect_inner(
-                    px,
-                    y - 1,
-                    pw,
-                    FM_ROW_H as u32,
-                    Color::rgba(80, 120, 200, 100),
-                );
-            }
-
-            let (prefix, prefix_clr) = if entry.is_dir {
-                ("[D]", Color::rgb(255, 220, 80))
-            } else {
-                ("[F]", Color::rgb(180, 180, 180))
-            };
-            backend.draw_text_inner(prefix, px + 2, y, 8, prefix_clr);
-
-            let name_color = if entry.is_dir {
-                Color::rgb(120, 220, 255)
-            } else {
-                Color::WHITE
-            };
-            // Max chars for half-width panel (~28 chars at 8px each).
-            let max_name_chars = ((pw as i32 - 32) / CHAR_W).max(4) as usize;
-            let display_name = if entry.name.len() > max_name_chars {
-                let truncated: String =
-                    entry.name.chars().take(max_name_chars - 2).collect();
-                format!("{}..", truncated)
-            } else {
-                entry.name.clone()
-            };
-            backend.draw_text_inner(&display_name, px + 28, y, 8, name_color);
-        }
-
         // Scroll indicator per panel.
-        if entries.len() > panel_rows {
+        if entries.len() > FM_VISIBLE_ROWS {
             let ratio = selected as f32 / (entries.len() - 1).max(1) as f32;
-            let track_h = CONTENT_H as i32 - 16;
-            let dot_y = FM_START_Y + (ratio * track_h as f32) as i32;
+            let track_h = table_h as i32 - FM_ROW_H - 8;
+            let dot_y = rows_y + (ratio * track_h as f32) as i32;
             let dot_x = px + pw as i32 - 4;
             backend.fill_rect_inner(dot_x, dot_y, 3, 8, Color::rgba(255, 255, 255, 120));
         }
@@ -389,6 +389,7 @@ impl NetworkService for PspNetworkService {
 
         Ok(HttpResponse {
             status_code: resp.status_code,
+            headers: Vec::new(),
             body: resp.body,
         })
     }
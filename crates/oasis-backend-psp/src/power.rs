@@ -11,6 +11,9 @@ use psp::sys;
 /// Flag indicating a resume-from-sleep event occurred.
 static POWER_RESUMED: AtomicBool = AtomicBool::new(false);
 
+/// Flag indicating the PSP is about to suspend.
+static POWER_SUSPENDING: AtomicBool = AtomicBool::new(false);
+
 /// Set CPU and bus clock frequencies.
 ///
 /// Common presets:
@@ -40,14 +43,19 @@ pub fn check_power_resumed() -> bool {
     POWER_RESUMED.swap(false, Ordering::AcqRel)
 }
 
+/// Check and clear the "about to suspend" flag.
+pub fn check_power_suspending() -> bool {
+    POWER_SUSPENDING.swap(false, Ordering::AcqRel)
+}
+
 /// Prevent the PSP from auto-suspending due to idle timeout.
 /// Call once per frame during active use.
 pub fn power_tick() {
     psp::power::prevent_sleep();
 }
 
-/// SAFETY: Called by the PSP firmware on power state changes. POWER_RESUMED
-/// is an AtomicBool, so cross-thread access is safe without unsafe.
+/// SAFETY: Called by the PSP firmware on power state changes. The flags
+/// are AtomicBools, so cross-thread access is safe without unsafe.
 unsafe extern "C" fn power_callback(_arg1: i32, power_info: i32, _arg: *mut c_void) -> i32 {
     let info = sys::PowerInfo::from_bits_truncate(power_info as u32);
     if info.contains(sys::PowerInfo::RESUME_COMPLETE) {
@@ -56,6 +64,7 @@ unsafe extern "C" fn power_callback(_arg1: i32, power_info: i32, _arg: *mut c_vo
     }
     if info.contains(sys::PowerInfo::SUSPENDING) {
         psp::dprintln!("OASIS_OS: Entering suspend");
+        POWER_SUSPENDING.store(true, Ordering::Release);
     }
     0
 }
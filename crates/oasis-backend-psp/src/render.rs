@@ -1,4 +1,5 @@
-//! GU rendering primitives: vertices, clear, fill, text, blit.
+//! GU rendering primitives: vertices, clear, fill, text, blit, and the
+//! extended shapes (rounded rects, circles, lines, triangles, gradients).
 
 use std::ffi::c_void;
 use std::mem::size_of;
@@ -11,7 +12,9 @@ use psp::sys::{
 };
 
 use oasis_core::backend::Color;
+use oasis_core::color::lerp_color;
 
+use crate::shapes::{self, Span};
 use crate::{ColorExt, PspBackend};
 
 // ---------------------------------------------------------------------------
@@ -334,6 +337,80 @@ impl PspBackend {
         }
     }
 
+    /// Blit a loaded texture rotated by `angle_degrees` clockwise about
+    /// `center` (relative to the destination rect; `None` = its middle).
+    ///
+    /// Sprites are always axis-aligned, so the rotated rect is drawn as
+    /// a four-vertex triangle strip instead.
+    pub fn blit_rotated_inner(
+        &mut self,
+        tex: oasis_core::backend::TextureId,
+        x: i32,
+        y: i32,
+        w: u32,
+        h: u32,
+        angle_degrees: f32,
+        center: Option<(i32, i32)>,
+    ) {
+        let idx = tex.0 as usize;
+        let Some(Some(texture)) = self.textures.get(idx) else {
+            return;
+        };
+        let tex_w = texture.width as i16;
+        let tex_h = texture.height as i16;
+        let buf_w = texture.buf_w;
+        let buf_h = texture.buf_h;
+        let data_ptr = texture.data;
+
+        let corners = rotated_quad(x, y, w, h, angle_degrees, center);
+        let uvs = [(0, 0), (tex_w, 0), (0, tex_h), (tex_w, tex_h)];
+
+        // SAFETY: Same texture binding as blit_inner; the four vertices
+        // are written into GE display-list memory before the draw.
+        unsafe {
+            let uncached_ptr =
+                psp::cache::UncachedPtr::from_cached_addr(data_ptr).as_ptr() as *const c_void;
+            sys::sceGuTexMode(TexturePixelFormat::Psm8888, 0, 0, 0);
+            sys::sceGuTexImage(
+                MipmapLevel::None,
+                buf_w as i32,
+                buf_h as i32,
+                buf_w as i32,
+                uncached_ptr,
+            );
+            sys::sceGuTexFunc(TextureEffect::Modulate, TextureColorComponent::Rgba);
+
+            let verts = sys::sceGuGetMemory((4 * size_of::<TexturedColorVertex>()) as i32)
+                as *mut TexturedColorVertex;
+            if verts.is_null() {
+                return;
+            }
+
+            for (i, (&(vx, vy), &(u, v))) in corners.iter().zip(uvs.iter()).enumerate() {
+                ptr::write(
+                    verts.add(i),
+                    TexturedColorVertex {
+                        u,
+                        v,
+                        color: 0xFFFF_FFFF,
+                        x: vx,
+                        y: vy,
+                        z: 0,
+                        _pad: 0,
+                    },
+                );
+            }
+
+            sys::sceGuDrawArray(
+                GuPrimitive::TriangleStrip,
+                TEXTURED_COLOR_VTYPE,
+                4,
+                ptr::null(),
+                verts as *const c_void,
+            );
+        }
+    }
+
     /// Blit a texture scaled to the given size with bilinear filtering.
     ///
     /// Used for the wallpaper: a small texture (64x64) scaled to fullscreen.
@@ -418,3 +495,277 @@ impl PspBackend {
         }
     }
 }
+
+// ---------------------------------------------------------------------------
+// Extended shapes
+// ---------------------------------------------------------------------------
+
+impl PspBackend {
+    /// Draw `points` (x, y, ABGR color) as one `prim` primitive batch.
+    ///
+    /// Every vertex samples the font atlas's white texel, as in
+    /// `fill_rect_inner`, so the GE colors the shape from the vertex
+    /// colors alone. Those are interpolated across triangles, which makes
+    /// gradients a matter of giving the corners different colors. Like
+    /// every GE draw, the result is clipped to the active scissor.
+    fn draw_solid(&mut self, prim: GuPrimitive, points: &[(i16, i16, u32)]) {
+        if points.is_empty() {
+            return;
+        }
+        // SAFETY: sceGuGetMemory returns display-list memory valid until
+        // sceGuFinish, sized for exactly `points.len()` vertices, all of
+        // which are written before the draw. font_atlas_ptr is checked
+        // non-null during init().
+        unsafe {
+            let verts =
+                sys::sceGuGetMemory((points.len() * size_of::<TexturedColorVertex>()) as i32)
+                    as *mut TexturedColorVertex;
+            if verts.is_null() {
+                return;
+            }
+
+            let uncached_atlas = psp::cache::UncachedPtr::from_cached_addr(self.font_atlas_ptr)
+                .as_ptr() as *const c_void;
+            sys::sceGuTexMode(TexturePixelFormat::Psm8888, 0, 0, 0);
+            sys::sceGuTexImage(
+                MipmapLevel::None,
+                FONT_ATLAS_W as i32,
+                FONT_ATLAS_H as i32,
+                FONT_ATLAS_W as i32,
+                uncached_atlas,
+            );
+
+            let white_u = (FONT_ATLAS_W - 1) as i16;
+            let white_v = (FONT_ATLAS_H - 1) as i16;
+            for (i, &(x, y, color)) in points.iter().enumerate() {
+                ptr::write(
+                    verts.add(i),
+                    TexturedColorVertex {
+                        u: white_u,
+                        v: white_v,
+                        color,
+                        x,
+                        y,
+                        z: 0,
+                        _pad: 0,
+                    },
+                );
+            }
+
+            sys::sceGuDrawArray(
+                prim,
+                TEXTURED_COLOR_VTYPE,
+                points.len() as i32,
+                ptr::null(),
+                verts as *const c_void,
+            );
+        }
+    }
+
+    /// Fill `spans` in one color as a single batch of sprites.
+    fn fill_spans(&mut self, spans: &[Span], color: Color) {
+        let abgr = color.to_abgr();
+        let mut points = Vec::with_capacity(spans.len() * 2);
+        for s in spans {
+            points.push((s.x as i16, s.y as i16, abgr));
+            points.push(((s.x + s.w as i32) as i16, (s.y + s.h as i32) as i16, abgr));
+        }
+        self.draw_solid(GuPrimitive::Sprites, &points);
+    }
+
+    /// Fill `spans` with the vertical gradient of the `h`-tall shape they
+    /// belong to, starting at row `y`.
+    ///
+    /// Sprites take a single color, so each span becomes two triangles
+    /// whose top and bottom edges carry the gradient's color at those
+    /// rows.
+    fn fill_spans_gradient_v(
+        &mut self,
+        spans: &[Span],
+        y: i32,
+        h: u32,
+        top_color: Color,
+        bottom_color: Color,
+    ) {
+        let at = |row: i32| {
+            lerp_color(top_color, bottom_color, shapes::gradient_t(row - y, h)).to_abgr()
+        };
+        let mut points = Vec::with_capacity(spans.len() * 6);
+        for s in spans {
+            let (x0, x1) = (s.x as i16, (s.x + s.w as i32) as i16);
+            let (y0, y1) = (s.y, s.y + s.h as i32);
+            let (c0, c1) = (at(y0), at(y1));
+            let (y0, y1) = (y0 as i16, y1 as i16);
+            points.extend_from_slice(&[
+                (x0, y0, c0),
+                (x1, y0, c0),
+                (x0, y1, c1),
+                (x1, y0, c0),
+                (x1, y1, c1),
+                (x0, y1, c1),
+            ]);
+        }
+        self.draw_solid(GuPrimitive::Triangles, &points);
+    }
+
+    /// Draw a filled rectangle with rounded corners.
+    pub fn fill_rounded_rect_inner(
+        &mut self,
+        x: i32,
+        y: i32,
+        w: u32,
+        h: u32,
+        radius: u16,
+        color: Color,
+    ) {
+        if radius == 0 {
+            self.fill_rect_inner(x, y, w, h, color);
+            return;
+        }
+        self.fill_spans(&shapes::rounded_rect_spans(x, y, w, h, radius), color);
+    }
+
+    /// Draw the outline of a rounded rectangle, `stroke_width` thick
+    /// inward from its bounds.
+    pub fn stroke_rounded_rect_inner(
+        &mut self,
+        x: i32,
+        y: i32,
+        w: u32,
+        h: u32,
+        radius: u16,
+        stroke_width: u16,
+        color: Color,
+    ) {
+        let spans = shapes::rounded_rect_outline_spans(x, y, w, h, radius, stroke_width);
+        self.fill_spans(&spans, color);
+    }
+
+    /// Draw a filled circle centered on pixel (`cx`, `cy`).
+    pub fn fill_circle_inner(&mut self, cx: i32, cy: i32, radius: u16, color: Color) {
+        self.fill_spans(&shapes::circle_spans(cx, cy, radius), color);
+    }
+
+    /// Draw a circle outline, `stroke_width` thick inward from `radius`.
+    pub fn stroke_circle_inner(
+        &mut self,
+        cx: i32,
+        cy: i32,
+        radius: u16,
+        stroke_width: u16,
+        color: Color,
+    ) {
+        self.fill_spans(&shapes::ring_spans(cx, cy, radius, stroke_width), color);
+    }
+
+    /// Draw a line. One-pixel lines use the GE's `Lines` primitive;
+    /// thicker ones are drawn as a quad.
+    pub fn draw_line_inner(
+        &mut self,
+        x1: i32,
+        y1: i32,
+        x2: i32,
+        y2: i32,
+        width: u16,
+        color: Color,
+    ) {
+        let abgr = color.to_abgr();
+        if width <= 1 {
+            self.draw_solid(
+                GuPrimitive::Lines,
+                &[(x1 as i16, y1 as i16, abgr), (x2 as i16, y2 as i16, abgr)],
+            );
+            return;
+        }
+        let quad = shapes::line_quad(x1, y1, x2, y2, width).map(|(x, y)| (x, y, abgr));
+        self.draw_solid(GuPrimitive::TriangleStrip, &quad);
+    }
+
+    /// Draw a filled triangle.
+    pub fn fill_triangle_inner(
+        &mut self,
+        x1: i32,
+        y1: i32,
+        x2: i32,
+        y2: i32,
+        x3: i32,
+        y3: i32,
+        color: Color,
+    ) {
+        let abgr = color.to_abgr();
+        self.draw_solid(
+            GuPrimitive::Triangles,
+            &[
+                (x1 as i16, y1 as i16, abgr),
+                (x2 as i16, y2 as i16, abgr),
+                (x3 as i16, y3 as i16, abgr),
+            ],
+        );
+    }
+
+    /// Draw a rectangle shaded between four corner colors. The GE
+    /// interpolates them across the rect, so vertical and horizontal
+    /// gradients are this with matching pairs of corners.
+    pub fn fill_rect_gradient_inner(
+        &mut self,
+        x: i32,
+        y: i32,
+        w: u32,
+        h: u32,
+        [top_left, top_right, bottom_left, bottom_right]: [Color; 4],
+    ) {
+        let (x0, y0) = (x as i16, y as i16);
+        let (x1, y1) = ((x + w as i32) as i16, (y + h as i32) as i16);
+        self.draw_solid(
+            GuPrimitive::TriangleStrip,
+            &[
+                (x0, y0, top_left.to_abgr()),
+                (x1, y0, top_right.to_abgr()),
+                (x0, y1, bottom_left.to_abgr()),
+                (x1, y1, bottom_right.to_abgr()),
+            ],
+        );
+    }
+
+    /// Draw a rounded rectangle with a vertical gradient.
+    pub fn fill_rounded_rect_gradient_v_inner(
+        &mut self,
+        x: i32,
+        y: i32,
+        w: u32,
+        h: u32,
+        radius: u16,
+        top_color: Color,
+        bottom_color: Color,
+    ) {
+        let spans = shapes::rounded_rect_spans(x, y, w, h, radius);
+        self.fill_spans_gradient_v(&spans, y, h, top_color, bottom_color);
+    }
+}
+
+/// Screen positions of the corners of the `w`x`h` rect at (`x`, `y`)
+/// rotated clockwise by `angle_degrees` about `center` (relative to the
+/// rect, defaulting to its middle), in triangle-strip order: top-left,
+/// top-right, bottom-left, bottom-right.
+fn rotated_quad(
+    x: i32,
+    y: i32,
+    w: u32,
+    h: u32,
+    angle_degrees: f32,
+    center: Option<(i32, i32)>,
+) -> [(i16, i16); 4] {
+    let (cx, cy) = center.unwrap_or((w as i32 / 2, h as i32 / 2));
+    let (px, py) = ((x + cx) as f32, (y + cy) as f32);
+    let rad = angle_degrees.to_radians();
+    let (sin, cos) = (libm::sinf(rad), libm::cosf(rad));
+    let corner = |dx: i32, dy: i32| {
+        let (dx, dy) = ((dx - cx) as f32, (dy - cy) as f32);
+        // Screen y points down, so this turns clockwise.
+        let rx = px + dx * cos - dy * sin;
+        let ry = py + dx * sin + dy * cos;
+        (libm::roundf(rx) as i16, libm::roundf(ry) as i16)
+    };
+    let (w, h) = (w as i32, h as i32);
+    [corner(0, 0), corner(w, 0), corner(0, h), corner(w, h)]
+}
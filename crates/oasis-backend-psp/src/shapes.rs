@@ -0,0 +1,332 @@
+//! Span and vertex generation for the extended shape primitives.
+//!
+//! Pure integer/float math with no GU calls, so it can be checked off
+//! device. `render.rs` turns the results into display-list vertices:
+//! spans become batched `Sprites`, quads and lines become triangles and
+//! `Lines`. Spans never overlap, so translucent shapes blend each pixel
+//! exactly once.
+
+/// An axis-aligned run of pixels, drawn as one sprite.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Span {
+    pub x: i32,
+    pub y: i32,
+    pub w: u32,
+    pub h: u32,
+}
+
+/// Integer square root (floor).
+fn isqrt(n: i64) -> i64 {
+    if n <= 0 {
+        return 0;
+    }
+    let mut r = libm::sqrt(n as f64) as i64;
+    while r * r > n {
+        r -= 1;
+    }
+    while (r + 1) * (r + 1) <= n {
+        r += 1;
+    }
+    r
+}
+
+/// Push a `w`-wide, one-row span, merging it into the span directly
+/// above when that has the same columns. Rows hold at most two spans, so
+/// only the last two need checking.
+fn push_row(spans: &mut Vec<Span>, x: i32, y: i32, w: u32) {
+    if w == 0 {
+        return;
+    }
+    let n = spans.len();
+    for s in spans[n.saturating_sub(2)..].iter_mut() {
+        if s.x == x && s.w == w && s.y + s.h as i32 == y {
+            s.h += 1;
+            return;
+        }
+    }
+    spans.push(Span { x, y, w, h: 1 });
+}
+
+/// Half-width of row `dy` of a disc of radius `r`, or `None` if the row
+/// misses it. A pixel is inside when its center is within `r + 0.5` of
+/// the disc's center pixel, so the disc is `2r + 1` pixels across.
+fn disc_half_width(r: i32, dy: i32) -> Option<i32> {
+    let (r, dy) = (i64::from(r), i64::from(dy));
+    let n = r * r + r - dy * dy;
+    (n >= 0).then(|| isqrt(n) as i32)
+}
+
+/// Spans filling the circle of radius `r` centered on pixel (`cx`, `cy`).
+pub fn circle_spans(cx: i32, cy: i32, r: u16) -> Vec<Span> {
+    let r = i32::from(r);
+    let mut spans = Vec::new();
+    for dy in -r..=r {
+        if let Some(half) = disc_half_width(r, dy) {
+            push_row(&mut spans, cx - half, cy + dy, (2 * half + 1) as u32);
+        }
+    }
+    spans
+}
+
+/// Spans covering a `stroke_width`-thick ring whose outer edge is the
+/// circle of radius `r` centered on pixel (`cx`, `cy`).
+pub fn ring_spans(cx: i32, cy: i32, r: u16, stroke_width: u16) -> Vec<Span> {
+    let r = i32::from(r);
+    let inner = r - i32::from(stroke_width.max(1));
+    if inner < 0 {
+        return circle_spans(cx, cy, r as u16);
+    }
+    let mut spans = Vec::new();
+    for dy in -r..=r {
+        let Some(outer) = disc_half_width(r, dy) else {
+            continue;
+        };
+        match disc_half_width(inner, dy) {
+            Some(hole) => {
+                let w = (outer - hole) as u32;
+                push_row(&mut spans, cx - outer, cy + dy, w);
+                push_row(&mut spans, cx + hole + 1, cy + dy, w);
+            },
+            None => push_row(&mut spans, cx - outer, cy + dy, (2 * outer + 1) as u32),
+        }
+    }
+    spans
+}
+
+/// Columns cut from each side of row `dy` of a `w`x`h` rect whose corners
+/// are rounded with radius `r` (already clamped to half the size).
+///
+/// A corner pixel is kept when its center lies inside the corner's
+/// quarter circle. In doubled coordinates that circle has radius `2r`
+/// and the row center sits `2r - 2d - 1` from it, where `d` is the
+/// distance from the nearest horizontal edge.
+fn corner_inset(h: u32, r: i32, dy: i32) -> i32 {
+    let d = dy.min(h as i32 - 1 - dy);
+    if d >= r {
+        return 0;
+    }
+    let a = i64::from(2 * r - 2 * d - 1);
+    let reach = isqrt(4 * i64::from(r) * i64::from(r) - a * a) as i32;
+    (2 * r - reach) / 2
+}
+
+/// Clamp a corner radius to half the rect's smaller side.
+fn clamp_radius(w: u32, h: u32, radius: u16) -> i32 {
+    (radius as u32).min(w / 2).min(h / 2) as i32
+}
+
+/// Spans filling a `w`x`h` rect at (`x`, `y`) with rounded corners.
+pub fn rounded_rect_spans(x: i32, y: i32, w: u32, h: u32, radius: u16) -> Vec<Span> {
+    if w == 0 || h == 0 {
+        return Vec::new();
+    }
+    let r = clamp_radius(w, h, radius);
+    let mut spans = Vec::new();
+    for dy in 0..h as i32 {
+        let inset = corner_inset(h, r, dy);
+        push_row(&mut spans, x + inset, y + dy, w - 2 * inset as u32);
+    }
+    spans
+}
+
+/// Spans covering the `stroke_width`-thick outline of a rounded rect,
+/// drawn inward from its bounds. The inner edge is rounded with the
+/// radius shrunk by the stroke width, so the outline keeps its thickness
+/// around the corners.
+pub fn rounded_rect_outline_spans(
+    x: i32,
+    y: i32,
+    w: u32,
+    h: u32,
+    radius: u16,
+    stroke_width: u16,
+) -> Vec<Span> {
+    let sw = u32::from(stroke_width.max(1));
+    if w <= 2 * sw || h <= 2 * sw {
+        return rounded_rect_spans(x, y, w, h, radius);
+    }
+    let r = clamp_radius(w, h, radius);
+    let (iw, ih) = (w - 2 * sw, h - 2 * sw);
+    let ir = clamp_radius(iw, ih, (r - sw as i32).max(0) as u16);
+    let mut spans = Vec::new();
+    for dy in 0..h as i32 {
+        let outer = corner_inset(h, r, dy);
+        let idy = dy - sw as i32;
+        if idy < 0 || idy >= ih as i32 {
+            push_row(&mut spans, x + outer, y + dy, w - 2 * outer as u32);
+            continue;
+        }
+        let hole = sw as i32 + corner_inset(ih, ir, idy);
+        let side = (hole - outer) as u32;
+        push_row(&mut spans, x + outer, y + dy, side);
+        push_row(&mut spans, x + w as i32 - hole, y + dy, side);
+    }
+    spans
+}
+
+/// Corners of the quad covering a `width`-thick line from (`x1`, `y1`)
+/// to (`x2`, `y2`), in triangle-strip order. The line is offset across
+/// its direction by `-width / 2 .. width - width / 2`, like the SDL
+/// backend's parallel strokes.
+pub fn line_quad(x1: i32, y1: i32, x2: i32, y2: i32, width: u16) -> [(i16, i16); 4] {
+    let (dx, dy) = ((x2 - x1) as f32, (y2 - y1) as f32);
+    let len = libm::sqrtf(dx * dx + dy * dy).max(1.0);
+    let (nx, ny) = (-dy / len, dx / len);
+    let near = -f32::from(width / 2);
+    let far = f32::from(width) + near;
+    let point = |x: i32, y: i32, t: f32| {
+        (
+            libm::roundf(x as f32 + nx * t) as i16,
+            libm::roundf(y as f32 + ny * t) as i16,
+        )
+    };
+    [
+        point(x1, y1, near),
+        point(x1, y1, far),
+        point(x2, y2, near),
+        point(x2, y2, far),
+    ]
+}
+
+/// Position of row offset `dy` within a rect of height `h`, as the
+/// interpolation factor for a top-to-bottom gradient.
+pub fn gradient_t(dy: i32, h: u32) -> f32 {
+    if h == 0 { 0.0 } else { dy as f32 / h as f32 }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Every covered pixel, failing on overlap.
+    fn pixels(spans: &[Span]) -> std::collections::BTreeSet<(i32, i32)> {
+        let mut set = std::collections::BTreeSet::new();
+        for s in spans {
+            for y in s.y..s.y + s.h as i32 {
+                for x in s.x..s.x + s.w as i32 {
+                    assert!(set.insert((x, y)), "({x}, {y}) is covered twice");
+                }
+            }
+        }
+        set
+    }
+
+    #[test]
+    fn isqrt_floors() {
+        assert_eq!(isqrt(0), 0);
+        assert_eq!(isqrt(15), 3);
+        assert_eq!(isqrt(16), 4);
+        assert_eq!(isqrt(1 << 40), 1 << 20);
+    }
+
+    #[test]
+    fn circle_is_symmetric_and_2r_plus_1_across() {
+        let px = pixels(&circle_spans(50, 40, 10));
+        assert!(px.contains(&(40, 40)) && px.contains(&(60, 40)));
+        assert!(!px.contains(&(39, 40)) && !px.contains(&(61, 40)));
+        assert!(px.contains(&(50, 30)) && px.contains(&(50, 50)));
+        assert!(!px.contains(&(50, 29)) && !px.contains(&(50, 51)));
+        for &(x, y) in &px {
+            assert!(px.contains(&(100 - x, y)) && px.contains(&(x, 80 - y)));
+        }
+        // The bounding box corners are outside.
+        assert!(!px.contains(&(40, 30)));
+    }
+
+    #[test]
+    fn zero_radius_circle_is_one_pixel() {
+        assert_eq!(
+            circle_spans(3, 4, 0),
+            vec![Span {
+                x: 3,
+                y: 4,
+                w: 1,
+                h: 1
+            }]
+        );
+    }
+
+    #[test]
+    fn ring_is_the_circle_minus_the_inner_disc() {
+        let ring = pixels(&ring_spans(20, 20, 8, 2));
+        let outer = pixels(&circle_spans(20, 20, 8));
+        let inner = pixels(&circle_spans(20, 20, 6));
+        let expected: std::collections::BTreeSet<_> = outer.difference(&inner).copied().collect();
+        assert_eq!(ring, expected);
+        // A stroke as wide as the radius fills the circle.
+        assert_eq!(
+            pixels(&ring_spans(20, 20, 4, 9)),
+            pixels(&circle_spans(20, 20, 4))
+        );
+    }
+
+    #[test]
+    fn rounded_rect_cuts_only_the_corners() {
+        let spans = rounded_rect_spans(10, 20, 40, 30, 4);
+        let px = pixels(&spans);
+        assert!(!px.contains(&(10, 20)) && !px.contains(&(49, 49)));
+        assert!(px.contains(&(14, 20)) && px.contains(&(10, 24)));
+        // The straight middle rows are a single sprite.
+        assert!(spans.contains(&Span {
+            x: 10,
+            y: 22,
+            w: 40,
+            h: 26
+        }));
+        assert_eq!(corner_inset(30, 4, 0), 2);
+        assert_eq!(corner_inset(30, 4, 29), 2);
+        assert_eq!(corner_inset(30, 4, 3), 0);
+    }
+
+    #[test]
+    fn rounded_rect_radius_is_clamped_and_zero_is_square() {
+        assert_eq!(
+            rounded_rect_spans(0, 0, 8, 6, 0),
+            vec![Span {
+                x: 0,
+                y: 0,
+                w: 8,
+                h: 6
+            }]
+        );
+        // Radius 100 on a 10x10 rect rounds to a circle-like blob.
+        let px = pixels(&rounded_rect_spans(0, 0, 10, 10, 100));
+        assert!(px.contains(&(5, 0)) && !px.contains(&(0, 0)));
+        assert!(rounded_rect_spans(0, 0, 0, 10, 4).is_empty());
+    }
+
+    #[test]
+    fn rounded_outline_keeps_the_stroke_inside_the_fill() {
+        let fill = pixels(&rounded_rect_spans(0, 0, 40, 30, 6));
+        let outline = pixels(&rounded_rect_outline_spans(0, 0, 40, 30, 6, 2));
+        assert!(outline.is_subset(&fill));
+        assert!(outline.contains(&(20, 0)) && outline.contains(&(20, 1)));
+        assert!(!outline.contains(&(20, 2)) && !outline.contains(&(20, 15)));
+        assert!(outline.contains(&(0, 15)) && outline.contains(&(39, 15)));
+        // Too thick to leave a hole: filled.
+        assert_eq!(
+            rounded_rect_outline_spans(0, 0, 6, 6, 2, 3),
+            rounded_rect_spans(0, 0, 6, 6, 2)
+        );
+    }
+
+    #[test]
+    fn line_quad_offsets_across_the_direction() {
+        assert_eq!(
+            line_quad(10, 50, 100, 50, 2),
+            [(10, 49), (10, 51), (100, 49), (100, 51)]
+        );
+        assert_eq!(
+            line_quad(5, 0, 5, 10, 3),
+            [(6, 0), (3, 0), (6, 10), (3, 10)]
+        );
+    }
+
+    #[test]
+    fn gradient_t_spans_the_height() {
+        assert_eq!(gradient_t(0, 10), 0.0);
+        assert_eq!(gradient_t(5, 10), 0.5);
+        assert_eq!(gradient_t(10, 10), 1.0);
+        assert_eq!(gradient_t(3, 0), 0.0);
+    }
+}
@@ -9,6 +9,8 @@ use std::sync::Arc;
 use psp::sync::{SpinMutex, SpscQueue};
 use psp::thread::ThreadBuilder;
 
+use oasis_core::browser::image::decode_image;
+
 use crate::audio::AudioPlayer;
 use crate::filesystem::decode_jpeg;
 use crate::sfx::{SfxEngine, SfxId};
@@ -327,7 +329,7 @@ fn publish_audio_state(player: &AudioPlayer) {
 // I/O thread
 // ---------------------------------------------------------------------------
 
-/// Dedicated I/O thread: file reads and JPEG decoding.
+/// Dedicated I/O thread: file reads and image decoding.
 fn io_thread_fn() {
     loop {
         match IO_CMD_QUEUE.pop() {
@@ -351,7 +353,11 @@ fn io_thread_fn() {
 
 fn handle_load_texture(path: String, max_w: i32, max_h: i32) {
     match psp::io::read_to_vec(&path) {
-        Ok(data) => match decode_jpeg(&data, max_w, max_h) {
+        // JPEGs use the hardware decoder; anything else (a skin's PNG
+        // wallpaper) the shared one, at its own size.
+        Ok(data) => match decode_jpeg(&data, max_w, max_h)
+            .or_else(|| decode_image(&data).map(|i| (i.width, i.height, i.pixels)))
+        {
             Some((w, h, rgba)) => {
                 let _ = IO_RESP_QUEUE.push(IoResponse::TextureReady {
                     path,
@@ -363,7 +369,7 @@ fn handle_load_texture(path: String, max_w: i32, max_h: i32) {
             None => {
                 let _ = IO_RESP_QUEUE.push(IoResponse::Error {
                     path,
-                    msg: "JPEG decode failed".into(),
+                    msg: "image decode failed".into(),
                 });
             },
         },
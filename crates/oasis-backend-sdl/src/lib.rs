@@ -10,19 +10,23 @@
 mod font;
 mod sdl_audio;
 
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 
-use sdl2::EventPump;
-use sdl2::event::Event;
-use sdl2::keyboard::Keycode;
+use sdl2::clipboard::ClipboardUtil;
+use sdl2::controller::{Axis, Button as PadButton, GameController};
+use sdl2::event::{Event, WindowEvent};
+use sdl2::keyboard::{Keycode, Mod};
 use sdl2::pixels::PixelFormatEnum;
 use sdl2::rect::Rect;
 use sdl2::render::{Canvas, Texture, TextureCreator};
 use sdl2::video::{Window, WindowContext};
+use sdl2::{EventPump, GameControllerSubsystem};
 
 use oasis_core::backend::{Color, SdiBackend, TextureId};
+use oasis_core::clipboard::Clipboard;
+use oasis_core::config::{GamepadConfig, PadAction, WindowScaling};
 use oasis_core::error::{OasisError, Result};
-use oasis_core::input::{Button, InputEvent, Trigger};
+use oasis_core::input::{Button, EditKey, InputEvent, Trigger};
 
 pub use sdl_audio::SdlAudioBackend;
 
@@ -43,7 +47,7 @@ struct ClipRect {
 ///
 /// `textures` is declared before `texture_creator` so that Rust's drop order
 /// (declaration order) destroys all textures before the creator they borrow from.
-/// The `Texture<'static>` lifetime is erased via transmute in `load_texture()` --
+/// The `Texture<'static>` lifetime is erased via transmute when a texture is created --
 /// this is sound because the `TextureCreator` always outlives the textures.
 pub struct SdlBackend {
     canvas: Canvas<Window>,
@@ -54,34 +58,97 @@ pub struct SdlBackend {
     clip_stack: Vec<ClipRect>,
     translate_stack: Vec<(i32, i32)>,
     cumulative_translate: (i32, i32),
-    viewport_w: u32,
-    viewport_h: u32,
+    /// Render target drawing currently goes to (`None` for the window).
+    target: Option<u64>,
+    /// Where the logical screen sits in the window.
+    view: Viewport,
+    /// Window pixels per logical pixel in the initial window, and in
+    /// every window with [`WindowScaling::Native`].
+    scale: u32,
+    /// Kept alive so controllers plugged in later can be opened.
+    controller_subsystem: Option<GameControllerSubsystem>,
+    controller: Option<GameController>,
+    /// Stick tuning; its button table is parsed into `pad_overrides`.
+    gamepad: GamepadConfig,
+    /// Controller buttons mapped differently from [`default_pad_action`].
+    pad_overrides: HashMap<PadButton, PadAction>,
+    /// Left stick deflection (x, y) after the deadzone is applied.
+    stick: (i16, i16),
+    /// Last pointer position, moved by the mouse or the left stick.
+    cursor: (i32, i32),
+    clipboard: ClipboardUtil,
 }
 
 impl SdlBackend {
     /// Create a new SDL2 backend with a window.
     pub fn new(title: &str, width: u32, height: u32) -> Result<Self> {
+        Self::with_scale(title, width, height, 1)
+    }
+
+    /// Create a window `scale` times the logical size.
+    ///
+    /// All drawing and pointer coordinates stay in the logical
+    /// `logical_w` x `logical_h` space; SDL scales the output up with
+    /// nearest-neighbor filtering so pixel art stays crisp.
+    pub fn with_scale(title: &str, logical_w: u32, logical_h: u32, scale: u32) -> Result<Self> {
+        Self::with_scaling(title, logical_w, logical_h, scale, WindowScaling::Integer)
+    }
+
+    /// Create a resizable window `scale` times the logical size, whose
+    /// contents follow resizes as `scaling` says.
+    pub fn with_scaling(
+        title: &str,
+        logical_w: u32,
+        logical_h: u32,
+        scale: u32,
+        scaling: WindowScaling,
+    ) -> Result<Self> {
+        let scale = scale.max(1);
+        // Must be set before the renderer creates any textures.
+        let quality = if scaling == WindowScaling::Smooth {
+            "1"
+        } else {
+            "0"
+        };
+        sdl2::hint::set("SDL_RENDER_SCALE_QUALITY", quality);
+
         let sdl = sdl2::init().map_err(|e| OasisError::Backend(e.to_string()))?;
         let video = sdl
             .video()
             .map_err(|e| OasisError::Backend(e.to_string()))?;
         let window = video
-            .window(title, width, height)
+            .window(title, logical_w * scale, logical_h * scale)
             .position_centered()
+            .resizable()
             .build()
             .map_err(|e| OasisError::Backend(e.to_string()))?;
-        let canvas = window
+        let mut canvas = window
             .into_canvas()
             .accelerated()
             .present_vsync()
             .build()
             .map_err(|e| OasisError::Backend(e.to_string()))?;
+        let view = Viewport::fit(
+            scaling,
+            (logical_w, logical_h),
+            (logical_w * scale, logical_h * scale),
+            scale,
+        );
+        view.apply(&mut canvas)?;
         let texture_creator = canvas.texture_creator();
+        let clipboard = video.clipboard();
+        let controller_subsystem = sdl
+            .game_controller()
+            .inspect_err(|e| log::warn!("Game controllers unavailable: {e}"))
+            .ok();
+        let controller = controller_subsystem
+            .as_ref()
+            .and_then(open_first_controller);
         let event_pump = sdl
             .event_pump()
             .map_err(|e| OasisError::Backend(e.to_string()))?;
 
-        log::info!("SDL2 backend initialized: {width}x{height}");
+        log::info!("SDL2 backend initialized: {logical_w}x{logical_h} at {scale}x");
 
         Ok(Self {
             canvas,
@@ -92,11 +159,53 @@ impl SdlBackend {
             clip_stack: Vec::new(),
             translate_stack: Vec::new(),
             cumulative_translate: (0, 0),
-            viewport_w: width,
-            viewport_h: height,
+            target: None,
+            view,
+            scale,
+            controller_subsystem,
+            controller,
+            gamepad: GamepadConfig::default(),
+            pad_overrides: HashMap::new(),
+            stick: (0, 0),
+            cursor: (0, 0),
+            clipboard,
         })
     }
 
+    /// Apply game controller settings: stick deadzone and cursor speed,
+    /// and buttons remapped from the default layout.
+    pub fn set_gamepad(&mut self, config: &GamepadConfig) {
+        self.pad_overrides = parse_pad_overrides(&config.buttons);
+        self.gamepad = config.clone();
+    }
+
+    /// Refit the logical screen to the window's current size. Returns
+    /// the new logical size if it changed.
+    fn fit_to_window(&mut self) -> Option<(u32, u32)> {
+        let window = self.canvas.output_size().ok()?;
+        let view = Viewport::fit(
+            self.view.mode,
+            (self.view.logical_w, self.view.logical_h),
+            window,
+            self.scale,
+        );
+        if let Err(e) = view.apply(&mut self.canvas) {
+            log::warn!("Failed to fit the screen to the window: {e}");
+            return None;
+        }
+        let old = std::mem::replace(&mut self.view, view);
+        let size = (view.logical_w, view.logical_h);
+        (size != (old.logical_w, old.logical_h)).then_some(size)
+    }
+
+    /// Keep `texture` under a new id.
+    fn insert_texture(&mut self, texture: Texture<'static>) -> TextureId {
+        let id = self.next_texture_id;
+        self.next_texture_id += 1;
+        self.textures.insert(id, texture);
+        TextureId(id)
+    }
+
     /// Apply cumulative translation to coordinates.
     fn translate(&self, x: i32, y: i32) -> (i32, i32) {
         (
@@ -229,20 +338,20 @@ impl SdiBackend for SdlBackend {
         // same struct. `textures` is declared before `texture_creator`, so Rust drops
         // textures first. The erased lifetime is therefore always valid.
         let texture: Texture<'static> = unsafe { std::mem::transmute(texture) };
-
-        let id = self.next_texture_id;
-        self.next_texture_id += 1;
-        self.textures.insert(id, texture);
-        Ok(TextureId(id))
+        Ok(self.insert_texture(texture))
     }
 
     fn destroy_texture(&mut self, tex: TextureId) -> Result<()> {
+        if self.target == Some(tex.0) {
+            self.set_render_target(None)?;
+        }
         self.textures.remove(&tex.0);
         Ok(())
     }
 
     fn set_clip_rect(&mut self, x: i32, y: i32, w: u32, h: u32) -> Result<()> {
-        self.canvas.set_clip_rect(Rect::new(x, y, w, h));
+        let (tx, ty) = self.translate(x, y);
+        self.canvas.set_clip_rect(Rect::new(tx, ty, w, h));
         Ok(())
     }
 
@@ -256,10 +365,13 @@ impl SdiBackend for SdlBackend {
     }
 
     fn read_pixels(&self, x: i32, y: i32, w: u32, h: u32) -> Result<Vec<u8>> {
-        let rect = Rect::new(x, y, w, h);
-        self.canvas
-            .read_pixels(rect, PixelFormatEnum::ABGR8888)
-            .map_err(|e| OasisError::Backend(e.to_string()))
+        // Reads happen in window pixels; sample one per logical pixel.
+        let (wx, wy, ww, wh) = self.view.window_rect(x, y, w, h);
+        let pixels = self
+            .canvas
+            .read_pixels(Rect::new(wx, wy, ww, wh), PixelFormatEnum::ABGR8888)
+            .map_err(|e| OasisError::Backend(e.to_string()))?;
+        Ok(downsample(&pixels, (ww, wh), (w, h)))
     }
 
     fn shutdown(&mut self) -> Result<()> {
@@ -703,8 +815,8 @@ impl SdiBackend for SdlBackend {
         self.fill_rect(
             0,
             0,
-            self.viewport_w,
-            self.viewport_h,
+            self.view.logical_w,
+            self.view.logical_h,
             Color::rgba(0, 0, 0, alpha),
         )
     }
@@ -840,6 +952,80 @@ impl SdiBackend for SdlBackend {
         Ok(())
     }
 
+    fn blit_rotated(
+        &mut self,
+        tex: TextureId,
+        x: i32,
+        y: i32,
+        w: u32,
+        h: u32,
+        angle_degrees: f32,
+        center: Option<(i32, i32)>,
+    ) -> Result<()> {
+        let (tx, ty) = self.translate(x, y);
+        let texture = self
+            .textures
+            .get(&tex.0)
+            .ok_or_else(|| OasisError::Backend(format!("texture not found: {}", tex.0)))?;
+        let dst_rect = Rect::new(tx, ty, w, h);
+        let center = center.map(|(cx, cy)| sdl2::rect::Point::new(cx, cy));
+        self.canvas
+            .copy_ex(
+                texture,
+                None,
+                dst_rect,
+                f64::from(angle_degrees),
+                center,
+                false,
+                false,
+            )
+            .map_err(|e| OasisError::Backend(e.to_string()))?;
+        Ok(())
+    }
+
+    // -------------------------------------------------------------------
+    // Extended: Render Targets
+    // -------------------------------------------------------------------
+
+    fn create_render_target(&mut self, w: u32, h: u32) -> Result<TextureId> {
+        if !self.canvas.render_target_supported() {
+            return Err(OasisError::Backend(
+                "the SDL renderer cannot draw to textures".to_string(),
+            ));
+        }
+        let mut texture = self
+            .texture_creator
+            .create_texture_target(PixelFormatEnum::ABGR8888, w, h)
+            .map_err(|e| OasisError::Backend(e.to_string()))?;
+        texture.set_blend_mode(sdl2::render::BlendMode::Blend);
+        // SAFETY: as in `load_texture`, the creator outlives the texture.
+        let texture: Texture<'static> = unsafe { std::mem::transmute(texture) };
+        Ok(self.insert_texture(texture))
+    }
+
+    fn set_render_target(&mut self, target: Option<TextureId>) -> Result<()> {
+        // `Canvas::with_texture_canvas` only redirects drawing for the
+        // length of a closure; targets here stay set across calls, so
+        // this is the same SDL call made directly.
+        let raw = match target {
+            Some(tex) => self
+                .textures
+                .get(&tex.0)
+                .ok_or_else(|| OasisError::Backend(format!("texture not found: {}", tex.0)))?
+                .raw(),
+            None => std::ptr::null_mut(),
+        };
+        // SAFETY: the renderer is alive for as long as `self.canvas`, and
+        // `raw` is null or a texture created by that renderer, which
+        // `destroy_texture` unsets before freeing.
+        let status = unsafe { sdl2::sys::SDL_SetRenderTarget(self.canvas.raw(), raw) };
+        if status != 0 {
+            return Err(OasisError::Backend(sdl2::get_error()));
+        }
+        self.target = target.map(|tex| tex.0);
+        Ok(())
+    }
+
     // -------------------------------------------------------------------
     // Extended: Clip and Transform Stack
     // -------------------------------------------------------------------
@@ -865,8 +1051,8 @@ impl SdiBackend for SdlBackend {
             self.clip_stack.push(ClipRect {
                 x: 0,
                 y: 0,
-                w: self.viewport_w,
-                h: self.viewport_h,
+                w: self.view.logical_w,
+                h: self.view.logical_h,
             });
             self.canvas
                 .set_clip_rect(Rect::new(new_clip.x, new_clip.y, new_clip.w, new_clip.h));
@@ -876,7 +1062,10 @@ impl SdiBackend for SdlBackend {
 
     fn pop_clip_rect(&mut self) -> Result<()> {
         if let Some(prev) = self.clip_stack.pop() {
-            if prev.x == 0 && prev.y == 0 && prev.w == self.viewport_w && prev.h == self.viewport_h
+            if prev.x == 0
+                && prev.y == 0
+                && prev.w == self.view.logical_w
+                && prev.h == self.view.logical_h
             {
                 self.canvas.set_clip_rect(None);
             } else {
@@ -917,34 +1106,263 @@ impl SdiBackend for SdlBackend {
 impl oasis_core::backend::InputBackend for SdlBackend {
     fn poll_events(&mut self) -> Vec<InputEvent> {
         let mut events = Vec::new();
-        for event in self.event_pump.poll_iter() {
-            if let Some(e) = map_sdl_event(event) {
+        let sdl_events: Vec<Event> = self.event_pump.poll_iter().collect();
+        for event in sdl_events {
+            match event {
+                Event::ControllerAxisMotion { axis, value, .. } => match axis {
+                    Axis::LeftX => self.stick.0 = apply_deadzone(value, self.gamepad.deadzone),
+                    Axis::LeftY => self.stick.1 = apply_deadzone(value, self.gamepad.deadzone),
+                    _ => {},
+                },
+                Event::ControllerButtonDown { button, .. } => {
+                    events.extend(map_pad_button(button, true, &self.pad_overrides));
+                    continue;
+                },
+                Event::ControllerButtonUp { button, .. } => {
+                    events.extend(map_pad_button(button, false, &self.pad_overrides));
+                    continue;
+                },
+                Event::KeyDown {
+                    keycode: Some(Keycode::V),
+                    keymod,
+                    ..
+                } if keymod.intersects(Mod::LCTRLMOD | Mod::RCTRLMOD) => {
+                    if let Some(text) = Clipboard::paste_text(self) {
+                        events.push(InputEvent::Paste(text));
+                    }
+                    continue;
+                },
+                Event::ControllerDeviceAdded { which, .. } if self.controller.is_none() => {
+                    self.controller = self
+                        .controller_subsystem
+                        .as_ref()
+                        .and_then(|sub| sub.open(which).ok());
+                },
+                Event::ControllerDeviceRemoved { which, .. }
+                    if self
+                        .controller
+                        .as_ref()
+                        .is_some_and(|c| c.instance_id() == which) =>
+                {
+                    log::info!("Game controller disconnected");
+                    self.controller = None;
+                    self.stick = (0, 0);
+                },
+                _ => {},
+            }
+            if let Event::Window {
+                win_event: WindowEvent::SizeChanged(..),
+                ..
+            } = event
+                && let Some((w, h)) = self.fit_to_window()
+            {
+                events.push(InputEvent::Resized { w, h });
+                continue;
+            }
+            if let Some(e) = map_sdl_event(event, &self.view) {
+                if let InputEvent::CursorMove { x, y } = e {
+                    self.cursor = (x, y);
+                }
                 events.push(e);
             }
         }
+        if self.stick != (0, 0) {
+            let (x, y) = move_cursor(
+                self.cursor,
+                self.stick,
+                self.gamepad.cursor_speed,
+                self.view.logical_w,
+                self.view.logical_h,
+            );
+            if (x, y) != self.cursor {
+                self.cursor = (x, y);
+                events.push(InputEvent::CursorMove { x, y });
+            }
+        }
         events
     }
+
+    fn clipboard_text(&self) -> Option<String> {
+        if !self.clipboard.has_clipboard_text() {
+            return None;
+        }
+        self.clipboard
+            .clipboard_text()
+            .inspect_err(|e| log::warn!("Failed to read clipboard: {e}"))
+            .ok()
+            .filter(|text| !text.is_empty())
+    }
+
+    fn set_clipboard_text(&mut self, text: &str) -> Result<()> {
+        self.clipboard
+            .set_clipboard_text(text)
+            .map_err(|e| OasisError::Backend(e.to_string()))
+    }
+}
+
+/// Open the first attached joystick that SDL recognizes as a game controller.
+fn open_first_controller(sub: &GameControllerSubsystem) -> Option<GameController> {
+    let count = sub.num_joysticks().ok()?;
+    let index = (0..count).find(|&i| sub.is_game_controller(i))?;
+    match sub.open(index) {
+        Ok(controller) => {
+            log::info!("Game controller connected: {}", controller.name());
+            Some(controller)
+        },
+        Err(e) => {
+            log::warn!("Failed to open game controller {index}: {e}");
+            None
+        },
+    }
+}
+
+/// Zero out stick values inside `deadzone`.
+fn apply_deadzone(value: i16, deadzone: u16) -> i16 {
+    if value.unsigned_abs() < deadzone {
+        0
+    } else {
+        value
+    }
+}
+
+/// Advance the cursor by one poll's worth of stick deflection, `speed`
+/// pixels at full deflection, keeping it inside the logical area.
+fn move_cursor(cursor: (i32, i32), stick: (i16, i16), speed: u32, w: u32, h: u32) -> (i32, i32) {
+    let speed = speed.min(i16::MAX as u32) as i32;
+    let step = |v: i16| v as i32 * speed / i16::MAX as i32;
+    (
+        (cursor.0 + step(stick.0)).clamp(0, w.saturating_sub(1) as i32),
+        (cursor.1 + step(stick.1)).clamp(0, h.saturating_sub(1) as i32),
+    )
+}
+
+/// How the logical screen is laid out in the window: its size, the
+/// window pixels per logical pixel and where it starts.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Viewport {
+    mode: WindowScaling,
+    logical_w: u32,
+    logical_h: u32,
+    /// Window pixels per logical pixel.
+    scale: f32,
+    /// Top-left corner of the screen, in logical pixels from the
+    /// window's. SDL viewports are positioned in whole logical pixels.
+    origin: (i32, i32),
+}
+
+impl Viewport {
+    /// Lay a `logical` sized screen out in a `window` sized window.
+    ///
+    /// [`WindowScaling::Native`] ignores `logical`: the screen takes the
+    /// window's size, at `native_scale` window pixels per logical pixel.
+    /// The others keep `logical` and center it, at whole multiples for
+    /// [`WindowScaling::Integer`] unless the window is smaller than it.
+    fn fit(
+        mode: WindowScaling,
+        logical: (u32, u32),
+        window: (u32, u32),
+        native_scale: u32,
+    ) -> Self {
+        let (window_w, window_h) = (window.0.max(1), window.1.max(1));
+        if mode == WindowScaling::Native {
+            let scale = native_scale.max(1);
+            return Self {
+                mode,
+                logical_w: (window_w / scale).max(1),
+                logical_h: (window_h / scale).max(1),
+                scale: scale as f32,
+                origin: (0, 0),
+            };
+        }
+        let (logical_w, logical_h) = (logical.0.max(1), logical.1.max(1));
+        let fit = (window_w as f32 / logical_w as f32).min(window_h as f32 / logical_h as f32);
+        let scale = if mode == WindowScaling::Integer && fit >= 1.0 {
+            fit.floor()
+        } else {
+            fit
+        };
+        let center =
+            |window: u32, logical: u32| ((window as f32 / scale - logical as f32) / 2.0) as i32;
+        Self {
+            mode,
+            logical_w,
+            logical_h,
+            scale,
+            origin: (center(window_w, logical_w), center(window_h, logical_h)),
+        }
+    }
+
+    /// Set the canvas up to draw through this viewport.
+    fn apply(&self, canvas: &mut Canvas<Window>) -> Result<()> {
+        canvas
+            .set_scale(self.scale, self.scale)
+            .map_err(|e| OasisError::Backend(e.to_string()))?;
+        // SDL scales the viewport rect, so it is given in logical pixels.
+        canvas.set_viewport(Rect::new(
+            self.origin.0,
+            self.origin.1,
+            self.logical_w,
+            self.logical_h,
+        ));
+        Ok(())
+    }
+
+    /// The logical pixel under window position (`x`, `y`), clamped to the
+    /// screen so clicks on the letterbox border land on its edge.
+    fn logical_at(&self, x: i32, y: i32) -> (i32, i32) {
+        let axis = |v: i32, origin: i32, size: u32| {
+            ((v as f32 / self.scale).floor() as i32 - origin)
+                .clamp(0, size.saturating_sub(1) as i32)
+        };
+        (
+            axis(x, self.origin.0, self.logical_w),
+            axis(y, self.origin.1, self.logical_h),
+        )
+    }
+
+    /// The window pixels covered by a logical rect, as `(x, y, w, h)`.
+    fn window_rect(&self, x: i32, y: i32, w: u32, h: u32) -> (i32, i32, u32, u32) {
+        let edge = |v: i32, origin: i32| ((v + origin) as f32 * self.scale).round() as i32;
+        let (x0, y0) = (edge(x, self.origin.0), edge(y, self.origin.1));
+        let x1 = edge(x + w as i32, self.origin.0);
+        let y1 = edge(y + h as i32, self.origin.1);
+        (x0, y0, (x1 - x0).max(1) as u32, (y1 - y0).max(1) as u32)
+    }
 }
 
 /// Map an SDL2 event to an OASIS_OS input event.
-fn map_sdl_event(event: Event) -> Option<InputEvent> {
+///
+/// Mouse positions arrive in window pixels and are mapped onto the
+/// logical screen laid out by `view`.
+fn map_sdl_event(event: Event, view: &Viewport) -> Option<InputEvent> {
     match event {
         Event::Quit { .. } => Some(InputEvent::Quit),
         Event::KeyDown {
-            keycode: Some(key), ..
-        } => map_key_down(key),
+            keycode: Some(key),
+            keymod,
+            ..
+        } => map_edit_key(key, keymod).or_else(|| map_key_down(key)),
         Event::KeyUp {
             keycode: Some(key), ..
         } => map_key_up(key),
-        Event::MouseMotion { x, y, .. } => Some(InputEvent::CursorMove { x, y }),
-        Event::MouseButtonDown { x, y, .. } => Some(InputEvent::PointerClick { x, y }),
-        Event::MouseButtonUp { x, y, .. } => Some(InputEvent::PointerRelease { x, y }),
+        Event::MouseMotion { x, y, .. } => {
+            let (x, y) = view.logical_at(x, y);
+            Some(InputEvent::CursorMove { x, y })
+        },
+        Event::MouseButtonDown { x, y, .. } => {
+            let (x, y) = view.logical_at(x, y);
+            Some(InputEvent::PointerClick { x, y })
+        },
+        Event::MouseButtonUp { x, y, .. } => {
+            let (x, y) = view.logical_at(x, y);
+            Some(InputEvent::PointerRelease { x, y })
+        },
         Event::Window {
-            win_event: sdl2::event::WindowEvent::FocusGained,
+            win_event: WindowEvent::FocusGained,
             ..
         } => Some(InputEvent::FocusGained),
         Event::Window {
-            win_event: sdl2::event::WindowEvent::FocusLost,
+            win_event: WindowEvent::FocusLost,
             ..
         } => Some(InputEvent::FocusLost),
         Event::TextInput { text, .. } => text.chars().next().map(InputEvent::TextInput),
@@ -952,6 +1370,83 @@ fn map_sdl_event(event: Event) -> Option<InputEvent> {
     }
 }
 
+/// Map a game controller button press or release, through `overrides`
+/// and then the default layout.
+fn map_pad_button(
+    button: PadButton,
+    pressed: bool,
+    overrides: &HashMap<PadButton, PadAction>,
+) -> Option<InputEvent> {
+    let action = overrides
+        .get(&button)
+        .copied()
+        .unwrap_or_else(|| default_pad_action(button));
+    let button = match action {
+        PadAction::Up => Button::Up,
+        PadAction::Down => Button::Down,
+        PadAction::Left => Button::Left,
+        PadAction::Right => Button::Right,
+        PadAction::Confirm => Button::Confirm,
+        PadAction::Cancel => Button::Cancel,
+        PadAction::Triangle => Button::Triangle,
+        PadAction::Square => Button::Square,
+        PadAction::Start => Button::Start,
+        PadAction::Select => Button::Select,
+        PadAction::LeftTrigger => return Some(trigger_event(Trigger::Left, pressed)),
+        PadAction::RightTrigger => return Some(trigger_event(Trigger::Right, pressed)),
+        PadAction::None => return None,
+    };
+    Some(if pressed {
+        InputEvent::ButtonPress(button)
+    } else {
+        InputEvent::ButtonRelease(button)
+    })
+}
+
+/// The default controller layout, like the PSP's: A/B confirm and
+/// cancel, X/Y square and triangle, Back is Select and the shoulder
+/// buttons are the triggers.
+fn default_pad_action(button: PadButton) -> PadAction {
+    match button {
+        PadButton::DPadUp => PadAction::Up,
+        PadButton::DPadDown => PadAction::Down,
+        PadButton::DPadLeft => PadAction::Left,
+        PadButton::DPadRight => PadAction::Right,
+        PadButton::A => PadAction::Confirm,
+        PadButton::B => PadAction::Cancel,
+        PadButton::X => PadAction::Square,
+        PadButton::Y => PadAction::Triangle,
+        PadButton::Start => PadAction::Start,
+        PadButton::Back => PadAction::Select,
+        PadButton::LeftShoulder => PadAction::LeftTrigger,
+        PadButton::RightShoulder => PadAction::RightTrigger,
+        _ => PadAction::None,
+    }
+}
+
+/// Resolve the configured remappings' SDL button names, skipping (with
+/// a warning) names SDL does not know.
+fn parse_pad_overrides(buttons: &BTreeMap<String, PadAction>) -> HashMap<PadButton, PadAction> {
+    buttons
+        .iter()
+        .filter_map(|(name, &action)| match PadButton::from_string(name) {
+            Some(button) => Some((button, action)),
+            None => {
+                log::warn!("Unknown game controller button in config: {name}");
+                None
+            },
+        })
+        .collect()
+}
+
+fn trigger_event(trigger: Trigger, pressed: bool) -> InputEvent {
+    if pressed {
+        InputEvent::TriggerPress(trigger)
+    } else {
+        InputEvent::TriggerRelease(trigger)
+    }
+}
+
 fn map_key_down(key: Keycode) -> Option<InputEvent> {
     match key {
         Keycode::Up => Some(InputEvent::ButtonPress(Button::Up)),
@@ -961,7 +1456,7 @@ fn map_key_down(key: Keycode) -> Option<InputEvent> {
         Keycode::Return => Some(InputEvent::ButtonPress(Button::Confirm)),
         Keycode::Escape => Some(InputEvent::ButtonPress(Button::Cancel)),
         Keycode::Space => Some(InputEvent::ButtonPress(Button::Triangle)),
-        Keycode::Tab => Some(InputEvent::ButtonPress(Button::Square)),
+        Keycode::Tab => Some(InputEvent::Tab),
         Keycode::F1 => Some(InputEvent::ButtonPress(Button::Start)),
         Keycode::F2 => Some(InputEvent::ButtonPress(Button::Select)),
         Keycode::Backspace => Some(InputEvent::Backspace),
@@ -971,6 +1466,29 @@ fn map_key_down(key: Keycode) -> Option<InputEvent> {
     }
 }
 
+/// Map text-editing keys: Home, End, Delete, arrows with Shift or Ctrl,
+/// and Ctrl+A/C/X. Plain arrows are left to [`map_key_down`].
+fn map_edit_key(key: Keycode, keymod: Mod) -> Option<InputEvent> {
+    let shift = keymod.intersects(Mod::LSHIFTMOD | Mod::RSHIFTMOD);
+    let ctrl = keymod.intersects(Mod::LCTRLMOD | Mod::RCTRLMOD);
+    let edit_key = match key {
+        Keycode::Left if shift || ctrl => EditKey::Left,
+        Keycode::Right if shift || ctrl => EditKey::Right,
+        Keycode::Home => EditKey::Home,
+        Keycode::End => EditKey::End,
+        Keycode::Delete => EditKey::Delete,
+        Keycode::A if ctrl => EditKey::SelectAll,
+        Keycode::C if ctrl => EditKey::Copy,
+        Keycode::X if ctrl => EditKey::Cut,
+        _ => return None,
+    };
+    Some(InputEvent::EditKey {
+        key: edit_key,
+        shift,
+        ctrl,
+    })
+}
+
 fn map_key_up(key: Keycode) -> Option<InputEvent> {
     match key {
         Keycode::Up => Some(InputEvent::ButtonRelease(Button::Up)),
@@ -980,7 +1498,6 @@ fn map_key_up(key: Keycode) -> Option<InputEvent> {
         Keycode::Return => Some(InputEvent::ButtonRelease(Button::Confirm)),
         Keycode::Escape => Some(InputEvent::ButtonRelease(Button::Cancel)),
         Keycode::Space => Some(InputEvent::ButtonRelease(Button::Triangle)),
-        Keycode::Tab => Some(InputEvent::ButtonRelease(Button::Square)),
         Keycode::F1 => Some(InputEvent::ButtonRelease(Button::Start)),
         Keycode::F2 => Some(InputEvent::ButtonRelease(Button::Select)),
         Keycode::Q => Some(InputEvent::TriggerRelease(Trigger::Left)),
@@ -989,6 +1506,25 @@ fn map_key_up(key: Keycode) -> Option<InputEvent> {
     }
 }
 
+/// Pick one pixel per `out` pixel from a 4-byte-per-pixel buffer of
+/// `src` size, scaling by nearest neighbor.
+fn downsample(pixels: &[u8], src: (u32, u32), out: (u32, u32)) -> Vec<u8> {
+    if src == out {
+        return pixels.to_vec();
+    }
+    let (src_w, src_h) = (src.0 as usize, src.1 as usize);
+    let (w, h) = (out.0 as usize, out.1 as usize);
+    let mut result = Vec::with_capacity(w * h * 4);
+    for row in 0..h {
+        let line = &pixels[row * src_h / h * src_w * 4..];
+        for col in 0..w {
+            let i = col * src_w / w * 4;
+            result.extend_from_slice(&line[i..i + 4]);
+        }
+    }
+    result
+}
+
 /// Compute the intersection of two clip rectangles.
 fn intersect_clip(a: &ClipRect, b: &ClipRect) -> Option<ClipRect> {
     let ax2 = a.x.saturating_add(a.w as i32);
@@ -1091,10 +1627,7 @@ mod tests {
             map_key_down(Keycode::Space),
             Some(InputEvent::ButtonPress(Button::Triangle))
         );
-        assert_eq!(
-            map_key_down(Keycode::Tab),
-            Some(InputEvent::ButtonPress(Button::Square))
-        );
+        assert_eq!(map_key_down(Keycode::Tab), Some(InputEvent::Tab));
     }
 
     #[test]
@@ -1129,6 +1662,30 @@ mod tests {
         );
     }
 
+    #[test]
+    fn edit_keys_carry_their_modifiers() {
+        let edit = |key, shift, ctrl| Some(InputEvent::EditKey { key, shift, ctrl });
+        assert_eq!(
+            map_edit_key(Keycode::Left, Mod::LSHIFTMOD),
+            edit(EditKey::Left, true, false)
+        );
+        assert_eq!(
+            map_edit_key(Keycode::Right, Mod::RCTRLMOD | Mod::LSHIFTMOD),
+            edit(EditKey::Right, true, true)
+        );
+        assert_eq!(
+            map_edit_key(Keycode::Home, Mod::NOMOD),
+            edit(EditKey::Home, false, false)
+        );
+        assert_eq!(
+            map_edit_key(Keycode::C, Mod::LCTRLMOD),
+            edit(EditKey::Copy, false, true)
+        );
+        // Plain arrows and letters are not editing keys.
+        assert_eq!(map_edit_key(Keycode::Left, Mod::NOMOD), None);
+        assert_eq!(map_edit_key(Keycode::C, Mod::LSHIFTMOD), None);
+    }
+
     #[test]
     fn key_down_unmapped_returns_none() {
         assert_eq!(map_key_down(Keycode::A), None);
@@ -1137,6 +1694,167 @@ mod tests {
         assert_eq!(map_key_down(Keycode::F5), None);
     }
 
+    #[test]
+    fn pad_buttons_follow_psp_layout() {
+        let map_pad_button = |button, pressed| map_pad_button(button, pressed, &HashMap::new());
+        assert_eq!(
+            map_pad_button(PadButton::A, true),
+            Some(InputEvent::ButtonPress(Button::Confirm))
+        );
+        assert_eq!(
+            map_pad_button(PadButton::B, false),
+            Some(InputEvent::ButtonRelease(Button::Cancel))
+        );
+        assert_eq!(
+            map_pad_button(PadButton::DPadLeft, true),
+            Some(InputEvent::ButtonPress(Button::Left))
+        );
+        assert_eq!(
+            map_pad_button(PadButton::RightShoulder, true),
+            Some(InputEvent::TriggerPress(Trigger::Right))
+        );
+        assert_eq!(
+            map_pad_button(PadButton::LeftShoulder, false),
+            Some(InputEvent::TriggerRelease(Trigger::Left))
+        );
+        assert_eq!(map_pad_button(PadButton::Guide, true), None);
+    }
+
+    #[test]
+    fn pad_overrides_remap_and_disable_buttons() {
+        let buttons = BTreeMap::from([
+            ("a".to_string(), PadAction::Cancel),
+            ("b".to_string(), PadAction::Confirm),
+            ("guide".to_string(), PadAction::Start),
+            ("leftshoulder".to_string(), PadAction::None),
+            ("nosuchbutton".to_string(), PadAction::Up),
+        ]);
+        let overrides = parse_pad_overrides(&buttons);
+        assert_eq!(overrides.len(), 4);
+        assert_eq!(
+            map_pad_button(PadButton::A, true, &overrides),
+            Some(InputEvent::ButtonPress(Button::Cancel))
+        );
+        assert_eq!(
+            map_pad_button(PadButton::B, false, &overrides),
+            Some(InputEvent::ButtonRelease(Button::Confirm))
+        );
+        assert_eq!(
+            map_pad_button(PadButton::Guide, true, &overrides),
+            Some(InputEvent::ButtonPress(Button::Start))
+        );
+        assert_eq!(
+            map_pad_button(PadButton::LeftShoulder, true, &overrides),
+            None
+        );
+        // Buttons not overridden keep their default.
+        assert_eq!(
+            map_pad_button(PadButton::Y, true, &overrides),
+            Some(InputEvent::ButtonPress(Button::Triangle))
+        );
+    }
+
+    #[test]
+    fn stick_deadzone_and_cursor_movement() {
+        let GamepadConfig {
+            deadzone,
+            cursor_speed,
+            ..
+        } = GamepadConfig::default();
+        let edge = deadzone as i16;
+        assert_eq!(apply_deadzone(edge - 1, deadzone), 0);
+        assert_eq!(apply_deadzone(-edge + 1, deadzone), 0);
+        assert_eq!(apply_deadzone(edge - 1, 1000), edge - 1);
+        assert_eq!(apply_deadzone(i16::MIN, deadzone), i16::MIN);
+        assert_eq!(
+            move_cursor((100, 100), (i16::MAX, 0), cursor_speed, 480, 272),
+            (100 + cursor_speed as i32, 100)
+        );
+        assert_eq!(
+            move_cursor((100, 100), (i16::MAX, 0), 12, 480, 272),
+            (112, 100)
+        );
+        assert_eq!(
+            move_cursor((2, 270), (i16::MIN, i16::MAX), cursor_speed, 480, 272),
+            (0, 271)
+        );
+    }
+
+    #[test]
+    fn pointer_events_clamp_to_logical_area() {
+        let click = |x, y| Event::MouseButtonDown {
+            timestamp: 0,
+            window_id: 0,
+            which: 0,
+            mouse_btn: sdl2::mouse::MouseButton::Left,
+            clicks: 1,
+            x,
+            y,
+        };
+        let view = Viewport::fit(WindowScaling::Integer, (480, 272), (480, 272), 1);
+        assert_eq!(
+            map_sdl_event(click(100, 50), &view),
+            Some(InputEvent::PointerClick { x: 100, y: 50 })
+        );
+        assert_eq!(
+            map_sdl_event(click(-4, 300), &view),
+            Some(InputEvent::PointerClick { x: 0, y: 271 })
+        );
+
+        // Scaled up 2x and letterboxed, the screen starts 10 logical
+        // pixels in and clicks land on the pixel under the pointer.
+        let view = Viewport::fit(WindowScaling::Integer, (480, 272), (1000, 600), 2);
+        assert_eq!(view.origin, (10, 14));
+        assert_eq!(
+            map_sdl_event(click(20 + 201, 28 + 99), &view),
+            Some(InputEvent::PointerClick { x: 100, y: 49 })
+        );
+        assert_eq!(
+            map_sdl_event(click(999, 0), &view),
+            Some(InputEvent::PointerClick { x: 479, y: 0 })
+        );
+    }
+
+    #[test]
+    fn integer_scaling_uses_whole_multiples_until_the_window_is_too_small() {
+        let view = Viewport::fit(WindowScaling::Integer, (480, 272), (1000, 600), 2);
+        assert_eq!(view.scale, 2.0);
+        assert_eq!((view.logical_w, view.logical_h), (480, 272));
+        let view = Viewport::fit(WindowScaling::Integer, (480, 272), (1440, 816), 2);
+        assert_eq!((view.scale, view.origin), (3.0, (0, 0)));
+        // Below 1x, whole multiples would not fit at all.
+        let view = Viewport::fit(WindowScaling::Integer, (480, 272), (240, 200), 2);
+        assert_eq!(view.scale, 0.5);
+        assert_eq!(view.origin, (0, 64));
+        assert_eq!(view.logical_at(120, 100), (240, 136));
+    }
+
+    #[test]
+    fn smooth_scaling_fills_the_window_keeping_the_aspect_ratio() {
+        let view = Viewport::fit(WindowScaling::Smooth, (480, 272), (960, 600), 2);
+        assert_eq!(view.scale, 2.0);
+        assert_eq!(view.origin, (0, 14));
+        let view = Viewport::fit(WindowScaling::Smooth, (480, 272), (720, 408), 2);
+        assert_eq!(view.scale, 1.5);
+        assert_eq!(view.origin, (0, 0));
+        assert_eq!(view.logical_at(719, 407), (479, 271));
+        assert_eq!(view.logical_at(150, 3), (100, 2));
+        // Reads cover the window pixels of the logical rect.
+        assert_eq!(view.window_rect(10, 10, 4, 4), (15, 15, 6, 6));
+    }
+
+    #[test]
+    fn native_scaling_follows_the_window_size() {
+        let view = Viewport::fit(WindowScaling::Native, (480, 272), (1000, 601), 2);
+        assert_eq!((view.logical_w, view.logical_h), (500, 300));
+        assert_eq!((view.scale, view.origin), (2.0, (0, 0)));
+        assert_eq!(view.logical_at(999, 600), (499, 299));
+        assert_eq!(view.window_rect(1, 2, 3, 4), (2, 4, 6, 8));
+        // A minimized window still leaves a usable screen.
+        let view = Viewport::fit(WindowScaling::Native, (480, 272), (0, 0), 2);
+        assert_eq!((view.logical_w, view.logical_h), (1, 1));
+    }
+
     #[test]
     fn key_up_arrow_keys() {
         assert_eq!(
@@ -1171,10 +1889,6 @@ mod tests {
             map_key_up(Keycode::Space),
             Some(InputEvent::ButtonRelease(Button::Triangle))
         );
-        assert_eq!(
-            map_key_up(Keycode::Tab),
-            Some(InputEvent::ButtonRelease(Button::Square))
-        );
     }
 
     #[test]
@@ -1193,11 +1907,13 @@ mod tests {
     fn key_up_unmapped_returns_none() {
         assert_eq!(map_key_up(Keycode::A), None);
         assert_eq!(map_key_up(Keycode::Backspace), None);
+        assert_eq!(map_key_up(Keycode::Tab), None);
     }
 
     #[test]
     fn key_down_up_symmetry() {
-        // Every mapped key_down should have a corresponding key_up (except Backspace).
+        // Every mapped key_down should have a corresponding key_up (except
+        // Backspace and Tab).
         let keys = [
             Keycode::Up,
             Keycode::Down,
@@ -1206,7 +1922,6 @@ mod tests {
             Keycode::Return,
             Keycode::Escape,
             Keycode::Space,
-            Keycode::Tab,
             Keycode::F1,
             Keycode::F2,
             Keycode::Q,
@@ -1347,6 +2062,28 @@ mod tests {
         assert_eq!(isqrt(99), 9);
     }
 
+    #[test]
+    fn downsample_keeps_one_pixel_per_block() {
+        // 2x2 logical image read back at 2x: each source pixel is a
+        // 2x2 block whose top-left byte is its index.
+        let mut pixels = Vec::new();
+        for row in 0..4u8 {
+            for col in 0..4u8 {
+                let idx = (row / 2) * 2 + col / 2;
+                pixels.extend_from_slice(&[idx, row, col, 255]);
+            }
+        }
+        let out = downsample(&pixels, (4, 4), (2, 2));
+        assert_eq!(out.len(), 2 * 2 * 4);
+        let firsts: Vec<u8> = out.chunks(4).map(|p| p[0]).collect();
+        assert_eq!(firsts, vec![0, 1, 2, 3]);
+        assert_eq!(downsample(&pixels, (4, 4), (4, 4)), pixels);
+        // Read back at 1.5x: 3 logical pixels from a 4-pixel-wide row.
+        let out = downsample(&pixels[..16], (4, 1), (3, 1));
+        let cols: Vec<u8> = out.chunks(4).map(|p| p[2]).collect();
+        assert_eq!(cols, vec![0, 1, 2]);
+    }
+
     #[test]
     fn isqrt_negative() {
         assert_eq!(isqrt(-1), 0);
@@ -0,0 +1,105 @@
+//! In-page anchors: where the element a URL `#fragment` names sits.
+//!
+//! A fragment names an element by its `id`, or an `<a>` by its `name`.
+//! Positions are layout-space Y coordinates of the top of the element's
+//! first box, ready for [`ScrollState::scroll_to`](crate::scroll::ScrollState::scroll_to).
+
+use std::collections::HashMap;
+
+use crate::html::dom::{Document, NodeId, NodeKind, TagName};
+use crate::layout::box_model::LayoutBox;
+
+/// Map every anchor name on the page to the top of its element.
+///
+/// Elements that produced no box (e.g. `display: none`) are left out.
+/// When several elements share a name, the one highest on the page wins.
+pub fn anchor_positions(doc: &Document, root: &LayoutBox) -> HashMap<String, f32> {
+    let mut names: HashMap<NodeId, Vec<&str>> = HashMap::new();
+    for (id, node) in doc.nodes.iter().enumerate() {
+        let NodeKind::Element(elem) = &node.kind else {
+            continue;
+        };
+        let mut element_names = Vec::new();
+        if let Some(name) = elem.get_attribute("id") {
+            element_names.push(name);
+        }
+        if elem.tag == TagName::A
+            && let Some(name) = elem.get_attribute("name")
+        {
+            element_names.push(name);
+        }
+        element_names.retain(|name| !name.is_empty());
+        if !element_names.is_empty() {
+            names.insert(id, element_names);
+        }
+    }
+
+    let mut positions = HashMap::new();
+    if !names.is_empty() {
+        collect_positions(root, &names, &mut positions);
+    }
+    positions
+}
+
+fn collect_positions(
+    layout_box: &LayoutBox,
+    names: &HashMap<NodeId, Vec<&str>>,
+    positions: &mut HashMap<String, f32>,
+) {
+    // Inline elements are flattened into their text runs, which keep the
+    // element's node, so the first run marks the element's top.
+    if let Some(element_names) = layout_box.node.and_then(|node| names.get(&node)) {
+        let top = layout_box.dimensions.border_box().y;
+        for name in element_names {
+            positions
+                .entry(name.to_string())
+                .and_modify(|y: &mut f32| *y = y.min(top))
+                .or_insert(top);
+        }
+    }
+    for child in &layout_box.children {
+        collect_positions(child, names, positions);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::layout::block::{TextMeasurer, build_layout_tree};
+
+    struct FixedMeasurer;
+
+    impl TextMeasurer for FixedMeasurer {
+        fn measure_text(&self, text: &str, font_size: u16) -> u32 {
+            oasis_types::backend::bitmap_measure_text(text, font_size)
+        }
+    }
+
+    fn positions(src: &str) -> HashMap<String, f32> {
+        let tokens = crate::html::tokenizer::Tokenizer::new(src).tokenize();
+        let doc = crate::html::tree_builder::TreeBuilder::build(tokens);
+        let styles = crate::css::cascade::style_document(&doc);
+        let root = build_layout_tree(&doc, &styles, &FixedMeasurer, 480.0, 272.0);
+        anchor_positions(&doc, &root)
+    }
+
+    #[test]
+    fn ids_and_link_names_map_to_their_tops() {
+        let map = positions(
+            "<p>intro</p><h2 id=\"one\">One</h2><p>text</p>\
+             <p><a name=\"two\">Two</a></p><p name=\"three\">not an anchor</p>",
+        );
+        let one = map["one"];
+        let two = map["two"];
+        assert!(one > 0.0);
+        assert!(two > one);
+        assert!(!map.contains_key("three"));
+    }
+
+    #[test]
+    fn hidden_elements_have_no_position() {
+        let map = positions("<p id=\"gone\" style=\"display: none\">x</p><p id=\"here\">y</p>");
+        assert!(!map.contains_key("gone"));
+        assert!(map.contains_key("here"));
+    }
+}
@@ -0,0 +1,131 @@
+//! Bookmarks page.
+//!
+//! Entering [`BOOKMARKS_URL`] renders the saved bookmarks as an HTML
+//! page: one link per bookmark, with its URL underneath and a delete
+//! link pointing at [`BOOKMARK_REMOVE_URL`]. Following a delete link
+//! removes the bookmark and re-renders the page in place.
+
+use crate::nav::Bookmark;
+use crate::push_escaped;
+
+/// URL that opens the bookmarks page.
+pub const BOOKMARKS_URL: &str = "about:bookmarks";
+
+/// Delete links on the bookmarks page: `about:bookmark-remove?url=...`
+/// with the bookmarked URL percent-encoded.
+pub const BOOKMARK_REMOVE_URL: &str = "about:bookmark-remove";
+
+/// Build the bookmarks page.
+pub fn render(bookmarks: &[Bookmark]) -> String {
+    let mut html = String::from(
+        "<html><head><title>Bookmarks</title></head><body>\
+         <h1>Bookmarks</h1>",
+    );
+    if bookmarks.is_empty() {
+        html.push_str("<p>No bookmarks yet. Hold R and press Triangle to add one.</p>");
+    }
+    for bm in bookmarks {
+        let title = if bm.title.is_empty() {
+            &bm.url
+        } else {
+            &bm.title
+        };
+        html.push_str("<p><a href=\"");
+        push_escaped(&mut html, &bm.url);
+        html.push_str("\">");
+        push_escaped(&mut html, title);
+        html.push_str("</a><br><small>");
+        push_escaped(&mut html, &bm.url);
+        html.push_str("</small> <a href=\"");
+        html.push_str(&remove_url(&bm.url));
+        html.push_str("\">[delete]</a></p>");
+    }
+    html.push_str("</body></html>");
+    html
+}
+
+/// The delete link for the bookmark of `url`.
+pub fn remove_url(url: &str) -> String {
+    let mut out = format!("{BOOKMARK_REMOVE_URL}?url=");
+    for &b in url.as_bytes() {
+        match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                out.push(b as char)
+            },
+            _ => out.push_str(&format!("%{b:02X}")),
+        }
+    }
+    out
+}
+
+/// The bookmarked URL a delete link refers to, if `href` is one.
+pub fn remove_target(href: &str) -> Option<String> {
+    let encoded = href
+        .strip_prefix(BOOKMARK_REMOVE_URL)?
+        .strip_prefix('?')?
+        .split('&')
+        .find_map(|param| param.strip_prefix("url="))?;
+    let bytes = encoded.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let hex = bytes
+            .get(i + 1..i + 3)
+            .and_then(|h| std::str::from_utf8(h).ok())
+            .and_then(|h| u8::from_str_radix(h, 16).ok());
+        match (bytes[i], hex) {
+            (b'%', Some(b)) => {
+                out.push(b);
+                i += 3;
+            },
+            (b, _) => {
+                out.push(b);
+                i += 1;
+            },
+        }
+    }
+    String::from_utf8(out).ok()
+}
+
+// -------------------------------------------------------------------
+// Tests
+// -------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn page_lists_bookmarks_with_delete_links() {
+        let bookmarks = vec![
+            Bookmark {
+                url: "vfs://sites/home/index.html".to_string(),
+                title: "Home <1>".to_string(),
+            },
+            Bookmark {
+                url: "gemini://gem.example/".to_string(),
+                title: String::new(),
+            },
+        ];
+        let html = render(&bookmarks);
+        assert!(html.contains("<a href=\"vfs://sites/home/index.html\">Home &lt;1&gt;</a>"));
+        assert!(html.contains(">gemini://gem.example/</a>"));
+        assert!(
+            html.contains(
+                "href=\"about:bookmark-remove?url=gemini%3A%2F%2Fgem.example%2F\">[delete]"
+            )
+        );
+        assert!(render(&[]).contains("No bookmarks yet"));
+    }
+
+    #[test]
+    fn remove_links_round_trip() {
+        let url = "http://example.com/a b?q=1&r=%20";
+        assert_eq!(remove_target(&remove_url(url)).as_deref(), Some(url));
+        assert_eq!(remove_target("about:bookmarks"), None);
+        assert_eq!(
+            remove_target("about:bookmark-remove?url=50%"),
+            Some("50%".into())
+        );
+    }
+}
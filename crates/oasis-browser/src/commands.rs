@@ -1,7 +1,13 @@
 //! Terminal commands for the browser subsystem.
 
-use oasis_terminal::{Command, CommandOutput, CommandRegistry, Environment};
-use oasis_types::error::Result;
+use oasis_platform::HttpResponse;
+use oasis_terminal::network_commands::HttpCmd;
+use oasis_terminal::{Command, CommandOutput, CommandRegistry, Environment, resolve_path};
+use oasis_types::error::{OasisError, Result};
+use oasis_vfs::EntryKind;
+
+use crate::download;
+use crate::loader::{self, ResourceRequest, ResourceSource, Url};
 
 /// Register all browser commands into a registry.
 pub fn register_browser_commands(reg: &mut CommandRegistry) {
@@ -9,7 +15,11 @@ pub fn register_browser_commands(reg: &mut CommandRegistry) {
     reg.register(Box::new(FetchCmd));
     reg.register(Box::new(GeminiCmd));
     reg.register(Box::new(CurlCmd));
+    reg.register(Box::new(WgetCmd));
+    reg.register(Box::new(DownloadCmd));
+    reg.register(Box::new(HttpCmd::new(loader_fetch)));
     reg.register(Box::new(SandboxCmd));
+    reg.register(Box::new(WebCacheCmd));
 }
 
 // -------------------------------------------------------------------
@@ -161,7 +171,9 @@ impl Command for FetchCmd {
         if (url.starts_with("http://") || url.starts_with("https://"))
             && let Some(parsed) = super::loader::Url::parse(url)
         {
-            match super::loader::http::http_get(&parsed, env.tls) {
+            match super::loader::http::http_get(&parsed, env.tls, loader::timeout::DEFAULT_TIMEOUT)
+                .map(loader::decode_content)
+            {
                 Ok(resp) => {
                     let text = String::from_utf8_lossy(&resp.body);
                     if show_headers {
@@ -321,6 +333,224 @@ impl Command for CurlCmd {
     }
 }
 
+// -------------------------------------------------------------------
+// wget
+// -------------------------------------------------------------------
+struct WgetCmd;
+
+impl Command for WgetCmd {
+    fn name(&self) -> &str {
+        "wget"
+    }
+
+    fn description(&self) -> &str {
+        "Download a URL into the VFS"
+    }
+
+    fn usage(&self) -> &str {
+        "wget [-f] <url> [dest]"
+    }
+
+    fn category(&self) -> &str {
+        "browser"
+    }
+
+    fn execute(&self, args: &[&str], env: &mut Environment<'_>) -> Result<CommandOutput> {
+        let (force, url, dest) = save_args(args, self.usage())?;
+        let (path, response, elapsed) = save_url("wget", url, dest, force, env)?;
+
+        Ok(CommandOutput::Text(format!(
+            "Saved {path}\n\
+             Content-Type: {:?}\n\
+             Length: {} bytes\n\
+             Time: {} ms",
+            response.content_type,
+            response.body.len(),
+            elapsed.as_millis(),
+        )))
+    }
+}
+
+/// Parse `[-f] <url> [dest]`, the arguments `wget` and `download` share,
+/// into the force flag, URL and destination.
+fn save_args<'a>(args: &[&'a str], usage: &str) -> Result<(bool, &'a str, Option<&'a str>)> {
+    let force = args.contains(&"-f");
+    let operands: Vec<&str> = args.iter().copied().filter(|a| *a != "-f").collect();
+    match operands.as_slice() {
+        [url] => Ok((force, url, None)),
+        [url, dest] => Ok((force, url, Some(dest))),
+        _ => Err(OasisError::Command(format!("usage: {usage}"))),
+    }
+}
+
+// -------------------------------------------------------------------
+// download
+// -------------------------------------------------------------------
+
+/// `wget` with a required destination and a one-line report, for
+/// scripts that want to name where the file goes and check its size.
+struct DownloadCmd;
+
+impl Command for DownloadCmd {
+    fn name(&self) -> &str {
+        "download"
+    }
+
+    fn description(&self) -> &str {
+        "Save a URL's raw body to a VFS path (wget with a required destination)"
+    }
+
+    fn usage(&self) -> &str {
+        "download [-f] <url> <vfs-path>"
+    }
+
+    fn category(&self) -> &str {
+        "network"
+    }
+
+    fn execute(&self, args: &[&str], env: &mut Environment<'_>) -> Result<CommandOutput> {
+        let (force, url, Some(dest)) = save_args(args, self.usage())? else {
+            return Err(OasisError::Command(format!("usage: {}", self.usage())));
+        };
+        let (path, response, _) = save_url("download", url, Some(dest), force, env)?;
+        Ok(CommandOutput::Text(format!(
+            "{path}: {} bytes written ({})",
+            response.body.len(),
+            response.content_type.mime(),
+        )))
+    }
+}
+
+// -------------------------------------------------------------------
+// http
+// -------------------------------------------------------------------
+
+/// [`HttpFetch`](oasis_terminal::network_commands::HttpFetch) through the
+/// browser's loader. Registered after the terminal's own commands, it gives
+/// `http` TLS and redirects.
+fn loader_fetch(url: &str, head: bool, env: &mut Environment<'_>) -> Result<HttpResponse> {
+    let scheme = Url::parse(url).map(|u| u.scheme).unwrap_or_default();
+    if scheme != "http" && scheme != "https" {
+        return Err(OasisError::Command(format!(
+            "http: not an http:// or https:// URL: {url}"
+        )));
+    }
+    if scheme == "https" && env.tls.is_none() {
+        return Err(OasisError::Command(format!(
+            "http: {url}: https:// needs TLS, and no TLS provider is configured"
+        )));
+    }
+
+    // The loader only sends GET: `head` drops the body, and shows a
+    // redirect itself rather than following it.
+    let request = ResourceRequest {
+        url: url.to_string(),
+        base_url: None,
+        source: ResourceSource::Network,
+        max_redirects: if head { 0 } else { 5 },
+        timeout: loader::timeout::DEFAULT_TIMEOUT,
+        retries: loader::timeout::DEFAULT_RETRIES,
+    };
+    let response = loader::load_resource(&*env.vfs, &request, env.tls, None)
+        .map_err(|e| OasisError::Command(format!("http: {url}: {e}")))?;
+    Ok(HttpResponse {
+        status_code: response.status,
+        headers: response.headers,
+        body: response.body,
+    })
+}
+
+/// Fetch `url` with the browser's loaders and write its body to `dest`
+/// (a file, or a directory to save into under the URL's file name),
+/// refusing to replace an existing file unless `force`. Returns the
+/// path written, the response and how long the fetch took.
+fn save_url(
+    cmd: &str,
+    url: &str,
+    dest: Option<&str>,
+    force: bool,
+    env: &mut Environment<'_>,
+) -> Result<(String, loader::ResourceResponse, std::time::Duration)> {
+    let parsed =
+        Url::parse(url).ok_or_else(|| OasisError::Command(format!("{cmd}: invalid URL: {url}")))?;
+
+    // Without a TLS provider the network loaders answer https:// and
+    // gemini:// with an explanatory page; only the VFS can serve them.
+    let needs_tls = matches!(parsed.scheme.as_str(), "https" | "gemini");
+    let no_tls = needs_tls && env.tls.is_none();
+    let source = if no_tls {
+        ResourceSource::Vfs
+    } else {
+        ResourceSource::VfsThenNetwork
+    };
+    let request = ResourceRequest {
+        url: url.to_string(),
+        base_url: None,
+        source,
+        max_redirects: 5,
+        // An idle limit: a large download that keeps arriving, however
+        // slowly, is never cut off.
+        timeout: loader::timeout::DEFAULT_TIMEOUT,
+        retries: loader::timeout::DEFAULT_RETRIES,
+    };
+
+    let started = std::time::Instant::now();
+    let response = match loader::load_resource(&*env.vfs, &request, env.tls, None) {
+        Ok(response) => response,
+        Err(_) if no_tls => {
+            return Err(OasisError::Command(format!(
+                "{cmd}: {url}: {}:// needs TLS, and no TLS provider is configured",
+                parsed.scheme
+            )));
+        },
+        Err(e) => return Err(OasisError::Command(format!("{cmd}: {url}: {e}"))),
+    };
+    let elapsed = started.elapsed();
+    if is_error_status(&parsed.scheme, response.status) {
+        return Err(OasisError::Command(format!(
+            "{cmd}: {url}: status {}",
+            response.status
+        )));
+    }
+    // A plain-HTTP site redirecting to HTTPS ends on the loader's
+    // explanatory page instead of the resource.
+    if !needs_tls && env.tls.is_none() && response.url.starts_with("https://") {
+        return Err(OasisError::Command(format!(
+            "{cmd}: {url}: redirected to {}, and no TLS provider is configured",
+            response.url
+        )));
+    }
+
+    let path = match dest {
+        Some(dest) => resolve_path(&env.cwd, dest),
+        None => resolve_path(&env.cwd, &download::file_name_for(&response.url)),
+    };
+    let path = match env.vfs.stat(&path) {
+        Ok(meta) if meta.kind == EntryKind::Directory => {
+            let name = download::file_name_for(&response.url);
+            resolve_path(&path, &name)
+        },
+        _ => path,
+    };
+    if !force && env.vfs.exists(&path) {
+        return Err(OasisError::Command(format!(
+            "{cmd}: {path} exists (use -f to overwrite)"
+        )));
+    }
+    env.vfs.write(&path, &response.body)?;
+    Ok((path, response, elapsed))
+}
+
+/// Whether a response status means the download failed: 4xx/5xx for
+/// HTTP and VFS responses, 40-69 for Gemini.
+fn is_error_status(scheme: &str, status: u16) -> bool {
+    if scheme == "gemini" {
+        (40..70).contains(&status)
+    } else {
+        status >= 400
+    }
+}
+
 // -------------------------------------------------------------------
 // sandbox
 // -------------------------------------------------------------------
@@ -357,6 +587,39 @@ impl Command for SandboxCmd {
     }
 }
 
+// -------------------------------------------------------------------
+// webcache
+// -------------------------------------------------------------------
+struct WebCacheCmd;
+
+impl Command for WebCacheCmd {
+    fn name(&self) -> &str {
+        "webcache"
+    }
+
+    fn description(&self) -> &str {
+        "Show browser resource cache statistics"
+    }
+
+    fn usage(&self) -> &str {
+        "webcache | webcache clear"
+    }
+
+    fn category(&self) -> &str {
+        "browser"
+    }
+
+    fn execute(&self, args: &[&str], _env: &mut Environment<'_>) -> Result<CommandOutput> {
+        match args.first().copied() {
+            None => Ok(CommandOutput::BrowserCache { clear: false }),
+            Some("clear") => Ok(CommandOutput::BrowserCache { clear: true }),
+            _ => Ok(CommandOutput::Text(
+                "Usage: webcache | webcache clear".to_string(),
+            )),
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -379,6 +642,7 @@ mod tests {
             network: None,
             tls: None,
             stdin: None,
+            enforce_permissions: false,
         };
         reg.execute(line, &mut env)
     }
@@ -647,6 +911,19 @@ mod tests {
         }
     }
 
+    #[test]
+    fn webcache_signals_app() {
+        let (reg, mut vfs) = setup();
+        match exec(&reg, &mut vfs, "webcache").unwrap() {
+            CommandOutput::BrowserCache { clear } => assert!(!clear),
+            _ => panic!("expected BrowserCache"),
+        }
+        match exec(&reg, &mut vfs, "webcache clear").unwrap() {
+            CommandOutput::BrowserCache { clear } => assert!(clear),
+            _ => panic!("expected BrowserCache"),
+        }
+    }
+
     #[test]
     fn sandbox_no_args_shows_usage() {
         let (reg, mut vfs) = setup();
@@ -689,4 +966,145 @@ mod tests {
             _ => panic!("expected text"),
         }
     }
+
+    #[test]
+    fn wget_round_trips_bytes_into_the_vfs() {
+        let (reg, mut vfs) = setup();
+        let data: Vec<u8> = (0..=255).collect();
+        vfs.mkdir("/media").unwrap();
+        vfs.write("/media/blob.bin", &data).unwrap();
+        vfs.mkdir("/home").unwrap();
+
+        match exec(&reg, &mut vfs, "wget vfs://media/blob.bin /home/copy.bin").unwrap() {
+            CommandOutput::Text(s) => {
+                assert!(s.contains("/home/copy.bin"));
+                assert!(s.contains("256 bytes"));
+                assert!(s.contains("Content-Type: Unknown"));
+            },
+            _ => panic!("expected text"),
+        }
+        assert_eq!(vfs.read("/home/copy.bin").unwrap(), data);
+
+        // Without a destination the name comes from the URL; a directory
+        // destination keeps that name.
+        exec(&reg, &mut vfs, "wget vfs://media/blob.bin").unwrap();
+        assert_eq!(vfs.read("/blob.bin").unwrap(), data);
+        exec(&reg, &mut vfs, "wget vfs://media/blob.bin /home").unwrap();
+        assert_eq!(vfs.read("/home/blob.bin").unwrap(), data);
+    }
+
+    #[test]
+    fn wget_serves_http_urls_from_the_site_tree() {
+        let (reg, mut vfs) = setup();
+        vfs.mkdir("/sites/example.com").unwrap();
+        vfs.write("/sites/example.com/page.html", b"<html>hi</html>")
+            .unwrap();
+        exec(&reg, &mut vfs, "wget https://example.com/page.html").unwrap();
+        assert_eq!(vfs.read("/page.html").unwrap(), b"<html>hi</html>");
+    }
+
+    #[test]
+    fn wget_refuses_to_overwrite_without_force() {
+        let (reg, mut vfs) = setup();
+        vfs.mkdir("/media").unwrap();
+        vfs.write("/media/src.txt", b"new").unwrap();
+        vfs.write("/dest.txt", b"old").unwrap();
+        let err = exec(&reg, &mut vfs, "wget vfs://media/src.txt dest.txt").unwrap_err();
+        assert!(err.to_string().contains("-f"));
+        assert_eq!(vfs.read("/dest.txt").unwrap(), b"old");
+        exec(&reg, &mut vfs, "wget -f vfs://media/src.txt dest.txt").unwrap();
+        assert_eq!(vfs.read("/dest.txt").unwrap(), b"new");
+    }
+
+    #[test]
+    fn wget_fails_on_missing_resources_and_bad_usage() {
+        let (reg, mut vfs) = setup();
+        assert!(exec(&reg, &mut vfs, "wget").is_err());
+        assert!(exec(&reg, &mut vfs, "wget vfs://missing.txt").is_err());
+        // No TLS provider: gemini:// can only come from the VFS.
+        assert!(exec(&reg, &mut vfs, "wget gemini://example.org/").is_err());
+        assert!(!vfs.exists("/missing.txt"));
+    }
+
+    #[test]
+    fn download_reports_bytes_and_content_type() {
+        let (reg, mut vfs) = setup();
+        vfs.mkdir("/sites/example.com").unwrap();
+        vfs.write("/sites/example.com/logo.png", &[0x89, b'P', b'N', b'G'])
+            .unwrap();
+        vfs.mkdir("/home").unwrap();
+
+        match exec(
+            &reg,
+            &mut vfs,
+            "download http://example.com/logo.png /home/logo.png",
+        )
+        .unwrap()
+        {
+            CommandOutput::Text(s) => assert_eq!(s, "/home/logo.png: 4 bytes written (image/png)"),
+            _ => panic!("expected text"),
+        }
+        assert_eq!(
+            vfs.read("/home/logo.png").unwrap(),
+            [0x89, b'P', b'N', b'G']
+        );
+
+        let err = exec(
+            &reg,
+            &mut vfs,
+            "download http://example.com/logo.png /home/logo.png",
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("use -f"), "{err}");
+        exec(
+            &reg,
+            &mut vfs,
+            "download -f http://example.com/logo.png /home/logo.png",
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn download_errors_are_plain_messages() {
+        let (reg, mut vfs) = setup();
+        // The destination is required.
+        assert!(exec(&reg, &mut vfs, "download vfs://missing.txt").is_err());
+
+        let err = exec(
+            &reg,
+            &mut vfs,
+            "download https://nowhere.example/a.bin /a.bin",
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("no TLS provider"), "{err}");
+
+        let err = exec(&reg, &mut vfs, "download vfs://gone.txt /gone.txt").unwrap_err();
+        assert!(
+            err.to_string()
+                .starts_with("command error: download: vfs://gone.txt: "),
+            "{err}"
+        );
+        assert!(!vfs.exists("/gone.txt"));
+    }
+
+    #[test]
+    fn http_checks_usage_scheme_and_tls() {
+        let (reg, mut vfs) = setup();
+        assert!(exec(&reg, &mut vfs, "http").is_err());
+        assert!(exec(&reg, &mut vfs, "http head").is_err());
+
+        let err = exec(&reg, &mut vfs, "http head vfs://sites/a.html").unwrap_err();
+        assert!(err.to_string().contains("not an http://"), "{err}");
+        let err = exec(&reg, &mut vfs, "http head https://example.com/").unwrap_err();
+        assert!(err.to_string().contains("no TLS provider"), "{err}");
+    }
+
+    #[test]
+    fn error_statuses_per_scheme() {
+        assert!(is_error_status("https", 404));
+        assert!(!is_error_status("http", 200));
+        assert!(is_error_status("gemini", 51));
+        assert!(!is_error_status("gemini", 200));
+        assert!(!is_error_status("gemini", 20));
+    }
 }
@@ -1,9 +1,28 @@
 //! Browser configuration and skin feature gates.
 
+use std::time::Duration;
+
 use oasis_skin::SkinTheme;
 use oasis_types::backend::Color;
 use oasis_types::color::{darken, lighten};
 
+use crate::loader::timeout::{DEFAULT_RETRIES, DEFAULT_TIMEOUT};
+use crate::scroll::ScrollTuning;
+
+/// Smallest page zoom allowed by [`BrowserConfig::clamped_text_scale`].
+pub const MIN_TEXT_SCALE: f32 = 0.5;
+
+/// Largest page zoom allowed by [`BrowserConfig::clamped_text_scale`].
+pub const MAX_TEXT_SCALE: f32 = 3.0;
+
+/// Smallest reader-mode text size allowed by
+/// [`BrowserConfig::clamped_reader_text_scale`].
+pub const MIN_READER_TEXT_SCALE: f32 = 0.75;
+
+/// Largest reader-mode text size allowed by
+/// [`BrowserConfig::clamped_reader_text_scale`].
+pub const MAX_READER_TEXT_SCALE: f32 = 2.0;
+
 /// Browser feature configuration (from skin features.toml).
 #[derive(Debug, Clone)]
 pub struct BrowserFeatures {
@@ -23,6 +42,8 @@ pub struct BrowserFeatures {
     pub home_url: String,
     /// Resource cache limit in MB.
     pub max_cache_mb: usize,
+    /// Maximum number of open tabs.
+    pub max_tabs: usize,
 }
 
 impl Default for BrowserFeatures {
@@ -36,6 +57,7 @@ impl Default for BrowserFeatures {
             sandbox_only: false,
             home_url: "vfs://sites/home/index.html".to_string(),
             max_cache_mb: 2,
+            max_tabs: 4,
         }
     }
 }
@@ -49,6 +71,9 @@ pub struct BrowserConfig {
     pub url_bar_height: u32,
     pub status_bar_height: u32,
     pub button_width: u32,
+    /// Height of the tab strip, shown below the URL bar when more than
+    /// one tab is open.
+    pub tab_bar_height: u32,
 
     // Chrome colors
     pub chrome_bg: Color,
@@ -62,6 +87,12 @@ pub struct BrowserConfig {
 
     // Page defaults
     pub default_font_size: f32,
+    /// Page zoom: every font size and line height is multiplied by this
+    /// before layout.
+    pub text_scale: f32,
+    /// Reader-mode text size, applied on top of `text_scale`. Line
+    /// heights scale along with the font.
+    pub reader_text_scale: f32,
     pub default_text_color: Color,
     pub default_bg_color: Color,
     pub default_link_color: Color,
@@ -69,11 +100,40 @@ pub struct BrowserConfig {
 
     // Scroll
     pub smooth_scroll: bool,
-    pub scroll_line_px: i32,
+    /// Step sizes and smooth-scroll motion; [`ScrollTuning::snappy`]
+    /// suits small screens.
+    pub scroll_tuning: ScrollTuning,
 
     // Limits
     pub max_redirects: u8,
+    /// Time a network request may go without progress before the page
+    /// shows "Request timed out".
+    pub request_timeout: Duration,
+    /// Retries of a network request that timed out or lost its
+    /// connection.
+    pub network_retries: u8,
     pub max_image_dimension: u32,
+    /// Parsed pages kept for instant back/forward navigation.
+    pub page_cache_entries: usize,
+
+    /// VFS directory that downloaded files are saved into.
+    pub downloads_dir: String,
+
+    /// VFS file bookmarks are persisted to (`None` keeps them in
+    /// memory only).
+    pub bookmarks_path: Option<String>,
+
+    /// VFS file pinned Gemini server certificates are persisted to
+    /// (`None` keeps them in memory only).
+    pub known_hosts_path: Option<String>,
+
+    /// VFS file the browsing history is persisted to (`None` keeps it
+    /// in memory only).
+    pub history_path: Option<String>,
+
+    /// Maximum number of pages kept in the browsing history; the least
+    /// recently visited are dropped first.
+    pub max_history_entries: usize,
 
     /// Use themed chrome with rounded rects (true) or legacy flat chrome (false).
     pub use_themed_chrome: bool,
@@ -86,6 +146,7 @@ impl Default for BrowserConfig {
             url_bar_height: 20,
             status_bar_height: 14,
             button_width: 20,
+            tab_bar_height: 12,
             chrome_bg: Color::rgb(48, 48, 48),
             chrome_text: Color::rgb(200, 200, 200),
             chrome_button_bg: Color::rgb(64, 64, 64),
@@ -95,14 +156,24 @@ impl Default for BrowserConfig {
             status_bar_bg: Color::rgb(40, 40, 40),
             status_bar_text: Color::rgb(160, 160, 160),
             default_font_size: 8.0,
+            text_scale: 1.0,
+            reader_text_scale: 1.0,
             default_text_color: Color::rgb(0, 0, 0),
             default_bg_color: Color::rgb(255, 255, 255),
             default_link_color: Color::rgb(0, 102, 204),
             default_visited_color: Color::rgb(85, 26, 139),
             smooth_scroll: false,
-            scroll_line_px: 16,
+            scroll_tuning: ScrollTuning::default(),
             max_redirects: 5,
+            request_timeout: DEFAULT_TIMEOUT,
+            network_retries: DEFAULT_RETRIES,
             max_image_dimension: 480,
+            page_cache_entries: 4,
+            downloads_dir: "/downloads".to_string(),
+            bookmarks_path: Some("/home/.browser/bookmarks".to_string()),
+            known_hosts_path: Some("/home/.browser/gemini_known_hosts".to_string()),
+            history_path: Some("/home/.browser/history".to_string()),
+            max_history_entries: crate::nav::DEFAULT_MAX_VISITS,
             use_themed_chrome: true,
         }
     }
@@ -149,6 +220,26 @@ impl BrowserConfig {
         self.features.max_cache_mb * 1024 * 1024
     }
 
+    /// `text_scale` limited to [`MIN_TEXT_SCALE`]..=[`MAX_TEXT_SCALE`].
+    pub fn clamped_text_scale(&self) -> f32 {
+        if self.text_scale.is_nan() {
+            1.0
+        } else {
+            self.text_scale.clamp(MIN_TEXT_SCALE, MAX_TEXT_SCALE)
+        }
+    }
+
+    /// `reader_text_scale` limited to
+    /// [`MIN_READER_TEXT_SCALE`]..=[`MAX_READER_TEXT_SCALE`].
+    pub fn clamped_reader_text_scale(&self) -> f32 {
+        if self.reader_text_scale.is_nan() {
+            1.0
+        } else {
+            self.reader_text_scale
+                .clamp(MIN_READER_TEXT_SCALE, MAX_READER_TEXT_SCALE)
+        }
+    }
+
     /// Content area height (viewport minus chrome).
     pub fn content_height(&self, window_height: u32) -> u32 {
         window_height
@@ -176,10 +267,26 @@ mod tests {
         assert_eq!(cfg.status_bar_height, 14);
         assert_eq!(cfg.button_width, 20);
         assert!((cfg.default_font_size - 8.0).abs() < f32::EPSILON);
+        assert!((cfg.text_scale - 1.0).abs() < f32::EPSILON);
         assert_eq!(cfg.max_redirects, 5);
+        assert_eq!(cfg.request_timeout, Duration::from_secs(15));
+        assert_eq!(cfg.network_retries, 1);
         assert_eq!(cfg.max_image_dimension, 480);
+        assert_eq!(cfg.page_cache_entries, 4);
+        assert_eq!(cfg.downloads_dir, "/downloads");
+        assert_eq!(
+            cfg.bookmarks_path.as_deref(),
+            Some("/home/.browser/bookmarks")
+        );
+        assert_eq!(
+            cfg.known_hosts_path.as_deref(),
+            Some("/home/.browser/gemini_known_hosts")
+        );
+        assert_eq!(cfg.history_path.as_deref(), Some("/home/.browser/history"));
+        assert_eq!(cfg.max_history_entries, 500);
         assert!(!cfg.smooth_scroll);
-        assert_eq!(cfg.scroll_line_px, 16);
+        assert_eq!(cfg.scroll_tuning, ScrollTuning::default());
+        assert_eq!(cfg.scroll_tuning.line_step, crate::scroll::SCROLL_LINE);
     }
 
     #[test]
@@ -189,6 +296,23 @@ mod tests {
         assert_eq!(cfg.cache_size_bytes(), 2 * 1024 * 1024);
     }
 
+    #[test]
+    fn text_scale_is_clamped() {
+        let mut cfg = BrowserConfig {
+            text_scale: 10.0,
+            ..BrowserConfig::default()
+        };
+        assert_eq!(cfg.clamped_text_scale(), MAX_TEXT_SCALE);
+        cfg.text_scale = 0.1;
+        assert_eq!(cfg.clamped_text_scale(), MIN_TEXT_SCALE);
+        cfg.text_scale = f32::NAN;
+        assert_eq!(cfg.clamped_text_scale(), 1.0);
+        cfg.reader_text_scale = 5.0;
+        assert_eq!(cfg.clamped_reader_text_scale(), MAX_READER_TEXT_SCALE);
+        cfg.reader_text_scale = f32::NAN;
+        assert_eq!(cfg.clamped_reader_text_scale(), 1.0);
+    }
+
     #[test]
     fn content_height_calculation() {
         let cfg = BrowserConfig::default();
@@ -6,10 +6,10 @@
 
 use super::parser::{
     Combinator, CompoundSelector, CssValue, Declaration, Rule, SimpleSelector, Specificity,
-    Stylesheet,
+    Stylesheet, parse_inline_style,
 };
 use super::values::ComputedStyle;
-use crate::html::dom::{Document, ElementData, NodeId, NodeKind};
+use crate::html::dom::{Document, ElementData, NodeId, NodeKind, TagName};
 
 // -----------------------------------------------------------------------
 // Public API
@@ -29,6 +29,39 @@ pub fn style_tree(
     styles
 }
 
+/// Style a document with the user-agent stylesheet followed by the
+/// author styles it embeds (see [`author_styles`]).
+pub fn style_document(doc: &Document) -> Vec<Option<ComputedStyle>> {
+    let ua_sheet = default_stylesheet();
+    let (author_sheets, inline_styles) = author_styles(doc);
+    let mut sheets = vec![&ua_sheet];
+    sheets.extend(author_sheets.iter());
+    style_tree(doc, &sheets, &inline_styles)
+}
+
+/// Collect the author styles embedded in a document: one stylesheet
+/// per `<style>` element, in document order, and the declarations of
+/// every `style=""` attribute.
+pub fn author_styles(doc: &Document) -> (Vec<Stylesheet>, Vec<(NodeId, Vec<Declaration>)>) {
+    let mut sheets = Vec::new();
+    let mut inline = Vec::new();
+    for id in 0..doc.nodes.len() {
+        let Some(elem) = doc.element(id) else {
+            continue;
+        };
+        if elem.tag == TagName::Style {
+            sheets.push(Stylesheet::parse(&doc.text_content(id)));
+        }
+        if let Some(attr) = elem.get_attribute("style") {
+            let decls = parse_inline_style(attr);
+            if !decls.is_empty() {
+                inline.push((id, decls));
+            }
+        }
+    }
+    (sheets, inline)
+}
+
 /// Recursively compute styles depth-first so that children can inherit
 /// from their (already-computed) parent.
 fn style_subtree(
@@ -339,6 +372,11 @@ summary, blockquote, fieldset, form {
     display: block;
 }
 
+center {
+    display: block;
+    text-align: center;
+}
+
 p {
     display: block;
     margin-top: 1em;
@@ -394,9 +432,24 @@ ul, ol {
     margin-bottom: 1em;
     padding-left: 40px;
 }
+ul {
+    list-style-type: disc;
+}
+ol {
+    list-style-type: decimal;
+}
+ul ul, ol ul {
+    list-style-type: circle;
+}
+ul ul ul, ul ol ul, ol ul ul, ol ol ul {
+    list-style-type: square;
+}
+ul ul, ul ol, ol ul, ol ol {
+    margin-top: 0;
+    margin-bottom: 0;
+}
 li {
     display: list-item;
-    list-style-type: disc;
 }
 
 pre {
@@ -5,7 +5,7 @@
 //! single canonical representation (e.g. all lengths are resolved to `f32`
 //! pixels, all colors to `Color`).
 
-use super::parser::{CssColor, CssValue, LengthUnit};
+use super::parser::{CssColor, CssValue, LengthUnit, named_color};
 use oasis_types::backend::Color;
 
 /// Root font size in pixels. Standard CSS uses 16px but the OASIS
@@ -116,6 +116,23 @@ pub enum WhiteSpace {
     PreLine,
 }
 
+impl WhiteSpace {
+    /// Whether newlines in the source text break lines.
+    pub fn preserves_newlines(self) -> bool {
+        matches!(self, Self::Pre | Self::PreWrap | Self::PreLine)
+    }
+
+    /// Whether runs of spaces and tabs are kept as written.
+    pub fn preserves_spaces(self) -> bool {
+        matches!(self, Self::Pre | Self::PreWrap)
+    }
+
+    /// Whether lines wrap at the edge of the box.
+    pub fn wraps(self) -> bool {
+        !matches!(self, Self::Pre | Self::NoWrap)
+    }
+}
+
 /// CSS `list-style-type` property (subset).
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum ListStyleType {
@@ -764,30 +781,9 @@ fn css_color_to_backend(c: &CssColor) -> Color {
     Color::rgba(c.r, c.g, c.b, c.a)
 }
 
-/// Map a named CSS color keyword to an RGBA `Color`.
+/// Map a named CSS color keyword (case-insensitive) to an RGBA `Color`.
 fn keyword_color(name: &str) -> Option<Color> {
-    let c = match name {
-        "black" => Color::rgb(0, 0, 0),
-        "white" => Color::rgb(255, 255, 255),
-        "red" => Color::rgb(255, 0, 0),
-        "green" => Color::rgb(0, 128, 0),
-        "blue" => Color::rgb(0, 0, 255),
-        "yellow" => Color::rgb(255, 255, 0),
-        "cyan" | "aqua" => Color::rgb(0, 255, 255),
-        "magenta" | "fuchsia" => Color::rgb(255, 0, 255),
-        "gray" | "grey" => Color::rgb(128, 128, 128),
-        "silver" => Color::rgb(192, 192, 192),
-        "maroon" => Color::rgb(128, 0, 0),
-        "olive" => Color::rgb(128, 128, 0),
-        "lime" => Color::rgb(0, 255, 0),
-        "teal" => Color::rgb(0, 128, 128),
-        "navy" => Color::rgb(0, 0, 128),
-        "purple" => Color::rgb(128, 0, 128),
-        "orange" => Color::rgb(255, 165, 0),
-        "transparent" => Color::rgba(0, 0, 0, 0),
-        _ => return None,
-    };
-    Some(c)
+    named_color(name).map(|c| css_color_to_backend(&c))
 }
 
 /// Resolve a `border-style` keyword.
@@ -1080,4 +1076,37 @@ mod tests {
         assert_eq!(keyword_color("transparent"), Some(Color::rgba(0, 0, 0, 0)),);
         assert_eq!(keyword_color("nonexistent"), None);
     }
+
+    #[test]
+    fn extended_named_colors() {
+        assert_eq!(
+            keyword_color("rebeccapurple"),
+            Some(Color::rgb(102, 51, 153))
+        );
+        assert_eq!(
+            keyword_color("CornflowerBlue"),
+            Some(Color::rgb(100, 149, 237))
+        );
+        assert_eq!(keyword_color("TRANSPARENT"), Some(Color::rgba(0, 0, 0, 0)));
+        assert_eq!(
+            keyword_color("lightgoldenrodyellow"),
+            Some(Color::rgb(250, 250, 210))
+        );
+    }
+
+    #[test]
+    fn unknown_color_name_keeps_inherited_color() {
+        let mut s = ComputedStyle {
+            color: Color::rgb(1, 2, 3),
+            ..ComputedStyle::default()
+        };
+        s.apply_declaration("color", &CssValue::Keyword("notacolor".into()), 16.0);
+        assert_eq!(s.color, Color::rgb(1, 2, 3));
+        s.apply_declaration(
+            "background-color",
+            &CssValue::Keyword("Tomato".into()),
+            16.0,
+        );
+        assert_eq!(s.background_color, Color::rgb(255, 99, 71));
+    }
 }
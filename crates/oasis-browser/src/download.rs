@@ -0,0 +1,175 @@
+//! Download manager for content the browser cannot render.
+//!
+//! Responses with an unrenderable content type are held as a pending
+//! download; on confirmation the body is written into the configured
+//! downloads directory through the [`Vfs`] trait, choosing a
+//! collision-free name (`file.zip`, `file (1).zip`, ...).
+
+use oasis_types::error::Result;
+use oasis_vfs::Vfs;
+
+use crate::loader::{ContentType, ResourceResponse, Url};
+
+/// How many leading bytes to inspect when sniffing for binary data.
+const SNIFF_LEN: usize = 512;
+
+/// Whether a response should be offered as a download instead of
+/// being rendered.
+///
+/// Responses sent with `Content-Disposition: attachment` are always
+/// downloaded, and known document and image types otherwise rendered.
+/// `Unknown` responses are rendered as text unless the body looks
+/// binary (a NUL byte or invalid UTF-8 near the start), so
+/// extension-less text pages still display.
+pub fn should_download(response: &ResourceResponse) -> bool {
+    let attachment = response.header("content-disposition").is_some_and(|v| {
+        let kind = v.split(';').next().unwrap_or("").trim();
+        kind.eq_ignore_ascii_case("attachment")
+    });
+    if attachment {
+        return true;
+    }
+    match response.content_type {
+        ContentType::Unknown => looks_binary(&response.body),
+        _ => false,
+    }
+}
+
+/// Heuristic binary sniff over the first [`SNIFF_LEN`] bytes.
+pub fn looks_binary(body: &[u8]) -> bool {
+    let head = &body[..body.len().min(SNIFF_LEN)];
+    if head.contains(&0) {
+        return true;
+    }
+    match std::str::from_utf8(head) {
+        Ok(_) => false,
+        // A multi-byte sequence cut off by the sniff window is fine.
+        Err(e) => e.error_len().is_some(),
+    }
+}
+
+/// Derive a file name from the last path segment of `url`.
+pub fn file_name_for(url: &str) -> String {
+    let path = Url::parse(url).map(|u| u.path).unwrap_or_default();
+    match path.rsplit('/').next() {
+        Some(name) if !name.is_empty() => name.to_string(),
+        _ => "download".to_string(),
+    }
+}
+
+/// Pick a path in `dir` for `name` that does not exist yet, appending
+/// ` (1)`, ` (2)`, ... before the extension on collision.
+pub fn unique_path(vfs: &dyn Vfs, dir: &str, name: &str) -> String {
+    let dir = dir.trim_end_matches('/');
+    let candidate = format!("{dir}/{name}");
+    if !vfs.exists(&candidate) {
+        return candidate;
+    }
+    let (stem, ext) = match name.rfind('.') {
+        Some(i) if i > 0 => (&name[..i], &name[i..]),
+        _ => (name, ""),
+    };
+    let mut n = 1;
+    loop {
+        let candidate = format!("{dir}/{stem} ({n}){ext}");
+        if !vfs.exists(&candidate) {
+            return candidate;
+        }
+        n += 1;
+    }
+}
+
+/// Write the response body into `dir`, creating it if needed.
+///
+/// Returns the VFS path the file was saved to.
+pub fn save(vfs: &mut dyn Vfs, dir: &str, response: &ResourceResponse) -> Result<String> {
+    vfs.mkdir(dir)?;
+    let path = unique_path(vfs, dir, &file_name_for(&response.url));
+    vfs.write(&path, &response.body)?;
+    Ok(path)
+}
+
+/// Human-readable size for the confirm prompt (e.g. `"142 KB"`).
+pub fn format_size(bytes: usize) -> String {
+    if bytes < 1024 {
+        format!("{bytes} B")
+    } else if bytes < 1024 * 1024 {
+        format!("{} KB", bytes.div_ceil(1024))
+    } else {
+        format!("{:.1} MB", bytes as f64 / (1024.0 * 1024.0))
+    }
+}
+
+// -------------------------------------------------------------------
+// Tests
+// -------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::loader::mock;
+    use oasis_vfs::MemoryVfs;
+
+    #[test]
+    fn binary_unknown_is_downloaded() {
+        let mut resp = mock::response("vfs://a/f.zip", 200, ContentType::Unknown, "");
+        resp.body = vec![b'P', b'K', 3, 4, 0, 0];
+        assert!(should_download(&resp));
+    }
+
+    #[test]
+    fn text_unknown_is_rendered() {
+        let resp = mock::response("vfs://a/notes.md", 200, ContentType::Unknown, "# hi");
+        assert!(!should_download(&resp));
+        let html = mock::response("vfs://a/x.html", 200, ContentType::Html, "\0");
+        assert!(!should_download(&html));
+    }
+
+    #[test]
+    fn attachments_are_downloaded_whatever_their_type() {
+        let mut resp = mock::response("http://a/report.html", 200, ContentType::Html, "<p>");
+        resp.headers = vec![(
+            "Content-Disposition".to_string(),
+            "Attachment; filename=\"report.html\"".to_string(),
+        )];
+        assert!(should_download(&resp));
+        resp.headers[0].1 = "inline".to_string();
+        assert!(!should_download(&resp));
+    }
+
+    #[test]
+    fn file_name_from_url() {
+        assert_eq!(
+            file_name_for("http://example.com/files/photo.zip"),
+            "photo.zip"
+        );
+        assert_eq!(file_name_for("http://example.com/"), "download");
+        assert_eq!(file_name_for("not a url"), "download");
+    }
+
+    #[test]
+    fn save_uses_collision_safe_names() {
+        let mut vfs = MemoryVfs::new();
+        let resp = mock::response("gemini://x/file.zip", 200, ContentType::Unknown, "data");
+        assert_eq!(
+            save(&mut vfs, "/downloads", &resp).unwrap(),
+            "/downloads/file.zip"
+        );
+        assert_eq!(
+            save(&mut vfs, "/downloads", &resp).unwrap(),
+            "/downloads/file (1).zip"
+        );
+        assert_eq!(
+            save(&mut vfs, "/downloads/", &resp).unwrap(),
+            "/downloads/file (2).zip"
+        );
+        assert_eq!(vfs.read("/downloads/file (1).zip").unwrap(), b"data");
+    }
+
+    #[test]
+    fn format_sizes() {
+        assert_eq!(format_size(512), "512 B");
+        assert_eq!(format_size(142 * 1024), "142 KB");
+        assert_eq!(format_size(3 * 1024 * 1024 / 2), "1.5 MB");
+    }
+}
@@ -0,0 +1,146 @@
+//! Single-line editable text field.
+//!
+//! Shared by the find bar, Gemini input prompts and `<input type=text>`
+//! form controls so they all get the same editing keys: character insert,
+//! paste, Backspace, and Left/Right cursor movement. The URL bar uses the
+//! richer [`oasis_ui::text_input::TextInput`].
+
+use oasis_types::input::{Button, InputEvent};
+
+/// An editable text buffer with a cursor.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TextField {
+    /// Current contents.
+    pub text: String,
+    /// Cursor position within `text` (byte offset, on a char boundary).
+    pub cursor: usize,
+}
+
+impl TextField {
+    /// Create a field holding `text` with the cursor at the end.
+    pub fn new(text: &str) -> Self {
+        Self {
+            text: text.to_string(),
+            cursor: text.len(),
+        }
+    }
+
+    /// Replace the contents and move the cursor to the end.
+    pub fn set(&mut self, text: &str) {
+        self.text = text.to_string();
+        self.cursor = self.text.len();
+    }
+
+    /// Empty the field.
+    pub fn clear(&mut self) {
+        self.text.clear();
+        self.cursor = 0;
+    }
+
+    /// Whether the field is empty.
+    pub fn is_empty(&self) -> bool {
+        self.text.is_empty()
+    }
+
+    /// Number of characters before the cursor (for drawing the caret).
+    pub fn cursor_chars(&self) -> usize {
+        self.text[..self.cursor].chars().count()
+    }
+
+    /// Apply an editing event. Returns `true` if the event was an
+    /// editing key and was consumed.
+    pub fn handle_edit(&mut self, event: &InputEvent) -> bool {
+        match event {
+            InputEvent::TextInput(ch) => {
+                self.text.insert(self.cursor, *ch);
+                self.cursor += ch.len_utf8();
+            },
+            InputEvent::Paste(text) => {
+                // Single-line field: drop newlines and other controls.
+                let text: String = text.chars().filter(|c| !c.is_control()).collect();
+                self.text.insert_str(self.cursor, &text);
+                self.cursor += text.len();
+            },
+            InputEvent::Backspace => {
+                if let Some(prev) = self.prev_boundary() {
+                    self.text.remove(prev);
+                    self.cursor = prev;
+                }
+            },
+            InputEvent::ButtonPress(Button::Left) => {
+                if let Some(prev) = self.prev_boundary() {
+                    self.cursor = prev;
+                }
+            },
+            InputEvent::ButtonPress(Button::Right) => {
+                if let Some(c) = self.text[self.cursor..].chars().next() {
+                    self.cursor += c.len_utf8();
+                }
+            },
+            _ => return false,
+        }
+        true
+    }
+
+    /// Byte offset of the character before the cursor, if any.
+    fn prev_boundary(&self) -> Option<usize> {
+        self.text[..self.cursor]
+            .char_indices()
+            .next_back()
+            .map(|(i, _)| i)
+    }
+}
+
+// -------------------------------------------------------------------
+// Tests
+// -------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn insert_and_backspace_multibyte() {
+        let mut f = TextField::default();
+        for ch in "héllo".chars() {
+            assert!(f.handle_edit(&InputEvent::TextInput(ch)));
+        }
+        assert_eq!(f.text, "héllo");
+        f.handle_edit(&InputEvent::ButtonPress(Button::Left));
+        f.handle_edit(&InputEvent::ButtonPress(Button::Left));
+        f.handle_edit(&InputEvent::ButtonPress(Button::Left));
+        assert_eq!(f.cursor_chars(), 2);
+        f.handle_edit(&InputEvent::Backspace);
+        assert_eq!(f.text, "hllo");
+        assert_eq!(f.cursor, 1);
+    }
+
+    #[test]
+    fn paste_inserts_at_cursor() {
+        let mut f = TextField::new("vfs://index.html");
+        for _ in 0.."index.html".len() {
+            f.handle_edit(&InputEvent::ButtonPress(Button::Left));
+        }
+        assert!(f.handle_edit(&InputEvent::Paste("sites/\r\nhomé/".to_string())));
+        assert_eq!(f.text, "vfs://sites/homé/index.html");
+        assert_eq!(f.cursor_chars(), "vfs://sites/homé/".chars().count());
+    }
+
+    #[test]
+    fn cursor_stays_in_bounds() {
+        let mut f = TextField::new("ab");
+        f.handle_edit(&InputEvent::ButtonPress(Button::Right));
+        assert_eq!(f.cursor, 2);
+        f.clear();
+        f.handle_edit(&InputEvent::Backspace);
+        f.handle_edit(&InputEvent::ButtonPress(Button::Left));
+        assert_eq!(f.cursor, 0);
+    }
+
+    #[test]
+    fn non_edit_events_are_not_consumed() {
+        let mut f = TextField::new("x");
+        assert!(!f.handle_edit(&InputEvent::ButtonPress(Button::Confirm)));
+        assert_eq!(f.text, "x");
+    }
+}
@@ -0,0 +1,281 @@
+//! In-page text search (find bar).
+//!
+//! Matches are collected by walking the rendered text runs of the
+//! page's DOM text nodes in document order. Comparison is
+//! case-insensitive and may span several runs (e.g. a multi-word query
+//! that crosses a line break or an inline `<b>` boundary). Each match
+//! records the layout-space rectangles covering its glyphs so the
+//! paint pass can highlight them.
+
+use crate::field::TextField;
+use crate::layout::block::TextMeasurer;
+use crate::layout::box_model::{BoxType, LayoutBox, Rect};
+
+/// A single match of the find query.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FindMatch {
+    /// Layout-space rectangles covering the matched glyphs (one per
+    /// text run the match touches).
+    pub rects: Vec<Rect>,
+}
+
+impl FindMatch {
+    /// Bounding rectangle of the whole match.
+    pub fn bounds(&self) -> Rect {
+        self.rects
+            .iter()
+            .skip(1)
+            .fold(self.rects.first().copied().unwrap_or_default(), |acc, r| {
+                acc.union(r)
+            })
+    }
+}
+
+/// Find-bar editing and match state.
+#[derive(Debug, Clone, Default)]
+pub struct FindState {
+    /// Query editing buffer.
+    pub query: TextField,
+    /// Matches for the current query, in document order.
+    pub matches: Vec<FindMatch>,
+    /// Index of the current match within `matches`.
+    pub current: usize,
+}
+
+impl FindState {
+    /// The currently selected match, if any.
+    pub fn current_match(&self) -> Option<&FindMatch> {
+        self.matches.get(self.current)
+    }
+
+    /// Advance to the next match, wrapping at the end.
+    pub fn next(&mut self) {
+        if !self.matches.is_empty() {
+            self.current = (self.current + 1) % self.matches.len();
+        }
+    }
+
+    /// Step back to the previous match, wrapping at the start.
+    pub fn prev(&mut self) {
+        if !self.matches.is_empty() {
+            self.current = (self.current + self.matches.len() - 1) % self.matches.len();
+        }
+    }
+
+    /// Status text for the status bar, e.g. `"3/17 matches"`.
+    pub fn status_text(&self) -> String {
+        if self.query.is_empty() {
+            "Find".to_string()
+        } else if self.matches.is_empty() {
+            "No matches".to_string()
+        } else {
+            format!("{}/{} matches", self.current + 1, self.matches.len())
+        }
+    }
+
+    /// Clear the query and all matches.
+    pub fn clear(&mut self) {
+        *self = Self::default();
+    }
+}
+
+/// One character of the searchable text, mapped back to its run.
+struct SearchChar {
+    /// Lower-cased character used for comparison.
+    lower: char,
+    /// Index into the run list, or `None` for a synthetic separator.
+    run: Option<usize>,
+    /// Byte offset of the character within the run's text.
+    offset: usize,
+    /// Byte length of the character.
+    len: usize,
+}
+
+/// Collect all case-insensitive matches of `query` in the layout tree.
+pub fn find_matches(root: &LayoutBox, query: &str, measurer: &dyn TextMeasurer) -> Vec<FindMatch> {
+    let needle: Vec<char> = query.chars().map(lower_char).collect();
+    if needle.is_empty() {
+        return Vec::new();
+    }
+
+    let mut runs: Vec<&LayoutBox> = Vec::new();
+    collect_text_runs(root, &mut runs);
+
+    // Flatten the runs into one searchable character sequence. Runs on
+    // different lines that do not end in whitespace (block boundaries)
+    // get a separator so matches cannot bridge two paragraphs.
+    let mut chars: Vec<SearchChar> = Vec::new();
+    for (i, run) in runs.iter().enumerate() {
+        let text = run.text.as_deref().unwrap_or("");
+        if let Some(prev) = i.checked_sub(1).map(|p| runs[p]) {
+            let prev_text = prev.text.as_deref().unwrap_or("");
+            let same_line = prev.dimensions.content.y == run.dimensions.content.y;
+            if !same_line && !prev_text.ends_with(char::is_whitespace) {
+                chars.push(SearchChar {
+                    lower: '\n',
+                    run: None,
+                    offset: 0,
+                    len: 0,
+                });
+            }
+        }
+        for (offset, ch) in text.char_indices() {
+            chars.push(SearchChar {
+                lower: lower_char(ch),
+                run: Some(i),
+                offset,
+                len: ch.len_utf8(),
+            });
+        }
+    }
+
+    let mut matches = Vec::new();
+    let mut start = 0;
+    while start + needle.len() <= chars.len() {
+        let hit = chars[start..start + needle.len()]
+            .iter()
+            .zip(&needle)
+            .all(|(c, n)| c.lower == *n);
+        if hit {
+            let span = &chars[start..start + needle.len()];
+            matches.push(FindMatch {
+                rects: match_rects(span, &runs, measurer),
+            });
+            start += needle.len();
+        } else {
+            start += 1;
+        }
+    }
+    matches
+}
+
+/// Compute one rectangle per run touched by a matched character span.
+fn match_rects(span: &[SearchChar], runs: &[&LayoutBox], measurer: &dyn TextMeasurer) -> Vec<Rect> {
+    let mut rects = Vec::new();
+    let mut i = 0;
+    while i < span.len() {
+        let Some(run_idx) = span[i].run else {
+            i += 1;
+            continue;
+        };
+        let begin = span[i].offset;
+        let mut end = begin + span[i].len;
+        while i + 1 < span.len() && span[i + 1].run == Some(run_idx) {
+            i += 1;
+            end = span[i].offset + span[i].len;
+        }
+        i += 1;
+
+        let run = runs[run_idx];
+        let text = run.text.as_deref().unwrap_or("");
+        let font_size = run.style.font_size as u16;
+        let content = &run.dimensions.content;
+        let x = content.x + measurer.measure_text(&text[..begin], font_size) as f32;
+        let width = measurer.measure_text(&text[begin..end], font_size) as f32;
+        rects.push(Rect::new(x, content.y, width, content.height));
+    }
+    rects
+}
+
+/// Gather inline text boxes in document order.
+fn collect_text_runs<'a>(lb: &'a LayoutBox, out: &mut Vec<&'a LayoutBox>) {
+    if matches!(lb.box_type, BoxType::Inline) && lb.text.is_some() {
+        out.push(lb);
+    }
+    for child in &lb.children {
+        collect_text_runs(child, out);
+    }
+}
+
+/// Simple case folding: the first character of the lowercase mapping.
+fn lower_char(ch: char) -> char {
+    ch.to_lowercase().next().unwrap_or(ch)
+}
+
+// -------------------------------------------------------------------
+// Tests
+// -------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::css::values::ComputedStyle;
+
+    fn text_box(text: &str, x: f32, y: f32) -> LayoutBox {
+        let mut lb = LayoutBox::new(BoxType::Inline, ComputedStyle::default(), None);
+        lb.text = Some(text.to_string());
+        lb.dimensions.content = Rect::new(x, y, 40.0, 12.0);
+        lb
+    }
+
+    fn root(children: Vec<LayoutBox>) -> LayoutBox {
+        let mut lb = LayoutBox::new(BoxType::Block, ComputedStyle::default(), None);
+        lb.children = children;
+        lb
+    }
+
+    #[test]
+    fn finds_case_insensitive_matches() {
+        let tree = root(vec![
+            text_box("Hello ", 0.0, 0.0),
+            text_box("world, ", 30.0, 0.0),
+            text_box("HELLO", 70.0, 0.0),
+        ]);
+        let matches = find_matches(&tree, "hello", &crate::SimpleTextMeasurer);
+        assert_eq!(matches.len(), 2);
+        assert_eq!(matches[0].rects[0].x, 0.0);
+        assert_eq!(matches[1].rects[0].x, 70.0);
+    }
+
+    #[test]
+    fn match_can_span_runs() {
+        let tree = root(vec![text_box("big ", 0.0, 0.0), text_box("cat", 0.0, 12.0)]);
+        let matches = find_matches(&tree, "big cat", &crate::SimpleTextMeasurer);
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].rects.len(), 2);
+    }
+
+    #[test]
+    fn match_does_not_bridge_blocks() {
+        // "end" and "start" sit on different lines with no whitespace
+        // between them, i.e. separate paragraphs.
+        let tree = root(vec![
+            text_box("end", 0.0, 0.0),
+            text_box("start", 0.0, 20.0),
+        ]);
+        assert!(find_matches(&tree, "endstart", &crate::SimpleTextMeasurer).is_empty());
+    }
+
+    #[test]
+    fn match_rect_offsets_into_run() {
+        let tree = root(vec![text_box("abcdef", 10.0, 5.0)]);
+        let m = &find_matches(&tree, "cd", &crate::SimpleTextMeasurer)[0];
+        let prefix = crate::SimpleTextMeasurer.measure_text("ab", 8) as f32;
+        assert_eq!(m.rects[0].x, 10.0 + prefix);
+        assert_eq!(m.rects[0].y, 5.0);
+    }
+
+    #[test]
+    fn empty_query_has_no_matches() {
+        let tree = root(vec![text_box("anything", 0.0, 0.0)]);
+        assert!(find_matches(&tree, "", &crate::SimpleTextMeasurer).is_empty());
+    }
+
+    #[test]
+    fn state_cycles_and_reports_status() {
+        let mut state = FindState {
+            query: TextField::new("x"),
+            matches: vec![FindMatch { rects: Vec::new() }; 3],
+            ..FindState::default()
+        };
+        assert_eq!(state.status_text(), "1/3 matches");
+        state.next();
+        state.next();
+        state.next();
+        assert_eq!(state.current, 0);
+        state.prev();
+        assert_eq!(state.status_text(), "3/3 matches");
+        state.matches.clear();
+        assert_eq!(state.status_text(), "No matches");
+    }
+}
@@ -0,0 +1,323 @@
+//! HTML form submission.
+//!
+//! Only `method=get` forms are supported: the successful controls of
+//! the form are serialized as `application/x-www-form-urlencoded`
+//! pairs into the query string of the form's `action` URL (resolved
+//! against the page URL). Values typed by the user are kept by the
+//! widget in a `NodeId -> String` map and take precedence over the
+//! `value` attributes in the DOM. No cookies are sent or stored.
+
+use std::collections::HashMap;
+
+use crate::html::dom::{Document, ElementData, NodeId, TagName};
+use crate::layout::block::input_type;
+use crate::loader::Url;
+
+/// HTTP method declared by a form's `method` attribute.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FormMethod {
+    Get,
+    Post,
+}
+
+/// A form submission ready to be navigated to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FormSubmission {
+    /// Declared method of the submitted form.
+    pub method: FormMethod,
+    /// Target URL, including the encoded query for GET forms.
+    pub url: String,
+}
+
+/// Find the nearest ancestor `<form>` of `node`.
+pub fn form_for(doc: &Document, node: NodeId) -> Option<NodeId> {
+    let mut current = doc.get(node).parent;
+    while let Some(id) = current {
+        if doc.element(id).is_some_and(|e| e.tag == TagName::Form) {
+            return Some(id);
+        }
+        current = doc.get(id).parent;
+    }
+    None
+}
+
+/// Whether activating `node` submits its form (a submit `<input>` or a
+/// `<button>` that is not `type=button`/`type=reset`).
+pub fn is_submit_button(doc: &Document, node: NodeId) -> bool {
+    match doc.element(node) {
+        Some(e) if e.tag == TagName::Input => input_type(e) == "submit",
+        Some(e) if e.tag == TagName::Button => {
+            !matches!(button_type(e).as_str(), "button" | "reset")
+        },
+        _ => false,
+    }
+}
+
+/// Build the submission for the form owning `control`.
+///
+/// `submitter` is the button that triggered the submission (included
+/// in the data set when named), or `None` for implicit submission by
+/// pressing Confirm in a text field. Returns `None` when `control` is
+/// not inside a `<form>` or the action cannot be resolved.
+pub fn build_submission(
+    doc: &Document,
+    control: NodeId,
+    submitter: Option<NodeId>,
+    values: &HashMap<NodeId, String>,
+    page_url: &str,
+) -> Option<FormSubmission> {
+    let form_id = form_for(doc, control)?;
+    let form = doc.element(form_id)?;
+
+    let method = match form.get_attribute("method") {
+        Some(m) if m.trim().eq_ignore_ascii_case("post") => FormMethod::Post,
+        _ => FormMethod::Get,
+    };
+
+    let base = Url::parse(page_url)?;
+    let mut target = match form.get_attribute("action") {
+        Some(action) => base.resolve(action)?,
+        None => base,
+    };
+    target.fragment = None;
+    if method == FormMethod::Get {
+        let pairs = collect_form_data(doc, form_id, submitter, values);
+        target.query = Some(urlencode(&pairs));
+    }
+
+    Some(FormSubmission {
+        method,
+        url: target.to_string(),
+    })
+}
+
+/// Collect the `(name, value)` pairs of the successful controls inside
+/// `form`, in document order.
+pub fn collect_form_data(
+    doc: &Document,
+    form: NodeId,
+    submitter: Option<NodeId>,
+    values: &HashMap<NodeId, String>,
+) -> Vec<(String, String)> {
+    let mut pairs = Vec::new();
+    collect_controls(doc, form, submitter, values, &mut pairs);
+    pairs
+}
+
+fn collect_controls(
+    doc: &Document,
+    node: NodeId,
+    submitter: Option<NodeId>,
+    values: &HashMap<NodeId, String>,
+    out: &mut Vec<(String, String)>,
+) {
+    for &child in &doc.get(node).children {
+        if let Some(elem) = doc.element(child)
+            && elem.get_attribute("disabled").is_none()
+            && let Some(name) = elem.get_attribute("name").filter(|n| !n.is_empty())
+            && let Some(value) = control_value(doc, child, elem, submitter, values)
+        {
+            out.push((name.to_string(), value));
+        }
+        collect_controls(doc, child, submitter, values, out);
+    }
+}
+
+/// The submitted value of a single control, or `None` if the control
+/// is not successful (unchecked box, non-submitting button, ...).
+fn control_value(
+    doc: &Document,
+    node: NodeId,
+    elem: &ElementData,
+    submitter: Option<NodeId>,
+    values: &HashMap<NodeId, String>,
+) -> Option<String> {
+    let attr_value = || elem.get_attribute("value").unwrap_or("").to_string();
+    match elem.tag {
+        TagName::Input => match input_type(elem).as_str() {
+            "submit" | "button" | "reset" | "image" | "file" => {
+                (submitter == Some(node)).then(attr_value)
+            },
+            "checkbox" | "radio" => elem
+                .get_attribute("checked")
+                .map(|_| elem.get_attribute("value").unwrap_or("on").to_string()),
+            _ => Some(values.get(&node).cloned().unwrap_or_else(attr_value)),
+        },
+        TagName::Button => (submitter == Some(node)).then(attr_value),
+        TagName::Textarea => Some(
+            values
+                .get(&node)
+                .cloned()
+                .unwrap_or_else(|| doc.text_content(node)),
+        ),
+        TagName::Select => select_value(doc, node),
+        _ => None,
+    }
+}
+
+/// The value of a `<select>`: the first `selected` option, or the
+/// first option if none is selected.
+fn select_value(doc: &Document, select: NodeId) -> Option<String> {
+    let mut options = Vec::new();
+    collect_options(doc, select, &mut options);
+    let chosen = options
+        .iter()
+        .copied()
+        .find(|&id| {
+            doc.element(id)
+                .and_then(|e| e.get_attribute("selected"))
+                .is_some()
+        })
+        .or_else(|| options.first().copied())?;
+    let elem = doc.element(chosen)?;
+    Some(match elem.get_attribute("value") {
+        Some(v) => v.to_string(),
+        None => doc.text_content(chosen).trim().to_string(),
+    })
+}
+
+fn collect_options(doc: &Document, node: NodeId, out: &mut Vec<NodeId>) {
+    for &child in &doc.get(node).children {
+        if doc.element(child).is_some_and(|e| e.tag == TagName::Option) {
+            out.push(child);
+        } else {
+            collect_options(doc, child, out);
+        }
+    }
+}
+
+/// The lower-cased `type` of a `<button>`, defaulting to `"submit"`.
+fn button_type(elem: &ElementData) -> String {
+    elem.get_attribute("type")
+        .map(|t| t.trim().to_ascii_lowercase())
+        .unwrap_or_else(|| "submit".to_string())
+}
+
+/// Encode pairs as `application/x-www-form-urlencoded`.
+pub fn urlencode(pairs: &[(String, String)]) -> String {
+    pairs
+        .iter()
+        .map(|(k, v)| format!("{}={}", encode_component(k), encode_component(v)))
+        .collect::<Vec<_>>()
+        .join("&")
+}
+
+/// Percent-encode one name or value; spaces become `+`.
+fn encode_component(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for &b in s.as_bytes() {
+        match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'*' => {
+                out.push(b as char)
+            },
+            b' ' => out.push('+'),
+            _ => out.push_str(&format!("%{b:02X}")),
+        }
+    }
+    out
+}
+
+// -------------------------------------------------------------------
+// Tests
+// -------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::html::tokenizer::Tokenizer;
+    use crate::html::tree_builder::TreeBuilder;
+
+    fn parse(html: &str) -> Document {
+        TreeBuilder::build(Tokenizer::new(html).tokenize())
+    }
+
+    fn find_tag(doc: &Document, tag: TagName, nth: usize) -> NodeId {
+        (0..doc.nodes.len())
+            .filter(|&id| doc.element(id).is_some_and(|e| e.tag == tag))
+            .nth(nth)
+            .expect("element not found")
+    }
+
+    #[test]
+    fn encodes_reserved_characters() {
+        let pairs = vec![
+            ("q".to_string(), "a b&c=d".to_string()),
+            ("ü".to_string(), "x/y".to_string()),
+        ];
+        assert_eq!(urlencode(&pairs), "q=a+b%26c%3Dd&%C3%BC=x%2Fy");
+    }
+
+    #[test]
+    fn get_submission_uses_action_and_values() {
+        let doc = parse(
+            r#"<form action="/search#top"><input name="q" value="old">
+            <input type="hidden" name="lang" value="en">
+            <input type="checkbox" name="safe">
+            <input type="submit" name="go" value="Go"></form>"#,
+        );
+        let text = find_tag(&doc, TagName::Input, 0);
+        let submit = find_tag(&doc, TagName::Input, 3);
+        let mut values = HashMap::new();
+        values.insert(text, "rust lang".to_string());
+
+        let sub = build_submission(
+            &doc,
+            submit,
+            Some(submit),
+            &values,
+            "http://example.com/dir/page.html?x=1",
+        )
+        .unwrap();
+        assert_eq!(sub.method, FormMethod::Get);
+        assert_eq!(
+            sub.url,
+            "http://example.com/search?q=rust+lang&lang=en&go=Go"
+        );
+
+        // Implicit submission leaves the button out.
+        let sub =
+            build_submission(&doc, text, None, &HashMap::new(), "http://example.com/").unwrap();
+        assert_eq!(sub.url, "http://example.com/search?q=old&lang=en");
+    }
+
+    #[test]
+    fn missing_action_submits_to_page_url() {
+        let doc = parse(r#"<form method="POST"><input name="a" value="1"></form>"#);
+        let input = find_tag(&doc, TagName::Input, 0);
+        let sub =
+            build_submission(&doc, input, None, &HashMap::new(), "vfs://sites/a.html").unwrap();
+        assert_eq!(sub.method, FormMethod::Post);
+        assert_eq!(sub.url, "vfs://sites/a.html");
+    }
+
+    #[test]
+    fn controls_outside_a_form_do_not_submit() {
+        let doc = parse(r#"<p><input name="a"><button>Go</button></p>"#);
+        let button = find_tag(&doc, TagName::Button, 0);
+        assert!(is_submit_button(&doc, button));
+        assert!(
+            build_submission(&doc, button, Some(button), &HashMap::new(), "http://x/").is_none()
+        );
+    }
+
+    #[test]
+    fn select_and_textarea_values() {
+        let doc = parse(
+            r#"<form><select name="s"><option value="a">A</option>
+            <option selected>B</option></select>
+            <textarea name="t">hi</textarea>
+            <button type="button" name="b">x</button></form>"#,
+        );
+        let form = find_tag(&doc, TagName::Form, 0);
+        let button = find_tag(&doc, TagName::Button, 0);
+        assert!(!is_submit_button(&doc, button));
+        let pairs = collect_form_data(&doc, form, None, &HashMap::new());
+        assert_eq!(
+            pairs,
+            vec![
+                ("s".to_string(), "B".to_string()),
+                ("t".to_string(), "hi".to_string())
+            ]
+        );
+    }
+}
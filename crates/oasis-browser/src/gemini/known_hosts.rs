@@ -0,0 +1,331 @@
+//! Trust-on-first-use certificate pinning for Gemini hosts.
+//!
+//! Gemini servers mostly use self-signed certificates, so instead of a
+//! CA chain the browser remembers the certificate fingerprint each
+//! host presented the first time and checks it on later visits. A
+//! changed fingerprint is held as *pending* until the user accepts or
+//! rejects it from the warning page.
+//!
+//! The store is persisted to a VFS file with a comment header followed
+//! by one `host<TAB>fingerprint<TAB>expiry` line per host, where the
+//! expiry is seconds since the Unix epoch or `-` if unknown.
+
+use std::collections::{BTreeMap, HashMap};
+
+use oasis_types::error::Result;
+use oasis_types::tls::PeerCertificate;
+use oasis_vfs::Vfs;
+
+/// First line written to a known-hosts file.
+const KNOWN_HOSTS_HEADER: &str = "# OASIS Gemini known hosts";
+
+/// Link on the warning page that accepts a changed certificate.
+pub const TOFU_ACCEPT_URL: &str = "about:tofu-accept";
+
+/// Link on the warning page that rejects a changed certificate.
+pub const TOFU_ABORT_URL: &str = "about:tofu-abort";
+
+/// A pinned certificate.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KnownHost {
+    /// SHA-256 fingerprint of the certificate, as lowercase hex.
+    pub fingerprint: String,
+    /// When the certificate expires, in seconds since the Unix epoch.
+    pub expires: Option<u64>,
+}
+
+impl From<&PeerCertificate> for KnownHost {
+    fn from(cert: &PeerCertificate) -> Self {
+        Self {
+            fingerprint: cert.fingerprint.clone(),
+            expires: cert.not_after,
+        }
+    }
+}
+
+/// Outcome of checking a presented certificate.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TofuCheck {
+    /// The host was unknown (or its pin had expired); the certificate
+    /// is now pinned.
+    FirstUse,
+    /// The certificate matches the pin.
+    Trusted,
+    /// The certificate differs from the pin, whose fingerprint is
+    /// given. The new certificate is held as pending.
+    Changed { expected: String },
+}
+
+/// Pinned certificates by host.
+#[derive(Debug, Clone, Default)]
+pub struct KnownHosts {
+    hosts: BTreeMap<String, KnownHost>,
+    /// Changed certificates awaiting the user's decision.
+    pending: HashMap<String, KnownHost>,
+    /// Whether pins changed since the last load or save.
+    dirty: bool,
+}
+
+impl KnownHosts {
+    /// Create an empty store.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The pin for `host`, if any.
+    pub fn get(&self, host: &str) -> Option<&KnownHost> {
+        self.hosts.get(host)
+    }
+
+    /// Number of pinned hosts.
+    pub fn len(&self) -> usize {
+        self.hosts.len()
+    }
+
+    /// Whether no hosts are pinned.
+    pub fn is_empty(&self) -> bool {
+        self.hosts.is_empty()
+    }
+
+    /// Whether pins changed since the last load or save.
+    pub fn is_dirty(&self) -> bool {
+        self.dirty
+    }
+
+    /// Check the certificate `host` presented at time `now`, pinning
+    /// it silently if the host is new or its pin has expired.
+    pub fn check(&mut self, host: &str, cert: &PeerCertificate, now: u64) -> TofuCheck {
+        match self.hosts.get(host) {
+            Some(known) if known.fingerprint == cert.fingerprint => TofuCheck::Trusted,
+            Some(known) if known.expires.is_none_or(|t| t > now) => {
+                let expected = known.fingerprint.clone();
+                self.pending.insert(host.to_string(), KnownHost::from(cert));
+                TofuCheck::Changed { expected }
+            },
+            _ => {
+                self.hosts.insert(host.to_string(), KnownHost::from(cert));
+                self.dirty = true;
+                TofuCheck::FirstUse
+            },
+        }
+    }
+
+    /// Pin the pending certificate for `host` in place of the old one.
+    /// Returns `false` if no change is pending for `host`.
+    pub fn accept_pending(&mut self, host: &str) -> bool {
+        match self.pending.remove(host) {
+            Some(known) => {
+                self.hosts.insert(host.to_string(), known);
+                self.dirty = true;
+                true
+            },
+            None => false,
+        }
+    }
+
+    /// Drop the pending certificate for `host`, keeping the old pin.
+    pub fn reject_pending(&mut self, host: &str) {
+        self.pending.remove(host);
+    }
+
+    /// Forget the pin for `host`.
+    pub fn remove(&mut self, host: &str) {
+        if self.hosts.remove(host).is_some() {
+            self.dirty = true;
+        }
+    }
+
+    /// Write the pins to `path` in the VFS, creating the parent
+    /// directory if needed.
+    pub fn save(&mut self, vfs: &mut dyn Vfs, path: &str) -> Result<()> {
+        if let Some((parent, _)) = path.rsplit_once('/')
+            && !parent.is_empty()
+            && !vfs.exists(parent)
+        {
+            vfs.mkdir(parent)?;
+        }
+        let mut out = String::from(KNOWN_HOSTS_HEADER);
+        out.push('\n');
+        for (host, known) in &self.hosts {
+            let expires = known
+                .expires
+                .map_or_else(|| "-".to_string(), |t| t.to_string());
+            out.push_str(&format!("{host}\t{}\t{expires}\n", known.fingerprint));
+        }
+        vfs.write(path, out.as_bytes())?;
+        self.dirty = false;
+        Ok(())
+    }
+
+    /// Replace the pins with the contents of `path`.
+    ///
+    /// A missing file leaves the store untouched. Malformed lines are
+    /// skipped.
+    pub fn load(&mut self, vfs: &dyn Vfs, path: &str) -> Result<()> {
+        if !vfs.exists(path) {
+            return Ok(());
+        }
+        let data = vfs.read(path)?;
+        let text = String::from_utf8_lossy(&data);
+        let mut hosts = BTreeMap::new();
+        for line in text.lines() {
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let mut fields = line.split('\t');
+            let (Some(host), Some(fingerprint), Some(expires), None) =
+                (fields.next(), fields.next(), fields.next(), fields.next())
+            else {
+                log::warn!("skipping malformed known-hosts line in {path}");
+                continue;
+            };
+            let expires = match expires {
+                "-" => None,
+                t => match t.parse() {
+                    Ok(t) => Some(t),
+                    Err(_) => {
+                        log::warn!("skipping malformed known-hosts line in {path}");
+                        continue;
+                    },
+                },
+            };
+            hosts.insert(
+                host.to_string(),
+                KnownHost {
+                    fingerprint: fingerprint.to_string(),
+                    expires,
+                },
+            );
+        }
+        self.hosts = hosts;
+        self.dirty = false;
+        Ok(())
+    }
+}
+
+/// The `host` parameter of an `about:tofu-*` link, if `href` is a link
+/// to `base` (one of [`TOFU_ACCEPT_URL`] and [`TOFU_ABORT_URL`]).
+pub fn tofu_link_host<'a>(href: &'a str, base: &str) -> Option<&'a str> {
+    href.strip_prefix(base)?
+        .strip_prefix('?')?
+        .split('&')
+        .find_map(|param| param.strip_prefix("host="))
+}
+
+/// The warning page shown when `host` presents a certificate other
+/// than the pinned one.
+pub fn certificate_changed_page(host: &str, expected: &str, presented: &str) -> String {
+    format!(
+        "<html><head><title>Certificate changed</title></head><body>\
+         <h1>Certificate changed</h1>\
+         <p>{host} presented a different certificate from the one seen \
+         on earlier visits. The site may have replaced its certificate, \
+         or someone may be intercepting the connection.</p>\
+         <p>Known: {expected}</p>\
+         <p>Presented: {presented}</p>\
+         <p><a href=\"{TOFU_ACCEPT_URL}?host={host}\">Accept new certificate</a></p>\
+         <p><a href=\"{TOFU_ABORT_URL}?host={host}\">Abort</a></p>\
+         </body></html>"
+    )
+}
+
+// -------------------------------------------------------------------
+// Tests
+// -------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use oasis_vfs::MemoryVfs;
+
+    fn cert(fingerprint: &str, not_after: Option<u64>) -> PeerCertificate {
+        PeerCertificate {
+            fingerprint: fingerprint.to_string(),
+            not_after,
+        }
+    }
+
+    #[test]
+    fn first_use_pins_and_mismatch_is_held_pending() {
+        let mut known = KnownHosts::new();
+        assert_eq!(
+            known.check("gem.example", &cert("aa", Some(100)), 10),
+            TofuCheck::FirstUse
+        );
+        assert!(known.is_dirty());
+        assert_eq!(
+            known.check("gem.example", &cert("aa", Some(100)), 20),
+            TofuCheck::Trusted
+        );
+        assert_eq!(
+            known.check("gem.example", &cert("bb", Some(200)), 30),
+            TofuCheck::Changed {
+                expected: "aa".to_string()
+            }
+        );
+        assert_eq!(known.get("gem.example").unwrap().fingerprint, "aa");
+
+        assert!(known.accept_pending("gem.example"));
+        assert!(!known.accept_pending("gem.example"));
+        assert_eq!(
+            known.get("gem.example"),
+            Some(&KnownHost {
+                fingerprint: "bb".to_string(),
+                expires: Some(200)
+            })
+        );
+    }
+
+    #[test]
+    fn expired_pin_is_replaced_silently() {
+        let mut known = KnownHosts::new();
+        known.check("gem.example", &cert("aa", Some(100)), 10);
+        assert_eq!(
+            known.check("gem.example", &cert("bb", None), 100),
+            TofuCheck::FirstUse
+        );
+        assert_eq!(known.get("gem.example").unwrap().fingerprint, "bb");
+    }
+
+    #[test]
+    fn save_and_load_round_trip() {
+        let mut vfs = MemoryVfs::new();
+        let mut known = KnownHosts::new();
+        known.check("b.example", &cert("bb", None), 0);
+        known.check("a.example:1966", &cert("aa", Some(42)), 0);
+        known.save(&mut vfs, "/home/.browser/known").unwrap();
+        assert!(!known.is_dirty());
+
+        let text = String::from_utf8(vfs.read("/home/.browser/known").unwrap()).unwrap();
+        assert_eq!(
+            text,
+            "# OASIS Gemini known hosts\na.example:1966\taa\t42\nb.example\tbb\t-\n"
+        );
+
+        vfs.write(
+            "/home/.browser/known",
+            format!("{text}junk\nc\tcc\tsoon\n").as_bytes(),
+        )
+        .unwrap();
+        let mut loaded = KnownHosts::new();
+        loaded.load(&vfs, "/home/.browser/known").unwrap();
+        assert_eq!(loaded.len(), 2);
+        assert_eq!(loaded.get("a.example:1966"), known.get("a.example:1966"));
+        assert_eq!(loaded.get("b.example").unwrap().expires, None);
+    }
+
+    #[test]
+    fn tofu_links_carry_the_host() {
+        let page = certificate_changed_page("gem.example", "aa", "bb");
+        assert!(page.contains("href=\"about:tofu-accept?host=gem.example\""));
+        assert_eq!(
+            tofu_link_host("about:tofu-accept?host=gem.example", TOFU_ACCEPT_URL),
+            Some("gem.example")
+        );
+        assert_eq!(
+            tofu_link_host("about:tofu-abort?host=gem.example", TOFU_ACCEPT_URL),
+            None
+        );
+        assert_eq!(tofu_link_host("about:tofu-abort", TOFU_ABORT_URL), None);
+    }
+}
@@ -3,9 +3,12 @@
 //! Gemini is a lightweight protocol with mandatory TLS, single-line
 //! requests, and a simple text-based content format (text/gemini).
 
+pub mod known_hosts;
 pub mod parser;
 pub mod renderer;
 
+use crate::field::TextField;
+
 /// Gemini response status codes.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum GeminiStatus {
@@ -46,17 +49,37 @@ impl GeminiStatus {
     pub fn is_redirect(&self) -> bool {
         matches!(self, GeminiStatus::Redirect(_))
     }
+
+    /// The numeric status code.
+    pub fn code(&self) -> u8 {
+        match *self {
+            GeminiStatus::Input(c)
+            | GeminiStatus::Success(c)
+            | GeminiStatus::Redirect(c)
+            | GeminiStatus::TemporaryFailure(c)
+            | GeminiStatus::PermanentFailure(c)
+            | GeminiStatus::ClientCert(c) => c,
+        }
+    }
 }
 
 /// A parsed Gemini response.
-#[derive(Debug, Clone)]
-pub struct GeminiResponse {
-    /// Status code category.
-    pub status: GeminiStatus,
-    /// MIME type for success, URL for redirect, message for errors.
-    pub meta: String,
-    /// Response body (present only on success).
-    pub body: Option<String>,
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GeminiResponse {
+    /// 20-29: a body of the given MIME type (the meta line). Non-text
+    /// MIME types may carry arbitrary binary data.
+    Success { mime: String, body: Vec<u8> },
+    /// 10-11: the server asks the user for a line of input, to be sent
+    /// back as the query string (see [`input_url`]). Status 11 marks
+    /// sensitive input such as a password.
+    Input { prompt: String, sensitive: bool },
+    /// 30-39: redirect to `url` (possibly relative).
+    Redirect { url: String },
+    /// 40-69: failure, with the server's message.
+    Failure {
+        status: GeminiStatus,
+        message: String,
+    },
 }
 
 /// Parse a raw Gemini response (status line + optional body).
@@ -64,9 +87,8 @@ pub struct GeminiResponse {
 /// The response format is: `<STATUS><SPACE><META>\r\n[body]`
 /// where STATUS is a two-digit code.
 pub fn parse_response(data: &[u8]) -> Option<GeminiResponse> {
-    let text = std::str::from_utf8(data).ok()?;
-    let first_line_end = text.find("\r\n")?;
-    let status_line = &text[..first_line_end];
+    let first_line_end = data.windows(2).position(|w| w == b"\r\n")?;
+    let status_line = std::str::from_utf8(&data[..first_line_end]).ok()?;
 
     if status_line.len() < 2 {
         return None;
@@ -79,15 +101,52 @@ pub fn parse_response(data: &[u8]) -> Option<GeminiResponse> {
         String::new()
     };
 
-    let status = GeminiStatus::from_code(code);
-
-    let body = if status.is_success() && text.len() > first_line_end + 2 {
-        Some(text[first_line_end + 2..].to_string())
-    } else {
-        None
+    let response = match GeminiStatus::from_code(code) {
+        GeminiStatus::Success(_) => GeminiResponse::Success {
+            mime: meta,
+            body: data[first_line_end + 2..].to_vec(),
+        },
+        GeminiStatus::Input(code) => GeminiResponse::Input {
+            prompt: meta,
+            sensitive: code == 11,
+        },
+        GeminiStatus::Redirect(_) => GeminiResponse::Redirect { url: meta },
+        status => GeminiResponse::Failure {
+            status,
+            message: meta,
+        },
     };
+    Some(response)
+}
+
+/// The URL to request after the user answers an input prompt for
+/// `url`: any existing query is replaced by the percent-encoded input.
+pub fn input_url(url: &str, input: &str) -> String {
+    let base = url.split(['?', '#']).next().unwrap_or(url);
+    let mut out = format!("{base}?");
+    for &b in input.as_bytes() {
+        match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                out.push(b as char)
+            },
+            _ => out.push_str(&format!("%{b:02X}")),
+        }
+    }
+    out
+}
 
-    Some(GeminiResponse { status, meta, body })
+/// An open input prompt: the user's answer is sent back to `url` as
+/// its query string.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct InputPrompt {
+    /// The URL that asked for input.
+    pub url: String,
+    /// Prompt text from the server's meta line.
+    pub prompt: String,
+    /// Mask typed characters when painting (status 11).
+    pub sensitive: bool,
+    /// The answer being typed.
+    pub field: TextField,
 }
 
 /// Build a Gemini request string (just the URL terminated by CRLF).
@@ -103,27 +162,75 @@ mod tests {
     fn parse_success_response() {
         let data = b"20 text/gemini\r\n# Hello\r\nWorld";
         let resp = parse_response(data).unwrap();
-        assert_eq!(resp.status, GeminiStatus::Success(20));
-        assert_eq!(resp.meta, "text/gemini");
-        assert_eq!(resp.body.as_deref(), Some("# Hello\r\nWorld"));
+        assert_eq!(
+            resp,
+            GeminiResponse::Success {
+                mime: "text/gemini".to_string(),
+                body: b"# Hello\r\nWorld".to_vec(),
+            }
+        );
+    }
+
+    #[test]
+    fn parse_binary_body() {
+        let data = b"20 application/zip\r\nPK\x03\x04\xff\x00";
+        let GeminiResponse::Success { mime, body } = parse_response(data).unwrap() else {
+            panic!("expected success");
+        };
+        assert_eq!(mime, "application/zip");
+        assert_eq!(body, b"PK\x03\x04\xff\x00");
     }
 
     #[test]
     fn parse_redirect_response() {
         let data = b"31 gemini://example.com/new\r\n";
         let resp = parse_response(data).unwrap();
-        assert!(resp.status.is_redirect());
-        assert_eq!(resp.meta, "gemini://example.com/new");
-        assert!(resp.body.is_none());
+        assert_eq!(
+            resp,
+            GeminiResponse::Redirect {
+                url: "gemini://example.com/new".to_string()
+            }
+        );
     }
 
     #[test]
     fn parse_error_response() {
         let data = b"51 Not found\r\n";
         let resp = parse_response(data).unwrap();
-        assert_eq!(resp.status, GeminiStatus::PermanentFailure(51));
-        assert_eq!(resp.meta, "Not found");
-        assert!(resp.body.is_none());
+        assert_eq!(
+            resp,
+            GeminiResponse::Failure {
+                status: GeminiStatus::PermanentFailure(51),
+                message: "Not found".to_string(),
+            }
+        );
+    }
+
+    #[test]
+    fn parse_input_responses() {
+        assert_eq!(
+            parse_response(b"10 Search terms\r\n").unwrap(),
+            GeminiResponse::Input {
+                prompt: "Search terms".to_string(),
+                sensitive: false,
+            }
+        );
+        let resp = parse_response(b"11 Password\r\n").unwrap();
+        assert!(matches!(
+            resp,
+            GeminiResponse::Input {
+                sensitive: true,
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn input_url_replaces_query() {
+        assert_eq!(
+            input_url("gemini://example.com/search?old#top", "café & co"),
+            "gemini://example.com/search?caf%C3%A9%20%26%20co"
+        );
     }
 
     #[test]
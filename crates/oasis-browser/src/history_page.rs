@@ -0,0 +1,132 @@
+//! History page.
+//!
+//! Entering [`HISTORY_URL`] renders the browsing history as an HTML
+//! page: the most recently visited pages first, under one heading per
+//! day, each with its URL and visit count. A link to
+//! [`HISTORY_CLEAR_URL`] forgets the whole history.
+
+use crate::history_view::SESSION_HISTORY_URL;
+use crate::nav::Visit;
+use crate::push_escaped;
+
+/// URL that opens the history page.
+pub const HISTORY_URL: &str = "about:history";
+
+/// Link on the history page that clears the history.
+pub const HISTORY_CLEAR_URL: &str = "about:history-clear";
+
+/// Maximum number of pages listed on the history page.
+pub const PAGE_ENTRIES: usize = 100;
+
+/// Build the history page from visits ordered least recently visited
+/// first, as kept by the navigation controller.
+pub fn render(visits: &[Visit]) -> String {
+    let mut html = String::from(
+        "<html><head><title>History</title></head><body>\
+         <h1>History</h1>",
+    );
+    html.push_str(&format!(
+        "<p><a href=\"{SESSION_HISTORY_URL}\">[back/forward list]</a>"
+    ));
+    if visits.is_empty() {
+        html.push_str("</p><p>No pages visited yet.</p>");
+    } else {
+        html.push_str(&format!(
+            " <a href=\"{HISTORY_CLEAR_URL}\">[clear history]</a></p>"
+        ));
+    }
+    let mut day = None;
+    for visit in visits.iter().rev().take(PAGE_ENTRIES) {
+        let visit_day = (visit.last_visit > 0).then_some(visit.last_visit / 86_400);
+        if day != Some(visit_day) {
+            if day.is_some() {
+                html.push_str("</ul>");
+            }
+            html.push_str("<h2>");
+            html.push_str(&visit_day.map_or_else(|| "Earlier".to_string(), format_day));
+            html.push_str("</h2><ul>");
+            day = Some(visit_day);
+        }
+        let title = if visit.title.is_empty() {
+            &visit.url
+        } else {
+            &visit.title
+        };
+        html.push_str("<li><a href=\"");
+        push_escaped(&mut html, &visit.url);
+        html.push_str("\">");
+        push_escaped(&mut html, title);
+        html.push_str("</a><br><small>");
+        push_escaped(&mut html, &visit.url);
+        match visit.visits {
+            1 => html.push_str(" - 1 visit"),
+            n => html.push_str(&format!(" - {n} visits")),
+        }
+        html.push_str("</small></li>");
+    }
+    if day.is_some() {
+        html.push_str("</ul>");
+    }
+    html.push_str("</body></html>");
+    html
+}
+
+/// Format days since the Unix epoch as an ISO date (`YYYY-MM-DD`).
+fn format_day(days: u64) -> String {
+    // Civil-from-days conversion (proleptic Gregorian calendar).
+    let z = days + 719_468;
+    let era = z / 146_097;
+    let doe = z % 146_097;
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + u64::from(month <= 2);
+    format!("{year:04}-{month:02}-{day:02}")
+}
+
+// -------------------------------------------------------------------
+// Tests
+// -------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn visit(url: &str, title: &str, visits: u32, last_visit: u64) -> Visit {
+        Visit {
+            url: url.to_string(),
+            title: title.to_string(),
+            visits,
+            last_visit,
+        }
+    }
+
+    #[test]
+    fn page_groups_visits_by_day_newest_first() {
+        let visits = vec![
+            visit("https://old.example/", "", 1, 0),
+            visit("https://a.example/", "A & B", 1, 1_700_000_000),
+            visit("gemini://b.example/", "B", 3, 1_700_050_000),
+        ];
+        let html = render(&visits);
+        let b = html.find("gemini://b.example/").unwrap();
+        let a = html.find("https://a.example/").unwrap();
+        let earlier = html.find("<h2>Earlier</h2>").unwrap();
+        assert!(html.find("<h2>2023-11-15</h2>").unwrap() < b);
+        assert!(b < html.find("<h2>2023-11-14</h2>").unwrap());
+        assert!(a < earlier && earlier < html.find("https://old.example/").unwrap());
+        assert!(html.contains(">A &amp; B</a>"));
+        assert!(html.contains(" - 3 visits") && html.contains(" - 1 visit<"));
+        assert!(html.contains("href=\"about:history-clear\""));
+        assert!(render(&[]).contains("No pages visited yet"));
+    }
+
+    #[test]
+    fn days_format_as_dates() {
+        assert_eq!(format_day(0), "1970-01-01");
+        assert_eq!(format_day(11_016), "2000-02-29");
+        assert_eq!(format_day(19_722), "2023-12-31");
+    }
+}
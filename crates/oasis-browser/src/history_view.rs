@@ -0,0 +1,171 @@
+//! History list view.
+//!
+//! Shows the session history as a list, newest first, in place of the
+//! page content. Each row has the page title and URL. Consecutive
+//! entries for the same URL (reloads, a link to the current page)
+//! collapse into a single row. Up/Down move the selection, wrapping at
+//! either end like link selection; Confirm jumps to the selected entry.
+//! The list is opened by entering [`SESSION_HISTORY_URL`] in the URL
+//! bar or following the link at the top of the history page.
+
+use crate::nav::HistoryEntry;
+
+/// URL that opens the history list when entered in the URL bar.
+pub const SESSION_HISTORY_URL: &str = "about:session";
+
+/// Height of one row in pixels (title line plus URL line).
+pub const ROW_HEIGHT: u32 = 28;
+
+/// One row of the history list.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HistoryRow {
+    /// Index into `NavigationController::history_entries`: the newest
+    /// entry of the run, or the current page if the run contains it.
+    pub index: usize,
+    pub title: String,
+    pub url: String,
+    /// Whether this row contains the current page.
+    pub current: bool,
+}
+
+/// Rows and selection state of the history list.
+#[derive(Debug, Clone, Default)]
+pub struct HistoryView {
+    /// Rows, newest first.
+    pub rows: Vec<HistoryRow>,
+    /// Index of the selected row within `rows`.
+    pub selected: usize,
+    /// Index of the first row shown at the top of the viewport.
+    pub first_visible: usize,
+}
+
+impl HistoryView {
+    /// Build the list from chronological history entries, selecting
+    /// the row of the current page.
+    pub fn new(entries: &[HistoryEntry], current: Option<usize>) -> Self {
+        let mut rows: Vec<HistoryRow> = Vec::new();
+        for (index, entry) in entries.iter().enumerate().rev() {
+            let is_current = current == Some(index);
+            if let Some(last) = rows.last_mut()
+                && last.url == entry.url
+            {
+                if is_current {
+                    last.index = index;
+                    last.current = true;
+                }
+                continue;
+            }
+            let title = if entry.title.is_empty() {
+                entry.url.clone()
+            } else {
+                entry.title.clone()
+            };
+            rows.push(HistoryRow {
+                index,
+                title,
+                url: entry.url.clone(),
+                current: is_current,
+            });
+        }
+        let selected = rows.iter().position(|r| r.current).unwrap_or(0);
+        Self {
+            rows,
+            selected,
+            first_visible: 0,
+        }
+    }
+
+    /// The selected row, if the list is not empty.
+    pub fn selected_row(&self) -> Option<&HistoryRow> {
+        self.rows.get(self.selected)
+    }
+
+    /// Select the next (older) row, wrapping at the end.
+    pub fn next(&mut self) {
+        if !self.rows.is_empty() {
+            self.selected = (self.selected + 1) % self.rows.len();
+        }
+    }
+
+    /// Select the previous (newer) row, wrapping at the start.
+    pub fn prev(&mut self) {
+        if !self.rows.is_empty() {
+            self.selected = (self.selected + self.rows.len() - 1) % self.rows.len();
+        }
+    }
+
+    /// Scroll so the selected row is within a viewport of
+    /// `visible_rows` rows.
+    pub fn scroll_to_selected(&mut self, visible_rows: usize) {
+        let visible_rows = visible_rows.max(1);
+        if self.selected < self.first_visible {
+            self.first_visible = self.selected;
+        } else if self.selected >= self.first_visible + visible_rows {
+            self.first_visible = self.selected + 1 - visible_rows;
+        }
+    }
+
+    /// Status text for the status bar, e.g. `"History 2/5"`.
+    pub fn status_text(&self) -> String {
+        if self.rows.is_empty() {
+            "History is empty".to_string()
+        } else {
+            format!("History {}/{}", self.selected + 1, self.rows.len())
+        }
+    }
+}
+
+// -------------------------------------------------------------------
+// Tests
+// -------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(url: &str, title: &str) -> HistoryEntry {
+        HistoryEntry {
+            url: url.to_string(),
+            title: title.to_string(),
+            scroll_y: 0,
+            reader_mode: false,
+            timestamp: 0,
+        }
+    }
+
+    #[test]
+    fn rows_are_newest_first_and_deduplicated() {
+        let entries = vec![
+            entry("a", "A"),
+            entry("b", ""),
+            entry("b", "B"),
+            entry("a", "A"),
+        ];
+        let view = HistoryView::new(&entries, Some(1));
+        let urls: Vec<&str> = view.rows.iter().map(|r| r.url.as_str()).collect();
+        assert_eq!(urls, ["a", "b", "a"]);
+        assert_eq!(view.rows[0].index, 3);
+        // A collapsed run is titled by its newest entry but points at
+        // the current page when it holds it.
+        assert_eq!(view.rows[1].index, 1);
+        assert_eq!(view.rows[1].title, "B");
+        assert_eq!(view.selected, 1);
+    }
+
+    #[test]
+    fn selection_wraps_and_scrolls() {
+        let entries: Vec<HistoryEntry> = (0..6).map(|i| entry(&i.to_string(), "")).collect();
+        let mut view = HistoryView::new(&entries, Some(5));
+        assert_eq!(view.selected, 0);
+        assert_eq!(view.rows[0].title, "5");
+
+        view.prev();
+        assert_eq!(view.selected, 5);
+        view.scroll_to_selected(4);
+        assert_eq!(view.first_visible, 2);
+        view.next();
+        view.scroll_to_selected(4);
+        assert_eq!((view.selected, view.first_visible), (0, 0));
+        assert_eq!(view.status_text(), "History 1/6");
+    }
+}
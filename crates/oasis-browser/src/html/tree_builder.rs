@@ -46,6 +46,8 @@ pub struct TreeBuilder {
     frameset_ok: bool,
     /// Saved mode for returning from `Text` insertion mode.
     original_mode: InsertionMode,
+    /// Drop a newline that directly follows a `<pre>` start tag.
+    skip_newline: bool,
 }
 
 impl TreeBuilder {
@@ -60,6 +62,7 @@ impl TreeBuilder {
             form_element: None,
             frameset_ok: true,
             original_mode: InsertionMode::InBody,
+            skip_newline: false,
         }
     }
 
@@ -77,6 +80,17 @@ impl TreeBuilder {
     // =============================================================
 
     fn process_token(&mut self, token: Token) {
+        // A newline right after `<pre>` only formats the markup.
+        let skip_newline = std::mem::take(&mut self.skip_newline);
+        let token = match token {
+            Token::Character(s) if skip_newline && s.starts_with('\n') => {
+                if s.len() == 1 {
+                    return;
+                }
+                Token::Character(s[1..].to_string())
+            },
+            token => token,
+        };
         match self.mode {
             InsertionMode::Initial => {
                 self.handle_initial(token);
@@ -342,7 +356,13 @@ impl TreeBuilder {
                 let id = self.create_element_from_start_tag(tag);
                 self.insert_element(id);
             },
-            TagName::Pre | TagName::Blockquote => {
+            TagName::Pre => {
+                self.close_p_if_in_scope();
+                let id = self.create_element_from_start_tag(tag);
+                self.insert_element(id);
+                self.skip_newline = true;
+            },
+            TagName::Blockquote => {
                 self.close_p_if_in_scope();
                 let id = self.create_element_from_start_tag(tag);
                 self.insert_element(id);
@@ -1217,6 +1237,20 @@ mod tests {
 
     // ---- Test 2: Implicit elements ----
 
+    #[test]
+    fn newline_after_pre_start_tag_is_dropped() {
+        let tokens = vec![start("pre"), text("\n\tx\n"), end("pre"), Token::Eof];
+        let doc = TreeBuilder::build(tokens);
+        let pre = doc.get(doc.body().unwrap()).children[0];
+        assert_eq!(doc.text_content(pre), "\tx\n");
+
+        // Only the first newline, and only straight after the tag.
+        let tokens = vec![start("pre"), text("\n\nx"), end("pre"), Token::Eof];
+        let doc = TreeBuilder::build(tokens);
+        let pre = doc.get(doc.body().unwrap()).children[0];
+        assert_eq!(doc.text_content(pre), "\nx");
+    }
+
     #[test]
     fn implicit_elements() {
         let tokens = vec![start("p"), text("Hello"), end("p"), Token::Eof];
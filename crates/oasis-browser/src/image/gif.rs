@@ -0,0 +1,399 @@
+//! Minimal GIF decoder.
+//!
+//! Decodes the first frame of a GIF87a/GIF89a file -- interlaced or not,
+//! with a global or local palette and the transparent index of its
+//! graphic control extension -- onto the logical screen. Later frames are
+//! only counted, for a future animation pass. A malformed first frame
+//! fails the whole decode so the page shows its broken-image placeholder.
+
+use super::{DecodedImage, MAX_PIXELS};
+
+/// Largest LZW code width.
+const MAX_CODE_BITS: u32 = 12;
+
+/// Block introducers.
+const EXTENSION: u8 = 0x21;
+const IMAGE: u8 = 0x2C;
+
+/// Extension label of the graphic control extension.
+const GRAPHIC_CONTROL: u8 = 0xF9;
+
+/// A decoded GIF.
+#[derive(Debug, Clone)]
+pub struct DecodedGif {
+    /// The first frame on the logical screen, transparent where the frame
+    /// does not cover it.
+    pub image: DecodedImage,
+    /// Number of frames; more than one for an animation.
+    pub frame_count: usize,
+}
+
+/// Decode the first frame of a GIF into RGBA pixels and count its frames.
+/// `None` if it is malformed or too big.
+pub fn decode_gif(data: &[u8]) -> Option<DecodedGif> {
+    if !data.starts_with(b"GIF87a") && !data.starts_with(b"GIF89a") {
+        return None;
+    }
+    let mut reader = Reader { data, pos: 6 };
+    let screen_w = u32::from(reader.u16()?);
+    let screen_h = u32::from(reader.u16()?);
+    let flags = reader.u8()?;
+    // Background color index and pixel aspect ratio.
+    reader.take(2)?;
+    let global_palette = if flags & 0x80 != 0 {
+        Some(reader.take(palette_len(flags))?)
+    } else {
+        None
+    };
+
+    let mut image = None;
+    let mut frame_count = 0;
+    let mut transparent = None;
+    // Stop at the trailer or at anything unexpected: past the first frame
+    // a truncated file still counts the frames it has.
+    while let Some(introducer) = reader.u8() {
+        match introducer {
+            EXTENSION => {
+                let Some(label) = reader.u8() else { break };
+                let Some(body) = reader.sub_blocks() else {
+                    break;
+                };
+                if label == GRAPHIC_CONTROL && body.len() >= 4 {
+                    transparent = (body[0] & 0x01 != 0).then_some(body[3]);
+                }
+            },
+            IMAGE if image.is_none() => {
+                let frame = Frame::read(&mut reader, global_palette, transparent)?;
+                image = Some(frame.draw(screen_w, screen_h)?);
+                frame_count = 1;
+                transparent = None;
+            },
+            IMAGE => {
+                if Frame::skip(&mut reader).is_none() {
+                    break;
+                }
+                frame_count += 1;
+            },
+            _ => break,
+        }
+    }
+    Some(DecodedGif {
+        image: image?,
+        frame_count,
+    })
+}
+
+/// Bytes in the color table announced by a packed flags byte.
+fn palette_len(flags: u8) -> usize {
+    3 << ((flags & 0x07) + 1)
+}
+
+/// A cursor over the file.
+struct Reader<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, n: usize) -> Option<&'a [u8]> {
+        let bytes = self.data.get(self.pos..self.pos.checked_add(n)?)?;
+        self.pos += n;
+        Some(bytes)
+    }
+
+    fn u8(&mut self) -> Option<u8> {
+        Some(self.take(1)?[0])
+    }
+
+    fn u16(&mut self) -> Option<u16> {
+        Some(u16::from_le_bytes(self.take(2)?.try_into().ok()?))
+    }
+
+    /// Read a chain of data sub-blocks up to its empty terminator.
+    fn sub_blocks(&mut self) -> Option<Vec<u8>> {
+        let mut out = Vec::new();
+        loop {
+            let len = self.u8()? as usize;
+            if len == 0 {
+                return Some(out);
+            }
+            out.extend_from_slice(self.take(len)?);
+        }
+    }
+}
+
+/// One image of the file: its placement, palette and color indices.
+struct Frame<'a> {
+    left: u32,
+    top: u32,
+    width: u32,
+    height: u32,
+    palette: &'a [u8],
+    transparent: Option<u8>,
+    /// Color indices, row by row from the top.
+    indices: Vec<u8>,
+}
+
+impl<'a> Frame<'a> {
+    /// Read an image after its introducer.
+    fn read(
+        reader: &mut Reader<'a>,
+        global_palette: Option<&'a [u8]>,
+        transparent: Option<u8>,
+    ) -> Option<Self> {
+        let left = u32::from(reader.u16()?);
+        let top = u32::from(reader.u16()?);
+        let width = u32::from(reader.u16()?);
+        let height = u32::from(reader.u16()?);
+        let flags = reader.u8()?;
+        let palette = if flags & 0x80 != 0 {
+            reader.take(palette_len(flags))?
+        } else {
+            global_palette?
+        };
+        if width == 0 || height == 0 || u64::from(width) * u64::from(height) > MAX_PIXELS {
+            return None;
+        }
+        let min_code_size = reader.u8()?;
+        let compressed = reader.sub_blocks()?;
+        let pixel_count = (width * height) as usize;
+        let mut indices = lzw_decode(&compressed, min_code_size, pixel_count)?;
+        if flags & 0x40 != 0 {
+            indices = deinterlace(&indices, width as usize, height as usize);
+        }
+        Some(Self {
+            left,
+            top,
+            width,
+            height,
+            palette,
+            transparent,
+            indices,
+        })
+    }
+
+    /// Skip an image after its introducer.
+    fn skip(reader: &mut Reader<'_>) -> Option<()> {
+        reader.take(8)?;
+        let flags = reader.u8()?;
+        if flags & 0x80 != 0 {
+            reader.take(palette_len(flags))?;
+        }
+        // LZW minimum code size.
+        reader.u8()?;
+        reader.sub_blocks()?;
+        Some(())
+    }
+
+    /// Paint the frame onto a transparent logical screen. A screen too
+    /// small for the frame grows to fit it.
+    fn draw(&self, screen_w: u32, screen_h: u32) -> Option<DecodedImage> {
+        let width = screen_w.max(self.left + self.width);
+        let height = screen_h.max(self.top + self.height);
+        if u64::from(width) * u64::from(height) > MAX_PIXELS {
+            return None;
+        }
+        let mut pixels = vec![0u8; (width * height * 4) as usize];
+        for (row, indices) in self.indices.chunks(self.width as usize).enumerate() {
+            let y = self.top as usize + row;
+            for (col, &index) in indices.iter().enumerate() {
+                if self.transparent == Some(index) {
+                    continue;
+                }
+                let x = self.left as usize + col;
+                let dst = (y * width as usize + x) * 4;
+                // Indices past the end of the palette show as black.
+                let rgb = self
+                    .palette
+                    .get(index as usize * 3..index as usize * 3 + 3)
+                    .unwrap_or(&[0, 0, 0]);
+                pixels[dst..dst + 3].copy_from_slice(rgb);
+                pixels[dst + 3] = 255;
+            }
+        }
+        Some(DecodedImage {
+            width,
+            height,
+            pixels,
+        })
+    }
+}
+
+/// Put the rows of an interlaced image, stored as every 8th row from 0,
+/// every 8th from 4, every 4th from 2 and every 2nd from 1, back in order.
+fn deinterlace(indices: &[u8], width: usize, height: usize) -> Vec<u8> {
+    let mut out = vec![0u8; indices.len()];
+    let rows = [(0, 8), (4, 8), (2, 4), (1, 2)]
+        .into_iter()
+        .flat_map(|(start, step)| (start..height).step_by(step));
+    for (stored, row) in indices.chunks(width).zip(rows) {
+        out[row * width..(row + 1) * width].copy_from_slice(stored);
+    }
+    out
+}
+
+/// Decompress GIF LZW data into `pixel_count` color indices, failing if it
+/// is corrupt or falls short. Codes past the last pixel are ignored.
+fn lzw_decode(data: &[u8], min_code_size: u8, pixel_count: usize) -> Option<Vec<u8>> {
+    if !(2..=8).contains(&min_code_size) {
+        return None;
+    }
+    let clear = 1u16 << min_code_size;
+    let end = clear + 1;
+    let table_size = 1 << MAX_CODE_BITS;
+    // Each code's string is its prefix code's string plus a suffix byte.
+    let mut prefix = vec![0u16; table_size];
+    let mut suffix = vec![0u8; table_size];
+    // First byte of each code's string.
+    let mut first = vec![0u8; table_size];
+    for code in 0..clear {
+        suffix[code as usize] = code as u8;
+        first[code as usize] = code as u8;
+    }
+
+    let mut out = Vec::with_capacity(pixel_count);
+    let mut stack = Vec::new();
+    let (mut bit_buf, mut bit_count, mut pos) = (0u32, 0u32, 0usize);
+    let mut width = u32::from(min_code_size) + 1;
+    let mut next = end + 1;
+    let mut prev: Option<u16> = None;
+    while out.len() < pixel_count {
+        while bit_count < width {
+            bit_buf |= u32::from(*data.get(pos)?) << bit_count;
+            pos += 1;
+            bit_count += 8;
+        }
+        let code = (bit_buf & ((1 << width) - 1)) as u16;
+        bit_buf >>= width;
+        bit_count -= width;
+
+        if code == clear {
+            width = u32::from(min_code_size) + 1;
+            next = end + 1;
+            prev = None;
+            continue;
+        }
+        if code == end {
+            break;
+        }
+        match prev {
+            Some(p) => {
+                if code > next {
+                    return None;
+                }
+                // A code not in the table yet is the previous string
+                // plus its own first byte.
+                let byte = if code == next {
+                    first[p as usize]
+                } else {
+                    first[code as usize]
+                };
+                if (next as usize) < table_size {
+                    prefix[next as usize] = p;
+                    suffix[next as usize] = byte;
+                    first[next as usize] = first[p as usize];
+                    next += 1;
+                    if u32::from(next) == 1 << width && width < MAX_CODE_BITS {
+                        width += 1;
+                    }
+                }
+            },
+            None if code >= clear => return None,
+            None => {},
+        }
+
+        let mut c = code;
+        while c > end {
+            stack.push(suffix[c as usize]);
+            c = prefix[c as usize];
+        }
+        stack.push(c as u8);
+        out.extend(stack.drain(..).rev());
+        prev = Some(code);
+    }
+    if out.len() < pixel_count {
+        return None;
+    }
+    out.truncate(pixel_count);
+    Some(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 2x2: red, green / blue, and a transparent white.
+    const TINY: [u8; 50] = [
+        0x47, 0x49, 0x46, 0x38, 0x39, 0x61, 0x02, 0x00, 0x02, 0x00, 0x81, 0x00, 0x00, 0xFF, 0x00,
+        0x00, 0x00, 0xFF, 0x00, 0x00, 0x00, 0xFF, 0xFF, 0xFF, 0xFF, 0x21, 0xF9, 0x04, 0x01, 0x00,
+        0x00, 0x03, 0x00, 0x2C, 0x00, 0x00, 0x00, 0x00, 0x02, 0x00, 0x02, 0x00, 0x00, 0x02, 0x03,
+        0x44, 0x34, 0x05, 0x00, 0x3B,
+    ];
+
+    /// 4x4, two frames: an interlaced frame with a local palette whose row
+    /// `y` is all index `y`, a comment, then a 2x2 frame.
+    const ANIMATED: [u8; 73] = [
+        0x47, 0x49, 0x46, 0x38, 0x39, 0x61, 0x04, 0x00, 0x04, 0x00, 0x80, 0x00, 0x00, 0x00, 0x00,
+        0x00, 0xFF, 0xFF, 0xFF, 0x2C, 0x00, 0x00, 0x00, 0x00, 0x04, 0x00, 0x04, 0x00, 0xC1, 0x0A,
+        0x00, 0x00, 0x14, 0x00, 0x00, 0x1E, 0x00, 0x00, 0x28, 0x00, 0x00, 0x02, 0x07, 0x84, 0x21,
+        0x29, 0xC1, 0x31, 0x3F, 0x0A, 0x00, 0x21, 0xFE, 0x02, 0x68, 0x69, 0x00, 0x2C, 0x00, 0x00,
+        0x00, 0x00, 0x02, 0x00, 0x02, 0x00, 0x00, 0x02, 0x02, 0x8C, 0x53, 0x00, 0x3B,
+    ];
+
+    fn pixel(img: &DecodedImage, x: u32, y: u32) -> [u8; 4] {
+        let i = (y * img.width + x) as usize * 4;
+        img.pixels[i..i + 4].try_into().unwrap()
+    }
+
+    #[test]
+    fn decodes_tiny_gif_with_transparency() {
+        let gif = decode_gif(&TINY).unwrap();
+        assert_eq!(gif.frame_count, 1);
+        let img = &gif.image;
+        assert_eq!((img.width, img.height), (2, 2));
+        assert_eq!(pixel(img, 0, 0), [255, 0, 0, 255]);
+        assert_eq!(pixel(img, 1, 0), [0, 255, 0, 255]);
+        assert_eq!(pixel(img, 0, 1), [0, 0, 255, 255]);
+        assert_eq!(pixel(img, 1, 1)[3], 0);
+    }
+
+    #[test]
+    fn first_frame_of_an_interlaced_animation() {
+        let gif = decode_gif(&ANIMATED).unwrap();
+        assert_eq!(gif.frame_count, 2);
+        for y in 0..4 {
+            let red = 10 * (y as u8 + 1);
+            assert_eq!(pixel(&gif.image, 3, y), [red, 0, 0, 255]);
+        }
+        // A cut-off second frame still leaves the first.
+        let cut = decode_gif(&ANIMATED[..60]).unwrap();
+        assert_eq!(cut.frame_count, 1);
+    }
+
+    #[test]
+    fn rejects_corrupt_gifs() {
+        assert!(decode_gif(b"GIF89a").is_none());
+        assert!(decode_gif(&TINY[..45]).is_none());
+        let mut bad = TINY;
+        // LZW minimum code size out of range.
+        bad[43] = 12;
+        assert!(decode_gif(&bad).is_none());
+    }
+
+    #[test]
+    fn lzw_grows_code_width_and_handles_unseen_codes() {
+        // A run, a repeated pattern and another run of 200 indices.
+        let data = [
+            0x84, 0x8F, 0xA9, 0xCB, 0x9D, 0x21, 0x06, 0x94, 0x71, 0xDA, 0x8A, 0xA9, 0xBE, 0x3B,
+            0xF3, 0xEF, 0x85, 0xDD, 0xC8, 0x09, 0xE6, 0x89, 0xA6, 0xEA, 0xCA, 0xB6, 0x6E, 0x5A,
+            0x00,
+        ];
+        let mut expected = vec![0u8; 50];
+        for _ in 0..30 {
+            expected.extend_from_slice(&[1, 2, 3]);
+        }
+        expected.extend_from_slice(&[2; 60]);
+        assert_eq!(lzw_decode(&data, 2, 200).unwrap(), expected);
+        assert!(lzw_decode(&data, 2, 201).is_none());
+    }
+}
@@ -0,0 +1,465 @@
+//! zlib / gzip / DEFLATE decompression (RFC 1950, 1952, 1951) for PNG
+//! image data and `Content-Encoding`-compressed HTTP bodies.
+//!
+//! A small canonical-Huffman decoder in the style of zlib's `puff`: it
+//! favors size over speed, which is fine for the pages and images a
+//! browser on this hardware shows.
+
+/// Longest Huffman code DEFLATE uses.
+const MAX_BITS: usize = 15;
+
+/// Base lengths of length codes 257..285.
+const LENGTH_BASE: [u16; 29] = [
+    3, 4, 5, 6, 7, 8, 9, 10, 11, 13, 15, 17, 19, 23, 27, 31, 35, 43, 51, 59, 67, 83, 99, 115, 131,
+    163, 195, 227, 258,
+];
+
+/// Extra bits of length codes 257..285.
+const LENGTH_EXTRA: [u8; 29] = [
+    0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 2, 2, 2, 2, 3, 3, 3, 3, 4, 4, 4, 4, 5, 5, 5, 5, 0,
+];
+
+/// Base distances of distance codes 0..29.
+const DIST_BASE: [u16; 30] = [
+    1, 2, 3, 4, 5, 7, 9, 13, 17, 25, 33, 49, 65, 97, 129, 193, 257, 385, 513, 769, 1025, 1537,
+    2049, 3073, 4097, 6145, 8193, 12289, 16385, 24577,
+];
+
+/// Extra bits of distance codes 0..29.
+const DIST_EXTRA: [u8; 30] = [
+    0, 0, 0, 0, 1, 1, 2, 2, 3, 3, 4, 4, 5, 5, 6, 6, 7, 7, 8, 8, 9, 9, 10, 10, 11, 11, 12, 12, 13,
+    13,
+];
+
+/// Order code length code lengths are stored in.
+const CODE_LENGTH_ORDER: [usize; 19] = [
+    16, 17, 18, 0, 8, 7, 9, 6, 10, 5, 11, 4, 12, 3, 13, 2, 14, 1, 15,
+];
+
+/// Decompress a zlib stream, failing if it is corrupt, truncated or
+/// inflates to more than `limit` bytes.
+pub fn zlib_decompress(data: &[u8], limit: usize) -> Option<Vec<u8>> {
+    let (&cmf, rest) = data.split_first()?;
+    let (&flg, _) = rest.split_first()?;
+    // Deflate with a window of at most 32K, no preset dictionary.
+    if cmf & 0x0F != 8 || cmf >> 4 > 7 || flg & 0x20 != 0 {
+        return None;
+    }
+    if (u16::from(cmf) << 8 | u16::from(flg)) % 31 != 0 {
+        return None;
+    }
+    let mut inflater = Inflater {
+        input: BitReader::new(&data[2..]),
+        out: Vec::new(),
+        limit,
+    };
+    inflater.run()?;
+    let pos = inflater.input.byte_pos();
+    let checksum = data.get(2 + pos..2 + pos + 4)?;
+    if u32::from_be_bytes(checksum.try_into().ok()?) != adler32(&inflater.out) {
+        return None;
+    }
+    Some(inflater.out)
+}
+
+/// Decompress a raw DEFLATE stream, with no zlib or gzip wrapper.
+pub fn deflate_decompress(data: &[u8], limit: usize) -> Option<Vec<u8>> {
+    let mut inflater = Inflater {
+        input: BitReader::new(data),
+        out: Vec::new(),
+        limit,
+    };
+    inflater.run()?;
+    Some(inflater.out)
+}
+
+/// Decompress a gzip member, failing if it is corrupt, truncated,
+/// fails its CRC-32 or length check, or inflates to more than `limit`
+/// bytes.
+pub fn gzip_decompress(data: &[u8], limit: usize) -> Option<Vec<u8>> {
+    const FHCRC: u8 = 0x02;
+    const FEXTRA: u8 = 0x04;
+    const FNAME: u8 = 0x08;
+    const FCOMMENT: u8 = 0x10;
+
+    let header = data.get(..10)?;
+    // Magic, deflate, and no reserved flags.
+    if header[..3] != [0x1F, 0x8B, 8] || header[3] & 0xE0 != 0 {
+        return None;
+    }
+    let flags = header[3];
+    let mut pos = 10;
+    if flags & FEXTRA != 0 {
+        let len = data.get(pos..pos + 2)?;
+        pos += 2 + usize::from(u16::from_le_bytes([len[0], len[1]]));
+    }
+    // File name and comment are zero-terminated.
+    for flag in [FNAME, FCOMMENT] {
+        if flags & flag != 0 {
+            pos += data.get(pos..)?.iter().position(|&b| b == 0)? + 1;
+        }
+    }
+    if flags & FHCRC != 0 {
+        pos += 2;
+    }
+
+    let mut inflater = Inflater {
+        input: BitReader::new(data.get(pos..)?),
+        out: Vec::new(),
+        limit,
+    };
+    inflater.run()?;
+    let end = pos + inflater.input.byte_pos();
+    let trailer = data.get(end..end + 8)?;
+    let crc = u32::from_le_bytes(trailer[..4].try_into().ok()?);
+    let size = u32::from_le_bytes(trailer[4..].try_into().ok()?);
+    // The size is stored modulo 2^32.
+    if crc != crc32(&[&inflater.out]) || size != inflater.out.len() as u32 {
+        return None;
+    }
+    Some(inflater.out)
+}
+
+/// CRC-32 (ISO 3309) lookup table.
+const CRC_TABLE: [u32; 256] = crc_table();
+
+const fn crc_table() -> [u32; 256] {
+    let mut table = [0u32; 256];
+    let mut n = 0;
+    while n < 256 {
+        let mut c = n as u32;
+        let mut k = 0;
+        while k < 8 {
+            c = if c & 1 != 0 {
+                0xEDB8_8320 ^ (c >> 1)
+            } else {
+                c >> 1
+            };
+            k += 1;
+        }
+        table[n] = c;
+        n += 1;
+    }
+    table
+}
+
+/// CRC-32 (the gzip / PNG polynomial) of `parts`, one after another.
+pub(super) fn crc32(parts: &[&[u8]]) -> u32 {
+    let mut c = 0xFFFF_FFFFu32;
+    for part in parts {
+        for &byte in *part {
+            c = CRC_TABLE[((c ^ u32::from(byte)) & 0xFF) as usize] ^ (c >> 8);
+        }
+    }
+    c ^ 0xFFFF_FFFF
+}
+
+/// Adler-32 checksum of `data`.
+fn adler32(data: &[u8]) -> u32 {
+    const MOD: u32 = 65521;
+    let (mut a, mut b) = (1u32, 0u32);
+    // 5552 bytes is the most that can be summed before `b` overflows.
+    for chunk in data.chunks(5552) {
+        for &byte in chunk {
+            a += u32::from(byte);
+            b += a;
+        }
+        a %= MOD;
+        b %= MOD;
+    }
+    b << 16 | a
+}
+
+/// Reads DEFLATE's least-significant-bit-first bit stream.
+struct BitReader<'a> {
+    data: &'a [u8],
+    pos: usize,
+    bit_buf: u32,
+    bit_count: u32,
+}
+
+impl<'a> BitReader<'a> {
+    fn new(data: &'a [u8]) -> Self {
+        Self {
+            data,
+            pos: 0,
+            bit_buf: 0,
+            bit_count: 0,
+        }
+    }
+
+    /// Read `n` (at most 16) bits.
+    fn bits(&mut self, n: u32) -> Option<u32> {
+        while self.bit_count < n {
+            let byte = *self.data.get(self.pos)?;
+            self.pos += 1;
+            self.bit_buf |= u32::from(byte) << self.bit_count;
+            self.bit_count += 8;
+        }
+        let value = self.bit_buf & ((1 << n) - 1);
+        self.bit_buf >>= n;
+        self.bit_count -= n;
+        Some(value)
+    }
+
+    /// Drop the bits left in the current byte.
+    fn align(&mut self) {
+        self.bit_buf = 0;
+        self.bit_count = 0;
+    }
+
+    /// Offset of the first byte not (even partly) consumed.
+    fn byte_pos(&self) -> usize {
+        self.pos - (self.bit_count / 8) as usize
+    }
+}
+
+/// A canonical Huffman code: how many codes there are of each length
+/// and the symbols ordered by code.
+struct Huffman {
+    counts: [u16; MAX_BITS + 1],
+    symbols: Vec<u16>,
+}
+
+impl Huffman {
+    /// Build the code from per-symbol code lengths (0 = unused). Fails
+    /// for an over-subscribed set of lengths.
+    fn new(lengths: &[u8]) -> Option<Self> {
+        let mut counts = [0u16; MAX_BITS + 1];
+        for &len in lengths {
+            counts[len as usize] += 1;
+        }
+        let mut left: i32 = 1;
+        for &count in &counts[1..] {
+            left = (left << 1) - i32::from(count);
+            if left < 0 {
+                return None;
+            }
+        }
+        let mut offsets = [0u16; MAX_BITS + 2];
+        for len in 1..=MAX_BITS {
+            offsets[len + 1] = offsets[len] + counts[len];
+        }
+        let mut symbols = vec![0u16; lengths.len()];
+        for (symbol, &len) in lengths.iter().enumerate() {
+            if len != 0 {
+                symbols[offsets[len as usize] as usize] = symbol as u16;
+                offsets[len as usize] += 1;
+            }
+        }
+        Some(Self { counts, symbols })
+    }
+
+    /// Decode one symbol.
+    fn decode(&self, input: &mut BitReader<'_>) -> Option<u16> {
+        let (mut code, mut first, mut index) = (0i32, 0i32, 0i32);
+        for len in 1..=MAX_BITS {
+            code |= input.bits(1)? as i32;
+            let count = i32::from(self.counts[len]);
+            if code - first < count {
+                return self.symbols.get((index + code - first) as usize).copied();
+            }
+            index += count;
+            first = (first + count) << 1;
+            code <<= 1;
+        }
+        None
+    }
+}
+
+struct Inflater<'a> {
+    input: BitReader<'a>,
+    out: Vec<u8>,
+    limit: usize,
+}
+
+impl Inflater<'_> {
+    fn run(&mut self) -> Option<()> {
+        loop {
+            let last = self.input.bits(1)? == 1;
+            match self.input.bits(2)? {
+                0 => self.stored()?,
+                1 => self.fixed()?,
+                2 => self.dynamic()?,
+                _ => return None,
+            }
+            if last {
+                return Some(());
+            }
+        }
+    }
+
+    fn stored(&mut self) -> Option<()> {
+        self.input.align();
+        let pos = self.input.pos;
+        let header = self.input.data.get(pos..pos + 4)?;
+        let len = u16::from_le_bytes([header[0], header[1]]);
+        let nlen = u16::from_le_bytes([header[2], header[3]]);
+        if len != !nlen {
+            return None;
+        }
+        let start = pos + 4;
+        let bytes = self.input.data.get(start..start + len as usize)?;
+        if self.out.len() + bytes.len() > self.limit {
+            return None;
+        }
+        self.out.extend_from_slice(bytes);
+        self.input.pos = start + len as usize;
+        Some(())
+    }
+
+    fn fixed(&mut self) -> Option<()> {
+        let mut lengths = [0u8; 288];
+        lengths[..144].fill(8);
+        lengths[144..256].fill(9);
+        lengths[256..280].fill(7);
+        lengths[280..].fill(8);
+        let lit = Huffman::new(&lengths)?;
+        let dist = Huffman::new(&[5; 30])?;
+        self.codes(&lit, &dist)
+    }
+
+    fn dynamic(&mut self) -> Option<()> {
+        let nlen = self.input.bits(5)? as usize + 257;
+        let ndist = self.input.bits(5)? as usize + 1;
+        let ncode = self.input.bits(4)? as usize + 4;
+        if nlen > 286 || ndist > 30 {
+            return None;
+        }
+        let mut code_lengths = [0u8; 19];
+        for &index in &CODE_LENGTH_ORDER[..ncode] {
+            code_lengths[index] = self.input.bits(3)? as u8;
+        }
+        let code_code = Huffman::new(&code_lengths)?;
+
+        let mut lengths = vec![0u8; nlen + ndist];
+        let mut i = 0;
+        while i < lengths.len() {
+            let symbol = code_code.decode(&mut self.input)?;
+            let (value, repeat) = match symbol {
+                0..=15 => (symbol as u8, 1),
+                16 => (*lengths.get(i.checked_sub(1)?)?, 3 + self.input.bits(2)?),
+                17 => (0, 3 + self.input.bits(3)?),
+                _ => (0, 11 + self.input.bits(7)?),
+            };
+            let end = i + repeat as usize;
+            lengths.get_mut(i..end)?.fill(value);
+            i = end;
+        }
+        // A block without an end-of-block code could never finish.
+        if lengths[256] == 0 {
+            return None;
+        }
+        let lit = Huffman::new(&lengths[..nlen])?;
+        let dist = Huffman::new(&lengths[nlen..])?;
+        self.codes(&lit, &dist)
+    }
+
+    /// Decode literals and back-references up to the end of the block.
+    fn codes(&mut self, lit: &Huffman, dist: &Huffman) -> Option<()> {
+        loop {
+            let symbol = lit.decode(&mut self.input)? as usize;
+            if symbol < 256 {
+                if self.out.len() >= self.limit {
+                    return None;
+                }
+                self.out.push(symbol as u8);
+                continue;
+            }
+            if symbol == 256 {
+                return Some(());
+            }
+            let index = symbol - 257;
+            let len = *LENGTH_BASE.get(index)? as usize
+                + self.input.bits(u32::from(LENGTH_EXTRA[index]))? as usize;
+            let index = dist.decode(&mut self.input)? as usize;
+            let distance = *DIST_BASE.get(index)? as usize
+                + self.input.bits(u32::from(DIST_EXTRA[index]))? as usize;
+            if distance > self.out.len() || self.out.len() + len > self.limit {
+                return None;
+            }
+            // Copy byte by byte: the source may overlap what is written.
+            let start = self.out.len() - distance;
+            for k in 0..len {
+                let byte = self.out[start + k];
+                self.out.push(byte);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stored_block() {
+        // zlib header, one final stored block holding "hello".
+        let mut data = vec![0x78, 0x01, 0x01, 0x05, 0x00, 0xFA, 0xFF];
+        data.extend_from_slice(b"hello");
+        data.extend_from_slice(&adler32(b"hello").to_be_bytes());
+        assert_eq!(zlib_decompress(&data, 100).unwrap(), b"hello");
+        // Too big for the limit.
+        assert!(zlib_decompress(&data, 4).is_none());
+        // Bad checksum.
+        let last = data.len() - 1;
+        data[last] ^= 1;
+        assert!(zlib_decompress(&data, 100).is_none());
+    }
+
+    #[test]
+    fn fixed_huffman_with_back_reference() {
+        // zlib.compress(b"abcabcabcabc") from CPython.
+        let data = [
+            0x78, 0x9C, 0x4B, 0x4C, 0x4A, 0x4E, 0x84, 0x21, 0x00, 0x1D, 0xE0, 0x04, 0x99,
+        ];
+        assert_eq!(zlib_decompress(&data, 100).unwrap(), b"abcabcabcabc");
+        assert!(zlib_decompress(&data[..8], 100).is_none());
+    }
+
+    #[test]
+    fn gzip_member() {
+        // gzip.compress(b"abcabcabcabc", mtime=0) from CPython.
+        let mut data = vec![
+            0x1F, 0x8B, 0x08, 0x00, 0x00, 0x00, 0x00, 0x00, 0x02, 0x03, 0x4B, 0x4C, 0x4A, 0x4E,
+            0x84, 0x21, 0x00, 0x34, 0x2A, 0x6E, 0x5A, 0x0C, 0x00, 0x00, 0x00,
+        ];
+        assert_eq!(gzip_decompress(&data, 100).unwrap(), b"abcabcabcabc");
+        // The deflate stream alone.
+        assert_eq!(
+            deflate_decompress(&data[10..17], 100).unwrap(),
+            b"abcabcabcabc"
+        );
+        // Truncated, too big, or a wrong length in the trailer.
+        assert!(gzip_decompress(&data[..20], 100).is_none());
+        assert!(gzip_decompress(&data, 10).is_none());
+        let last = data.len() - 4;
+        data[last] = 13;
+        assert!(gzip_decompress(&data, 100).is_none());
+    }
+
+    #[test]
+    fn gzip_skips_the_optional_header_fields() {
+        // FNAME and FCOMMENT set, then one final stored block "hi".
+        let mut data = vec![0x1F, 0x8B, 0x08, 0x18, 0, 0, 0, 0, 0, 0xFF];
+        data.extend_from_slice(b"hi.txt\0a comment\0");
+        data.extend_from_slice(&[0x01, 0x02, 0x00, 0xFD, 0xFF]);
+        data.extend_from_slice(b"hi");
+        data.extend_from_slice(&crc32(&[b"hi"]).to_le_bytes());
+        data.extend_from_slice(&2u32.to_le_bytes());
+        assert_eq!(gzip_decompress(&data, 100).unwrap(), b"hi");
+        // Not gzip at all.
+        assert!(gzip_decompress(b"<html></html>", 100).is_none());
+    }
+
+    #[test]
+    fn crc32_known_value() {
+        assert_eq!(crc32(&[b"123456789"]), 0xCBF4_3926);
+        assert_eq!(crc32(&[b"1234", b"56789"]), 0xCBF4_3926);
+    }
+
+    #[test]
+    fn adler32_known_value() {
+        assert_eq!(adler32(b"Wikipedia"), 0x11E6_0398);
+    }
+}
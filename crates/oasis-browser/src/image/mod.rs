@@ -1,7 +1,15 @@
 //! Image decode dispatch and scaling for the browser.
 
+mod gif;
+pub(crate) mod inflate;
+mod png;
+
+pub use gif::{DecodedGif, decode_gif};
 use oasis_types::backend::Color;
 
+/// Largest image decoded, in pixels (16 MiB of RGBA).
+const MAX_PIXELS: u64 = 4 * 1024 * 1024;
+
 /// Decoded image data (RGBA pixels).
 #[derive(Debug, Clone)]
 pub struct DecodedImage {
@@ -42,16 +50,17 @@ pub fn detect_format(data: &[u8]) -> ImageFormat {
 
 /// Decode an image from raw bytes.
 ///
-/// Returns the decoded RGBA pixel data with dimensions.
-///
-/// For v1.0, this provides a basic BMP decoder. JPEG and PNG require
-/// external crate support (handled by the backend or crate features).
+/// Returns the decoded RGBA pixel data with dimensions, or `None` for a
+/// malformed image or an unsupported format. BMP, PNG and GIF (its first
+/// frame; see [`decode_gif`] for the frame count) are decoded here; JPEG
+/// requires external crate support (handled by the backend or crate
+/// features).
 pub fn decode_image(data: &[u8]) -> Option<DecodedImage> {
     match detect_format(data) {
         ImageFormat::Bmp => decode_bmp(data),
-        ImageFormat::Png => None,  // Requires `png` crate
+        ImageFormat::Png => png::decode_png(data),
         ImageFormat::Jpeg => None, // Requires `jpeg-decoder` crate
-        ImageFormat::Gif => None,  // Requires `gif` crate
+        ImageFormat::Gif => decode_gif(data).map(|gif| gif.image),
         ImageFormat::Unknown => None,
     }
 }
@@ -339,7 +348,7 @@ mod tests {
         let w: u32 = 2;
         let h: u32 = 2;
         let bpp: u16 = 24;
-        let row_bytes = ((w * 3 + 3) / 4) * 4; // 8 bytes (padded)
+        let row_bytes = (w * 3).div_ceil(4) * 4; // 8 bytes (padded)
         let pixel_data_size = row_bytes * h;
         let file_size = 54 + pixel_data_size;
 
@@ -425,7 +434,7 @@ mod tests {
     }
 
     #[test]
-    fn decode_image_returns_none_for_png() {
+    fn decode_image_returns_none_for_corrupt_png() {
         // PNG magic followed by garbage.
         let data = [0x89, 0x50, 0x4E, 0x47, 0x0D, 0x0A, 0x1A, 0x0A];
         assert!(decode_image(&data).is_none());
@@ -6,8 +6,10 @@
 
 use super::box_model::*;
 use super::inline::layout_inline;
-use crate::css::values::{ComputedStyle, Dimension, Display, ListStyleType};
+use super::table::{encode_spans, layout_table};
+use crate::css::values::{BorderStyle, ComputedStyle, Dimension, Display, ListStyleType};
 use crate::html::dom::{Document, ElementData, NodeId, NodeKind, TagName};
+use oasis_types::backend::Color;
 
 // -------------------------------------------------------------------
 // TextMeasurer trait
@@ -28,17 +30,48 @@ pub trait TextMeasurer {
 // Build layout tree from DOM
 // -------------------------------------------------------------------
 
+/// Size of an image box whose image is not (yet) decoded and that has
+/// no `width`/`height` attributes.
+pub const IMAGE_PLACEHOLDER_SIZE: u32 = 16;
+
 /// Build a layout tree from a styled DOM tree.
 ///
 /// Starts from the `<body>` element (or the document root if no body
 /// is found). The returned `LayoutBox` is the root block box with its
-/// dimensions laid out to fit the given viewport.
+/// dimensions laid out to fit the given viewport. Images are sized
+/// from their attributes or as placeholders; see
+/// [`build_layout_tree_with_images`].
 pub fn build_layout_tree(
+    doc: &Document,
+    styles: &[Option<ComputedStyle>],
+    measurer: &dyn TextMeasurer,
+    viewport_width: f32,
+    viewport_height: f32,
+) -> LayoutBox {
+    build_layout_tree_with_images(
+        doc,
+        styles,
+        measurer,
+        viewport_width,
+        viewport_height,
+        &|_| None,
+    )
+}
+
+/// Build a layout tree, sizing `<img>` boxes from the intrinsic size
+/// `image_size` reports for their `src` attribute.
+///
+/// CSS `width`/`height` and then the `width`/`height` attributes take
+/// precedence (a single one keeps the aspect ratio) and images are
+/// scaled down to the viewport width. Images of unknown size without a
+/// specified size get a placeholder box wide enough for their alt text.
+pub fn build_layout_tree_with_images(
     doc: &Document,
     styles: &[Option<ComputedStyle>],
     measurer: &dyn TextMeasurer,
     viewport_width: f32,
     _viewport_height: f32,
+    image_size: &dyn Fn(&str) -> Option<(u32, u32)>,
 ) -> LayoutBox {
     let start_node = doc.body().unwrap_or(doc.root);
     let style = styles
@@ -53,8 +86,9 @@ pub fn build_layout_tree(
 
     // Recursively build children.
     let children = doc.get(start_node).children.clone();
-    let child_boxes = build_children(doc, &children, styles);
-    root.children = wrap_anonymous(child_boxes);
+    let child_boxes = build_children(doc, &children, styles, 1);
+    root.children = wrap_anonymous(child_boxes, &root.style);
+    size_images(&mut root, viewport_width as u32, measurer, image_size);
 
     // Layout from the root.
     root.dimensions.content.x = 0.0;
@@ -64,21 +98,88 @@ pub fn build_layout_tree(
     root
 }
 
+/// Give image boxes their display sizes.
+///
+/// Percentage widths are of `max_width`; percentage heights, which
+/// depend on the height of the containing block, count as unspecified.
+fn size_images(
+    layout_box: &mut LayoutBox,
+    max_width: u32,
+    measurer: &dyn TextMeasurer,
+    image_size: &dyn Fn(&str) -> Option<(u32, u32)>,
+) {
+    if let BoxType::Replaced(ReplacedContent::Image {
+        width,
+        height,
+        alt,
+        src,
+        ..
+    }) = &mut layout_box.box_type
+    {
+        let specified_w = match layout_box.style.width {
+            Dimension::Px(w) => Some(w.max(0.0) as u32),
+            Dimension::Percent(pct) => Some((max_width as f32 * pct.max(0.0) / 100.0) as u32),
+            Dimension::Auto => None,
+        };
+        let specified_h = match layout_box.style.height {
+            Dimension::Px(h) => Some(h.max(0.0) as u32),
+            Dimension::Percent(_) | Dimension::Auto => None,
+        };
+        (*width, *height) = match image_size(src) {
+            Some((w, h)) => {
+                crate::image::calculate_display_size(w, h, specified_w, specified_h, max_width)
+            },
+            None => {
+                let label_w = measurer.measure_text(alt, 8) + 4;
+                (
+                    specified_w.unwrap_or(label_w.max(IMAGE_PLACEHOLDER_SIZE)),
+                    specified_h.unwrap_or(IMAGE_PLACEHOLDER_SIZE),
+                )
+            },
+        };
+    }
+    for child in &mut layout_box.children {
+        size_images(child, max_width, measurer, image_size);
+    }
+}
+
 /// Recursively build child layout boxes for a list of DOM node IDs.
+///
+/// List items among the children are numbered from `first_ordinal`
+/// (the parent list's `start`); an item's `value` attribute resets the
+/// count. Each parent numbers its own items, so nested lists restart.
 fn build_children(
     doc: &Document,
     children: &[NodeId],
     styles: &[Option<ComputedStyle>],
+    first_ordinal: usize,
 ) -> Vec<LayoutBox> {
     let mut boxes = Vec::new();
+    let mut ordinal = first_ordinal;
     for &child_id in children {
-        if let Some(lb) = build_box_for_node(doc, child_id, styles) {
+        if let Some(mut lb) = build_box_for_node(doc, child_id, styles) {
+            if let BoxType::ListItem { marker } = &mut lb.box_type {
+                if let NodeKind::Element(elem) = &doc.get(child_id).kind
+                    && let Some(value) = usize_attribute(elem, "value")
+                {
+                    ordinal = value;
+                }
+                if let ListMarker::Decimal(n) = marker {
+                    *n = ordinal;
+                }
+                ordinal += 1;
+            }
             boxes.push(lb);
         }
     }
     boxes
 }
 
+/// Parse a non-negative integer attribute such as `<ol start>`.
+fn usize_attribute(elem: &ElementData, name: &str) -> Option<usize> {
+    elem.get_attribute(name)?.trim().parse().ok()
+}
+
 /// Build a single layout box for a DOM node. Returns `None` for
 /// `display: none`, comments, and nodes without styles.
 fn build_box_for_node(
@@ -95,13 +196,22 @@ fn build_box_for_node(
                 return None;
             }
 
+            // Hidden inputs take part in form submission but
+            // generate no box.
+            if elem.tag == TagName::Input && input_type(elem) == "hidden" {
+                return None;
+            }
+
             // Determine box type.
             let box_type = box_type_for_element(elem, &style);
 
             // Handle replaced elements.
-            if let Some(replaced) = replaced_content(elem) {
+            if let Some(replaced) = replaced_content(doc, node_id, elem) {
                 let mut lb = LayoutBox::new(BoxType::Replaced(replaced), style, Some(node_id));
                 lb.children = Vec::new();
+                if elem.tag == TagName::Img {
+                    apply_image_size_hints(elem, &mut lb.style);
+                }
                 return Some(lb);
             }
 
@@ -109,17 +219,37 @@ fn build_box_for_node(
 
             // Recursively build children.
             let child_ids = node.children.clone();
-            let child_boxes = build_children(doc, &child_ids, styles);
-            lb.children = wrap_anonymous(child_boxes);
+            let first_ordinal = match elem.tag {
+                TagName::Ol => usize_attribute(elem, "start").unwrap_or(1),
+                _ => 1,
+            };
+            let child_boxes = build_children(doc, &child_ids, styles, first_ordinal);
+            lb.children = wrap_anonymous(child_boxes, &lb.style);
+
+            // Table presentational attributes.
+            match elem.tag {
+                TagName::Td | TagName::Th => {
+                    let colspan = span_attribute(elem, "colspan");
+                    let rowspan = span_attribute(elem, "rowspan");
+                    encode_spans(&mut lb.style, colspan, rowspan);
+                },
+                TagName::Table => apply_table_border_hint(elem, &mut lb),
+                _ => {},
+            }
 
             Some(lb)
         },
         NodeKind::Text(text) => {
-            // Skip whitespace-only text nodes.
-            if text.trim().is_empty() {
+            let style = find_inherited_style(doc, node_id, styles);
+            // Skip whitespace-only text nodes, unless `white-space`
+            // keeps their spaces or line breaks.
+            let ws = style.white_space;
+            if text.trim().is_empty()
+                && !ws.preserves_spaces()
+                && !(ws.preserves_newlines() && text.contains('\n'))
+            {
                 return None;
             }
-            let style = find_inherited_style(doc, node_id, styles);
             let mut inline_style = style;
             inline_style.display = Display::Inline;
             let mut lb = LayoutBox::new(BoxType::Inline, inline_style, Some(node_id));
@@ -160,31 +290,168 @@ fn resolve_list_marker(style: &ComputedStyle) -> ListMarker {
 }
 
 /// Check if an element is a replaced element and return its content.
-fn replaced_content(elem: &ElementData) -> Option<ReplacedContent> {
+fn replaced_content(
+    doc: &Document,
+    node_id: NodeId,
+    elem: &ElementData,
+) -> Option<ReplacedContent> {
     match elem.tag {
         TagName::Img => {
-            let width = elem
-                .get_attribute("width")
-                .and_then(|v| v.parse::<u32>().ok())
-                .unwrap_or(0);
-            let height = elem
-                .get_attribute("height")
-                .and_then(|v| v.parse::<u32>().ok())
-                .unwrap_or(0);
             let alt = elem.get_attribute("alt").unwrap_or("").to_string();
+            let src = elem.get_attribute("src").unwrap_or("").trim().to_string();
             Some(ReplacedContent::Image {
-                width,
-                height,
+                width: 0,
+                height: 0,
                 texture: None,
                 alt,
+                src,
             })
         },
         TagName::Hr => Some(ReplacedContent::HorizontalRule),
         TagName::Br => Some(ReplacedContent::LineBreak),
+        TagName::Input => {
+            let value = elem.get_attribute("value").unwrap_or("");
+            match input_type(elem).as_str() {
+                "text" | "search" | "email" | "url" | "tel" | "number" | "password" => {
+                    let size = elem
+                        .get_attribute("size")
+                        .and_then(|v| v.trim().parse::<u32>().ok())
+                        .filter(|&n| n > 0)
+                        .unwrap_or(20);
+                    Some(ReplacedContent::TextInput {
+                        value: value.to_string(),
+                        size,
+                        masked: input_type(elem) == "password",
+                    })
+                },
+                "submit" | "reset" | "button" => {
+                    let label = match (elem.get_attribute("value"), input_type(elem).as_str()) {
+                        (Some(v), _) => v.to_string(),
+                        (None, "submit") => "Submit".to_string(),
+                        (None, "reset") => "Reset".to_string(),
+                        (None, _) => String::new(),
+                    };
+                    Some(ReplacedContent::Button { label })
+                },
+                // Checkboxes, radios, etc. are not rendered yet.
+                _ => None,
+            }
+        },
+        TagName::Button => {
+            let label = doc.text_content(node_id).trim().to_string();
+            Some(ReplacedContent::Button { label })
+        },
         _ => None,
     }
 }
 
+/// The lower-cased `type` attribute of an `<input>`, defaulting to
+/// `"text"`.
+pub fn input_type(elem: &ElementData) -> String {
+    elem.get_attribute("type")
+        .map(|t| t.trim().to_ascii_lowercase())
+        .filter(|t| !t.is_empty())
+        .unwrap_or_else(|| "text".to_string())
+}
+
+/// Parse a `colspan` / `rowspan` attribute, defaulting to 1.
+fn span_attribute(elem: &ElementData, name: &str) -> usize {
+    elem.get_attribute(name)
+        .and_then(|v| v.trim().parse::<usize>().ok())
+        .unwrap_or(1)
+        .max(1)
+}
+
+/// Apply the `<img width height>` presentational hints: each attribute
+/// sets the matching CSS size unless the author already gave one.
+fn apply_image_size_hints(elem: &ElementData, style: &mut ComputedStyle) {
+    if style.width == Dimension::Auto
+        && let Some(width) = dimension_attribute(elem, "width")
+    {
+        style.width = width;
+    }
+    if style.height == Dimension::Auto
+        && let Some(height) = dimension_attribute(elem, "height")
+    {
+        style.height = height;
+    }
+}
+
+/// Parse a dimension attribute the way browsers do: a number of pixels
+/// or, followed by `%`, a percentage. Anything else after the number
+/// (such as `px`) is ignored.
+fn dimension_attribute(elem: &ElementData, name: &str) -> Option<Dimension> {
+    let value = elem.get_attribute(name)?.trim_start();
+    let end = value
+        .find(|c: char| !c.is_ascii_digit() && c != '.')
+        .unwrap_or(value.len());
+    let number = value[..end].parse::<f32>().ok()?;
+    if value[end..].starts_with('%') {
+        Some(Dimension::Percent(number))
+    } else {
+        Some(Dimension::Px(number))
+    }
+}
+
+/// Apply the `<table border="N">` presentational hint.
+///
+/// The table gets an `N`-pixel solid frame and every cell belonging to
+/// it gets a 1-pixel solid border, matching legacy HTML rendering.
+/// `border="0"` disables the hint; an empty value means 1. Author
+/// borders (any non-zero border width) take precedence.
+fn apply_table_border_hint(elem: &ElementData, table: &mut LayoutBox) {
+    let Some(value) = elem.get_attribute("border") else {
+        return;
+    };
+    let width = value.trim().parse::<f32>().unwrap_or(1.0);
+    if width <= 0.0 {
+        return;
+    }
+    set_border_hint(&mut table.style, width);
+    for child in &mut table.children {
+        apply_cell_border_hint(child);
+    }
+}
+
+/// Give every cell under a table (through rows and row groups) a
+/// 1-pixel border. Nested tables are left alone.
+fn apply_cell_border_hint(lb: &mut LayoutBox) {
+    match lb.box_type {
+        BoxType::TableCell => set_border_hint(&mut lb.style, 1.0),
+        BoxType::TableWrapper => {},
+        _ => {
+            for child in &mut lb.children {
+                apply_cell_border_hint(child);
+            }
+        },
+    }
+}
+
+/// Set a solid grey border of `width` on all four edges unless the
+/// style already carries a border.
+fn set_border_hint(style: &mut ComputedStyle, width: f32) {
+    let has_border = style.border_top_width > 0.0
+        || style.border_right_width > 0.0
+        || style.border_bottom_width > 0.0
+        || style.border_left_width > 0.0;
+    if has_border {
+        return;
+    }
+    let color = Color::rgb(128, 128, 128);
+    style.border_top_width = width;
+    style.border_right_width = width;
+    style.border_bottom_width = width;
+    style.border_left_width = width;
+    style.border_top_style = BorderStyle::Solid;
+    style.border_right_style = BorderStyle::Solid;
+    style.border_bottom_style = BorderStyle::Solid;
+    style.border_left_style = BorderStyle::Solid;
+    style.border_top_color = color;
+    style.border_right_color = color;
+    style.border_bottom_color = color;
+    style.border_left_color = color;
+}
+
 /// Walk up the DOM to find an inherited style for a text node.
 fn find_inherited_style(
     doc: &Document,
@@ -207,8 +474,9 @@ fn find_inherited_style(
 /// wrap consecutive runs of inline children in anonymous block boxes.
 ///
 /// This ensures the block formatting context only contains block-level
-/// boxes, as required by CSS 2.1.
-fn wrap_anonymous(children: Vec<LayoutBox>) -> Vec<LayoutBox> {
+/// boxes, as required by CSS 2.1. Anonymous boxes inherit from
+/// `parent` so properties such as `text-align` reach the wrapped text.
+fn wrap_anonymous(children: Vec<LayoutBox>, parent: &ComputedStyle) -> Vec<LayoutBox> {
     if children.is_empty() {
         return children;
     }
@@ -228,7 +496,7 @@ fn wrap_anonymous(children: Vec<LayoutBox>) -> Vec<LayoutBox> {
     for child in children {
         if child.is_block_level() {
             if !inline_run.is_empty() {
-                let anon = make_anonymous_block(std::mem::take(&mut inline_run));
+                let anon = make_anonymous_block(std::mem::take(&mut inline_run), parent);
                 result.push(anon);
             }
             result.push(child);
@@ -239,14 +507,14 @@ fn wrap_anonymous(children: Vec<LayoutBox>) -> Vec<LayoutBox> {
 
     // Flush any trailing inline run.
     if !inline_run.is_empty() {
-        result.push(make_anonymous_block(inline_run));
+        result.push(make_anonymous_block(inline_run, parent));
     }
 
     result
 }
 
 /// Create an anonymous block box wrapping the given inline children.
-fn make_anonymous_block(children: Vec<LayoutBox>) -> LayoutBox {
+fn make_anonymous_block(children: Vec<LayoutBox>, parent: &ComputedStyle) -> LayoutBox {
     LayoutBox {
         box_type: BoxType::Anonymous,
         dimensions: Dimensions::default(),
@@ -254,7 +522,7 @@ fn make_anonymous_block(children: Vec<LayoutBox>) -> LayoutBox {
         node: None,
         style: ComputedStyle {
             display: Display::Block,
-            ..ComputedStyle::default()
+            ..ComputedStyle::inherit(parent)
         },
         text: None,
     }
@@ -287,6 +555,16 @@ pub fn layout_block(
     calculate_block_height(layout_box);
 }
 
+/// Lay out the children of a box whose content width and position
+/// have already been fixed by the caller, then compute its height.
+///
+/// Table cells use this: the table algorithm decides the cell width,
+/// and the cell's contents flow as an ordinary block container.
+pub(super) fn layout_block_contents(layout_box: &mut LayoutBox, measurer: &dyn TextMeasurer) {
+    layout_block_children(layout_box, measurer);
+    calculate_block_height(layout_box);
+}
+
 /// Resolve padding, border, and margin from the computed style into
 /// the layout box's dimensions.
 fn resolve_edge_sizes(layout_box: &mut LayoutBox, _containing_width: f32) {
@@ -299,12 +577,7 @@ fn resolve_edge_sizes(layout_box: &mut LayoutBox, _containing_width: f32) {
         left: s.padding_left,
     };
 
-    layout_box.dimensions.border = EdgeSizes {
-        top: s.border_top_width,
-        right: s.border_right_width,
-        bottom: s.border_bottom_width,
-        left: s.border_left_width,
-    };
+    layout_box.dimensions.border = EdgeSizes::border_of(s);
 
     layout_box.dimensions.margin = EdgeSizes {
         top: s.margin_top,
@@ -389,7 +662,34 @@ fn layout_block_children(parent: &mut LayoutBox, measurer: &dyn TextMeasurer) {
 
     for child in &mut parent.children {
         match child.box_type {
-            BoxType::Block | BoxType::ListItem { .. } | BoxType::TableWrapper => {
+            BoxType::TableWrapper => {
+                resolve_edge_sizes(child, content_width);
+                let collapsed = collapse_margins(prev_margin_bottom, child.dimensions.margin.top);
+
+                // The table grid is computed relative to a (0, 0)
+                // content origin, then moved into place.
+                let avail = (content_width - child.dimensions.margin.horizontal()).max(0.0);
+                let mut table = layout_table(&child.children, &child.style, avail, measurer);
+                table.node = child.node;
+                table.dimensions.margin = child.dimensions.margin;
+
+                let x = content_x
+                    + parent.dimensions.padding.left
+                    + table.dimensions.margin.left
+                    + table.dimensions.border.left
+                    + table.dimensions.padding.left;
+                let y = cursor_y
+                    + collapsed
+                    + table.dimensions.border.top
+                    + table.dimensions.padding.top;
+                table.translate(x, y);
+                *child = table;
+
+                let bb = child.dimensions.border_box();
+                cursor_y = bb.y + bb.height;
+                prev_margin_bottom = child.dimensions.margin.bottom;
+            },
+            BoxType::Block | BoxType::ListItem { .. } => {
                 // Resolve child's edge sizes first so we can read
                 // margins for positioning.
                 resolve_edge_sizes(child, content_width);
@@ -680,7 +980,7 @@ mod tests {
         let block_box = LayoutBox::new(BoxType::Block, block_style(), None);
         let inline_box2 = LayoutBox::new(BoxType::Inline, ComputedStyle::default(), None);
 
-        let wrapped = wrap_anonymous(vec![inline_box, block_box, inline_box2]);
+        let wrapped = wrap_anonymous(vec![inline_box, block_box, inline_box2], &block_style());
 
         // Should be: anon(inline), block, anon(inline)
         assert_eq!(wrapped.len(), 3);
@@ -693,7 +993,7 @@ mod tests {
     fn wrap_anonymous_all_blocks() {
         let b1 = LayoutBox::new(BoxType::Block, block_style(), None);
         let b2 = LayoutBox::new(BoxType::Block, block_style(), None);
-        let wrapped = wrap_anonymous(vec![b1, b2]);
+        let wrapped = wrap_anonymous(vec![b1, b2], &block_style());
         // No wrapping needed.
         assert_eq!(wrapped.len(), 2);
         assert!(matches!(wrapped[0].box_type, BoxType::Block));
@@ -704,9 +1004,378 @@ mod tests {
     fn wrap_anonymous_all_inline() {
         let i1 = LayoutBox::new(BoxType::Inline, ComputedStyle::default(), None);
         let i2 = LayoutBox::new(BoxType::Inline, ComputedStyle::default(), None);
-        let wrapped = wrap_anonymous(vec![i1, i2]);
+        let wrapped = wrap_anonymous(vec![i1, i2], &block_style());
         // No wrapping needed (all inline).
         assert_eq!(wrapped.len(), 2);
         assert!(matches!(wrapped[0].box_type, BoxType::Inline));
     }
+
+    // -- tables ---------------------------------------------------------
+
+    fn layout_html(src: &str, width: f32) -> LayoutBox {
+        let tokens = crate::html::tokenizer::Tokenizer::new(src).tokenize();
+        let doc = crate::html::tree_builder::TreeBuilder::build(tokens);
+        let styles = crate::css::cascade::style_document(&doc);
+        build_layout_tree(&doc, &styles, &FixedMeasurer, width, 272.0)
+    }
+
+    fn find_table(lb: &LayoutBox) -> Option<&LayoutBox> {
+        if matches!(lb.box_type, BoxType::TableWrapper) {
+            return Some(lb);
+        }
+        lb.children.iter().find_map(find_table)
+    }
+
+    #[test]
+    fn table_cells_form_a_grid() {
+        let root = layout_html(
+            "<table><tr><td>Alpha</td><td>Beta</td></tr>\
+             <tr><td>Gamma</td><td>Delta</td></tr></table>",
+            480.0,
+        );
+        let table = find_table(&root).expect("table box");
+        assert_eq!(table.children.len(), 2, "two rows");
+
+        let r0 = &table.children[0].children;
+        let r1 = &table.children[1].children;
+        assert_eq!(r0.len(), 2);
+        assert_eq!(r1.len(), 2);
+
+        // Cells in a row share a top edge; columns line up across rows.
+        assert_eq!(r0[0].dimensions.content.y, r0[1].dimensions.content.y);
+        assert!(r1[0].dimensions.content.y > r0[0].dimensions.content.y);
+        assert!(r0[1].dimensions.content.x > r0[0].dimensions.content.x);
+        assert_eq!(r0[1].dimensions.content.x, r1[1].dimensions.content.x);
+
+        // Text inside a cell is positioned within the cell.
+        let text = &r0[1].children[0];
+        assert_eq!(text.text.as_deref(), Some("Beta"));
+        assert!(text.dimensions.content.x >= r0[1].dimensions.content.x);
+    }
+
+    // -- white-space ----------------------------------------------------
+
+    /// Text boxes in paint order as `(text, x, y)`.
+    fn text_boxes(lb: &LayoutBox) -> Vec<(String, f32, f32)> {
+        let mut out = Vec::new();
+        if let Some(text) = &lb.text {
+            let c = &lb.dimensions.content;
+            out.push((text.clone(), c.x, c.y));
+        }
+        for child in &lb.children {
+            out.extend(text_boxes(child));
+        }
+        out
+    }
+
+    #[test]
+    fn pre_keeps_tab_indentation_columns() {
+        let root = layout_html(
+            "<pre>\nfn main() {\n\tif ok {\n\t\treturn;\n\t}\n\n}\n</pre>",
+            480.0,
+        );
+        let boxes = text_boxes(&root);
+        let texts: Vec<_> = boxes.iter().map(|(t, _, _)| t.as_str()).collect();
+        assert_eq!(
+            texts,
+            [
+                "fn main() {",
+                "        if ok {",
+                "                return;",
+                "        }",
+                "}"
+            ]
+        );
+        // Every line starts at the left edge, so indentation lines up in
+        // columns, and each source line (the blank one too) is a line.
+        assert!(boxes.iter().all(|(_, x, _)| *x == boxes[0].1));
+        let ys: Vec<f32> = boxes.iter().map(|(_, _, y)| *y).collect();
+        let lh = ys[1] - ys[0];
+        assert!(lh > 0.0);
+        assert_eq!(ys[3] - ys[0], 3.0 * lh);
+        assert_eq!(ys[4] - ys[0], 5.0 * lh);
+    }
+
+    #[test]
+    fn pre_and_nowrap_do_not_wrap_but_pre_wrap_does() {
+        let line = "let  total = first + second;";
+        let wide = |ws: &str| {
+            let root = layout_html(&format!("<p style=\"white-space: {ws}\">{line}</p>"), 80.0);
+            let boxes = text_boxes(&root);
+            let rows = boxes
+                .iter()
+                .map(|b| b.2.to_bits())
+                .collect::<std::collections::BTreeSet<_>>();
+            (boxes, rows.len())
+        };
+        assert_eq!(wide("pre").1, 1);
+        assert_eq!(wide("nowrap").1, 1);
+        assert!(wide("normal").1 > 1);
+        let (boxes, rows) = wide("pre-wrap");
+        assert!(rows > 1);
+        // The doubled space survives the wrap.
+        let joined: String = boxes.iter().map(|b| b.0.as_str()).collect();
+        assert_eq!(joined, line);
+    }
+
+    #[test]
+    fn line_break_element_starts_a_new_line() {
+        let root = layout_html("<p>one<br>two</p>", 480.0);
+        let boxes = text_boxes(&root);
+        assert_eq!(boxes.len(), 2);
+        assert_eq!(boxes[0].1, boxes[1].1);
+        assert!(boxes[1].2 > boxes[0].2);
+    }
+
+    #[test]
+    fn table_row_height_is_tallest_cell() {
+        let root = layout_html(
+            "<table><tr><td>x</td>\
+             <td>one two three four five six seven eight nine ten</td>\
+             </tr></table>",
+            80.0,
+        );
+        let table = find_table(&root).expect("table box");
+        let row = &table.children[0];
+        let short = &row.children[0];
+        let tall = &row.children[1];
+        assert!(tall.children.len() > 1, "long cell should wrap");
+        assert_eq!(
+            short.dimensions.content.height,
+            tall.dimensions.content.height
+        );
+        assert_eq!(
+            row.dimensions.content.height,
+            tall.dimensions.border_box().height
+        );
+    }
+
+    #[test]
+    fn table_columns_sized_by_widest_cell() {
+        let root = layout_html(
+            "<table><tr><td>a</td><td>a much longer cell</td></tr></table>",
+            480.0,
+        );
+        let table = find_table(&root).expect("table box");
+        let cells = &table.children[0].children;
+        assert!(cells[1].dimensions.content.width > cells[0].dimensions.content.width);
+    }
+
+    #[test]
+    fn table_follows_preceding_block() {
+        let root = layout_html("<p>intro</p><table><tr><td>cell</td></tr></table>", 480.0);
+        let table = find_table(&root).expect("table box");
+        let p = &root.children[0];
+        let p_bottom = p.dimensions.border_box().y + p.dimensions.border_box().height;
+        assert!(table.dimensions.border_box().y >= p_bottom);
+    }
+
+    #[test]
+    fn table_border_attribute_adds_borders() {
+        let root = layout_html("<table border=\"2\"><tr><td>a</td></tr></table>", 480.0);
+        let table = find_table(&root).expect("table box");
+        assert_eq!(table.dimensions.border.top, 2.0);
+        let cell = &table.children[0].children[0];
+        assert_eq!(cell.dimensions.border.left, 1.0);
+        assert_eq!(cell.style.border_left_style, BorderStyle::Solid);
+
+        let plain = layout_html("<table><tr><td>a</td></tr></table>", 480.0);
+        let table = find_table(&plain).expect("table box");
+        assert_eq!(table.dimensions.border.top, 0.0);
+    }
+
+    #[test]
+    fn table_colspan_attribute_spans_columns() {
+        let root = layout_html(
+            "<table><tr><td colspan=\"2\">wide</td></tr>\
+             <tr><td>a</td><td>b</td></tr></table>",
+            480.0,
+        );
+        let table = find_table(&root).expect("table box");
+        let wide = &table.children[0].children[0];
+        let narrow = &table.children[1].children[0];
+        assert!(wide.dimensions.content.width > narrow.dimensions.content.width);
+    }
+
+    // -- text alignment -------------------------------------------------
+
+    #[test]
+    fn text_align_shifts_lines_after_breaking() {
+        let root = layout_html(
+            "<div style=\"text-align: right\">tail<p>block</p></div>\
+             <center>mid</center>",
+            480.0,
+        );
+        // The anonymous box around "tail" inherits the div's alignment.
+        let div = &root.children[0];
+        let tail = &div.children[0].children[0];
+        assert_eq!(tail.text.as_deref(), Some("tail"));
+        let right_edge = tail.dimensions.content.x + tail.dimensions.content.width;
+        assert_eq!(right_edge, 480.0);
+
+        let center = &root.children[1];
+        let mid = &center.children[0];
+        let left_gap = mid.dimensions.content.x;
+        let right_gap = 480.0 - (left_gap + mid.dimensions.content.width);
+        assert!((left_gap - right_gap).abs() <= 1.0);
+    }
+
+    // -- lists ----------------------------------------------------------
+
+    fn collect_markers(lb: &LayoutBox, out: &mut Vec<String>) {
+        if let BoxType::ListItem { marker } = &lb.box_type {
+            out.push(format!("{marker:?}"));
+        }
+        for child in &lb.children {
+            collect_markers(child, out);
+        }
+    }
+
+    #[test]
+    fn list_items_are_numbered_per_list() {
+        let root = layout_html(
+            r#"<ol start="3"><li>a<li>b<ol><li>x<li>y</ol>
+            <li value="10">c<li style="display: none">gone<li>d</ol>
+            <ul><li>u<ul><li>v<ul><li>w</ul></ul></ul>"#,
+            480.0,
+        );
+        let mut markers = Vec::new();
+        collect_markers(&root, &mut markers);
+        assert_eq!(
+            markers,
+            [
+                "Decimal(3)",
+                "Decimal(4)",
+                "Decimal(1)",
+                "Decimal(2)",
+                "Decimal(10)",
+                "Decimal(11)",
+                "Disc",
+                "Circle",
+                "Square",
+            ]
+        );
+    }
+
+    #[test]
+    fn wrapped_list_item_lines_align_with_text() {
+        let root = layout_html(
+            "<ol><li>one two three four five six seven eight nine ten</li></ol>",
+            120.0,
+        );
+        let li = &root.children[0].children[0];
+        assert!(matches!(li.box_type, BoxType::ListItem { .. }));
+        // Each line starts at the item's content edge; the marker
+        // hangs to its left.
+        let mut line_starts: Vec<(f32, f32)> = Vec::new();
+        for frag in &li.children {
+            let (x, y) = (frag.dimensions.content.x, frag.dimensions.content.y);
+            match line_starts.iter_mut().find(|(_, ly)| *ly == y) {
+                Some(start) => start.0 = start.0.min(x),
+                None => line_starts.push((x, y)),
+            }
+        }
+        assert!(line_starts.len() > 1, "long item should wrap");
+        for (x, _) in line_starts {
+            assert_eq!(x, li.dimensions.content.x);
+        }
+    }
+
+    // -- images ---------------------------------------------------------
+
+    #[test]
+    fn images_are_sized_from_decoded_size_attributes_or_placeholder() {
+        let src = r#"<p><img src="a.bmp"><img src="a.bmp" width="20">
+            <img src="big.bmp"><img src="gone.bmp" alt="Lost cat">
+            <img src="gone.bmp" width="30" height="10"></p>"#;
+        let tokens = crate::html::tokenizer::Tokenizer::new(src).tokenize();
+        let doc = crate::html::tree_builder::TreeBuilder::build(tokens);
+        let styles = crate::css::cascade::style_document(&doc);
+        let root =
+            build_layout_tree_with_images(&doc, &styles, &FixedMeasurer, 200.0, 272.0, &|src| {
+                match src {
+                    "a.bmp" => Some((40, 30)),
+                    "big.bmp" => Some((800, 100)),
+                    _ => None,
+                }
+            });
+        let mut replaced = Vec::new();
+        collect_replaced(&root, &mut replaced);
+        let sizes: Vec<(u32, u32)> = replaced
+            .iter()
+            .map(|r| match r {
+                ReplacedContent::Image { width, height, .. } => (*width, *height),
+                _ => panic!("expected only images"),
+            })
+            .collect();
+        let alt_w = FixedMeasurer.measure_text("Lost cat", 8) + 4;
+        assert_eq!(
+            sizes,
+            [(40, 30), (20, 15), (200, 25), (alt_w, 16), (30, 10)]
+        );
+        assert!(matches!(&replaced[0], ReplacedContent::Image { src, .. } if src == "a.bmp"));
+    }
+
+    #[test]
+    fn image_size_attributes_accept_units_and_percentages_below_css() {
+        let src = r#"<p><img src="a.bmp" width="20px"><img src="a.bmp" height=" 60 ">
+            <img src="a.bmp" width="50%"><img src="gone.bmp" width="25%" height="10%">
+            <img src="a.bmp" width="10" style="width: 80px"></p>"#;
+        let tokens = crate::html::tokenizer::Tokenizer::new(src).tokenize();
+        let doc = crate::html::tree_builder::TreeBuilder::build(tokens);
+        let styles = crate::css::cascade::style_document(&doc);
+        let root =
+            build_layout_tree_with_images(&doc, &styles, &FixedMeasurer, 200.0, 272.0, &|src| {
+                (src == "a.bmp").then_some((40, 30))
+            });
+        let mut replaced = Vec::new();
+        collect_replaced(&root, &mut replaced);
+        let sizes: Vec<(u32, u32)> = replaced
+            .iter()
+            .map(|r| match r {
+                ReplacedContent::Image { width, height, .. } => (*width, *height),
+                _ => panic!("expected only images"),
+            })
+            .collect();
+        assert_eq!(sizes, [(20, 15), (80, 60), (100, 75), (50, 16), (80, 60)]);
+    }
+
+    // -- form controls --------------------------------------------------
+
+    fn collect_replaced(lb: &LayoutBox, out: &mut Vec<ReplacedContent>) {
+        if let BoxType::Replaced(r) = &lb.box_type {
+            out.push(r.clone());
+        }
+        for child in &lb.children {
+            collect_replaced(child, out);
+        }
+    }
+
+    #[test]
+    fn form_controls_become_replaced_boxes() {
+        let root = layout_html(
+            r#"<form><input type="hidden" name="h" value="x">
+            <input name="q" value="v" size="4"><input type="password" value="pw">
+            <input type="submit"><button>Send <b>now</b></button>
+            <input type="checkbox"></form>"#,
+            480.0,
+        );
+        let mut replaced = Vec::new();
+        collect_replaced(&root, &mut replaced);
+        assert_eq!(replaced.len(), 4);
+        assert!(matches!(
+            &replaced[0],
+            ReplacedContent::TextInput { value, size: 4, masked: false } if value == "v"
+        ));
+        assert!(matches!(
+            &replaced[1],
+            ReplacedContent::TextInput {
+                size: 20,
+                masked: true,
+                ..
+            }
+        ));
+        assert!(matches!(&replaced[2], ReplacedContent::Button { label } if label == "Submit"));
+        assert!(matches!(&replaced[3], ReplacedContent::Button { label } if label == "Send now"));
+    }
 }
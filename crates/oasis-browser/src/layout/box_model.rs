@@ -3,7 +3,7 @@
 //! Defines rectangles, edge sizes, dimensions, box types, and the layout
 //! tree data structures used by block and inline layout algorithms.
 
-use crate::css::values::ComputedStyle;
+use crate::css::values::{BorderStyle, ComputedStyle};
 use crate::html::dom::NodeId;
 use oasis_types::backend::TextureId;
 
@@ -81,6 +81,24 @@ impl EdgeSizes {
         }
     }
 
+    /// Used border widths of `style`. A side whose `border-style` is
+    /// `none` has no border, whatever its specified width.
+    pub fn border_of(style: &ComputedStyle) -> Self {
+        let used = |width: f32, style: BorderStyle| {
+            if style == BorderStyle::None {
+                0.0
+            } else {
+                width
+            }
+        };
+        Self {
+            top: used(style.border_top_width, style.border_top_style),
+            right: used(style.border_right_width, style.border_right_style),
+            bottom: used(style.border_bottom_width, style.border_bottom_style),
+            left: used(style.border_left_width, style.border_left_style),
+        }
+    }
+
     /// Total horizontal size (left + right).
     pub fn horizontal(&self) -> f32 {
         self.left + self.right
@@ -160,17 +178,33 @@ pub enum ListMarker {
     None,
 }
 
-/// Content for replaced elements (img, hr, br).
+/// Content for replaced elements (img, hr, br, form controls).
 #[derive(Debug, Clone)]
 pub enum ReplacedContent {
+    /// `<img>`. `width` and `height` are the display size, filled in
+    /// before layout from the box's CSS size (which the `width` and
+    /// `height` attributes feed) and the decoded image; `src` is the
+    /// unresolved attribute value.
     Image {
         width: u32,
         height: u32,
         texture: Option<TextureId>,
         alt: String,
+        src: String,
     },
     HorizontalRule,
     LineBreak,
+    /// Single-line text `<input>`. `size` is the visible width in
+    /// characters; `masked` hides the value (`type=password`).
+    TextInput {
+        value: String,
+        size: u32,
+        masked: bool,
+    },
+    /// `<button>` or `<input type=submit|reset|button>`.
+    Button {
+        label: String,
+    },
 }
 
 /// A single box in the layout tree.
@@ -210,6 +244,36 @@ impl LayoutBox {
     pub fn is_inline_level(&self) -> bool {
         matches!(self.box_type, BoxType::Inline | BoxType::InlineBlock)
     }
+
+    /// Shift this box and all of its descendants by `(dx, dy)`.
+    ///
+    /// Used when a subtree is laid out relative to a local origin
+    /// (e.g. table cells) and must then be moved into place.
+    pub fn translate(&mut self, dx: f32, dy: f32) {
+        self.dimensions.content.x += dx;
+        self.dimensions.content.y += dy;
+        for child in &mut self.children {
+            child.translate(dx, dy);
+        }
+    }
+
+    /// Find the first box (depth-first) generated by DOM node `node`.
+    pub fn find_node(&self, node: NodeId) -> Option<&LayoutBox> {
+        if self.node == Some(node) {
+            return Some(self);
+        }
+        self.children.iter().find_map(|child| child.find_node(node))
+    }
+
+    /// Mutable variant of [`find_node`](Self::find_node).
+    pub fn find_node_mut(&mut self, node: NodeId) -> Option<&mut LayoutBox> {
+        if self.node == Some(node) {
+            return Some(self);
+        }
+        self.children
+            .iter_mut()
+            .find_map(|child| child.find_node_mut(node))
+    }
 }
 
 /// A line box containing inline fragments.
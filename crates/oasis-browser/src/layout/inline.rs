@@ -31,6 +31,16 @@ pub fn layout_inline(parent: &mut LayoutBox, measurer: &dyn TextMeasurer) {
     let mut current_line = LineBox::new(available_width);
 
     for fragment in &fragments {
+        if is_forced_break(fragment) {
+            // An empty line still takes up a line (blank lines in `<pre>`).
+            lines.push(current_line);
+            current_line = LineBox::new(available_width);
+            continue;
+        }
+        if !wraps(fragment) {
+            current_line.fragments.push(fragment.clone());
+            continue;
+        }
         if !current_line.try_add(fragment) {
             lines.push(current_line);
             current_line = LineBox::new(available_width);
@@ -63,7 +73,10 @@ pub fn layout_inline(parent: &mut LayoutBox, measurer: &dyn TextMeasurer) {
         };
         line.baseline = line.height * 0.8; // simple approximation
 
-        // Position fragments horizontally.
+        // Position fragments horizontally. The space after the last
+        // word must not count towards the line width, or right and
+        // centered lines would sit short of their edge.
+        trim_trailing_space(line, measurer);
         let is_last_line = i == last_line_idx;
         position_fragments_on_line(
             line,
@@ -111,7 +124,7 @@ fn collect_inline_fragments(
                 });
             },
             BoxType::Replaced(replaced) => {
-                let (w, h) = replaced_dimensions(replaced);
+                let (w, h) = replaced_dimensions(replaced, &child.style, measurer);
                 fragments.push(InlineFragment::ReplacedInline {
                     replaced: replaced.clone(),
                     x: 0.0,
@@ -174,11 +187,30 @@ fn text_fragments_for_inline(
 }
 
 /// Get the dimensions of a replaced inline element.
-fn replaced_dimensions(replaced: &ReplacedContent) -> (f32, f32) {
+///
+/// Form controls are sized from their text at the element's font
+/// size, plus room for a 1px border and 2px (input) or 5px (button)
+/// horizontal padding.
+pub(super) fn replaced_dimensions(
+    replaced: &ReplacedContent,
+    style: &ComputedStyle,
+    measurer: &dyn TextMeasurer,
+) -> (f32, f32) {
+    let font_size = style.font_size as u16;
+    let control_height = style.font_size + 6.0;
     match replaced {
         ReplacedContent::Image { width, height, .. } => (*width as f32, *height as f32),
         ReplacedContent::HorizontalRule => (0.0, 2.0),
         ReplacedContent::LineBreak => (0.0, 0.0),
+        ReplacedContent::TextInput { size, .. } => {
+            let sample = "0".repeat(*size as usize);
+            let w = measurer.measure_text(&sample, font_size) as f32 + 6.0;
+            (w, control_height)
+        },
+        ReplacedContent::Button { label } => {
+            let w = measurer.measure_text(label, font_size) as f32 + 12.0;
+            (w, control_height)
+        },
     }
 }
 
@@ -280,29 +312,74 @@ fn position_fragments_on_line(
             }
         },
         TextAlign::Justify => {
-            if is_last_line || line.fragments.len() <= 1 {
-                // Last line or single fragment: left-align.
-                let mut x = content_x;
-                for frag in &mut line.fragments {
-                    set_fragment_x(frag, x);
-                    x += frag.width();
-                }
+            // Stretch the spaces between words; a boundary between two
+            // fragments of the same word (e.g. `<b>Hel</b>lo`) is not a
+            // gap.
+            let last = line.fragments.len().saturating_sub(1);
+            let gaps = line.fragments[..last]
+                .iter()
+                .filter(|f| ends_with_space(f))
+                .count();
+            let gap_extra = if is_last_line || gaps == 0 {
+                // Last line or a single word: left-align.
+                0.0
             } else {
-                let gaps = line.fragments.len() - 1;
-                let gap_extra = extra / gaps as f32;
-                let mut x = content_x;
-                for (i, frag) in line.fragments.iter_mut().enumerate() {
-                    set_fragment_x(frag, x);
-                    x += frag.width();
-                    if i < gaps {
-                        x += gap_extra;
-                    }
+                extra / gaps as f32
+            };
+            let mut x = content_x;
+            for (i, frag) in line.fragments.iter_mut().enumerate() {
+                set_fragment_x(frag, x);
+                x += frag.width();
+                if i < last && ends_with_space(frag) {
+                    x += gap_extra;
                 }
             }
         },
     }
 }
 
+/// Whether a fragment ends the line: a `<br>`, or a newline kept by
+/// `white-space`.
+fn is_forced_break(frag: &InlineFragment) -> bool {
+    match frag {
+        InlineFragment::Text { text, .. } => text == "\n",
+        InlineFragment::ReplacedInline { replaced, .. } => {
+            matches!(replaced, ReplacedContent::LineBreak)
+        },
+        InlineFragment::InlineBox { .. } => false,
+    }
+}
+
+/// Whether a line may break before this fragment when it does not fit.
+fn wraps(frag: &InlineFragment) -> bool {
+    match frag {
+        InlineFragment::Text { style, .. } => style.white_space.wraps(),
+        _ => true,
+    }
+}
+
+/// Drop the trailing space of the last text fragment on a line, unless
+/// its spaces are preserved.
+fn trim_trailing_space(line: &mut LineBox, measurer: &dyn TextMeasurer) {
+    if let Some(InlineFragment::Text {
+        text, width, style, ..
+    }) = line.fragments.last_mut()
+        && text.ends_with(' ')
+        && !style.white_space.preserves_spaces()
+    {
+        text.pop();
+        *width = (*width - measure_space(style.font_size, measurer)).max(0.0);
+    }
+}
+
+/// Whether a fragment ends at a word boundary.
+fn ends_with_space(frag: &InlineFragment) -> bool {
+    match frag {
+        InlineFragment::Text { text, .. } => text.ends_with(' '),
+        _ => false,
+    }
+}
+
 /// Set the x position on a fragment.
 fn set_fragment_x(frag: &mut InlineFragment, x: f32) {
     match frag {
@@ -535,6 +612,54 @@ mod tests {
         }
     }
 
+    #[test]
+    fn right_aligned_line_ignores_trailing_space() {
+        let m = FixedMeasurer;
+        let mut parent = anon_parent(200.0);
+        parent.style.text_align = TextAlign::Right;
+        let mut word = LayoutBox::new(BoxType::Inline, inline_style(), None);
+        word.text = Some("hello ".into());
+        parent.children = vec![word];
+
+        layout_inline(&mut parent, &m);
+
+        let text = &parent.children[0];
+        assert_eq!(text.text.as_deref(), Some("hello"));
+        assert_eq!(
+            text.dimensions.content.x + text.dimensions.content.width,
+            200.0
+        );
+    }
+
+    #[test]
+    fn justify_stretches_word_gaps_only() {
+        let m = FixedMeasurer;
+        let style = inline_style();
+        let mut line = LineBox::new(200.0);
+        // "ab" split across two fragments, then a second word.
+        for f in make_text_fragments("a", &style, None, &m)
+            .iter()
+            .chain(&make_text_fragments("b c", &style, None, &m))
+        {
+            line.try_add(f);
+        }
+        trim_trailing_space(&mut line, &m);
+
+        position_fragments_on_line(&mut line, 200.0, TextAlign::Justify, false, 0.0);
+
+        let xs: Vec<f32> = line
+            .fragments
+            .iter()
+            .map(|f| match f {
+                InlineFragment::Text { x, .. } => *x,
+                _ => unreachable!(),
+            })
+            .collect();
+        // "a" and "b" stay adjacent; "c" is pushed to the right edge.
+        assert_eq!(xs[1], line.fragments[0].width());
+        assert_eq!(xs[2] + line.fragments[2].width(), 200.0);
+    }
+
     // -- white-space: nowrap prevents breaks --------------------------
 
     #[test]
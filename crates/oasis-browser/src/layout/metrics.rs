@@ -0,0 +1,120 @@
+//! Glyph metrics read from the rendering backend.
+//!
+//! Pages are laid out while navigating, when no backend is at hand, so
+//! the browser keeps a [`GlyphTable`] of the backend's glyph advances and
+//! measures text with that. This makes line breaking follow a
+//! proportional backend font (such as the PSP system font) instead of
+//! the bitmap font metrics of [`SimpleTextMeasurer`](crate::SimpleTextMeasurer).
+
+use oasis_types::backend::{SdiBackend, bitmap_glyph_advance};
+
+use super::block::TextMeasurer;
+
+/// Largest font size the table holds advances for. Larger sizes scale
+/// the advances at this size.
+pub const MAX_TABLE_SIZE: u16 = 48;
+
+/// First and last glyph in the table (printable ASCII).
+const FIRST_GLYPH: char = ' ';
+const LAST_GLYPH: char = '~';
+const GLYPH_COUNT: usize = LAST_GLYPH as usize - FIRST_GLYPH as usize + 1;
+
+/// Text used to tell fonts apart, and the sizes it is measured at.
+const PROBE_TEXT: &str = "Wil .0mM?";
+const PROBE_SIZES: [u16; 2] = [12, 24];
+
+/// Widths of `PROBE_TEXT` as measured by `measurer`. Two measurers with
+/// the same signature are assumed to use the same font.
+pub fn signature(measurer: &dyn TextMeasurer) -> [u32; 2] {
+    PROBE_SIZES.map(|size| measurer.measure_text(PROBE_TEXT, size))
+}
+
+/// Measures text through a backend directly.
+pub struct BackendTextMeasurer<'a>(pub &'a dyn SdiBackend);
+
+impl TextMeasurer for BackendTextMeasurer<'_> {
+    fn measure_text(&self, text: &str, font_size: u16) -> u32 {
+        self.0.measure_text(text, font_size)
+    }
+}
+
+/// Advance widths of the printable ASCII glyphs at every font size from
+/// 1 to [`MAX_TABLE_SIZE`], as reported by a backend.
+///
+/// Other characters are measured with the bitmap font metrics.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GlyphTable {
+    /// `advances[size - 1][ch - FIRST_GLYPH]`.
+    advances: Vec<[u16; GLYPH_COUNT]>,
+}
+
+impl GlyphTable {
+    /// Read the glyph advances of `backend`.
+    pub fn from_backend(backend: &dyn SdiBackend) -> Self {
+        let advances = (1..=MAX_TABLE_SIZE)
+            .map(|size| {
+                let mut row = [0u16; GLYPH_COUNT];
+                for (slot, ch) in row.iter_mut().zip(FIRST_GLYPH..=LAST_GLYPH) {
+                    *slot = backend.glyph_advance(ch, size).min(u16::MAX as u32) as u16;
+                }
+                row
+            })
+            .collect();
+        Self { advances }
+    }
+
+    /// Advance width of `ch` at `font_size`.
+    pub fn glyph_advance(&self, ch: char, font_size: u16) -> u32 {
+        if !(FIRST_GLYPH..=LAST_GLYPH).contains(&ch) {
+            return bitmap_glyph_advance(ch, font_size);
+        }
+        let index = ch as usize - FIRST_GLYPH as usize;
+        let size = font_size.max(1);
+        if size <= MAX_TABLE_SIZE {
+            return self.advances[size as usize - 1][index] as u32;
+        }
+        let base = self.advances[MAX_TABLE_SIZE as usize - 1][index] as u32;
+        (base * size as u32 + MAX_TABLE_SIZE as u32 / 2) / MAX_TABLE_SIZE as u32
+    }
+}
+
+impl TextMeasurer for GlyphTable {
+    fn measure_text(&self, text: &str, font_size: u16) -> u32 {
+        text.chars()
+            .map(|ch| self.glyph_advance(ch, font_size))
+            .sum()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::SimpleTextMeasurer;
+    use crate::test_utils::MockBackend;
+
+    #[test]
+    fn table_matches_the_bitmap_backend() {
+        let backend = MockBackend::new();
+        let table = GlyphTable::from_backend(&backend);
+        for size in [1, 8, 12, 16, 24, MAX_TABLE_SIZE] {
+            assert_eq!(
+                table.measure_text("Hello, world!", size),
+                SimpleTextMeasurer.measure_text("Hello, world!", size),
+            );
+        }
+        assert_eq!(signature(&table), signature(&BackendTextMeasurer(&backend)));
+    }
+
+    #[test]
+    fn large_sizes_and_other_characters() {
+        let table = GlyphTable::from_backend(&MockBackend::new());
+        // 'A' is 7px at scale 1, so 42px at 48 and 56px at 64.
+        assert_eq!(table.glyph_advance('A', MAX_TABLE_SIZE), 42);
+        assert_eq!(table.glyph_advance('A', 64), 56);
+        assert_eq!(table.glyph_advance('A', 0), table.glyph_advance('A', 1));
+        assert_eq!(
+            table.glyph_advance('\u{e9}', 16),
+            bitmap_glyph_advance('\u{e9}', 16)
+        );
+    }
+}
@@ -9,5 +9,6 @@ pub mod block;
 pub mod box_model;
 pub mod float;
 pub mod inline;
+pub mod metrics;
 pub mod table;
 pub mod text;
@@ -116,7 +116,7 @@ pub fn layout_table(
     let table_border_h = if border_collapse {
         0.0
     } else {
-        style.border_left_width + style.border_right_width
+        EdgeSizes::border_of(style).horizontal()
     };
     let table_padding_h = style.padding_left + style.padding_right;
     let total_spacing = if border_collapse {
@@ -352,10 +352,27 @@ fn measure_cell_widths(tl: &mut TableLayout, measurer: &dyn TextMeasurer) {
 
 /// Measure the minimum and preferred content widths of a layout box.
 ///
-/// The `measurer` is passed through to recursive child measurements;
-/// leaf nodes use explicit CSS widths directly.
-#[allow(clippy::only_used_in_recursion)]
+/// Text leaves are measured with `measurer`: the minimum is the widest
+/// single word and the preferred width is the whole run on one line.
+/// Other leaves use their explicit CSS width (or intrinsic image
+/// width) directly.
 fn measure_box_widths(layout_box: &LayoutBox, measurer: &dyn TextMeasurer) -> (f32, f32) {
+    if let Some(ref text) = layout_box.text {
+        let font_size = layout_box.style.font_size as u16;
+        let pref = measurer.measure_text(text.trim(), font_size) as f32;
+        let min = text
+            .split_whitespace()
+            .map(|word| measurer.measure_text(word, font_size))
+            .max()
+            .unwrap_or(0) as f32;
+        return (min, pref);
+    }
+
+    if let BoxType::Replaced(ref replaced) = layout_box.box_type {
+        let (w, _) = super::inline::replaced_dimensions(replaced, &layout_box.style, measurer);
+        return (w, w);
+    }
+
     // Leaf cell with no children: use explicit width if set,
     // otherwise zero.
     if layout_box.children.is_empty() {
@@ -387,7 +404,7 @@ fn measure_box_widths(layout_box: &LayoutBox, measurer: &dyn TextMeasurer) -> (f
 
     // Account for cell padding and border.
     let pad_h = layout_box.style.padding_left + layout_box.style.padding_right;
-    let bdr_h = layout_box.style.border_left_width + layout_box.style.border_right_width;
+    let bdr_h = EdgeSizes::border_of(&layout_box.style).horizontal();
     let extra = pad_h + bdr_h;
 
     (total_min + extra, total_pref + extra)
@@ -539,37 +556,17 @@ fn layout_cell_content(
         bottom: s.padding_bottom,
         left: s.padding_left,
     };
-    cell_box.dimensions.border = EdgeSizes {
-        top: s.border_top_width,
-        right: s.border_right_width,
-        bottom: s.border_bottom_width,
-        left: s.border_left_width,
-    };
+    cell_box.dimensions.border = EdgeSizes::border_of(s);
 
     let pad_h = cell_box.dimensions.padding.horizontal();
     let bdr_h = cell_box.dimensions.border.horizontal();
     cell_box.dimensions.content.width = (available_width - pad_h - bdr_h).max(0.0);
 
-    // Compute content height from children.
-    let mut content_height: f32 = 0.0;
-    let content_width = cell_box.dimensions.content.width;
-
-    for child in &mut cell_box.children {
-        match child.box_type {
-            BoxType::Block
-            | BoxType::Anonymous
-            | BoxType::ListItem { .. }
-            | BoxType::TableWrapper => {
-                super::block::layout_block(child, content_width, measurer);
-                let mb = child.dimensions.margin_box();
-                content_height += mb.height;
-            },
-            _ => {
-                // Inline or other: approximate as a single line.
-                content_height += child.style.line_height;
-            },
-        }
-    }
+    // The cell's contents flow as a normal block container laid out
+    // at a (0, 0) content origin; `build_table_box` moves them into
+    // their final position.
+    super::block::layout_block_contents(cell_box, measurer);
+    let content_height = cell_box.dimensions.content.height;
 
     // Use explicit height if specified and larger.
     let explicit_h = match cell_box.style.height {
@@ -648,12 +645,7 @@ fn build_table_box(tl: &TableLayout, style: &ComputedStyle, containing_width: f3
         bottom: style.padding_bottom,
         left: style.padding_left,
     };
-    table_box.dimensions.border = EdgeSizes {
-        top: style.border_top_width,
-        right: style.border_right_width,
-        bottom: style.border_bottom_width,
-        left: style.border_left_width,
-    };
+    table_box.dimensions.border = EdgeSizes::border_of(style);
 
     // Center the table if its content width is less than the
     // containing width.
@@ -717,10 +709,15 @@ fn build_table_box(tl: &TableLayout, style: &ComputedStyle, containing_width: f3
             cell_height += spacing * (cell.rowspan - 1) as f32;
         }
 
-        cell_box.dimensions.content.x =
-            x + cell_box.dimensions.padding.left + cell_box.dimensions.border.left;
-        cell_box.dimensions.content.y =
-            y + cell_box.dimensions.padding.top + cell_box.dimensions.border.top;
+        // Move the cell (and the contents laid out in step 5) to its
+        // grid slot. Cells in a row share a top edge, so single-line
+        // cells in the same font share a baseline.
+        let old_x = cell_box.dimensions.content.x;
+        let old_y = cell_box.dimensions.content.y;
+        cell_box.translate(
+            x + cell_box.dimensions.padding.left + cell_box.dimensions.border.left - old_x,
+            y + cell_box.dimensions.padding.top + cell_box.dimensions.border.top - old_y,
+        );
         cell_box.dimensions.content.width = (cell_width
             - cell_box.dimensions.padding.horizontal()
             - cell_box.dimensions.border.horizontal())
@@ -800,6 +797,20 @@ fn make_empty_table(style: &ComputedStyle) -> LayoutBox {
     table_box
 }
 
+/// Encode `colspan` / `rowspan` into a cell style using the
+/// convention read back by [`extract_span_attrs`].
+///
+/// `colspan` is encoded as `min_width: Px(colspan * 1000.0)`.
+/// `rowspan` is encoded as `max_width: Px(rowspan * 1000.0)`.
+pub fn encode_spans(style: &mut ComputedStyle, colspan: usize, rowspan: usize) {
+    if colspan > 1 {
+        style.min_width = Dimension::Px(colspan as f32 * 1000.0);
+    }
+    if rowspan > 1 {
+        style.max_width = Dimension::Px(rowspan as f32 * 1000.0);
+    }
+}
+
 /// Create a table cell `LayoutBox` with colspan and rowspan encoded
 /// in the style for use in tests and the table layout algorithm.
 ///
@@ -813,12 +824,7 @@ pub fn make_cell_with_spans(
 ) -> LayoutBox {
     let mut cell_style = style.clone();
     cell_style.display = Display::TableCell;
-    if colspan > 1 {
-        cell_style.min_width = Dimension::Px(colspan as f32 * 1000.0);
-    }
-    if rowspan > 1 {
-        cell_style.max_width = Dimension::Px(rowspan as f32 * 1000.0);
-    }
+    encode_spans(&mut cell_style, colspan, rowspan);
     let mut lb = LayoutBox::new(BoxType::TableCell, cell_style, None);
     lb.children = children;
     lb
@@ -23,11 +23,16 @@ pub struct TextWord {
 // Whitespace collapsing
 // -------------------------------------------------------------------
 
+/// Columns between tab stops in preserved whitespace (the CSS
+/// `tab-size` default).
+pub const TAB_SIZE: usize = 8;
+
 /// Collapse whitespace according to the CSS `white-space` property.
 ///
 /// - `Normal` / `NoWrap`: collapse runs of whitespace to a single
 ///   space and strip leading/trailing whitespace.
-/// - `Pre` / `PreWrap`: preserve all whitespace as-is.
+/// - `Pre` / `PreWrap`: preserve all whitespace, expanding tabs to
+///   spaces up to the next tab stop.
 /// - `PreLine`: collapse spaces/tabs to a single space but preserve
 ///   newlines.
 pub fn collapse_whitespace(text: &str, white_space: WhiteSpace) -> String {
@@ -52,7 +57,7 @@ pub fn collapse_whitespace(text: &str, white_space: WhiteSpace) -> String {
             }
             result
         },
-        WhiteSpace::Pre | WhiteSpace::PreWrap => text.to_string(),
+        WhiteSpace::Pre | WhiteSpace::PreWrap => expand_tabs(text),
         WhiteSpace::PreLine => {
             let mut result = String::with_capacity(text.len());
             let mut in_space = false;
@@ -79,6 +84,34 @@ pub fn collapse_whitespace(text: &str, white_space: WhiteSpace) -> String {
     }
 }
 
+/// Replace each tab with spaces up to the next multiple of [`TAB_SIZE`]
+/// columns, counting from the start of each line.
+fn expand_tabs(text: &str) -> String {
+    if !text.contains('\t') {
+        return text.to_string();
+    }
+    let mut result = String::with_capacity(text.len());
+    let mut column = 0;
+    for ch in text.chars() {
+        match ch {
+            '\t' => {
+                let spaces = TAB_SIZE - column % TAB_SIZE;
+                result.extend(std::iter::repeat_n(' ', spaces));
+                column += spaces;
+            },
+            '\n' => {
+                result.push(ch);
+                column = 0;
+            },
+            _ => {
+                result.push(ch);
+                column += 1;
+            },
+        }
+    }
+    result
+}
+
 // -------------------------------------------------------------------
 // Word splitting
 // -------------------------------------------------------------------
@@ -92,8 +125,9 @@ pub fn collapse_whitespace(text: &str, white_space: WhiteSpace) -> String {
 pub fn split_into_words(text: &str, white_space: WhiteSpace) -> Vec<TextWord> {
     match white_space {
         WhiteSpace::Pre | WhiteSpace::PreWrap => {
-            // In pre modes, split only on newlines; preserve spaces
-            // within each line as a single chunk.
+            // Split on newlines. A `pre` line is one unbreakable chunk;
+            // a `pre-wrap` line may break after each run of spaces,
+            // which stays in the chunk before it.
             let mut words = Vec::new();
             for (i, line) in text.split('\n').enumerate() {
                 if i > 0 {
@@ -102,7 +136,12 @@ pub fn split_into_words(text: &str, white_space: WhiteSpace) -> Vec<TextWord> {
                         trailing_space: false,
                     });
                 }
-                if !line.is_empty() {
+                if line.is_empty() {
+                    continue;
+                }
+                if white_space == WhiteSpace::PreWrap {
+                    split_preserved_line(line, &mut words);
+                } else {
                     words.push(TextWord {
                         text: line.to_string(),
                         trailing_space: false,
@@ -150,6 +189,27 @@ fn split_line_into_words(line: &str, out: &mut Vec<TextWord>) {
     }
 }
 
+/// Split a line whose spaces are preserved into chunks that each end
+/// after a run of spaces, so that joining the chunks gives the line back.
+fn split_preserved_line(line: &str, out: &mut Vec<TextWord>) {
+    let mut chunk = String::new();
+    for ch in line.chars() {
+        if ch != ' ' && chunk.ends_with(' ') {
+            out.push(TextWord {
+                text: std::mem::take(&mut chunk),
+                trailing_space: false,
+            });
+        }
+        chunk.push(ch);
+    }
+    if !chunk.is_empty() {
+        out.push(TextWord {
+            text: chunk,
+            trailing_space: false,
+        });
+    }
+}
+
 // -------------------------------------------------------------------
 // Text measurement
 // -------------------------------------------------------------------
@@ -262,6 +322,24 @@ mod tests {
         assert_eq!(result, "hello world\n next");
     }
 
+    #[test]
+    fn pre_expands_tabs_to_tab_stops() {
+        let result = collapse_whitespace("\tfn\n\t\tx\nab\tc", WhiteSpace::Pre);
+        assert_eq!(result, "        fn\n                x\nab      c");
+    }
+
+    #[test]
+    fn pre_wrap_splits_after_space_runs() {
+        let words = split_into_words("  let  x = 1;", WhiteSpace::PreWrap);
+        let texts: Vec<_> = words.iter().map(|w| w.text.as_str()).collect();
+        assert_eq!(texts, ["  ", "let  ", "x ", "= ", "1;"]);
+        // A `pre` line stays whole.
+        assert_eq!(
+            split_into_words("  let  x", WhiteSpace::Pre)[0].text,
+            "  let  x"
+        );
+    }
+
     // -- word splitting -----------------------------------------------
 
     #[test]
@@ -6,20 +6,30 @@
 //! [`BrowserWidget`] -- the top-level component that the window manager
 //! drives.
 
+pub mod anchor;
+pub mod bookmarks_page;
 pub mod commands;
 pub mod config;
 pub mod css;
+pub mod download;
+pub mod field;
+pub mod find;
+pub mod form;
 pub mod gemini;
+pub mod history_page;
+pub mod history_view;
 pub mod html;
 pub mod image;
 pub mod layout;
 pub mod loader;
 pub mod nav;
+pub mod page_cache;
 pub mod paint;
 pub mod plugin;
 pub mod reader;
 pub mod scroll;
 pub mod skin;
+pub mod tab;
 
 #[cfg(test)]
 pub(crate) mod test_utils;
@@ -30,24 +40,47 @@ pub(crate) mod test_utils;
 
 pub use config::BrowserConfig;
 pub use loader::{ContentType, ResourceResponse, ResourceSource, Url};
-pub use nav::{Bookmark, HistoryEntry, NavigationController};
-pub use scroll::ScrollState;
+pub use nav::{Bookmark, Bookmarks, History, HistoryEntry, NavigationController, Visit};
+pub use scroll::{ScrollState, ScrollTuning};
+pub use tab::BrowserTab;
 
 // -----------------------------------------------------------------------
 // Imports
 // -----------------------------------------------------------------------
 
+use std::borrow::Cow;
 use std::collections::HashMap;
 
-use oasis_types::backend::{Color, SdiBackend};
+use oasis_types::backend::{Color, SdiBackend, TextureId};
 use oasis_types::error::Result;
 use oasis_types::input::{Button, InputEvent, Trigger};
+use oasis_ui::spinner::Spinner;
+use oasis_ui::text_input::{TextInput, TextInputResponse};
+use oasis_ui::widget::Widget;
+use oasis_ui::{DrawContext, Theme};
 use oasis_vfs::Vfs;
 
+use field::TextField;
+use gemini::known_hosts::{self, KnownHosts};
 use html::dom::NodeId;
-use loader::cache::{CacheEntry, ResourceCache};
+use layout::block::TextMeasurer;
+use layout::box_model::{BoxType, LayoutBox, ReplacedContent};
+use layout::metrics::{BackendTextMeasurer, GlyphTable};
+use loader::cache::{CacheEntry, CacheStats, ResourceCache};
 use loader::{ResourceRequest, load_resource};
-use paint::LinkRegion;
+use page_cache::{CachedPage, PageCache};
+use paint::{ControlKind, ControlRegion};
+
+/// Step applied by [`BrowserWidget::zoom_in`] and
+/// [`BrowserWidget::zoom_out`].
+const ZOOM_STEP: f32 = 0.25;
+
+/// Step applied by [`BrowserWidget::reader_increase_font`] and
+/// [`BrowserWidget::reader_decrease_font`].
+const READER_TEXT_STEP: f32 = 0.125;
+
+/// Frames a flashed status-bar message (e.g. the zoom level) stays up.
+const STATUS_FLASH_FRAMES: u32 = 90;
 
 // -----------------------------------------------------------------------
 // LoadingState
@@ -68,11 +101,12 @@ pub enum LoadingState {
 // SimpleTextMeasurer
 // -----------------------------------------------------------------------
 
-/// A text measurer that approximates glyph widths as 8 pixels per
-/// character, matching the 8x8 bitmap font used by OASIS backends.
+/// A text measurer using the proportional metrics of the 8x8 bitmap
+/// font used by OASIS backends. The browser switches to a
+/// [`GlyphTable`] read from the backend when its font differs.
 pub struct SimpleTextMeasurer;
 
-impl layout::block::TextMeasurer for SimpleTextMeasurer {
+impl TextMeasurer for SimpleTextMeasurer {
     fn measure_text(&self, text: &str, font_size: u16) -> u32 {
         oasis_types::backend::bitmap_measure_text(text, font_size)
     }
@@ -89,6 +123,14 @@ pub enum Focus {
     Content,
     /// Keyboard input goes to the URL bar for editing.
     UrlBar,
+    /// Keyboard input goes to the in-page find bar.
+    FindBar,
+    /// Keyboard input goes to the page's text input with this node.
+    FormField(NodeId),
+    /// The history list is shown in place of the page.
+    History,
+    /// Keyboard input goes to a Gemini input prompt.
+    GeminiInput,
 }
 
 // -----------------------------------------------------------------------
@@ -104,53 +146,77 @@ pub struct BrowserWidget {
     /// Visual and feature configuration.
     pub config: BrowserConfig,
 
-    /// Navigation controller (history, bookmarks).
-    nav: NavigationController,
+    /// Open tabs, each with its own history and page state. Never
+    /// empty.
+    tabs: Vec<BrowserTab>,
 
-    /// Scroll state for the content viewport.
-    scroll: ScrollState,
+    /// Index of the tab shown in the content viewport.
+    active_tab: usize,
 
-    /// Resource cache (LRU, bounded by byte size).
-    cache: ResourceCache,
+    /// Trigger currently held down, for Trigger+button chords.
+    held_trigger: Option<Trigger>,
 
-    /// Current loading state.
-    state: LoadingState,
+    /// Whether a button was pressed while `held_trigger` was down. A
+    /// trigger pages the content only when released without a chord.
+    trigger_chorded: bool,
 
-    /// The most recent error message (if any).
-    error_message: Option<String>,
+    /// Bookmarks, shared by all tabs.
+    bookmarks: Bookmarks,
 
-    /// Parsed DOM of the current page.
-    document: Option<html::dom::Document>,
+    /// Browsing history, shared by all tabs.
+    history: History,
 
-    /// Computed styles indexed by `NodeId`.
-    styles: Vec<Option<css::values::ComputedStyle>>,
+    /// Resource cache (LRU, bounded by byte size), shared by all tabs.
+    cache: ResourceCache,
 
-    /// Layout tree root for the current page.
-    layout_root: Option<layout::box_model::LayoutBox>,
+    /// Parsed pages for back/forward (LRU, bounded by entry count).
+    page_cache: PageCache,
 
-    /// Link regions from the most recent paint pass.
-    link_map: Vec<LinkRegion>,
+    /// Intrinsic sizes of decoded images by URL, used to size `<img>`
+    /// boxes. Only images that decoded successfully are listed.
+    image_sizes: HashMap<String, (u32, u32)>,
 
-    /// Map from DOM `NodeId` to `href` for `<a>` elements.
-    href_map: HashMap<NodeId, String>,
+    /// Images decoded for the current page, waiting for the next paint
+    /// to upload them as textures.
+    pending_images: HashMap<String, image::DecodedImage>,
 
-    /// Index of the currently focused link (-1 = none).
-    selected_link: i32,
+    /// Editing buffer for the focused form text input.
+    form_field: TextField,
 
     /// Which part of the chrome has keyboard focus.
     focus: Focus,
 
     /// URL bar editing buffer (populated when focus is `UrlBar`).
-    url_input: String,
+    url_field: TextInput,
+
+    /// Text cut or copied in the URL bar, waiting for the host to put it
+    /// on the clipboard.
+    copied_text: Option<String>,
+
+    /// History list rows and selection (shown when focus is `History`).
+    history_view: history_view::HistoryView,
+
+    /// Set while reloading a history entry, so the load updates that
+    /// entry instead of pushing a new one.
+    reloading_history: bool,
 
-    /// Cursor position within `url_input` (byte offset).
-    url_cursor: usize,
+    /// Non-renderable response awaiting a save/discard decision.
+    pending_download: Option<ResourceResponse>,
 
-    /// Whether reader mode is active.
-    reader_mode: bool,
+    /// Where the last download was saved, until the host takes it.
+    saved_download: Option<String>,
 
-    /// Reader-mode article HTML (re-parsed when toggled).
-    reader_html: Option<String>,
+    /// Gemini input prompt being answered (focus is `GeminiInput`).
+    gemini_input: Option<gemini::InputPrompt>,
+
+    /// Transient status-bar message (e.g. where a download was saved).
+    status_message: Option<String>,
+
+    /// Short-lived status-bar message and the frames it has left.
+    status_flash: Option<(String, u32)>,
+
+    /// Frames painted so far; drives the loading spinner.
+    frame: u32,
 
     /// Window position and size set by the window manager.
     window_x: i32,
@@ -160,6 +226,25 @@ pub struct BrowserWidget {
 
     /// Optional TLS provider for HTTPS and Gemini connections.
     tls: Option<Box<dyn oasis_net::tls::TlsProvider>>,
+
+    /// Pinned Gemini server certificates.
+    known_hosts: KnownHosts,
+
+    /// Set when bookmarks changed during navigation (which has no
+    /// mutable VFS) and still need writing through.
+    bookmarks_dirty: bool,
+
+    /// Set when the browsing history changed and still needs writing
+    /// through.
+    history_dirty: bool,
+
+    /// Glyph advances of the backend's font, when it is not the bitmap
+    /// font `SimpleTextMeasurer` assumes.
+    glyphs: Option<GlyphTable>,
+
+    /// [`layout::metrics::signature`] of the font pages are laid out
+    /// with.
+    glyph_signature: [u32; 2],
 }
 
 impl BrowserWidget {
@@ -168,32 +253,71 @@ impl BrowserWidget {
         let home = config.features.home_url.clone();
         let cache_bytes = config.cache_size_bytes();
         let smooth = config.smooth_scroll;
+        let page_cache_entries = config.page_cache_entries;
+        let mut tab = BrowserTab::new(&home, 238, smooth); // 272 - 34
+        tab.scroll.set_tuning(config.scroll_tuning);
+        let mut history = History::default();
+        history.set_max(config.max_history_entries);
         Self {
             config,
-            nav: NavigationController::new(&home),
-            scroll: ScrollState::new(238, smooth), // 272 - 34
+            tabs: vec![tab],
+            active_tab: 0,
+            held_trigger: None,
+            trigger_chorded: false,
+            bookmarks: Bookmarks::default(),
+            history,
             cache: ResourceCache::new(cache_bytes),
-            state: LoadingState::Idle,
-            error_message: None,
-            document: None,
-            styles: Vec::new(),
-            layout_root: None,
-            link_map: Vec::new(),
-            href_map: HashMap::new(),
-            selected_link: -1,
+            page_cache: PageCache::new(page_cache_entries),
+            image_sizes: HashMap::new(),
+            pending_images: HashMap::new(),
+            form_field: TextField::default(),
             focus: Focus::Content,
-            url_input: String::new(),
-            url_cursor: 0,
-            reader_mode: false,
-            reader_html: None,
+            url_field: TextInput::default(),
+            copied_text: None,
+            history_view: history_view::HistoryView::default(),
+            reloading_history: false,
+            pending_download: None,
+            saved_download: None,
+            gemini_input: None,
+            status_message: None,
+            status_flash: None,
+            frame: 0,
             window_x: 0,
             window_y: 0,
             window_w: 480,
             window_h: 272,
             tls: None,
+            known_hosts: KnownHosts::new(),
+            bookmarks_dirty: false,
+            history_dirty: false,
+            glyphs: None,
+            glyph_signature: layout::metrics::signature(&SimpleTextMeasurer),
         }
     }
 
+    /// Create a browser widget and load persisted bookmarks, browsing
+    /// history and Gemini certificate pins from `config.bookmarks_path`,
+    /// `config.history_path` and `config.known_hosts_path`, if set.
+    pub fn with_vfs(config: BrowserConfig, vfs: &dyn Vfs) -> Self {
+        let mut widget = Self::new(config);
+        if let Some(path) = widget.config.bookmarks_path.clone()
+            && let Err(e) = widget.bookmarks.load_from_vfs(vfs, &path)
+        {
+            log::warn!("failed to load bookmarks from {path}: {e}");
+        }
+        if let Some(path) = widget.config.history_path.clone()
+            && let Err(e) = widget.history.load_from_vfs(vfs, &path)
+        {
+            log::warn!("failed to load history from {path}: {e}");
+        }
+        if let Some(path) = widget.config.known_hosts_path.clone()
+            && let Err(e) = widget.known_hosts.load(vfs, &path)
+        {
+            log::warn!("failed to load known hosts from {path}: {e}");
+        }
+        widget
+    }
+
     /// Attach a TLS provider for HTTPS and Gemini support.
     pub fn set_tls_provider(&mut self, provider: Box<dyn oasis_net::tls::TlsProvider>) {
         self.tls = Some(provider);
@@ -205,8 +329,7 @@ impl BrowserWidget {
         self.window_y = y;
         self.window_w = w;
         self.window_h = h;
-        let vh = self.config.content_height(h) as i32;
-        self.scroll.set_viewport_height(vh);
+        self.update_viewport_heights();
     }
 
     // ---------------------------------------------------------------
@@ -215,43 +338,98 @@ impl BrowserWidget {
 
     /// Navigate to a URL using the VFS as the resource source.
     pub fn navigate_vfs(&mut self, url: &str, vfs: &dyn Vfs) {
-        self.state = LoadingState::Loading;
-        self.selected_link = -1;
-        self.reader_mode = false;
-        self.reader_html = None;
-        self.error_message = None;
-
-        let source = if self.config.features.sandbox_only {
-            ResourceSource::Vfs
-        } else {
-            ResourceSource::VfsThenNetwork
+        // The fragment picks a spot on the page; it is not part of the
+        // resource.
+        let (url, fragment) = match url.split_once('#') {
+            Some((url, fragment)) => (url, Some(fragment)),
+            None => (url, None),
         };
+        let tab = self.tab_mut();
+        tab.state = LoadingState::Loading;
+        tab.selected_link = -1;
+        tab.reader_mode = false;
+        tab.reader_html = None;
+        tab.error_message = None;
+        self.pending_download = None;
+        self.gemini_input = None;
+        self.status_message = None;
+
+        if url == bookmarks_page::BOOKMARKS_URL {
+            self.load_bookmarks_page();
+            return;
+        }
+        if url == history_page::HISTORY_URL {
+            self.load_history_page();
+            return;
+        }
 
         let request = ResourceRequest {
             url: url.to_string(),
-            base_url: self.nav.current_url().map(String::from),
-            source,
+            base_url: self.tab().nav.current_url().map(String::from),
+            source: self.resource_source(),
+            max_redirects: self.config.max_redirects,
+            timeout: self.config.request_timeout,
+            retries: self.config.network_retries,
         };
 
-        match load_resource(vfs, &request, self.tls.as_deref()) {
+        match load_resource(
+            vfs,
+            &request,
+            self.tls.as_deref(),
+            Some(&mut self.known_hosts),
+        ) {
             Ok(response) => {
                 self.process_response(response);
+                self.load_page_images(vfs);
+                if let Some(fragment) = fragment {
+                    self.scroll_to_fragment(fragment);
+                }
             },
             Err(e) => {
                 let err_resp = loader::vfs::error_page(url, &e.to_string());
                 self.process_response(err_resp);
-                self.state = LoadingState::Error;
-                self.error_message = Some(e.to_string());
+                self.tab_mut().state = LoadingState::Error;
+                self.tab_mut().error_message = Some(e.to_string());
             },
         }
+        self.record_visit();
+    }
+
+    /// Where resources are loaded from.
+    fn resource_source(&self) -> ResourceSource {
+        if self.config.features.sandbox_only {
+            ResourceSource::Vfs
+        } else {
+            ResourceSource::VfsThenNetwork
+        }
     }
 
     /// Process a loaded resource response.
+    ///
+    /// Content that cannot be rendered is held as a pending download
+    /// and the current page stays on screen under a save prompt.
     pub fn process_response(&mut self, response: ResourceResponse) {
+        if download::should_download(&response) {
+            self.tab_mut().state = LoadingState::Idle;
+            self.pending_download = Some(response);
+            return;
+        }
+        if let ContentType::GeminiInput { sensitive } = response.content_type {
+            let prompt = String::from_utf8_lossy(&response.body).into_owned();
+            self.load_gemini_input(&prompt, sensitive, &response.url);
+            return;
+        }
+
         let url = response.url.clone();
         let content_type = response.content_type;
+        let decode = || {
+            loader::charset::decode_text(
+                &response.body,
+                response.charset.as_deref(),
+                content_type == ContentType::Html,
+            )
+        };
 
-        // Cache the response.
         self.cache.insert(
             url.clone(),
             CacheEntry {
@@ -262,19 +440,16 @@ impl BrowserWidget {
 
         match content_type {
             ContentType::Html | ContentType::PlainText | ContentType::Unknown => {
-                let body = String::from_utf8_lossy(&response.body);
+                let body = decode();
                 self.load_html(&body, &url);
             },
             ContentType::GeminiText => {
-                let body = String::from_utf8_lossy(&response.body);
+                let body = decode();
                 self.load_gemini(&body, &url);
             },
             ContentType::Css => {
                 // CSS files are not directly renderable.
-                let wrapped = format!(
-                    "<html><body><pre>{}</pre></body></html>",
-                    String::from_utf8_lossy(&response.body)
-                );
+                let wrapped = format!("<html><body><pre>{}</pre></body></html>", decode());
                 self.load_html(&wrapped, &url);
             },
             _ if content_type.is_image() => {
@@ -307,34 +482,294 @@ impl BrowserWidget {
         // 2. Extract page title.
         let title = doc.title().unwrap_or_else(|| url.to_string());
 
-        // 3. CSS cascade with default stylesheet.
-        let ua_sheet = css::default::default_stylesheet();
-        let styles = css::cascade::style_tree(&doc, &[&ua_sheet], &[]);
+        // 3. CSS cascade: default stylesheet plus the page's own styles.
+        let styles = css::cascade::style_document(&doc);
 
         // 4. Build link href map from DOM.
         let href_map = Self::build_link_map(&doc);
 
         // 5. Build layout tree.
-        let content_h = self.config.content_height(self.window_h);
-        let layout_root = layout::block::build_layout_tree(
-            &doc,
+        let layout_root =
+            self.layout_document(&doc, &styles, url, self.config.clamped_text_scale());
+
+        // 6. Keep a parsed copy for back/forward, then store results.
+        self.page_cache.insert(
+            url.to_string(),
+            CachedPage {
+                document: doc.clone(),
+                styles: styles.clone(),
+                href_map: href_map.clone(),
+                layout_root: layout_root.clone(),
+                layout_width: self.window_w,
+                layout_scale: self.config.clamped_text_scale(),
+                layout_glyphs: self.glyph_signature,
+            },
+        );
+        let tab = self.tab_mut();
+        tab.document = Some(doc);
+        tab.styles = styles;
+        tab.href_map = href_map;
+        tab.layout_root = Some(layout_root);
+        tab.link_map.clear();
+        tab.control_map.clear();
+        tab.form_values.clear();
+        tab.scroll.reset();
+        tab.state = LoadingState::Idle;
+        tab.find.clear();
+        if matches!(self.focus, Focus::FindBar | Focus::FormField(_)) {
+            self.focus = Focus::Content;
+        }
+
+        // 7. Update navigation.
+        if self.reloading_history {
+            self.tab_mut().nav.update_title(&title);
+        } else {
+            self.tab_mut().nav.navigate(url, &title);
+        }
+    }
+
+    /// Lay out a document for the current window with its text scaled
+    /// by `scale`, sizing images whose `src` (relative to `base_url`)
+    /// has been decoded.
+    fn layout_document(
+        &self,
+        doc: &html::dom::Document,
+        styles: &[Option<css::values::ComputedStyle>],
+        base_url: &str,
+        scale: f32,
+    ) -> LayoutBox {
+        let base = Url::parse(base_url);
+        let image_size = |src: &str| {
+            let url = base.as_ref()?.resolve(src)?.to_string();
+            self.image_sizes.get(&url).copied()
+        };
+        let styles = if scale == 1.0 {
+            Cow::Borrowed(styles)
+        } else {
+            Cow::Owned(scale_styles(styles, scale))
+        };
+        layout::block::build_layout_tree_with_images(
+            doc,
             &styles,
-            &SimpleTextMeasurer,
+            self.text_measurer(),
             self.window_w as f32,
-            content_h as f32,
-        );
+            self.content_height() as f32,
+            &image_size,
+        )
+    }
+
+    /// Fetch and decode the current page's images that are not cached
+    /// yet, then lay the page out again if any image changed size.
+    ///
+    /// Decoded pixels wait in `pending_images` for the next paint to
+    /// upload them. Images that fail to load or decode keep their
+    /// placeholder box.
+    fn load_page_images(&mut self, vfs: &dyn Vfs) {
+        self.pending_images.clear();
+        let Some(base) = self.tab().nav.current_url().and_then(Url::parse) else {
+            return;
+        };
+        let Some(doc) = &self.tab().document else {
+            return;
+        };
+        let mut urls: Vec<String> = Vec::new();
+        for node in &doc.nodes {
+            if let html::dom::NodeKind::Element(elem) = &node.kind
+                && elem.tag == html::dom::TagName::Img
+                && let Some(src) = elem.get_attribute("src").map(str::trim)
+                && !src.is_empty()
+                && let Some(url) = base.resolve(src).map(|u| u.to_string())
+                && !urls.contains(&url)
+            {
+                urls.push(url);
+            }
+        }
+
+        let mut changed = false;
+        for url in urls {
+            // A cached image is decoded already unless it was cached as
+            // a page of its own; then only its body needs decoding.
+            let cached = match self.cache.get(&url) {
+                Some(_) if self.image_sizes.contains_key(&url) => continue,
+                Some(entry) => Some(image::decode_image(&entry.response.body)),
+                None => None,
+            };
+            let decoded = match cached {
+                Some(img) => img,
+                None => {
+                    let request = ResourceRequest {
+                        url: url.clone(),
+                        base_url: Some(base.to_string()),
+                        source: self.resource_source(),
+                        max_redirects: self.config.max_redirects,
+                        timeout: self.config.request_timeout,
+                        retries: self.config.network_retries,
+                    };
+                    // Images are pinned like pages: a gemini:// image from a
+                    // host whose certificate changed is refused.
+                    let known_hosts = Some(&mut self.known_hosts);
+                    match load_resource(vfs, &request, self.tls.as_deref(), known_hosts) {
+                        Ok(response) => {
+                            let img = image::decode_image(&response.body);
+                            if img.is_some() {
+                                let entry = CacheEntry {
+                                    response,
+                                    texture: None,
+                                };
+                                self.cache.insert(url.clone(), entry);
+                            }
+                            img
+                        },
+                        Err(e) => {
+                            log::debug!("failed to load image {url}: {e}");
+                            None
+                        },
+                    }
+                },
+            };
+            let Some(img) = decoded else {
+                changed |= self.image_sizes.remove(&url).is_some();
+                continue;
+            };
+            let size = (img.width, img.height);
+            changed |= self.image_sizes.insert(url.clone(), size) != Some(size);
+            let max = self.config.max_image_dimension;
+            self.pending_images
+                .insert(url, image::scale_to_fit(&img, max, max));
+        }
+        if changed {
+            self.relayout_current_page();
+        }
+    }
 
-        // 6. Store results.
-        self.document = Some(doc);
-        self.styles = styles;
-        self.href_map = href_map;
-        self.layout_root = Some(layout_root);
-        self.link_map.clear();
-        self.scroll.reset();
-        self.state = LoadingState::Idle;
+    /// The measurer pages are laid out with.
+    fn text_measurer(&self) -> &dyn TextMeasurer {
+        match &self.glyphs {
+            Some(table) => table,
+            None => &SimpleTextMeasurer,
+        }
+    }
 
-        // 7. Update navigation.
-        self.nav.navigate(url, &title);
+    /// Measure text with the backend's glyph advances from now on if its
+    /// font changed (say, the PSP switched between its system and bitmap
+    /// fonts), and reflow every tab.
+    fn sync_glyph_metrics(&mut self, backend: &dyn SdiBackend) {
+        let signature = layout::metrics::signature(&BackendTextMeasurer(backend));
+        if signature == self.glyph_signature {
+            return;
+        }
+        self.glyph_signature = signature;
+        self.glyphs = (signature != layout::metrics::signature(&SimpleTextMeasurer))
+            .then(|| GlyphTable::from_backend(backend));
+        self.reflow_tabs();
+    }
+
+    /// Text scale for the current tab: the page zoom, times the reader
+    /// text size while in reader mode.
+    fn layout_scale(&self) -> f32 {
+        let scale = self.config.clamped_text_scale();
+        if self.tab().reader_mode {
+            scale * self.config.clamped_reader_text_scale()
+        } else {
+            scale
+        }
+    }
+
+    /// Lay out the current page again, keeping its cached copy in step.
+    fn relayout_current_page(&mut self) {
+        let Some(url) = self.tab().nav.current_url().map(String::from) else {
+            return;
+        };
+        let tab = self.tab();
+        let Some(doc) = &tab.document else {
+            return;
+        };
+        let layout_root = self.layout_document(doc, &tab.styles, &url, self.layout_scale());
+        // The reader view is never cached in place of the page.
+        if !tab.reader_mode && self.page_cache.contains(&url) {
+            let page = CachedPage {
+                document: doc.clone(),
+                styles: tab.styles.clone(),
+                href_map: tab.href_map.clone(),
+                layout_root: layout_root.clone(),
+                layout_width: self.window_w,
+                layout_scale: self.config.clamped_text_scale(),
+                layout_glyphs: self.glyph_signature,
+            };
+            self.page_cache.insert(url, page);
+        }
+        let tab = self.tab_mut();
+        tab.layout_root = Some(layout_root);
+        tab.link_map.clear();
+        tab.control_map.clear();
+    }
+
+    /// Point the current page's image boxes at their textures,
+    /// uploading images decoded since the last frame. This runs every
+    /// frame because the cache may have dropped (and so destroyed) a
+    /// texture since the last one.
+    fn attach_image_textures(&mut self, backend: &mut dyn SdiBackend) {
+        if self.image_sizes.is_empty() {
+            return;
+        }
+        let Some(base) = self.tab().nav.current_url().and_then(Url::parse) else {
+            return;
+        };
+        let Some(mut root) = self.tab_mut().layout_root.take() else {
+            return;
+        };
+        self.attach_textures_in(&mut root, &base, backend);
+        self.tab_mut().layout_root = Some(root);
+    }
+
+    fn attach_textures_in(
+        &mut self,
+        layout_box: &mut LayoutBox,
+        base: &Url,
+        backend: &mut dyn SdiBackend,
+    ) {
+        if let BoxType::Replaced(ReplacedContent::Image { texture, src, .. }) =
+            &mut layout_box.box_type
+            && !src.is_empty()
+        {
+            *texture = base
+                .resolve(src)
+                .and_then(|url| self.image_texture(&url.to_string(), backend));
+        }
+        for child in &mut layout_box.children {
+            self.attach_textures_in(child, base, backend);
+        }
+    }
+
+    /// The texture of the decoded image at `url`, uploading it first if
+    /// needed. An image that fails to upload is treated as broken.
+    fn image_texture(&mut self, url: &str, backend: &mut dyn SdiBackend) -> Option<TextureId> {
+        if !self.image_sizes.contains_key(url) {
+            return None;
+        }
+        let entry = self.cache.peek(url)?;
+        if entry.texture.is_some() {
+            return entry.texture;
+        }
+        let decoded = match self.pending_images.remove(url) {
+            Some(img) => img,
+            None => {
+                let img = image::decode_image(&entry.response.body)?;
+                let max = self.config.max_image_dimension;
+                image::scale_to_fit(&img, max, max)
+            },
+        };
+        match backend.load_texture(decoded.width, decoded.height, &decoded.pixels) {
+            Ok(tex) => {
+                self.cache.set_texture(url, tex);
+                Some(tex)
+            },
+            Err(e) => {
+                log::warn!("failed to upload image {url}: {e}");
+                self.image_sizes.remove(url);
+                None
+            },
+        }
     }
 
     /// Walk the DOM to build a map of `<a>` element NodeIds to their
@@ -362,7 +797,59 @@ impl BrowserWidget {
         self.load_html(&html, url);
 
         // Override the title with the Gemini document title.
-        self.nav.update_title(&title);
+        self.tab_mut().nav.update_title(&title);
+    }
+
+    /// Show the prompt page for a Gemini URL that asked for input.
+    ///
+    /// The page states the question; the answer is typed in the URL
+    /// bar (masked when `sensitive`). Confirm re-requests `url` with
+    /// the answer as its query string, Cancel goes back.
+    pub fn load_gemini_input(&mut self, prompt: &str, sensitive: bool, url: &str) {
+        let prompt = if prompt.trim().is_empty() {
+            "Input"
+        } else {
+            prompt.trim()
+        };
+        let mut html = String::from("<html><head><title>");
+        push_escaped(&mut html, prompt);
+        html.push_str("</title></head><body><h2>");
+        push_escaped(&mut html, prompt);
+        html.push_str(
+            "</h2><p>Type your answer in the bar above. \
+             Confirm sends it, Cancel goes back.</p></body></html>",
+        );
+        self.load_html(&html, url);
+        // Revisiting the URL must ask the server again.
+        self.page_cache.invalidate(url);
+
+        self.gemini_input = Some(gemini::InputPrompt {
+            url: url.to_string(),
+            prompt: prompt.to_string(),
+            sensitive,
+            field: TextField::default(),
+        });
+        self.focus = Focus::GeminiInput;
+    }
+
+    /// The open Gemini input prompt, if any.
+    pub fn gemini_input(&self) -> Option<&gemini::InputPrompt> {
+        self.gemini_input.as_ref()
+    }
+
+    /// Send the typed answer to the prompting URL.
+    fn submit_gemini_input(&mut self, vfs: &dyn Vfs) {
+        self.focus = Focus::Content;
+        if let Some(input) = self.gemini_input.take() {
+            let url = gemini::input_url(&input.url, &input.field.text);
+            self.navigate_vfs(&url, vfs);
+        }
+    }
+
+    /// Dismiss the Gemini input prompt without sending anything.
+    fn cancel_gemini_input(&mut self) {
+        self.gemini_input = None;
+        self.focus = Focus::Content;
     }
 
     /// Toggle reader mode on/off.
@@ -371,43 +858,52 @@ impl BrowserWidget {
             return;
         }
 
-        self.reader_mode = !self.reader_mode;
-        self.nav.update_reader_mode(self.reader_mode);
+        let tab = &mut self.tabs[self.active_tab];
+        tab.reader_mode = !tab.reader_mode;
+        tab.nav.update_reader_mode(tab.reader_mode);
+        if let Some(url) = tab.nav.current_url() {
+            self.page_cache.invalidate(url);
+        }
 
-        if self.reader_mode {
+        if tab.reader_mode {
             // Extract article and re-render.
-            if let Some(doc) = &self.document
+            if let Some(doc) = &tab.document
                 && let Some(article) = reader::extract_article(doc)
             {
-                self.reader_html = Some(article.html.clone());
+                tab.reader_html = Some(article.html.clone());
                 // Re-parse the reader HTML.
-                let url = self.nav.current_url().unwrap_or("about:reader").to_string();
+                let url = tab.nav.current_url().unwrap_or("about:reader").to_string();
                 let tokens = html::tokenizer::Tokenizer::new(&article.html).tokenize();
                 let reader_doc = html::tree_builder::TreeBuilder::build(tokens);
                 let ua_sheet = css::default::default_stylesheet();
                 let styles = css::cascade::style_tree(&reader_doc, &[&ua_sheet], &[]);
                 let href_map = Self::build_link_map(&reader_doc);
-                self.document = Some(reader_doc);
-                self.styles = styles;
-                self.href_map = href_map;
-                self.layout_root = None;
-                self.link_map.clear();
-                self.scroll.reset();
-                self.selected_link = -1;
-                self.nav.update_title(&format!("Reader: {}", article.title));
+                tab.document = Some(reader_doc);
+                tab.styles = styles;
+                tab.href_map = href_map;
+                tab.layout_root = None;
+                tab.link_map.clear();
+                tab.scroll.reset();
+                tab.selected_link = -1;
+                tab.nav.update_title(&format!("Reader: {}", article.title));
                 let _ = url; // suppress unused warning
+                self.relayout_current_page();
             }
         } else {
             // Restore original page by re-navigating.
-            self.reader_html = None;
-            if let Some(url) = self.nav.current_url() {
+            tab.reader_html = None;
+            if let Some(url) = tab.nav.current_url() {
                 let url = url.to_string();
                 // Re-parse original from cache if available.
                 if let Some(entry) = self.cache.get(&url) {
-                    let body = entry.response.body.clone();
-                    let ct = entry.response.content_type;
+                    let resp = &entry.response;
+                    let ct = resp.content_type;
                     if ct == ContentType::Html || ct == ContentType::PlainText {
-                        let text = String::from_utf8_lossy(&body);
+                        let text = loader::charset::decode_text(
+                            &resp.body,
+                            resp.charset.as_deref(),
+                            ct == ContentType::Html,
+                        );
                         self.load_html(&text, &url);
                     }
                 }
@@ -415,6 +911,26 @@ impl BrowserWidget {
         }
     }
 
+    /// Advance smooth scrolling by `dt_ms`. Call once per frame, input
+    /// or not; returns true while any tab is still scrolling.
+    pub fn tick(&mut self, dt_ms: u32) -> bool {
+        let mut moving = false;
+        for tab in &mut self.tabs {
+            moving |= tab.scroll.tick(dt_ms);
+        }
+        moving
+    }
+
+    /// Whether the browser shows something that changes without input
+    /// (a loading spinner, a status message counting down, images
+    /// waiting for the next paint to upload them), so it must be painted
+    /// again.
+    pub fn needs_repaint(&self) -> bool {
+        self.tab().state == LoadingState::Loading
+            || self.status_flash.is_some()
+            || !self.pending_images.is_empty()
+    }
+
     // ---------------------------------------------------------------
     // Painting
     // ---------------------------------------------------------------
@@ -424,6 +940,14 @@ impl BrowserWidget {
     /// Draws chrome (URL bar, navigation buttons, status bar) and
     /// the page content viewport.
     pub fn paint(&mut self, backend: &mut dyn SdiBackend) -> Result<()> {
+        self.frame = self.frame.wrapping_add(1);
+        // Free textures of resources dropped from the cache.
+        for tex in self.cache.take_released_textures() {
+            backend.destroy_texture(tex)?;
+        }
+        self.sync_glyph_metrics(backend);
+        self.attach_image_textures(backend);
+
         // Set clip to our window area.
         backend.set_clip_rect(self.window_x, self.window_y, self.window_w, self.window_h)?;
 
@@ -431,8 +955,8 @@ impl BrowserWidget {
         self.paint_chrome(backend)?;
 
         // Content viewport.
-        let content_y = self.window_y + self.config.url_bar_height as i32;
-        let content_h = self.config.content_height(self.window_h);
+        let content_y = self.content_top();
+        let content_h = self.content_height();
 
         backend.set_clip_rect(self.window_x, content_y, self.window_w, content_h)?;
 
@@ -445,33 +969,81 @@ impl BrowserWidget {
             self.config.default_bg_color,
         )?;
 
+        // The history list replaces the page while it is open.
+        if self.focus == Focus::History {
+            self.paint_history_list(backend)?;
+            self.paint_status_bar(backend)?;
+            backend.reset_clip_rect()?;
+            return Ok(());
+        }
+
+        // Paint find-in-page highlights behind the text.
+        for (i, m) in self.tab().find.matches.iter().enumerate() {
+            let color = if i == self.tab().find.current {
+                Color::rgb(255, 150, 50)
+            } else {
+                Color::rgb(255, 240, 100)
+            };
+            paint::paint_find_highlight(
+                m,
+                backend,
+                self.tab().scroll.scroll_y as f32,
+                self.window_x,
+                content_y,
+                color,
+            )?;
+        }
+
         // Paint layout tree if available.
-        if let Some(layout) = &self.layout_root {
+        if let Some(layout) = &self.tab().layout_root {
             let result = paint::paint(
                 layout,
                 backend,
-                self.scroll.scroll_y as f32,
+                self.tab().scroll.scroll_y as f32,
                 self.window_x,
                 content_y,
                 self.window_w as f32,
                 content_h as f32,
-                &self.href_map,
+                &self.tab().href_map,
             )?;
-            self.link_map = result.links;
-            self.scroll.set_content_height(result.content_height as i32);
+            let tab = self.tab_mut();
+            tab.link_map = result.links;
+            tab.control_map = result.controls;
+            tab.scroll.set_content_height(result.content_height as i32);
         }
 
-        // Paint link highlight if a link is selected.
-        if self.selected_link >= 0 {
-            let idx = self.selected_link as usize;
-            if idx < self.link_map.len() {
-                let link = self.link_map[idx].clone();
-                paint::paint_link_highlight(&link, backend, Color::rgb(255, 200, 0))?;
+        // Paint link highlight if a link or control is selected.
+        if self.tab().selected_link >= 0 {
+            let idx = self.tab().selected_link as usize;
+            let highlight = Color::rgb(255, 200, 0);
+            if idx < self.tab().link_map.len() {
+                let link = self.tab().link_map[idx].clone();
+                paint::paint_link_highlight(&link, backend, highlight)?;
+            } else if let Some(control) =
+                self.tab().control_map.get(idx - self.tab().link_map.len())
+            {
+                paint::paint_rect_highlight(&control.rect, backend, highlight)?;
             }
         }
 
+        // Caret in the focused form field.
+        if let Focus::FormField(node) = self.focus {
+            self.paint_form_caret(node, backend)?;
+        }
+
+        // Paint the download prompt over the content.
+        if self.pending_download.is_some() {
+            self.paint_download_prompt(backend)?;
+        }
+
         // Paint status bar.
         self.paint_status_bar(backend)?;
+        if let Some((_, frames)) = &mut self.status_flash {
+            *frames = frames.saturating_sub(1);
+            if *frames == 0 {
+                self.status_flash = None;
+            }
+        }
 
         backend.reset_clip_rect()?;
         Ok(())
@@ -505,7 +1077,7 @@ impl BrowserWidget {
         }
 
         // Back button.
-        let back_color = if self.nav.can_go_back() {
+        let back_color = if self.tab().nav.can_go_back() {
             self.config.chrome_button_bg
         } else {
             self.config.chrome_bg
@@ -524,7 +1096,7 @@ impl BrowserWidget {
         )?;
 
         // Forward button.
-        let fwd_color = if self.nav.can_go_forward() {
+        let fwd_color = if self.tab().nav.can_go_forward() {
             self.config.chrome_button_bg
         } else {
             self.config.chrome_bg
@@ -554,7 +1126,11 @@ impl BrowserWidget {
         let url_w = self.window_w.saturating_sub(bw * 3);
 
         // Use a highlighted background when the URL bar is focused.
-        let bar_bg = if self.focus == Focus::UrlBar {
+        let editing = matches!(
+            self.focus,
+            Focus::UrlBar | Focus::FindBar | Focus::GeminiInput
+        );
+        let bar_bg = if editing {
             Color::rgb(60, 60, 80)
         } else {
             self.config.url_bar_bg
@@ -586,11 +1162,36 @@ impl BrowserWidget {
         // the current navigation URL.
         let max_chars = (url_w / 8).saturating_sub(1) as usize;
         if self.focus == Focus::UrlBar {
-            // Show editing buffer with cursor indicator.
-            let display = if self.url_input.len() > max_chars {
-                &self.url_input[..self.url_input.floor_char_boundary(max_chars)]
+            // The URL being edited scrolls to keep the cursor in view.
+            let theme = Theme {
+                text_primary: self.config.url_bar_text,
+                font_size_md: 12,
+                ..Theme::dark()
+            };
+            let mut ctx = DrawContext::new(&mut *backend, &theme);
+            self.url_field.draw_text_at(
+                &mut ctx,
+                url_x + 4,
+                self.window_y + 2,
+                url_w.saturating_sub(8),
+                h.saturating_sub(4),
+            )?;
+        } else if editing {
+            // Show editing buffer with cursor indicator. The find bar
+            // and Gemini input prompts reuse the URL bar slot.
+            let (prompt, field, masked) = match (&self.focus, &self.gemini_input) {
+                (Focus::GeminiInput, Some(input)) => ("> ", &input.field, input.sensitive),
+                _ => ("Find: ", &self.tab().find.query, false),
+            };
+            let text = if masked {
+                format!("{prompt}{}", "*".repeat(field.text.chars().count()))
+            } else {
+                format!("{prompt}{}", field.text)
+            };
+            let display = if text.len() > max_chars {
+                &text[..text.floor_char_boundary(max_chars)]
             } else {
-                &self.url_input
+                &text
             };
             backend.draw_text(
                 display,
@@ -601,7 +1202,7 @@ impl BrowserWidget {
             )?;
 
             // Draw cursor line.
-            let cursor_chars = self.url_input[..self.url_cursor].chars().count();
+            let cursor_chars = prompt.len() + field.cursor_chars();
             let cursor_px = url_x + 4 + cursor_chars as i32 * 8;
             if cursor_px < url_x + url_w as i32 - 4 {
                 backend.fill_rect(
@@ -613,7 +1214,7 @@ impl BrowserWidget {
                 )?;
             }
         } else {
-            let url_text = self.nav.current_url().unwrap_or("about:blank");
+            let url_text = self.tab().nav.current_url().unwrap_or("about:blank");
             let display_url = if url_text.len() > max_chars {
                 &url_text[..url_text.floor_char_boundary(max_chars)]
             } else {
@@ -650,6 +1251,155 @@ impl BrowserWidget {
             self.config.chrome_text,
         )?;
 
+        if self.tabs.len() > 1 {
+            self.paint_tab_strip(backend)?;
+        }
+        Ok(())
+    }
+
+    /// Paint the tab strip below the URL bar: one equal-width cell per
+    /// tab with its truncated title, the active tab highlighted.
+    fn paint_tab_strip(&self, backend: &mut dyn SdiBackend) -> Result<()> {
+        let y = self.window_y + self.config.url_bar_height as i32;
+        let h = self.config.tab_bar_height;
+        backend.fill_rect(self.window_x, y, self.window_w, h, self.config.chrome_bg)?;
+
+        let tab_w = self.window_w / self.tabs.len() as u32;
+        for (i, tab) in self.tabs.iter().enumerate() {
+            let x = self.window_x + (tab_w * i as u32) as i32;
+            if i == self.active_tab {
+                backend.fill_rect(x, y, tab_w, h, self.config.chrome_button_bg)?;
+            }
+            let mut label = tab.label();
+            while !label.is_empty()
+                && oasis_types::backend::bitmap_measure_text(label, 10) > tab_w.saturating_sub(8)
+            {
+                label = &label[..label.floor_char_boundary(label.len() - 1)];
+            }
+            backend.draw_text(label, x + 4, y + 1, 10, self.config.chrome_text)?;
+        }
+        Ok(())
+    }
+
+    /// Draw the text cursor inside the focused form text input.
+    fn paint_form_caret(&self, node: NodeId, backend: &mut dyn SdiBackend) -> Result<()> {
+        let Some(control) = self.tab().control_map.iter().find(|c| c.node == node) else {
+            return Ok(());
+        };
+        let Some(lb) = self
+            .tab()
+            .layout_root
+            .as_ref()
+            .and_then(|r| r.find_node(node))
+        else {
+            return Ok(());
+        };
+        let masked = matches!(
+            &lb.box_type,
+            BoxType::Replaced(ReplacedContent::TextInput { masked: true, .. })
+        );
+        let before = &self.form_field.text[..self.form_field.cursor];
+        let shown = if masked {
+            "*".repeat(before.chars().count())
+        } else {
+            before.to_string()
+        };
+        let font_size = lb.style.font_size as u16;
+        let r = &control.rect;
+        let x =
+            r.x as i32 + 3 + oasis_types::backend::bitmap_measure_text(&shown, font_size) as i32;
+        if x < (r.x + r.width) as i32 - 2 {
+            backend.fill_rect(
+                x,
+                r.y as i32 + 2,
+                1,
+                (r.height as u32).saturating_sub(4),
+                lb.style.color,
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Paint the "Save file?" prompt centered over the content area.
+    pub fn paint_download_prompt(&self, backend: &mut dyn SdiBackend) -> Result<()> {
+        let Some(resp) = &self.pending_download else {
+            return Ok(());
+        };
+        let question = format!(
+            "Save {} ({})?",
+            download::file_name_for(&resp.url),
+            download::format_size(resp.body.len()),
+        );
+        let hint = "Confirm/Cancel";
+
+        let text_w = oasis_types::backend::bitmap_measure_text(&question, 12)
+            .max(oasis_types::backend::bitmap_measure_text(hint, 12));
+        let w = (text_w + 16).min(self.window_w);
+        let h = 40;
+        let content_y = self.content_top();
+        let content_h = self.content_height();
+        let x = self.window_x + (self.window_w as i32 - w as i32) / 2;
+        let y = content_y + (content_h as i32 - h as i32) / 2;
+
+        if self.config.use_themed_chrome {
+            backend.fill_rounded_rect(x, y, w, h, 4, self.config.chrome_bg)?;
+            backend.stroke_rounded_rect(x, y, w, h, 4, 1, self.config.chrome_button_bg)?;
+        } else {
+            backend.fill_rect(x, y, w, h, self.config.chrome_bg)?;
+        }
+        backend.draw_text(&question, x + 8, y + 6, 12, self.config.chrome_text)?;
+        backend.draw_text(hint, x + 8, y + 22, 12, self.config.chrome_text)?;
+        Ok(())
+    }
+
+    /// Paint the history list over the content viewport.
+    pub fn paint_history_list(&self, backend: &mut dyn SdiBackend) -> Result<()> {
+        let content_y = self.content_top();
+        let row_h = history_view::ROW_HEIGHT;
+        let view = &self.history_view;
+        if view.rows.is_empty() {
+            return backend.draw_text(
+                "No history",
+                self.window_x + 8,
+                content_y + 8,
+                12,
+                self.config.default_text_color,
+            );
+        }
+
+        let visible = view
+            .rows
+            .iter()
+            .enumerate()
+            .skip(view.first_visible)
+            .take(self.history_visible_rows());
+        for (slot, (i, row)) in visible.enumerate() {
+            let y = content_y + (slot as u32 * row_h) as i32;
+            if i == view.selected {
+                backend.fill_rect(
+                    self.window_x,
+                    y,
+                    self.window_w,
+                    row_h,
+                    Color::rgb(255, 200, 0),
+                )?;
+            }
+            let marker = if row.current { "* " } else { "" };
+            backend.draw_text(
+                &format!("{marker}{}", row.title),
+                self.window_x + 8,
+                y + 3,
+                12,
+                self.config.default_text_color,
+            )?;
+            backend.draw_text(
+                &row.url,
+                self.window_x + 8,
+                y + 17,
+                10,
+                self.config.default_link_color,
+            )?;
+        }
         Ok(())
     }
 
@@ -667,10 +1417,27 @@ impl BrowserWidget {
         )?;
 
         // Status text.
-        let status = match self.state {
+        let find_status;
+        let status = match self.tab().state {
             LoadingState::Idle => {
-                if self.reader_mode {
-                    "Reader mode"
+                if self.focus == Focus::FindBar {
+                    find_status = self.tab().find.status_text();
+                    &find_status
+                } else if self.focus == Focus::History {
+                    find_status = self.history_view.status_text();
+                    &find_status
+                } else if self.focus == Focus::GeminiInput
+                    && let Some(input) = &self.gemini_input
+                {
+                    &input.prompt
+                } else if let Some((msg, _)) = &self.status_flash {
+                    msg
+                } else if let Some(msg) = &self.status_message {
+                    msg
+                } else if self.tab().reader_mode {
+                    let percent = (self.config.clamped_reader_text_scale() * 100.0).round();
+                    find_status = format!("Reader mode, text {percent}%");
+                    &find_status
                 } else {
                     "Ready"
                 }
@@ -678,16 +1445,24 @@ impl BrowserWidget {
             LoadingState::Loading => "Loading...",
             LoadingState::Error => "Error",
         };
-        backend.draw_text(
-            status,
-            self.window_x + 4,
-            sy + 2,
-            10,
-            self.config.status_bar_text,
-        )?;
+        let mut status_x = self.window_x + 4;
+        if self.tab().state == LoadingState::Loading {
+            let theme = Theme {
+                progress_fill: self.config.status_bar_text,
+                progress_track: oasis_types::color::with_alpha(self.config.status_bar_text, 50),
+                ..Theme::dark()
+            };
+            let mut ctx = DrawContext::new(&mut *backend, &theme);
+            let size = sh.saturating_sub(4);
+            Spinner::new()
+                .with_frame(self.frame)
+                .draw(&mut ctx, status_x, sy + 2, size, size)?;
+            status_x += size as i32 + 4;
+        }
+        backend.draw_text(status, status_x, sy + 2, 10, self.config.status_bar_text)?;
 
         // Scroll indicator on the right.
-        let frac = self.scroll.scroll_fraction();
+        let frac = self.tab().scroll.scroll_fraction();
         let pct = (frac * 100.0) as u32;
         let scroll_text = format!("{}%", pct);
         let text_w = oasis_types::backend::bitmap_measure_text(&scroll_text, 10) as i32;
@@ -708,31 +1483,110 @@ impl BrowserWidget {
 
     /// Handle an input event. Returns `true` if the event was
     /// consumed.
-    pub fn handle_input(&mut self, event: &InputEvent, vfs: &dyn Vfs) -> bool {
-        // URL-bar editing mode intercepts most keys.
-        if self.focus == Focus::UrlBar {
+    ///
+    /// Whatever the event changed is written through as by
+    /// [`persist`](Self::persist).
+    pub fn handle_input(&mut self, event: &InputEvent, vfs: &mut dyn Vfs) -> bool {
+        let consumed = self.dispatch_input(event, vfs);
+        self.persist(vfs);
+        consumed
+    }
+
+    /// Write bookmarks, browsing history and Gemini certificate pins
+    /// changed since the last call through to `config.bookmarks_path`,
+    /// `config.history_path` and `config.known_hosts_path`.
+    ///
+    /// Navigation only reads the VFS, so a host navigating outside
+    /// [`handle_input`](Self::handle_input) calls this afterwards.
+    /// Nothing is written when nothing changed.
+    pub fn persist(&mut self, vfs: &mut dyn Vfs) {
+        if self.bookmarks_dirty {
+            self.persist_bookmarks(vfs);
+        }
+        if self.history_dirty {
+            self.save_history(vfs);
+        }
+        if self.known_hosts.is_dirty() {
+            self.save_known_hosts(vfs);
+        }
+    }
+
+    fn dispatch_input(&mut self, event: &InputEvent, vfs: &mut dyn Vfs) -> bool {
+        // The download prompt is modal.
+        if self.pending_download.is_some() {
+            return match event {
+                InputEvent::ButtonPress(Button::Confirm) => {
+                    self.confirm_download(vfs);
+                    true
+                },
+                InputEvent::ButtonPress(Button::Cancel) => {
+                    self.cancel_download();
+                    true
+                },
+                _ => false,
+            };
+        }
+
+        // Track the held trigger for Trigger+button chords. A trigger
+        // released without a chord pages the content (below).
+        let released = match event {
+            InputEvent::TriggerPress(trigger) => {
+                // Pressing the other trigger while one is held is a chord too.
+                self.trigger_chorded = self.held_trigger.is_some();
+                self.held_trigger = Some(*trigger);
+                None
+            },
+            InputEvent::TriggerRelease(trigger) if self.held_trigger == Some(*trigger) => {
+                self.held_trigger = None;
+                (!self.trigger_chorded).then_some(*trigger)
+            },
+            InputEvent::ButtonPress(_) if self.held_trigger.is_some() => {
+                self.trigger_chorded = true;
+                None
+            },
+            _ => None,
+        };
+
+        // Gemini input prompt: edit the answer in the URL bar slot.
+        if self.focus == Focus::GeminiInput {
+            if let Some(input) = &mut self.gemini_input
+                && input.field.handle_edit(event)
+            {
+                return true;
+            }
             match event {
-                InputEvent::TextInput(ch) => {
-                    self.url_input.insert(self.url_cursor, *ch);
-                    self.url_cursor += ch.len_utf8();
+                InputEvent::ButtonPress(Button::Confirm) => {
+                    self.submit_gemini_input(vfs);
                     return true;
                 },
-                InputEvent::Backspace => {
-                    if self.url_cursor > 0 {
-                        // Find the previous character boundary.
-                        let prev = self.url_input[..self.url_cursor]
-                            .char_indices()
-                            .next_back()
-                            .map(|(i, _)| i)
-                            .unwrap_or(0);
-                        self.url_input.remove(prev);
-                        self.url_cursor = prev;
-                    }
+                InputEvent::ButtonPress(Button::Cancel) => {
+                    self.cancel_gemini_input();
+                    self.go_back(vfs);
                     return true;
                 },
-                InputEvent::ButtonPress(Button::Confirm) => {
-                    let url = self.url_input.clone();
-                    self.focus = Focus::Content;
+                InputEvent::PointerClick { x, y } => {
+                    self.cancel_gemini_input();
+                    self.handle_click(*x, *y, vfs);
+                    return true;
+                },
+                _ => return false,
+            }
+        }
+
+        // URL-bar editing mode intercepts most keys.
+        if self.focus == Focus::UrlBar {
+            match self.url_field.handle_input(event) {
+                TextInputResponse::Ignored => {},
+                TextInputResponse::Copied(text) | TextInputResponse::Cut(text) => {
+                    self.copied_text = Some(text);
+                    return true;
+                },
+                _ => return true,
+            }
+            match event {
+                InputEvent::ButtonPress(Button::Confirm) => {
+                    let url = self.url_field.text.clone();
+                    self.focus = Focus::Content;
                     if !url.is_empty() {
                         self.navigate_to(&url, vfs);
                     }
@@ -741,33 +1595,77 @@ impl BrowserWidget {
                 InputEvent::ButtonPress(Button::Cancel) => {
                     // Discard edits.
                     self.focus = Focus::Content;
-                    self.url_input.clear();
-                    self.url_cursor = 0;
+                    self.url_field.clear();
                     return true;
                 },
-                InputEvent::ButtonPress(Button::Left) => {
-                    if self.url_cursor > 0 {
-                        let prev = self.url_input[..self.url_cursor]
-                            .char_indices()
-                            .next_back()
-                            .map(|(i, _)| i)
-                            .unwrap_or(0);
-                        self.url_cursor = prev;
-                    }
+                InputEvent::PointerClick { x, y } => {
+                    self.handle_click(*x, *y, vfs);
                     return true;
                 },
-                InputEvent::ButtonPress(Button::Right) => {
-                    if self.url_cursor < self.url_input.len() {
-                        let next = self.url_input[self.url_cursor..]
-                            .chars()
-                            .next()
-                            .map(|c| self.url_cursor + c.len_utf8())
-                            .unwrap_or(self.url_input.len());
-                        self.url_cursor = next;
-                    }
+                _ => return false,
+            }
+        }
+
+        // Find-bar mode: edit the query and step through matches.
+        if self.focus == Focus::FindBar {
+            if self.tab_mut().find.query.handle_edit(event) {
+                let query = self.tab().find.query.text.clone();
+                self.find_in_page(&query);
+                return true;
+            }
+            match event {
+                InputEvent::ButtonPress(Button::Confirm | Button::Down) => {
+                    self.find_next();
+                    return true;
+                },
+                InputEvent::ButtonPress(Button::Up) => {
+                    self.find_prev();
+                    return true;
+                },
+                InputEvent::ButtonPress(Button::Cancel) => {
+                    self.close_find_bar();
+                    return true;
+                },
+                InputEvent::PointerClick { x, y } => {
+                    self.close_find_bar();
+                    self.handle_click(*x, *y, vfs);
+                    return true;
+                },
+                _ => return false,
+            }
+        }
+
+        // History list: Up/Down select, Confirm opens the entry.
+        if self.focus == Focus::History {
+            match event {
+                InputEvent::ButtonPress(Button::Down) => self.select_next_history(),
+                InputEvent::ButtonPress(Button::Up) => self.select_prev_history(),
+                InputEvent::ButtonPress(Button::Confirm) => self.open_selected_history(vfs),
+                InputEvent::ButtonPress(Button::Cancel) => self.close_history(),
+                InputEvent::PointerClick { x, y } => self.handle_history_click(*x, *y, vfs),
+                _ => return false,
+            }
+            return true;
+        }
+
+        // Form text input mode: edit the value, Confirm submits.
+        if let Focus::FormField(node) = self.focus {
+            if self.form_field.handle_edit(event) {
+                self.sync_form_field(node);
+                return true;
+            }
+            match event {
+                InputEvent::ButtonPress(Button::Confirm) => {
+                    self.focus = Focus::Content;
+                    self.submit_form(node, None, vfs);
+                    return true;
+                },
+                InputEvent::ButtonPress(Button::Cancel) => {
+                    self.focus = Focus::Content;
                     return true;
                 },
                 InputEvent::PointerClick { x, y } => {
+                    self.focus = Focus::Content;
                     self.handle_click(*x, *y, vfs);
                     return true;
                 },
@@ -775,13 +1673,41 @@ impl BrowserWidget {
             }
         }
 
+        // Trigger+Up/Down/Select zoom the page in, out, and back to 100%.
+        // In reader mode Up/Down change the reader text size instead.
+        if self.held_trigger.is_some() {
+            match event {
+                InputEvent::ButtonPress(Button::Up) if self.tab().reader_mode => {
+                    self.reader_increase_font();
+                    return true;
+                },
+                InputEvent::ButtonPress(Button::Down) if self.tab().reader_mode => {
+                    self.reader_decrease_font();
+                    return true;
+                },
+                InputEvent::ButtonPress(Button::Up) => {
+                    self.zoom_in();
+                    return true;
+                },
+                InputEvent::ButtonPress(Button::Down) => {
+                    self.zoom_out();
+                    return true;
+                },
+                InputEvent::ButtonPress(Button::Select) => {
+                    self.zoom_reset();
+                    return true;
+                },
+                _ => {},
+            }
+        }
+
         match event {
             InputEvent::ButtonPress(Button::Up) => {
-                self.scroll.scroll_up();
+                self.tab_mut().scroll.scroll_up();
                 true
             },
             InputEvent::ButtonPress(Button::Down) => {
-                self.scroll.scroll_down();
+                self.tab_mut().scroll.scroll_down();
                 true
             },
             InputEvent::ButtonPress(Button::Left) => {
@@ -801,19 +1727,32 @@ impl BrowserWidget {
                 true
             },
             InputEvent::ButtonPress(Button::Triangle) => {
-                self.toggle_reader_mode();
+                match self.held_trigger {
+                    Some(Trigger::Right) => self.bookmark_current_page(vfs),
+                    Some(Trigger::Left) => self.navigate_to(bookmarks_page::BOOKMARKS_URL, vfs),
+                    None => self.toggle_reader_mode(),
+                }
                 true
             },
             InputEvent::ButtonPress(Button::Square) => {
-                self.go_home(vfs);
+                match self.held_trigger {
+                    Some(Trigger::Right) => self.next_tab(),
+                    Some(Trigger::Left) => self.prev_tab(),
+                    None => self.go_home(vfs),
+                }
                 true
             },
-            InputEvent::TriggerPress(Trigger::Left) => {
-                self.scroll.page_up();
+            InputEvent::ButtonPress(Button::Select) => {
+                self.open_find_bar();
                 true
             },
-            InputEvent::TriggerPress(Trigger::Right) => {
-                self.scroll.page_down();
+            InputEvent::TriggerPress(_) => true,
+            InputEvent::TriggerRelease(_) => {
+                match released {
+                    Some(Trigger::Left) => self.tab_mut().scroll.page_up(),
+                    Some(Trigger::Right) => self.tab_mut().scroll.page_down(),
+                    None => {},
+                }
                 true
             },
             InputEvent::PointerClick { x, y } => {
@@ -824,60 +1763,333 @@ impl BrowserWidget {
         }
     }
 
-    /// Select the next link in the link map.
+    /// Save the pending download into `config.downloads_dir` and report
+    /// the result in the status bar.
+    pub fn confirm_download(&mut self, vfs: &mut dyn Vfs) {
+        let Some(resp) = self.pending_download.take() else {
+            return;
+        };
+        self.status_message = Some(
+            match download::save(vfs, &self.config.downloads_dir, &resp) {
+                Ok(path) => {
+                    let msg = format!("Saved to {path}");
+                    self.saved_download = Some(path);
+                    msg
+                },
+                Err(e) => format!("Download failed: {e}"),
+            },
+        );
+    }
+
+    /// Take the path of a download saved since the last call, so the
+    /// host can announce it.
+    pub fn take_saved_download(&mut self) -> Option<String> {
+        self.saved_download.take()
+    }
+
+    /// Take the text cut or copied in the URL bar since the last call, so
+    /// the host can put it on the clipboard.
+    pub fn take_copied_text(&mut self) -> Option<String> {
+        self.copied_text.take()
+    }
+
+    /// Discard the pending download.
+    pub fn cancel_download(&mut self) {
+        self.pending_download = None;
+    }
+
+    /// The response awaiting a save decision, if any.
+    pub fn pending_download(&self) -> Option<&ResourceResponse> {
+        self.pending_download.as_ref()
+    }
+
+    /// Search the current page for `query` (case-insensitive) and
+    /// scroll to the first match.
+    ///
+    /// An empty query clears all matches.
+    pub fn find_in_page(&mut self, query: &str) {
+        if self.tab_mut().find.query.text != query {
+            self.tab_mut().find.query.set(query);
+        }
+        self.tab_mut().find.matches = match &self.tab().layout_root {
+            Some(root) => find::find_matches(root, query, self.text_measurer()),
+            None => Vec::new(),
+        };
+        self.tab_mut().find.current = 0;
+        self.scroll_to_current_match();
+    }
+
+    /// Move to the next find match, wrapping at the end.
+    pub fn find_next(&mut self) {
+        self.tab_mut().find.next();
+        self.scroll_to_current_match();
+    }
+
+    /// Move to the previous find match, wrapping at the start.
+    pub fn find_prev(&mut self) {
+        self.tab_mut().find.prev();
+        self.scroll_to_current_match();
+    }
+
+    /// Give keyboard focus to the find bar.
+    pub fn open_find_bar(&mut self) {
+        self.focus = Focus::FindBar;
+    }
+
+    /// Leave the find bar and clear the query and highlights.
+    pub fn close_find_bar(&mut self) {
+        self.focus = Focus::Content;
+        self.tab_mut().find.clear();
+    }
+
+    /// Find-in-page state (query, matches, current index).
+    pub fn find_state(&self) -> &find::FindState {
+        &self.tab().find
+    }
+
+    /// Show the history list in place of the page, with the current
+    /// page selected.
+    pub fn open_history(&mut self) {
+        self.history_view = history_view::HistoryView::new(
+            self.tab().nav.history_entries(),
+            self.tab().nav.current_index(),
+        );
+        self.history_view
+            .scroll_to_selected(self.history_visible_rows());
+        self.focus = Focus::History;
+    }
+
+    /// Close the history list and return to the page.
+    pub fn close_history(&mut self) {
+        self.focus = Focus::Content;
+    }
+
+    /// History list rows and selection.
+    pub fn history_view(&self) -> &history_view::HistoryView {
+        &self.history_view
+    }
+
+    /// Select the next (older) history row, wrapping at the end.
+    pub fn select_next_history(&mut self) {
+        self.history_view.next();
+        self.history_view
+            .scroll_to_selected(self.history_visible_rows());
+    }
+
+    /// Select the previous (newer) history row, wrapping at the start.
+    pub fn select_prev_history(&mut self) {
+        self.history_view.prev();
+        self.history_view
+            .scroll_to_selected(self.history_visible_rows());
+    }
+
+    /// Close the history list and go to the selected entry.
+    pub fn open_selected_history(&mut self, vfs: &dyn Vfs) {
+        let index = self.history_view.selected_row().map(|r| r.index);
+        self.close_history();
+        if let Some(index) = index {
+            self.go_to_history_entry(index, vfs);
+        }
+    }
+
+    /// Jump to the entry at `index` in the navigation history without
+    /// discarding the entries around it.
+    pub fn go_to_history_entry(&mut self, index: usize, vfs: &dyn Vfs) {
+        if self.tab().nav.current_index() == Some(index) {
+            return;
+        }
+        self.tab_mut().save_scroll();
+        if let Some(entry) = self.tab_mut().nav.go_to_entry(index) {
+            self.show_history_entry(&entry, vfs);
+        }
+    }
+
+    /// A click while the history list is open opens the row under the
+    /// pointer; a click on the chrome closes the list first.
+    fn handle_history_click(&mut self, x: i32, y: i32, vfs: &dyn Vfs) {
+        let content_y = self.content_top();
+        let content_h = self.content_height() as i32;
+        if y < content_y || y >= content_y + content_h {
+            self.close_history();
+            self.handle_click(x, y, vfs);
+            return;
+        }
+        let row = (y - content_y) as usize / history_view::ROW_HEIGHT as usize;
+        let selected = self.history_view.first_visible + row;
+        if selected < self.history_view.rows.len() {
+            self.history_view.selected = selected;
+            self.open_selected_history(vfs);
+        }
+    }
+
+    /// Number of history rows that fit in the content viewport.
+    fn history_visible_rows(&self) -> usize {
+        (self.content_height() / history_view::ROW_HEIGHT).max(1) as usize
+    }
+
+    /// Scroll to make the current find match visible.
+    fn scroll_to_current_match(&mut self) {
+        if let Some(m) = self.tab().find.current_match() {
+            let r = m.bounds();
+            self.tab_mut()
+                .scroll
+                .scroll_to_visible(r.y as i32, r.height as i32);
+        }
+    }
+
+    /// Number of selectable items: links followed by form controls.
+    fn focusable_count(&self) -> usize {
+        self.tab().link_map.len() + self.tab().control_map.len()
+    }
+
+    /// Select the next link or form control.
     pub fn select_next_link(&mut self) {
-        if self.link_map.is_empty() {
+        let count = self.focusable_count();
+        if count == 0 {
             return;
         }
-        self.selected_link += 1;
-        if self.selected_link >= self.link_map.len() as i32 {
-            self.selected_link = 0;
+        self.tab_mut().selected_link += 1;
+        if self.tab().selected_link >= count as i32 {
+            self.tab_mut().selected_link = 0;
         }
         self.scroll_to_selected_link();
     }
 
-    /// Select the previous link in the link map.
+    /// Select the previous link or form control.
     pub fn select_prev_link(&mut self) {
-        if self.link_map.is_empty() {
+        let count = self.focusable_count();
+        if count == 0 {
             return;
         }
-        self.selected_link -= 1;
-        if self.selected_link < 0 {
-            self.selected_link = self.link_map.len() as i32 - 1;
+        self.tab_mut().selected_link -= 1;
+        if self.tab().selected_link < 0 {
+            self.tab_mut().selected_link = count as i32 - 1;
         }
         self.scroll_to_selected_link();
     }
 
-    /// Scroll to make the currently selected link visible.
+    /// Scroll to make the currently selected link or control visible.
     fn scroll_to_selected_link(&mut self) {
-        if self.selected_link < 0 {
+        if self.tab().selected_link < 0 {
             return;
         }
-        let idx = self.selected_link as usize;
-        if idx < self.link_map.len() {
-            let link = &self.link_map[idx];
-            self.scroll
-                .scroll_to_visible(link.rect.y as i32, link.rect.height as i32);
-        }
+        let idx = self.tab().selected_link as usize;
+        let rect = if idx < self.tab().link_map.len() {
+            self.tab().link_map[idx].rect
+        } else if let Some(control) = self.tab().control_map.get(idx - self.tab().link_map.len()) {
+            control.rect
+        } else {
+            return;
+        };
+        self.tab_mut()
+            .scroll
+            .scroll_to_visible(rect.y as i32, rect.height as i32);
     }
 
-    /// Activate the currently selected link.
+    /// Activate the currently selected link or form control.
     pub fn activate_selected_link(&mut self, vfs: &dyn Vfs) {
-        if self.selected_link < 0 {
+        if self.tab().selected_link < 0 {
             return;
         }
-        let idx = self.selected_link as usize;
-        if idx < self.link_map.len() {
-            let href = self.link_map[idx].href.clone();
+        let idx = self.tab().selected_link as usize;
+        if idx < self.tab().link_map.len() {
+            let href = self.tab().link_map[idx].href.clone();
             self.navigate_to(&href, vfs);
+        } else if let Some(control) = self.tab().control_map.get(idx - self.tab().link_map.len()) {
+            let control = control.clone();
+            self.activate_control(&control, vfs);
+        }
+    }
+
+    /// Focus a text input or press a button.
+    fn activate_control(&mut self, control: &ControlRegion, vfs: &dyn Vfs) {
+        match control.kind {
+            ControlKind::TextInput => self.focus_form_field(control.node),
+            ControlKind::Button => {
+                let is_submit = self
+                    .tab()
+                    .document
+                    .as_ref()
+                    .is_some_and(|doc| form::is_submit_button(doc, control.node));
+                if is_submit {
+                    self.submit_form(control.node, Some(control.node), vfs);
+                }
+            },
+        }
+    }
+
+    /// Give keyboard focus to the form text input `node`.
+    pub fn focus_form_field(&mut self, node: NodeId) {
+        let value = match self
+            .tab()
+            .layout_root
+            .as_ref()
+            .and_then(|r| r.find_node(node))
+        {
+            Some(lb) => match &lb.box_type {
+                BoxType::Replaced(ReplacedContent::TextInput { value, .. }) => value.clone(),
+                _ => return,
+            },
+            None => return,
+        };
+        self.form_field.set(&value);
+        self.focus = Focus::FormField(node);
+    }
+
+    /// Store the editing buffer as the value of form input `node`.
+    fn sync_form_field(&mut self, node: NodeId) {
+        let text = self.form_field.text.clone();
+        if let Some(lb) = self
+            .tab_mut()
+            .layout_root
+            .as_mut()
+            .and_then(|r| r.find_node_mut(node))
+            && let BoxType::Replaced(ReplacedContent::TextInput { value, .. }) = &mut lb.box_type
+        {
+            *value = text.clone();
+        }
+        self.tab_mut().form_values.insert(node, text);
+    }
+
+    /// Submit the form containing `control`.
+    ///
+    /// GET forms navigate to the action URL with the encoded field
+    /// values as its query; POST forms are not supported.
+    pub fn submit_form(&mut self, control: NodeId, submitter: Option<NodeId>, vfs: &dyn Vfs) {
+        let (Some(doc), Some(url)) = (&self.tab().document, self.tab().nav.current_url()) else {
+            return;
+        };
+        let Some(submission) =
+            form::build_submission(doc, control, submitter, &self.tab().form_values, url)
+        else {
+            return;
+        };
+        match submission.method {
+            form::FormMethod::Get => self.navigate_to(&submission.url, vfs),
+            form::FormMethod::Post => {
+                self.status_message = Some("POST forms are not supported".to_string());
+            },
         }
     }
 
+    /// Form text input values typed on the current page, by `NodeId`.
+    pub fn form_values(&self) -> &HashMap<NodeId, String> {
+        &self.tab().form_values
+    }
+
     /// Handle a pointer click at window-relative coordinates.
     pub fn handle_click(&mut self, x: i32, y: i32, vfs: &dyn Vfs) {
         let rel_y = y - self.window_y;
         let chrome_h = self.config.url_bar_height as i32;
 
+        // Click in the tab strip?
+        if rel_y >= chrome_h && rel_y < self.chrome_height() as i32 {
+            let tab_w = (self.window_w / self.tabs.len() as u32).max(1) as i32;
+            let index = ((x - self.window_x).max(0) / tab_w) as usize;
+            self.switch_tab(index.min(self.tabs.len() - 1));
+            return;
+        }
+
         // Click in chrome area?
         if rel_y < chrome_h {
             let rel_x = x - self.window_x;
@@ -898,8 +2110,14 @@ impl BrowserWidget {
             } else {
                 // URL bar area -- enter edit mode.
                 self.focus = Focus::UrlBar;
-                self.url_input = self.nav.current_url().unwrap_or("about:blank").to_string();
-                self.url_cursor = self.url_input.len();
+                let url = self
+                    .tab()
+                    .nav
+                    .current_url()
+                    .unwrap_or("about:blank")
+                    .to_string();
+                self.url_field.set(&url);
+                self.url_field.focused = true;
             }
             return;
         }
@@ -908,7 +2126,7 @@ impl BrowserWidget {
         self.focus = Focus::Content;
 
         // Check link hit regions.
-        for link in &self.link_map {
+        for link in &self.tab().link_map {
             let lx = link.rect.x;
             let ly = link.rect.y;
             let lw = link.rect.width;
@@ -920,12 +2138,52 @@ impl BrowserWidget {
                 return;
             }
         }
+
+        // Check form control hit regions.
+        let (fx, fy) = (x as f32, y as f32);
+        let hit = self.tab().control_map.iter().find(|c| {
+            fx >= c.rect.x
+                && fx < c.rect.x + c.rect.width
+                && fy >= c.rect.y
+                && fy < c.rect.y + c.rect.height
+        });
+        if let Some(control) = hit.cloned() {
+            self.activate_control(&control, vfs);
+        }
     }
 
     /// Navigate to a URL, resolving relative references against
     /// the current page.
     pub fn navigate_to(&mut self, href: &str, vfs: &dyn Vfs) {
-        let resolved = if let Some(current) = self.nav.current_url() {
+        if href == history_view::SESSION_HISTORY_URL {
+            self.open_history();
+            return;
+        }
+        if href == history_page::HISTORY_CLEAR_URL {
+            self.clear_history();
+            return;
+        }
+        if let Some(url) = bookmarks_page::remove_target(href) {
+            self.remove_bookmark_from_page(&url, vfs);
+            return;
+        }
+        if let Some(host) = known_hosts::tofu_link_host(href, known_hosts::TOFU_ACCEPT_URL) {
+            self.accept_certificate(host, vfs);
+            return;
+        }
+        if let Some(host) = known_hosts::tofu_link_host(href, known_hosts::TOFU_ABORT_URL) {
+            self.known_hosts.reject_pending(host);
+            self.go_back(vfs);
+            return;
+        }
+
+        // Remember where we were for when the user comes back.
+        self.tab_mut().save_scroll();
+
+        // `about:` URLs have no authority, so they are never relative.
+        let resolved = if href.starts_with("about:") {
+            href.to_string()
+        } else if let Some(current) = self.tab().nav.current_url() {
             if let Some(base) = Url::parse(current) {
                 base.resolve(href)
                     .map(|u| u.to_string())
@@ -937,112 +2195,598 @@ impl BrowserWidget {
             href.to_string()
         };
 
+        // A fragment of the page on screen only scrolls it.
+        if let Some((url, fragment)) = resolved.split_once('#')
+            && self.tab().nav.current_url() == Some(url)
+            && self.tab().document.is_some()
+        {
+            self.scroll_to_fragment(fragment);
+            return;
+        }
+
         self.navigate_vfs(&resolved, vfs);
     }
 
+    /// Scroll the current page to the element `fragment` names, or to
+    /// the top if nothing on the page has that name.
+    fn scroll_to_fragment(&mut self, fragment: &str) {
+        let tab = self.tab_mut();
+        let (Some(doc), Some(root)) = (&tab.document, &tab.layout_root) else {
+            return;
+        };
+        let top = anchor::anchor_positions(doc, root)
+            .get(fragment)
+            .map_or(0, |&y| y as i32);
+        // The content height is normally learned at paint time; set it
+        // now so the anchor is not clamped away.
+        tab.scroll
+            .set_content_height(root.dimensions.margin_box().height as i32);
+        tab.scroll.scroll_to(top);
+    }
+
     /// Go back in history.
     pub fn go_back(&mut self, vfs: &dyn Vfs) {
         // Save current scroll position.
-        self.nav.update_scroll(self.scroll.scroll_y);
+        self.tab_mut().save_scroll();
 
-        if let Some(entry) = self.nav.go_back() {
-            let url = entry.url.clone();
-            let scroll_y = entry.scroll_y;
-            self.navigate_vfs(&url, vfs);
-            self.scroll.scroll_to(scroll_y);
+        if let Some(entry) = self.tab_mut().nav.go_back() {
+            self.show_history_entry(&entry, vfs);
         }
     }
 
     /// Go forward in history.
     pub fn go_forward(&mut self, vfs: &dyn Vfs) {
-        self.nav.update_scroll(self.scroll.scroll_y);
+        self.tab_mut().save_scroll();
 
-        if let Some(entry) = self.nav.go_forward() {
-            let url = entry.url.clone();
-            let scroll_y = entry.scroll_y;
-            self.navigate_vfs(&url, vfs);
-            self.scroll.scroll_to(scroll_y);
+        if let Some(entry) = self.tab_mut().nav.go_forward() {
+            self.show_history_entry(&entry, vfs);
         }
     }
 
-    /// Navigate to the home page.
-    pub fn go_home(&mut self, vfs: &dyn Vfs) {
-        let url = self.nav.go_home();
-        self.navigate_vfs(&url, vfs);
+    /// Display a history entry, restoring it from the page cache when
+    /// possible and reloading it otherwise.
+    fn show_history_entry(&mut self, entry: &HistoryEntry, vfs: &dyn Vfs) {
+        if self.restore_cached_page(&entry.url) {
+            self.load_page_images(vfs);
+        } else {
+            self.reloading_history = true;
+            self.navigate_vfs(&entry.url, vfs);
+            self.reloading_history = false;
+        }
+        // The content height is normally learned at paint time; set it
+        // now so the saved offset is not clamped away.
+        if let Some(root) = &self.tab().layout_root {
+            let height = root.dimensions.margin_box().height as i32;
+            self.tab_mut().scroll.set_content_height(height);
+        }
+        self.tab_mut().scroll.scroll_to(entry.scroll_y);
     }
 
-    // ---------------------------------------------------------------
-    // Accessors
-    // ---------------------------------------------------------------
+    /// Swap in a cached page without re-parsing it. Returns `false` if
+    /// `url` is not cached.
+    ///
+    /// The history position is left untouched; the layout is redone
+    /// only if the window width or text scale changed since it was
+    /// cached.
+    fn restore_cached_page(&mut self, url: &str) -> bool {
+        let Some(page) = self.page_cache.get(url) else {
+            return false;
+        };
+        let page = page.clone();
+        let layout_root = if page.layout_width == self.window_w
+            && page.layout_scale == self.config.clamped_text_scale()
+            && page.layout_glyphs == self.glyph_signature
+        {
+            page.layout_root
+        } else {
+            self.layout_document(
+                &page.document,
+                &page.styles,
+                url,
+                self.config.clamped_text_scale(),
+            )
+        };
 
-    /// Get the window X position (set by the WM).
-    pub fn window_x(&self) -> i32 {
-        self.window_x
+        let tab = self.tab_mut();
+        tab.document = Some(page.document);
+        tab.styles = page.styles;
+        tab.href_map = page.href_map;
+        tab.layout_root = Some(layout_root);
+        tab.link_map.clear();
+        tab.control_map.clear();
+        tab.form_values.clear();
+        tab.selected_link = -1;
+        tab.reader_mode = false;
+        tab.reader_html = None;
+        tab.error_message = None;
+        self.pending_download = None;
+        self.status_message = None;
+        self.tab_mut().find.clear();
+        if matches!(self.focus, Focus::FindBar | Focus::FormField(_)) {
+            self.focus = Focus::Content;
+        }
+        self.tab_mut().scroll.reset();
+        self.tab_mut().state = LoadingState::Idle;
+        true
     }
 
-    /// Get the window Y position (set by the WM).
-    pub fn window_y(&self) -> i32 {
-        self.window_y
+    /// Enlarge the page text by one zoom step.
+    pub fn zoom_in(&mut self) {
+        self.set_text_scale(self.config.clamped_text_scale() + ZOOM_STEP);
     }
 
-    /// Get the title of the current page.
-    pub fn title(&self) -> Option<&str> {
-        self.nav.current_title()
+    /// Shrink the page text by one zoom step.
+    pub fn zoom_out(&mut self) {
+        self.set_text_scale(self.config.clamped_text_scale() - ZOOM_STEP);
     }
 
-    /// Get the URL of the current page.
-    pub fn current_url(&self) -> Option<&str> {
-        self.nav.current_url()
+    /// Return the page text to its normal size.
+    pub fn zoom_reset(&mut self) {
+        self.set_text_scale(1.0);
     }
 
-    /// Get the current loading state.
-    pub fn loading_state(&self) -> LoadingState {
-        self.state
+    /// Change the page zoom and reflow every tab, keeping each at the
+    /// same fraction of the way down its page. The new level is flashed
+    /// in the status bar.
+    pub fn set_text_scale(&mut self, scale: f32) {
+        let old = self.config.clamped_text_scale();
+        self.config.text_scale = scale;
+        let scale = self.config.clamped_text_scale();
+        self.config.text_scale = scale;
+        let percent = (scale * 100.0).round() as u32;
+        self.status_flash = Some((format!("Zoom {percent}%"), STATUS_FLASH_FRAMES));
+        if scale == old {
+            return;
+        }
+        self.reflow_tabs();
     }
 
-    /// Check if reader mode is active.
-    pub fn is_reader_mode(&self) -> bool {
-        self.reader_mode
+    /// Enlarge the reader-mode text by one step.
+    pub fn reader_increase_font(&mut self) {
+        self.set_reader_text_scale(self.config.clamped_reader_text_scale() + READER_TEXT_STEP);
     }
 
-    /// Get an immutable reference to the navigation controller.
-    pub fn navigation(&self) -> &NavigationController {
-        &self.nav
+    /// Shrink the reader-mode text by one step.
+    pub fn reader_decrease_font(&mut self) {
+        self.set_reader_text_scale(self.config.clamped_reader_text_scale() - READER_TEXT_STEP);
     }
 
-    /// Get a mutable reference to the navigation controller.
-    pub fn navigation_mut(&mut self) -> &mut NavigationController {
-        &mut self.nav
+    /// Change the reader-mode text size and reflow the tabs in reader
+    /// mode. The size is kept in the config, so it outlives toggling
+    /// reader mode off and on. The new level is flashed in the status
+    /// bar.
+    pub fn set_reader_text_scale(&mut self, scale: f32) {
+        let old = self.config.clamped_reader_text_scale();
+        self.config.reader_text_scale = scale;
+        let scale = self.config.clamped_reader_text_scale();
+        self.config.reader_text_scale = scale;
+        let percent = (scale * 100.0).round() as u32;
+        self.status_flash = Some((format!("Reader text {percent}%"), STATUS_FLASH_FRAMES));
+        if scale == old {
+            return;
+        }
+        self.reflow_tabs();
     }
 
-    /// Get the current error message, if any.
-    pub fn error_message(&self) -> Option<&str> {
-        self.error_message.as_deref()
+    /// Lay out every tab again, keeping each at the same fraction of the
+    /// way down its page.
+    fn reflow_tabs(&mut self) {
+        let active = self.active_tab;
+        for index in 0..self.tabs.len() {
+            // `relayout_current_page` works on the active tab.
+            self.active_tab = index;
+            let fraction = self.tab().scroll.scroll_fraction();
+            self.relayout_current_page();
+            let measurer: &dyn TextMeasurer = match &self.glyphs {
+                Some(table) => table,
+                None => &SimpleTextMeasurer,
+            };
+            let tab = &mut self.tabs[index];
+            let Some(root) = &tab.layout_root else {
+                continue;
+            };
+            tab.scroll
+                .set_content_height(root.dimensions.margin_box().height as i32);
+            let max = tab.scroll.max_scroll() as f32;
+            tab.scroll.scroll_to((fraction * max).round() as i32);
+            // Find highlights hold positions from the old layout.
+            if !tab.find.matches.is_empty() {
+                tab.find.matches = find::find_matches(root, &tab.find.query.text, measurer);
+                tab.find.current = tab
+                    .find
+                    .current
+                    .min(tab.find.matches.len().saturating_sub(1));
+            }
+        }
+        self.active_tab = active;
     }
 
-    /// Get the scroll state.
-    pub fn scroll(&self) -> &ScrollState {
-        &self.scroll
+    /// Navigate to the home page.
+    pub fn go_home(&mut self, vfs: &dyn Vfs) {
+        self.tab_mut().save_scroll();
+        let url = self.tab_mut().nav.go_home();
+        self.navigate_vfs(&url, vfs);
     }
 
-    /// Get a mutable reference to the scroll state.
-    pub fn scroll_mut(&mut self) -> &mut ScrollState {
-        &mut self.scroll
+    /// Bookmark the current page and write the list through to
+    /// `config.bookmarks_path`.
+    pub fn add_bookmark(&mut self, vfs: &mut dyn Vfs) {
+        let nav = &self.tabs[self.active_tab].nav;
+        if let Some(url) = nav.current_url() {
+            self.bookmarks
+                .add(url, nav.current_title().unwrap_or_default());
+        }
+        self.persist_bookmarks(vfs);
     }
-}
-
-// -----------------------------------------------------------------------
-// Gemini-to-HTML helper
-// -----------------------------------------------------------------------
 
-/// Convert a parsed Gemini document to a simple HTML string for
-/// rendering through the HTML pipeline.
-fn gemini_to_html(doc: &gemini::parser::GeminiDocument) -> String {
-    let mut html = String::from("<html><head><title>");
-    if let Some(title) = doc.title() {
-        html.push_str(title);
+    /// Remove the bookmark for `url` and write the list through.
+    pub fn remove_bookmark(&mut self, url: &str, vfs: &mut dyn Vfs) {
+        self.bookmarks.remove(url);
+        self.persist_bookmarks(vfs);
     }
-    html.push_str("</title></head><body>");
+
+    /// Bookmark the current page (with its title) and report it in the
+    /// status bar.
+    fn bookmark_current_page(&mut self, vfs: &mut dyn Vfs) {
+        let nav = &self.tab().nav;
+        let Some(url) = nav.current_url() else {
+            return;
+        };
+        let label = nav.current_title().filter(|t| !t.is_empty()).unwrap_or(url);
+        let message = if self.bookmarks.contains(url) {
+            format!("Already bookmarked: {label}")
+        } else {
+            format!("Bookmarked: {label}")
+        };
+        self.add_bookmark(vfs);
+        if self.status_message.is_none() {
+            self.status_message = Some(message);
+        }
+    }
+
+    /// Render the bookmarks page.
+    fn load_bookmarks_page(&mut self) {
+        let html = bookmarks_page::render(self.bookmarks.list());
+        self.load_html(&html, bookmarks_page::BOOKMARKS_URL);
+        // The page must be rebuilt whenever it is shown.
+        self.page_cache.invalidate(bookmarks_page::BOOKMARKS_URL);
+    }
+
+    /// Handle a delete link on the bookmarks page: drop the bookmark,
+    /// mark the list for writing through, and re-render in place.
+    fn remove_bookmark_from_page(&mut self, url: &str, vfs: &dyn Vfs) {
+        self.bookmarks.remove(url);
+        self.bookmarks_dirty = true;
+        if self.tab().nav.current_url() == Some(bookmarks_page::BOOKMARKS_URL) {
+            self.reloading_history = true;
+            self.navigate_vfs(bookmarks_page::BOOKMARKS_URL, vfs);
+            self.reloading_history = false;
+        }
+    }
+
+    /// Save bookmarks to the configured path, reporting failures in
+    /// the status bar.
+    fn persist_bookmarks(&mut self, vfs: &mut dyn Vfs) {
+        self.bookmarks_dirty = false;
+        let Some(path) = &self.config.bookmarks_path else {
+            return;
+        };
+        if let Err(e) = self.bookmarks.save_to_vfs(vfs, path) {
+            self.status_message = Some(format!("Could not save bookmarks: {e}"));
+        }
+    }
+
+    /// Add the page just loaded to the browsing history.
+    ///
+    /// Reloads and back/forward moves are not new visits, and neither
+    /// are error pages, internal `about:` pages, or downloads.
+    fn record_visit(&mut self) {
+        if self.reloading_history
+            || self.pending_download.is_some()
+            || self.tab().state != LoadingState::Idle
+        {
+            return;
+        }
+        let nav = &self.tab().nav;
+        let Some(url) = nav.current_url().filter(|u| !u.starts_with("about:")) else {
+            return;
+        };
+        let url = url.to_string();
+        let title = nav.current_title().unwrap_or_default().to_string();
+        let now = loader::unix_now();
+        self.history.record(&url, &title, now);
+        self.history_dirty = true;
+    }
+
+    /// Render the history page.
+    fn load_history_page(&mut self) {
+        let html = history_page::render(self.history.visits());
+        self.load_html(&html, history_page::HISTORY_URL);
+        // The page must be rebuilt whenever it is shown.
+        self.page_cache.invalidate(history_page::HISTORY_URL);
+    }
+
+    /// Forget the browsing history and re-render the history page in
+    /// place if it is showing.
+    pub fn clear_history(&mut self) {
+        self.history.clear();
+        self.history_dirty = true;
+        if self.tab().nav.current_url() == Some(history_page::HISTORY_URL) {
+            self.reloading_history = true;
+            self.load_history_page();
+            self.reloading_history = false;
+        }
+    }
+
+    /// Save the browsing history to the configured path, reporting
+    /// failures in the status bar.
+    pub fn save_history(&mut self, vfs: &mut dyn Vfs) {
+        self.history_dirty = false;
+        let Some(path) = &self.config.history_path else {
+            return;
+        };
+        if let Err(e) = self.history.save_to_vfs(vfs, path) {
+            self.status_message = Some(format!("Could not save history: {e}"));
+        }
+    }
+
+    /// Pinned Gemini server certificates.
+    pub fn known_hosts(&self) -> &KnownHosts {
+        &self.known_hosts
+    }
+
+    /// Pin the changed certificate `host` presented and reload the
+    /// page that warned about it.
+    fn accept_certificate(&mut self, host: &str, vfs: &dyn Vfs) {
+        if !self.known_hosts.accept_pending(host) {
+            self.status_message = Some(format!("No certificate change pending for {host}"));
+            return;
+        }
+        let Some(url) = self.tab().nav.current_url().map(String::from) else {
+            return;
+        };
+        self.page_cache.invalidate(&url);
+        self.reloading_history = true;
+        self.navigate_vfs(&url, vfs);
+        self.reloading_history = false;
+    }
+
+    /// Save pinned Gemini certificates to the configured path,
+    /// reporting failures in the status bar.
+    pub fn save_known_hosts(&mut self, vfs: &mut dyn Vfs) {
+        let Some(path) = &self.config.known_hosts_path else {
+            return;
+        };
+        if let Err(e) = self.known_hosts.save(vfs, path) {
+            self.status_message = Some(format!("Could not save known hosts: {e}"));
+        }
+    }
+
+    // ---------------------------------------------------------------
+    // Tabs
+    // ---------------------------------------------------------------
+
+    /// The active tab.
+    pub fn tab(&self) -> &BrowserTab {
+        &self.tabs[self.active_tab]
+    }
+
+    /// The active tab, mutably.
+    pub(crate) fn tab_mut(&mut self) -> &mut BrowserTab {
+        &mut self.tabs[self.active_tab]
+    }
+
+    /// All open tabs, in tab-strip order.
+    pub fn tabs(&self) -> &[BrowserTab] {
+        &self.tabs
+    }
+
+    /// Index of the active tab.
+    pub fn active_tab(&self) -> usize {
+        self.active_tab
+    }
+
+    /// Open a tab after the active one, switch to it, and load `url`.
+    ///
+    /// Returns `false` without opening anything when
+    /// `config.features.max_tabs` tabs are already open.
+    pub fn new_tab(&mut self, url: &str, vfs: &dyn Vfs) -> bool {
+        let max_tabs = self.config.features.max_tabs.max(1);
+        if self.tabs.len() >= max_tabs {
+            self.status_message = Some(format!("Tab limit reached ({max_tabs})"));
+            return false;
+        }
+        let mut tab = BrowserTab::new(&self.config.features.home_url, 0, self.config.smooth_scroll);
+        tab.scroll.set_tuning(self.config.scroll_tuning);
+        self.tabs.insert(self.active_tab + 1, tab);
+        self.update_viewport_heights();
+        self.switch_tab(self.active_tab + 1);
+        self.navigate_vfs(url, vfs);
+        true
+    }
+
+    /// Close the tab at `index`. The last remaining tab cannot be
+    /// closed.
+    pub fn close_tab(&mut self, index: usize) {
+        if self.tabs.len() <= 1 || index >= self.tabs.len() {
+            return;
+        }
+        self.tabs.remove(index);
+        let active = if index < self.active_tab {
+            self.active_tab - 1
+        } else {
+            self.active_tab.min(self.tabs.len() - 1)
+        };
+        self.update_viewport_heights();
+        self.switch_tab(active);
+    }
+
+    /// Switch to the next tab, wrapping at the end.
+    pub fn next_tab(&mut self) {
+        self.switch_tab((self.active_tab + 1) % self.tabs.len());
+    }
+
+    /// Switch to the previous tab, wrapping at the start.
+    pub fn prev_tab(&mut self) {
+        self.switch_tab((self.active_tab + self.tabs.len() - 1) % self.tabs.len());
+    }
+
+    /// Make the tab at `index` active, leaving any page-specific input
+    /// mode of the previous tab.
+    fn switch_tab(&mut self, index: usize) {
+        if index >= self.tabs.len() {
+            return;
+        }
+        self.active_tab = index;
+        self.focus = Focus::Content;
+        self.status_message = None;
+    }
+
+    /// Height of the chrome above the content: the URL bar, plus the
+    /// tab strip when more than one tab is open.
+    fn chrome_height(&self) -> u32 {
+        if self.tabs.len() > 1 {
+            self.config.url_bar_height + self.config.tab_bar_height
+        } else {
+            self.config.url_bar_height
+        }
+    }
+
+    /// Screen y of the top of the content viewport.
+    fn content_top(&self) -> i32 {
+        self.window_y + self.chrome_height() as i32
+    }
+
+    /// Height of the content viewport.
+    fn content_height(&self) -> u32 {
+        self.window_h
+            .saturating_sub(self.chrome_height())
+            .saturating_sub(self.config.status_bar_height)
+    }
+
+    /// Resize every tab's scroll viewport to the content area.
+    fn update_viewport_heights(&mut self) {
+        let vh = self.content_height() as i32;
+        for tab in &mut self.tabs {
+            tab.scroll.set_viewport_height(vh);
+        }
+    }
+
+    // ---------------------------------------------------------------
+    // Accessors
+    // ---------------------------------------------------------------
+
+    /// Get the window X position (set by the WM).
+    pub fn window_x(&self) -> i32 {
+        self.window_x
+    }
+
+    /// Get the window Y position (set by the WM).
+    pub fn window_y(&self) -> i32 {
+        self.window_y
+    }
+
+    /// Get the title of the current page.
+    pub fn title(&self) -> Option<&str> {
+        self.tab().nav.current_title()
+    }
+
+    /// Get the URL of the current page.
+    pub fn current_url(&self) -> Option<&str> {
+        self.tab().nav.current_url()
+    }
+
+    /// Get the current loading state.
+    pub fn loading_state(&self) -> LoadingState {
+        self.tab().state
+    }
+
+    /// Check if reader mode is active.
+    pub fn is_reader_mode(&self) -> bool {
+        self.tab().reader_mode
+    }
+
+    /// All bookmarks.
+    pub fn bookmarks(&self) -> &[Bookmark] {
+        self.bookmarks.list()
+    }
+
+    /// Browsing history, least recently visited first.
+    pub fn visits(&self) -> &[Visit] {
+        self.history.visits()
+    }
+
+    /// Get an immutable reference to the navigation controller.
+    pub fn navigation(&self) -> &NavigationController {
+        &self.tab().nav
+    }
+
+    /// Get a mutable reference to the navigation controller.
+    pub fn navigation_mut(&mut self) -> &mut NavigationController {
+        &mut self.tab_mut().nav
+    }
+
+    /// Get the current error message, if any.
+    pub fn error_message(&self) -> Option<&str> {
+        self.tab().error_message.as_deref()
+    }
+
+    /// Get the scroll state.
+    pub fn scroll(&self) -> &ScrollState {
+        &self.tab().scroll
+    }
+
+    /// Get a mutable reference to the scroll state.
+    pub fn scroll_mut(&mut self) -> &mut ScrollState {
+        &mut self.tab_mut().scroll
+    }
+
+    /// Resource cache usage counters.
+    pub fn cache_stats(&self) -> CacheStats {
+        self.cache.stats()
+    }
+
+    /// Drop every cached resource. Their textures are freed on the
+    /// next paint.
+    pub fn clear_cache(&mut self) {
+        self.cache.clear();
+    }
+}
+
+// -----------------------------------------------------------------------
+// Zoom helper
+// -----------------------------------------------------------------------
+
+/// Copy `styles` with every font size and line height multiplied by
+/// `scale`.
+fn scale_styles(
+    styles: &[Option<css::values::ComputedStyle>],
+    scale: f32,
+) -> Vec<Option<css::values::ComputedStyle>> {
+    styles
+        .iter()
+        .map(|style| {
+            style.clone().map(|mut style| {
+                style.font_size *= scale;
+                style.line_height *= scale;
+                style
+            })
+        })
+        .collect()
+}
+
+// -----------------------------------------------------------------------
+// Gemini-to-HTML helper
+// -----------------------------------------------------------------------
+
+/// Convert a parsed Gemini document to a simple HTML string for
+/// rendering through the HTML pipeline.
+fn gemini_to_html(doc: &gemini::parser::GeminiDocument) -> String {
+    let mut html = String::from("<html><head><title>");
+    if let Some(title) = doc.title() {
+        html.push_str(title);
+    }
+    html.push_str("</title></head><body>");
 
     for line in &doc.lines {
         match line {
@@ -1124,7 +2868,9 @@ fn push_escaped(out: &mut String, text: &str) {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::test_utils::MockBackend;
+    use crate::paint::LinkRegion;
+    use crate::test_utils::{DrawCall, MockBackend};
+    use oasis_types::input::EditKey;
     use oasis_vfs::MemoryVfs;
 
     // ---------------------------------------------------------------
@@ -1243,7 +2989,7 @@ mod tests {
         assert_eq!(browser.loading_state(), LoadingState::Idle);
         assert_eq!(browser.current_url(), Some("vfs://sites/home/index.html"));
         assert_eq!(browser.title(), Some("Home"));
-        assert!(browser.document.is_some());
+        assert!(browser.tab().document.is_some());
     }
 
     // ---------------------------------------------------------------
@@ -1252,33 +2998,57 @@ mod tests {
 
     #[test]
     fn scroll_input_changes_offset() {
-        let vfs = test_vfs();
+        let mut vfs = test_vfs();
         let mut browser = make_browser();
         browser.set_window(0, 0, 480, 272);
         browser.navigate_vfs("vfs://sites/home/index.html", &vfs);
 
         // Set a large content height so scroll is possible.
-        browser.scroll.set_content_height(1000);
+        browser.tab_mut().scroll.set_content_height(1000);
 
-        let initial = browser.scroll.scroll_y;
+        let initial = browser.tab_mut().scroll.scroll_y;
 
         // Scroll down.
-        browser.handle_input(&InputEvent::ButtonPress(Button::Down), &vfs);
+        browser.handle_input(&InputEvent::ButtonPress(Button::Down), &mut vfs);
         assert!(
-            browser.scroll.scroll_y > initial,
+            browser.tab_mut().scroll.scroll_y > initial,
             "scroll_y should increase on Down"
         );
 
-        let after_down = browser.scroll.scroll_y;
+        let after_down = browser.tab_mut().scroll.scroll_y;
 
         // Scroll up.
-        browser.handle_input(&InputEvent::ButtonPress(Button::Up), &vfs);
+        browser.handle_input(&InputEvent::ButtonPress(Button::Up), &mut vfs);
         assert!(
-            browser.scroll.scroll_y < after_down,
+            browser.tab_mut().scroll.scroll_y < after_down,
             "scroll_y should decrease on Up"
         );
     }
 
+    #[test]
+    fn smooth_scroll_advances_on_tick_with_the_configured_tuning() {
+        let mut vfs = test_vfs();
+        let mut browser = BrowserWidget::new(BrowserConfig {
+            smooth_scroll: true,
+            scroll_tuning: ScrollTuning::snappy(),
+            ..BrowserConfig::default()
+        });
+        browser.set_window(0, 0, 480, 272);
+        browser.navigate_vfs("vfs://sites/home/index.html", &vfs);
+        browser.tab_mut().scroll.set_content_height(1000);
+        assert_eq!(browser.tab().scroll.tuning(), ScrollTuning::snappy());
+
+        browser.handle_input(&InputEvent::ButtonPress(Button::Down), &mut vfs);
+        assert_eq!(
+            browser.tab().scroll.scroll_y,
+            0,
+            "nothing moves before a tick"
+        );
+        while browser.tick(16) {}
+        assert!(browser.tab().scroll.scroll_y > 0);
+        assert!(!browser.tick(16), "settled");
+    }
+
     // ---------------------------------------------------------------
     // Test 5: link navigation
     // ---------------------------------------------------------------
@@ -1288,7 +3058,7 @@ mod tests {
         let mut browser = make_browser();
 
         // Manually set up some link regions.
-        browser.link_map = vec![
+        browser.tab_mut().link_map = vec![
             LinkRegion {
                 rect: layout::box_model::Rect::new(10.0, 100.0, 80.0, 16.0),
                 href: "page1.html".to_string(),
@@ -1301,27 +3071,27 @@ mod tests {
             },
         ];
 
-        assert_eq!(browser.selected_link, -1);
+        assert_eq!(browser.tab().selected_link, -1);
 
         // Select next -> index 0.
         browser.select_next_link();
-        assert_eq!(browser.selected_link, 0);
+        assert_eq!(browser.tab().selected_link, 0);
 
         // Select next -> index 1.
         browser.select_next_link();
-        assert_eq!(browser.selected_link, 1);
+        assert_eq!(browser.tab().selected_link, 1);
 
         // Select next wraps -> index 0.
         browser.select_next_link();
-        assert_eq!(browser.selected_link, 0);
+        assert_eq!(browser.tab().selected_link, 0);
 
         // Select prev wraps -> index 1.
         browser.select_prev_link();
-        assert_eq!(browser.selected_link, 1);
+        assert_eq!(browser.tab().selected_link, 1);
 
         // Select prev -> index 0.
         browser.select_prev_link();
-        assert_eq!(browser.selected_link, 0);
+        assert_eq!(browser.tab().selected_link, 0);
     }
 
     // ---------------------------------------------------------------
@@ -1338,7 +3108,7 @@ mod tests {
         // Navigate to a second page so back works.
         browser.navigate_vfs("vfs://sites/home/page2.html", &vfs);
         assert_eq!(browser.current_url(), Some("vfs://sites/home/page2.html"));
-        assert!(browser.nav.can_go_back());
+        assert!(browser.tab().nav.can_go_back());
 
         // Click the back button (x < button_width, y < url_bar_h).
         browser.handle_click(5, 5, &vfs);
@@ -1384,9 +3154,11 @@ mod tests {
             content_type: ContentType::Html,
             body: b"<html><body>Test</body></html>".to_vec(),
             status: 200,
+            headers: Vec::new(),
+            charset: None,
         };
         browser.process_response(response);
-        assert!(browser.document.is_some());
+        assert!(browser.tab().document.is_some());
         assert_eq!(browser.loading_state(), LoadingState::Idle);
 
         // Gemini content type dispatches through load_gemini.
@@ -1396,9 +3168,11 @@ mod tests {
             content_type: ContentType::GeminiText,
             body: b"# Gemini\nHello".to_vec(),
             status: 200,
+            headers: Vec::new(),
+            charset: None,
         };
         browser2.process_response(response);
-        assert!(browser2.document.is_some());
+        assert!(browser2.tab().document.is_some());
 
         // CSS content type wraps in <pre>.
         let mut browser3 = make_browser();
@@ -1407,9 +3181,11 @@ mod tests {
             content_type: ContentType::Css,
             body: b"body { color: red; }".to_vec(),
             status: 200,
+            headers: Vec::new(),
+            charset: None,
         };
         browser3.process_response(response);
-        assert!(browser3.document.is_some());
+        assert!(browser3.tab().document.is_some());
 
         // Image content type wraps in <img> tag.
         let mut browser4 = make_browser();
@@ -1418,9 +3194,11 @@ mod tests {
             content_type: ContentType::Png,
             body: vec![0u8; 16],
             status: 200,
+            headers: Vec::new(),
+            charset: None,
         };
         browser4.process_response(response);
-        assert!(browser4.document.is_some());
+        assert!(browser4.tab().document.is_some());
     }
 
     // ===============================================================
@@ -1455,6 +3233,45 @@ mod tests {
         );
     }
 
+    // ---------------------------------------------------------------
+    // Test: layout follows the backend's glyph advances
+    // ---------------------------------------------------------------
+
+    #[test]
+    fn layout_uses_backend_glyph_widths() {
+        let mut browser = make_browser();
+        browser.set_window(0, 0, 480, 272);
+        let text = "lorem ipsum dolor ".repeat(20);
+        browser.load_html(&format!("<p>{text}</p>"), "vfs://test.html");
+        let height = |b: &BrowserWidget| {
+            b.tab()
+                .layout_root
+                .as_ref()
+                .unwrap()
+                .dimensions
+                .margin_box()
+                .height
+        };
+        let bitmap_height = height(&browser);
+
+        // The bitmap backend keeps the layout as it is.
+        browser.paint(&mut MockBackend::new()).unwrap();
+        assert!(browser.glyphs.is_none());
+        assert_eq!(height(&browser), bitmap_height);
+
+        // A wider font wraps onto more lines.
+        let mut wide = MockBackend::new();
+        wide.glyph_width = Some(12);
+        browser.paint(&mut wide).unwrap();
+        assert!(browser.glyphs.is_some());
+        assert!(height(&browser) > bitmap_height);
+
+        // Switching back restores the bitmap metrics.
+        browser.paint(&mut MockBackend::new()).unwrap();
+        assert!(browser.glyphs.is_none());
+        assert_eq!(height(&browser), bitmap_height);
+    }
+
     // ---------------------------------------------------------------
     // Test: page renders links as clickable regions
     // ---------------------------------------------------------------
@@ -1470,10 +3287,11 @@ mod tests {
         browser.paint(&mut backend).unwrap();
 
         assert!(
-            !browser.link_map.is_empty(),
+            !browser.tab().link_map.is_empty(),
             "link_map should contain at least one link"
         );
         let has_page2 = browser
+            .tab()
             .link_map
             .iter()
             .any(|l| l.href.contains("page2.html"));
@@ -1614,8 +3432,8 @@ mod tests {
         browser.handle_click(click_x, click_y, &vfs);
 
         assert_eq!(browser.focus, Focus::UrlBar);
-        assert_eq!(browser.url_input, "vfs://sites/home/index.html");
-        assert_eq!(browser.url_cursor, browser.url_input.len());
+        assert_eq!(browser.url_field.text, "vfs://sites/home/index.html");
+        assert_eq!(browser.url_field.cursor, browser.url_field.text.len());
     }
 
     // ---------------------------------------------------------------
@@ -1624,7 +3442,7 @@ mod tests {
 
     #[test]
     fn url_bar_typing_inserts_chars() {
-        let vfs = test_vfs();
+        let mut vfs = test_vfs();
         let mut browser = make_browser();
         browser.set_window(0, 0, 480, 272);
         browser.navigate_vfs("vfs://sites/home/index.html", &vfs);
@@ -1634,15 +3452,15 @@ mod tests {
         browser.handle_click((bw * 2 + 10) as i32, 5, &vfs);
         assert_eq!(browser.focus, Focus::UrlBar);
 
-        let base_len = browser.url_input.len();
+        let base_len = browser.url_field.text.len();
 
-        browser.handle_input(&InputEvent::TextInput('a'), &vfs);
-        browser.handle_input(&InputEvent::TextInput('b'), &vfs);
-        browser.handle_input(&InputEvent::TextInput('c'), &vfs);
+        browser.handle_input(&InputEvent::TextInput('a'), &mut vfs);
+        browser.handle_input(&InputEvent::TextInput('b'), &mut vfs);
+        browser.handle_input(&InputEvent::TextInput('c'), &mut vfs);
 
-        assert_eq!(browser.url_input.len(), base_len + 3);
-        assert!(browser.url_input.ends_with("abc"));
-        assert_eq!(browser.url_cursor, browser.url_input.len());
+        assert_eq!(browser.url_field.text.len(), base_len + 3);
+        assert!(browser.url_field.text.ends_with("abc"));
+        assert_eq!(browser.url_field.cursor, browser.url_field.text.len());
     }
 
     // ---------------------------------------------------------------
@@ -1651,7 +3469,7 @@ mod tests {
 
     #[test]
     fn url_bar_backspace_deletes() {
-        let vfs = test_vfs();
+        let mut vfs = test_vfs();
         let mut browser = make_browser();
         browser.set_window(0, 0, 480, 272);
         browser.navigate_vfs("vfs://sites/home/index.html", &vfs);
@@ -1659,13 +3477,13 @@ mod tests {
         // Enter URL bar focus and type some chars.
         let bw = browser.config.button_width;
         browser.handle_click((bw * 2 + 10) as i32, 5, &vfs);
-        browser.handle_input(&InputEvent::TextInput('x'), &vfs);
-        browser.handle_input(&InputEvent::TextInput('y'), &vfs);
-        let before_bs = browser.url_input.len();
+        browser.handle_input(&InputEvent::TextInput('x'), &mut vfs);
+        browser.handle_input(&InputEvent::TextInput('y'), &mut vfs);
+        let before_bs = browser.url_field.text.len();
 
-        browser.handle_input(&InputEvent::Backspace, &vfs);
-        assert_eq!(browser.url_input.len(), before_bs - 1);
-        assert!(browser.url_input.ends_with('x'));
+        browser.handle_input(&InputEvent::Backspace, &mut vfs);
+        assert_eq!(browser.url_field.text.len(), before_bs - 1);
+        assert!(browser.url_field.text.ends_with('x'));
     }
 
     // ---------------------------------------------------------------
@@ -1674,7 +3492,7 @@ mod tests {
 
     #[test]
     fn url_bar_confirm_navigates() {
-        let vfs = test_vfs();
+        let mut vfs = test_vfs();
         let mut browser = make_browser();
         browser.set_window(0, 0, 480, 272);
         browser.navigate_vfs("vfs://sites/home/index.html", &vfs);
@@ -1684,15 +3502,15 @@ mod tests {
         browser.handle_click((bw * 2 + 10) as i32, 5, &vfs);
 
         // Clear the input and type a new URL.
-        browser.url_input.clear();
-        browser.url_cursor = 0;
+        browser.url_field.text.clear();
+        browser.url_field.cursor = 0;
         let target = "vfs://sites/home/page2.html";
         for ch in target.chars() {
-            browser.handle_input(&InputEvent::TextInput(ch), &vfs);
+            browser.handle_input(&InputEvent::TextInput(ch), &mut vfs);
         }
 
         // Press Confirm.
-        browser.handle_input(&InputEvent::ButtonPress(Button::Confirm), &vfs);
+        browser.handle_input(&InputEvent::ButtonPress(Button::Confirm), &mut vfs);
 
         assert_eq!(browser.focus, Focus::Content);
         assert_eq!(browser.current_url(), Some("vfs://sites/home/page2.html"));
@@ -1704,7 +3522,7 @@ mod tests {
 
     #[test]
     fn url_bar_cancel_discards() {
-        let vfs = test_vfs();
+        let mut vfs = test_vfs();
         let mut browser = make_browser();
         browser.set_window(0, 0, 480, 272);
         browser.navigate_vfs("vfs://sites/home/index.html", &vfs);
@@ -1714,13 +3532,13 @@ mod tests {
         // Enter URL bar and modify.
         let bw = browser.config.button_width;
         browser.handle_click((bw * 2 + 10) as i32, 5, &vfs);
-        browser.handle_input(&InputEvent::TextInput('z'), &vfs);
+        browser.handle_input(&InputEvent::TextInput('z'), &mut vfs);
 
         // Press Cancel.
-        browser.handle_input(&InputEvent::ButtonPress(Button::Cancel), &vfs);
+        browser.handle_input(&InputEvent::ButtonPress(Button::Cancel), &mut vfs);
 
         assert_eq!(browser.focus, Focus::Content);
-        assert!(browser.url_input.is_empty());
+        assert!(browser.url_field.text.is_empty());
         assert_eq!(browser.current_url(), Some(original_url.as_str()));
     }
 
@@ -1730,7 +3548,7 @@ mod tests {
 
     #[test]
     fn url_bar_left_right_moves_cursor() {
-        let vfs = test_vfs();
+        let mut vfs = test_vfs();
         let mut browser = make_browser();
         browser.set_window(0, 0, 480, 272);
         browser.navigate_vfs("vfs://sites/home/index.html", &vfs);
@@ -1738,18 +3556,104 @@ mod tests {
         // Enter URL bar.
         let bw = browser.config.button_width;
         browser.handle_click((bw * 2 + 10) as i32, 5, &vfs);
-        let end_pos = browser.url_cursor;
+        let end_pos = browser.url_field.cursor;
         assert!(end_pos > 0);
 
         // Move left.
-        browser.handle_input(&InputEvent::ButtonPress(Button::Left), &vfs);
-        assert!(browser.url_cursor < end_pos);
+        browser.handle_input(&InputEvent::ButtonPress(Button::Left), &mut vfs);
+        assert!(browser.url_field.cursor < end_pos);
 
-        let after_left = browser.url_cursor;
+        let after_left = browser.url_field.cursor;
 
         // Move right.
-        browser.handle_input(&InputEvent::ButtonPress(Button::Right), &vfs);
-        assert!(browser.url_cursor > after_left);
+        browser.handle_input(&InputEvent::ButtonPress(Button::Right), &mut vfs);
+        assert!(browser.url_field.cursor > after_left);
+    }
+
+    #[test]
+    fn url_bar_paste_inserts_at_cursor() {
+        let mut vfs = test_vfs();
+        let mut browser = make_browser();
+        browser.set_window(0, 0, 480, 272);
+        browser.navigate_vfs("vfs://sites/home/index.html", &vfs);
+
+        let bw = browser.config.button_width;
+        browser.handle_click((bw * 2 + 10) as i32, 5, &vfs);
+        for _ in 0.."index.html".len() {
+            browser.handle_input(&InputEvent::ButtonPress(Button::Left), &mut vfs);
+        }
+        let paste = InputEvent::Paste("../home/".to_string());
+        assert!(browser.handle_input(&paste, &mut vfs));
+        assert_eq!(
+            browser.url_field.text,
+            "vfs://sites/home/../home/index.html"
+        );
+        assert_eq!(browser.url_field.cursor, "vfs://sites/home/../home/".len());
+    }
+
+    #[test]
+    fn url_bar_selection_and_copy() {
+        let mut vfs = test_vfs();
+        let mut browser = make_browser();
+        browser.set_window(0, 0, 480, 272);
+        browser.navigate_vfs("vfs://sites/home/index.html", &vfs);
+
+        let bw = browser.config.button_width;
+        browser.handle_click((bw * 2 + 10) as i32, 5, &vfs);
+        let select_word = InputEvent::EditKey {
+            key: EditKey::Left,
+            shift: true,
+            ctrl: true,
+        };
+        let copy = InputEvent::EditKey {
+            key: EditKey::Copy,
+            shift: false,
+            ctrl: true,
+        };
+        assert!(browser.handle_input(&select_word, &mut vfs));
+        assert!(browser.handle_input(&select_word, &mut vfs));
+        assert!(browser.handle_input(&copy, &mut vfs));
+        assert_eq!(browser.take_copied_text().as_deref(), Some("index.html"));
+        assert_eq!(browser.take_copied_text(), None);
+
+        // Typing replaces the selection.
+        for ch in "page2.html".chars() {
+            browser.handle_input(&InputEvent::TextInput(ch), &mut vfs);
+        }
+        assert_eq!(browser.url_field.text, "vfs://sites/home/page2.html");
+    }
+
+    #[test]
+    fn status_bar_spins_while_loading() {
+        let mut browser = make_browser();
+        browser.set_window(0, 0, 480, 272);
+        let status_bar = |browser: &BrowserWidget| {
+            let mut backend = MockBackend::new();
+            browser.paint_status_bar(&mut backend).unwrap();
+            let rects: Vec<_> = backend
+                .calls
+                .iter()
+                .filter_map(|c| match c {
+                    DrawCall::FillRect { x, y, color, .. } => Some((*x, *y, *color)),
+                    _ => None,
+                })
+                .collect();
+            let text_x = backend.text_positions()[0].1;
+            (rects, text_x)
+        };
+
+        // Idle: just the background behind the status text.
+        let (idle, idle_x) = status_bar(&browser);
+        assert_eq!(idle.len(), 1);
+
+        browser.tab_mut().state = LoadingState::Loading;
+        let (first, loading_x) = status_bar(&browser);
+        assert!(first.len() > 1);
+        assert!(loading_x > idle_x, "the spinner sits before the text");
+        browser.frame += 10;
+        let (later, _) = status_bar(&browser);
+        assert_eq!(first.len(), later.len());
+        assert_ne!(first, later, "the spinner animates with the frame count");
     }
 
     // ---------------------------------------------------------------
@@ -1784,7 +3688,7 @@ mod tests {
 
     #[test]
     fn paint_chrome_url_bar_editing() {
-        let vfs = test_vfs();
+        let mut vfs = test_vfs();
         let mut browser = make_browser();
         browser.set_window(0, 0, 480, 272);
         browser.navigate_vfs("vfs://sites/home/index.html", &vfs);
@@ -1792,7 +3696,7 @@ mod tests {
         // Enter URL bar and type something.
         let bw = browser.config.button_width;
         browser.handle_click((bw * 2 + 10) as i32, 5, &vfs);
-        browser.handle_input(&InputEvent::TextInput('!'), &vfs);
+        browser.handle_input(&InputEvent::TextInput('!'), &mut vfs);
 
         let mut backend = MockBackend::new();
         browser.paint(&mut backend).unwrap();
@@ -1997,7 +3901,11 @@ mod tests {
 
         // Check layout tree: all inline boxes should have
         // content.height >= font_size.
-        let layout = browser.layout_root.as_ref().expect("should have layout");
+        let layout = browser
+            .tab()
+            .layout_root
+            .as_ref()
+            .expect("should have layout");
         check_line_heights(layout);
     }
 
@@ -2034,11 +3942,11 @@ mod tests {
         browser.paint(&mut backend).unwrap();
 
         assert!(
-            !browser.link_map.is_empty(),
+            !browser.tab().link_map.is_empty(),
             "should have at least one link region"
         );
 
-        for link in &browser.link_map {
+        for link in &browser.tab().link_map {
             assert!(
                 link.rect.width > 0.0,
                 "link '{}' should have positive width, got {}",
@@ -2067,7 +3975,7 @@ mod tests {
         let chrome_y = browser.config.url_bar_height as f32;
         let view_bottom = browser.window_h as f32;
 
-        for link in &browser.link_map {
+        for link in &browser.tab().link_map {
             assert!(
                 link.rect.x >= 0.0,
                 "link x ({}) should be >= 0",
@@ -2099,16 +4007,16 @@ mod tests {
         browser.paint(&mut backend).unwrap();
 
         assert!(
-            browser.link_map.len() >= 3,
+            browser.tab().link_map.len() >= 3,
             "multi-link page should have at least 3 links, got {}",
-            browser.link_map.len(),
+            browser.tab().link_map.len(),
         );
 
         // No two links should have the same Y position.
-        for i in 0..browser.link_map.len() {
-            for j in (i + 1)..browser.link_map.len() {
-                let a = &browser.link_map[i].rect;
-                let b = &browser.link_map[j].rect;
+        for i in 0..browser.tab().link_map.len() {
+            for j in (i + 1)..browser.tab().link_map.len() {
+                let a = &browser.tab().link_map[i].rect;
+                let b = &browser.tab().link_map[j].rect;
                 // Check they don't fully overlap (different hrefs should
                 // have different rects).
                 let overlaps_x = a.x < b.x + b.width && b.x < a.x + a.width;
@@ -2117,8 +4025,8 @@ mod tests {
                     !(overlaps_x && overlaps_y),
                     "links '{}' and '{}' should not overlap: \
                      a=({},{},{},{}) b=({},{},{},{})",
-                    browser.link_map[i].href,
-                    browser.link_map[j].href,
+                    browser.tab().link_map[i].href,
+                    browser.tab().link_map[j].href,
                     a.x,
                     a.y,
                     a.width,
@@ -2143,6 +4051,7 @@ mod tests {
         browser.paint(&mut backend).unwrap();
 
         let link = browser
+            .tab()
             .link_map
             .iter()
             .find(|l| l.href.contains("target.html"))
@@ -2193,6 +4102,7 @@ mod tests {
         browser.paint(&mut backend).unwrap();
 
         let link = browser
+            .tab()
             .link_map
             .iter()
             .find(|l| l.href.contains("target.html"))
@@ -2236,6 +4146,7 @@ mod tests {
         browser.paint(&mut backend).unwrap();
 
         let link = browser
+            .tab()
             .link_map
             .iter()
             .find(|l| l.href.contains("target.html"))
@@ -2255,37 +4166,147 @@ mod tests {
     }
 
     #[test]
-    fn click_outside_link_does_not_navigate() {
-        let vfs = interaction_vfs();
+    fn aligned_link_regions_follow_text_align() {
+        let mut vfs = interaction_vfs();
+        vfs.write(
+            "/sites/test/aligned.html",
+            b"<html><head><style>.r { text-align: right }</style></head><body>\
+              <p class=\"r\"><a href=\"target.html\">Right</a></p>\
+              <p style=\"text-align: center\"><a href=\"single_link.html\">Mid</a></p>\
+              </body></html>",
+        )
+        .unwrap();
         let mut browser = make_interaction_browser();
         browser.set_window(0, 0, 480, 272);
-        browser.navigate_vfs("vfs://sites/test/single_link.html", &vfs);
-        let original = browser.current_url().unwrap().to_string();
+        browser.navigate_vfs("vfs://sites/test/aligned.html", &vfs);
 
         let mut backend = MockBackend::new();
         browser.paint(&mut backend).unwrap();
 
-        // Click far below any content.
-        browser.handle_click(240, 260, &vfs);
-        assert_eq!(
-            browser.current_url(),
-            Some(original.as_str()),
-            "clicking outside links should not navigate",
+        let right = browser.tab().link_map[0].rect;
+        let mid = browser.tab().link_map[1].rect;
+        let content_right = 480.0;
+        assert!(
+            right.x + right.width > content_right - 20.0,
+            "right-aligned link ends near the right edge: {right:?}"
         );
+        assert!(mid.x > 100.0 && mid.x + mid.width < content_right - 100.0);
+
+        let cx = (right.x + right.width / 2.0) as i32;
+        let cy = (right.y + right.height / 2.0) as i32;
+        browser.handle_click(cx, cy, &vfs);
+        assert_eq!(browser.current_url(), Some("vfs://sites/test/target.html"));
     }
 
     #[test]
-    fn click_on_second_link_navigates_to_correct_target() {
-        let vfs = interaction_vfs();
-        let mut browser = make_interaction_browser();
-        browser.set_window(0, 0, 480, 272);
-        browser.navigate_vfs("vfs://sites/test/multi_links.html", &vfs);
-
+    fn div_background_and_border_are_painted() {
+        let mut vfs = test_vfs();
+        vfs.write(
+            "/sites/home/boxed.html",
+            b"<html><body>\
+              <div style=\"background-color:#333;border:2px solid red;padding:4px\">Boxed</div>\
+              <div style=\"border-bottom:1px solid blue\">Under</div>\
+              </body></html>",
+        )
+        .unwrap();
+        let mut bw = make_browser();
+        bw.set_window(0, 0, 480, 272);
+        bw.navigate_vfs("vfs://sites/home/boxed.html", &vfs);
         let mut backend = MockBackend::new();
-        browser.paint(&mut backend).unwrap();
+        bw.paint(&mut backend).unwrap();
 
-        // Find link B.
-        let link_b = browser
+        let bg = backend
+            .calls
+            .iter()
+            .find_map(|c| match c {
+                DrawCall::FillRect { x, y, w, h, color }
+                    if *color == Color::rgb(0x33, 0x33, 0x33) =>
+                {
+                    Some((*x, *y, *w, *h))
+                },
+                _ => None,
+            })
+            .expect("background fill");
+        let border = backend
+            .calls
+            .iter()
+            .find_map(|c| match c {
+                DrawCall::StrokeRect {
+                    x,
+                    y,
+                    w,
+                    h,
+                    stroke_width,
+                    color,
+                } if *color == Color::rgb(255, 0, 0) => Some((*x, *y, *w, *h, *stroke_width)),
+                _ => None,
+            })
+            .expect("border stroke");
+        // Background and border both cover the border box.
+        assert_eq!((border.0, border.1, border.2, border.3), bg);
+        assert_eq!(border.4, 2);
+
+        // Text sits inside the border and padding.
+        let (_, tx, ty, _) = backend
+            .text_positions()
+            .into_iter()
+            .find(|t| t.0.contains("Boxed"))
+            .unwrap();
+        assert!(tx >= bg.0 + 6, "text x {tx} overlaps border of {bg:?}");
+        assert!(ty >= bg.1 + 6, "text y {ty} overlaps border of {bg:?}");
+        let content_bottom = bg.1 + bg.3 as i32 - 6;
+
+        // A single side is filled on its own, below the first box.
+        let under = backend
+            .calls
+            .iter()
+            .find_map(|c| match c {
+                DrawCall::FillRect { x, y, w, h, color } if *color == Color::rgb(0, 0, 255) => {
+                    Some((*x, *y, *w, *h))
+                },
+                _ => None,
+            })
+            .expect("bottom border fill");
+        assert_eq!(under.3, 1);
+        assert!(under.1 > content_bottom);
+        assert!(!backend.calls.iter().any(
+            |c| matches!(c, DrawCall::StrokeRect { color, .. } if *color == Color::rgb(0, 0, 255))
+        ));
+    }
+
+    #[test]
+    fn click_outside_link_does_not_navigate() {
+        let vfs = interaction_vfs();
+        let mut browser = make_interaction_browser();
+        browser.set_window(0, 0, 480, 272);
+        browser.navigate_vfs("vfs://sites/test/single_link.html", &vfs);
+        let original = browser.current_url().unwrap().to_string();
+
+        let mut backend = MockBackend::new();
+        browser.paint(&mut backend).unwrap();
+
+        // Click far below any content.
+        browser.handle_click(240, 260, &vfs);
+        assert_eq!(
+            browser.current_url(),
+            Some(original.as_str()),
+            "clicking outside links should not navigate",
+        );
+    }
+
+    #[test]
+    fn click_on_second_link_navigates_to_correct_target() {
+        let vfs = interaction_vfs();
+        let mut browser = make_interaction_browser();
+        browser.set_window(0, 0, 480, 272);
+        browser.navigate_vfs("vfs://sites/test/multi_links.html", &vfs);
+
+        let mut backend = MockBackend::new();
+        browser.paint(&mut backend).unwrap();
+
+        // Find link B.
+        let link_b = browser
+            .tab()
             .link_map
             .iter()
             .find(|l| l.href.contains("page_b.html"))
@@ -2305,7 +4326,7 @@ mod tests {
 
     #[test]
     fn tab_then_confirm_navigates() {
-        let vfs = interaction_vfs();
+        let mut vfs = interaction_vfs();
         let mut browser = make_interaction_browser();
         browser.set_window(0, 0, 480, 272);
         browser.navigate_vfs("vfs://sites/test/single_link.html", &vfs);
@@ -2313,14 +4334,14 @@ mod tests {
         let mut backend = MockBackend::new();
         browser.paint(&mut backend).unwrap();
 
-        assert!(!browser.link_map.is_empty(), "should have links");
+        assert!(!browser.tab().link_map.is_empty(), "should have links");
 
         // Tab to select first link.
-        browser.handle_input(&InputEvent::ButtonPress(Button::Right), &vfs);
-        assert_eq!(browser.selected_link, 0);
+        browser.handle_input(&InputEvent::ButtonPress(Button::Right), &mut vfs);
+        assert_eq!(browser.tab().selected_link, 0);
 
         // Confirm.
-        browser.handle_input(&InputEvent::ButtonPress(Button::Confirm), &vfs);
+        browser.handle_input(&InputEvent::ButtonPress(Button::Confirm), &mut vfs);
 
         assert_eq!(
             browser.current_url(),
@@ -2343,6 +4364,7 @@ mod tests {
             browser.paint(&mut backend).unwrap();
 
             let link = browser
+                .tab()
                 .link_map
                 .iter()
                 .find(|l| l.href.contains(target))
@@ -2368,7 +4390,7 @@ mod tests {
 
     #[test]
     fn link_regions_update_after_scroll_and_repaint() {
-        let vfs = interaction_vfs();
+        let mut vfs = interaction_vfs();
         let mut browser = make_interaction_browser();
         browser.set_window(0, 0, 480, 272);
         browser.navigate_vfs("vfs://sites/test/long.html", &vfs);
@@ -2376,14 +4398,14 @@ mod tests {
         let mut backend = MockBackend::new();
         browser.paint(&mut backend).unwrap();
 
-        let initial_links = browser.link_map.clone();
+        let initial_links = browser.tab().link_map.clone();
 
         // Force content height so scrolling is possible.
-        browser.scroll.set_content_height(2000);
+        browser.tab_mut().scroll.set_content_height(2000);
 
         // Scroll down.
         for _ in 0..5 {
-            browser.handle_input(&InputEvent::ButtonPress(Button::Down), &vfs);
+            browser.handle_input(&InputEvent::ButtonPress(Button::Down), &mut vfs);
         }
 
         // Repaint to get updated link regions.
@@ -2392,11 +4414,11 @@ mod tests {
 
         // If there were links visible before scroll, their Y positions
         // should have shifted (or they may be off-screen now).
-        if !initial_links.is_empty() && !browser.link_map.is_empty() {
+        if !initial_links.is_empty() && !browser.tab().link_map.is_empty() {
             // At minimum, verify the link_map was regenerated (it's
             // rebuilt every paint pass).
             assert!(
-                !browser.link_map.is_empty(),
+                !browser.tab().link_map.is_empty(),
                 "link_map should be regenerated after repaint"
             );
         }
@@ -2418,11 +4440,12 @@ mod tests {
 
         // Should render "this page" as link text.
         assert!(
-            !browser.link_map.is_empty(),
+            !browser.tab().link_map.is_empty(),
             "inline link should produce link regions"
         );
 
         let link = browser
+            .tab()
             .link_map
             .iter()
             .find(|l| l.href.contains("target.html"))
@@ -2449,6 +4472,7 @@ mod tests {
 
         // Click link to navigate.
         let link = browser
+            .tab()
             .link_map
             .iter()
             .find(|l| l.href.contains("target.html"))
@@ -2480,6 +4504,7 @@ mod tests {
 
         // Step 2: Click Link A.
         let link_a = browser
+            .tab()
             .link_map
             .iter()
             .find(|l| l.href.contains("page_a.html"))
@@ -2502,6 +4527,7 @@ mod tests {
 
         // Step 5: Links should work again after going back.
         let link_c = browser
+            .tab()
             .link_map
             .iter()
             .find(|l| l.href.contains("page_c.html"))
@@ -2526,7 +4552,7 @@ mod tests {
         let mut backend = MockBackend::new();
         browser.paint(&mut backend).unwrap();
 
-        for link in &browser.link_map {
+        for link in &browser.tab().link_map {
             // Verify that the center of the rect, cast to i32,
             // still falls inside the f32 rect. This catches rounding
             // edge cases.
@@ -2562,7 +4588,7 @@ mod tests {
         browser.paint(&mut backend).unwrap();
 
         let chrome_h = browser.config.url_bar_height as f32;
-        for link in &browser.link_map {
+        for link in &browser.tab().link_map {
             let cy = (link.rect.y + link.rect.height / 2.0) as i32;
             let rel_y = cy - browser.window_y;
             assert!(
@@ -2589,11 +4615,11 @@ mod tests {
         browser.paint(&mut backend).unwrap();
 
         assert!(
-            !browser.link_map.is_empty(),
+            !browser.tab().link_map.is_empty(),
             "should have at least one link"
         );
 
-        let link = &browser.link_map[0];
+        let link = &browser.tab().link_map[0];
         let cx = (link.rect.x + link.rect.width / 2.0) as i32;
         let cy = (link.rect.y + link.rect.height / 2.0) as i32;
 
@@ -2641,6 +4667,7 @@ mod tests {
         browser.paint(&mut backend).unwrap();
 
         let link = browser
+            .tab()
             .link_map
             .iter()
             .find(|l| l.href.contains("page2.html"))
@@ -2672,9 +4699,13 @@ mod tests {
         let vfs = test_vfs();
         // No TLS provider set -- HTTPS should produce an error page.
         bw.navigate_vfs("https://example.com/page", &vfs);
-        assert_eq!(bw.state, LoadingState::Idle);
+        assert_eq!(bw.tab().state, LoadingState::Idle);
         // The HTTPS error page should be rendered as HTML in the DOM.
-        let doc = bw.document.as_ref().expect("document should be loaded");
+        let doc = bw
+            .tab()
+            .document
+            .as_ref()
+            .expect("document should be loaded");
         let text = doc.text_content(doc.root);
         assert!(
             text.contains("HTTPS Required"),
@@ -2688,12 +4719,1220 @@ mod tests {
         let vfs = test_vfs();
         // No TLS provider -- Gemini should show a TLS Required page.
         bw.navigate_vfs("gemini://example.com/page", &vfs);
-        assert_eq!(bw.state, LoadingState::Idle);
-        let doc = bw.document.as_ref().expect("document should be loaded");
+        assert_eq!(bw.tab().state, LoadingState::Idle);
+        let doc = bw
+            .tab()
+            .document
+            .as_ref()
+            .expect("document should be loaded");
         let text = doc.text_content(doc.root);
         assert!(
             text.contains("TLS Required"),
             "expected 'TLS Required' in page text, got: {text}",
         );
     }
+
+    /// A pass-through TLS provider that reports a fixed certificate.
+    struct PinnedTlsProvider(&'static str);
+
+    impl oasis_net::tls::TlsProvider for PinnedTlsProvider {
+        fn connect_tls(
+            &self,
+            stream: Box<dyn oasis_types::backend::NetworkStream>,
+            _server_name: &str,
+        ) -> Result<Box<dyn oasis_types::backend::NetworkStream>> {
+            Ok(stream)
+        }
+
+        fn connect_tls_peer(
+            &self,
+            stream: Box<dyn oasis_types::backend::NetworkStream>,
+            _server_name: &str,
+        ) -> Result<(
+            Box<dyn oasis_types::backend::NetworkStream>,
+            Option<oasis_types::tls::PeerCertificate>,
+        )> {
+            let cert = oasis_types::tls::PeerCertificate {
+                fingerprint: self.0.to_string(),
+                not_after: None,
+            };
+            Ok((stream, Some(cert)))
+        }
+    }
+
+    #[test]
+    fn changed_gemini_certificate_can_be_aborted_or_accepted() {
+        use std::io::{Read, Write};
+
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let server = std::thread::spawn(move || {
+            for _ in 0..4 {
+                let (mut stream, _) = listener.accept().unwrap();
+                let mut buf = [0u8; 1024];
+                let _ = stream.read(&mut buf);
+                let _ = stream.write_all(b"20 text/gemini\r\n# Capsule");
+            }
+        });
+        let url = format!("gemini://127.0.0.1:{port}/");
+        let host = format!("127.0.0.1:{port}");
+        let accept = format!("about:tofu-accept?host={host}");
+
+        let mut vfs = test_vfs();
+        let mut bw = make_browser();
+        bw.navigate_vfs("vfs://sites/home/index.html", &vfs);
+        bw.set_tls_provider(Box::new(PinnedTlsProvider("aa")));
+        bw.navigate_to(&url, &vfs);
+        assert_eq!(bw.navigation().current_title(), Some("Capsule"));
+
+        // A changed certificate shows the warning; Abort goes back.
+        bw.set_tls_provider(Box::new(PinnedTlsProvider("bb")));
+        bw.navigate_to("vfs://sites/home/index.html", &vfs);
+        bw.navigate_to(&url, &vfs);
+        assert_eq!(bw.navigation().current_title(), Some("Certificate changed"));
+        bw.navigate_to(&format!("about:tofu-abort?host={host}"), &vfs);
+        assert_eq!(bw.navigation().current_title(), Some("Home"));
+        bw.navigate_to(&accept, &vfs);
+        assert_eq!(
+            bw.status_message.as_deref(),
+            Some(format!("No certificate change pending for {host}").as_str())
+        );
+
+        // Accept pins the new certificate and reloads in place.
+        bw.navigate_to(&url, &vfs);
+        let depth = bw.navigation().history_entries().len();
+        bw.navigate_to(&accept, &vfs);
+        assert_eq!(bw.navigation().current_title(), Some("Capsule"));
+        assert_eq!(bw.navigation().history_entries().len(), depth);
+        assert_eq!(bw.known_hosts().get(&host).unwrap().fingerprint, "bb");
+
+        // Input handling writes the pins through.
+        bw.handle_input(&InputEvent::TriggerRelease(Trigger::Left), &mut vfs);
+        let saved = vfs.read("/home/.browser/gemini_known_hosts").unwrap();
+        assert!(
+            String::from_utf8(saved)
+                .unwrap()
+                .contains(&format!("{host}\tbb\t-"))
+        );
+        let _ = server.join();
+    }
+
+    #[test]
+    fn gemini_input_prompt_masks_and_requeries() {
+        let mut vfs = test_vfs();
+        let mut bw = make_browser();
+        bw.navigate_vfs("vfs://sites/home/index.html", &vfs);
+        bw.process_response(ResourceResponse {
+            url: "gemini://gem.example/login?old".to_string(),
+            content_type: ContentType::GeminiInput { sensitive: true },
+            body: b"Password".to_vec(),
+            status: 200,
+            headers: Vec::new(),
+            charset: None,
+        });
+        // The prompt page asks the question; the answer goes in the bar.
+        assert_eq!(bw.focus, Focus::GeminiInput);
+        assert_eq!(bw.current_url(), Some("gemini://gem.example/login?old"));
+        assert_eq!(bw.title(), Some("Password"));
+
+        for ch in "a b".chars() {
+            bw.handle_input(&InputEvent::TextInput(ch), &mut vfs);
+        }
+        let mut backend = MockBackend::new();
+        bw.paint(&mut backend).unwrap();
+        assert!(backend.has_text("> ***"));
+        assert!(backend.has_text("Password"));
+        assert!(!backend.has_text("a b"));
+
+        bw.handle_input(&InputEvent::ButtonPress(Button::Confirm), &mut vfs);
+        assert_eq!(bw.focus, Focus::Content);
+        assert!(bw.gemini_input().is_none());
+        assert_eq!(bw.current_url(), Some("gemini://gem.example/login?a%20b"));
+
+        // Cancel goes back without requesting anything.
+        bw.load_gemini_input("", false, "gemini://gem.example/search");
+        assert_eq!(bw.gemini_input().unwrap().prompt, "Input");
+        assert_eq!(bw.current_url(), Some("gemini://gem.example/search"));
+        bw.handle_input(&InputEvent::ButtonPress(Button::Cancel), &mut vfs);
+        assert!(bw.gemini_input().is_none());
+        assert_eq!(bw.current_url(), Some("gemini://gem.example/login?a%20b"));
+    }
+
+    // ---------------------------------------------------------------
+    // Find in page
+    // ---------------------------------------------------------------
+
+    fn find_vfs() -> MemoryVfs {
+        let mut vfs = MemoryVfs::new();
+        vfs.mkdir("/sites").unwrap();
+        vfs.write(
+            "/sites/find.html",
+            b"<html><body><p>Apple pie</p><p>apple tart</p>\
+              <p>Banana APPLE</p></body></html>",
+        )
+        .unwrap();
+        vfs
+    }
+
+    fn type_text(bw: &mut BrowserWidget, text: &str, vfs: &mut dyn Vfs) {
+        for ch in text.chars() {
+            bw.handle_input(&InputEvent::TextInput(ch), vfs);
+        }
+    }
+
+    #[test]
+    fn find_in_page_counts_case_insensitive_matches() {
+        let vfs = find_vfs();
+        let mut bw = make_browser();
+        bw.navigate_vfs("vfs://sites/find.html", &vfs);
+        bw.find_in_page("apple");
+        assert_eq!(bw.find_state().matches.len(), 3);
+        bw.find_in_page("banana apple");
+        assert_eq!(bw.find_state().matches.len(), 1);
+        bw.find_in_page("cherry");
+        assert!(bw.find_state().matches.is_empty());
+    }
+
+    #[test]
+    fn find_bar_confirm_cycles_matches() {
+        let mut vfs = find_vfs();
+        let mut bw = make_browser();
+        bw.navigate_vfs("vfs://sites/find.html", &vfs);
+        bw.handle_input(&InputEvent::ButtonPress(Button::Select), &mut vfs);
+        assert_eq!(bw.focus, Focus::FindBar);
+        type_text(&mut bw, "apple", &mut vfs);
+        assert_eq!(bw.find_state().current, 0);
+
+        bw.handle_input(&InputEvent::ButtonPress(Button::Confirm), &mut vfs);
+        assert_eq!(bw.find_state().current, 1);
+        bw.handle_input(&InputEvent::ButtonPress(Button::Confirm), &mut vfs);
+        bw.handle_input(&InputEvent::ButtonPress(Button::Confirm), &mut vfs);
+        assert_eq!(bw.find_state().current, 0, "should wrap to first match");
+        bw.handle_input(&InputEvent::ButtonPress(Button::Up), &mut vfs);
+        assert_eq!(bw.find_state().current, 2);
+    }
+
+    #[test]
+    fn find_bar_cancel_clears_highlights() {
+        let mut vfs = find_vfs();
+        let mut bw = make_browser();
+        bw.navigate_vfs("vfs://sites/find.html", &vfs);
+        bw.open_find_bar();
+        type_text(&mut bw, "tart", &mut vfs);
+        assert_eq!(bw.find_state().matches.len(), 1);
+
+        bw.handle_input(&InputEvent::ButtonPress(Button::Cancel), &mut vfs);
+        assert_eq!(bw.focus, Focus::Content);
+        assert!(bw.find_state().query.is_empty());
+        assert!(bw.find_state().matches.is_empty());
+        // Cancel should not navigate back while leaving the find bar.
+        assert_eq!(bw.current_url(), Some("vfs://sites/find.html"));
+    }
+
+    #[test]
+    fn find_bar_paints_status_and_highlight() {
+        let mut vfs = find_vfs();
+        let mut bw = make_browser();
+        bw.set_window(0, 0, 480, 272);
+        bw.navigate_vfs("vfs://sites/find.html", &vfs);
+        bw.open_find_bar();
+        type_text(&mut bw, "apple", &mut vfs);
+        bw.handle_input(&InputEvent::ButtonPress(Button::Confirm), &mut vfs);
+
+        let mut backend = MockBackend::new();
+        bw.paint(&mut backend).unwrap();
+        assert!(backend.has_text("2/3 matches"));
+        assert!(backend.has_text("Find: apple"));
+        let current = Color::rgb(255, 150, 50);
+        assert!(
+            backend
+                .calls
+                .iter()
+                .any(|c| matches!(c, DrawCall::FillRect { color, .. } if *color == current))
+        );
+    }
+
+    #[test]
+    fn find_state_resets_on_navigation() {
+        let mut vfs = test_vfs();
+        let mut bw = make_browser();
+        bw.navigate_vfs("vfs://sites/home/index.html", &vfs);
+        bw.open_find_bar();
+        type_text(&mut bw, "hello", &mut vfs);
+        assert_eq!(bw.find_state().matches.len(), 1);
+        bw.navigate_vfs("vfs://sites/home/page2.html", &vfs);
+        assert!(bw.find_state().matches.is_empty());
+        assert_eq!(bw.focus, Focus::Content);
+    }
+
+    // ---------------------------------------------------------------
+    // Downloads
+    // ---------------------------------------------------------------
+
+    fn download_vfs() -> MemoryVfs {
+        let mut vfs = test_vfs();
+        vfs.mkdir("/sites/files").unwrap();
+        let mut zip = vec![b'P', b'K', 3, 4, 0, 0];
+        zip.resize(2048, 0);
+        vfs.write("/sites/files/photo.zip", &zip).unwrap();
+        vfs
+    }
+
+    #[test]
+    fn binary_content_prompts_for_download() {
+        let mut vfs = download_vfs();
+        let mut bw = make_browser();
+        bw.set_window(0, 0, 480, 272);
+        bw.navigate_vfs("vfs://sites/home/index.html", &vfs);
+        bw.navigate_vfs("vfs://sites/files/photo.zip", &vfs);
+
+        assert!(bw.pending_download().is_some());
+        // The previous page stays loaded under the prompt.
+        assert_eq!(bw.current_url(), Some("vfs://sites/home/index.html"));
+
+        let mut backend = MockBackend::new();
+        bw.paint(&mut backend).unwrap();
+        assert!(backend.has_text("Save photo.zip (2 KB)?"));
+        assert!(backend.has_text("Confirm/Cancel"));
+
+        // Other input is ignored while the prompt is up.
+        assert!(!bw.handle_input(&InputEvent::ButtonPress(Button::Down), &mut vfs));
+    }
+
+    #[test]
+    fn confirm_saves_download_with_unique_name() {
+        let mut vfs = download_vfs();
+        let mut bw = make_browser();
+        bw.set_window(0, 0, 480, 272);
+
+        bw.navigate_vfs("vfs://sites/files/photo.zip", &vfs);
+        bw.handle_input(&InputEvent::ButtonPress(Button::Confirm), &mut vfs);
+        assert!(bw.pending_download().is_none());
+        assert_eq!(vfs.read("/downloads/photo.zip").unwrap().len(), 2048);
+
+        let mut backend = MockBackend::new();
+        bw.paint(&mut backend).unwrap();
+        assert!(backend.has_text("Saved to /downloads/photo.zip"));
+        assert_eq!(
+            bw.take_saved_download().as_deref(),
+            Some("/downloads/photo.zip")
+        );
+        assert_eq!(bw.take_saved_download(), None);
+
+        bw.navigate_vfs("vfs://sites/files/photo.zip", &vfs);
+        bw.handle_input(&InputEvent::ButtonPress(Button::Confirm), &mut vfs);
+        assert!(vfs.exists("/downloads/photo (1).zip"));
+    }
+
+    #[test]
+    fn cancel_discards_download() {
+        let mut vfs = download_vfs();
+        let mut bw = make_browser();
+        bw.navigate_vfs("vfs://sites/files/photo.zip", &vfs);
+        bw.handle_input(&InputEvent::ButtonPress(Button::Cancel), &mut vfs);
+        assert!(bw.pending_download().is_none());
+        assert!(!vfs.exists("/downloads/photo.zip"));
+    }
+
+    // ---------------------------------------------------------------
+    // Forms
+    // ---------------------------------------------------------------
+
+    fn form_vfs(method: &str) -> MemoryVfs {
+        let mut vfs = test_vfs();
+        let page = format!(
+            "<html><body><a href=\"index.html\">Home</a>\
+             <form action=\"results.html\" method=\"{method}\">\
+             <input name=\"q\" value=\"hi\">\
+             <button name=\"go\" value=\"1\">Search</button>\
+             </form></body></html>"
+        );
+        vfs.write("/sites/home/search.html", page.as_bytes())
+            .unwrap();
+        vfs.write(
+            "/sites/home/results.html",
+            b"<html><body><p>Results</p></body></html>",
+        )
+        .unwrap();
+        vfs
+    }
+
+    fn load_form_page(vfs: &MemoryVfs) -> (BrowserWidget, MockBackend) {
+        let mut bw = make_browser();
+        bw.set_window(0, 0, 480, 272);
+        bw.navigate_vfs("vfs://sites/home/search.html", vfs);
+        let mut backend = MockBackend::new();
+        bw.paint(&mut backend).unwrap();
+        (bw, backend)
+    }
+
+    #[test]
+    fn form_controls_render_and_join_link_cycle() {
+        let vfs = form_vfs("get");
+        let (mut bw, backend) = load_form_page(&vfs);
+
+        let kinds: Vec<_> = bw.tab().control_map.iter().map(|c| c.kind).collect();
+        assert_eq!(kinds, vec![ControlKind::TextInput, ControlKind::Button]);
+        assert!(backend.has_text("hi"));
+        assert!(backend.has_text("Search"));
+
+        // Left/Right walk the link, then the input, then the button.
+        assert_eq!(bw.tab().link_map.len(), 1);
+        for expected in [0, 1, 2, 0] {
+            bw.select_next_link();
+            assert_eq!(bw.tab().selected_link, expected);
+        }
+        bw.select_prev_link();
+        assert_eq!(bw.tab().selected_link, 2);
+    }
+
+    #[test]
+    fn typing_updates_form_field() {
+        let mut vfs = form_vfs("get");
+        let (mut bw, _) = load_form_page(&vfs);
+        bw.select_next_link();
+        bw.select_next_link();
+        bw.handle_input(&InputEvent::ButtonPress(Button::Confirm), &mut vfs);
+        let input = bw.tab().control_map[0].node;
+        assert_eq!(bw.focus, Focus::FormField(input));
+
+        bw.handle_input(&InputEvent::TextInput('!'), &mut vfs);
+        assert_eq!(
+            bw.form_values().get(&input).map(String::as_str),
+            Some("hi!")
+        );
+
+        let mut backend = MockBackend::new();
+        bw.paint(&mut backend).unwrap();
+        assert!(backend.has_text("hi!"));
+
+        bw.handle_input(&InputEvent::ButtonPress(Button::Cancel), &mut vfs);
+        assert_eq!(bw.focus, Focus::Content);
+        assert_eq!(bw.current_url(), Some("vfs://sites/home/search.html"));
+    }
+
+    #[test]
+    fn confirm_in_field_submits_get_form() {
+        let mut vfs = form_vfs("get");
+        let (mut bw, _) = load_form_page(&vfs);
+        let input = bw.tab().control_map[0].node;
+        bw.focus_form_field(input);
+        for ch in " there".chars() {
+            bw.handle_input(&InputEvent::TextInput(ch), &mut vfs);
+        }
+        bw.handle_input(&InputEvent::ButtonPress(Button::Confirm), &mut vfs);
+        assert_eq!(
+            bw.current_url(),
+            Some("vfs://sites/home/results.html?q=hi+there")
+        );
+        assert!(bw.form_values().is_empty());
+    }
+
+    #[test]
+    fn clicking_button_submits_with_submitter() {
+        let mut vfs = form_vfs("get");
+        let (mut bw, _) = load_form_page(&vfs);
+        let r = bw.tab().control_map[1].rect;
+        bw.handle_input(
+            &InputEvent::PointerClick {
+                x: r.x as i32 + 2,
+                y: r.y as i32 + 2,
+            },
+            &mut vfs,
+        );
+        assert_eq!(
+            bw.current_url(),
+            Some("vfs://sites/home/results.html?q=hi&go=1")
+        );
+    }
+
+    #[test]
+    fn post_form_is_not_submitted() {
+        let vfs = form_vfs("post");
+        let (mut bw, _) = load_form_page(&vfs);
+        let button = bw.tab().control_map[1].clone();
+        bw.activate_control(&button, &vfs);
+        assert_eq!(bw.current_url(), Some("vfs://sites/home/search.html"));
+
+        let mut backend = MockBackend::new();
+        bw.paint(&mut backend).unwrap();
+        assert!(backend.has_text("POST forms are not supported"));
+    }
+
+    // ---------------------------------------------------------------
+    // Bookmarks
+    // ---------------------------------------------------------------
+
+    #[test]
+    fn bookmarks_persist_across_widgets() {
+        let mut vfs = test_vfs();
+        let mut bw = make_browser();
+        bw.navigate_vfs("vfs://sites/home/index.html", &vfs);
+        bw.add_bookmark(&mut vfs);
+        assert!(vfs.exists("/home/.browser/bookmarks"));
+
+        let restored = BrowserWidget::with_vfs(BrowserConfig::default(), &vfs);
+        assert_eq!(restored.bookmarks().len(), 1);
+        assert_eq!(restored.bookmarks()[0].title, "Home");
+
+        bw.remove_bookmark("vfs://sites/home/index.html", &mut vfs);
+        let restored = BrowserWidget::with_vfs(BrowserConfig::default(), &vfs);
+        assert!(restored.bookmarks().is_empty());
+    }
+
+    #[test]
+    fn bookmarks_stay_in_memory_without_path() {
+        let mut vfs = test_vfs();
+        let config = BrowserConfig {
+            bookmarks_path: None,
+            ..BrowserConfig::default()
+        };
+        let mut bw = BrowserWidget::new(config);
+        bw.navigate_vfs("vfs://sites/home/index.html", &vfs);
+        bw.add_bookmark(&mut vfs);
+        assert_eq!(bw.bookmarks().len(), 1);
+        assert!(!vfs.exists("/home/.browser/bookmarks"));
+    }
+
+    /// Paint, then click the first link whose `href` satisfies `pick`.
+    fn click_link(bw: &mut BrowserWidget, vfs: &mut MemoryVfs, pick: impl Fn(&str) -> bool) {
+        bw.paint(&mut MockBackend::new()).unwrap();
+        let rect = bw
+            .tab()
+            .link_map
+            .iter()
+            .find(|l| pick(&l.href))
+            .expect("link should be painted")
+            .rect;
+        let x = (rect.x + rect.width / 2.0) as i32;
+        let y = (rect.y + rect.height / 2.0) as i32;
+        bw.handle_input(&InputEvent::PointerClick { x, y }, vfs);
+    }
+
+    #[test]
+    fn bookmarks_page_lists_opens_and_deletes_bookmarks() {
+        let mut vfs = test_vfs();
+        let mut bw = BrowserWidget::with_vfs(BrowserConfig::default(), &vfs);
+        bw.set_window(0, 0, 480, 272);
+        let chord = |bw: &mut BrowserWidget, vfs: &mut MemoryVfs, trigger: Trigger| {
+            bw.handle_input(&InputEvent::TriggerPress(trigger), vfs);
+            bw.handle_input(&InputEvent::ButtonPress(Button::Triangle), vfs);
+            bw.handle_input(&InputEvent::TriggerRelease(trigger), vfs);
+        };
+
+        // R + Triangle bookmarks the current page.
+        bw.navigate_vfs("vfs://sites/home/index.html", &vfs);
+        chord(&mut bw, &mut vfs, Trigger::Right);
+        assert_eq!(bw.status_message.as_deref(), Some("Bookmarked: Home"));
+        bw.navigate_vfs("vfs://sites/home/page2.html", &vfs);
+        chord(&mut bw, &mut vfs, Trigger::Right);
+        assert_eq!(bw.bookmarks().len(), 2);
+
+        // L + Triangle opens the listing.
+        chord(&mut bw, &mut vfs, Trigger::Left);
+        assert_eq!(bw.current_url(), Some("about:bookmarks"));
+        assert_eq!(bw.navigation().current_title(), Some("Bookmarks"));
+        let mut backend = MockBackend::new();
+        bw.paint(&mut backend).unwrap();
+        assert!(backend.has_text("Home"));
+        assert!(backend.has_text("page2.html"));
+
+        // Following a bookmark, then coming back, rebuilds the listing.
+        click_link(&mut bw, &mut vfs, |href| {
+            href == "vfs://sites/home/index.html"
+        });
+        assert_eq!(bw.current_url(), Some("vfs://sites/home/index.html"));
+        bw.handle_input(&InputEvent::ButtonPress(Button::Cancel), &mut vfs);
+        assert_eq!(bw.current_url(), Some("about:bookmarks"));
+
+        // Deleting re-renders in place and writes through.
+        let depth = bw.navigation().history_entries().len();
+        click_link(&mut bw, &mut vfs, |href| {
+            href.starts_with("about:bookmark-remove") && href.contains("index.html")
+        });
+        assert_eq!(bw.current_url(), Some("about:bookmarks"));
+        assert_eq!(bw.navigation().history_entries().len(), depth);
+        let mut backend = MockBackend::new();
+        bw.paint(&mut backend).unwrap();
+        assert!(!backend.has_text("Home"));
+        assert!(backend.has_text("page2.html"));
+
+        let restored = BrowserWidget::with_vfs(BrowserConfig::default(), &vfs);
+        let urls: Vec<&str> = restored
+            .bookmarks()
+            .iter()
+            .map(|b| b.url.as_str())
+            .collect();
+        assert_eq!(urls, ["vfs://sites/home/page2.html"]);
+    }
+
+    #[test]
+    fn history_page_persists_visits_and_clears() {
+        let mut vfs = test_vfs();
+        let mut bw = BrowserWidget::with_vfs(BrowserConfig::default(), &vfs);
+        bw.set_window(0, 0, 480, 272);
+        bw.navigate_vfs("vfs://sites/home/index.html", &vfs);
+        bw.navigate_vfs("vfs://sites/home/page2.html", &vfs);
+        bw.navigate_vfs("vfs://sites/home/index.html", &vfs);
+        bw.go_back(&vfs);
+
+        // Going back is not a new visit; the page counts two.
+        let visits = bw.visits();
+        assert_eq!(visits.len(), 2);
+        assert_eq!(visits[1].url, "vfs://sites/home/index.html");
+        assert_eq!((visits[1].title.as_str(), visits[1].visits), ("Home", 2));
+
+        // Input flushes the history, so it survives a restart.
+        bw.handle_input(&InputEvent::ButtonPress(Button::Down), &mut vfs);
+        let mut restored = BrowserWidget::with_vfs(BrowserConfig::default(), &vfs);
+        assert_eq!(restored.visits(), bw.visits());
+
+        restored.set_window(0, 0, 480, 272);
+        restored.navigate_to("about:history", &vfs);
+        assert_eq!(restored.current_url(), Some("about:history"));
+        assert_eq!(restored.visits().len(), 2);
+        let mut backend = MockBackend::new();
+        restored.paint(&mut backend).unwrap();
+        assert!(backend.has_text("page2.html"));
+        assert!(backend.has_text("visits"));
+
+        let depth = restored.navigation().history_entries().len();
+        click_link(&mut restored, &mut vfs, |href| {
+            href == "about:history-clear"
+        });
+        assert_eq!(restored.current_url(), Some("about:history"));
+        assert_eq!(restored.navigation().history_entries().len(), depth);
+        assert!(restored.visits().is_empty());
+        let reloaded = BrowserWidget::with_vfs(BrowserConfig::default(), &vfs);
+        assert!(reloaded.visits().is_empty());
+    }
+
+    #[test]
+    fn tabs_share_one_history_written_through_on_persist() {
+        let mut vfs = test_vfs();
+        let mut bw = BrowserWidget::with_vfs(BrowserConfig::default(), &vfs);
+        bw.set_window(0, 0, 480, 272);
+        bw.navigate_vfs("vfs://sites/home/index.html", &vfs);
+        bw.new_tab("vfs://sites/home/page2.html", &vfs);
+        bw.switch_tab(0);
+        let urls: Vec<&str> = bw.visits().iter().map(|v| v.url.as_str()).collect();
+        assert_eq!(
+            urls,
+            ["vfs://sites/home/index.html", "vfs://sites/home/page2.html"]
+        );
+
+        // Navigation without input is saved once the host persists.
+        assert!(!vfs.exists("/home/.browser/history"));
+        bw.persist(&mut vfs);
+        let restored = BrowserWidget::with_vfs(BrowserConfig::default(), &vfs);
+        assert_eq!(restored.visits(), bw.visits());
+    }
+
+    #[test]
+    fn history_is_capped_and_kept_in_memory_without_path() {
+        let mut vfs = test_vfs();
+        let config = BrowserConfig {
+            history_path: None,
+            max_history_entries: 2,
+            ..BrowserConfig::default()
+        };
+        let mut bw = BrowserWidget::new(config);
+        bw.navigate_vfs("vfs://sites/home/index.html", &vfs);
+        bw.navigate_vfs("vfs://sites/home/page2.html", &vfs);
+        bw.navigate_vfs("vfs://sites/home/article.html", &vfs);
+        bw.handle_input(&InputEvent::ButtonPress(Button::Down), &mut vfs);
+        let urls: Vec<&str> = bw.visits().iter().map(|v| v.url.as_str()).collect();
+        assert_eq!(
+            urls,
+            [
+                "vfs://sites/home/page2.html",
+                "vfs://sites/home/article.html"
+            ]
+        );
+        assert!(!vfs.exists("/home/.browser/history"));
+    }
+
+    // ---------------------------------------------------------------
+    // Inline images
+    // ---------------------------------------------------------------
+
+    #[test]
+    fn inline_images_load_paint_and_release_textures() {
+        let mut vfs = test_vfs();
+        vfs.write("/sites/home/pic.bmp", &test_utils::bmp_image(40, 20))
+            .unwrap();
+        vfs.write(
+            "/sites/home/gallery.html",
+            b"<html><body><p>Look <img src=\"pic.bmp\" alt=\"Pic\"> \
+              <img src=\"pic.bmp\" width=\"80\"> \
+              <img src=\"missing.bmp\" alt=\"Gone\"></p></body></html>",
+        )
+        .unwrap();
+        let mut bw = make_browser();
+        bw.set_window(0, 0, 480, 272);
+        bw.navigate_vfs("vfs://sites/home/gallery.html", &vfs);
+        assert!(bw.cache.contains("vfs://sites/home/pic.bmp"));
+
+        // The image is uploaded once and blitted at both display sizes.
+        let mut backend = MockBackend::new();
+        bw.paint(&mut backend).unwrap();
+        let loads: Vec<(u32, u32)> = backend
+            .calls
+            .iter()
+            .filter_map(|c| match c {
+                DrawCall::LoadTexture { w, h, .. } => Some((*w, *h)),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(loads, [(40, 20)]);
+        let blits: Vec<(u32, u32)> = backend
+            .calls
+            .iter()
+            .filter_map(|c| match c {
+                DrawCall::Blit { w, h, .. } => Some((*w, *h)),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(blits, [(40, 20), (80, 40)]);
+        assert!(backend.has_text("Gone"), "broken image shows its alt text");
+        assert!(!backend.has_text("Pic"));
+
+        let mut backend = MockBackend::new();
+        bw.paint(&mut backend).unwrap();
+        assert!(
+            !backend
+                .calls
+                .iter()
+                .any(|c| matches!(c, DrawCall::LoadTexture { .. }))
+        );
+
+        // Dropping the image from the cache destroys its texture, and
+        // the page falls back to placeholders.
+        bw.cache.clear();
+        let mut backend = MockBackend::new();
+        bw.paint(&mut backend).unwrap();
+        assert!(matches!(
+            backend.calls[0],
+            DrawCall::DestroyTexture { tex: TextureId(0) }
+        ));
+        assert!(backend.has_text("Pic"));
+        assert!(
+            !backend
+                .calls
+                .iter()
+                .any(|c| matches!(c, DrawCall::Blit { .. }))
+        );
+    }
+
+    #[test]
+    fn png_images_render_scaled_or_as_placeholders() {
+        let mut vfs = test_vfs();
+        let png = test_utils::png_image(30, 10);
+        vfs.write("/sites/home/pic.png", &png).unwrap();
+        vfs.write("/sites/home/cut.png", &png[..png.len() - 20])
+            .unwrap();
+        vfs.write(
+            "/sites/home/gallery.html",
+            b"<html><body><p><img src=\"pic.png\" width=\"60\"> \
+              <img src=\"cut.png\" alt=\"Cut\"></p></body></html>",
+        )
+        .unwrap();
+        let mut bw = make_browser();
+        bw.set_window(0, 0, 480, 272);
+        bw.navigate_vfs("vfs://sites/home/gallery.html", &vfs);
+
+        let mut backend = MockBackend::new();
+        bw.paint(&mut backend).unwrap();
+        let loads: Vec<(u32, u32)> = backend
+            .calls
+            .iter()
+            .filter_map(|c| match c {
+                DrawCall::LoadTexture { w, h, .. } => Some((*w, *h)),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(loads, [(30, 10)]);
+        let blits: Vec<(u32, u32)> = backend
+            .calls
+            .iter()
+            .filter_map(|c| match c {
+                DrawCall::Blit { w, h, .. } => Some((*w, *h)),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(blits, [(60, 20)]);
+        assert!(backend.has_text("Cut"), "corrupt PNG shows its placeholder");
+    }
+
+    // ---------------------------------------------------------------
+    // Page cache
+    // ---------------------------------------------------------------
+
+    fn long_page_vfs() -> MemoryVfs {
+        let mut vfs = test_vfs();
+        let mut body = String::from("<html><body>");
+        for i in 0..80 {
+            body.push_str(&format!("<p>Paragraph {i}</p>"));
+        }
+        body.push_str("<a href=\"page2.html\">Next</a></body></html>");
+        vfs.write("/sites/home/long.html", body.as_bytes()).unwrap();
+        vfs
+    }
+
+    #[test]
+    fn back_restores_cached_page_without_reload() {
+        let mut vfs = long_page_vfs();
+        let mut bw = make_browser();
+        bw.set_window(0, 0, 480, 272);
+        bw.navigate_vfs("vfs://sites/home/long.html", &vfs);
+        bw.paint(&mut MockBackend::new()).unwrap();
+        bw.scroll_mut().scroll_to(300);
+        bw.navigate_to("page2.html", &vfs);
+        assert!(bw.page_cache.contains("vfs://sites/home/long.html"));
+
+        // Remove the file: a reload would now produce an error page.
+        vfs.remove("/sites/home/long.html").unwrap();
+        bw.go_back(&vfs);
+        assert_eq!(bw.current_url(), Some("vfs://sites/home/long.html"));
+        assert_eq!(bw.loading_state(), LoadingState::Idle);
+        assert_eq!(bw.scroll().scroll_y, 300);
+
+        let mut backend = MockBackend::new();
+        bw.paint(&mut backend).unwrap();
+        assert!(backend.has_text("Paragraph"));
+
+        // Forward is still available because history was not rewritten.
+        assert!(bw.navigation().can_go_forward());
+        bw.go_forward(&vfs);
+        assert_eq!(bw.current_url(), Some("vfs://sites/home/page2.html"));
+    }
+
+    #[test]
+    fn cache_hits_count_only_responses_served_from_the_cache() {
+        let mut vfs = test_vfs();
+        vfs.write("/sites/home/pic.bmp", &test_utils::bmp_image(4, 4))
+            .unwrap();
+        vfs.write(
+            "/sites/home/gallery.html",
+            b"<html><body><img src=\"pic.bmp\"></body></html>",
+        )
+        .unwrap();
+        let mut bw = make_browser();
+        // The page is fetched both times; only the image comes from the
+        // cache on the second visit.
+        bw.navigate_vfs("vfs://sites/home/gallery.html", &vfs);
+        bw.navigate_vfs("vfs://sites/home/gallery.html", &vfs);
+        let stats = bw.cache_stats();
+        assert_eq!((stats.hits, stats.misses, stats.entries), (1, 1, 2));
+
+        bw.clear_cache();
+        let stats = bw.cache_stats();
+        assert_eq!((stats.entries, stats.size_bytes), (0, 0));
+        assert_eq!(stats.hits, 1);
+    }
+
+    #[test]
+    fn evicted_page_is_reloaded() {
+        let vfs = test_vfs();
+        let config = BrowserConfig {
+            page_cache_entries: 1,
+            ..BrowserConfig::default()
+        };
+        let mut bw = BrowserWidget::new(config);
+        bw.navigate_vfs("vfs://sites/home/index.html", &vfs);
+        bw.navigate_vfs("vfs://sites/home/page2.html", &vfs);
+        assert!(!bw.page_cache.contains("vfs://sites/home/index.html"));
+
+        bw.go_back(&vfs);
+        assert_eq!(bw.current_url(), Some("vfs://sites/home/index.html"));
+        assert_eq!(bw.title(), Some("Home"));
+        // Reloading an entry does not push a new one.
+        assert_eq!(bw.navigation().history_entries().len(), 2);
+        assert!(bw.navigation().can_go_forward());
+    }
+
+    #[test]
+    fn reader_toggle_invalidates_cached_page() {
+        let vfs = test_vfs();
+        let mut bw = make_browser();
+        bw.navigate_vfs("vfs://sites/home/article.html", &vfs);
+        assert!(bw.page_cache.contains("vfs://sites/home/article.html"));
+        bw.toggle_reader_mode();
+        assert!(bw.is_reader_mode());
+        assert!(!bw.page_cache.contains("vfs://sites/home/article.html"));
+    }
+
+    #[test]
+    fn fragments_scroll_to_their_anchor() {
+        let mut vfs = test_vfs();
+        let filler = "<p>Filler paragraph.</p>".repeat(40);
+        let page = format!(
+            "<html><body><h1 id=\"top\">Top</h1>{filler}\
+             <h2 id=\"middle\">Middle</h2>{filler}\
+             <p><a name=\"end\">End</a></p>{filler}</body></html>"
+        );
+        vfs.write("/sites/home/long.html", page.as_bytes()).unwrap();
+        let mut bw = make_browser();
+        bw.set_window(0, 0, 480, 272);
+        let anchor = |bw: &BrowserWidget, name: &str| {
+            let tab = bw.tab();
+            let positions = anchor::anchor_positions(
+                tab.document.as_ref().unwrap(),
+                tab.layout_root.as_ref().unwrap(),
+            );
+            positions[name] as i32
+        };
+
+        // Loading a page with a fragment lands on the anchor.
+        bw.navigate_vfs("vfs://sites/home/long.html#middle", &vfs);
+        assert_eq!(bw.current_url(), Some("vfs://sites/home/long.html"));
+        let middle = anchor(&bw, "middle");
+        assert!(middle > 0);
+        assert_eq!(bw.tab().scroll.scroll_y, middle);
+
+        // A fragment of the same page scrolls without a reload.
+        bw.tab_mut().error_message = Some("not reloaded".to_string());
+        let entries = bw.navigation().history_entries().len();
+        bw.navigate_to("#end", &vfs);
+        assert_eq!(bw.tab().scroll.scroll_y, anchor(&bw, "end"));
+        assert!(bw.tab().scroll.scroll_y > middle);
+        assert_eq!(bw.tab().error_message.as_deref(), Some("not reloaded"));
+        assert_eq!(bw.navigation().history_entries().len(), entries);
+
+        // An unknown anchor goes to the top without an error.
+        bw.navigate_to("#missing", &vfs);
+        assert_eq!(bw.tab().scroll.scroll_y, 0);
+        assert_eq!(bw.loading_state(), LoadingState::Idle);
+    }
+
+    #[test]
+    fn reader_font_size_survives_toggling() {
+        let mut vfs = test_vfs();
+        let mut bw = make_browser();
+        bw.set_window(0, 0, 480, 272);
+        bw.navigate_vfs("vfs://sites/home/article.html", &vfs);
+        let largest_font = |bw: &mut BrowserWidget| {
+            let mut backend = MockBackend::new();
+            bw.paint(&mut backend).unwrap();
+            let content_top = bw.content_top();
+            let status_y = 272 - bw.config.status_bar_height as i32;
+            backend
+                .text_positions()
+                .into_iter()
+                .filter(|(_, _, y, _)| *y >= content_top && *y < status_y)
+                .map(|(_, _, _, fs)| fs)
+                .max()
+                .expect("page text should be drawn")
+        };
+
+        bw.toggle_reader_mode();
+        let normal = largest_font(&mut bw);
+        let page_height = |bw: &BrowserWidget| {
+            bw.tab()
+                .layout_root
+                .as_ref()
+                .unwrap()
+                .dimensions
+                .margin_box()
+                .height
+        };
+        let height = page_height(&bw);
+
+        // Trigger+Up sizes the reader text, not the page zoom.
+        bw.handle_input(&InputEvent::TriggerPress(Trigger::Right), &mut vfs);
+        bw.handle_input(&InputEvent::ButtonPress(Button::Up), &mut vfs);
+        bw.handle_input(&InputEvent::TriggerRelease(Trigger::Right), &mut vfs);
+        bw.reader_increase_font();
+        assert_eq!(bw.config.text_scale, 1.0);
+        assert_eq!(bw.config.reader_text_scale, 1.25);
+        let larger = largest_font(&mut bw);
+        assert!(larger > normal, "{larger} > {normal}");
+        assert!(page_height(&bw) > height, "lines grow too");
+
+        let mut backend = MockBackend::new();
+        bw.paint(&mut backend).unwrap();
+        assert!(backend.has_text("Reader text 125%"));
+
+        // Off shows the page at its own size; on again keeps the choice.
+        bw.toggle_reader_mode();
+        assert!(!bw.is_reader_mode());
+        assert_eq!(bw.config.reader_text_scale, 1.25);
+        bw.toggle_reader_mode();
+        assert_eq!(largest_font(&mut bw), larger);
+
+        for _ in 0..20 {
+            bw.reader_decrease_font();
+        }
+        assert_eq!(bw.config.reader_text_scale, config::MIN_READER_TEXT_SCALE);
+    }
+
+    // ---------------------------------------------------------------
+    // History list
+    // ---------------------------------------------------------------
+
+    #[test]
+    fn history_list_opens_from_url_bar_and_jumps_to_entry() {
+        let mut vfs = test_vfs();
+        let mut bw = make_browser();
+        bw.set_window(0, 0, 480, 272);
+        bw.navigate_vfs("vfs://sites/home/index.html", &vfs);
+        bw.navigate_vfs("vfs://sites/home/page2.html", &vfs);
+        bw.navigate_vfs("vfs://sites/home/page2.html", &vfs);
+        bw.navigate_vfs("vfs://sites/home/article.html", &vfs);
+
+        bw.navigate_to("about:session", &vfs);
+        assert_eq!(bw.focus, Focus::History);
+        assert_eq!(bw.current_url(), Some("vfs://sites/home/article.html"));
+        let rows = &bw.history_view().rows;
+        assert_eq!(rows.len(), 3, "repeated page2 collapses into one row");
+        assert_eq!(bw.history_view().selected, 0);
+
+        let mut backend = MockBackend::new();
+        bw.paint(&mut backend).unwrap();
+        assert!(backend.has_text("vfs://sites/home/index.html"));
+        assert!(backend.has_text("* "));
+
+        // Up wraps to the oldest entry; Confirm goes there.
+        bw.handle_input(&InputEvent::ButtonPress(Button::Up), &mut vfs);
+        bw.handle_input(&InputEvent::ButtonPress(Button::Confirm), &mut vfs);
+        assert_eq!(bw.focus, Focus::Content);
+        assert_eq!(bw.current_url(), Some("vfs://sites/home/index.html"));
+        assert_eq!(bw.navigation().history_entries().len(), 4);
+        assert!(bw.navigation().can_go_forward());
+    }
+
+    #[test]
+    fn history_list_click_and_cancel() {
+        let mut vfs = test_vfs();
+        let mut bw = make_browser();
+        bw.set_window(0, 0, 480, 272);
+        bw.navigate_vfs("vfs://sites/home/index.html", &vfs);
+        bw.navigate_vfs("vfs://sites/home/page2.html", &vfs);
+
+        bw.open_history();
+        bw.handle_input(&InputEvent::ButtonPress(Button::Cancel), &mut vfs);
+        assert_eq!(bw.focus, Focus::Content);
+        assert_eq!(bw.current_url(), Some("vfs://sites/home/page2.html"));
+
+        bw.open_history();
+        let second_row = bw.config.url_bar_height as i32 + history_view::ROW_HEIGHT as i32 + 4;
+        bw.handle_input(
+            &InputEvent::PointerClick {
+                x: 40,
+                y: second_row,
+            },
+            &mut vfs,
+        );
+        assert_eq!(bw.current_url(), Some("vfs://sites/home/index.html"));
+        assert_eq!(bw.focus, Focus::Content);
+    }
+
+    // ---------------------------------------------------------------
+    // Tabs
+    // ---------------------------------------------------------------
+
+    #[test]
+    fn tabs_keep_separate_history_and_honour_limit() {
+        let vfs = test_vfs();
+        let mut bw = make_browser();
+        bw.config.features.max_tabs = 2;
+        bw.set_window(0, 0, 480, 272);
+        bw.navigate_vfs("vfs://sites/home/index.html", &vfs);
+        let single_height = bw.tab().scroll.viewport_height;
+
+        assert!(bw.new_tab("vfs://sites/home/page2.html", &vfs));
+        assert_eq!(bw.active_tab(), 1);
+        assert_eq!(bw.current_url(), Some("vfs://sites/home/page2.html"));
+        assert!(!bw.tab().nav.can_go_back());
+        // The tab strip takes room from both tabs' content viewports.
+        let strip = bw.config.tab_bar_height as i32;
+        assert!(
+            bw.tabs()
+                .iter()
+                .all(|t| t.scroll.viewport_height == single_height - strip)
+        );
+
+        assert!(!bw.new_tab("vfs://sites/home/article.html", &vfs));
+        assert_eq!(bw.tabs().len(), 2);
+
+        bw.next_tab();
+        assert_eq!(bw.current_url(), Some("vfs://sites/home/index.html"));
+        assert_eq!(bw.tabs()[1].label(), "Page 2");
+
+        bw.close_tab(0);
+        assert_eq!(bw.tabs().len(), 1);
+        assert_eq!(bw.current_url(), Some("vfs://sites/home/page2.html"));
+        bw.close_tab(0);
+        assert_eq!(bw.tabs().len(), 1);
+        assert_eq!(bw.tab().scroll.viewport_height, single_height);
+    }
+
+    #[test]
+    fn tab_strip_click_and_trigger_square_cycle_tabs() {
+        let mut vfs = test_vfs();
+        let mut bw = make_browser();
+        bw.set_window(0, 0, 480, 272);
+        bw.navigate_vfs("vfs://sites/home/index.html", &vfs);
+        bw.new_tab("vfs://sites/home/page2.html", &vfs);
+
+        let mut backend = MockBackend::new();
+        bw.paint(&mut backend).unwrap();
+        assert!(backend.has_text("Home"));
+        assert!(backend.has_text("page2.html"));
+
+        let strip_y = bw.config.url_bar_height as i32 + 2;
+        bw.handle_input(&InputEvent::PointerClick { x: 10, y: strip_y }, &mut vfs);
+        assert_eq!(bw.active_tab(), 0);
+
+        bw.handle_input(&InputEvent::TriggerPress(Trigger::Right), &mut vfs);
+        bw.handle_input(&InputEvent::ButtonPress(Button::Square), &mut vfs);
+        assert_eq!(bw.active_tab(), 1);
+        bw.handle_input(&InputEvent::TriggerRelease(Trigger::Right), &mut vfs);
+
+        // Without a held trigger, Square still goes home.
+        bw.handle_input(&InputEvent::ButtonPress(Button::Square), &mut vfs);
+        assert_eq!(bw.active_tab(), 1);
+        assert_eq!(bw.current_url(), Some("vfs://sites/home/index.html"));
+    }
+
+    // ---------------------------------------------------------------
+    // Charset decoding
+    // ---------------------------------------------------------------
+
+    #[test]
+    fn latin1_page_renders_accented_text() {
+        let mut vfs = test_vfs();
+        vfs.write(
+            "/sites/home/latin1.html",
+            b"<html><head><meta charset=\"ISO-8859-1\"><title>Caf\xe9</title></head>\
+              <body><p>Cr\xe8me br\xfbl\xe9e</p></body></html>",
+        )
+        .unwrap();
+        let mut bw = make_browser();
+        bw.set_window(0, 0, 480, 272);
+        bw.navigate_vfs("vfs://sites/home/latin1.html", &vfs);
+        assert_eq!(bw.title(), Some("Café"));
+
+        let mut backend = MockBackend::new();
+        bw.paint(&mut backend).unwrap();
+        assert!(backend.has_text("Crème"));
+        assert!(backend.has_text("brûlée"));
+        assert!(!backend.has_text("\u{FFFD}"));
+    }
+
+    #[test]
+    fn response_charset_overrides_meta() {
+        let mut bw = make_browser();
+        let mut resp = loader::mock::response("http://example.com/q", 200, ContentType::Html, "");
+        resp.body = b"<meta charset=utf-8><p>\x93hi\x94</p>".to_vec();
+        resp.charset = Some("windows-1252".to_string());
+        bw.process_response(resp);
+        let doc = bw.tab().document.as_ref().unwrap();
+        assert!(doc.text_content(doc.root).contains("\u{201C}hi\u{201D}"));
+    }
+
+    #[test]
+    fn zoom_reflows_page_and_keeps_scroll_position() {
+        let mut vfs = long_page_vfs();
+        let mut bw = make_browser();
+        bw.set_window(0, 0, 480, 272);
+        bw.navigate_vfs("vfs://sites/home/long.html", &vfs);
+        let mut backend = MockBackend::new();
+        bw.paint(&mut backend).unwrap();
+        let height = bw.tab().scroll.max_scroll();
+
+        // Zoom with Trigger+Up from the middle of the page.
+        bw.handle_input(&InputEvent::TriggerPress(Trigger::Right), &mut vfs);
+        bw.tab_mut().scroll.scroll_to(height / 2);
+        bw.handle_input(&InputEvent::ButtonPress(Button::Up), &mut vfs);
+        bw.handle_input(&InputEvent::ButtonPress(Button::Up), &mut vfs);
+        bw.handle_input(&InputEvent::TriggerRelease(Trigger::Right), &mut vfs);
+        assert_eq!(bw.config.text_scale, 1.5);
+        assert!(bw.tab().scroll.max_scroll() > height);
+        let fraction = bw.tab().scroll.scroll_fraction();
+        assert!((fraction - 0.5).abs() < 0.01, "fraction {fraction}");
+
+        let mut backend = MockBackend::new();
+        bw.paint(&mut backend).unwrap();
+        assert!(backend.has_text("Zoom 150%"));
+        for _ in 0..STATUS_FLASH_FRAMES {
+            bw.paint(&mut MockBackend::new()).unwrap();
+        }
+        let mut backend = MockBackend::new();
+        bw.paint(&mut backend).unwrap();
+        assert!(!backend.has_text("Zoom"));
+
+        for _ in 0..20 {
+            bw.zoom_in();
+        }
+        assert_eq!(bw.config.text_scale, config::MAX_TEXT_SCALE);
+        bw.zoom_reset();
+        assert_eq!(bw.config.text_scale, 1.0);
+        bw.zoom_out();
+        assert_eq!(bw.config.text_scale, 0.75);
+    }
+
+    #[test]
+    fn trigger_pages_only_when_released_without_a_chord() {
+        let mut vfs = long_page_vfs();
+        let mut bw = make_browser();
+        bw.set_window(0, 0, 480, 272);
+        bw.navigate_vfs("vfs://sites/home/long.html", &vfs);
+        let mut backend = MockBackend::new();
+        bw.paint(&mut backend).unwrap();
+
+        // R + Triangle bookmarks the page without scrolling it.
+        bw.handle_input(&InputEvent::TriggerPress(Trigger::Right), &mut vfs);
+        assert_eq!(bw.tab().scroll.scroll_y, 0);
+        bw.handle_input(&InputEvent::ButtonPress(Button::Triangle), &mut vfs);
+        bw.handle_input(&InputEvent::TriggerRelease(Trigger::Right), &mut vfs);
+        assert_eq!(bw.bookmarks().len(), 1);
+        assert_eq!(bw.tab().scroll.scroll_y, 0);
+
+        // A bare R press and release pages down, a bare L pages back up.
+        bw.handle_input(&InputEvent::TriggerPress(Trigger::Right), &mut vfs);
+        bw.handle_input(&InputEvent::TriggerRelease(Trigger::Right), &mut vfs);
+        assert!(bw.tab().scroll.scroll_y > 0);
+        bw.handle_input(&InputEvent::TriggerPress(Trigger::Left), &mut vfs);
+        bw.handle_input(&InputEvent::TriggerRelease(Trigger::Left), &mut vfs);
+        assert_eq!(bw.tab().scroll.scroll_y, 0);
+
+        // Releasing the other trigger keeps the held one's chords.
+        bw.new_tab("vfs://sites/home/index.html", &vfs);
+        assert_eq!(bw.active_tab(), 1);
+        bw.handle_input(&InputEvent::TriggerPress(Trigger::Left), &mut vfs);
+        bw.handle_input(&InputEvent::TriggerRelease(Trigger::Right), &mut vfs);
+        bw.handle_input(&InputEvent::ButtonPress(Button::Square), &mut vfs);
+        assert_eq!(bw.active_tab(), 0);
+        bw.handle_input(&InputEvent::TriggerRelease(Trigger::Left), &mut vfs);
+        assert_eq!(bw.tab().scroll.scroll_y, 0);
+    }
+
+    #[test]
+    fn zoomed_link_regions_align_with_text() {
+        let vfs = interaction_vfs();
+        let mut bw = make_interaction_browser();
+        bw.set_window(0, 0, 480, 272);
+        bw.navigate_vfs("vfs://sites/test/single_link.html", &vfs);
+        bw.set_text_scale(2.0);
+
+        let mut backend = MockBackend::new();
+        bw.paint(&mut backend).unwrap();
+        let link = bw
+            .tab()
+            .link_map
+            .iter()
+            .find(|l| l.href.contains("target.html"))
+            .expect("should have link to target.html");
+        let (text, tx, ty, fs) = backend
+            .text_positions()
+            .into_iter()
+            .find(|(t, _, _, _)| t.contains("Click"))
+            .expect("link text should be drawn");
+        assert_eq!(fs, 16);
+        let right = tx + oasis_types::backend::bitmap_measure_text(text, fs) as i32;
+        let (lx, ly) = (link.rect.x as i32, link.rect.y as i32);
+        assert!(tx >= lx && right <= lx + link.rect.width as i32 + 1);
+        assert!(ty >= ly && ty + 16 <= ly + link.rect.height as i32 + 1);
+    }
 }
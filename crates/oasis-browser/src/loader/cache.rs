@@ -1,9 +1,15 @@
 //! LRU resource cache.
 //!
 //! Bounds memory usage by evicting the least-recently-used entries when
-//! the total cached body size exceeds a configurable limit.
+//! the total cached body size exceeds a configurable limit. Hit, miss,
+//! and eviction counts are kept for [`ResourceCache::stats`].
+//!
+//! The cache does not own a backend, so textures of dropped entries are
+//! queued for the caller to destroy (see
+//! [`ResourceCache::take_released_textures`]).
 
 use std::collections::{HashMap, VecDeque};
+use std::fmt;
 
 use oasis_types::backend::TextureId;
 
@@ -18,6 +24,33 @@ pub struct CacheEntry {
     pub texture: Option<TextureId>,
 }
 
+/// A snapshot of cache usage counters.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CacheStats {
+    /// Lookups that found the URL cached.
+    pub hits: u64,
+    /// Lookups that did not.
+    pub misses: u64,
+    /// Entries dropped to stay within the size limit.
+    pub evictions: u64,
+    /// Current total body size in bytes.
+    pub size_bytes: usize,
+    /// Size limit in bytes.
+    pub max_bytes: usize,
+    /// Number of cached entries.
+    pub entries: usize,
+}
+
+impl fmt::Display for CacheStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} entries, {}/{} bytes, {} hits, {} misses, {} evictions",
+            self.entries, self.size_bytes, self.max_bytes, self.hits, self.misses, self.evictions
+        )
+    }
+}
+
 /// LRU resource cache with bounded size (measured in body bytes).
 pub struct ResourceCache {
     entries: HashMap<String, CacheEntry>,
@@ -25,6 +58,11 @@ pub struct ResourceCache {
     order: VecDeque<String>,
     current_size: usize,
     max_size: usize,
+    hits: u64,
+    misses: u64,
+    evictions: u64,
+    /// Textures of dropped entries, waiting to be destroyed.
+    released: Vec<TextureId>,
 }
 
 impl ResourceCache {
@@ -35,18 +73,24 @@ impl ResourceCache {
             order: VecDeque::new(),
             current_size: 0,
             max_size,
+            hits: 0,
+            misses: 0,
+            evictions: 0,
+            released: Vec::new(),
         }
     }
 
     /// Look up a cached resource by URL, promoting it to the
-    /// most-recently-used position.
+    /// most-recently-used position. Counts as a hit or a miss.
     pub fn get(&mut self, url: &str) -> Option<&CacheEntry> {
         if self.entries.contains_key(url) {
+            self.hits += 1;
             // Move to front of LRU order.
             self.order.retain(|u| u != url);
             self.order.push_front(url.to_string());
             self.entries.get(url)
         } else {
+            self.misses += 1;
             None
         }
     }
@@ -68,6 +112,9 @@ impl ResourceCache {
         if let Some(old) = self.entries.remove(&url) {
             self.current_size -= old.response.body.len();
             self.order.retain(|u| u != &url);
+            if old.texture != entry.texture {
+                self.released.extend(old.texture);
+            }
         }
 
         // Evict until there is room.
@@ -75,6 +122,8 @@ impl ResourceCache {
             if let Some(evicted_url) = self.order.pop_back() {
                 if let Some(evicted) = self.entries.remove(&evicted_url) {
                     self.current_size -= evicted.response.body.len();
+                    self.released.extend(evicted.texture);
+                    self.evictions += 1;
                 }
             } else {
                 break;
@@ -92,13 +141,57 @@ impl ResourceCache {
         self.entries.contains_key(url)
     }
 
-    /// Drop all cached entries.
+    /// Look up a cached resource without promoting it or counting a
+    /// hit or miss.
+    pub fn peek(&self, url: &str) -> Option<&CacheEntry> {
+        self.entries.get(url)
+    }
+
+    /// Attach the texture decoded from the cached resource at `url`,
+    /// queueing any texture it replaces for release.
+    ///
+    /// Returns `false` if `url` is not cached; the caller still owns
+    /// `texture` then.
+    pub fn set_texture(&mut self, url: &str, texture: TextureId) -> bool {
+        let Some(entry) = self.entries.get_mut(url) else {
+            return false;
+        };
+        if let Some(old) = entry.texture.replace(texture)
+            && old != texture
+        {
+            self.released.push(old);
+        }
+        true
+    }
+
+    /// Drop all cached entries, queueing their textures for release.
+    /// The hit/miss/eviction counters are kept.
     pub fn clear(&mut self) {
-        self.entries.clear();
+        self.released
+            .extend(self.entries.drain().filter_map(|(_, e)| e.texture));
         self.order.clear();
         self.current_size = 0;
     }
 
+    /// Textures of entries dropped by eviction, replacement, or
+    /// [`clear`](Self::clear), for the caller to destroy on its
+    /// backend.
+    pub fn take_released_textures(&mut self) -> Vec<TextureId> {
+        std::mem::take(&mut self.released)
+    }
+
+    /// Current usage counters.
+    pub fn stats(&self) -> CacheStats {
+        CacheStats {
+            hits: self.hits,
+            misses: self.misses,
+            evictions: self.evictions,
+            size_bytes: self.current_size,
+            max_bytes: self.max_size,
+            entries: self.entries.len(),
+        }
+    }
+
     /// Current total body size in bytes.
     pub fn size(&self) -> usize {
         self.current_size
@@ -133,6 +226,8 @@ mod tests {
                 content_type: ContentType::Html,
                 body,
                 status: 200,
+                headers: Vec::new(),
+                charset: None,
             },
             texture: None,
         };
@@ -261,6 +356,67 @@ mod tests {
         assert!(cache.get("http://missing.com/x").is_none());
     }
 
+    #[test]
+    fn stats_count_hits_misses_and_evictions() {
+        let mut cache = ResourceCache::new(100);
+        for i in 0..3 {
+            let (u, e) = make_entry(&format!("http://a.com/{i}"), 50);
+            cache.insert(u, e);
+        }
+        let _ = cache.get("http://a.com/2");
+        let _ = cache.get("http://a.com/0");
+        assert_eq!(
+            cache.stats(),
+            CacheStats {
+                hits: 1,
+                misses: 1,
+                evictions: 1,
+                size_bytes: 100,
+                max_bytes: 100,
+                entries: 2,
+            }
+        );
+        assert_eq!(
+            cache.stats().to_string(),
+            "2 entries, 100/100 bytes, 1 hits, 1 misses, 1 evictions"
+        );
+    }
+
+    #[test]
+    fn set_texture_attaches_and_releases_replaced() {
+        let mut cache = ResourceCache::new(100);
+        let (u1, e1) = make_entry("http://a.com/1.bmp", 50);
+        cache.insert(u1, e1);
+        assert!(!cache.set_texture("http://a.com/2.bmp", TextureId(9)));
+
+        assert!(cache.set_texture("http://a.com/1.bmp", TextureId(1)));
+        assert!(cache.set_texture("http://a.com/1.bmp", TextureId(2)));
+        assert_eq!(
+            cache.peek("http://a.com/1.bmp").unwrap().texture,
+            Some(TextureId(2))
+        );
+        assert_eq!(cache.take_released_textures(), [TextureId(1)]);
+        assert_eq!(cache.stats().hits + cache.stats().misses, 0);
+    }
+
+    #[test]
+    fn dropped_textures_are_released() {
+        let mut cache = ResourceCache::new(100);
+        for i in 0..3 {
+            let (u, mut e) = make_entry(&format!("http://a.com/{i}"), 50);
+            e.texture = Some(TextureId(i));
+            cache.insert(u, e);
+        }
+        assert_eq!(cache.take_released_textures(), [TextureId(0)]);
+        assert!(cache.take_released_textures().is_empty());
+
+        cache.clear();
+        let mut released = cache.take_released_textures();
+        released.sort_by_key(|t| t.0);
+        assert_eq!(released, [TextureId(1), TextureId(2)]);
+        assert_eq!(cache.stats().evictions, 1);
+    }
+
     #[test]
     fn multiple_evictions_for_large_insert() {
         let mut cache = ResourceCache::new(200);
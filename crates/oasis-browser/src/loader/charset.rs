@@ -0,0 +1,225 @@
+//! Character set detection and decoding for text responses.
+//!
+//! The encoding of a text body is chosen in this order:
+//!
+//! 1. A byte order mark (UTF-8, UTF-16LE, UTF-16BE).
+//! 2. The `charset` parameter from the transport (HTTP `Content-Type`
+//!    header or Gemini meta line), carried in
+//!    [`ResourceResponse::charset`](super::ResourceResponse::charset).
+//! 3. For HTML, a `<meta charset>` or `<meta http-equiv="Content-Type">`
+//!    declaration within the first [`SNIFF_LEN`] bytes.
+//! 4. UTF-8, with invalid sequences replaced.
+//!
+//! Following the WHATWG Encoding Standard, `iso-8859-1` and `us-ascii`
+//! labels decode as Windows-1252. Unsupported encodings (e.g.
+//! Shift-JIS) fall back to lossy UTF-8.
+
+/// How many leading bytes to scan for a `<meta>` charset declaration.
+pub const SNIFF_LEN: usize = 4096;
+
+/// Encodings the decoder understands.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Charset {
+    Utf8,
+    Utf16Le,
+    Utf16Be,
+    Windows1252,
+}
+
+impl Charset {
+    /// Map an encoding label (case-insensitive) to a supported charset.
+    pub fn from_label(label: &str) -> Option<Self> {
+        let label = label.trim().trim_matches(|c| c == '"' || c == '\'');
+        match label.to_ascii_lowercase().as_str() {
+            "utf-8" | "utf8" | "unicode-1-1-utf-8" => Some(Self::Utf8),
+            "utf-16" | "utf-16le" => Some(Self::Utf16Le),
+            "utf-16be" => Some(Self::Utf16Be),
+            "windows-1252" | "cp1252" | "x-cp1252" | "iso-8859-1" | "iso8859-1" | "iso_8859-1"
+            | "latin1" | "l1" | "us-ascii" | "ascii" => Some(Self::Windows1252),
+            _ => None,
+        }
+    }
+
+    /// Decode `bytes` from this charset into a UTF-8 string.
+    pub fn decode(self, bytes: &[u8]) -> String {
+        match self {
+            Self::Utf8 => String::from_utf8_lossy(bytes).into_owned(),
+            Self::Utf16Le => decode_utf16(bytes, u16::from_le_bytes),
+            Self::Utf16Be => decode_utf16(bytes, u16::from_be_bytes),
+            Self::Windows1252 => bytes.iter().map(|&b| windows_1252_char(b)).collect(),
+        }
+    }
+}
+
+/// Decode a text body to UTF-8.
+///
+/// `declared` is the transport-level charset, if any; `is_html`
+/// enables `<meta>` sniffing.
+pub fn decode_text(body: &[u8], declared: Option<&str>, is_html: bool) -> String {
+    if let Some((charset, bom_len)) = detect_bom(body) {
+        return charset.decode(&body[bom_len..]);
+    }
+    let charset = declared
+        .and_then(Charset::from_label)
+        .or_else(|| {
+            if is_html {
+                sniff_meta_charset(body).and_then(|label| Charset::from_label(&label))
+            } else {
+                None
+            }
+        })
+        .unwrap_or(Charset::Utf8);
+    charset.decode(body)
+}
+
+/// Extract the `charset` parameter from a MIME type such as
+/// `text/html; charset=ISO-8859-1`.
+pub fn mime_charset(mime: &str) -> Option<String> {
+    mime.split(';').skip(1).find_map(|param| {
+        let (name, value) = param.split_once('=')?;
+        if !name.trim().eq_ignore_ascii_case("charset") {
+            return None;
+        }
+        let value = value.trim().trim_matches('"');
+        (!value.is_empty()).then(|| value.to_string())
+    })
+}
+
+/// Find a charset declared by a `<meta>` tag near the start of an HTML
+/// document.
+///
+/// Handles both `<meta charset="...">` and the older
+/// `<meta http-equiv="Content-Type" content="text/html; charset=...">`.
+pub fn sniff_meta_charset(body: &[u8]) -> Option<String> {
+    let head = body[..body.len().min(SNIFF_LEN)].to_ascii_lowercase();
+    let mut pos = 0;
+    while let Some(start) = find(&head[pos..], b"<meta").map(|i| i + pos) {
+        let end = head[start..]
+            .iter()
+            .position(|&b| b == b'>')
+            .map_or(head.len(), |i| start + i);
+        let tag = &head[start..end];
+        if let Some(i) = find(tag, b"charset=") {
+            let value: String = tag[i + b"charset=".len()..]
+                .iter()
+                .skip_while(|&&b| b == b'"' || b == b'\'' || b.is_ascii_whitespace())
+                .take_while(|&&b| {
+                    b.is_ascii_alphanumeric() || matches!(b, b'-' | b'_' | b'.' | b':')
+                })
+                .map(|&b| b as char)
+                .collect();
+            if !value.is_empty() {
+                return Some(value);
+            }
+        }
+        pos = end;
+    }
+    None
+}
+
+/// Detect a byte order mark, returning the charset and BOM length.
+fn detect_bom(body: &[u8]) -> Option<(Charset, usize)> {
+    if body.starts_with(&[0xEF, 0xBB, 0xBF]) {
+        Some((Charset::Utf8, 3))
+    } else if body.starts_with(&[0xFF, 0xFE]) {
+        Some((Charset::Utf16Le, 2))
+    } else if body.starts_with(&[0xFE, 0xFF]) {
+        Some((Charset::Utf16Be, 2))
+    } else {
+        None
+    }
+}
+
+fn decode_utf16(bytes: &[u8], unit: fn([u8; 2]) -> u16) -> String {
+    let units = bytes.chunks_exact(2).map(|c| unit([c[0], c[1]]));
+    char::decode_utf16(units)
+        .map(|r| r.unwrap_or(char::REPLACEMENT_CHARACTER))
+        .collect()
+}
+
+/// Windows-1252 code points for bytes 0x80..=0x9F (the rest of the
+/// range maps directly to U+0000..U+00FF like ISO-8859-1).
+const WINDOWS_1252_HIGH: [char; 32] = [
+    '\u{20AC}', '\u{0081}', '\u{201A}', '\u{0192}', '\u{201E}', '\u{2026}', '\u{2020}', '\u{2021}',
+    '\u{02C6}', '\u{2030}', '\u{0160}', '\u{2039}', '\u{0152}', '\u{008D}', '\u{017D}', '\u{008F}',
+    '\u{0090}', '\u{2018}', '\u{2019}', '\u{201C}', '\u{201D}', '\u{2022}', '\u{2013}', '\u{2014}',
+    '\u{02DC}', '\u{2122}', '\u{0161}', '\u{203A}', '\u{0153}', '\u{009D}', '\u{017E}', '\u{0178}',
+];
+
+fn windows_1252_char(b: u8) -> char {
+    match b {
+        0x80..=0x9F => WINDOWS_1252_HIGH[(b - 0x80) as usize],
+        _ => b as char,
+    }
+}
+
+fn find(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack.windows(needle.len()).position(|w| w == needle)
+}
+
+// -------------------------------------------------------------------
+// Tests
+// -------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sniffs_meta_charset_forms() {
+        assert_eq!(
+            sniff_meta_charset(b"<html><head><META CharSet=\"ISO-8859-1\">").as_deref(),
+            Some("iso-8859-1")
+        );
+        assert_eq!(
+            sniff_meta_charset(
+                b"<meta http-equiv=\"Content-Type\" content=\"text/html; charset=windows-1252\">"
+            )
+            .as_deref(),
+            Some("windows-1252")
+        );
+        assert_eq!(
+            sniff_meta_charset(b"<meta name=\"x\"><p>charset=no</p>"),
+            None
+        );
+    }
+
+    #[test]
+    fn decodes_latin1_and_windows_1252() {
+        let body = b"<meta charset=latin1>caf\xe9 \x93quoted\x94 \x80";
+        assert_eq!(
+            decode_text(body, None, true),
+            "<meta charset=latin1>café \u{201C}quoted\u{201D} €"
+        );
+        // Without sniffing, plain text falls back to lossy UTF-8.
+        assert!(decode_text(b"caf\xe9", None, false).contains('\u{FFFD}'));
+        assert_eq!(decode_text(b"caf\xe9", Some("ISO-8859-1"), false), "café");
+    }
+
+    #[test]
+    fn declared_charset_beats_meta_and_bom_beats_both() {
+        let body = b"<meta charset=utf-8>\xe9";
+        assert_eq!(
+            decode_text(body, Some("windows-1252"), true),
+            "<meta charset=utf-8>é"
+        );
+        let bom = b"\xef\xbb\xbf\xc3\xa9";
+        assert_eq!(decode_text(bom, Some("windows-1252"), true), "é");
+        let utf16 = b"\xff\xfeh\x00i\x00";
+        assert_eq!(decode_text(utf16, None, false), "hi");
+    }
+
+    #[test]
+    fn mime_charset_parameter() {
+        assert_eq!(
+            mime_charset("text/html; charset=\"ISO-8859-1\"").as_deref(),
+            Some("ISO-8859-1")
+        );
+        assert_eq!(
+            mime_charset("text/gemini; lang=en; charset=utf-8").as_deref(),
+            Some("utf-8")
+        );
+        assert_eq!(mime_charset("text/plain"), None);
+        assert_eq!(Charset::from_label("Shift_JIS"), None);
+    }
+}
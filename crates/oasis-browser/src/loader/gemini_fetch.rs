@@ -3,16 +3,22 @@
 //! Gemini mandates TLS on every connection (default port 1965).
 //! The request is a single URL terminated by CRLF; the response
 //! starts with a status line followed by an optional body.
+//!
+//! Certificates are pinned per host on first use (see
+//! [`KnownHosts`]); a host presenting a different certificate gets a
+//! warning page instead of the request being sent.
 
 use std::io::{Read, Write};
-use std::net::TcpStream;
 use std::time::Duration;
 
 use crate::gemini;
+use crate::gemini::known_hosts::{self, KnownHosts, TofuCheck};
 use oasis_net::tls::TlsProvider;
 use oasis_types::error::{OasisError, Result};
 
-use super::{ContentType, ResourceResponse, Url};
+use super::http::tcp_connect;
+use super::timeout::{self, Deadline};
+use super::{ContentType, ResourceResponse, Url, unix_now};
 
 /// Maximum Gemini response size (2 MB).
 const MAX_BODY_SIZE: usize = 2 * 1024 * 1024;
@@ -20,17 +26,19 @@ const MAX_BODY_SIZE: usize = 2 * 1024 * 1024;
 /// Maximum number of redirects to follow.
 const MAX_REDIRECTS: u8 = 5;
 
-/// TCP connect timeout.
-const CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
-
-/// Read timeout.
-const READ_TIMEOUT: Duration = Duration::from_secs(15);
-
 /// Fetch a Gemini resource over TLS.
 ///
 /// Returns an error page if no TLS provider is available (Gemini
-/// requires TLS for every connection).
-pub fn gemini_get(url: &Url, tls: Option<&dyn TlsProvider>) -> Result<ResourceResponse> {
+/// requires TLS for every connection). With `known_hosts`, each
+/// connection's certificate is checked against the host's pin.
+///
+/// Each exchange, redirects included, must finish within `timeout`.
+pub fn gemini_get(
+    url: &Url,
+    tls: Option<&dyn TlsProvider>,
+    mut known_hosts: Option<&mut KnownHosts>,
+    timeout: Duration,
+) -> Result<ResourceResponse> {
     let tls = match tls {
         Some(t) => t,
         None => return Ok(tls_required_page(url)),
@@ -38,69 +46,128 @@ pub fn gemini_get(url: &Url, tls: Option<&dyn TlsProvider>) -> Result<ResourceRe
 
     let mut current_url = url.clone();
     for _ in 0..MAX_REDIRECTS {
-        let resp = do_gemini_request(&current_url, tls)?;
-
-        if resp.status.is_redirect() {
-            let target = &resp.meta;
-            current_url = current_url
-                .resolve(target)
-                .ok_or_else(|| OasisError::Backend(format!("bad Gemini redirect: {target}")))?;
-            continue;
-        }
-
-        if !resp.status.is_success() {
-            let html = format!(
-                "<html><body>\
-                 <h1>Gemini Error</h1>\
-                 <p>Status: {:?}</p>\
-                 <p>{}</p>\
-                 </body></html>",
-                resp.status, resp.meta,
-            );
-            return Ok(ResourceResponse {
-                url: current_url.to_string(),
-                content_type: ContentType::Html,
-                body: html.into_bytes(),
-                status: 200,
-            });
-        }
-
-        // Success -- determine content type from the meta line.
-        let content_type = if resp.meta.starts_with("text/gemini") {
-            ContentType::GeminiText
-        } else if resp.meta.starts_with("text/html") {
-            ContentType::Html
-        } else if resp.meta.starts_with("text/") {
-            ContentType::PlainText
-        } else {
-            ContentType::Html // fallback
+        let mut deadline = Deadline::start(timeout);
+        let exchange =
+            do_gemini_request(&current_url, tls, known_hosts.as_deref_mut(), &mut deadline)?;
+        let response = match exchange {
+            Exchange::Response(response) => response,
+            Exchange::CertificateChanged {
+                host,
+                expected,
+                presented,
+            } => {
+                let html = known_hosts::certificate_changed_page(&host, &expected, &presented);
+                return Ok(ResourceResponse {
+                    url: current_url.to_string(),
+                    content_type: ContentType::Html,
+                    body: html.into_bytes(),
+                    status: 200,
+                    headers: Vec::new(),
+                    charset: None,
+                });
+            },
+        };
+        let mut status = 200;
+        let (content_type, body, charset) = match response {
+            gemini::GeminiResponse::Redirect { url: target } => {
+                current_url = current_url
+                    .resolve(&target)
+                    .ok_or_else(|| OasisError::Backend(format!("bad Gemini redirect: {target}")))?;
+                continue;
+            },
+            gemini::GeminiResponse::Input { prompt, sensitive } => (
+                ContentType::GeminiInput { sensitive },
+                prompt.into_bytes(),
+                None,
+            ),
+            gemini::GeminiResponse::Failure {
+                status: failure,
+                message,
+            } => {
+                status = u16::from(failure.code());
+                let html = format!(
+                    "<html><body>\
+                     <h1>Gemini Error</h1>\
+                     <p>Status: {failure:?}</p>\
+                     <p>{message}</p>\
+                     </body></html>",
+                );
+                (ContentType::Html, html.into_bytes(), None)
+            },
+            gemini::GeminiResponse::Success { mime, body } => {
+                // Determine content type from the meta line. Text types
+                // render; anything else (archives, audio, ...) is left
+                // as `Unknown` so the browser offers it as a download.
+                let content_type = if mime.trim().is_empty() {
+                    ContentType::GeminiText
+                } else {
+                    match ContentType::from_mime(&mime) {
+                        ContentType::Unknown if mime.starts_with("text/") => ContentType::PlainText,
+                        ct => ct,
+                    }
+                };
+                (content_type, body, super::charset::mime_charset(&mime))
+            },
         };
-
-        let body = resp.body.unwrap_or_default();
 
         return Ok(ResourceResponse {
             url: current_url.to_string(),
             content_type,
-            body: body.into_bytes(),
-            status: 200,
+            body,
+            status,
+            headers: Vec::new(),
+            charset,
         });
     }
 
     Err(OasisError::Backend("too many Gemini redirects".to_string()))
 }
 
+/// Result of a single Gemini exchange.
+enum Exchange {
+    /// The server answered.
+    Response(gemini::GeminiResponse),
+    /// The server's certificate does not match its pin; nothing was
+    /// sent.
+    CertificateChanged {
+        host: String,
+        expected: String,
+        presented: String,
+    },
+}
+
 /// Perform a single Gemini request over TLS.
-fn do_gemini_request(url: &Url, tls: &dyn TlsProvider) -> Result<gemini::GeminiResponse> {
+fn do_gemini_request(
+    url: &Url,
+    tls: &dyn TlsProvider,
+    known_hosts: Option<&mut KnownHosts>,
+    deadline: &mut Deadline,
+) -> Result<Exchange> {
     let host = &url.host;
     let port = url.port.unwrap_or(1965);
 
     // Connect TCP.
-    let stream = tcp_connect(host, port)?;
+    let stream = tcp_connect(host, port, deadline)?;
 
     // Wrap in TLS.
     let net_stream: Box<dyn oasis_types::backend::NetworkStream> =
         Box::new(oasis_net::StdNetworkStream::new(stream));
-    let tls_stream = tls.connect_tls(net_stream, host)?;
+    let (tls_stream, cert) = tls.connect_tls_peer(net_stream, host)?;
+
+    // Pin per host and port, since each port may be a different server.
+    if let (Some(known_hosts), Some(cert)) = (known_hosts, cert) {
+        let key = match url.port {
+            Some(p) if p != 1965 => format!("{host}:{p}"),
+            _ => host.clone(),
+        };
+        if let TofuCheck::Changed { expected } = known_hosts.check(&key, &cert, unix_now()) {
+            return Ok(Exchange::CertificateChanged {
+                host: key,
+                expected,
+                presented: cert.fingerprint,
+            });
+        }
+    }
 
     let mut adapter = super::http::NetworkStreamAdapter(tls_stream);
 
@@ -114,6 +181,7 @@ fn do_gemini_request(url: &Url, tls: &dyn TlsProvider) -> Result<gemini::GeminiR
     let mut buf = Vec::with_capacity(8192);
     let mut chunk = [0u8; 8192];
     loop {
+        deadline.remaining()?;
         match adapter.read(&mut chunk) {
             Ok(0) => break,
             Ok(n) => {
@@ -121,43 +189,26 @@ fn do_gemini_request(url: &Url, tls: &dyn TlsProvider) -> Result<gemini::GeminiR
                     return Err(OasisError::Backend("Gemini response too large".to_string()));
                 }
                 buf.extend_from_slice(&chunk[..n]);
+                deadline.progress();
             },
             Err(e)
                 if e.kind() == std::io::ErrorKind::WouldBlock
                     || e.kind() == std::io::ErrorKind::TimedOut =>
             {
+                if buf.is_empty() {
+                    return Err(timeout::timed_out());
+                }
                 break;
             },
-            Err(e) => {
-                return Err(OasisError::Backend(format!("Gemini read: {e}")));
-            },
+            Err(e) => return Err(timeout::io_error("Gemini read", &e)),
         }
     }
 
     gemini::parse_response(&buf)
+        .map(Exchange::Response)
         .ok_or_else(|| OasisError::Backend("malformed Gemini response".to_string()))
 }
 
-/// Open a TCP connection with a connect timeout.
-fn tcp_connect(host: &str, port: u16) -> Result<TcpStream> {
-    use std::net::ToSocketAddrs;
-
-    let addr = format!("{host}:{port}")
-        .to_socket_addrs()
-        .map_err(|e| OasisError::Backend(format!("DNS resolution failed: {e}")))?
-        .next()
-        .ok_or_else(|| OasisError::Backend(format!("no addresses for {host}:{port}")))?;
-
-    let stream = TcpStream::connect_timeout(&addr, CONNECT_TIMEOUT)
-        .map_err(|e| OasisError::Backend(format!("TCP connect failed: {e}")))?;
-
-    stream
-        .set_read_timeout(Some(READ_TIMEOUT))
-        .map_err(|e| OasisError::Backend(format!("set read timeout: {e}")))?;
-
-    Ok(stream)
-}
-
 /// Error page when TLS is not available (Gemini requires it).
 fn tls_required_page(url: &Url) -> ResourceResponse {
     let html = format!(
@@ -172,6 +223,8 @@ fn tls_required_page(url: &Url) -> ResourceResponse {
         content_type: ContentType::Html,
         body: html.into_bytes(),
         status: 200,
+        headers: Vec::new(),
+        charset: None,
     }
 }
 
@@ -196,6 +249,34 @@ mod tests {
         }
     }
 
+    /// A pass-through provider that reports a fixed certificate.
+    struct PinnedTlsProvider(&'static str);
+
+    impl TlsProvider for PinnedTlsProvider {
+        fn connect_tls(
+            &self,
+            stream: Box<dyn NetworkStream>,
+            _server_name: &str,
+        ) -> oasis_types::error::Result<Box<dyn NetworkStream>> {
+            Ok(stream)
+        }
+
+        fn connect_tls_peer(
+            &self,
+            stream: Box<dyn NetworkStream>,
+            _server_name: &str,
+        ) -> oasis_types::error::Result<(
+            Box<dyn NetworkStream>,
+            Option<oasis_types::tls::PeerCertificate>,
+        )> {
+            let cert = oasis_types::tls::PeerCertificate {
+                fingerprint: self.0.to_string(),
+                not_after: None,
+            };
+            Ok((stream, Some(cert)))
+        }
+    }
+
     /// Spawn a local TCP server that accepts one connection, reads
     /// the Gemini request, and sends the given raw response bytes.
     fn spawn_gemini_server(response: Vec<u8>) -> (std::thread::JoinHandle<()>, u16) {
@@ -218,7 +299,7 @@ mod tests {
     #[test]
     fn test_gemini_without_tls_returns_tls_required() {
         let url = Url::parse("gemini://example.com/page").unwrap();
-        let resp = gemini_get(&url, None).unwrap();
+        let resp = gemini_get(&url, None, None, timeout::DEFAULT_TIMEOUT).unwrap();
         let body = String::from_utf8(resp.body).unwrap();
         assert!(body.contains("TLS Required"));
         assert!(body.contains("example.com"));
@@ -230,7 +311,7 @@ mod tests {
         let (handle, port) = spawn_gemini_server(b"20 text/gemini\r\n# Hello\nWelcome!".to_vec());
         let url = Url::parse(&format!("gemini://127.0.0.1:{port}/")).unwrap();
         let provider = PassthroughTlsProvider;
-        let resp = gemini_get(&url, Some(&provider)).unwrap();
+        let resp = gemini_get(&url, Some(&provider), None, timeout::DEFAULT_TIMEOUT).unwrap();
         assert_eq!(resp.content_type, ContentType::GeminiText);
         let body = String::from_utf8(resp.body).unwrap();
         assert!(body.contains("Hello"));
@@ -259,7 +340,7 @@ mod tests {
         });
         let url = Url::parse(&format!("gemini://127.0.0.1:{port}/start")).unwrap();
         let provider = PassthroughTlsProvider;
-        let resp = gemini_get(&url, Some(&provider)).unwrap();
+        let resp = gemini_get(&url, Some(&provider), None, timeout::DEFAULT_TIMEOUT).unwrap();
         let body = String::from_utf8(resp.body).unwrap();
         assert!(body.contains("Redirected!"));
         let _ = handle.join();
@@ -287,7 +368,7 @@ mod tests {
         });
         let url = Url::parse(&format!("gemini://127.0.0.1:{port}/start")).unwrap();
         let provider = PassthroughTlsProvider;
-        let result = gemini_get(&url, Some(&provider));
+        let result = gemini_get(&url, Some(&provider), None, timeout::DEFAULT_TIMEOUT);
         assert!(result.is_err());
         let msg = result.unwrap_err().to_string();
         assert!(msg.contains("too many"));
@@ -298,36 +379,197 @@ mod tests {
         let (handle, port) = spawn_gemini_server(b"51 Not Found\r\n".to_vec());
         let url = Url::parse(&format!("gemini://127.0.0.1:{port}/missing")).unwrap();
         let provider = PassthroughTlsProvider;
-        let resp = gemini_get(&url, Some(&provider)).unwrap();
+        let resp = gemini_get(&url, Some(&provider), None, timeout::DEFAULT_TIMEOUT).unwrap();
         let body = String::from_utf8(resp.body).unwrap();
         assert!(body.contains("Gemini Error"));
         assert!(body.contains("Not Found"));
         assert_eq!(resp.content_type, ContentType::Html);
+        assert_eq!(resp.status, 51);
+        let _ = handle.join();
+    }
+
+    #[test]
+    fn test_gemini_input_status() {
+        let (handle, port) = spawn_gemini_server(b"11 Enter password\r\n".to_vec());
+        let url = Url::parse(&format!("gemini://127.0.0.1:{port}/login")).unwrap();
+        let resp = gemini_get(
+            &url,
+            Some(&PassthroughTlsProvider),
+            None,
+            timeout::DEFAULT_TIMEOUT,
+        )
+        .unwrap();
+        assert_eq!(
+            resp.content_type,
+            ContentType::GeminiInput { sensitive: true }
+        );
+        assert_eq!(resp.body, b"Enter password");
+        let _ = handle.join();
+    }
+
+    #[test]
+    fn test_gemini_input_round_trip() {
+        // First request asks for input; the second echoes its request
+        // line so the test can check the query.
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let handle = std::thread::spawn(move || {
+            for i in 0..2 {
+                let (mut stream, _) = listener.accept().unwrap();
+                let mut buf = [0u8; 2048];
+                let n = stream.read(&mut buf).unwrap_or(0);
+                let request = String::from_utf8_lossy(&buf[..n]).trim_end().to_string();
+                let resp = if i == 0 {
+                    "10 Search terms\r\n".to_string()
+                } else {
+                    format!("20 text/gemini\r\n{request}")
+                };
+                let _ = stream.write_all(resp.as_bytes());
+                let _ = stream.flush();
+            }
+        });
+
+        let base = format!("gemini://127.0.0.1:{port}/search");
+        let resp = gemini_get(
+            &Url::parse(&base).unwrap(),
+            Some(&PassthroughTlsProvider),
+            None,
+            timeout::DEFAULT_TIMEOUT,
+        )
+        .unwrap();
+        assert_eq!(
+            resp.content_type,
+            ContentType::GeminiInput { sensitive: false }
+        );
+        assert_eq!(resp.body, b"Search terms");
+
+        let answer = gemini::input_url(&resp.url, "rust & psp");
+        let resp = gemini_get(
+            &Url::parse(&answer).unwrap(),
+            Some(&PassthroughTlsProvider),
+            None,
+            timeout::DEFAULT_TIMEOUT,
+        )
+        .unwrap();
+        assert_eq!(resp.content_type, ContentType::GeminiText);
+        assert_eq!(
+            String::from_utf8(resp.body).unwrap(),
+            format!("{base}?rust%20%26%20psp")
+        );
         let _ = handle.join();
     }
 
+    #[test]
+    fn test_gemini_certificate_pinning() {
+        // Each connection's request line is sent back over a channel;
+        // a rejected connection sends nothing.
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let (tx, rx) = std::sync::mpsc::channel();
+        let handle = std::thread::spawn(move || {
+            for _ in 0..3 {
+                let (mut stream, _) = listener.accept().unwrap();
+                let mut buf = [0u8; 2048];
+                let n = stream.read(&mut buf).unwrap_or(0);
+                tx.send(String::from_utf8_lossy(&buf[..n]).trim_end().to_string())
+                    .unwrap();
+                let _ = stream.write_all(b"20 text/gemini\r\nHello");
+            }
+        });
+        let url = Url::parse(&format!("gemini://127.0.0.1:{port}/")).unwrap();
+        let host = format!("127.0.0.1:{port}");
+        let mut known = KnownHosts::new();
+
+        // First connection pins the certificate silently.
+        let resp = gemini_get(
+            &url,
+            Some(&PinnedTlsProvider("aa")),
+            Some(&mut known),
+            timeout::DEFAULT_TIMEOUT,
+        )
+        .unwrap();
+        assert_eq!(resp.body, b"Hello");
+        assert_eq!(known.get(&host).unwrap().fingerprint, "aa");
+
+        // Same certificate: trusted.
+        let resp = gemini_get(
+            &url,
+            Some(&PinnedTlsProvider("aa")),
+            Some(&mut known),
+            timeout::DEFAULT_TIMEOUT,
+        )
+        .unwrap();
+        assert_eq!(resp.body, b"Hello");
+
+        // Changed certificate: warning page, request never sent.
+        let resp = gemini_get(
+            &url,
+            Some(&PinnedTlsProvider("bb")),
+            Some(&mut known),
+            timeout::DEFAULT_TIMEOUT,
+        )
+        .unwrap();
+        assert_eq!(resp.content_type, ContentType::Html);
+        let body = String::from_utf8(resp.body).unwrap();
+        assert!(body.contains("Certificate changed"));
+        assert!(body.contains(&format!("about:tofu-accept?host={host}")));
+        assert_eq!(known.get(&host).unwrap().fingerprint, "aa");
+
+        let _ = handle.join();
+        let requests: Vec<String> = rx.iter().collect();
+        assert_eq!(requests, [url.to_string(), url.to_string(), String::new()]);
+
+        // Accepting the change pins the new certificate.
+        assert!(known.accept_pending(&host));
+        assert_eq!(known.get(&host).unwrap().fingerprint, "bb");
+    }
+
     #[test]
     fn test_gemini_content_type_detection() {
         // text/gemini -> GeminiText
         let (h1, p1) = spawn_gemini_server(b"20 text/gemini\r\n# Test".to_vec());
         let url1 = Url::parse(&format!("gemini://127.0.0.1:{p1}/")).unwrap();
         let provider = PassthroughTlsProvider;
-        let r1 = gemini_get(&url1, Some(&provider)).unwrap();
+        let r1 = gemini_get(&url1, Some(&provider), None, timeout::DEFAULT_TIMEOUT).unwrap();
         assert_eq!(r1.content_type, ContentType::GeminiText);
         let _ = h1.join();
 
         // text/html -> Html
         let (h2, p2) = spawn_gemini_server(b"20 text/html\r\n<html>hi</html>".to_vec());
         let url2 = Url::parse(&format!("gemini://127.0.0.1:{p2}/")).unwrap();
-        let r2 = gemini_get(&url2, Some(&provider)).unwrap();
+        let r2 = gemini_get(&url2, Some(&provider), None, timeout::DEFAULT_TIMEOUT).unwrap();
         assert_eq!(r2.content_type, ContentType::Html);
         let _ = h2.join();
 
         // text/plain -> PlainText
         let (h3, p3) = spawn_gemini_server(b"20 text/plain\r\nhello".to_vec());
         let url3 = Url::parse(&format!("gemini://127.0.0.1:{p3}/")).unwrap();
-        let r3 = gemini_get(&url3, Some(&provider)).unwrap();
+        let r3 = gemini_get(&url3, Some(&provider), None, timeout::DEFAULT_TIMEOUT).unwrap();
         assert_eq!(r3.content_type, ContentType::PlainText);
         let _ = h3.join();
+
+        // Non-text MIME -> Unknown (offered as a download), body intact.
+        let (h4, p4) = spawn_gemini_server(b"20 application/zip\r\nPK\x03\x04\xff".to_vec());
+        let url4 = Url::parse(&format!("gemini://127.0.0.1:{p4}/f.zip")).unwrap();
+        let r4 = gemini_get(&url4, Some(&provider), None, timeout::DEFAULT_TIMEOUT).unwrap();
+        assert_eq!(r4.content_type, ContentType::Unknown);
+        assert_eq!(r4.body, b"PK\x03\x04\xff");
+        let _ = h4.join();
+    }
+
+    #[test]
+    fn test_gemini_charset_parameter() {
+        let (h, p) = spawn_gemini_server(b"20 text/gemini; charset=iso-8859-1\r\ncaf\xe9".to_vec());
+        let url = Url::parse(&format!("gemini://127.0.0.1:{p}/")).unwrap();
+        let resp = gemini_get(
+            &url,
+            Some(&PassthroughTlsProvider),
+            None,
+            timeout::DEFAULT_TIMEOUT,
+        )
+        .unwrap();
+        assert_eq!(resp.content_type, ContentType::GeminiText);
+        assert_eq!(resp.charset.as_deref(), Some("iso-8859-1"));
+        let _ = h.join();
     }
 }
@@ -11,29 +11,31 @@ use oasis_net::tls::TlsProvider;
 use oasis_types::backend::NetworkStream;
 use oasis_types::error::{OasisError, Result};
 
-use super::{ContentType, ResourceResponse, Url};
+use super::timeout::{self, Deadline};
+use super::{ContentType, ResourceResponse, Url, find_header};
 
 /// Maximum response body size (8 MB).
-const MAX_BODY_SIZE: usize = 8 * 1024 * 1024;
-
-/// Maximum number of redirects to follow.
-const MAX_REDIRECTS: u8 = 5;
-
-/// TCP connect timeout.
-const CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
-
-/// TCP read timeout.
-const READ_TIMEOUT: Duration = Duration::from_secs(15);
+pub(super) const MAX_BODY_SIZE: usize = 8 * 1024 * 1024;
 
 /// Perform an HTTP(S) GET request for the given URL.
 ///
 /// When `tls` is `Some`, HTTPS URLs are supported.  When `None`, HTTPS
 /// URLs produce a user-friendly error page instead.
 ///
-/// Follows redirects (301/302/307/308) up to [`MAX_REDIRECTS`] hops.
-pub fn http_get(url: &Url, tls: Option<&dyn TlsProvider>) -> Result<ResourceResponse> {
+/// Redirects are not followed here: a 3xx response is returned as-is,
+/// `Location` header included, so [`super::load_resource`] can apply
+/// the configured hop limit. The `Content-Type` header decides the
+/// content type; the URL's extension only stands in when it is absent.
+///
+/// The whole exchange must finish within `timeout`, or it fails with a
+/// timeout error (see [`timeout::is_timeout`]).
+pub fn http_get(
+    url: &Url,
+    tls: Option<&dyn TlsProvider>,
+    timeout: Duration,
+) -> Result<ResourceResponse> {
     if url.scheme == "https" && tls.is_none() {
-        return Ok(https_error_page(url, url));
+        return Ok(https_error_page(url));
     }
     if url.scheme != "http" && url.scheme != "https" {
         return Err(OasisError::Backend(format!(
@@ -42,36 +44,28 @@ pub fn http_get(url: &Url, tls: Option<&dyn TlsProvider>) -> Result<ResourceResp
         )));
     }
 
-    let mut current_url = url.clone();
-    for _ in 0..MAX_REDIRECTS {
-        let resp = do_request(&current_url, tls)?;
-
-        if is_redirect(resp.status_code)
-            && let Some(location) = find_header(&resp.headers, "location")
-        {
-            let location = location.to_string();
-            current_url = current_url
-                .resolve(&location)
-                .ok_or_else(|| OasisError::Backend(format!("bad redirect Location: {location}")))?;
-            if current_url.scheme == "https" && tls.is_none() {
-                return Ok(https_error_page(url, &current_url));
-            }
-            continue;
-        }
-
-        let content_type = find_header(&resp.headers, "content-type")
-            .map(ContentType::from_mime)
-            .unwrap_or_else(|| super::detect_content_type(&current_url));
-
-        return Ok(ResourceResponse {
-            url: current_url.to_string(),
-            content_type,
-            body: resp.body,
-            status: resp.status_code,
-        });
-    }
+    let mut deadline = Deadline::start(timeout);
+    Ok(resource_response(url, do_request(url, tls, &mut deadline)?))
+}
 
-    Err(OasisError::Backend("too many redirects".to_string()))
+/// Build the response for `url` from a parsed HTTP response.
+fn resource_response(url: &Url, resp: HttpResponse) -> ResourceResponse {
+    let mut response = ResourceResponse {
+        url: url.to_string(),
+        content_type: ContentType::Unknown,
+        body: resp.body,
+        status: resp.status_code,
+        headers: resp.headers,
+        charset: None,
+    };
+    let mime = response.header("content-type");
+    let content_type = mime
+        .map(ContentType::from_mime)
+        .unwrap_or_else(|| super::detect_content_type(url));
+    let charset = mime.and_then(super::charset::mime_charset);
+    response.content_type = content_type;
+    response.charset = charset;
+    response
 }
 
 // -------------------------------------------------------------------
@@ -94,13 +88,17 @@ pub struct HttpResponse {
 // -------------------------------------------------------------------
 
 /// Connect, optionally upgrade to TLS, send GET, read and parse.
-fn do_request(url: &Url, tls: Option<&dyn TlsProvider>) -> Result<HttpResponse> {
+fn do_request(
+    url: &Url,
+    tls: Option<&dyn TlsProvider>,
+    deadline: &mut Deadline,
+) -> Result<HttpResponse> {
     let host = &url.host;
     let is_https = url.scheme == "https";
     let default_port = if is_https { 443 } else { 80 };
     let port = url.port.unwrap_or(default_port);
 
-    let stream = tcp_connect(host, port)?;
+    let stream = tcp_connect(host, port, deadline)?;
 
     if is_https {
         let tls_provider =
@@ -112,18 +110,20 @@ fn do_request(url: &Url, tls: Option<&dyn TlsProvider>) -> Result<HttpResponse>
 
         let mut adapter = NetworkStreamAdapter(tls_stream);
         send_request(&mut adapter, url, is_https)?;
-        let raw = read_response(&mut adapter)?;
+        let raw = read_response(&mut adapter, deadline)?;
         parse_response(&raw)
     } else {
         let mut stream = stream;
         send_request(&mut stream, url, is_https)?;
-        let raw = read_response(&mut stream)?;
+        let raw = read_response(&mut stream, deadline)?;
         parse_response(&raw)
     }
 }
 
-/// Open a TCP connection with a connect timeout.
-fn tcp_connect(host: &str, port: u16) -> Result<TcpStream> {
+/// Open a TCP connection within the time left on `deadline`. Connecting
+/// counts as progress, and the full budget becomes the socket's read
+/// timeout: the longest wait for the next bytes.
+pub(super) fn tcp_connect(host: &str, port: u16, deadline: &mut Deadline) -> Result<TcpStream> {
     use std::net::ToSocketAddrs;
 
     let addr = format!("{host}:{port}")
@@ -132,11 +132,12 @@ fn tcp_connect(host: &str, port: u16) -> Result<TcpStream> {
         .next()
         .ok_or_else(|| OasisError::Backend(format!("no addresses for {host}:{port}")))?;
 
-    let stream = TcpStream::connect_timeout(&addr, CONNECT_TIMEOUT)
-        .map_err(|e| OasisError::Backend(format!("TCP connect failed: {e}")))?;
+    let stream = TcpStream::connect_timeout(&addr, deadline.remaining()?)
+        .map_err(|e| timeout::io_error("TCP connect failed", &e))?;
+    deadline.progress();
 
     stream
-        .set_read_timeout(Some(READ_TIMEOUT))
+        .set_read_timeout(Some(deadline.remaining()?))
         .map_err(|e| OasisError::Backend(format!("set read timeout: {e}")))?;
 
     Ok(stream)
@@ -161,6 +162,7 @@ fn send_request(stream: &mut impl Write, url: &Url, is_https: bool) -> Result<()
          Host: {host_header}\r\n\
          User-Agent: OASIS/1.0\r\n\
          Accept: */*\r\n\
+         Accept-Encoding: gzip, deflate\r\n\
          Connection: close\r\n\
          \r\n"
     );
@@ -173,10 +175,15 @@ fn send_request(stream: &mut impl Write, url: &Url, is_https: bool) -> Result<()
 }
 
 /// Read the entire response until EOF or until the read timeout fires.
-fn read_response(stream: &mut impl Read) -> Result<Vec<u8>> {
+///
+/// A read timeout before any byte arrived fails the request, as does
+/// the server going the whole `deadline` without sending anything. A
+/// slow transfer that keeps delivering bytes is never cut off.
+fn read_response(stream: &mut impl Read, deadline: &mut Deadline) -> Result<Vec<u8>> {
     let mut buf = Vec::with_capacity(8192);
     let mut chunk = [0u8; 8192];
     loop {
+        deadline.remaining()?;
         match stream.read(&mut chunk) {
             Ok(0) => break,
             Ok(n) => {
@@ -184,15 +191,17 @@ fn read_response(stream: &mut impl Read) -> Result<Vec<u8>> {
                     return Err(OasisError::Backend("response too large".to_string()));
                 }
                 buf.extend_from_slice(&chunk[..n]);
+                deadline.progress();
             },
             Err(e)
                 if e.kind() == io::ErrorKind::WouldBlock || e.kind() == io::ErrorKind::TimedOut =>
             {
+                if buf.is_empty() {
+                    return Err(timeout::timed_out());
+                }
                 break;
             },
-            Err(e) => {
-                return Err(OasisError::Backend(format!("read response: {e}")));
-            },
+            Err(e) => return Err(timeout::io_error("read response", &e)),
         }
     }
     Ok(buf)
@@ -226,7 +235,7 @@ pub fn parse_response(data: &[u8]) -> Result<HttpResponse> {
             break;
         }
         if let Some((name, value)) = line.split_once(':') {
-            headers.push((name.trim().to_lowercase(), value.trim().to_string()));
+            headers.push((name.trim().to_string(), value.trim().to_string()));
         }
     }
 
@@ -274,15 +283,6 @@ fn parse_status_line(line: &str) -> Result<u16> {
         .map_err(|_| OasisError::Backend(format!("bad status code in: {line}")))
 }
 
-/// Case-insensitive header lookup.
-fn find_header<'a>(headers: &'a [(String, String)], name: &str) -> Option<&'a str> {
-    let name_lower = name.to_lowercase();
-    headers
-        .iter()
-        .find(|(k, _)| k == &name_lower)
-        .map(|(_, v)| v.as_str())
-}
-
 /// Decode a chunked transfer-encoded body.
 fn decode_chunked(data: &[u8]) -> Result<Vec<u8>> {
     let mut result = Vec::new();
@@ -328,11 +328,6 @@ fn decode_chunked(data: &[u8]) -> Result<Vec<u8>> {
     Ok(result)
 }
 
-/// Whether a status code is a redirect we should follow.
-fn is_redirect(status: u16) -> bool {
-    matches!(status, 301 | 302 | 307 | 308)
-}
-
 /// Find the position of a byte subsequence in a slice.
 fn find_subsequence(haystack: &[u8], needle: &[u8]) -> Option<usize> {
     haystack.windows(needle.len()).position(|w| w == needle)
@@ -376,7 +371,7 @@ fn oasis_err_to_io(e: OasisError) -> io::Error {
 // -------------------------------------------------------------------
 
 /// Generate a user-friendly error page when a site requires HTTPS.
-fn https_error_page(original_url: &Url, https_url: &Url) -> ResourceResponse {
+fn https_error_page(https_url: &Url) -> ResourceResponse {
     let html = format!(
         "<html><body>\
          <h1>HTTPS Required</h1>\
@@ -389,16 +384,19 @@ fn https_error_page(original_url: &Url, https_url: &Url) -> ResourceResponse {
          </body></html>"
     );
     ResourceResponse {
-        url: original_url.to_string(),
+        url: https_url.to_string(),
         content_type: ContentType::Html,
         body: html.into_bytes(),
         status: 200,
+        headers: Vec::new(),
+        charset: None,
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::loader::is_redirect;
 
     #[test]
     fn parse_simple_response() {
@@ -466,7 +464,7 @@ mod tests {
     #[test]
     fn https_returns_error_page_without_tls() {
         let url = Url::parse("https://example.com/page").unwrap();
-        let resp = http_get(&url, None).unwrap();
+        let resp = http_get(&url, None, timeout::DEFAULT_TIMEOUT).unwrap();
         let body = String::from_utf8(resp.body).unwrap();
         assert!(body.contains("HTTPS Required"));
     }
@@ -474,7 +472,7 @@ mod tests {
     #[test]
     fn unsupported_scheme_rejected() {
         let url = Url::parse("ftp://example.com/file").unwrap();
-        let err = http_get(&url, None).unwrap_err();
+        let err = http_get(&url, None, timeout::DEFAULT_TIMEOUT).unwrap_err();
         let msg = err.to_string();
         assert!(msg.contains("unsupported scheme"));
     }
@@ -490,6 +488,29 @@ mod tests {
         assert_eq!(find_header(&resp.headers, "location"), Some("/new-page"),);
     }
 
+    #[test]
+    fn content_type_header_wins_over_the_extension() {
+        let raw = b"HTTP/1.1 200 OK\r\n\
+                     Content-Type: text/html; charset=iso-8859-1\r\n\
+                     X-Served-By: cache-7\r\n\
+                     \r\n\
+                     <p>hi</p>";
+        let url = Url::parse("http://example.com/image.png").unwrap();
+        let resp = resource_response(&url, parse_response(raw).unwrap());
+        assert_eq!(resp.content_type, ContentType::Html);
+        assert_eq!(resp.charset.as_deref(), Some("iso-8859-1"));
+        assert_eq!(
+            resp.headers[1],
+            ("X-Served-By".to_string(), "cache-7".to_string())
+        );
+        assert_eq!(resp.header("x-served-by"), Some("cache-7"));
+
+        // Without the header the extension decides.
+        let raw = b"HTTP/1.1 200 OK\r\n\r\n\x89PNG";
+        let resp = resource_response(&url, parse_response(raw).unwrap());
+        assert_eq!(resp.content_type, ContentType::Png);
+    }
+
     #[test]
     fn case_insensitive_header_lookup() {
         let headers = vec![
@@ -517,6 +538,7 @@ mod tests {
     fn is_redirect_codes() {
         assert!(is_redirect(301));
         assert!(is_redirect(302));
+        assert!(is_redirect(303));
         assert!(is_redirect(307));
         assert!(is_redirect(308));
         assert!(!is_redirect(200));
@@ -547,6 +569,57 @@ mod tests {
         assert_eq!(find_subsequence(b"no boundary", b"\r\n\r\n"), None);
     }
 
+    /// Sends its bytes one per read.
+    struct Trickle(&'static [u8]);
+
+    impl Read for Trickle {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            let Some((&byte, rest)) = self.0.split_first() else {
+                return Ok(0);
+            };
+            buf[0] = byte;
+            self.0 = rest;
+            Ok(1)
+        }
+    }
+
+    /// Has nothing to send: every read times out.
+    struct Silent;
+
+    impl Read for Silent {
+        fn read(&mut self, _buf: &mut [u8]) -> io::Result<usize> {
+            Err(io::ErrorKind::WouldBlock.into())
+        }
+    }
+
+    /// A deadline of `secs` on a clock that advances a second per look.
+    fn ticking_deadline(secs: u64) -> Deadline {
+        let mut now = Duration::ZERO;
+        Deadline::with_clock(Duration::from_secs(secs), move || {
+            now += Duration::from_secs(1);
+            now
+        })
+    }
+
+    #[test]
+    fn trickling_response_outlasts_the_deadline() {
+        // Far more than five seconds in all, but a byte every second.
+        let mut trickle = Trickle(b"HTTP/1.1 200 OK\r\n\r\nslow but steady");
+        let raw = read_response(&mut trickle, &mut ticking_deadline(5)).unwrap();
+        assert_eq!(parse_response(&raw).unwrap().body, b"slow but steady");
+    }
+
+    #[test]
+    fn read_timeout_fails_only_before_the_first_byte() {
+        let err = read_response(&mut Silent, &mut ticking_deadline(5)).unwrap_err();
+        assert!(timeout::is_timeout(&err), "got: {err}");
+
+        // A server that stalls after sending keeps what it sent.
+        let mut stalled = b"HTTP/1.1 200 OK\r\n\r\nhi".chain(Silent);
+        let raw = read_response(&mut stalled, &mut ticking_deadline(5)).unwrap();
+        assert_eq!(parse_response(&raw).unwrap().body, b"hi");
+    }
+
     #[test]
     fn http_to_https_redirect_without_tls() {
         use std::io::Write as IoWrite;
@@ -569,9 +642,17 @@ mod tests {
             let _ = stream.flush();
         });
 
-        let url = Url::parse(&format!("http://127.0.0.1:{port}/page")).unwrap();
+        let request = super::super::ResourceRequest {
+            url: format!("http://127.0.0.1:{port}/page"),
+            base_url: None,
+            source: super::super::ResourceSource::Network,
+            max_redirects: 5,
+            timeout: timeout::DEFAULT_TIMEOUT,
+            retries: timeout::DEFAULT_RETRIES,
+        };
         // No TLS provider -- redirect to HTTPS should produce error page.
-        let resp = http_get(&url, None).unwrap();
+        let vfs = oasis_vfs::MemoryVfs::new();
+        let resp = super::super::load_resource(&vfs, &request, None, None).unwrap();
         let body = String::from_utf8(resp.body).unwrap();
         assert!(
             body.contains("HTTPS Required"),
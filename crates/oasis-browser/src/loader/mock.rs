@@ -0,0 +1,95 @@
+//! In-memory mock resource loader.
+//!
+//! Serves canned [`ResourceResponse`]s keyed by URL so tests can
+//! simulate network responses -- specific status codes, content types,
+//! redirects -- without a socket or TLS provider.
+
+use std::collections::HashMap;
+
+use super::{ContentType, ResourceRequest, ResourceResponse};
+
+/// Look up the request URL in `responses`.
+///
+/// A hit returns a copy of the stored response verbatim (including
+/// non-200 statuses). A miss returns the standard "page not found"
+/// page with status 404.
+pub fn load_from_mock(
+    responses: &HashMap<String, ResourceResponse>,
+    request: &ResourceRequest,
+) -> ResourceResponse {
+    match responses.get(&request.url) {
+        Some(resp) => resp.clone(),
+        None => super::vfs::not_found_page(&request.url),
+    }
+}
+
+/// Build a mock response with the given status, content type, and body.
+pub fn response(url: &str, status: u16, content_type: ContentType, body: &str) -> ResourceResponse {
+    ResourceResponse {
+        url: url.to_string(),
+        content_type,
+        body: body.as_bytes().to_vec(),
+        status,
+        headers: Vec::new(),
+        charset: None,
+    }
+}
+
+// -----------------------------------------------------------------------
+// Tests
+// -----------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::loader::{ResourceSource, load_resource};
+    use oasis_vfs::MemoryVfs;
+
+    fn mock_request(url: &str, responses: HashMap<String, ResourceResponse>) -> ResourceRequest {
+        ResourceRequest {
+            url: url.to_string(),
+            base_url: None,
+            source: ResourceSource::Mock(responses),
+            max_redirects: 5,
+            timeout: crate::loader::timeout::DEFAULT_TIMEOUT,
+            retries: crate::loader::timeout::DEFAULT_RETRIES,
+        }
+    }
+
+    #[test]
+    fn mock_hit_returns_stored_response() {
+        let url = "http://example.com/";
+        let mut map = HashMap::new();
+        map.insert(
+            url.to_string(),
+            response(url, 200, ContentType::Html, "<p>hi</p>"),
+        );
+        let req = mock_request(url, map);
+        let resp = load_resource(&MemoryVfs::new(), &req, None, None).unwrap();
+        assert_eq!(resp.status, 200);
+        assert_eq!(resp.body, b"<p>hi</p>");
+    }
+
+    #[test]
+    fn mock_preserves_status_code() {
+        let url = "http://example.com/gone";
+        let mut map = HashMap::new();
+        map.insert(
+            url.to_string(),
+            response(url, 410, ContentType::PlainText, "gone"),
+        );
+        let req = mock_request(url, map);
+        let resp = load_resource(&MemoryVfs::new(), &req, None, None).unwrap();
+        assert_eq!(resp.status, 410);
+        assert_eq!(resp.content_type, ContentType::PlainText);
+    }
+
+    #[test]
+    fn mock_miss_returns_not_found_page() {
+        let req = mock_request("http://example.com/missing", HashMap::new());
+        let resp = load_resource(&MemoryVfs::new(), &req, None, None).unwrap();
+        assert_eq!(resp.status, 404);
+        let body = String::from_utf8(resp.body).unwrap();
+        assert!(body.contains("Page Not Found"));
+    }
+}
@@ -2,16 +2,23 @@
 //! orchestration.
 
 pub mod cache;
+pub mod charset;
 pub mod gemini_fetch;
 pub mod http;
+pub mod mock;
+pub mod timeout;
 pub mod vfs;
 
+use std::collections::HashMap;
 use std::fmt;
+use std::time::Duration;
 
 use oasis_types::error::Result;
 
+use crate::gemini::known_hosts::KnownHosts;
+
 /// How to resolve resources.
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum ResourceSource {
     /// Fetch over the network (live mode).
     Network,
@@ -19,6 +26,9 @@ pub enum ResourceSource {
     Vfs,
     /// Try VFS first, fall back to network.
     VfsThenNetwork,
+    /// Serve canned responses keyed by URL (tests). Unknown URLs get a
+    /// "page not found" response.
+    Mock(HashMap<String, ResourceResponse>),
 }
 
 /// A request for a resource.
@@ -27,15 +37,47 @@ pub struct ResourceRequest {
     pub url: String,
     pub base_url: Option<String>,
     pub source: ResourceSource,
+    /// Maximum number of 3xx redirects [`load_resource`] will follow.
+    pub max_redirects: u8,
+    /// Time each network request may wait to connect, or for the next
+    /// bytes of its response. A transfer that keeps progressing may take
+    /// longer in all.
+    pub timeout: Duration,
+    /// How many times a network request that timed out or lost its
+    /// connection is retried.
+    pub retries: u8,
 }
 
 /// A loaded resource.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct ResourceResponse {
     pub url: String,
     pub content_type: ContentType,
     pub body: Vec<u8>,
+    /// HTTP status code. Gemini failures carry their Gemini status
+    /// (40-69) here; other Gemini responses report 200.
     pub status: u16,
+    /// Response headers in the order received, as (name, value) pairs.
+    /// Only HTTP responses have any.
+    pub headers: Vec<(String, String)>,
+    /// Text encoding declared by the transport (the `charset`
+    /// parameter of an HTTP `Content-Type` or Gemini meta line).
+    pub charset: Option<String>,
+}
+
+impl ResourceResponse {
+    /// The value of header `name`, matched case-insensitively.
+    pub fn header(&self, name: &str) -> Option<&str> {
+        find_header(&self.headers, name)
+    }
+}
+
+/// Case-insensitive header lookup.
+pub(crate) fn find_header<'a>(headers: &'a [(String, String)], name: &str) -> Option<&'a str> {
+    headers
+        .iter()
+        .find(|(k, _)| k.eq_ignore_ascii_case(name))
+        .map(|(_, v)| v.as_str())
 }
 
 /// Content types the browser can handle.
@@ -48,6 +90,11 @@ pub enum ContentType {
     Bmp,
     Gif,
     GeminiText,
+    /// A Gemini input prompt (status 10/11); the body is the prompt
+    /// text.
+    GeminiInput {
+        sensitive: bool,
+    },
     PlainText,
     Unknown,
 }
@@ -84,6 +131,22 @@ impl ContentType {
         }
     }
 
+    /// The MIME type this content type is served as;
+    /// `application/octet-stream` when unknown.
+    pub fn mime(&self) -> &'static str {
+        match self {
+            ContentType::Html => "text/html",
+            ContentType::Css => "text/css",
+            ContentType::Jpeg => "image/jpeg",
+            ContentType::Png => "image/png",
+            ContentType::Bmp => "image/bmp",
+            ContentType::Gif => "image/gif",
+            ContentType::GeminiText | ContentType::GeminiInput { .. } => "text/gemini",
+            ContentType::PlainText => "text/plain",
+            ContentType::Unknown => "application/octet-stream",
+        }
+    }
+
     /// Is this an image content type?
     pub fn is_image(&self) -> bool {
         matches!(
@@ -346,6 +409,13 @@ fn resolve_path(base_dir: &str, relative: &str) -> String {
     format!("/{}", segments.join("/"))
 }
 
+/// Seconds since the Unix epoch.
+pub(crate) fn unix_now() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs())
+}
+
 /// Detect the content type for a URL by inspecting its file extension.
 /// Defaults to [`ContentType::Html`] when no extension is recognised.
 pub fn detect_content_type(url: &Url) -> ContentType {
@@ -354,25 +424,185 @@ pub fn detect_content_type(url: &Url) -> ContentType {
         .unwrap_or(ContentType::Html)
 }
 
+/// Whether a status code is a redirect the loader should follow.
+pub fn is_redirect(status: u16) -> bool {
+    matches!(status, 301 | 302 | 303 | 307 | 308)
+}
+
 /// Load a resource according to the request's [`ResourceSource`].
 ///
 /// For `Vfs` requests only the VFS is consulted. For `Network` requests
 /// the HTTP client is used directly. For `VfsThenNetwork` it tries the
-/// VFS first and falls back to the network.
+/// VFS first and falls back to the network. `Mock` requests are
+/// answered from the in-memory response map.
+///
+/// Redirect responses (301/302/303/307/308 with a `Location`) are
+/// followed by resolving the target against the current URL and
+/// re-issuing the request, up to `request.max_redirects` hops. Beyond
+/// that an error page stating "Too many redirects" is returned. Only
+/// http(s) responses are followed, and only to http(s) targets: any
+/// other redirect (a server pointing at `vfs://` or `about:`, say) gives
+/// an error page stating "Redirect to unsupported scheme".
+///
+/// A `Content-Encoding` of gzip or deflate is undone before the body is
+/// returned (see [`decode_content`]).
+///
+/// A network request failing transiently (see
+/// [`timeout::is_transient`]) is retried up to `request.retries` times.
+/// One that still goes `request.timeout` without progress gets an error
+/// page stating "Request timed out".
 ///
 /// `tls` is forwarded to the HTTP client for HTTPS support.
+/// `known_hosts` pins Gemini server certificates on first use.
 pub fn load_resource(
     vfs_backend: &dyn oasis_vfs::Vfs,
     request: &ResourceRequest,
     tls: Option<&dyn oasis_net::tls::TlsProvider>,
+    mut known_hosts: Option<&mut KnownHosts>,
+) -> Result<ResourceResponse> {
+    follow_redirects(request, |current| {
+        load_once(vfs_backend, current, tls, known_hosts.as_deref_mut())
+    })
+}
+
+/// The redirect, retry and timeout handling of [`load_resource`], with
+/// each single load performed by `load`.
+fn follow_redirects(
+    request: &ResourceRequest,
+    mut load: impl FnMut(&ResourceRequest) -> Result<ResourceResponse>,
 ) -> Result<ResourceResponse> {
-    match request.source {
+    let mut current = request.clone();
+    for _ in 0..=request.max_redirects {
+        let response = match with_retries(&current, &mut load) {
+            Err(e) if timeout::is_timeout(&e) => {
+                return Ok(vfs::error_page(&current.url, "Request timed out"));
+            },
+            result => result?,
+        };
+        let location = match response.header("location") {
+            Some(location) if is_redirect(response.status) => location,
+            _ => return Ok(decode_content(response)),
+        };
+        let base = Url::parse(&current.url).ok_or_else(|| {
+            oasis_types::error::OasisError::Backend(format!("invalid URL: {}", current.url))
+        })?;
+        let target = base.resolve(location).ok_or_else(|| {
+            oasis_types::error::OasisError::Backend(format!("bad redirect Location: {location}"))
+        })?;
+        let is_http = |scheme: &str| matches!(scheme, "http" | "https");
+        // `about:blank` has no `//` and would otherwise resolve as a path.
+        let named_scheme = explicit_scheme(location).is_some_and(|s| !is_http(&s));
+        if !is_http(&base.scheme) || !is_http(&target.scheme) || named_scheme {
+            return Ok(vfs::error_page(
+                &current.url,
+                "Redirect to unsupported scheme",
+            ));
+        }
+        current.url = target.to_string();
+    }
+    Ok(vfs::error_page(&request.url, "Too many redirects"))
+}
+
+/// The scheme a URL reference starts with (`about` in `about:blank`),
+/// lowercased, or `None` for a relative reference.
+fn explicit_scheme(reference: &str) -> Option<String> {
+    let (scheme, _) = reference.trim().split_once(':')?;
+    let mut chars = scheme.chars();
+    let valid = chars.next().is_some_and(|c| c.is_ascii_alphabetic())
+        && chars.all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.'));
+    valid.then(|| scheme.to_ascii_lowercase())
+}
+
+/// Inflate a body sent with `Content-Encoding: gzip` or `deflate`,
+/// dropping the header once it no longer applies. A corrupt or
+/// truncated stream, or an encoding this loader cannot undo, becomes an
+/// error page.
+pub fn decode_content(mut response: ResourceResponse) -> ResourceResponse {
+    use crate::image::inflate::{deflate_decompress, gzip_decompress, zlib_decompress};
+
+    let Some(encoding) = response.header("content-encoding") else {
+        return response;
+    };
+    let encoding = encoding.to_ascii_lowercase();
+    let mut body = std::mem::take(&mut response.body);
+    // Codings are listed in the order they were applied.
+    for coding in encoding.rsplit(',').map(str::trim) {
+        let limit = http::MAX_BODY_SIZE;
+        let decoded = match coding {
+            "" | "identity" => continue,
+            "gzip" | "x-gzip" => gzip_decompress(&body, limit),
+            // Meant to be zlib-wrapped, but some servers send raw DEFLATE.
+            "deflate" => zlib_decompress(&body, limit).or_else(|| deflate_decompress(&body, limit)),
+            other => {
+                let message = format!("Unsupported content encoding: {other}");
+                return vfs::error_page(&response.url, &message);
+            },
+        };
+        let Some(decoded) = decoded else {
+            let message = format!("Corrupt {coding} content encoding");
+            return vfs::error_page(&response.url, &message);
+        };
+        body = decoded;
+    }
+    response.body = body;
+    response
+        .headers
+        .retain(|(name, _)| !name.eq_ignore_ascii_case("content-encoding"));
+    response
+}
+
+/// Perform a single load, retrying transient network failures.
+fn with_retries(
+    request: &ResourceRequest,
+    load: &mut impl FnMut(&ResourceRequest) -> Result<ResourceResponse>,
+) -> Result<ResourceResponse> {
+    let mut attempt = 0;
+    loop {
+        match load(request) {
+            Err(e) if attempt < request.retries && timeout::is_transient(&e) => {
+                attempt += 1;
+                log::info!("retrying {} ({e})", request.url);
+            },
+            result => return result,
+        }
+    }
+}
+
+/// Perform a single load without following redirects.
+fn load_once(
+    vfs_backend: &dyn oasis_vfs::Vfs,
+    request: &ResourceRequest,
+    tls: Option<&dyn oasis_net::tls::TlsProvider>,
+    known_hosts: Option<&mut KnownHosts>,
+) -> Result<ResourceResponse> {
+    // `about:` pages never come from the VFS or the network.
+    if request.url.starts_with("about:") {
+        return Ok(about_page(&request.url));
+    }
+    match &request.source {
         ResourceSource::Vfs => vfs::load_from_vfs(vfs_backend, request),
-        ResourceSource::Network => load_from_network(request, tls),
+        ResourceSource::Network => load_from_network(request, tls, known_hosts),
         ResourceSource::VfsThenNetwork => match vfs::load_from_vfs(vfs_backend, request) {
             Ok(resp) => Ok(resp),
-            Err(_) => load_from_network(request, tls),
+            Err(_) => load_from_network(request, tls, known_hosts),
         },
+        ResourceSource::Mock(responses) => Ok(mock::load_from_mock(responses, request)),
+    }
+}
+
+/// Answer an `about:` URL the widget did not handle itself:
+/// `about:blank` is an empty page, anything else an error page.
+fn about_page(url: &str) -> ResourceResponse {
+    if url != "about:blank" {
+        return vfs::error_page(url, "Unknown about: page");
+    }
+    ResourceResponse {
+        url: url.to_string(),
+        content_type: ContentType::Html,
+        body: b"<html><body></body></html>".to_vec(),
+        status: 200,
+        headers: Vec::new(),
+        charset: None,
     }
 }
 
@@ -380,14 +610,15 @@ pub fn load_resource(
 fn load_from_network(
     request: &ResourceRequest,
     tls: Option<&dyn oasis_net::tls::TlsProvider>,
+    known_hosts: Option<&mut KnownHosts>,
 ) -> Result<ResourceResponse> {
     let url = Url::parse(&request.url).ok_or_else(|| {
         oasis_types::error::OasisError::Backend(format!("invalid URL: {}", request.url,))
     })?;
 
     match url.scheme.as_str() {
-        "http" | "https" => http::http_get(&url, tls),
-        "gemini" => gemini_fetch::gemini_get(&url, tls),
+        "http" | "https" => http::http_get(&url, tls, request.timeout),
+        "gemini" => gemini_fetch::gemini_get(&url, tls, known_hosts, request.timeout),
         scheme => Err(oasis_types::error::OasisError::Backend(format!(
             "unsupported network scheme: {scheme}",
         ))),
@@ -584,4 +815,249 @@ mod tests {
         let resolved = base.resolve("").unwrap();
         assert_eq!(resolved, base);
     }
+
+    // -- redirects ---------------------------------------------------------
+
+    fn redirect(url: &str, status: u16, location: &str) -> ResourceResponse {
+        ResourceResponse {
+            headers: vec![("Location".to_string(), location.to_string())],
+            ..mock::response(url, status, ContentType::Html, "")
+        }
+    }
+
+    fn mock_load(url: &str, responses: Vec<ResourceResponse>) -> ResourceResponse {
+        let map = responses.into_iter().map(|r| (r.url.clone(), r)).collect();
+        let request = ResourceRequest {
+            url: url.to_string(),
+            base_url: None,
+            source: ResourceSource::Mock(map),
+            max_redirects: 5,
+            timeout: timeout::DEFAULT_TIMEOUT,
+            retries: timeout::DEFAULT_RETRIES,
+        };
+        load_resource(&oasis_vfs::MemoryVfs::new(), &request, None, None).unwrap()
+    }
+
+    #[test]
+    fn follows_relative_redirect() {
+        let resp = mock_load(
+            "http://example.com/old/page.html",
+            vec![
+                redirect("http://example.com/old/page.html", 301, "../new/page.html"),
+                mock::response(
+                    "http://example.com/new/page.html",
+                    200,
+                    ContentType::Html,
+                    "ok",
+                ),
+            ],
+        );
+        assert_eq!(resp.status, 200);
+        assert_eq!(resp.url, "http://example.com/new/page.html");
+        assert_eq!(resp.body, b"ok");
+    }
+
+    #[test]
+    fn follows_redirect_chain_with_all_codes() {
+        let resp = mock_load(
+            "http://a.example/1",
+            vec![
+                redirect("http://a.example/1", 301, "/2"),
+                redirect("http://a.example/2", 302, "/3"),
+                redirect("http://a.example/3", 303, "/4"),
+                redirect("http://a.example/4", 307, "http://b.example/5"),
+                redirect("http://b.example/5", 308, "/done"),
+                mock::response("http://b.example/done", 200, ContentType::Html, "done"),
+            ],
+        );
+        assert_eq!(resp.status, 200);
+        assert_eq!(resp.url, "http://b.example/done");
+    }
+
+    #[test]
+    fn redirect_loop_gives_too_many_redirects_page() {
+        let resp = mock_load(
+            "http://example.com/a",
+            vec![
+                redirect("http://example.com/a", 302, "/b"),
+                redirect("http://example.com/b", 302, "/a"),
+            ],
+        );
+        let body = String::from_utf8(resp.body).unwrap();
+        assert!(body.contains("Too many redirects"), "got: {body}");
+        assert_eq!(resp.url, "http://example.com/a");
+    }
+
+    #[test]
+    fn redirects_to_other_schemes_are_not_followed() {
+        for location in ["vfs://home/.schedule", "about:blank"] {
+            let resp = mock_load(
+                "http://example.com/a",
+                vec![
+                    redirect("http://example.com/a", 302, location),
+                    mock::response(location, 200, ContentType::Html, "secret"),
+                ],
+            );
+            let body = String::from_utf8(resp.body).unwrap();
+            assert!(
+                body.contains("Redirect to unsupported scheme"),
+                "got: {body}"
+            );
+            assert!(!body.contains("secret"));
+            assert_eq!(resp.url, "http://example.com/a");
+        }
+    }
+
+    #[test]
+    fn redirect_status_without_location_is_returned() {
+        let resp = mock_load(
+            "http://example.com/a",
+            vec![mock::response(
+                "http://example.com/a",
+                301,
+                ContentType::Html,
+                "moved",
+            )],
+        );
+        assert_eq!(resp.status, 301);
+    }
+
+    #[test]
+    fn location_on_non_redirect_status_is_ignored() {
+        let resp = mock_load(
+            "http://example.com/a",
+            vec![redirect("http://example.com/a", 200, "/b")],
+        );
+        assert_eq!(resp.status, 200);
+        assert_eq!(resp.url, "http://example.com/a");
+    }
+
+    // -- Content-Encoding --------------------------------------------------
+
+    /// `<p>hi</p>`, gzipped.
+    const GZIP_HI: [u8; 29] = [
+        0x1F, 0x8B, 0x08, 0x00, 0x00, 0x00, 0x00, 0x00, 0x02, 0x03, 0xB3, 0x29, 0xB0, 0xCB, 0xC8,
+        0xB4, 0xD1, 0x2F, 0xB0, 0x03, 0x00, 0x65, 0xD2, 0x37, 0x6D, 0x09, 0x00, 0x00, 0x00,
+    ];
+
+    fn encoded(encoding: &str, body: &[u8]) -> ResourceResponse {
+        ResourceResponse {
+            body: body.to_vec(),
+            headers: vec![("Content-Encoding".to_string(), encoding.to_string())],
+            ..mock::response("http://example.com/", 200, ContentType::Html, "")
+        }
+    }
+
+    #[test]
+    fn gzip_body_is_inflated_before_it_is_returned() {
+        let resp = mock_load("http://example.com/", vec![encoded("gzip", &GZIP_HI)]);
+        assert_eq!(resp.status, 200);
+        assert_eq!(resp.body, b"<p>hi</p>");
+        assert_eq!(resp.header("content-encoding"), None);
+    }
+
+    #[test]
+    fn deflate_accepts_zlib_and_raw_streams() {
+        let raw = &GZIP_HI[10..21];
+        let mut zlib = vec![0x78, 0x9C];
+        zlib.extend_from_slice(raw);
+        zlib.extend_from_slice(&[0x0E, 0x6F, 0x02, 0xD5]);
+        for body in [&zlib[..], raw] {
+            let resp = decode_content(encoded("Deflate", body));
+            assert_eq!(resp.body, b"<p>hi</p>");
+        }
+        let resp = decode_content(encoded("identity", b"plain"));
+        assert_eq!(resp.body, b"plain");
+    }
+
+    #[test]
+    fn corrupt_or_unknown_encodings_become_error_pages() {
+        let truncated = mock_load("http://example.com/", vec![encoded("gzip", &GZIP_HI[..20])]);
+        assert_eq!(truncated.status, 500);
+        let body = String::from_utf8(truncated.body).unwrap();
+        assert!(
+            body.contains("Corrupt gzip content encoding"),
+            "got: {body}"
+        );
+
+        let brotli = decode_content(encoded("br", b"..."));
+        assert_eq!(brotli.status, 500);
+        let body = String::from_utf8(brotli.body).unwrap();
+        assert!(
+            body.contains("Unsupported content encoding: br"),
+            "got: {body}"
+        );
+    }
+
+    // -- about: pages ------------------------------------------------------
+
+    #[test]
+    fn about_urls_bypass_vfs_and_network() {
+        let blank = mock_load("about:blank", Vec::new());
+        assert_eq!(blank.status, 200);
+        assert_eq!(blank.content_type, ContentType::Html);
+
+        let unknown = mock_load("about:nothing", Vec::new());
+        assert_eq!(unknown.status, 500);
+        assert!(
+            String::from_utf8(unknown.body)
+                .unwrap()
+                .contains("Unknown about: page")
+        );
+    }
+
+    // -- timeouts ----------------------------------------------------------
+
+    fn slow_request() -> ResourceRequest {
+        ResourceRequest {
+            url: "http://example.com/slow".into(),
+            base_url: None,
+            source: ResourceSource::Network,
+            max_redirects: 5,
+            timeout: timeout::DEFAULT_TIMEOUT,
+            retries: 1,
+        }
+    }
+
+    #[test]
+    fn silent_server_times_out_after_one_retry() {
+        let mut attempts = 0;
+        let resp = follow_redirects(&slow_request(), |_| {
+            attempts += 1;
+            Err(timeout::timed_out())
+        })
+        .unwrap();
+        assert_eq!(resp.status, 500);
+        let body = String::from_utf8(resp.body).unwrap();
+        assert!(body.contains("Request timed out"), "got: {body}");
+        assert_eq!(attempts, 2);
+    }
+
+    #[test]
+    fn dropped_connection_is_retried() {
+        let mut attempts = 0;
+        let resp = follow_redirects(&slow_request(), |req| {
+            attempts += 1;
+            if attempts == 1 {
+                let reset = std::io::Error::from(std::io::ErrorKind::ConnectionReset);
+                return Err(timeout::io_error("read response", &reset));
+            }
+            Ok(mock::response(&req.url, 200, ContentType::Html, "made it"))
+        })
+        .unwrap();
+        assert_eq!(resp.status, 200);
+        assert_eq!(attempts, 2);
+    }
+
+    #[test]
+    fn permanent_failures_are_not_retried() {
+        let mut attempts = 0;
+        let err = follow_redirects(&slow_request(), |_| {
+            attempts += 1;
+            Err(oasis_types::error::OasisError::Backend("bad URL".into()))
+        })
+        .unwrap_err();
+        assert!(!timeout::is_transient(&err));
+        assert_eq!(attempts, 1);
+    }
 }
@@ -0,0 +1,152 @@
+//! Request time limits and transient-failure detection.
+//!
+//! A [`Deadline`] bounds how long a network request may go without
+//! progress: connecting, and then the wait for each next chunk of the
+//! response. A server that accepts the connection and then goes silent
+//! cannot hold the browser forever, while a slow transfer that keeps
+//! delivering bytes -- a large page or download over PSP WiFi -- is never
+//! cut off. Its clock can be injected, letting tests step time instead of
+//! sleeping.
+
+use std::io;
+use std::time::{Duration, Instant};
+
+use oasis_types::error::{OasisError, Result};
+
+/// Time a request may wait for a connection, or for the next bytes of
+/// its response, unless configured otherwise.
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(15);
+
+/// Retries after a transient failure unless configured otherwise.
+pub const DEFAULT_RETRIES: u8 = 1;
+
+/// The idle time budget of one request.
+pub struct Deadline {
+    timeout: Duration,
+    elapsed: Box<dyn FnMut() -> Duration>,
+    /// Clock reading when the request last made progress.
+    progressed_at: Duration,
+}
+
+impl Deadline {
+    /// Start an idle budget of `timeout` on the system clock.
+    pub fn start(timeout: Duration) -> Self {
+        let started = Instant::now();
+        Self::with_clock(timeout, move || started.elapsed())
+    }
+
+    /// Start an idle budget of `timeout` measured by `elapsed`, which
+    /// reports the time passed since the request began.
+    pub fn with_clock(timeout: Duration, elapsed: impl FnMut() -> Duration + 'static) -> Self {
+        let mut elapsed: Box<dyn FnMut() -> Duration> = Box::new(elapsed);
+        Self {
+            timeout,
+            progressed_at: elapsed(),
+            elapsed,
+        }
+    }
+
+    /// Note that the request made progress -- it connected, or bytes
+    /// arrived -- giving it the full budget again.
+    pub fn progress(&mut self) {
+        self.progressed_at = (self.elapsed)();
+    }
+
+    /// The time left until the request has gone `timeout` without
+    /// progress, or a timeout error once none is.
+    pub fn remaining(&mut self) -> Result<Duration> {
+        let idle = (self.elapsed)().saturating_sub(self.progressed_at);
+        self.timeout
+            .checked_sub(idle)
+            .filter(|left| !left.is_zero())
+            .ok_or_else(timed_out)
+    }
+}
+
+/// The error a request that ran out of time fails with.
+pub fn timed_out() -> OasisError {
+    OasisError::Io(io::Error::new(io::ErrorKind::TimedOut, "request timed out"))
+}
+
+/// Wrap a socket error as an I/O error, keeping its kind so
+/// [`is_transient`] can classify it.
+pub fn io_error(context: &str, e: &io::Error) -> OasisError {
+    OasisError::Io(io::Error::new(e.kind(), format!("{context}: {e}")))
+}
+
+/// Whether `e` is a request running out of time.
+pub fn is_timeout(e: &OasisError) -> bool {
+    matches!(e, OasisError::Io(e)
+        if matches!(e.kind(), io::ErrorKind::TimedOut | io::ErrorKind::WouldBlock))
+}
+
+/// Whether `e` is a network failure worth retrying: a timeout or a
+/// connection that dropped or never came up, as happens on a flaky
+/// WiFi link.
+pub fn is_transient(e: &OasisError) -> bool {
+    use io::ErrorKind::*;
+
+    matches!(e, OasisError::Io(e) if matches!(
+        e.kind(),
+        TimedOut
+            | WouldBlock
+            | Interrupted
+            | ConnectionRefused
+            | ConnectionReset
+            | ConnectionAborted
+            | NotConnected
+            | BrokenPipe
+            | UnexpectedEof
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::Cell;
+    use std::rc::Rc;
+
+    use super::*;
+
+    #[test]
+    fn deadline_runs_out_on_the_injected_clock() {
+        let now = Rc::new(Cell::new(Duration::ZERO));
+        let clock = Rc::clone(&now);
+        let mut deadline = Deadline::with_clock(Duration::from_secs(5), move || clock.get());
+
+        assert_eq!(deadline.remaining().unwrap(), Duration::from_secs(5));
+        now.set(Duration::from_secs(3));
+        assert_eq!(deadline.remaining().unwrap(), Duration::from_secs(2));
+        now.set(Duration::from_secs(5));
+        assert!(is_timeout(&deadline.remaining().unwrap_err()));
+        now.set(Duration::from_secs(60));
+        assert!(is_timeout(&deadline.remaining().unwrap_err()));
+    }
+
+    #[test]
+    fn progress_restarts_the_budget() {
+        let now = Rc::new(Cell::new(Duration::from_secs(100)));
+        let clock = Rc::clone(&now);
+        let mut deadline = Deadline::with_clock(Duration::from_secs(5), move || clock.get());
+
+        // Twelve seconds in all, but never four without progress.
+        for _ in 0..3 {
+            now.set(now.get() + Duration::from_secs(4));
+            assert_eq!(deadline.remaining().unwrap(), Duration::from_secs(1));
+            deadline.progress();
+        }
+        now.set(now.get() + Duration::from_secs(5));
+        assert!(is_timeout(&deadline.remaining().unwrap_err()));
+    }
+
+    #[test]
+    fn only_network_failures_are_transient() {
+        let reset = io::Error::from(io::ErrorKind::ConnectionReset);
+        assert!(is_transient(&timed_out()));
+        assert!(is_transient(&io_error("read response", &reset)));
+        assert!(!is_timeout(&io_error("read response", &reset)));
+
+        let denied = io::Error::from(io::ErrorKind::PermissionDenied);
+        assert!(!is_transient(&io_error("connect", &denied)));
+        assert!(!is_transient(&OasisError::Backend("bad URL".into())));
+    }
+}
@@ -26,6 +26,8 @@ pub fn load_from_vfs(vfs: &dyn Vfs, request: &ResourceRequest) -> Result<Resourc
         content_type,
         body,
         status: 200,
+        headers: Vec::new(),
+        charset: None,
     })
 }
 
@@ -83,6 +85,8 @@ pub fn not_found_page(url: &str) -> ResourceResponse {
         content_type: ContentType::Html,
         body: html.into_bytes(),
         status: 404,
+        headers: Vec::new(),
+        charset: None,
     }
 }
 
@@ -99,6 +103,8 @@ pub fn error_page(url: &str, message: &str) -> ResourceResponse {
         content_type: ContentType::Html,
         body: html.into_bytes(),
         status: 500,
+        headers: Vec::new(),
+        charset: None,
     }
 }
 
@@ -150,6 +156,9 @@ mod tests {
             url: "http://example.com/index.html".to_string(),
             base_url: None,
             source: ResourceSource::Vfs,
+            max_redirects: 5,
+            timeout: crate::loader::timeout::DEFAULT_TIMEOUT,
+            retries: crate::loader::timeout::DEFAULT_RETRIES,
         };
         let resp = load_from_vfs(&vfs, &req).unwrap();
         assert_eq!(resp.status, 200);
@@ -237,6 +246,9 @@ mod tests {
             url: "http://example.com/missing.html".to_string(),
             base_url: None,
             source: ResourceSource::Vfs,
+            max_redirects: 5,
+            timeout: crate::loader::timeout::DEFAULT_TIMEOUT,
+            retries: crate::loader::timeout::DEFAULT_RETRIES,
         };
         assert!(load_from_vfs(&vfs, &req).is_err());
     }
@@ -248,6 +260,9 @@ mod tests {
             url: "vfs://pages/about.html".to_string(),
             base_url: None,
             source: ResourceSource::Vfs,
+            max_redirects: 5,
+            timeout: crate::loader::timeout::DEFAULT_TIMEOUT,
+            retries: crate::loader::timeout::DEFAULT_RETRIES,
         };
         let resp = load_from_vfs(&vfs, &req).unwrap();
         assert_eq!(resp.status, 200);
@@ -1,4 +1,31 @@
 //! Navigation controller: history stack, bookmarks, URL bar state.
+//!
+//! [`Bookmarks`] are kept apart from the per-tab controller, as one
+//! list shared by every tab. They can be persisted to the VFS as a
+//! small line-based file:
+//! a comment header followed by one `url<TAB>title` line per bookmark,
+//! with tabs, newlines, and backslashes in either field escaped as
+//! `\t`, `\n`, and `\\`.
+//!
+//! Besides each tab's back/forward list there is the browsing
+//! [`History`], likewise shared by every tab: one [`Visit`] per URL,
+//! oldest first, capped at a configurable length. It is persisted the
+//! same way, one `url<TAB>title<TAB>visits<TAB>last_visit` line per
+//! page.
+
+use oasis_types::error::Result;
+use oasis_vfs::Vfs;
+
+use crate::loader::Url;
+
+/// First line written to a bookmarks file.
+const BOOKMARKS_HEADER: &str = "# OASIS browser bookmarks";
+
+/// First line written to a history file.
+const HISTORY_HEADER: &str = "# OASIS browser history";
+
+/// Default cap on the number of pages kept in the browsing history.
+pub const DEFAULT_MAX_VISITS: usize = 500;
 
 /// A single entry in the navigation history.
 #[derive(Debug, Clone)]
@@ -17,60 +44,72 @@ pub struct Bookmark {
     pub title: String,
 }
 
-/// Navigation controller managing history and bookmarks.
+/// A page in the browsing history.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Visit {
+    pub url: String,
+    pub title: String,
+    /// Number of times the page was visited.
+    pub visits: u32,
+    /// Time of the latest visit, in seconds since the Unix epoch.
+    pub last_visit: u64,
+}
+
+/// Navigation controller managing the history of one tab.
+///
+/// History is a single chronological list: entries before `current`
+/// form the back stack and entries after it the forward stack.
 pub struct NavigationController {
-    back_stack: Vec<HistoryEntry>,
-    forward_stack: Vec<HistoryEntry>,
-    current: Option<HistoryEntry>,
+    entries: Vec<HistoryEntry>,
+    current: Option<usize>,
     home_url: String,
-    bookmarks: Vec<Bookmark>,
 }
 
 impl NavigationController {
     pub fn new(home_url: &str) -> Self {
         Self {
-            back_stack: Vec::new(),
-            forward_stack: Vec::new(),
+            entries: Vec::new(),
             current: None,
             home_url: home_url.to_string(),
-            bookmarks: Vec::new(),
         }
     }
 
     /// Navigate to a new URL. Pushes current page to back stack,
     /// clears forward stack.
     pub fn navigate(&mut self, url: &str, title: &str) {
-        if let Some(entry) = self.current.take() {
-            self.back_stack.push(entry);
-        }
-        self.forward_stack.clear();
-        self.current = Some(HistoryEntry {
+        let keep = self.current.map_or(0, |i| i + 1);
+        self.entries.truncate(keep);
+        self.entries.push(HistoryEntry {
             url: url.to_string(),
             title: title.to_string(),
             scroll_y: 0,
             reader_mode: false,
             timestamp: 0,
         });
+        self.current = Some(self.entries.len() - 1);
     }
 
     /// Go back in history. Returns the URL to load, or None.
     pub fn go_back(&mut self) -> Option<HistoryEntry> {
-        let prev = self.back_stack.pop()?;
-        if let Some(current) = self.current.take() {
-            self.forward_stack.push(current);
-        }
-        self.current = Some(prev.clone());
-        Some(prev)
+        let index = self.current?.checked_sub(1)?;
+        self.go_to_entry(index)
     }
 
     /// Go forward in history. Returns the URL to load, or None.
     pub fn go_forward(&mut self) -> Option<HistoryEntry> {
-        let next = self.forward_stack.pop()?;
-        if let Some(current) = self.current.take() {
-            self.back_stack.push(current);
-        }
-        self.current = Some(next.clone());
-        Some(next)
+        let index = self.current? + 1;
+        self.go_to_entry(index)
+    }
+
+    /// Jump to the entry at `index` in [`history_entries`], keeping
+    /// the rest of the history intact. Returns the entry to load, or
+    /// `None` if the index is out of range.
+    ///
+    /// [`history_entries`]: Self::history_entries
+    pub fn go_to_entry(&mut self, index: usize) -> Option<HistoryEntry> {
+        let entry = self.entries.get(index)?.clone();
+        self.current = Some(index);
+        Some(entry)
     }
 
     /// Navigate to the home page.
@@ -82,75 +121,65 @@ impl NavigationController {
 
     /// Get the current URL, if any.
     pub fn current_url(&self) -> Option<&str> {
-        self.current.as_ref().map(|e| e.url.as_str())
+        self.current_entry().map(|e| e.url.as_str())
     }
 
     /// Get the current page title, if any.
     pub fn current_title(&self) -> Option<&str> {
-        self.current.as_ref().map(|e| e.title.as_str())
+        self.current_entry().map(|e| e.title.as_str())
     }
 
     /// Update the current page's scroll position (for restoring
     /// on back-nav).
     pub fn update_scroll(&mut self, scroll_y: i32) {
-        if let Some(entry) = self.current.as_mut() {
+        if let Some(entry) = self.current_entry_mut() {
             entry.scroll_y = scroll_y;
         }
     }
 
     /// Update the current page's reader mode state.
     pub fn update_reader_mode(&mut self, reader_mode: bool) {
-        if let Some(entry) = self.current.as_mut() {
+        if let Some(entry) = self.current_entry_mut() {
             entry.reader_mode = reader_mode;
         }
     }
 
     /// Update the current page's title (after page load completes).
     pub fn update_title(&mut self, title: &str) {
-        if let Some(entry) = self.current.as_mut() {
+        if let Some(entry) = self.current_entry_mut() {
             entry.title = title.to_string();
         }
     }
 
     /// Check if back navigation is possible.
     pub fn can_go_back(&self) -> bool {
-        !self.back_stack.is_empty()
+        self.current.is_some_and(|i| i > 0)
     }
 
     /// Check if forward navigation is possible.
     pub fn can_go_forward(&self) -> bool {
-        !self.forward_stack.is_empty()
+        self.current.is_some_and(|i| i + 1 < self.entries.len())
     }
 
-    /// Add a bookmark for the current page.
-    pub fn add_bookmark(&mut self) {
-        if let Some(entry) = &self.current {
-            let bm = Bookmark {
-                url: entry.url.clone(),
-                title: entry.title.clone(),
-            };
-            if !self.bookmarks.contains(&bm) {
-                self.bookmarks.push(bm);
-            }
-        }
+    /// All history entries in chronological order, including pages
+    /// that can be reached with forward navigation.
+    pub fn history_entries(&self) -> &[HistoryEntry] {
+        &self.entries
     }
 
-    /// Remove a bookmark by URL.
-    pub fn remove_bookmark(&mut self, url: &str) {
-        self.bookmarks.retain(|bm| bm.url != url);
+    /// Index of the current page within [`history_entries`].
+    ///
+    /// [`history_entries`]: Self::history_entries
+    pub fn current_index(&self) -> Option<usize> {
+        self.current
     }
 
-    /// Check if the current URL is bookmarked.
-    pub fn is_bookmarked(&self) -> bool {
-        let Some(entry) = &self.current else {
-            return false;
-        };
-        self.bookmarks.iter().any(|bm| bm.url == entry.url)
+    fn current_entry(&self) -> Option<&HistoryEntry> {
+        self.current.and_then(|i| self.entries.get(i))
     }
 
-    /// Get all bookmarks.
-    pub fn bookmarks(&self) -> &[Bookmark] {
-        &self.bookmarks
+    fn current_entry_mut(&mut self) -> Option<&mut HistoryEntry> {
+        self.current.and_then(|i| self.entries.get_mut(i))
     }
 
     /// Get history entries (most recent first).
@@ -158,14 +187,8 @@ impl NavigationController {
     /// Returns the current page followed by back-stack entries in
     /// reverse chronological order.
     pub fn history(&self) -> Vec<&HistoryEntry> {
-        let mut entries: Vec<&HistoryEntry> = Vec::new();
-        if let Some(current) = &self.current {
-            entries.push(current);
-        }
-        for entry in self.back_stack.iter().rev() {
-            entries.push(entry);
-        }
-        entries
+        let end = self.current.map_or(0, |i| i + 1);
+        self.entries[..end].iter().rev().collect()
     }
 
     /// Set the home URL.
@@ -177,53 +200,269 @@ impl NavigationController {
     pub fn home_url(&self) -> &str {
         &self.home_url
     }
+}
 
-    /// Generate HTML for the bookmarks page.
-    pub fn bookmarks_page_html(&self) -> String {
-        let mut html = String::from(
-            "<html><head><title>Bookmarks</title></head><body>\
-             <h1>Bookmarks</h1><ul>",
-        );
-        for bm in &self.bookmarks {
-            html.push_str(&format!(
-                "<li><a href=\"{}\">{}</a></li>",
-                bm.url,
-                if bm.title.is_empty() {
-                    &bm.url
-                } else {
-                    &bm.title
-                }
-            ));
+/// The browsing history, shared by every tab of a browser.
+#[derive(Debug, Clone)]
+pub struct History {
+    /// Least recently visited first.
+    list: Vec<Visit>,
+    max: usize,
+}
+
+impl Default for History {
+    fn default() -> Self {
+        Self {
+            list: Vec::new(),
+            max: DEFAULT_MAX_VISITS,
         }
-        html.push_str("</ul></body></html>");
-        html
     }
+}
 
-    /// Generate HTML for the history page.
-    pub fn history_page_html(&self) -> String {
-        let mut html = String::from(
-            "<html><head><title>History</title></head><body>\
-             <h1>History</h1><ul>",
-        );
-        for entry in self.history() {
-            html.push_str(&format!(
-                "<li><a href=\"{}\">{}</a></li>",
-                entry.url,
-                if entry.title.is_empty() {
-                    &entry.url
-                } else {
-                    &entry.title
-                }
+impl History {
+    /// Record a visit to `url` at time `now` in the browsing history,
+    /// dropping the least recently visited pages beyond the cap.
+    pub fn record(&mut self, url: &str, title: &str, now: u64) {
+        let mut visit = match self.list.iter().position(|v| v.url == url) {
+            Some(i) => self.list.remove(i),
+            None => Visit {
+                url: url.to_string(),
+                title: String::new(),
+                visits: 0,
+                last_visit: 0,
+            },
+        };
+        if !title.is_empty() {
+            visit.title = title.to_string();
+        }
+        visit.visits = visit.visits.saturating_add(1);
+        visit.last_visit = now;
+        self.list.push(visit);
+        self.prune();
+    }
+
+    /// Browsing history, least recently visited first.
+    pub fn visits(&self) -> &[Visit] {
+        &self.list
+    }
+
+    /// Replace the browsing history.
+    pub fn set_visits(&mut self, visits: Vec<Visit>) {
+        self.list = visits;
+        self.prune();
+    }
+
+    /// Forget the browsing history.
+    pub fn clear(&mut self) {
+        self.list.clear();
+    }
+
+    /// Set the maximum number of pages kept in the browsing history.
+    pub fn set_max(&mut self, max: usize) {
+        self.max = max;
+        self.prune();
+    }
+
+    fn prune(&mut self) {
+        let excess = self.list.len().saturating_sub(self.max);
+        self.list.drain(..excess);
+    }
+
+    /// Write the browsing history to `path` in the VFS, creating the
+    /// parent directory if needed.
+    pub fn save_to_vfs(&self, vfs: &mut dyn Vfs, path: &str) -> Result<()> {
+        if let Some((parent, _)) = path.rsplit_once('/')
+            && !parent.is_empty()
+            && !vfs.exists(parent)
+        {
+            vfs.mkdir(parent)?;
+        }
+        let mut out = String::from(HISTORY_HEADER);
+        out.push('\n');
+        for visit in &self.list {
+            out.push_str(&format!(
+                "{}\t{}\t{}\t{}\n",
+                escape_field(&visit.url),
+                escape_field(&visit.title),
+                visit.visits,
+                visit.last_visit
             ));
         }
-        html.push_str("</ul></body></html>");
-        html
+        vfs.write(path, out.as_bytes())
+    }
+
+    /// Replace the browsing history with the contents of `path`.
+    ///
+    /// A missing file leaves the history untouched. Malformed lines are
+    /// skipped, and entries beyond the cap are dropped oldest first.
+    pub fn load_from_vfs(&mut self, vfs: &dyn Vfs, path: &str) -> Result<()> {
+        if !vfs.exists(path) {
+            return Ok(());
+        }
+        let data = vfs.read(path)?;
+        let text = String::from_utf8_lossy(&data);
+        let mut visits: Vec<Visit> = Vec::new();
+        for line in text.lines() {
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let mut fields = line.split('\t');
+            let (Some(url), Some(title), Some(count), Some(last), None) = (
+                fields.next(),
+                fields.next(),
+                fields.next(),
+                fields.next(),
+                fields.next(),
+            ) else {
+                log::warn!("skipping malformed history line in {path}");
+                continue;
+            };
+            let url = unescape_field(url);
+            let (Ok(count), Ok(last_visit)) = (count.parse(), last.parse()) else {
+                log::warn!("skipping malformed history line in {path}");
+                continue;
+            };
+            if Url::parse(&url).is_none_or(|u| u.scheme.is_empty()) {
+                log::warn!("skipping malformed history line in {path}");
+                continue;
+            }
+            visits.retain(|v| v.url != url);
+            visits.push(Visit {
+                url,
+                title: unescape_field(title),
+                visits: count,
+                last_visit,
+            });
+        }
+        self.set_visits(visits);
+        Ok(())
+    }
+}
+
+/// The bookmark list, shared by every tab of a browser.
+#[derive(Debug, Clone, Default)]
+pub struct Bookmarks {
+    list: Vec<Bookmark>,
+}
+
+impl Bookmarks {
+    /// Bookmark `url` under `title`. Adding a bookmark twice is a no-op.
+    pub fn add(&mut self, url: &str, title: &str) {
+        if !self.contains(url) {
+            self.list.push(Bookmark {
+                url: url.to_string(),
+                title: title.to_string(),
+            });
+        }
     }
+
+    /// Remove a bookmark by URL.
+    pub fn remove(&mut self, url: &str) {
+        self.list.retain(|bm| bm.url != url);
+    }
+
+    /// Check if `url` is bookmarked.
+    pub fn contains(&self, url: &str) -> bool {
+        self.list.iter().any(|bm| bm.url == url)
+    }
+
+    /// Get all bookmarks.
+    pub fn list(&self) -> &[Bookmark] {
+        &self.list
+    }
+
+    /// Write the bookmark list to `path` in the VFS, creating the
+    /// parent directory if needed.
+    pub fn save_to_vfs(&self, vfs: &mut dyn Vfs, path: &str) -> Result<()> {
+        if let Some((parent, _)) = path.rsplit_once('/')
+            && !parent.is_empty()
+            && !vfs.exists(parent)
+        {
+            vfs.mkdir(parent)?;
+        }
+        let mut out = String::from(BOOKMARKS_HEADER);
+        out.push('\n');
+        for bm in &self.list {
+            out.push_str(&escape_field(&bm.url));
+            out.push('\t');
+            out.push_str(&escape_field(&bm.title));
+            out.push('\n');
+        }
+        vfs.write(path, out.as_bytes())
+    }
+
+    /// Replace the bookmark list with the contents of `path`.
+    ///
+    /// A missing file leaves the list untouched. Lines that do not hold
+    /// a parseable URL (e.g. from a truncated or corrupt file) are
+    /// skipped rather than failing the whole load.
+    pub fn load_from_vfs(&mut self, vfs: &dyn Vfs, path: &str) -> Result<()> {
+        if !vfs.exists(path) {
+            return Ok(());
+        }
+        let data = vfs.read(path)?;
+        let text = String::from_utf8_lossy(&data);
+        let mut bookmarks = Bookmarks::default();
+        for line in text.lines() {
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let (url, title) = line.split_once('\t').unwrap_or((line, ""));
+            let url = unescape_field(url);
+            if Url::parse(&url).is_none_or(|u| u.scheme.is_empty()) {
+                log::warn!("skipping malformed bookmark line in {path}");
+                continue;
+            }
+            bookmarks.add(&url, &unescape_field(title));
+        }
+        *self = bookmarks;
+        Ok(())
+    }
+}
+
+/// Escape a bookmark field for the line-based file format.
+fn escape_field(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for ch in s.chars() {
+        match ch {
+            '\\' => out.push_str("\\\\"),
+            '\t' => out.push_str("\\t"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            _ => out.push(ch),
+        }
+    }
+    out
+}
+
+/// Reverse [`escape_field`]. Unknown escapes are kept verbatim.
+fn unescape_field(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    let mut chars = s.chars();
+    while let Some(ch) = chars.next() {
+        if ch != '\\' {
+            out.push(ch);
+            continue;
+        }
+        match chars.next() {
+            Some('t') => out.push('\t'),
+            Some('n') => out.push('\n'),
+            Some('r') => out.push('\r'),
+            Some('\\') => out.push('\\'),
+            Some(other) => {
+                out.push('\\');
+                out.push(other);
+            },
+            None => out.push('\\'),
+        }
+    }
+    out
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use oasis_vfs::MemoryVfs;
 
     #[test]
     fn navigate_pushes_to_back_stack() {
@@ -272,6 +511,23 @@ mod tests {
         assert!(!nav.can_go_forward());
     }
 
+    #[test]
+    fn go_to_entry_keeps_history() {
+        let mut nav = NavigationController::new("about:home");
+        nav.navigate("https://a.com", "A");
+        nav.navigate("https://b.com", "B");
+        nav.navigate("https://c.com", "C");
+
+        let entry = nav.go_to_entry(0).unwrap();
+        assert_eq!(entry.url, "https://a.com");
+        assert_eq!(nav.current_index(), Some(0));
+        assert_eq!(nav.history_entries().len(), 3);
+        assert!(nav.can_go_forward());
+        assert_eq!(nav.go_forward().unwrap().url, "https://b.com");
+        assert!(nav.go_to_entry(3).is_none());
+        assert_eq!(nav.current_url(), Some("https://b.com"));
+    }
+
     #[test]
     fn can_go_back_and_forward() {
         let mut nav = NavigationController::new("about:home");
@@ -292,30 +548,75 @@ mod tests {
 
     #[test]
     fn add_remove_bookmarks() {
-        let mut nav = NavigationController::new("about:home");
-        nav.navigate("https://a.com", "A");
-        nav.add_bookmark();
+        let mut bookmarks = Bookmarks::default();
+        bookmarks.add("https://a.com", "A");
 
-        assert_eq!(nav.bookmarks().len(), 1);
-        assert_eq!(nav.bookmarks()[0].url, "https://a.com");
-        assert_eq!(nav.bookmarks()[0].title, "A");
+        assert_eq!(bookmarks.list().len(), 1);
+        assert_eq!(bookmarks.list()[0].url, "https://a.com");
+        assert_eq!(bookmarks.list()[0].title, "A");
 
-        nav.remove_bookmark("https://a.com");
-        assert!(nav.bookmarks().is_empty());
+        bookmarks.remove("https://a.com");
+        assert!(bookmarks.list().is_empty());
     }
 
     #[test]
-    fn is_bookmarked_check() {
-        let mut nav = NavigationController::new("about:home");
-        nav.navigate("https://a.com", "A");
-        assert!(!nav.is_bookmarked());
+    fn contains_check() {
+        let mut bookmarks = Bookmarks::default();
+        assert!(!bookmarks.contains("https://a.com"));
 
-        nav.add_bookmark();
-        assert!(nav.is_bookmarked());
+        bookmarks.add("https://a.com", "A");
+        assert!(bookmarks.contains("https://a.com"));
 
         // Duplicate add is a no-op.
-        nav.add_bookmark();
-        assert_eq!(nav.bookmarks().len(), 1);
+        bookmarks.add("https://a.com", "A again");
+        assert_eq!(bookmarks.list().len(), 1);
+        assert_eq!(bookmarks.list()[0].title, "A");
+    }
+
+    #[test]
+    fn bookmarks_round_trip_through_vfs() {
+        let mut bookmarks = Bookmarks::default();
+        bookmarks.add("https://a.com/", "Café ☕ 日本語");
+        bookmarks.add("gemini://b.org/x", "tab\there \\ and\nnewline");
+
+        let mut vfs = MemoryVfs::new();
+        bookmarks
+            .save_to_vfs(&mut vfs, "/home/.browser/bookmarks")
+            .unwrap();
+
+        let mut other = Bookmarks::default();
+        other
+            .load_from_vfs(&vfs, "/home/.browser/bookmarks")
+            .unwrap();
+        assert_eq!(other.list(), bookmarks.list());
+    }
+
+    #[test]
+    fn missing_bookmarks_file_is_not_an_error() {
+        let vfs = MemoryVfs::new();
+        let mut bookmarks = Bookmarks::default();
+        bookmarks.add("https://a.com", "A");
+        bookmarks.load_from_vfs(&vfs, "/nope/bookmarks").unwrap();
+        assert_eq!(bookmarks.list().len(), 1);
+    }
+
+    #[test]
+    fn corrupt_bookmark_lines_are_skipped() {
+        let mut vfs = MemoryVfs::new();
+        vfs.write(
+            "/bookmarks",
+            b"# header\n\xff\xfe\x00garbage\nhttps://ok.com\tOK\n\tno url\nhttps://ok.com\tdup\n",
+        )
+        .unwrap();
+        let mut bookmarks = Bookmarks::default();
+        bookmarks.load_from_vfs(&vfs, "/bookmarks").unwrap();
+        assert_eq!(
+            bookmarks.list(),
+            &[Bookmark {
+                url: "https://ok.com".to_string(),
+                title: "OK".to_string(),
+            }]
+        );
     }
 
     #[test]
@@ -340,6 +641,46 @@ mod tests {
         assert!(nav.can_go_back());
     }
 
+    #[test]
+    fn visits_are_counted_and_capped() {
+        let mut history = History::default();
+        history.set_max(2);
+        history.record("https://a.com", "A", 10);
+        history.record("https://b.com", "B", 20);
+        history.record("https://a.com", "", 30);
+        let visits = history.visits();
+        assert_eq!(visits[1].url, "https://a.com");
+        assert_eq!((visits[1].title.as_str(), visits[1].visits), ("A", 2));
+        assert_eq!(visits[1].last_visit, 30);
+
+        // The least recently visited page goes first.
+        history.record("https://c.com", "C", 40);
+        let urls: Vec<&str> = history.visits().iter().map(|v| v.url.as_str()).collect();
+        assert_eq!(urls, ["https://a.com", "https://c.com"]);
+    }
+
+    #[test]
+    fn visits_round_trip_through_vfs() {
+        let mut history = History::default();
+        history.record("https://a.com/", "tab\there", 10);
+        history.record("gemini://b.org/", "", 20);
+        history.record("https://a.com/", "", 30);
+
+        let mut vfs = MemoryVfs::new();
+        history
+            .save_to_vfs(&mut vfs, "/home/.browser/history")
+            .unwrap();
+        let mut text = vfs.read("/home/.browser/history").unwrap();
+        text.extend_from_slice(b"junk\nhttps://x.com\tX\tmany\t0\n");
+        vfs.write("/home/.browser/history", &text).unwrap();
+
+        let mut other = History::default();
+        other.set_max(1);
+        other.load_from_vfs(&vfs, "/home/.browser/history").unwrap();
+        assert_eq!(other.visits(), &history.visits()[1..]);
+        assert_eq!(other.visits()[0].visits, 2);
+    }
+
     #[test]
     fn history_listing() {
         let mut nav = NavigationController::new("about:home");
@@ -450,12 +791,11 @@ mod tests {
 
             #[test]
             fn bookmark_add_is_idempotent(url in arb_url()) {
-                let mut nav = NavigationController::new("about:home");
-                nav.navigate(&url, "Title");
-                nav.add_bookmark();
-                nav.add_bookmark();
-                nav.add_bookmark();
-                prop_assert_eq!(nav.bookmarks().len(), 1);
+                let mut bookmarks = Bookmarks::default();
+                bookmarks.add(&url, "Title");
+                bookmarks.add(&url, "Title");
+                bookmarks.add(&url, "Title");
+                prop_assert_eq!(bookmarks.list().len(), 1);
             }
 
             #[test]
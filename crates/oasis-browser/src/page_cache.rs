@@ -0,0 +1,154 @@
+//! Parsed-page cache for back/forward navigation.
+//!
+//! Keeps the DOM, computed styles, link map, and layout tree of recently
+//! visited pages so history navigation can skip tokenizing, parsing,
+//! and the CSS cascade. Bounded by entry count; the least recently used
+//! page is evicted first.
+
+use std::collections::{HashMap, VecDeque};
+
+use crate::css::values::ComputedStyle;
+use crate::html::dom::{Document, NodeId};
+use crate::layout::box_model::LayoutBox;
+
+/// A fully processed page, ready to be displayed again.
+#[derive(Debug, Clone)]
+pub struct CachedPage {
+    /// Parsed DOM.
+    pub document: Document,
+    /// Computed styles indexed by `NodeId`.
+    pub styles: Vec<Option<ComputedStyle>>,
+    /// `<a>` element hrefs by `NodeId`.
+    pub href_map: HashMap<NodeId, String>,
+    /// Layout tree root.
+    pub layout_root: LayoutBox,
+    /// Viewport width the layout was computed for.
+    pub layout_width: u32,
+    /// Text scale (page zoom) the layout was computed for.
+    pub layout_scale: f32,
+    /// Font signature the layout was measured with (see
+    /// [`crate::layout::metrics::signature`]).
+    pub layout_glyphs: [u32; 2],
+}
+
+/// LRU cache of [`CachedPage`]s keyed by URL.
+pub struct PageCache {
+    entries: HashMap<String, CachedPage>,
+    /// Front = most recently used, back = least recently used.
+    order: VecDeque<String>,
+    max_entries: usize,
+}
+
+impl PageCache {
+    /// Create a cache holding at most `max_entries` pages. A limit of
+    /// zero disables caching.
+    pub fn new(max_entries: usize) -> Self {
+        Self {
+            entries: HashMap::new(),
+            order: VecDeque::new(),
+            max_entries,
+        }
+    }
+
+    /// Look up a page, promoting it to most recently used.
+    pub fn get(&mut self, url: &str) -> Option<&CachedPage> {
+        if self.entries.contains_key(url) {
+            self.order.retain(|u| u != url);
+            self.order.push_front(url.to_string());
+        }
+        self.entries.get(url)
+    }
+
+    /// Store a page, evicting the least recently used entries to stay
+    /// within the limit.
+    pub fn insert(&mut self, url: String, page: CachedPage) {
+        if self.max_entries == 0 {
+            return;
+        }
+        self.order.retain(|u| u != &url);
+        while self.order.len() >= self.max_entries {
+            if let Some(evicted) = self.order.pop_back() {
+                self.entries.remove(&evicted);
+            }
+        }
+        self.order.push_front(url.clone());
+        self.entries.insert(url, page);
+    }
+
+    /// Drop the cached page for `url`, if any.
+    pub fn invalidate(&mut self, url: &str) {
+        if self.entries.remove(url).is_some() {
+            self.order.retain(|u| u != url);
+        }
+    }
+
+    /// Whether a page for `url` is cached.
+    pub fn contains(&self, url: &str) -> bool {
+        self.entries.contains_key(url)
+    }
+
+    /// Number of cached pages.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Whether the cache is empty.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+// -------------------------------------------------------------------
+// Tests
+// -------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::layout::box_model::BoxType;
+
+    fn page() -> CachedPage {
+        CachedPage {
+            document: Document::new(),
+            styles: Vec::new(),
+            href_map: HashMap::new(),
+            layout_root: LayoutBox::new(BoxType::Block, ComputedStyle::default(), None),
+            layout_width: 480,
+            layout_scale: 1.0,
+            layout_glyphs: [0, 0],
+        }
+    }
+
+    #[test]
+    fn evicts_least_recently_used() {
+        let mut cache = PageCache::new(2);
+        cache.insert("a".into(), page());
+        cache.insert("b".into(), page());
+        assert!(cache.get("a").is_some());
+        cache.insert("c".into(), page());
+        assert!(cache.contains("a"));
+        assert!(!cache.contains("b"));
+        assert_eq!(cache.len(), 2);
+    }
+
+    #[test]
+    fn reinsert_does_not_grow() {
+        let mut cache = PageCache::new(2);
+        cache.insert("a".into(), page());
+        cache.insert("a".into(), page());
+        cache.insert("b".into(), page());
+        assert!(cache.contains("a") && cache.contains("b"));
+    }
+
+    #[test]
+    fn invalidate_and_zero_limit() {
+        let mut cache = PageCache::new(2);
+        cache.insert("a".into(), page());
+        cache.invalidate("a");
+        assert!(cache.is_empty());
+
+        let mut disabled = PageCache::new(0);
+        disabled.insert("a".into(), page());
+        assert!(disabled.is_empty());
+    }
+}
@@ -6,8 +6,8 @@
 //! user input (mouse clicks, PSP button presses).
 //!
 //! Painting follows the CSS 2.1 painting order:
-//! 1. Background -- `fill_rect()` with `background-color`
-//! 2. Borders -- `fill_rect()` per edge with `border-color`
+//! 1. Background -- `fill_rect()` over the border box with `background-color`
+//! 2. Borders -- `stroke_rect()` when uniform, else `fill_rect()` per edge
 //! 3. Block children -- recurse
 //! 4. Inline content -- text runs via `draw_text()`, inline backgrounds
 //! 5. Replaced content -- images via `blit()`, `<hr>` via `fill_rect()`
@@ -16,11 +16,12 @@
 use std::collections::HashMap;
 
 use crate::css::values::{BorderStyle, TextDecoration};
+use crate::find::FindMatch;
 use crate::html::dom::NodeId;
 use crate::layout::box_model::{
     BoxType, InlineFragment, LayoutBox, LineBox, ListMarker, Rect, ReplacedContent,
 };
-use oasis_types::backend::{Color, SdiBackend};
+use oasis_types::backend::{Color, SdiBackend, bitmap_measure_text};
 use oasis_types::error::Result;
 
 // -------------------------------------------------------------------
@@ -38,10 +39,32 @@ pub struct LinkRegion {
     pub node: NodeId,
 }
 
+/// Kind of interactive form control.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ControlKind {
+    /// Editable text field (`<input type=text>` and friends).
+    TextInput,
+    /// Clickable button (`<button>`, `<input type=submit>`).
+    Button,
+}
+
+/// A form control hit region recorded during painting.
+#[derive(Debug, Clone)]
+pub struct ControlRegion {
+    /// Screen-space bounding rectangle of the control.
+    pub rect: Rect,
+    /// What kind of control this is.
+    pub kind: ControlKind,
+    /// The DOM node of the `<input>` / `<button>` element.
+    pub node: NodeId,
+}
+
 /// The result of a paint pass.
 pub struct PaintResult {
     /// Link hit regions recorded during this paint pass.
     pub links: Vec<LinkRegion>,
+    /// Form control hit regions recorded during this paint pass.
+    pub controls: Vec<ControlRegion>,
     /// Total content height in layout pixels (for scroll calculations).
     pub content_height: f32,
 }
@@ -54,6 +77,8 @@ pub struct PaintResult {
 struct PaintContext {
     /// Accumulated link regions.
     links: Vec<LinkRegion>,
+    /// Accumulated form control regions.
+    controls: Vec<ControlRegion>,
     /// When painting inside an `<a>` element, this holds `(href, node_id)`.
     current_link: Option<(String, NodeId)>,
     /// Vertical scroll offset (content shifts up by this amount).
@@ -85,6 +110,7 @@ pub fn paint(
 ) -> Result<PaintResult> {
     let mut ctx = PaintContext {
         links: Vec::new(),
+        controls: Vec::new(),
         current_link: None,
         scroll_y,
         viewport_height,
@@ -94,6 +120,7 @@ pub fn paint(
 
     Ok(PaintResult {
         links: ctx.links,
+        controls: ctx.controls,
         content_height: layout.dimensions.margin_box().height,
     })
 }
@@ -107,7 +134,15 @@ pub fn paint_link_highlight(
     backend: &mut dyn SdiBackend,
     highlight_color: Color,
 ) -> Result<()> {
-    let r = &link.rect;
+    paint_rect_highlight(&link.rect, backend, highlight_color)
+}
+
+/// Outline a screen-space rectangle (link or form control focus ring).
+pub fn paint_rect_highlight(
+    r: &Rect,
+    backend: &mut dyn SdiBackend,
+    highlight_color: Color,
+) -> Result<()> {
     let x = r.x as i32 - 2;
     let y = r.y as i32 - 1;
     let w = r.width as u32 + 4;
@@ -125,6 +160,28 @@ pub fn paint_link_highlight(
     Ok(())
 }
 
+/// Paint a filled highlight behind a find-in-page match.
+///
+/// Match rectangles are in layout coordinates; they are translated by
+/// the viewport origin and scroll offset the same way `paint` does.
+pub fn paint_find_highlight(
+    m: &FindMatch,
+    backend: &mut dyn SdiBackend,
+    scroll_y: f32,
+    offset_x: i32,
+    offset_y: i32,
+    highlight_color: Color,
+) -> Result<()> {
+    for r in &m.rects {
+        let x = (r.x + offset_x as f32) as i32;
+        let y = (r.y - scroll_y + offset_y as f32) as i32;
+        let w = r.width.ceil().max(1.0) as u32;
+        let h = r.height.ceil().max(1.0) as u32;
+        backend.fill_rect(x, y, w, h, highlight_color)?;
+    }
+    Ok(())
+}
+
 // -------------------------------------------------------------------
 // Recursive box painter
 // -------------------------------------------------------------------
@@ -187,6 +244,7 @@ fn paint_box(
         },
         BoxType::Replaced(replaced) => {
             paint_replaced(replaced, layout_box, backend, offset_x, offset_y, ctx)?;
+            record_control(replaced, layout_box, offset_x, offset_y, ctx);
         },
     }
 
@@ -237,10 +295,12 @@ fn paint_background(
         return Ok(());
     }
 
-    let padding = layout_box.dimensions.padding_box();
-    let x = (padding.x + offset_x as f32) as i32;
-    let y = (padding.y - ctx.scroll_y + offset_y as f32) as i32;
-    backend.fill_rect(x, y, padding.width as u32, padding.height as u32, bg)
+    // The background extends under the border (`background-clip:
+    // border-box`).
+    let border = layout_box.dimensions.border_box();
+    let x = (border.x + offset_x as f32) as i32;
+    let y = (border.y - ctx.scroll_y + offset_y as f32) as i32;
+    backend.fill_rect(x, y, border.width as u32, border.height as u32, bg)
 }
 
 // -------------------------------------------------------------------
@@ -262,6 +322,27 @@ fn paint_borders(
     let bw = border.width as u32;
     let bh = border.height as u32;
 
+    // A uniform border on all four sides is a single stroke.
+    let uniform = d.border.top > 0.0
+        && [d.border.right, d.border.bottom, d.border.left] == [d.border.top; 3]
+        && [
+            style.border_top_style,
+            style.border_right_style,
+            style.border_bottom_style,
+            style.border_left_style,
+        ]
+        .iter()
+        .all(|s| *s != BorderStyle::None)
+        && [
+            style.border_right_color,
+            style.border_bottom_color,
+            style.border_left_color,
+        ] == [style.border_top_color; 3];
+    if uniform {
+        return backend.stroke_rect(bx, by, bw, bh, d.border.top as u16, style.border_top_color);
+    }
+
+    // Otherwise fill each side that has a border.
     // Top
     if d.border.top > 0.0 && style.border_top_style != BorderStyle::None {
         backend.fill_rect(bx, by, bw, d.border.top as u32, style.border_top_color)?;
@@ -428,6 +509,10 @@ fn paint_line_box(
 // List markers
 // -------------------------------------------------------------------
 
+/// Paint a list item's marker in the hanging indent to the left of
+/// its content box, right-aligned so that markers of different widths
+/// ("9." and "10.") end at the same place and wrapped lines of the
+/// item align with its text rather than the marker.
 fn paint_list_marker(
     marker: &ListMarker,
     layout_box: &LayoutBox,
@@ -436,30 +521,21 @@ fn paint_list_marker(
     offset_y: i32,
     ctx: &PaintContext,
 ) -> Result<()> {
+    let text = match marker {
+        ListMarker::Disc => "\u{2022}".to_string(),
+        ListMarker::Circle => "\u{25E6}".to_string(),
+        ListMarker::Square => "\u{25AA}".to_string(),
+        ListMarker::Decimal(n) => format!("{n}."),
+        ListMarker::None => return Ok(()),
+    };
+
     let content = &layout_box.dimensions.content;
-    let x = (content.x + offset_x as f32 - 20.0) as i32;
-    let y = (content.y - ctx.scroll_y + offset_y as f32) as i32;
-    let color = layout_box.style.color;
     let font_size = layout_box.style.font_size as u16;
-
-    match marker {
-        ListMarker::Disc => {
-            backend.draw_text("\u{2022}", x, y, font_size, color)?;
-        },
-        ListMarker::Circle => {
-            backend.draw_text("\u{25E6}", x, y, font_size, color)?;
-        },
-        ListMarker::Square => {
-            backend.draw_text("\u{25AA}", x, y, font_size, color)?;
-        },
-        ListMarker::Decimal(n) => {
-            let text = format!("{}.", n);
-            backend.draw_text(&text, x - 10, y, font_size, color)?;
-        },
-        ListMarker::None => {},
-    }
-
-    Ok(())
+    let gap = (layout_box.style.font_size / 2.0) as i32;
+    let width = backend.measure_text(&text, font_size) as i32;
+    let x = (content.x + offset_x as f32) as i32 - gap - width;
+    let y = (content.y - ctx.scroll_y + offset_y as f32) as i32;
+    backend.draw_text(&text, x, y, font_size, layout_box.style.color)
 }
 
 // -------------------------------------------------------------------
@@ -509,11 +585,86 @@ fn paint_replaced(
         ReplacedContent::LineBreak => {
             // Nothing to paint.
         },
+        ReplacedContent::TextInput { value, masked, .. } => {
+            let w = content.width as u32;
+            let h = content.height as u32;
+            let style = &layout_box.style;
+            backend.fill_rect(x, y, w, h, Color::rgb(255, 255, 255))?;
+            paint_outline(x, y, w, h, Color::rgb(118, 118, 118), backend)?;
+            let shown = if *masked {
+                "*".repeat(value.chars().count())
+            } else {
+                value.clone()
+            };
+            // Clip the value to the box interior.
+            let font_size = style.font_size as u16;
+            let avail = w.saturating_sub(6);
+            let end = shown
+                .char_indices()
+                .map(|(i, c)| i + c.len_utf8())
+                .take_while(|&end| bitmap_measure_text(&shown[..end], font_size) <= avail)
+                .last()
+                .unwrap_or(0);
+            backend.draw_text(&shown[..end], x + 3, y + 3, font_size, style.color)?;
+        },
+        ReplacedContent::Button { label } => {
+            let w = content.width as u32;
+            let h = content.height as u32;
+            let style = &layout_box.style;
+            backend.fill_rect(x, y, w, h, Color::rgb(221, 221, 221))?;
+            paint_outline(x, y, w, h, Color::rgb(118, 118, 118), backend)?;
+            backend.draw_text(label, x + 6, y + 3, style.font_size as u16, style.color)?;
+        },
     }
 
     Ok(())
 }
 
+/// Draw a 1px outline just inside `(x, y, w, h)`.
+fn paint_outline(
+    x: i32,
+    y: i32,
+    w: u32,
+    h: u32,
+    color: Color,
+    backend: &mut dyn SdiBackend,
+) -> Result<()> {
+    backend.fill_rect(x, y, w, 1, color)?;
+    backend.fill_rect(x, y + h as i32 - 1, w, 1, color)?;
+    backend.fill_rect(x, y, 1, h, color)?;
+    backend.fill_rect(x + w as i32 - 1, y, 1, h, color)?;
+    Ok(())
+}
+
+/// Record a hit region for form controls.
+fn record_control(
+    replaced: &ReplacedContent,
+    layout_box: &LayoutBox,
+    offset_x: i32,
+    offset_y: i32,
+    ctx: &mut PaintContext,
+) {
+    let kind = match replaced {
+        ReplacedContent::TextInput { .. } => ControlKind::TextInput,
+        ReplacedContent::Button { .. } => ControlKind::Button,
+        _ => return,
+    };
+    let Some(node) = layout_box.node else {
+        return;
+    };
+    let content = &layout_box.dimensions.content;
+    ctx.controls.push(ControlRegion {
+        rect: Rect {
+            x: content.x + offset_x as f32,
+            y: content.y - ctx.scroll_y + offset_y as f32,
+            width: content.width,
+            height: content.height,
+        },
+        kind,
+        node,
+    });
+}
+
 // -------------------------------------------------------------------
 // Helpers
 // -------------------------------------------------------------------
@@ -780,6 +931,32 @@ mod tests {
         }
     }
 
+    #[test]
+    fn list_markers_are_right_aligned_in_the_indent() {
+        let mut ends = Vec::new();
+        for n in [9, 10] {
+            let mut backend = MockBackend::new();
+            let mut lb = LayoutBox::new(
+                BoxType::ListItem {
+                    marker: ListMarker::Decimal(n),
+                },
+                ComputedStyle::default(),
+                Some(0),
+            );
+            lb.dimensions.content.x = 40.0;
+            paint(&lb, &mut backend, 0.0, 0, 0, 480.0, 272.0, &HashMap::new()).unwrap();
+            let Some(DrawCall::DrawText {
+                text, x, font_size, ..
+            }) = backend.calls.first()
+            else {
+                panic!("expected DrawText for decimal marker");
+            };
+            ends.push(x + oasis_types::backend::bitmap_measure_text(text, *font_size) as i32);
+        }
+        assert_eq!(ends[0], ends[1]);
+        assert!(ends[0] < 40);
+    }
+
     // ---------------------------------------------------------------
     // Test 6: broken image placeholder dimensions
     // ---------------------------------------------------------------
@@ -795,6 +972,7 @@ mod tests {
                 height: 0,
                 texture: None,
                 alt: String::new(),
+                src: String::new(),
             }),
             style,
             Some(0),
@@ -836,6 +1014,7 @@ mod tests {
                 height: 0,
                 texture: None,
                 alt: "Photo".to_string(),
+                src: String::new(),
             }),
             style,
             Some(0),
@@ -1,7 +1,7 @@
 //! Reader mode: extract article text from complex HTML pages.
 //!
 //! Uses a heuristic scoring algorithm inspired by Mozilla's
-//! Readability.js.
+//! Readability.js, with its weights in a [`ReaderConfig`].
 
 use crate::html::dom::{Document, NodeId, NodeKind, TagName};
 
@@ -16,12 +16,73 @@ pub struct Article {
     pub html: String,
 }
 
+/// Weights and thresholds for picking the article container.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReaderConfig {
+    /// Score the best element needs for the page to count as an article.
+    pub min_score: f32,
+    /// Paragraphs shorter than this (in bytes, trimmed) add nothing to
+    /// their parent.
+    pub min_paragraph_len: usize,
+    /// What each long-enough paragraph adds to its parent...
+    pub paragraph_score: f32,
+    /// ...plus one point per this many bytes of paragraph text...
+    pub paragraph_chars_per_point: f32,
+    /// ...up to this many points.
+    pub max_paragraph_length_score: f32,
+    /// Subtracted from an element's score in proportion to how much of
+    /// its text is inside links, so link lists (navigation, sidebars,
+    /// "related" boxes) lose to prose. `0.0` turns it off.
+    pub link_density_penalty: f32,
+    /// Words in a `class` or `id` that suggest article content.
+    pub positive_hints: Vec<String>,
+    /// Words in a `class` or `id` that suggest page furniture.
+    pub negative_hints: Vec<String>,
+    /// Added for each positive hint, and subtracted for each negative one.
+    pub hint_score: f32,
+}
+
+impl Default for ReaderConfig {
+    fn default() -> Self {
+        let words = |words: &[&str]| words.iter().map(|w| w.to_string()).collect();
+        Self {
+            min_score: 10.0,
+            min_paragraph_len: 25,
+            paragraph_score: 1.0,
+            paragraph_chars_per_point: 100.0,
+            max_paragraph_length_score: 3.0,
+            link_density_penalty: 0.0,
+            positive_hints: words(&[
+                "content",
+                "article",
+                "post",
+                "entry",
+                "story",
+                "text",
+                "body-content",
+                "main",
+            ]),
+            negative_hints: words(&[
+                "sidebar", "comment", "menu", "nav", "ad", "banner", "footer", "header", "widget",
+                "social", "related", "popup", "modal",
+            ]),
+            hint_score: 5.0,
+        }
+    }
+}
+
 /// Extract the main article content from a DOM tree.
 pub fn extract_article(doc: &Document) -> Option<Article> {
+    extract_article_with(doc, &ReaderConfig::default())
+}
+
+/// Extract the main article content from a DOM tree, scoring elements
+/// with `config`.
+pub fn extract_article_with(doc: &Document, config: &ReaderConfig) -> Option<Article> {
     let title = extract_title(doc);
 
     // Score every element.
-    let scores = score_elements(doc);
+    let scores = score_elements(doc, config);
 
     // Find the highest-scoring element.
     let best_node = scores
@@ -31,7 +92,7 @@ pub fn extract_article(doc: &Document) -> Option<Article> {
         .map(|(id, _)| id)?;
 
     // Check minimum score threshold.
-    if scores[best_node] < 10.0 {
+    if scores[best_node] < config.min_score {
         return None;
     }
 
@@ -70,7 +131,7 @@ fn find_first_heading(doc: &Document, node_id: NodeId) -> Option<String> {
 }
 
 /// Score each element for article-ness.
-fn score_elements(doc: &Document) -> Vec<f32> {
+fn score_elements(doc: &Document, config: &ReaderConfig) -> Vec<f32> {
     let mut scores = vec![0.0f32; doc.nodes.len()];
 
     for (id, node) in doc.nodes.iter().enumerate() {
@@ -90,8 +151,10 @@ fn score_elements(doc: &Document) -> Vec<f32> {
                 if let Some(parent) = node.parent {
                     let text = doc.text_content(id);
                     let text_len = text.trim().len();
-                    if text_len >= 25 {
-                        scores[parent] += 1.0 + (text_len as f32 / 100.0).min(3.0);
+                    if text_len >= config.min_paragraph_len {
+                        let length_score = text_len as f32 / config.paragraph_chars_per_point;
+                        scores[parent] += config.paragraph_score
+                            + length_score.min(config.max_paragraph_length_score);
                     }
                 }
             },
@@ -108,28 +171,16 @@ fn score_elements(doc: &Document) -> Vec<f32> {
         let combined = format!("{} {}", class_str, id_str).to_lowercase();
 
         // Positive signals.
-        for keyword in &[
-            "content",
-            "article",
-            "post",
-            "entry",
-            "story",
-            "text",
-            "body-content",
-            "main",
-        ] {
-            if combined.contains(keyword) {
-                scores[id] += 5.0;
+        for keyword in &config.positive_hints {
+            if combined.contains(keyword.as_str()) {
+                scores[id] += config.hint_score;
             }
         }
 
         // Negative signals.
-        for keyword in &[
-            "sidebar", "comment", "menu", "nav", "ad", "banner", "footer", "header", "widget",
-            "social", "related", "popup", "modal",
-        ] {
-            if combined.contains(keyword) {
-                scores[id] -= 5.0;
+        for keyword in &config.negative_hints {
+            if combined.contains(keyword.as_str()) {
+                scores[id] -= config.hint_score;
             }
         }
 
@@ -142,11 +193,31 @@ fn score_elements(doc: &Document) -> Vec<f32> {
                 scores[id] += 2.0;
             }
         }
+
+        // Link density: share of the text that is link text.
+        if config.link_density_penalty != 0.0 && !text.is_empty() {
+            let link_density = link_text_len(doc, id) as f32 / text.len() as f32;
+            scores[id] -= link_density * config.link_density_penalty;
+        }
     }
 
     scores
 }
 
+/// Bytes of text inside `<a>` elements under `node_id`.
+fn link_text_len(doc: &Document, node_id: NodeId) -> usize {
+    let node = doc.get(node_id);
+    if let NodeKind::Element(elem) = &node.kind
+        && matches!(elem.tag, TagName::A)
+    {
+        return doc.text_content(node_id).len();
+    }
+    node.children
+        .iter()
+        .map(|&child| link_text_len(doc, child))
+        .sum()
+}
+
 /// Extract simplified HTML from the article container.
 fn extract_content_html(doc: &Document, node_id: NodeId) -> String {
     let mut html = String::new();
@@ -287,6 +358,8 @@ pub fn reader_stylesheet() -> &'static str {
 mod tests {
     use super::*;
     use crate::html::dom::{Attribute, Document, ElementData, NodeKind, TagName};
+    use crate::html::tokenizer::Tokenizer;
+    use crate::html::tree_builder::TreeBuilder;
 
     /// Build a minimal document with an `<article>` containing
     /// paragraphs, so `extract_article` can find it.
@@ -349,7 +422,7 @@ mod tests {
         let article = doc.add_node(NodeKind::Element(ElementData::new(TagName::Article)));
         doc.append_child(html, article);
 
-        let scores = score_elements(&doc);
+        let scores = score_elements(&doc, &ReaderConfig::default());
         assert!(
             scores[article] > scores[nav],
             "article score ({}) should exceed nav score ({})",
@@ -372,7 +445,7 @@ mod tests {
         let sidebar = doc.add_node(NodeKind::Element(sidebar_data));
         doc.append_child(html, sidebar);
 
-        let scores = score_elements(&doc);
+        let scores = score_elements(&doc, &ReaderConfig::default());
         // div base +1, "sidebar" -5, "widget" -5 => -9
         assert!(
             scores[sidebar] < 0.0,
@@ -431,6 +504,63 @@ mod tests {
         assert!(result.html.contains("Navigation links"));
     }
 
+    fn parse(html: &str) -> Document {
+        TreeBuilder::build(Tokenizer::new(html).tokenize())
+    }
+
+    /// A link list and some prose, neither with class/id hints.
+    const LINKS_AND_PROSE: &str = "<html><body>\
+        <div id=\"links\">\
+        <p><a href=\"/a\">Twelve surprising facts about the PSP homebrew scene</a></p>\
+        <p><a href=\"/b\">How the memory stick format changed over the years</a></p>\
+        <p><a href=\"/c\">A short history of portable game consoles in Japan</a></p>\
+        </div>\
+        <div id=\"prose\">\
+        <p>The handheld shipped with a widescreen display and an optical drive \
+        that read small discs.</p>\
+        <p>Developers soon found ways to run their own code on it, and a lively \
+        homebrew scene grew up around the hardware.</p>\
+        </div></body></html>";
+
+    #[test]
+    fn link_density_penalty_demotes_link_lists() {
+        let doc = parse(LINKS_AND_PROSE);
+        let links = doc.get_element_by_id("links").unwrap();
+        let prose = doc.get_element_by_id("prose").unwrap();
+        let mut config = ReaderConfig {
+            min_score: 0.0,
+            ..ReaderConfig::default()
+        };
+        // More paragraphs win without the penalty...
+        let article = extract_article_with(&doc, &config).unwrap();
+        assert_eq!(article.content_node, links);
+        // ...but with it the link list drops below the prose.
+        config.link_density_penalty = 10.0;
+        let scores = score_elements(&doc, &config);
+        assert!(scores[links] < 0.0, "links scored {}", scores[links]);
+        let article = extract_article_with(&doc, &config).unwrap();
+        assert_eq!(article.content_node, prose);
+        assert!(article.html.contains("homebrew scene grew up"));
+    }
+
+    #[test]
+    fn hints_and_threshold_come_from_the_config() {
+        let doc = parse(
+            "<html><body><div class=\"recipe\">\
+             <p>Whisk the eggs with a pinch of salt until smooth and pale.</p>\
+             </div></body></html>",
+        );
+        // Without a hint the div falls short of the default threshold.
+        assert!(extract_article(&doc).is_none());
+        let config = ReaderConfig {
+            positive_hints: vec!["recipe".into()],
+            hint_score: 8.0,
+            ..ReaderConfig::default()
+        };
+        let article = extract_article_with(&doc, &config).expect("recipe hint");
+        assert!(article.html.contains("Whisk the eggs"));
+    }
+
     #[test]
     fn title_extraction_from_h1() {
         let mut doc = Document::new();
@@ -1,17 +1,56 @@
 //! Viewport and scroll offset management.
+//!
+//! Smooth scrolling uses the same [`Momentum`] as the UI toolkit's
+//! scrollable widgets, so the page coasts like any other list.
+
+use oasis_ui::momentum::{DEFAULT_FRICTION, FRAME_MS, Momentum};
 
 /// Scroll amounts for different input types.
 pub const SCROLL_LINE: i32 = 24;
 pub const SCROLL_PAGE_FRACTION: f32 = 0.9;
 pub const SCROLL_WHEEL: i32 = 48;
 pub const SCROLL_ACCELERATION: f32 = 1.5;
+/// Fastest smooth scroll, in pixels per frame.
 pub const MAX_VELOCITY: f32 = 200.0;
 
-/// Friction applied each tick to decelerate smooth scrolling.
-const FRICTION: f32 = 0.85;
+/// Distance (pixels) under which a glide to a target snaps into place.
+const GLIDE_SNAP: f32 = 2.0;
+
+/// Step sizes and smooth-scroll motion, tunable per device.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ScrollTuning {
+    /// Pixels scrolled by one line step (arrow key or d-pad).
+    pub line_step: i32,
+    /// Pixels scrolled by one mouse wheel notch.
+    pub wheel_step: i32,
+    /// Share of the smooth scroll speed kept after each frame, in
+    /// `0.0..1.0`. Lower values stop sooner.
+    pub friction: f32,
+    /// Fastest smooth scroll, in pixels per frame.
+    pub max_velocity: f32,
+}
 
-/// Velocity below this threshold snaps to zero.
-const VELOCITY_EPSILON: f32 = 0.5;
+impl Default for ScrollTuning {
+    fn default() -> Self {
+        Self {
+            line_step: SCROLL_LINE,
+            wheel_step: SCROLL_WHEEL,
+            friction: DEFAULT_FRICTION,
+            max_velocity: MAX_VELOCITY,
+        }
+    }
+}
+
+impl ScrollTuning {
+    /// Short, quick glides for small screens and d-pad input.
+    pub fn snappy() -> Self {
+        Self {
+            friction: 0.85,
+            max_velocity: 120.0,
+            ..Self::default()
+        }
+    }
+}
 
 /// Scroll state for the browser viewport.
 #[derive(Debug, Clone)]
@@ -22,89 +61,103 @@ pub struct ScrollState {
     pub content_height: i32,
     /// Visible viewport height (from window content area).
     pub viewport_height: i32,
-    /// Scroll velocity for smooth scrolling (pixels per frame).
-    scroll_velocity: f32,
+    /// Smooth scrolling momentum.
+    momentum: Momentum,
+    /// Sub-pixel part of the smooth scroll position.
+    carry: f32,
+    /// Position a smooth [`scroll_to_visible`](Self::scroll_to_visible)
+    /// is gliding to.
+    glide_to: Option<f32>,
+    /// Step sizes and smooth-scroll motion.
+    tuning: ScrollTuning,
     /// Whether smooth scrolling is enabled.
     smooth: bool,
 }
 
 impl ScrollState {
     pub fn new(viewport_height: i32, smooth: bool) -> Self {
+        let tuning = ScrollTuning::default();
         Self {
             scroll_y: 0,
             content_height: 0,
             viewport_height,
-            scroll_velocity: 0.0,
+            momentum: Momentum::new(tuning.friction, tuning.max_velocity / FRAME_MS),
+            carry: 0.0,
+            glide_to: None,
+            tuning,
             smooth,
         }
     }
 
+    /// Current step sizes and smooth-scroll motion.
+    pub fn tuning(&self) -> ScrollTuning {
+        self.tuning
+    }
+
+    /// Change the step sizes and smooth-scroll motion. Takes effect on
+    /// the next scroll; a glide in progress keeps going.
+    pub fn set_tuning(&mut self, tuning: ScrollTuning) {
+        self.tuning = tuning;
+        self.momentum.friction = tuning.friction.clamp(0.0, 0.999);
+        self.momentum.max_velocity = tuning.max_velocity.max(1.0) / FRAME_MS;
+    }
+
     /// Scroll up by one line.
     pub fn scroll_up(&mut self) {
-        if self.smooth {
-            self.scroll_velocity -= SCROLL_LINE as f32;
-            self.scroll_velocity = self.scroll_velocity.max(-MAX_VELOCITY);
-        } else {
-            self.scroll_y -= SCROLL_LINE;
-            self.clamp();
-        }
+        self.scroll_by(-self.tuning.line_step);
     }
 
     /// Scroll down by one line.
     pub fn scroll_down(&mut self) {
-        if self.smooth {
-            self.scroll_velocity += SCROLL_LINE as f32;
-            self.scroll_velocity = self.scroll_velocity.min(MAX_VELOCITY);
-        } else {
-            self.scroll_y += SCROLL_LINE;
-            self.clamp();
-        }
+        self.scroll_by(self.tuning.line_step);
     }
 
     /// Scroll up by one page.
     pub fn page_up(&mut self) {
         let amount = (self.viewport_height as f32 * SCROLL_PAGE_FRACTION) as i32;
-        if self.smooth {
-            self.scroll_velocity -= amount as f32;
-            self.scroll_velocity = self.scroll_velocity.max(-MAX_VELOCITY);
-        } else {
-            self.scroll_y -= amount;
-            self.clamp();
-        }
+        self.scroll_by(-amount);
     }
 
     /// Scroll down by one page.
     pub fn page_down(&mut self) {
         let amount = (self.viewport_height as f32 * SCROLL_PAGE_FRACTION) as i32;
-        if self.smooth {
-            self.scroll_velocity += amount as f32;
-            self.scroll_velocity = self.scroll_velocity.min(MAX_VELOCITY);
-        } else {
-            self.scroll_y += amount;
-            self.clamp();
-        }
+        self.scroll_by(amount);
     }
 
     /// Scroll by a mouse wheel notch.
     pub fn wheel_scroll(&mut self, delta: i32) {
+        self.scroll_by(delta * self.tuning.wheel_step);
+    }
+
+    /// Scroll by `amount` pixels: at once, or with smooth scrolling by
+    /// giving the momentum enough speed to coast that far.
+    fn scroll_by(&mut self, amount: i32) {
         if self.smooth {
-            self.scroll_velocity += delta as f32 * SCROLL_WHEEL as f32;
-            self.scroll_velocity = self.scroll_velocity.clamp(-MAX_VELOCITY, MAX_VELOCITY);
+            self.glide_to = None;
+            self.momentum.push_distance(amount as f32);
         } else {
-            self.scroll_y += delta * SCROLL_WHEEL;
+            self.scroll_y += amount;
             self.clamp();
         }
     }
 
+    /// Stop smooth scrolling where it is.
+    fn stop(&mut self) {
+        self.momentum.stop();
+        self.glide_to = None;
+        self.carry = 0.0;
+    }
+
     /// Scroll to an absolute position.
     pub fn scroll_to(&mut self, y: i32) {
-        self.scroll_velocity = 0.0;
+        self.stop();
         self.scroll_y = y;
         self.clamp();
     }
 
     /// Scroll to make a specific y-coordinate visible.
-    /// Centers it in the viewport if it is offscreen.
+    /// Centers it in the viewport if it is offscreen, gliding there on
+    /// [`tick`](Self::tick) when smooth scrolling is on.
     pub fn scroll_to_visible(&mut self, target_y: i32, target_height: i32) {
         let visible_top = self.scroll_y;
         let visible_bottom = self.scroll_y + self.viewport_height;
@@ -116,20 +169,24 @@ impl ScrollState {
 
         // Center the target in the viewport.
         let center = target_y + target_height / 2;
-        self.scroll_y = center - self.viewport_height / 2;
-        self.scroll_velocity = 0.0;
-        self.clamp();
+        let dest = (center - self.viewport_height / 2).clamp(0, self.max_scroll());
+        self.stop();
+        if self.smooth {
+            self.glide_to = Some(dest as f32);
+        } else {
+            self.scroll_y = dest;
+        }
     }
 
     /// Scroll to top of document.
     pub fn scroll_to_top(&mut self) {
-        self.scroll_velocity = 0.0;
+        self.stop();
         self.scroll_y = 0;
     }
 
     /// Scroll to bottom of document.
     pub fn scroll_to_bottom(&mut self) {
-        self.scroll_velocity = 0.0;
+        self.stop();
         self.scroll_y = self.max_scroll();
     }
 
@@ -145,28 +202,46 @@ impl ScrollState {
         self.clamp();
     }
 
-    /// Tick the smooth scroll animation. Returns true if still
+    /// Advance smooth scrolling by `dt_ms`. Returns true if still
     /// animating.
-    pub fn tick(&mut self) -> bool {
-        if !self.smooth {
+    pub fn tick(&mut self, dt_ms: u32) -> bool {
+        if let Some(dest) = self.glide_to {
+            return self.glide(dest, dt_ms);
+        }
+        if !self.momentum.is_moving() {
             return false;
         }
 
-        if self.scroll_velocity.abs() < VELOCITY_EPSILON {
-            self.scroll_velocity = 0.0;
-            return false;
+        let pos = self.scroll_y as f32 + self.carry + self.momentum.step(dt_ms);
+        let bound = pos.clamp(0.0, self.max_scroll() as f32);
+        // Reaching either end stops the momentum.
+        if pos != bound {
+            self.momentum.stop();
         }
+        self.scroll_y = bound.round() as i32;
+        self.carry = bound - self.scroll_y as f32;
 
-        self.scroll_y += self.scroll_velocity as i32;
-        self.scroll_velocity *= FRICTION;
-        self.clamp();
+        self.momentum.is_moving()
+    }
 
-        // If clamped to boundary, stop velocity.
-        if self.scroll_y == 0 || self.scroll_y == self.max_scroll() {
-            self.scroll_velocity = 0.0;
+    /// Close the friction share of the distance to `dest` per frame, no
+    /// faster than the top speed, and snap into place once close.
+    fn glide(&mut self, dest: f32, dt_ms: u32) -> bool {
+        let dest = dest.clamp(0.0, self.max_scroll() as f32);
+        let pos = self.scroll_y as f32 + self.carry;
+        let frames = dt_ms as f32 / FRAME_MS;
+        let limit = self.tuning.max_velocity.max(1.0) * frames;
+        let step =
+            ((dest - pos) * (1.0 - self.momentum.friction.powf(frames))).clamp(-limit, limit);
+        let pos = pos + step;
+        if (dest - pos).abs() < GLIDE_SNAP {
+            self.scroll_y = dest.round() as i32;
+            self.stop();
+            return false;
         }
-
-        self.scroll_velocity.abs() >= VELOCITY_EPSILON
+        self.scroll_y = pos.round() as i32;
+        self.carry = pos - self.scroll_y as f32;
+        true
     }
 
     /// Get the maximum scroll offset.
@@ -207,7 +282,7 @@ impl ScrollState {
     /// Reset scroll state (for new page load).
     pub fn reset(&mut self) {
         self.scroll_y = 0;
-        self.scroll_velocity = 0.0;
+        self.stop();
         self.content_height = 0;
     }
 }
@@ -284,6 +359,86 @@ mod tests {
         assert_eq!(s.content_height, 0);
     }
 
+    #[test]
+    fn smooth_scrolling_coasts_the_scroll_distance() {
+        let mut s = ScrollState::new(200, true);
+        s.set_content_height(1000);
+        s.scroll_down();
+        assert_eq!(s.scroll_y, 0, "smooth scrolling moves on tick");
+        let mut frames = 0;
+        while s.tick(16) {
+            frames += 1;
+        }
+        assert!(frames > 1);
+        assert!((20..=SCROLL_LINE).contains(&s.scroll_y), "{}", s.scroll_y);
+
+        // Flinging past the bottom stops there.
+        for _ in 0..5 {
+            s.page_down();
+        }
+        while s.tick(16) {
+            assert!(s.scroll_y <= s.max_scroll());
+        }
+        assert_eq!(s.scroll_y, s.max_scroll());
+    }
+
+    #[test]
+    fn smooth_scroll_to_visible_glides_to_the_target() {
+        let mut s = ScrollState::new(200, true);
+        s.set_content_height(1000);
+        s.scroll_to_visible(600, 20);
+        assert_eq!(s.scroll_y, 0, "smooth scrolling moves on tick");
+
+        let mut last = 0;
+        let mut frames = 0;
+        while s.tick(16) {
+            assert!(s.scroll_y >= last, "glides one way");
+            last = s.scroll_y;
+            frames += 1;
+        }
+        assert!(frames > 1);
+        assert_eq!(s.scroll_y, 510);
+
+        // Scrolling by hand takes over from a glide.
+        s.scroll_to_visible(0, 20);
+        s.tick(16);
+        s.scroll_down();
+        while s.tick(16) {}
+        assert!(s.scroll_y > 0);
+    }
+
+    #[test]
+    fn tuning_sets_steps_and_motion() {
+        let mut s = ScrollState::new(200, false);
+        s.set_content_height(1000);
+        s.set_tuning(ScrollTuning {
+            line_step: 10,
+            wheel_step: 30,
+            ..ScrollTuning::default()
+        });
+        s.scroll_down();
+        assert_eq!(s.scroll_y, 10);
+        s.wheel_scroll(2);
+        assert_eq!(s.scroll_y, 70);
+
+        // Snappier motion settles in fewer frames.
+        let frames_to_settle = |tuning: ScrollTuning| {
+            let mut s = ScrollState::new(200, true);
+            s.set_content_height(1000);
+            s.set_tuning(tuning);
+            s.scroll_to_visible(600, 20);
+            let mut frames = 0;
+            while s.tick(16) {
+                frames += 1;
+            }
+            assert_eq!(s.scroll_y, 510);
+            frames
+        };
+        assert!(
+            frames_to_settle(ScrollTuning::snappy()) < frames_to_settle(ScrollTuning::default())
+        );
+    }
+
     #[test]
     fn scroll_fraction_calculation() {
         let mut s = ScrollState::new(200, false);
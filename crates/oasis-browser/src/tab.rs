@@ -0,0 +1,106 @@
+//! Per-tab page state.
+//!
+//! Each tab of the [`BrowserWidget`](crate::BrowserWidget) owns its
+//! own history, scroll position, parsed page, and link/control maps.
+//! The resource cache, the parsed-page cache, and the browser chrome
+//! are shared by all tabs.
+
+use std::collections::HashMap;
+
+use crate::LoadingState;
+use crate::css::values::ComputedStyle;
+use crate::find::FindState;
+use crate::html::dom::{Document, NodeId};
+use crate::layout::box_model::LayoutBox;
+use crate::nav::NavigationController;
+use crate::paint::{ControlRegion, LinkRegion};
+use crate::scroll::ScrollState;
+
+/// Title shown in the tab strip for a tab with no page loaded.
+pub const NEW_TAB_TITLE: &str = "New tab";
+
+/// The state of one browser tab.
+pub struct BrowserTab {
+    /// Navigation controller (history, bookmarks).
+    pub(crate) nav: NavigationController,
+
+    /// Scroll state for the content viewport.
+    pub(crate) scroll: ScrollState,
+
+    /// Current loading state.
+    pub(crate) state: LoadingState,
+
+    /// The most recent error message (if any).
+    pub(crate) error_message: Option<String>,
+
+    /// Parsed DOM of the current page.
+    pub(crate) document: Option<Document>,
+
+    /// Computed styles indexed by `NodeId`.
+    pub(crate) styles: Vec<Option<ComputedStyle>>,
+
+    /// Layout tree root for the current page.
+    pub(crate) layout_root: Option<LayoutBox>,
+
+    /// Link regions from the most recent paint pass.
+    pub(crate) link_map: Vec<LinkRegion>,
+
+    /// Map from DOM `NodeId` to `href` for `<a>` elements.
+    pub(crate) href_map: HashMap<NodeId, String>,
+
+    /// Form control regions from the most recent paint pass.
+    pub(crate) control_map: Vec<ControlRegion>,
+
+    /// Values typed into form text inputs, by `NodeId`.
+    pub(crate) form_values: HashMap<NodeId, String>,
+
+    /// Index of the currently focused link or control (-1 = none).
+    /// Links come first, followed by form controls.
+    pub(crate) selected_link: i32,
+
+    /// In-page find query and matches.
+    pub(crate) find: FindState,
+
+    /// Whether reader mode is active.
+    pub(crate) reader_mode: bool,
+
+    /// Reader-mode article HTML (re-parsed when toggled).
+    pub(crate) reader_html: Option<String>,
+}
+
+impl BrowserTab {
+    /// Create an empty tab.
+    pub fn new(home_url: &str, viewport_height: i32, smooth_scroll: bool) -> Self {
+        Self {
+            nav: NavigationController::new(home_url),
+            scroll: ScrollState::new(viewport_height, smooth_scroll),
+            state: LoadingState::Idle,
+            error_message: None,
+            document: None,
+            styles: Vec::new(),
+            layout_root: None,
+            link_map: Vec::new(),
+            href_map: HashMap::new(),
+            control_map: Vec::new(),
+            form_values: HashMap::new(),
+            selected_link: -1,
+            find: FindState::default(),
+            reader_mode: false,
+            reader_html: None,
+        }
+    }
+
+    /// Remember the scroll position in the current history entry.
+    pub fn save_scroll(&mut self) {
+        self.nav.update_scroll(self.scroll.scroll_y);
+    }
+
+    /// Title for the tab strip: the page title, else its URL.
+    pub fn label(&self) -> &str {
+        match (self.nav.current_title(), self.nav.current_url()) {
+            (Some(title), _) if !title.is_empty() => title,
+            (_, Some(url)) => url,
+            _ => NEW_TAB_TITLE,
+        }
+    }
+}
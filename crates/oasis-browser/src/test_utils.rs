@@ -17,6 +17,14 @@ pub enum DrawCall {
         h: u32,
         color: Color,
     },
+    StrokeRect {
+        x: i32,
+        y: i32,
+        w: u32,
+        h: u32,
+        stroke_width: u16,
+        color: Color,
+    },
     DrawText {
         text: String,
         x: i32,
@@ -31,16 +39,29 @@ pub enum DrawCall {
         w: u32,
         h: u32,
     },
+    LoadTexture {
+        tex: TextureId,
+        w: u32,
+        h: u32,
+    },
+    DestroyTexture {
+        tex: TextureId,
+    },
 }
 
 /// A mock backend that records all draw calls for test assertions.
 pub struct MockBackend {
     pub calls: Vec<DrawCall>,
+    /// Fixed glyph advance to report instead of the bitmap font metrics.
+    pub glyph_width: Option<u32>,
 }
 
 impl MockBackend {
     pub fn new() -> Self {
-        Self { calls: Vec::new() }
+        Self {
+            calls: Vec::new(),
+            glyph_width: None,
+        }
     }
 
     /// Count of `FillRect` calls.
@@ -164,6 +185,26 @@ impl SdiBackend for MockBackend {
         Ok(())
     }
 
+    fn stroke_rect(
+        &mut self,
+        x: i32,
+        y: i32,
+        w: u32,
+        h: u32,
+        stroke_width: u16,
+        color: Color,
+    ) -> Result<()> {
+        self.calls.push(DrawCall::StrokeRect {
+            x,
+            y,
+            w,
+            h,
+            stroke_width,
+            color,
+        });
+        Ok(())
+    }
+
     fn draw_text(
         &mut self,
         text: &str,
@@ -186,11 +227,23 @@ impl SdiBackend for MockBackend {
         Ok(())
     }
 
-    fn load_texture(&mut self, _width: u32, _height: u32, _rgba_data: &[u8]) -> Result<TextureId> {
-        Ok(TextureId(0))
+    fn load_texture(&mut self, width: u32, height: u32, _rgba_data: &[u8]) -> Result<TextureId> {
+        let loaded = self
+            .calls
+            .iter()
+            .filter(|c| matches!(c, DrawCall::LoadTexture { .. }))
+            .count();
+        let tex = TextureId(loaded as u64);
+        self.calls.push(DrawCall::LoadTexture {
+            tex,
+            w: width,
+            h: height,
+        });
+        Ok(tex)
     }
 
-    fn destroy_texture(&mut self, _tex: TextureId) -> Result<()> {
+    fn destroy_texture(&mut self, tex: TextureId) -> Result<()> {
+        self.calls.push(DrawCall::DestroyTexture { tex });
         Ok(())
     }
 
@@ -203,7 +256,15 @@ impl SdiBackend for MockBackend {
     }
 
     fn measure_text(&self, text: &str, font_size: u16) -> u32 {
-        oasis_types::backend::bitmap_measure_text(text, font_size)
+        match self.glyph_width {
+            Some(w) => text.chars().count() as u32 * w,
+            None => oasis_types::backend::bitmap_measure_text(text, font_size),
+        }
+    }
+
+    fn glyph_advance(&self, ch: char, font_size: u16) -> u32 {
+        self.glyph_width
+            .unwrap_or_else(|| oasis_types::backend::bitmap_glyph_advance(ch, font_size))
     }
 
     fn read_pixels(&self, _x: i32, _y: i32, _w: u32, _h: u32) -> Result<Vec<u8>> {
@@ -214,3 +275,83 @@ impl SdiBackend for MockBackend {
         Ok(())
     }
 }
+
+/// Build an uncompressed 32-bit BMP of the given size, filled grey.
+pub fn bmp_image(width: u32, height: u32) -> Vec<u8> {
+    let pixel_bytes = width * height * 4;
+    let mut bmp = vec![0x80u8; 54 + pixel_bytes as usize];
+    bmp[0..2].copy_from_slice(b"BM");
+    bmp[2..6].copy_from_slice(&(54 + pixel_bytes).to_le_bytes());
+    bmp[6..10].fill(0);
+    bmp[10..14].copy_from_slice(&54u32.to_le_bytes());
+    bmp[14..18].copy_from_slice(&40u32.to_le_bytes());
+    bmp[18..22].copy_from_slice(&(width as i32).to_le_bytes());
+    bmp[22..26].copy_from_slice(&(height as i32).to_le_bytes());
+    bmp[26..28].copy_from_slice(&1u16.to_le_bytes());
+    bmp[28..30].copy_from_slice(&32u16.to_le_bytes());
+    bmp[30..54].fill(0);
+    bmp
+}
+
+/// Build a PNG holding `rows` (each led by its filter type byte) as an
+/// uncompressed zlib stream, with `extra` chunks before the image data.
+pub fn png_from_rows(
+    width: u32,
+    height: u32,
+    depth: u8,
+    color_type: u8,
+    rows: &[u8],
+    extra: &[(&[u8; 4], &[u8])],
+) -> Vec<u8> {
+    fn chunk(png: &mut Vec<u8>, kind: &[u8; 4], body: &[u8]) {
+        let mut crc = 0xFFFF_FFFFu32;
+        for &byte in kind.iter().chain(body) {
+            crc ^= u32::from(byte);
+            for _ in 0..8 {
+                crc = if crc & 1 != 0 {
+                    0xEDB8_8320 ^ (crc >> 1)
+                } else {
+                    crc >> 1
+                };
+            }
+        }
+        png.extend_from_slice(&(body.len() as u32).to_be_bytes());
+        png.extend_from_slice(kind);
+        png.extend_from_slice(body);
+        png.extend_from_slice(&(crc ^ 0xFFFF_FFFF).to_be_bytes());
+    }
+    let mut ihdr = Vec::new();
+    ihdr.extend_from_slice(&width.to_be_bytes());
+    ihdr.extend_from_slice(&height.to_be_bytes());
+    ihdr.extend_from_slice(&[depth, color_type, 0, 0, 0]);
+    // One final stored block, then the Adler-32 of the rows.
+    let mut zlib = vec![0x78, 0x01, 0x01];
+    zlib.extend_from_slice(&(rows.len() as u16).to_le_bytes());
+    zlib.extend_from_slice(&(!(rows.len() as u16)).to_le_bytes());
+    zlib.extend_from_slice(rows);
+    let (mut a, mut b) = (1u32, 0u32);
+    for &byte in rows {
+        a = (a + u32::from(byte)) % 65521;
+        b = (b + a) % 65521;
+    }
+    zlib.extend_from_slice(&(b << 16 | a).to_be_bytes());
+
+    let mut png = vec![0x89, b'P', b'N', b'G', 0x0D, 0x0A, 0x1A, 0x0A];
+    chunk(&mut png, b"IHDR", &ihdr);
+    for (kind, body) in extra {
+        chunk(&mut png, kind, body);
+    }
+    chunk(&mut png, b"IDAT", &zlib);
+    chunk(&mut png, b"IEND", &[]);
+    png
+}
+
+/// Build an 8-bit RGB PNG of the given size, filled grey.
+pub fn png_image(width: u32, height: u32) -> Vec<u8> {
+    let row = 1 + width as usize * 3;
+    let mut rows = vec![0x80u8; row * height as usize];
+    for filter in rows.iter_mut().step_by(row) {
+        *filter = 0;
+    }
+    png_from_rows(width, height, 8, 2, &rows, &[])
+}
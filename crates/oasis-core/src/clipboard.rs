@@ -0,0 +1,84 @@
+//! Process-wide clipboard.
+//!
+//! Text cut or copied in a [`TextInput`](crate::ui::text_input::TextInput)
+//! is stored here with [`Clipboard::copy`], which also mirrors it to the
+//! system clipboard on platforms that have one. A paste prefers the system
+//! clipboard, so text copied in other programs arrives too, and falls back
+//! to the text stored here.
+
+use std::sync::{Mutex, MutexGuard};
+
+use crate::backend::InputBackend;
+
+static CONTENTS: Mutex<Option<String>> = Mutex::new(None);
+
+/// The clipboard shared by every window and app in the process.
+pub struct Clipboard;
+
+impl Clipboard {
+    /// Replace the clipboard contents.
+    pub fn set(text: &str) {
+        *contents() = Some(text.to_string());
+    }
+
+    /// The clipboard contents, if anything has been copied.
+    pub fn get() -> Option<String> {
+        contents().clone()
+    }
+
+    /// Empty the clipboard.
+    pub fn clear() {
+        *contents() = None;
+    }
+
+    /// Put `text` on the clipboard and on the system clipboard, if the
+    /// backend has one.
+    pub fn copy(text: &str, backend: &mut dyn InputBackend) {
+        Self::set(text);
+        if let Err(e) = backend.set_clipboard_text(text) {
+            log::warn!("Failed to set the system clipboard: {e}");
+        }
+    }
+
+    /// Text to paste: the system clipboard's, else what was last copied.
+    pub fn paste_text(backend: &dyn InputBackend) -> Option<String> {
+        backend.clipboard_text().or_else(Self::get)
+    }
+}
+
+fn contents() -> MutexGuard<'static, Option<String>> {
+    // The contents are a plain string; a panic elsewhere cannot leave
+    // them half-written.
+    CONTENTS.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::input::InputEvent;
+
+    /// A backend without a system clipboard.
+    struct NoSystemClipboard;
+
+    impl InputBackend for NoSystemClipboard {
+        fn poll_events(&mut self) -> Vec<InputEvent> {
+            Vec::new()
+        }
+    }
+
+    // The clipboard is process-wide, so everything is checked in one test.
+    #[test]
+    fn copy_then_paste_without_a_system_clipboard() {
+        let mut backend = NoSystemClipboard;
+        Clipboard::clear();
+        assert_eq!(Clipboard::paste_text(&backend), None);
+        Clipboard::copy("vfs://index.html", &mut backend);
+        assert_eq!(Clipboard::get().as_deref(), Some("vfs://index.html"));
+        assert_eq!(
+            Clipboard::paste_text(&backend).as_deref(),
+            Some("vfs://index.html")
+        );
+        Clipboard::clear();
+        assert_eq!(Clipboard::get(), None);
+    }
+}
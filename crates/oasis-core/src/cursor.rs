@@ -5,6 +5,7 @@
 
 use crate::input::InputEvent;
 use crate::sdi::SdiRegistry;
+use crate::wm::CURSOR_Z;
 
 /// Cursor arrow dimensions.
 const CURSOR_W: u32 = 12;
@@ -56,7 +57,7 @@ impl CursorState {
             obj.w = CURSOR_W;
             obj.h = CURSOR_H;
             obj.overlay = true;
-            obj.z = 10000; // Always on top.
+            obj.z = CURSOR_Z; // Always on top.
         }
         if let Ok(obj) = sdi.get_mut(CURSOR_SDI_NAME) {
             obj.x = self.x;
@@ -19,16 +19,18 @@ pub mod agent;
 pub mod apps;
 pub use oasis_audio as audio;
 pub mod bottombar;
+pub mod clipboard;
 pub use oasis_browser as browser;
 pub mod cursor;
 pub mod dashboard;
 pub use oasis_net as net;
+pub mod notification;
 pub mod osk;
 pub use oasis_platform as platform;
 pub mod plugin;
-pub mod script;
 pub use oasis_sdi as sdi;
 pub use oasis_skin as skin;
+pub mod skin_assets;
 pub mod startmenu;
 pub mod statusbar;
 pub mod terminal;
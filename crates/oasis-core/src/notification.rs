@@ -0,0 +1,561 @@
+//! Notification center: queued toasts stacked in a screen corner.
+//!
+//! Any subsystem can [`post`](NotificationCenter::post) a [`Notification`].
+//! Up to `max_visible` toasts are shown at a time and the rest wait in
+//! the queue. [`tick`](NotificationCenter::tick) counts down the shown
+//! toasts and expires them; critical ones stay until dismissed with a
+//! click or Select. Every posted notification is also kept in a bounded
+//! history.
+//!
+//! Toasts are drawn in the window manager's
+//! [`NOTIFICATION_Z`](crate::wm::NOTIFICATION_Z) layer, above the windows
+//! and below the cursor.
+
+use std::collections::VecDeque;
+
+use crate::backend::{Color, SdiBackend};
+use crate::error::Result;
+use crate::input::{Button, InputEvent};
+use crate::skin::SkinTheme;
+use crate::skin::theme::parse_hex_color;
+use crate::ui::card::Card;
+use crate::ui::color::lighten;
+use crate::ui::icon::{Icon, IconAtlas};
+use crate::ui::panel::Panel;
+use crate::ui::{DrawContext, Padding, Theme, Widget};
+
+/// How long a toast stays up unless its notification says otherwise.
+pub const DEFAULT_DURATION_MS: u32 = 4000;
+
+/// Number of notifications the history keeps.
+pub const HISTORY_LEN: usize = 50;
+
+/// Space between the toasts and the edges of the area they are drawn in.
+const MARGIN: u32 = 6;
+
+/// Space between stacked toasts.
+const GAP: u32 = 4;
+
+/// Width of the accent stripe on the left of a toast.
+const STRIPE_W: u32 = 3;
+
+/// How much a notification asks for attention.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Urgency {
+    Low,
+    Normal,
+    /// Stays up until dismissed.
+    Critical,
+}
+
+/// A message posted to the [`NotificationCenter`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct Notification {
+    pub title: String,
+    pub body: String,
+    /// Drawn when the center has an icon atlas.
+    pub icon: Option<Icon>,
+    /// How long the toast stays up once shown. Ignored for critical
+    /// notifications.
+    pub duration_ms: u32,
+    pub urgency: Urgency,
+}
+
+impl Notification {
+    /// A normal notification shown for [`DEFAULT_DURATION_MS`].
+    pub fn new(title: impl Into<String>, body: impl Into<String>) -> Self {
+        Self {
+            title: title.into(),
+            body: body.into(),
+            icon: None,
+            duration_ms: DEFAULT_DURATION_MS,
+            urgency: Urgency::Normal,
+        }
+    }
+
+    /// A critical notification, shown until dismissed.
+    pub fn critical(title: impl Into<String>, body: impl Into<String>) -> Self {
+        Self {
+            urgency: Urgency::Critical,
+            ..Self::new(title, body)
+        }
+    }
+}
+
+/// Screen corner the toasts stack from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Corner {
+    TopLeft,
+    TopRight,
+    BottomLeft,
+    BottomRight,
+}
+
+impl Corner {
+    /// Parse a skin corner name such as `"bottom_left"`.
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "top_left" => Some(Self::TopLeft),
+            "top_right" => Some(Self::TopRight),
+            "bottom_left" => Some(Self::BottomLeft),
+            "bottom_right" => Some(Self::BottomRight),
+            _ => None,
+        }
+    }
+}
+
+/// Look of the toasts, derived from the active skin.
+pub struct NotificationTheme {
+    /// Colors, fonts and spacing handed to the card widget.
+    pub ui: Theme,
+    pub border: Color,
+    pub accent: Color,
+    pub critical_accent: Color,
+    /// Shadow elevation of a toast (0 = no shadow).
+    pub shadow_level: u8,
+    pub width: u32,
+    pub max_visible: usize,
+    pub corner: Corner,
+}
+
+impl Default for NotificationTheme {
+    fn default() -> Self {
+        Self::from_skin_theme(&SkinTheme::default())
+    }
+}
+
+impl NotificationTheme {
+    /// Build a `NotificationTheme` from a skin theme.
+    ///
+    /// Fine-grained `notification_overrides` in the skin are checked
+    /// first, falling back to colors derived from the base palette.
+    pub fn from_skin_theme(skin: &SkinTheme) -> Self {
+        let ov = |opt: Option<&String>, fallback: Color| -> Color {
+            opt.and_then(|s| parse_hex_color(s)).unwrap_or(fallback)
+        };
+        let nt = skin.notification_overrides.as_ref();
+
+        let mut ui = skin.to_ui_theme();
+        ui.surface = ov(
+            nt.and_then(|n| n.bg.as_ref()),
+            lighten(skin.background_color(), 0.10),
+        );
+        ui.text_primary = ov(nt.and_then(|n| n.title_text.as_ref()), skin.text_color());
+        ui.text_secondary = ov(nt.and_then(|n| n.body_text.as_ref()), skin.dim_text_color());
+        if let Some(radius) = nt.and_then(|n| n.border_radius) {
+            ui.border_radius_lg = radius;
+        }
+
+        Self {
+            border: ov(nt.and_then(|n| n.border.as_ref()), skin.secondary_color()),
+            accent: ov(nt.and_then(|n| n.accent.as_ref()), skin.primary_color()),
+            critical_accent: ov(
+                nt.and_then(|n| n.critical_accent.as_ref()),
+                skin.error_color(),
+            ),
+            shadow_level: nt
+                .and_then(|n| n.shadow_level)
+                .unwrap_or(skin.shadow_intensity.unwrap_or(1)),
+            width: nt.and_then(|n| n.width).unwrap_or(200),
+            max_visible: nt.and_then(|n| n.max_visible).unwrap_or(3).max(1),
+            corner: nt
+                .and_then(|n| n.corner.as_deref())
+                .and_then(Corner::from_name)
+                .unwrap_or(Corner::TopRight),
+            ui,
+        }
+    }
+}
+
+/// A posted notification waiting for, or in, its turn on screen.
+struct Toast {
+    id: u64,
+    notification: Notification,
+    remaining_ms: u32,
+}
+
+/// Queues, expires and draws notification toasts.
+pub struct NotificationCenter {
+    theme: NotificationTheme,
+    /// Posted and not yet expired or dismissed, oldest first. The first
+    /// `max_visible` are on screen.
+    queue: VecDeque<Toast>,
+    history: VecDeque<Notification>,
+    next_id: u64,
+    /// Where each toast was drawn last frame, for clicks.
+    rects: Vec<(u64, (i32, i32, u32, u32))>,
+    atlas: Option<IconAtlas>,
+}
+
+impl NotificationCenter {
+    pub fn new(theme: NotificationTheme) -> Self {
+        Self {
+            theme,
+            queue: VecDeque::new(),
+            history: VecDeque::new(),
+            next_id: 1,
+            rects: Vec::new(),
+            atlas: None,
+        }
+    }
+
+    /// Replace the theme (after a skin switch).
+    pub fn set_theme(&mut self, theme: NotificationTheme) {
+        self.theme = theme;
+    }
+
+    /// Draw notification icons from `atlas`.
+    pub fn set_icon_atlas(&mut self, atlas: IconAtlas) {
+        self.atlas = Some(atlas);
+    }
+
+    /// Queue a notification and record it in the history. Returns its id,
+    /// for [`dismiss`](Self::dismiss).
+    pub fn post(&mut self, notification: Notification) -> u64 {
+        let id = self.next_id;
+        self.next_id += 1;
+        if self.history.len() == HISTORY_LEN {
+            self.history.pop_front();
+        }
+        self.history.push_back(notification.clone());
+        self.queue.push_back(Toast {
+            id,
+            remaining_ms: notification.duration_ms,
+            notification,
+        });
+        id
+    }
+
+    /// Count down the toasts on screen and drop the expired ones. Queued
+    /// toasts start counting once they are shown.
+    pub fn tick(&mut self, dt_ms: u32) {
+        let shown = self.theme.max_visible;
+        for toast in self.queue.iter_mut().take(shown) {
+            if toast.notification.urgency != Urgency::Critical {
+                toast.remaining_ms = toast.remaining_ms.saturating_sub(dt_ms);
+            }
+        }
+        let mut index = 0;
+        self.queue.retain(|toast| {
+            index += 1;
+            index > shown
+                || toast.notification.urgency == Urgency::Critical
+                || toast.remaining_ms > 0
+        });
+    }
+
+    /// Remove a notification from the screen or the queue. Returns
+    /// whether it was there.
+    pub fn dismiss(&mut self, id: u64) -> bool {
+        let before = self.queue.len();
+        self.queue.retain(|toast| toast.id != id);
+        self.rects.retain(|(rect_id, _)| *rect_id != id);
+        self.queue.len() != before
+    }
+
+    /// Notifications on screen, oldest first.
+    pub fn visible(&self) -> impl Iterator<Item = &Notification> {
+        self.queue
+            .iter()
+            .take(self.theme.max_visible)
+            .map(|toast| &toast.notification)
+    }
+
+    /// Number of notifications on screen or waiting for a turn.
+    pub fn pending(&self) -> usize {
+        self.queue.len()
+    }
+
+    /// The last [`HISTORY_LEN`] posted notifications, oldest first.
+    pub fn history(&self) -> impl Iterator<Item = &Notification> {
+        self.history.iter()
+    }
+
+    /// The history, one notification per line, for `notify --history`.
+    pub fn history_text(&self) -> String {
+        self.history
+            .iter()
+            .map(|n| {
+                let tag = if n.urgency == Urgency::Critical {
+                    "[critical] "
+                } else {
+                    ""
+                };
+                if n.title.is_empty() {
+                    format!("{tag}{}", n.body)
+                } else {
+                    format!("{tag}{}: {}", n.title, n.body)
+                }
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    /// Dismiss a toast that was clicked, or the oldest critical toast on
+    /// Select. Returns whether the event was consumed.
+    pub fn handle_input(&mut self, event: &InputEvent) -> bool {
+        let id = match event {
+            InputEvent::PointerClick { x, y } => self
+                .rects
+                .iter()
+                .find(|(_, (rx, ry, rw, rh))| {
+                    *x >= *rx && *x < rx + *rw as i32 && *y >= *ry && *y < ry + *rh as i32
+                })
+                .map(|(id, _)| *id),
+            InputEvent::ButtonPress(Button::Select) => self
+                .queue
+                .iter()
+                .take(self.theme.max_visible)
+                .find(|toast| toast.notification.urgency == Urgency::Critical)
+                .map(|toast| toast.id),
+            _ => None,
+        };
+        id.is_some_and(|id| self.dismiss(id))
+    }
+
+    /// Draw the toasts on screen, stacked from the theme's corner of the
+    /// area `(x, y, w, h)`.
+    pub fn draw(
+        &mut self,
+        backend: &mut dyn SdiBackend,
+        x: i32,
+        y: i32,
+        w: u32,
+        h: u32,
+    ) -> Result<()> {
+        self.rects.clear();
+        let theme = &self.theme;
+        let toast_w = theme.width.min(w.saturating_sub(2 * MARGIN));
+        let left = matches!(theme.corner, Corner::TopLeft | Corner::BottomLeft);
+        let top = matches!(theme.corner, Corner::TopLeft | Corner::TopRight);
+        let tx = if left {
+            x + MARGIN as i32
+        } else {
+            x + w as i32 - MARGIN as i32 - toast_w as i32
+        };
+        let mut ty = if top {
+            y + MARGIN as i32
+        } else {
+            y + h as i32 - MARGIN as i32
+        };
+
+        let mut ctx = DrawContext::new(backend, &theme.ui);
+        for toast in self.queue.iter().take(theme.max_visible) {
+            let n = &toast.notification;
+            let mut card = Card::themed(n.title.as_str(), &ctx);
+            card.body = (!n.body.is_empty()).then(|| n.body.clone());
+            card.elevation = theme.shadow_level;
+            let (_, toast_h) = card.measure(&ctx, toast_w, 0);
+            if !top {
+                ty -= toast_h as i32;
+            }
+            // Stop once the stack runs out of room.
+            if ty < y || ty + toast_h as i32 > y + h as i32 {
+                break;
+            }
+
+            card.draw(&mut ctx, tx, ty, toast_w, toast_h)?;
+            let outline = Panel {
+                background: None,
+                border: Some((1, theme.border)),
+                radius: card.radius,
+                elevation: 0,
+                padding: Padding::ZERO,
+            };
+            outline.draw_at(&mut ctx, tx, ty, toast_w, toast_h)?;
+            let accent = if n.urgency == Urgency::Critical {
+                theme.critical_accent
+            } else {
+                theme.accent
+            };
+            ctx.backend.fill_rect(tx, ty, STRIPE_W, toast_h, accent)?;
+            if let (Some(icon), Some(atlas)) = (n.icon, &self.atlas) {
+                let pad = theme.ui.spacing_md as i32;
+                let ix = tx + toast_w as i32 - pad - atlas.icon_size as i32;
+                atlas.draw(ctx.backend, icon, ix, ty + pad, accent)?;
+            }
+
+            self.rects.push((toast.id, (tx, ty, toast_w, toast_h)));
+            if top {
+                ty += (toast_h + GAP) as i32;
+            } else {
+                ty -= GAP as i32;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Default for NotificationCenter {
+    fn default() -> Self {
+        Self::new(NotificationTheme::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::{TextureId, bitmap_measure_text};
+
+    /// Records the text drawn, with its position.
+    #[derive(Default)]
+    struct TextBackend {
+        texts: Vec<(String, i32, i32)>,
+    }
+
+    impl SdiBackend for TextBackend {
+        fn init(&mut self, _w: u32, _h: u32) -> Result<()> {
+            Ok(())
+        }
+        fn clear(&mut self, _color: Color) -> Result<()> {
+            Ok(())
+        }
+        fn blit(&mut self, _tex: TextureId, _x: i32, _y: i32, _w: u32, _h: u32) -> Result<()> {
+            Ok(())
+        }
+        fn fill_rect(&mut self, _x: i32, _y: i32, _w: u32, _h: u32, _color: Color) -> Result<()> {
+            Ok(())
+        }
+        fn draw_text(
+            &mut self,
+            text: &str,
+            x: i32,
+            y: i32,
+            _size: u16,
+            _color: Color,
+        ) -> Result<()> {
+            self.texts.push((text.to_string(), x, y));
+            Ok(())
+        }
+        fn swap_buffers(&mut self) -> Result<()> {
+            Ok(())
+        }
+        fn load_texture(&mut self, _w: u32, _h: u32, _data: &[u8]) -> Result<TextureId> {
+            Ok(TextureId(1))
+        }
+        fn destroy_texture(&mut self, _tex: TextureId) -> Result<()> {
+            Ok(())
+        }
+        fn set_clip_rect(&mut self, _x: i32, _y: i32, _w: u32, _h: u32) -> Result<()> {
+            Ok(())
+        }
+        fn reset_clip_rect(&mut self) -> Result<()> {
+            Ok(())
+        }
+        fn measure_text(&self, text: &str, font_size: u16) -> u32 {
+            bitmap_measure_text(text, font_size)
+        }
+        fn read_pixels(&self, _x: i32, _y: i32, w: u32, h: u32) -> Result<Vec<u8>> {
+            Ok(vec![0; (w * h * 4) as usize])
+        }
+        fn shutdown(&mut self) -> Result<()> {
+            Ok(())
+        }
+    }
+
+    fn titles(center: &NotificationCenter) -> Vec<&str> {
+        center.visible().map(|n| n.title.as_str()).collect()
+    }
+
+    #[test]
+    fn toasts_expire_and_queued_ones_take_their_turn() {
+        let mut center = NotificationCenter::default();
+        for title in ["a", "b", "c", "d"] {
+            center.post(Notification::new(title, "body"));
+        }
+        assert_eq!(titles(&center), ["a", "b", "c"]);
+        center.tick(DEFAULT_DURATION_MS - 1);
+        assert_eq!(center.pending(), 4);
+        center.tick(1);
+        assert_eq!(titles(&center), ["d"]);
+        // "d" waited in the queue, so its time starts now.
+        center.tick(DEFAULT_DURATION_MS - 1);
+        assert_eq!(titles(&center), ["d"]);
+        center.tick(1);
+        assert_eq!(center.pending(), 0);
+        assert_eq!(center.history().count(), 4);
+    }
+
+    #[test]
+    fn critical_toasts_stay_until_dismissed() {
+        let mut center = NotificationCenter::default();
+        center.post(Notification::critical("Battery", "5% left"));
+        let id = center.post(Notification::new("Track", "Next song"));
+        center.tick(60_000);
+        assert_eq!(titles(&center), ["Battery"]);
+
+        assert!(!center.handle_input(&InputEvent::ButtonPress(Button::Confirm)));
+        assert!(center.handle_input(&InputEvent::ButtonPress(Button::Select)));
+        assert_eq!(center.pending(), 0);
+        // Nothing critical left, so Select goes through.
+        assert!(!center.handle_input(&InputEvent::ButtonPress(Button::Select)));
+        assert!(!center.dismiss(id));
+    }
+
+    #[test]
+    fn history_keeps_the_last_fifty() {
+        let mut center = NotificationCenter::default();
+        center.post(Notification::critical("Disk", "full"));
+        for i in 0..HISTORY_LEN {
+            center.post(Notification::new("", format!("message {i}")));
+        }
+        let text = center.history_text();
+        assert_eq!(text.lines().count(), HISTORY_LEN);
+        assert_eq!(text.lines().next(), Some("message 0"));
+
+        let mut center = NotificationCenter::default();
+        center.post(Notification::critical("Disk", "full"));
+        assert_eq!(center.history_text(), "[critical] Disk: full");
+    }
+
+    #[test]
+    fn draw_stacks_in_the_corner_and_clicks_dismiss() {
+        let mut center = NotificationCenter::default();
+        center.post(Notification::critical("First", "one"));
+        center.post(Notification::critical("Second", "two"));
+        let mut backend = TextBackend::default();
+        center.draw(&mut backend, 0, 24, 480, 224).unwrap();
+
+        let pos = |text: &str| {
+            let (_, x, y) = backend.texts.iter().find(|t| t.0 == text).unwrap();
+            (*x, *y)
+        };
+        let (first_x, first_y) = pos("First");
+        let (second_x, second_y) = pos("Second");
+        // Top right by default: 200px wide, 6px in from the edges.
+        assert!(first_x > 480 - 200 - 6 && first_x < 480 - 6);
+        assert!(first_y > 24 + 6 && first_y < second_y);
+        assert_eq!(first_x, second_x);
+
+        assert!(!center.handle_input(&InputEvent::PointerClick { x: 10, y: 100 }));
+        assert!(center.handle_input(&InputEvent::PointerClick {
+            x: second_x,
+            y: second_y
+        }));
+        assert_eq!(titles(&center), ["First"]);
+    }
+
+    #[test]
+    fn theme_comes_from_the_skin() {
+        let skin: SkinTheme = toml::from_str(
+            "[notification_overrides]\n\
+             corner = \"bottom_left\"\n\
+             max_visible = 1\n\
+             critical_accent = \"#00FF00\"\n",
+        )
+        .unwrap();
+        let theme = NotificationTheme::from_skin_theme(&skin);
+        assert_eq!(theme.corner, Corner::BottomLeft);
+        assert_eq!(theme.critical_accent, Color::rgb(0, 255, 0));
+        assert_eq!(theme.accent, skin.primary_color());
+
+        let mut center = NotificationCenter::new(theme);
+        center.post(Notification::new("a", ""));
+        center.post(Notification::new("b", ""));
+        assert_eq!(titles(&center), ["a"]);
+        let mut backend = TextBackend::default();
+        center.draw(&mut backend, 0, 0, 480, 272).unwrap();
+        let (_, x, y) = &backend.texts[0];
+        assert!(*x < 40 && *y > 200);
+    }
+}
@@ -0,0 +1,234 @@
+//! Skin image assets.
+//!
+//! The images a skin lists under `[assets]` are decoded here, with the
+//! browser's image decoder, for every frontend. They are uploaded as
+//! textures and become the window manager's nine-patches: the window
+//! frame and the titlebar buttons. Without an asset, that part is drawn
+//! flat in the theme's colors.
+
+use crate::backend::SdiBackend;
+use crate::browser::image::decode_image;
+use crate::error::Result;
+use crate::sdi::SdiRegistry;
+use crate::skin::{BUTTON_NORMAL_ASSET, BUTTON_PRESSED_ASSET, Skin, WINDOW_FRAME_ASSET};
+use crate::ui::nine_patch::NinePatch;
+use crate::wm::window::WmTheme;
+
+/// The uploaded nine-patches of a skin's assets.
+///
+/// Call [`destroy`](Self::destroy) before dropping them (e.g. on a skin
+/// switch), or the textures stay allocated.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SkinAssets {
+    /// The `window_frame` asset.
+    pub window_frame: Option<NinePatch>,
+    /// The `button_normal` asset.
+    pub button_normal: Option<NinePatch>,
+    /// The `button_pressed` asset.
+    pub button_pressed: Option<NinePatch>,
+}
+
+impl SkinAssets {
+    /// Decode and upload the assets of `skin`. An asset the skin does
+    /// not have, or whose image cannot be decoded, is left out.
+    pub fn load(skin: &Skin, backend: &mut dyn SdiBackend) -> Result<Self> {
+        Ok(Self {
+            window_frame: load_patch(skin, WINDOW_FRAME_ASSET, backend)?,
+            button_normal: load_patch(skin, BUTTON_NORMAL_ASSET, backend)?,
+            button_pressed: load_patch(skin, BUTTON_PRESSED_ASSET, backend)?,
+        })
+    }
+
+    /// Have `theme` draw with these assets.
+    pub fn apply(&self, theme: &mut WmTheme) {
+        theme.frame_patch = self.window_frame;
+        theme.button_patch = self.button_normal;
+        theme.button_pressed_patch = self.button_pressed;
+    }
+
+    /// Move the SDI objects drawn with one of `old`'s nine-patches to
+    /// the matching one of these assets, so windows opened under the
+    /// previous skin switch too.
+    pub fn replace(&self, old: &SkinAssets, sdi: &mut SdiRegistry) {
+        let pairs = [
+            (old.window_frame, self.window_frame),
+            (old.button_normal, self.button_normal),
+            (
+                old.button_pressed,
+                self.button_pressed.or(self.button_normal),
+            ),
+        ];
+        let names: Vec<String> = sdi.names().map(str::to_string).collect();
+        for name in names {
+            let Ok(obj) = sdi.get_mut(&name) else {
+                continue;
+            };
+            let Some(texture) = obj.nine_patch.map(|p| p.texture) else {
+                continue;
+            };
+            if let Some((_, patch)) = pairs
+                .iter()
+                .find(|(old, _)| old.is_some_and(|p| p.texture == texture))
+            {
+                obj.nine_patch = *patch;
+            }
+        }
+    }
+
+    /// Free the textures.
+    pub fn destroy(self, backend: &mut dyn SdiBackend) -> Result<()> {
+        for patch in [self.window_frame, self.button_normal, self.button_pressed]
+            .into_iter()
+            .flatten()
+        {
+            backend.destroy_texture(patch.texture)?;
+        }
+        Ok(())
+    }
+}
+
+/// Upload the skin's asset `name` as a nine-patch texture. `None` if the
+/// skin has no such asset or its image cannot be decoded.
+fn load_patch(skin: &Skin, name: &str, backend: &mut dyn SdiBackend) -> Result<Option<NinePatch>> {
+    let Some((asset, bytes)) = skin.asset(name) else {
+        return Ok(None);
+    };
+    let Some(image) = decode_image(bytes) else {
+        log::warn!(
+            "Skin asset {name} ({}) is not a PNG, BMP or GIF image -- ignoring it",
+            asset.image
+        );
+        return Ok(None);
+    };
+    let texture = backend.load_texture(image.width, image.height, &image.pixels)?;
+    log::info!(
+        "Skin asset {name} loaded ({}x{})",
+        image.width,
+        image.height
+    );
+    Ok(Some(NinePatch {
+        texture,
+        tex_width: image.width,
+        tex_height: image.height,
+        left: asset.left,
+        right: asset.right,
+        top: asset.top,
+        bottom: asset.bottom,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::{Color, TextureId};
+
+    /// Hands out texture ids in order and records the freed ones.
+    #[derive(Default)]
+    struct TextureBackend {
+        loaded: Vec<(u32, u32, Vec<u8>)>,
+        destroyed: Vec<TextureId>,
+    }
+
+    impl SdiBackend for TextureBackend {
+        fn init(&mut self, _w: u32, _h: u32) -> Result<()> {
+            Ok(())
+        }
+        fn clear(&mut self, _color: Color) -> Result<()> {
+            Ok(())
+        }
+        fn blit(&mut self, _tex: TextureId, _x: i32, _y: i32, _w: u32, _h: u32) -> Result<()> {
+            Ok(())
+        }
+        fn fill_rect(&mut self, _x: i32, _y: i32, _w: u32, _h: u32, _color: Color) -> Result<()> {
+            Ok(())
+        }
+        fn draw_text(&mut self, _t: &str, _x: i32, _y: i32, _s: u16, _c: Color) -> Result<()> {
+            Ok(())
+        }
+        fn swap_buffers(&mut self) -> Result<()> {
+            Ok(())
+        }
+        fn load_texture(&mut self, w: u32, h: u32, data: &[u8]) -> Result<TextureId> {
+            self.loaded.push((w, h, data.to_vec()));
+            Ok(TextureId(self.loaded.len() as u64))
+        }
+        fn destroy_texture(&mut self, tex: TextureId) -> Result<()> {
+            self.destroyed.push(tex);
+            Ok(())
+        }
+        fn set_clip_rect(&mut self, _x: i32, _y: i32, _w: u32, _h: u32) -> Result<()> {
+            Ok(())
+        }
+        fn reset_clip_rect(&mut self) -> Result<()> {
+            Ok(())
+        }
+        fn measure_text(&self, _text: &str, _font_size: u16) -> u32 {
+            0
+        }
+        fn read_pixels(&self, _x: i32, _y: i32, w: u32, h: u32) -> Result<Vec<u8>> {
+            Ok(vec![0; (w * h * 4) as usize])
+        }
+        fn shutdown(&mut self) -> Result<()> {
+            Ok(())
+        }
+    }
+
+    fn patch(id: u64) -> NinePatch {
+        NinePatch {
+            texture: TextureId(id),
+            tex_width: 8,
+            tex_height: 8,
+            left: 2,
+            right: 2,
+            top: 2,
+            bottom: 2,
+        }
+    }
+
+    #[test]
+    fn classic_frame_is_decoded_and_uploaded() {
+        let skin = crate::skin::resolve_skin("classic").unwrap();
+        let mut backend = TextureBackend::default();
+        let assets = SkinAssets::load(&skin, &mut backend).unwrap();
+
+        let frame = assets.window_frame.unwrap();
+        assert_eq!((frame.tex_width, frame.tex_height), (16, 16));
+        assert_eq!((frame.left, frame.top), (3, 3));
+        assert_eq!(assets.button_normal, None);
+        let (w, h, rgba) = &backend.loaded[0];
+        assert_eq!(rgba.len(), (w * h * 4) as usize);
+
+        let mut theme = WmTheme::default();
+        assets.apply(&mut theme);
+        assert_eq!(theme.frame_patch, Some(frame));
+        assets.destroy(&mut backend).unwrap();
+        assert_eq!(backend.destroyed, [frame.texture]);
+    }
+
+    #[test]
+    fn replace_moves_objects_to_the_new_patches() {
+        let old = SkinAssets {
+            window_frame: Some(patch(1)),
+            button_normal: Some(patch(2)),
+            button_pressed: Some(patch(3)),
+        };
+        let new = SkinAssets {
+            window_frame: Some(patch(4)),
+            button_normal: Some(patch(5)),
+            button_pressed: None,
+        };
+        let mut sdi = SdiRegistry::new();
+        for (name, id) in [("frame", 1), ("button", 2), ("hovered", 3)] {
+            sdi.create(name).nine_patch = Some(patch(id));
+        }
+        sdi.create("plain");
+
+        new.replace(&old, &mut sdi);
+        let texture = |name| sdi.get(name).unwrap().nine_patch.map(|p| p.texture);
+        assert_eq!(texture("frame"), Some(TextureId(4)));
+        assert_eq!(texture("button"), Some(TextureId(5)));
+        // Without a pressed asset, the hovered button looks normal.
+        assert_eq!(texture("hovered"), Some(TextureId(5)));
+        assert_eq!(texture("plain"), None);
+    }
+}
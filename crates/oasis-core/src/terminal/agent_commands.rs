@@ -455,6 +455,7 @@ tools = [
             network: None,
             tls: None,
             stdin: None,
+            enforce_permissions: false,
         };
         reg.execute(line, &mut env)
     }
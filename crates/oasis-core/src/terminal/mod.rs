@@ -6,6 +6,7 @@
 
 pub mod agent_commands;
 pub mod plugin_commands;
+pub mod sdi;
 
 // Re-export everything from the oasis-terminal crate.
 pub use oasis_terminal::*;
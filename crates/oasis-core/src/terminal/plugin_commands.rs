@@ -108,6 +108,7 @@ mod tests {
             network: None,
             tls: None,
             stdin: None,
+            enforce_permissions: false,
         };
         reg.execute(line, &mut env)
     }
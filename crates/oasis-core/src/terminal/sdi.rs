@@ -0,0 +1,106 @@
+//! SDI objects for the full-screen terminal: the output area, the last
+//! [`VISIBLE_OUTPUT_LINES`] of scrollback and the prompt line.
+
+use crate::backend::Color;
+use crate::sdi::SdiRegistry;
+
+/// Maximum lines visible in the terminal output area (display limit).
+pub const VISIBLE_OUTPUT_LINES: usize = 12;
+
+/// Maximum lines retained in the scrollback buffer.
+pub const MAX_OUTPUT_LINES: usize = 200;
+
+/// Set terminal-mode SDI objects visible/hidden.
+pub fn set_terminal_visible(sdi: &mut SdiRegistry, visible: bool) {
+    if let Ok(obj) = sdi.get_mut("terminal_bg") {
+        obj.visible = visible;
+    }
+    for i in 0..VISIBLE_OUTPUT_LINES {
+        let name = format!("term_line_{i}");
+        if let Ok(obj) = sdi.get_mut(&name) {
+            obj.visible = visible;
+        }
+    }
+    if let Ok(obj) = sdi.get_mut("term_input_bg") {
+        obj.visible = visible;
+    }
+    if let Ok(obj) = sdi.get_mut("term_prompt") {
+        obj.visible = visible;
+    }
+}
+
+/// Create/update terminal-mode SDI objects. `hint` is a transient line
+/// shown below the output, e.g. completion candidates.
+pub fn setup_terminal_objects(
+    sdi: &mut SdiRegistry,
+    output_lines: &[String],
+    hint: Option<&str>,
+    cwd: &str,
+    input_buf: &str,
+) {
+    if !sdi.contains("terminal_bg") {
+        let obj = sdi.create("terminal_bg");
+        obj.x = 4;
+        obj.y = 26;
+        obj.w = 472;
+        obj.h = 220;
+        obj.color = Color::rgb(12, 12, 20);
+        obj.border_radius = Some(4);
+        obj.stroke_width = Some(1);
+        obj.stroke_color = Some(Color::rgba(255, 255, 255, 30));
+    }
+    if let Ok(obj) = sdi.get_mut("terminal_bg") {
+        obj.visible = true;
+    }
+
+    // Show the last VISIBLE_OUTPUT_LINES from the scrollback buffer.
+    let lines: Vec<&str> = output_lines
+        .iter()
+        .map(String::as_str)
+        .chain(hint)
+        .collect();
+    let start = lines.len().saturating_sub(VISIBLE_OUTPUT_LINES);
+    for i in 0..VISIBLE_OUTPUT_LINES {
+        let name = format!("term_line_{i}");
+        if !sdi.contains(&name) {
+            let obj = sdi.create(&name);
+            obj.x = 8;
+            obj.y = 28 + (i as i32) * 16;
+            obj.font_size = 12;
+            obj.text_color = Color::rgb(0, 200, 0);
+            obj.w = 0;
+            obj.h = 0;
+        }
+        if let Ok(obj) = sdi.get_mut(&name) {
+            obj.text = lines.get(start + i).map(|line| line.to_string());
+            obj.visible = true;
+        }
+    }
+
+    if !sdi.contains("term_input_bg") {
+        let obj = sdi.create("term_input_bg");
+        obj.x = 4;
+        obj.y = 248;
+        obj.w = 472;
+        obj.h = 20;
+        obj.color = Color::rgb(20, 20, 35);
+        obj.border_radius = Some(3);
+    }
+    if let Ok(obj) = sdi.get_mut("term_input_bg") {
+        obj.visible = true;
+    }
+
+    if !sdi.contains("term_prompt") {
+        let obj = sdi.create("term_prompt");
+        obj.x = 8;
+        obj.y = 250;
+        obj.font_size = 12;
+        obj.text_color = Color::rgb(100, 200, 255);
+        obj.w = 0;
+        obj.h = 0;
+    }
+    if let Ok(obj) = sdi.get_mut("term_prompt") {
+        obj.text = Some(format!("{cwd}> {input_buf}_"));
+        obj.visible = true;
+    }
+}
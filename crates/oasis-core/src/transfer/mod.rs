@@ -41,6 +41,7 @@ pub fn process_ftp_request(line: &str, vfs: &mut dyn Vfs) -> String {
                         let kind = match entry.kind {
                             crate::vfs::EntryKind::Directory => "d",
                             crate::vfs::EntryKind::File => "f",
+                            crate::vfs::EntryKind::Symlink => "l",
                         };
                         resp.push_str(&format!("{kind} {} {}\n", entry.size, entry.name));
                     }
@@ -104,6 +105,7 @@ pub fn process_ftp_request(line: &str, vfs: &mut dyn Vfs) -> String {
                     let kind = match meta.kind {
                         crate::vfs::EntryKind::Directory => "directory",
                         crate::vfs::EntryKind::File => "file",
+                        crate::vfs::EntryKind::Symlink => "symlink",
                     };
                     format!("200 {kind} {} bytes\n", meta.size)
                 },
@@ -287,6 +289,7 @@ mod tests {
             network: None,
             tls: None,
             stdin: None,
+            enforce_permissions: false,
         };
         reg.execute(line, &mut env)
     }
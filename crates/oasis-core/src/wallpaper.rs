@@ -1,7 +1,178 @@
-//! Procedural wallpaper generation.
+//! Procedural wallpaper generation and skin wallpaper images.
 //!
 //! Generates PSIX-style gradient wallpapers as raw RGBA pixel buffers.
 //! No external PNG files needed -- keeps CI clean and the binary self-contained.
+//!
+//! Skins may also name a wallpaper image. Decoding is left to the
+//! frontend; [`WallpaperImage`] takes the decoded RGBA buffer, uploads
+//! it, and draws it each frame stretched, tiled, or centered.
+
+use crate::backend::{Color, SdiBackend, TextureId};
+use crate::error::{OasisError, Result};
+
+/// How a wallpaper image fills the screen.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum WallpaperMode {
+    /// Scale the image to cover the whole screen.
+    #[default]
+    Stretch,
+    /// Repeat the image at its own size from the top-left corner.
+    Tile,
+    /// Draw the image once at its own size in the middle of the screen,
+    /// over the fallback color.
+    Center,
+}
+
+impl WallpaperMode {
+    /// Parse a skin `mode` value. Unknown names give `None`.
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "stretch" => Some(Self::Stretch),
+            "tile" => Some(Self::Tile),
+            "center" => Some(Self::Center),
+            _ => None,
+        }
+    }
+}
+
+/// A wallpaper image uploaded as a texture.
+///
+/// Call [`destroy`](Self::destroy) before dropping it (e.g. on a skin
+/// switch), or the texture stays allocated.
+#[derive(Debug)]
+pub struct WallpaperImage {
+    texture: TextureId,
+    width: u32,
+    height: u32,
+    mode: WallpaperMode,
+    fallback: Color,
+}
+
+impl WallpaperImage {
+    /// Upload a decoded `width * height` RGBA image.
+    pub fn load(
+        backend: &mut dyn SdiBackend,
+        width: u32,
+        height: u32,
+        rgba: &[u8],
+        mode: WallpaperMode,
+        fallback: Color,
+    ) -> Result<Self> {
+        if width == 0 || height == 0 || rgba.len() != width as usize * height as usize * 4 {
+            return Err(OasisError::Backend(format!(
+                "wallpaper image: {} bytes for {width}x{height}",
+                rgba.len()
+            )));
+        }
+        let texture = backend.load_texture(width, height, rgba)?;
+        Ok(Self {
+            texture,
+            width,
+            height,
+            mode,
+            fallback,
+        })
+    }
+
+    /// Draw the wallpaper over the whole `screen_w` x `screen_h` screen.
+    pub fn draw(&self, backend: &mut dyn SdiBackend, screen_w: u32, screen_h: u32) -> Result<()> {
+        if self.mode == WallpaperMode::Center {
+            backend.fill_rect(0, 0, screen_w, screen_h, self.fallback)?;
+        }
+        for b in blits(self.mode, self.width, self.height, screen_w, screen_h) {
+            backend.blit_sub(
+                self.texture,
+                b.src_x,
+                b.src_y,
+                b.src_w,
+                b.src_h,
+                b.dst_x,
+                b.dst_y,
+                b.dst_w,
+                b.dst_h,
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Free the texture.
+    pub fn destroy(self, backend: &mut dyn SdiBackend) -> Result<()> {
+        backend.destroy_texture(self.texture)
+    }
+}
+
+/// One `blit_sub` call: a source rectangle of the image and where it
+/// lands on screen.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Blit {
+    src_x: u32,
+    src_y: u32,
+    src_w: u32,
+    src_h: u32,
+    dst_x: i32,
+    dst_y: i32,
+    dst_w: u32,
+    dst_h: u32,
+}
+
+/// Blits that draw a `img_w` x `img_h` image in `mode`. Tiles and
+/// centered images are cropped to the screen, never scaled.
+fn blits(mode: WallpaperMode, img_w: u32, img_h: u32, screen_w: u32, screen_h: u32) -> Vec<Blit> {
+    match mode {
+        WallpaperMode::Stretch => vec![Blit {
+            src_x: 0,
+            src_y: 0,
+            src_w: img_w,
+            src_h: img_h,
+            dst_x: 0,
+            dst_y: 0,
+            dst_w: screen_w,
+            dst_h: screen_h,
+        }],
+        WallpaperMode::Tile => {
+            let mut out = Vec::new();
+            for y in (0..screen_h).step_by(img_h as usize) {
+                for x in (0..screen_w).step_by(img_w as usize) {
+                    let w = img_w.min(screen_w - x);
+                    let h = img_h.min(screen_h - y);
+                    out.push(Blit {
+                        src_x: 0,
+                        src_y: 0,
+                        src_w: w,
+                        src_h: h,
+                        dst_x: x as i32,
+                        dst_y: y as i32,
+                        dst_w: w,
+                        dst_h: h,
+                    });
+                }
+            }
+            out
+        },
+        WallpaperMode::Center => {
+            // Per axis: (source offset, screen offset, visible length).
+            let fit = |img: u32, screen: u32| {
+                if img > screen {
+                    ((img - screen) / 2, 0, screen)
+                } else {
+                    (0, (screen - img) / 2, img)
+                }
+            };
+            let (src_x, dst_x, w) = fit(img_w, screen_w);
+            let (src_y, dst_y, h) = fit(img_h, screen_h);
+            vec![Blit {
+                src_x,
+                src_y,
+                src_w: w,
+                src_h: h,
+                dst_x: dst_x as i32,
+                dst_y: dst_y as i32,
+                dst_w: w,
+                dst_h: h,
+            }]
+        },
+    }
+}
 
 /// Generate a vibrant gradient wallpaper matching PSIX's orange->yellow->green style.
 ///
@@ -237,6 +408,48 @@ mod tests {
         }
     }
 
+    #[test]
+    fn wallpaper_modes_parse() {
+        assert_eq!(WallpaperMode::from_name("tile"), Some(WallpaperMode::Tile));
+        assert_eq!(
+            WallpaperMode::from_name("center"),
+            Some(WallpaperMode::Center)
+        );
+        assert_eq!(
+            WallpaperMode::from_name("stretch"),
+            Some(WallpaperMode::Stretch)
+        );
+        assert_eq!(WallpaperMode::from_name("fill"), None);
+    }
+
+    #[test]
+    fn tiles_cover_screen_and_crop_at_edges() {
+        let tiles = blits(WallpaperMode::Tile, 200, 100, 480, 272);
+        assert_eq!(tiles.len(), 9);
+        let area: u32 = tiles.iter().map(|b| b.dst_w * b.dst_h).sum();
+        assert_eq!(area, 480 * 272);
+        let last = tiles.last().unwrap();
+        assert_eq!((last.dst_x, last.dst_y), (400, 200));
+        assert_eq!((last.src_w, last.src_h), (80, 72));
+        assert!(
+            tiles
+                .iter()
+                .all(|b| b.src_w == b.dst_w && b.src_h == b.dst_h)
+        );
+    }
+
+    #[test]
+    fn centered_image_is_placed_or_cropped() {
+        let small = blits(WallpaperMode::Center, 100, 50, 480, 272);
+        assert_eq!((small[0].dst_x, small[0].dst_y), (190, 111));
+        assert_eq!((small[0].dst_w, small[0].dst_h), (100, 50));
+
+        let large = blits(WallpaperMode::Center, 680, 300, 480, 272);
+        assert_eq!((large[0].src_x, large[0].src_y), (100, 14));
+        assert_eq!((large[0].dst_x, large[0].dst_y), (0, 0));
+        assert_eq!((large[0].src_w, large[0].src_h), (480, 272));
+    }
+
     #[test]
     fn gradient_not_uniform() {
         let buf = generate_gradient(480, 272);
@@ -23,7 +23,9 @@ use oasis_core::input::{Button, InputEvent, Trigger};
 use oasis_core::platform::DesktopPlatform;
 use oasis_core::sdi::SdiRegistry;
 use oasis_core::skin::Skin;
-use oasis_core::terminal::{CommandOutput, CommandRegistry, Environment, register_builtins};
+use oasis_core::terminal::{
+    CommandOutput, CommandRegistry, Environment, format_json, register_builtins,
+};
 use oasis_core::vfs::GameAssetVfs;
 
 // ---------------------------------------------------------------------------
@@ -456,6 +458,7 @@ pub unsafe extern "C" fn oasis_send_command(
         network: None,
         tls: None,
         stdin: None,
+        enforce_permissions: false,
     };
 
     let output = match instance.cmd_reg.execute(cmd_str, &mut env) {
@@ -468,6 +471,7 @@ pub unsafe extern "C" fn oasis_send_command(
             }
             out
         },
+        Ok(CommandOutput::Json(value)) => format_json(&value),
         Ok(CommandOutput::Clear) => String::new(),
         Ok(CommandOutput::None) => String::new(),
         Ok(CommandOutput::ListenToggle { .. }) | Ok(CommandOutput::RemoteConnect { .. }) => {
@@ -477,9 +481,16 @@ pub unsafe extern "C" fn oasis_send_command(
             let state = if enable { "on" } else { "off" };
             format!("Browser sandbox: {state}")
         },
+        Ok(CommandOutput::BrowserCache { .. }) => "Not available via FFI.".to_string(),
         Ok(CommandOutput::SkinSwap { name }) => {
             format!("Skin swap to '{name}' not available via FFI.")
         },
+        Ok(
+            CommandOutput::SkinWatch { .. }
+            | CommandOutput::SkinReload
+            | CommandOutput::SkinIntensity { .. }
+            | CommandOutput::ThemeColor { .. },
+        ) => "Not available via FFI.".to_string(),
         Ok(CommandOutput::Multi(outputs)) => {
             let mut parts = Vec::new();
             for output in outputs {
@@ -493,23 +504,30 @@ pub unsafe extern "C" fn oasis_send_command(
                         }
                         out
                     },
+                    CommandOutput::Json(value) => format_json(&value),
                     CommandOutput::Clear | CommandOutput::None => continue,
                     CommandOutput::SkinSwap { name } => {
                         format!("Skin swap to '{name}' not available via FFI.")
                     },
-                    CommandOutput::ListenToggle { .. } | CommandOutput::RemoteConnect { .. } => {
-                        "Not available via FFI.".to_string()
-                    },
+                    CommandOutput::ListenToggle { .. }
+                    | CommandOutput::RemoteConnect { .. }
+                    | CommandOutput::BrowserCache { .. }
+                    | CommandOutput::SkinWatch { .. }
+                    | CommandOutput::SkinReload
+                    | CommandOutput::SkinIntensity { .. }
+                    | CommandOutput::ThemeColor { .. } => "Not available via FFI.".to_string(),
                     CommandOutput::BrowserSandbox { enable } => {
                         let state = if enable { "on" } else { "off" };
                         format!("Browser sandbox: {state}")
                     },
-                    CommandOutput::Multi(_) => continue,
+                    CommandOutput::Multi(_) | CommandOutput::Status { .. } => continue,
                 };
                 parts.push(text);
             }
             parts.join("\n")
         },
+        // Unwrapped by the registry; never returned from `execute`.
+        Ok(CommandOutput::Status { .. }) => String::new(),
         Err(e) => format!("error: {e}"),
     };
 
@@ -13,7 +13,7 @@ use rustls::pki_types::ServerName;
 use oasis_types::backend::NetworkStream;
 use oasis_types::error::{OasisError, Result};
 
-use super::tls::TlsProvider;
+use super::tls::{PeerCertificate, TlsProvider, x509_not_after};
 
 /// Shared, reusable TLS client configuration (one per process).
 ///
@@ -38,6 +38,18 @@ impl RustlsTlsProvider {
             config: Arc::new(config),
         }
     }
+
+    /// Start a client session for `server_name` and complete the
+    /// handshake over `stream`.
+    fn handshake(&self, stream: Box<dyn NetworkStream>, server_name: &str) -> Result<RustlsStream> {
+        let sni = ServerName::try_from(server_name.to_owned())
+            .map_err(|e| OasisError::Backend(format!("invalid server name: {e}")))?;
+
+        let conn = rustls::ClientConnection::new(Arc::clone(&self.config), sni)
+            .map_err(|e| OasisError::Backend(format!("TLS init: {e}")))?;
+
+        RustlsStream::new(conn, stream)
+    }
 }
 
 impl Default for RustlsTlsProvider {
@@ -52,13 +64,27 @@ impl TlsProvider for RustlsTlsProvider {
         stream: Box<dyn NetworkStream>,
         server_name: &str,
     ) -> Result<Box<dyn NetworkStream>> {
-        let sni = ServerName::try_from(server_name.to_owned())
-            .map_err(|e| OasisError::Backend(format!("invalid server name: {e}")))?;
+        Ok(Box::new(self.handshake(stream, server_name)?))
+    }
 
-        let conn = rustls::ClientConnection::new(Arc::clone(&self.config), sni)
-            .map_err(|e| OasisError::Backend(format!("TLS init: {e}")))?;
+    fn connect_tls_peer(
+        &self,
+        stream: Box<dyn NetworkStream>,
+        server_name: &str,
+    ) -> Result<(Box<dyn NetworkStream>, Option<PeerCertificate>)> {
+        let stream = self.handshake(stream, server_name)?;
+        let cert = stream.peer_certificate();
+        Ok((Box::new(stream), cert))
+    }
+}
 
-        Ok(Box::new(RustlsStream::new(conn, stream)?))
+/// Describe a DER-encoded leaf certificate for pinning.
+fn describe_certificate(der: &[u8]) -> PeerCertificate {
+    let digest = ring::digest::digest(&ring::digest::SHA256, der);
+    let fingerprint = digest.as_ref().iter().map(|b| format!("{b:02x}")).collect();
+    PeerCertificate {
+        fingerprint,
+        not_after: x509_not_after(der),
     }
 }
 
@@ -125,6 +151,12 @@ impl RustlsStream {
         })
     }
 
+    /// The server's leaf certificate, once the handshake is done.
+    fn peer_certificate(&self) -> Option<PeerCertificate> {
+        let leaf = self.tls.peer_certificates()?.first()?;
+        Some(describe_certificate(leaf.as_ref()))
+    }
+
     /// Pump ciphertext from the network into rustls and move any resulting
     /// plaintext into `self.plaintext_buf`.
     fn pull_plaintext(&mut self) -> Result<()> {
@@ -611,6 +643,28 @@ mod tests {
         let _ = handle.join();
     }
 
+    #[test]
+    fn test_connect_tls_peer_reports_leaf_certificate() {
+        let (server_cfg, cert_key) = make_server_config();
+        let provider = make_client_config(&cert_key);
+        let (handle, port) = spawn_server(server_cfg, Vec::new());
+        let tcp = TcpStream::connect(format!("127.0.0.1:{port}")).unwrap();
+        tcp.set_read_timeout(Some(std::time::Duration::from_secs(5)))
+            .unwrap();
+        let (mut stream, cert) = provider
+            .connect_tls_peer(Box::new(TcpNetworkStream(tcp)), "localhost")
+            .unwrap();
+
+        let cert = cert.expect("rustls should report the peer certificate");
+        assert_eq!(cert, describe_certificate(cert_key.cert.der()));
+        assert_eq!(cert.fingerprint.len(), 64);
+        // rcgen's default validity ends on 4096-01-01.
+        assert_eq!(cert.not_after, Some(67_090_118_400));
+
+        let _ = stream.close();
+        let _ = handle.join();
+    }
+
     #[test]
     fn test_provider_is_send_sync() {
         fn assert_send_sync<T: Send + Sync>() {}
@@ -62,6 +62,24 @@ pub struct SystemTime {
     pub second: u8,
 }
 
+impl SystemTime {
+    /// Break seconds since the Unix epoch down into UTC date and time
+    /// (no time zone handling -- good enough for an embedded OS).
+    pub fn from_unix_secs(secs: u64) -> Self {
+        let days = secs / 86400;
+        let time_of_day = secs % 86400;
+        let (year, month, day) = days_to_ymd(days);
+        Self {
+            year,
+            month,
+            day,
+            hour: (time_of_day / 3600) as u8,
+            minute: ((time_of_day % 3600) / 60) as u8,
+            second: (time_of_day % 60) as u8,
+        }
+    }
+}
+
 impl std::fmt::Display for SystemTime {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(
@@ -175,6 +193,9 @@ pub struct WifiInfo {
 pub struct HttpResponse {
     /// HTTP status code (e.g. 200, 404).
     pub status_code: u16,
+    /// Response headers in the order received, as (name, value) pairs.
+    /// Empty if the platform does not report them.
+    pub headers: Vec<(String, String)>,
     /// Response body as bytes.
     pub body: Vec<u8>,
 }
@@ -250,26 +271,7 @@ impl TimeService for DesktopPlatform {
         let dur = StdTime::now()
             .duration_since(StdTime::UNIX_EPOCH)
             .unwrap_or_default();
-        let secs = dur.as_secs();
-
-        // Simple UTC breakdown (no TZ handling -- good enough for an embedded OS).
-        let days = secs / 86400;
-        let time_of_day = secs % 86400;
-        let hour = (time_of_day / 3600) as u8;
-        let minute = ((time_of_day % 3600) / 60) as u8;
-        let second = (time_of_day % 60) as u8;
-
-        // Days since 1970-01-01 to Y-M-D.
-        let (year, month, day) = days_to_ymd(days);
-
-        Ok(SystemTime {
-            year,
-            month,
-            day,
-            hour,
-            minute,
-            second,
-        })
+        Ok(SystemTime::from_unix_secs(dur.as_secs()))
     }
 
     fn uptime_secs(&self) -> Result<u64> {
@@ -920,6 +922,7 @@ mod tests {
     fn http_response_debug_format() {
         let resp = HttpResponse {
             status_code: 200,
+            headers: Vec::new(),
             body: vec![1, 2, 3],
         };
         let debug_str = format!("{:?}", resp);
@@ -930,6 +933,7 @@ mod tests {
     fn http_response_clone() {
         let resp1 = HttpResponse {
             status_code: 404,
+            headers: Vec::new(),
             body: vec![4, 5, 6],
         };
         let resp2 = resp1.clone();
@@ -1010,6 +1014,13 @@ mod tests {
 
     // ---- Date helper function tests ----
 
+    #[test]
+    fn system_time_from_unix_secs() {
+        // 2024-02-29 13:05:09 UTC.
+        let t = SystemTime::from_unix_secs(19782 * 86400 + 13 * 3600 + 5 * 60 + 9);
+        assert_eq!(t.to_string(), "2024-02-29 13:05:09");
+    }
+
     #[test]
     fn days_to_ymd_zero() {
         let (y, m, d) = days_to_ymd(0);
@@ -6,6 +6,7 @@
 //! naming conventions.
 
 use oasis_types::backend::{Color, TextureId};
+use oasis_types::nine_patch::NinePatch;
 
 /// A single object in the SDI scene graph.
 #[derive(Debug, Clone)]
@@ -56,6 +57,9 @@ pub struct SdiObject {
     pub stroke_color: Option<Color>,
     /// Custom shadow color (default: black).
     pub shadow_color: Option<Color>,
+    /// Nine-patch stretched over the object's bounds. Takes precedence
+    /// over `texture` and every color fill.
+    pub nine_patch: Option<NinePatch>,
 }
 
 impl SdiObject {
@@ -83,6 +87,7 @@ impl SdiObject {
             stroke_width: None,
             stroke_color: None,
             shadow_color: None,
+            nine_patch: None,
         }
     }
 }
@@ -100,5 +105,6 @@ mod tests {
         assert_eq!(obj.alpha, 255);
         assert!(obj.visible);
         assert!(obj.texture.is_none());
+        assert!(obj.nine_patch.is_none());
     }
 }
@@ -5,6 +5,7 @@
 //! objects in z-order and dispatches to the rendering backend.
 
 use std::collections::HashMap;
+use std::ops::{Range, RangeBounds};
 
 use serde::Deserialize;
 
@@ -15,7 +16,7 @@ use oasis_types::shadow::Shadow;
 use crate::object::SdiObject;
 
 /// The SDI scene graph: a flat, named registry of blittable objects.
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct SdiRegistry {
     objects: HashMap<String, SdiObject>,
     /// Monotonically increasing counter for assigning z-order to new objects.
@@ -90,6 +91,14 @@ impl SdiRegistry {
         Ok(())
     }
 
+    /// Put an object at z-order `z`, e.g. into a band reserved for a layer.
+    pub fn set_z(&mut self, name: &str, z: i32) -> Result<()> {
+        let obj = self.get_mut(name)?;
+        obj.z = z;
+        self.z_dirty = true;
+        Ok(())
+    }
+
     /// Move an object to the bottom of the z-order (drawn first = behind).
     pub fn move_to_bottom(&mut self, name: &str) -> Result<()> {
         let min_z = self.objects.values().map(|o| o.z).min().unwrap_or(0) - 1;
@@ -235,36 +244,69 @@ impl SdiRegistry {
     /// overlay objects on top.
     pub fn draw(&mut self, backend: &mut dyn SdiBackend) -> Result<()> {
         self.ensure_z_sorted();
+        self.draw_pass(backend, false, ..)?;
+        self.draw_pass(backend, true, ..)
+    }
 
-        // Pass 1: base layer (overlay == false).
-        for name in &self.z_sorted_names {
-            let obj = &self.objects[name];
-            if obj.overlay || !obj.visible || obj.alpha == 0 {
-                continue;
-            }
-            Self::draw_object(obj, backend)?;
-        }
+    /// Draw like [`draw`](Self::draw), but leave out the overlay objects
+    /// at or above `z`, so the caller can draw between them and the rest.
+    /// Finish with [`draw_overlay_from`](Self::draw_overlay_from).
+    pub fn draw_below(&mut self, backend: &mut dyn SdiBackend, z: i32) -> Result<()> {
+        self.ensure_z_sorted();
+        self.draw_pass(backend, false, ..)?;
+        self.draw_pass(backend, true, ..z)
+    }
+
+    /// Draw the overlay objects at or above `z` that
+    /// [`draw_below`](Self::draw_below) left out.
+    pub fn draw_overlay_from(&mut self, backend: &mut dyn SdiBackend, z: i32) -> Result<()> {
+        self.ensure_z_sorted();
+        self.draw_pass(backend, true, z..)
+    }
+
+    /// Draw the overlay objects whose z is in `z`, for callers drawing
+    /// between bands of the overlay layer.
+    pub fn draw_overlay_range(
+        &mut self,
+        backend: &mut dyn SdiBackend,
+        z: Range<i32>,
+    ) -> Result<()> {
+        self.ensure_z_sorted();
+        self.draw_pass(backend, true, z)
+    }
 
-        // Pass 2: overlay layer (overlay == true).
+    /// Draw the visible objects of one layer whose z is in `z_range`.
+    fn draw_pass(
+        &self,
+        backend: &mut dyn SdiBackend,
+        overlay: bool,
+        z_range: impl RangeBounds<i32>,
+    ) -> Result<()> {
         for name in &self.z_sorted_names {
             let obj = &self.objects[name];
-            if !obj.overlay || !obj.visible || obj.alpha == 0 {
+            if obj.overlay != overlay || !obj.visible || obj.alpha == 0 || !z_range.contains(&obj.z)
+            {
                 continue;
             }
             Self::draw_object(obj, backend)?;
         }
-
         Ok(())
     }
 
     /// Render a single SDI object to the backend.
     ///
-    /// Dispatch order for non-textured objects with nonzero area:
+    /// Nine-patch and textured objects are blitted. Dispatch order for
+    /// the others, when they have a nonzero area:
     /// 1. Shadow (if `shadow_level > 0`)
     /// 2. Fill: gradient+radius → gradient → rounded → flat (existing)
     /// 3. Stroke (if `stroke_width` set)
     /// 4. Text (if present)
     fn draw_object(obj: &SdiObject, backend: &mut dyn SdiBackend) -> Result<()> {
+        // Nine-patch object -- stretch the patch over the bounds.
+        if let Some(patch) = &obj.nine_patch {
+            return patch.draw(backend, obj.x, obj.y, obj.w, obj.h);
+        }
+
         // Textured object -- blit the texture.
         if let Some(tex) = obj.texture {
             backend.blit(tex, obj.x, obj.y, obj.w, obj.h)?;
@@ -724,6 +766,46 @@ font_size = 16
         );
     }
 
+    #[test]
+    fn draw_below_leaves_high_overlays_for_later() {
+        let mut reg = SdiRegistry::new();
+        for (name, z, overlay) in [
+            ("window", 9500, false),
+            ("bar", 900, true),
+            ("cursor", 10000, true),
+        ] {
+            let obj = reg.create(name);
+            obj.text = Some(name.to_string());
+            obj.z = z;
+            obj.overlay = overlay;
+        }
+        let texts = |calls: &Rc<RefCell<Vec<DrawCall>>>| -> Vec<String> {
+            calls
+                .borrow()
+                .iter()
+                .filter_map(|c| match c {
+                    DrawCall::DrawText { text, .. } => Some(text.clone()),
+                    _ => None,
+                })
+                .collect()
+        };
+
+        let (mut backend, calls) = RecordingBackend::new();
+        reg.draw_below(&mut backend, 9000).unwrap();
+        // Base-layer objects are drawn whatever their z.
+        assert_eq!(texts(&calls), ["window", "bar"]);
+
+        let (mut backend, calls) = RecordingBackend::new();
+        reg.draw_overlay_from(&mut backend, 9000).unwrap();
+        assert_eq!(texts(&calls), ["cursor"]);
+
+        // A band of the overlay layer on its own.
+        reg.set_z("bar", 9100).unwrap();
+        let (mut backend, calls) = RecordingBackend::new();
+        reg.draw_overlay_range(&mut backend, 9000..9500).unwrap();
+        assert_eq!(texts(&calls), ["bar"]);
+    }
+
     #[test]
     fn draw_skips_zero_alpha_objects() {
         let mut reg = SdiRegistry::new();
@@ -7,6 +7,7 @@
 
 use oasis_types::backend::Color;
 use oasis_types::color::{lighten, with_alpha};
+use oasis_wm::WmTheme;
 
 use crate::SkinTheme;
 use crate::theme::parse_hex_color;
@@ -186,6 +187,11 @@ pub struct ActiveTheme {
     pub wallpaper_wave_intensity: f32,
     /// Gradient angle in degrees.
     pub wallpaper_angle: f32,
+    /// How a wallpaper image fills the screen: "stretch" (default),
+    /// "tile", or "center".
+    pub wallpaper_mode: String,
+    /// Color behind a centered wallpaper image.
+    pub wallpaper_fallback: Color,
 }
 
 impl Default for ActiveTheme {
@@ -274,6 +280,8 @@ impl Default for ActiveTheme {
             wallpaper_wave: true,
             wallpaper_wave_intensity: 1.0,
             wallpaper_angle: 0.0,
+            wallpaper_mode: "stretch".to_string(),
+            wallpaper_fallback: Color::BLACK,
         }
     }
 }
@@ -526,6 +534,17 @@ impl ActiveTheme {
                 .and_then(|w| w.wave_intensity)
                 .unwrap_or(1.0),
             wallpaper_angle: skin.wallpaper.as_ref().and_then(|w| w.angle).unwrap_or(0.0),
+            wallpaper_mode: skin
+                .wallpaper
+                .as_ref()
+                .and_then(|w| w.mode.clone())
+                .unwrap_or_else(|| "stretch".to_string()),
+            wallpaper_fallback: skin
+                .wallpaper
+                .as_ref()
+                .and_then(|w| w.fallback_color.as_deref())
+                .and_then(parse_hex_color)
+                .unwrap_or(Color::BLACK),
             statusbar_gradient_top: Self::bar_gradient_pair(
                 skin,
                 bar.and_then(|b| b.statusbar_gradient_top.as_ref()),
@@ -582,6 +601,109 @@ impl ActiveTheme {
     }
 }
 
+/// Implement by-name access to the listed `ActiveTheme` color fields.
+macro_rules! color_fields {
+    ($($field:ident),* $(,)?) => {
+        impl ActiveTheme {
+            /// Names of the color fields, as accepted by
+            /// [`ActiveTheme::color_mut`].
+            pub const COLOR_KEYS: &[&str] = &[$(stringify!($field)),*];
+
+            /// The color field called `key`, for editing the theme live
+            /// (`theme set`).
+            pub fn color_mut(&mut self, key: &str) -> Option<&mut Color> {
+                match key {
+                    $(stringify!($field) => Some(&mut self.$field),)*
+                    _ => None,
+                }
+            }
+        }
+    };
+}
+
+color_fields!(
+    statusbar_bg,
+    bar_bg,
+    separator_color,
+    battery_color,
+    version_color,
+    clock_color,
+    url_color,
+    usb_color,
+    tab_active_fill,
+    tab_inactive_fill,
+    media_tab_active,
+    media_tab_inactive,
+    pipe_color,
+    r_hint_color,
+    category_label_color,
+    page_dot_active,
+    page_dot_inactive,
+    icon_body_color,
+    icon_fold_color,
+    icon_outline_color,
+    icon_shadow_color,
+    icon_label_color,
+    cursor_color,
+    sm_panel_bg,
+    sm_panel_border,
+    sm_item_text,
+    sm_item_text_active,
+    sm_highlight_color,
+    sm_button_bg,
+    sm_button_text,
+    sm_header_bg,
+    sm_header_text_color,
+    sm_footer_bg,
+    sm_footer_text_color,
+    wallpaper_fallback,
+);
+
+/// Window manager colors by their `[wm_theme]` key in theme.toml, as
+/// accepted by [`wm_color_mut`]. `separator_color` is left out; the name
+/// belongs to [`ActiveTheme`].
+pub const WM_COLOR_KEYS: &[&str] = &[
+    "titlebar_active",
+    "titlebar_inactive",
+    "titlebar_text",
+    "frame_color",
+    "content_bg",
+    "btn_close",
+    "btn_minimize",
+    "btn_maximize",
+    "btn_close_hover",
+    "btn_minimize_hover",
+    "btn_maximize_hover",
+    "glyph_close_color",
+    "glyph_minimize_color",
+    "glyph_maximize_color",
+    "title_text_shadow_color",
+    "content_stroke_color",
+];
+
+/// The window manager color with theme.toml key `key`.
+pub fn wm_color_mut<'a>(theme: &'a mut WmTheme, key: &str) -> Option<&'a mut Color> {
+    Some(match key {
+        "titlebar_active" => &mut theme.titlebar_active_color,
+        "titlebar_inactive" => &mut theme.titlebar_inactive_color,
+        "titlebar_text" => &mut theme.titlebar_text_color,
+        "frame_color" => &mut theme.frame_color,
+        "content_bg" => &mut theme.content_bg_color,
+        "btn_close" => &mut theme.btn_close_color,
+        "btn_minimize" => &mut theme.btn_minimize_color,
+        "btn_maximize" => &mut theme.btn_maximize_color,
+        "btn_close_hover" => &mut theme.btn_close_hover,
+        "btn_minimize_hover" => &mut theme.btn_minimize_hover,
+        "btn_maximize_hover" => &mut theme.btn_maximize_hover,
+        "glyph_close_color" => &mut theme.glyph_close_color,
+        "glyph_minimize_color" => &mut theme.glyph_minimize_color,
+        "glyph_maximize_color" => &mut theme.glyph_maximize_color,
+        "title_text_shadow_color" => &mut theme.title_text_shadow_color,
+        "content_stroke_color" => &mut theme.content_stroke_color,
+        _ => return None,
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -656,4 +778,23 @@ error = "#FF0000"
         assert_eq!(at.clock_color, Color::rgb(0, 255, 0));
         assert_eq!(at.media_tab_active, Color::rgb(0, 255, 0));
     }
+
+    #[test]
+    fn colors_are_addressable_by_name() {
+        let mut at = ActiveTheme::default();
+        for key in ActiveTheme::COLOR_KEYS {
+            assert!(at.color_mut(key).is_some(), "{key}");
+        }
+        *at.color_mut("clock_color").unwrap() = Color::rgb(1, 2, 3);
+        assert_eq!(at.clock_color, Color::rgb(1, 2, 3));
+        assert!(at.color_mut("sm_columns").is_none());
+
+        let mut wm = WmTheme::default();
+        for key in WM_COLOR_KEYS {
+            assert!(wm_color_mut(&mut wm, key).is_some(), "{key}");
+            assert!(!ActiveTheme::COLOR_KEYS.contains(key), "{key}");
+        }
+        *wm_color_mut(&mut wm, "titlebar_active").unwrap() = Color::rgb(0x20, 0x46, 0x82);
+        assert_eq!(wm.titlebar_active_color, Color::rgb(0x20, 0x46, 0x82));
+    }
 }
@@ -288,6 +288,9 @@ alpha_flicker_chance = 0.15
 alpha_flicker_min = 60
 text_garble_chance = 0.08
 intensity = 1.0
+ramp_seconds = 3.0
+burst_every_seconds = 8.0
+burst_frames = 12
 "#;
 
 // ---------------------------------------------------------------------------
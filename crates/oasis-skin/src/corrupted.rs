@@ -3,6 +3,13 @@
 //! Provides visual distortion hooks that the Corrupted skin applies each
 //! frame. The WM and SDI remain functional -- the modifiers only inject
 //! cosmetic glitches.
+//!
+//! Glitches follow a schedule: the intensity can ramp up after the skin
+//! is applied and pulse periodically, and bursts glitch heavily for a few
+//! frames. The schedule is driven by [`CorruptedModifiers::tick`] or by an
+//! explicit frame number. All randomness for a frame is derived from the
+//! seed and the frame number, so a given frame always glitches the same
+//! way.
 
 use serde::Deserialize;
 
@@ -27,6 +34,40 @@ pub struct CorruptedModifiers {
     /// Overall intensity multiplier (0.0 = no corruption, 1.0 = full).
     #[serde(default = "default_intensity")]
     pub intensity: f32,
+    /// Probability (0.0-1.0) per frame that fill and text colors have
+    /// their red and blue channels swapped.
+    #[serde(default = "default_channel_swap")]
+    pub channel_swap_chance: f32,
+    /// Probability (0.0-1.0) per frame that a horizontal band of the
+    /// screen is displaced sideways.
+    #[serde(default = "default_scanline_chance")]
+    pub scanline_chance: f32,
+    /// Maximum sideways displacement of a band, in pixels.
+    #[serde(default = "default_scanline_shift")]
+    pub scanline_shift: i32,
+    /// Seconds over which the intensity ramps up from 0.0 to `intensity`
+    /// (0 = start at full intensity).
+    #[serde(default)]
+    pub ramp_seconds: f32,
+    /// Seconds between heavy glitch bursts (0 = no bursts).
+    #[serde(default)]
+    pub burst_every_seconds: f32,
+    /// Length of each burst, in frames.
+    #[serde(default = "default_burst_frames")]
+    pub burst_frames: u32,
+    /// Seconds per intensity pulse (0 = no pulse).
+    #[serde(default)]
+    pub pulse_seconds: f32,
+    /// How far each pulse dips the intensity, as a fraction of it
+    /// (0.0-1.0).
+    #[serde(default = "default_pulse_depth")]
+    pub pulse_depth: f32,
+    /// RNG seed. The same seed and frame always glitch the same way.
+    #[serde(default = "default_seed")]
+    pub seed: u32,
+    /// Time advanced by [`tick`](Self::tick), in milliseconds.
+    #[serde(skip)]
+    pub phase_ms: u64,
 }
 
 fn default_jitter() -> i32 {
@@ -44,6 +85,24 @@ fn default_garble() -> f32 {
 fn default_intensity() -> f32 {
     1.0
 }
+fn default_channel_swap() -> f32 {
+    0.02
+}
+fn default_scanline_chance() -> f32 {
+    0.05
+}
+fn default_scanline_shift() -> i32 {
+    6
+}
+fn default_pulse_depth() -> f32 {
+    0.5
+}
+fn default_burst_frames() -> u32 {
+    12
+}
+fn default_seed() -> u32 {
+    42
+}
 
 impl Default for CorruptedModifiers {
     fn default() -> Self {
@@ -53,6 +112,16 @@ impl Default for CorruptedModifiers {
             alpha_flicker_min: default_flicker_min(),
             text_garble_chance: default_garble(),
             intensity: default_intensity(),
+            channel_swap_chance: default_channel_swap(),
+            scanline_chance: default_scanline_chance(),
+            scanline_shift: default_scanline_shift(),
+            ramp_seconds: 0.0,
+            burst_every_seconds: 0.0,
+            burst_frames: default_burst_frames(),
+            pulse_seconds: 0.0,
+            pulse_depth: default_pulse_depth(),
+            seed: default_seed(),
+            phase_ms: 0,
         }
     }
 }
@@ -96,21 +165,130 @@ impl SimpleRng {
 /// Glitch characters used for text garbling.
 const GLITCH_CHARS: &[u8] = b"@#$%&*!?/\\|~^<>{}[]";
 
+/// Frame rate the schedule assumes when converting seconds to frames.
+pub const SCHEDULE_FPS: u32 = 60;
+
+/// Screen height scanline bands are placed within (PSP native).
+const SCREEN_H: u32 = 272;
+
+/// What the glitch schedule does on one frame.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GlitchFrame {
+    /// Intensity after the ramp and bursts (0.0-1.0).
+    pub intensity: f32,
+    /// Whether the frame is inside a burst.
+    pub burst: bool,
+    /// Whether text is garbled this frame.
+    pub scramble_text: bool,
+    /// Whether red and blue color channels are swapped this frame.
+    pub swap_channels: bool,
+    /// Band displaced this frame, as `(top, height, dx)`.
+    pub scanline: Option<(i32, u32, i32)>,
+}
+
 impl CorruptedModifiers {
+    /// Advance the schedule by `dt_ms` milliseconds.
+    pub fn tick(&mut self, dt_ms: u32) {
+        self.phase_ms = self.phase_ms.saturating_add(u64::from(dt_ms));
+    }
+
+    /// The schedule frame [`tick`](Self::tick) has reached.
+    pub fn phase_frame(&self) -> u64 {
+        self.phase_ms * u64::from(SCHEDULE_FPS) / 1000
+    }
+
+    /// The glitches for the current phase.
+    pub fn current(&self) -> GlitchFrame {
+        self.schedule(self.phase_frame())
+    }
+
+    /// Intensity at `frame` frames after the skin was applied, following
+    /// the ramp, pulse and bursts.
+    pub fn intensity_at(&self, frame: u64) -> f32 {
+        if self.intensity <= 0.0 {
+            return 0.0;
+        }
+        if self.in_burst(frame) {
+            return 1.0;
+        }
+        let mut level = self.intensity.min(1.0);
+        let ramp_frames = self.ramp_seconds * SCHEDULE_FPS as f32;
+        if ramp_frames > 0.0 {
+            level *= (frame as f32 / ramp_frames).min(1.0);
+        }
+        let pulse_frames = self.pulse_seconds * SCHEDULE_FPS as f32;
+        if pulse_frames > 0.0 {
+            // Full at the start of each period, lowest halfway through.
+            let angle = std::f32::consts::TAU * (frame as f32 % pulse_frames) / pulse_frames;
+            let dip = self.pulse_depth.clamp(0.0, 1.0) * (0.5 - 0.5 * angle.cos());
+            level *= 1.0 - dip;
+        }
+        level
+    }
+
+    /// Whether `frame` falls inside a burst. The first burst starts
+    /// `burst_every_seconds` in.
+    pub fn in_burst(&self, frame: u64) -> bool {
+        let every = (self.burst_every_seconds * SCHEDULE_FPS as f32) as u64;
+        every > 0 && frame >= every && frame % every < u64::from(self.burst_frames)
+    }
+
+    /// Work out which glitches fire on `frame`.
+    pub fn schedule(&self, frame: u64) -> GlitchFrame {
+        self.plan(frame, &mut self.frame_rng(frame))
+    }
+
+    /// RNG for one frame, seeded from `seed` and the frame number.
+    fn frame_rng(&self, frame: u64) -> SimpleRng {
+        let mix = (frame as u32).wrapping_mul(0x9E37_79B9) ^ (frame >> 32) as u32;
+        let mut rng = SimpleRng::new(self.seed ^ mix);
+        rng.next_u32();
+        rng
+    }
+
+    fn plan(&self, frame: u64, rng: &mut SimpleRng) -> GlitchFrame {
+        let intensity = self.intensity_at(frame);
+        let burst = intensity > 0.0 && self.in_burst(frame);
+        // Each trigger draws from the RNG even when it is forced on, so a
+        // burst doesn't change what the rest of the frame does.
+        let scramble_text = rng.next_f32() < intensity || burst;
+        let swap_channels = rng.next_f32() < self.channel_swap_chance * intensity || burst;
+        let scanline_roll = rng.next_f32() < self.scanline_chance * intensity || burst;
+        let top = (rng.next_u32() % SCREEN_H) as i32;
+        let height = 8 + rng.next_u32() % 24;
+        let shift = (self.scanline_shift as f32 * if burst { 2.0 } else { 1.0 }) as i32;
+        let dx = rng.next_range(shift.max(0));
+        GlitchFrame {
+            intensity,
+            burst,
+            scramble_text,
+            swap_channels,
+            scanline: (scanline_roll && dx != 0).then_some((top, height, dx)),
+        }
+    }
+
     /// Apply corrupted modifiers to all visible SDI objects.
     ///
-    /// This should be called once per frame, after the normal layout is set
-    /// and before drawing. The modifications are cosmetic and ephemeral --
-    /// they should be re-applied from the clean state each frame (i.e., the
-    /// skin system restores base positions before calling this).
-    pub fn apply(&self, sdi: &mut SdiRegistry, rng: &mut SimpleRng) {
-        if self.intensity <= 0.0 {
+    /// `frame` counts frames since the skin was applied and drives the
+    /// schedule. Call once per frame, after the normal layout is set and
+    /// before drawing. The modifications are cosmetic and ephemeral --
+    /// they should be re-applied from the clean state each frame (i.e.,
+    /// the caller restores the objects after drawing).
+    pub fn apply(&self, sdi: &mut SdiRegistry, frame: u64) {
+        let mut rng = self.frame_rng(frame);
+        let glitch = self.plan(frame, &mut rng);
+        if glitch.intensity <= 0.0 {
             return;
         }
 
-        let jitter = (self.position_jitter as f32 * self.intensity) as i32;
-        let flicker_chance = self.alpha_flicker_chance * self.intensity;
-        let garble_chance = self.text_garble_chance * self.intensity;
+        let burst_scale = if glitch.burst { 2.0 } else { 1.0 };
+        let jitter = (self.position_jitter as f32 * glitch.intensity * burst_scale) as i32;
+        let flicker_chance = self.alpha_flicker_chance * glitch.intensity;
+        let garble_chance = if glitch.scramble_text {
+            self.text_garble_chance * burst_scale
+        } else {
+            0.0
+        };
 
         // Collect names first to avoid borrow issues.
         let names: Vec<String> = sdi.names().map(String::from).collect();
@@ -139,6 +317,21 @@ impl CorruptedModifiers {
                     .min(obj.alpha);
             }
 
+            // Red/blue channel swap.
+            if glitch.swap_channels {
+                for c in [&mut obj.color, &mut obj.text_color] {
+                    std::mem::swap(&mut c.r, &mut c.b);
+                }
+            }
+
+            // Scanline displacement.
+            if let Some((top, height, dx)) = glitch.scanline
+                && obj.y < top + height as i32
+                && obj.y + obj.h.max(1) as i32 > top
+            {
+                obj.x += dx;
+            }
+
             // Text garbling.
             if garble_chance > 0.0
                 && let Some(ref text) = obj.text
@@ -231,8 +424,7 @@ mod tests {
             obj.y = 200;
             obj.text = Some("Hello World".to_string());
         }
-        let mut rng = SimpleRng::new(0);
-        m.apply(&mut sdi, &mut rng);
+        m.apply(&mut sdi, 0);
         let obj = sdi.get("test").unwrap();
         assert_eq!(obj.x, 100);
         assert_eq!(obj.y, 200);
@@ -254,8 +446,7 @@ mod tests {
             obj.x = 100;
             obj.y = 200;
         }
-        let mut rng = SimpleRng::new(42);
-        m.apply(&mut sdi, &mut rng);
+        m.apply(&mut sdi, 0);
         let obj = sdi.get("test").unwrap();
         // With jitter=10, positions should change (very unlikely to stay exact).
         // We run multiple seeds to ensure at least one changes.
@@ -305,6 +496,101 @@ intensity = 0.75
         assert!((m.intensity - 0.75).abs() < f32::EPSILON);
     }
 
+    #[test]
+    fn intensity_ramps_up_and_bursts_peak() {
+        let m = CorruptedModifiers {
+            intensity: 0.5,
+            ramp_seconds: 2.0,
+            burst_every_seconds: 10.0,
+            burst_frames: 6,
+            ..CorruptedModifiers::default()
+        };
+        assert_eq!(m.intensity_at(0), 0.0);
+        assert!((m.intensity_at(60) - 0.25).abs() < 1e-6);
+        assert!((m.intensity_at(120) - 0.5).abs() < 1e-6);
+        assert!(!m.in_burst(0));
+        assert!(m.in_burst(600) && m.in_burst(605) && !m.in_burst(606));
+        assert_eq!(m.intensity_at(1203), 1.0);
+        let burst = m.schedule(600);
+        assert!(burst.burst && burst.scramble_text && burst.swap_channels);
+
+        let off = CorruptedModifiers {
+            intensity: 0.0,
+            ..m.clone()
+        };
+        assert_eq!(off.schedule(600).intensity, 0.0);
+    }
+
+    #[test]
+    fn tick_drives_a_reproducible_pulse() {
+        let mut m = CorruptedModifiers {
+            intensity: 0.8,
+            ramp_seconds: 1.0,
+            pulse_seconds: 2.0,
+            pulse_depth: 0.5,
+            ..CorruptedModifiers::default()
+        };
+        let mut levels = Vec::new();
+        for _ in 0..6 {
+            levels.push(m.current().intensity);
+            m.tick(500);
+        }
+        let expected = [0.0, 0.3, 0.4, 0.6, 0.8, 0.6];
+        for (got, want) in levels.iter().zip(expected) {
+            assert!((got - want).abs() < 1e-5, "{levels:?}");
+        }
+        assert_eq!(m.phase_ms, 3000);
+        assert_eq!(m.phase_frame(), 180);
+        assert_eq!(m.current(), m.schedule(180));
+    }
+
+    #[test]
+    fn schedule_is_reproducible_per_seed_and_frame() {
+        let m = CorruptedModifiers {
+            scanline_chance: 0.5,
+            channel_swap_chance: 0.5,
+            ..CorruptedModifiers::default()
+        };
+        let frames: Vec<_> = (0..200).map(|f| m.schedule(f)).collect();
+        assert_eq!(frames, (0..200).map(|f| m.schedule(f)).collect::<Vec<_>>());
+        assert!(frames.iter().any(|g| g.swap_channels));
+        assert!(frames.iter().any(|g| !g.swap_channels));
+        assert!(frames.iter().any(|g| g.scanline.is_some()));
+
+        let reseeded = CorruptedModifiers {
+            seed: 7,
+            ..m.clone()
+        };
+        assert_ne!(
+            frames,
+            (0..200).map(|f| reseeded.schedule(f)).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn burst_swaps_channels_and_displaces_band() {
+        let m = CorruptedModifiers {
+            position_jitter: 0,
+            alpha_flicker_chance: 0.0,
+            text_garble_chance: 0.0,
+            burst_every_seconds: 1.0,
+            ..CorruptedModifiers::default()
+        };
+        let mut sdi = SdiRegistry::new();
+        {
+            let obj = sdi.create("bar");
+            obj.y = 0;
+            obj.w = 480;
+            obj.h = 272;
+            obj.color = oasis_types::backend::Color::rgb(200, 0, 10);
+        }
+        m.apply(&mut sdi, 60);
+        let obj = sdi.get("bar").unwrap();
+        assert_eq!((obj.color.r, obj.color.b), (10, 200));
+        let (_, _, dx) = m.schedule(60).scanline.unwrap();
+        assert_eq!(obj.x, dx);
+    }
+
     #[test]
     fn invisible_objects_skipped() {
         let m = CorruptedModifiers {
@@ -319,8 +605,7 @@ intensity = 0.75
             obj.y = 200;
             obj.visible = false;
         }
-        let mut rng = SimpleRng::new(42);
-        m.apply(&mut sdi, &mut rng);
+        m.apply(&mut sdi, 0);
         let obj = sdi.get("hidden").unwrap();
         assert_eq!(obj.x, 100);
         assert_eq!(obj.y, 200);
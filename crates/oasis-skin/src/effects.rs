@@ -8,7 +8,7 @@ use std::fmt::Debug;
 
 use oasis_sdi::SdiRegistry;
 
-use crate::corrupted::{CorruptedModifiers, SimpleRng};
+use crate::corrupted::CorruptedModifiers;
 
 /// A pluggable visual effect that modifies the SDI scene each frame.
 pub trait SkinEffect: Debug {
@@ -21,6 +21,10 @@ pub trait SkinEffect: Debug {
     /// Set the intensity (clamped to 0.0..=1.0).
     fn set_intensity(&mut self, intensity: f32);
 
+    /// Advance time-driven animation by `dt_ms` milliseconds. Call once
+    /// per frame, before [`apply`](Self::apply).
+    fn tick(&mut self, _dt_ms: u32) {}
+
     /// Apply the effect to the SDI scene for one frame.
     fn apply(&mut self, sdi: &mut SdiRegistry);
 }
@@ -30,18 +34,18 @@ pub trait SkinEffect: Debug {
 // ---------------------------------------------------------------------------
 
 /// Adapter wrapping `CorruptedModifiers` as a `SkinEffect`.
+///
+/// The glitch schedule (ramp, pulse, bursts) follows the time passed to
+/// [`tick`](SkinEffect::tick) since the effect was created.
 #[derive(Debug, Clone)]
 pub struct CorruptedEffect {
     modifiers: CorruptedModifiers,
-    rng: SimpleRng,
 }
 
 impl CorruptedEffect {
-    pub fn new(modifiers: CorruptedModifiers) -> Self {
-        Self {
-            modifiers,
-            rng: SimpleRng::new(42),
-        }
+    pub fn new(mut modifiers: CorruptedModifiers) -> Self {
+        modifiers.phase_ms = 0;
+        Self { modifiers }
     }
 }
 
@@ -58,8 +62,12 @@ impl SkinEffect for CorruptedEffect {
         self.modifiers.intensity = intensity.clamp(0.0, 1.0);
     }
 
+    fn tick(&mut self, dt_ms: u32) {
+        self.modifiers.tick(dt_ms);
+    }
+
     fn apply(&mut self, sdi: &mut SdiRegistry) {
-        self.modifiers.apply(sdi, &mut self.rng);
+        self.modifiers.apply(sdi, self.modifiers.phase_frame());
     }
 }
 
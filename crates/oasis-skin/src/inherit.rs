@@ -0,0 +1,374 @@
+//! Skin inheritance -- `extends = "<parent>"` (or `base = "<parent>"`) in
+//! `skin.toml`.
+//!
+//! A child skin names a parent: a built-in skin, a directory relative to
+//! the child's, or `./skins/{name}/`. The parent is loaded first and every
+//! value the child's files leave unset is taken from it, so a skin can
+//! change three colors and still pick up keys added to its parent later.
+//!
+//! Tables of optional values (layout objects, `[wm_theme]`, `[wallpaper]`,
+//! ...) are merged field by field. Values with a default (theme colors,
+//! features, strings) count as set only if the child's TOML names them.
+//! `corrupted.toml` is taken whole from the child if it has one.
+
+use std::path::{Path, PathBuf};
+
+use oasis_types::error::{OasisError, Result};
+
+use crate::Skin;
+use crate::builtin;
+
+/// How many directory skins an `extends` chain may pass through.
+pub const MAX_EXTENDS_DEPTH: usize = 4;
+
+/// Build `Self` with each listed `Option` field taken from `$child`, or
+/// from `$parent` where the child leaves it unset.
+///
+/// This is the whole of the `merge` of every override table (`[wm_theme]`,
+/// `[notifications]`, ...): overlay the child's overrides on `parent`'s,
+/// keeping the parent's value for every field left unset in the child.
+macro_rules! prefer_child {
+    ($child:ident, $parent:ident; $($field:ident),* $(,)?) => {
+        Self {
+            $($field: $child.$field.or($parent.$field)),*
+        }
+    };
+}
+
+/// Build `Self` with each listed field taken from `$child` if its TOML key
+/// is in `$keys`, otherwise from `$parent`.
+macro_rules! prefer_set_keys {
+    ($child:ident, $parent:ident, $keys:expr; $($field:ident),* $(,)?) => {
+        Self {
+            $($field: if $crate::inherit::is_set($keys, stringify!($field)) {
+                $child.$field
+            } else {
+                $parent.$field
+            }),*
+        }
+    };
+}
+
+pub(crate) use {prefer_child, prefer_set_keys};
+
+/// Whether `key` is among the keys a child skin set.
+pub(crate) fn is_set(keys: &[String], key: &str) -> bool {
+    keys.iter().any(|k| k == key)
+}
+
+/// Merge two optional override tables with `merge`, or keep whichever
+/// one is present.
+pub(crate) fn merge_nested<T>(
+    child: Option<T>,
+    parent: Option<T>,
+    merge: fn(T, T) -> T,
+) -> Option<T> {
+    match (child, parent) {
+        (Some(child), Some(parent)) => Some(merge(child, parent)),
+        (child, parent) => child.or(parent),
+    }
+}
+
+/// What a child skin's files set: top-level TOML keys, and whether it
+/// has its own `corrupted.toml`.
+#[derive(Debug, Default)]
+pub(crate) struct SetKeys {
+    pub manifest: Vec<String>,
+    pub features: Vec<String>,
+    pub theme: Vec<String>,
+    pub corrupted: bool,
+}
+
+impl SetKeys {
+    pub fn parse(manifest: &str, features: &str, theme: &str, corrupted: &str) -> Result<Self> {
+        let mut manifest = table_keys("skin.toml", manifest)?;
+        // `base` is read into the `extends` field.
+        for key in &mut manifest {
+            if key == "base" {
+                *key = "extends".to_string();
+            }
+        }
+        Ok(Self {
+            manifest,
+            features: table_keys("features.toml", features)?,
+            theme: table_keys("theme.toml", theme)?,
+            corrupted: !corrupted.is_empty(),
+        })
+    }
+}
+
+fn table_keys(file: &str, src: &str) -> Result<Vec<String>> {
+    let table: toml::Table =
+        toml::from_str(src).map_err(|e| OasisError::Config(format!("{file}: {e}")))?;
+    Ok(table.keys().cloned().collect())
+}
+
+/// Load the skin `child` extends, looking it up as described in the
+/// module docs. `chain` holds the directories of the skins loaded so far
+/// in this chain.
+pub(crate) fn load_parent(
+    child: &str,
+    parent: &str,
+    child_dir: &Path,
+    chain: &mut Vec<PathBuf>,
+) -> Result<Skin> {
+    if let Ok(skin) = builtin::load_builtin(parent) {
+        return Ok(skin);
+    }
+    for dir in [child_dir.join(parent), Path::new("skins").join(parent)] {
+        if dir.join("skin.toml").is_file() {
+            return Skin::from_directory_chain(&dir, chain);
+        }
+    }
+    if parent == "classic" {
+        return crate::classic_skin();
+    }
+    Err(OasisError::Config(format!(
+        "skin '{child}' extends unknown skin '{parent}'"
+    )))
+}
+
+/// Record `dir` in `chain`, failing on a cycle or a chain that is too
+/// long.
+pub(crate) fn enter(dir: &Path, chain: &mut Vec<PathBuf>) -> Result<()> {
+    let dir = dir.canonicalize().unwrap_or_else(|_| dir.to_path_buf());
+    if let Some(start) = chain.iter().position(|d| *d == dir) {
+        let cycle: Vec<String> = chain[start..]
+            .iter()
+            .chain([&dir])
+            .map(|d| d.display().to_string())
+            .collect();
+        return Err(OasisError::Config(format!(
+            "skin inheritance cycle: {}",
+            cycle.join(" -> ")
+        )));
+    }
+    if chain.len() >= MAX_EXTENDS_DEPTH {
+        return Err(OasisError::Config(format!(
+            "skin {} extends more than {MAX_EXTENDS_DEPTH} levels deep",
+            dir.display()
+        )));
+    }
+    chain.push(dir);
+    Ok(())
+}
+
+impl Skin {
+    /// Layer this skin over `parent`, keeping the parent's value for
+    /// everything this skin leaves unset.
+    pub(crate) fn inherit(self, parent: Skin, keys: &SetKeys) -> Skin {
+        let strings = self.strings.merge(
+            parent.strings,
+            self.string_keys.as_deref().unwrap_or_default(),
+        );
+        let string_keys = match (self.string_keys, parent.string_keys) {
+            (Some(mut child), Some(parent)) => {
+                let inherited: Vec<String> =
+                    parent.into_iter().filter(|k| !is_set(&child, k)).collect();
+                child.extend(inherited);
+                Some(child)
+            },
+            (child, parent) => child.or(parent),
+        };
+        let features = self.features.merge(parent.features, &keys.features);
+        let corrupted_modifiers = if !features.corrupted {
+            None
+        } else if keys.corrupted {
+            self.corrupted_modifiers
+        } else {
+            parent.corrupted_modifiers.or(self.corrupted_modifiers)
+        };
+        let mut asset_images = parent.asset_images;
+        asset_images.extend(self.asset_images);
+        Skin {
+            manifest: self.manifest.merge(parent.manifest, &keys.manifest),
+            layout: self.layout.merge(parent.layout),
+            strings,
+            theme: self.theme.merge(parent.theme, &keys.theme),
+            features,
+            corrupted_modifiers,
+            source_dir: self.source_dir,
+            string_keys,
+            wallpaper_image: self.wallpaper_image.or(parent.wallpaper_image),
+            asset_images,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::WINDOW_FRAME_ASSET;
+
+    /// The repo's `skins/` directory.
+    fn skins_dir() -> PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR")).join("../../skins")
+    }
+
+    fn write_skin(dir: &Path, files: &[(&str, &str)]) {
+        std::fs::create_dir_all(dir).unwrap();
+        for (name, text) in files {
+            std::fs::write(dir.join(name), text).unwrap();
+        }
+    }
+
+    #[test]
+    fn two_line_child_inherits_everything() {
+        let classic = Skin::from_directory(&skins_dir().join("classic")).unwrap();
+        let tmp = tempfile::tempdir().unwrap();
+        let manifest = format!(
+            "name = \"night\"\nextends = \"{}\"\n",
+            skins_dir().join("classic").display()
+        );
+        write_skin(tmp.path(), &[("skin.toml", &manifest)]);
+
+        let skin = Skin::from_directory(tmp.path()).unwrap();
+        assert_eq!(skin.manifest.name, "night");
+        assert_eq!(skin.manifest.description, classic.manifest.description);
+        assert_eq!(skin.layout.objects.len(), classic.layout.objects.len());
+        assert_eq!(skin.features.dashboard_pages, 4);
+        assert!(skin.features.window_manager);
+        assert_eq!(skin.theme.background, classic.theme.background);
+        assert_eq!(skin.theme.border_radius, Some(4));
+        assert!(skin.asset(WINDOW_FRAME_ASSET).is_some());
+        assert_eq!(
+            skin.asset(WINDOW_FRAME_ASSET),
+            classic.asset(WINDOW_FRAME_ASSET)
+        );
+        assert_eq!(skin.source_dir.as_deref(), Some(tmp.path()));
+    }
+
+    #[test]
+    fn child_overrides_only_what_it_sets() {
+        let tmp = tempfile::tempdir().unwrap();
+        write_skin(
+            &tmp.path().join("base"),
+            &[
+                ("skin.toml", "name = \"base\"\nscreen_width = 800\n"),
+                ("layout.toml", "[bar]\nx = 1\ny = 2\ncolor = \"#111111\"\n"),
+                ("features.toml", "dashboard = false\ngrid_cols = 5\n"),
+                (
+                    "theme.toml",
+                    "background = \"#000000\"\nprimary = \"#222222\"\n\
+                     [wm_theme]\ntitlebar_height = 20\nframe_color = \"#333333\"\n",
+                ),
+                ("strings.toml", "title = \"BASE\"\nhome_label = \"Start\"\n"),
+            ],
+        );
+        write_skin(
+            &tmp.path().join("child"),
+            &[
+                ("skin.toml", "name = \"child\"\nextends = \"../base\"\n"),
+                (
+                    "layout.toml",
+                    "[bar]\ncolor = \"#FFFFFF\"\n[extra]\nw = 3\n",
+                ),
+                ("features.toml", "grid_cols = 2\n"),
+                (
+                    "theme.toml",
+                    "primary = \"#ABCDEF\"\n[wm_theme]\nframe_color = \"#444444\"\n",
+                ),
+                ("strings.toml", "title = \"CHILD\"\n"),
+            ],
+        );
+
+        let skin = Skin::from_directory(&tmp.path().join("child")).unwrap();
+        assert_eq!(skin.manifest.screen_width, 800);
+        let bar = &skin.layout.objects["bar"];
+        assert_eq!((bar.x, bar.y), (Some(1), Some(2)));
+        assert_eq!(bar.color.as_deref(), Some("#FFFFFF"));
+        assert!(skin.layout.objects.contains_key("extra"));
+        assert!(!skin.features.dashboard);
+        assert_eq!(skin.features.grid_cols, 2);
+        assert_eq!(skin.theme.background, "#000000");
+        assert_eq!(skin.theme.primary, "#ABCDEF");
+        let wm = skin.theme.wm_theme.as_ref().unwrap();
+        assert_eq!(wm.titlebar_height, Some(20));
+        assert_eq!(wm.frame_color.as_deref(), Some("#444444"));
+        assert_eq!(skin.strings.title, "CHILD");
+        assert_eq!(skin.strings.home_label, "Start");
+    }
+
+    #[test]
+    fn extends_a_builtin() {
+        let tmp = tempfile::tempdir().unwrap();
+        write_skin(
+            tmp.path(),
+            &[
+                ("skin.toml", "name = \"green\"\nextends = \"terminal\"\n"),
+                ("theme.toml", "prompt = \"#00FF00\"\n"),
+            ],
+        );
+        let terminal = builtin::load_builtin("terminal").unwrap();
+        let skin = Skin::from_directory(tmp.path()).unwrap();
+        assert_eq!(skin.theme.prompt, "#00FF00");
+        assert_eq!(skin.theme.background, terminal.theme.background);
+        assert_eq!(skin.strings.prompt_format, terminal.strings.prompt_format);
+        assert_eq!(skin.features.dashboard, terminal.features.dashboard);
+    }
+
+    #[test]
+    fn base_is_an_alias_for_extends() {
+        let tmp = tempfile::tempdir().unwrap();
+        write_skin(
+            tmp.path(),
+            &[("skin.toml", "name = \"variant\"\nbase = \"terminal\"\n")],
+        );
+        let skin = Skin::from_directory(tmp.path()).unwrap();
+        assert_eq!(skin.manifest.extends.as_deref(), Some("terminal"));
+        let terminal = builtin::load_builtin("terminal").unwrap();
+        assert_eq!(skin.theme.background, terminal.theme.background);
+
+        write_skin(
+            tmp.path(),
+            &[(
+                "skin.toml",
+                "name = \"both\"\nbase = \"terminal\"\nextends = \"classic\"\n",
+            )],
+        );
+        assert!(Skin::from_directory(tmp.path()).is_err());
+    }
+
+    #[test]
+    fn unknown_parent_and_cycles_are_errors() {
+        let tmp = tempfile::tempdir().unwrap();
+        write_skin(
+            &tmp.path().join("orphan"),
+            &[(
+                "skin.toml",
+                "name = \"orphan\"\nextends = \"no-such-skin\"\n",
+            )],
+        );
+        let err = Skin::from_directory(&tmp.path().join("orphan")).unwrap_err();
+        assert!(
+            err.to_string()
+                .contains("extends unknown skin 'no-such-skin'")
+        );
+
+        write_skin(
+            &tmp.path().join("a"),
+            &[("skin.toml", "name = \"a\"\nextends = \"../b\"\n")],
+        );
+        write_skin(
+            &tmp.path().join("b"),
+            &[("skin.toml", "name = \"b\"\nextends = \"../a\"\n")],
+        );
+        let err = Skin::from_directory(&tmp.path().join("a")).unwrap_err();
+        assert!(err.to_string().contains("cycle"), "{err}");
+
+        for i in 0..=MAX_EXTENDS_DEPTH {
+            let manifest = format!("name = \"d{i}\"\nextends = \"../d{}\"\n", i + 1);
+            write_skin(
+                &tmp.path().join(format!("d{i}")),
+                &[("skin.toml", &manifest)],
+            );
+        }
+        write_skin(
+            &tmp.path().join(format!("d{}", MAX_EXTENDS_DEPTH + 1)),
+            &[("skin.toml", "name = \"last\"\nextends = \"terminal\"\n")],
+        );
+        let err = Skin::from_directory(&tmp.path().join("d0")).unwrap_err();
+        assert!(err.to_string().contains("levels deep"), "{err}");
+        assert!(Skin::from_directory(&tmp.path().join("d3")).is_ok());
+    }
+}
@@ -2,58 +2,112 @@
 //!
 //! A skin is a TOML manifest referencing layout definitions, theme colors,
 //! feature flags, strings, and optional corrupted modifiers. The core
-//! framework interprets skins at runtime. Skins can be hot-swapped.
+//! framework interprets skins at runtime. Skins can be hot-swapped, and
+//! skins loaded from a directory can be watched and reloaded as their
+//! files are edited. A skin can `extends` another and override only some
+//! of its keys.
 
 pub mod active_theme;
 pub mod builtin;
 pub mod corrupted;
 pub mod effects;
+pub mod inherit;
 pub mod legacy_theme;
 mod loader;
 pub mod strings;
 pub mod theme;
+pub mod validate;
+pub mod watcher;
 
 pub use active_theme::ActiveTheme;
 pub use corrupted::{CorruptedModifiers, SimpleRng};
 pub use effects::{CorruptedEffect, ScanlineEffect, SkinEffect};
-pub use loader::{Skin, SkinFeatures, SkinLayout, SkinManifest, SkinObjectDef};
+pub use loader::{
+    BUTTON_NORMAL_ASSET, BUTTON_PRESSED_ASSET, Skin, SkinAssetDef, SkinFeatures, SkinLayout,
+    SkinManifest, SkinObjectDef, WINDOW_FRAME_ASSET,
+};
 pub use strings::SkinStrings;
-pub use theme::{BarOverrides, BrowserOverrides, IconOverrides, SkinTheme, WmThemeOverrides};
+pub use theme::{
+    BarOverrides, BrowserOverrides, IconOverrides, NotificationOverrides, SkinTheme,
+    WmThemeOverrides,
+};
+pub use validate::{IssueSeverity, SkinValidationIssue};
+pub use watcher::SkinWatcher;
 
 use std::path::Path;
 
-use oasis_types::error::Result;
+use oasis_types::error::{OasisError, Result};
 
-/// Resolve a skin by name or path.
+/// Load a skin by name or path, without any fallback.
 ///
 /// Resolution order:
 /// 1. Built-in name match (e.g. "terminal", "modern")
 /// 2. Path containing `skin.toml` (e.g. "skins/classic")
 /// 3. Subdirectory under `./skins/{name}/`
-/// 4. Fallback to "classic" built-in skin with a warning
+pub fn load_skin(name_or_path: &str) -> Result<Skin> {
+    find_skin(name_or_path).unwrap_or_else(|| {
+        Err(OasisError::Config(format!(
+            "skin '{name_or_path}' not found"
+        )))
+    })
+}
+
+/// Resolve a skin by name or path.
+///
+/// Looks the skin up like [`load_skin`], falling back to the "classic"
+/// built-in skin with a warning when it is not found. Problems reported
+/// by [`Skin::validate`] are logged as warnings.
 pub fn resolve_skin(name_or_path: &str) -> Result<Skin> {
+    if let Some(result) = find_skin(name_or_path) {
+        let skin = result?;
+        for issue in skin.validate() {
+            log::warn!("skin '{}': {issue}", skin.manifest.name);
+        }
+        return Ok(skin);
+    }
+
+    log::warn!("Skin '{name_or_path}' not found -- falling back to classic");
+    classic_skin()
+}
+
+/// The "classic" skin as shipped in `skins/classic`, embedded so it is
+/// available without the skins directory.
+pub(crate) fn classic_skin() -> Result<Skin> {
+    let mut skin = Skin::from_toml_full(
+        include_str!("../../../skins/classic/skin.toml"),
+        include_str!("../../../skins/classic/layout.toml"),
+        include_str!("../../../skins/classic/features.toml"),
+        include_str!("../../../skins/classic/theme.toml"),
+        "",
+    )?;
+    skin.read_asset_images(|image| match image {
+        "window_frame.png" => {
+            Ok(include_bytes!("../../../skins/classic/window_frame.png").to_vec())
+        },
+        _ => Err(std::io::ErrorKind::NotFound.into()),
+    });
+    Ok(skin)
+}
+
+/// Look a skin up in the order documented on [`load_skin`]. Returns
+/// `None` when nothing matches.
+fn find_skin(name_or_path: &str) -> Option<Result<Skin>> {
     // 1. Try built-in name.
     if let Ok(skin) = builtin::load_builtin(name_or_path) {
-        return Ok(skin);
+        return Some(Ok(skin));
     }
 
     // 2. Try as a directory path.
     let path = Path::new(name_or_path);
     if path.join("skin.toml").is_file() {
-        return Skin::from_directory(path);
+        return Some(Skin::from_directory(path));
     }
 
     // 3. Try ./skins/{name}/.
     let skins_dir = Path::new("skins").join(name_or_path);
     if skins_dir.join("skin.toml").is_file() {
-        return Skin::from_directory(&skins_dir);
+        return Some(Skin::from_directory(&skins_dir));
     }
 
-    // 4. Fallback to classic embedded skin.
-    log::warn!("Skin '{name_or_path}' not found -- falling back to classic");
-    Skin::from_toml(
-        include_str!("../../../skins/classic/skin.toml"),
-        include_str!("../../../skins/classic/layout.toml"),
-        include_str!("../../../skins/classic/features.toml"),
-    )
+    None
 }
@@ -9,6 +9,7 @@ use oasis_sdi::SdiRegistry;
 use oasis_types::error::{OasisError, Result};
 
 use super::corrupted::CorruptedModifiers;
+use super::inherit::{self, SetKeys, prefer_child, prefer_set_keys};
 use super::strings::SkinStrings;
 use super::theme::{SkinTheme, parse_hex_color};
 
@@ -26,6 +27,67 @@ pub struct SkinManifest {
     pub screen_width: u32,
     #[serde(default = "default_height")]
     pub screen_height: u32,
+    /// Parent skin this one inherits unset values from: a built-in name
+    /// or a skin directory (see [`crate::inherit`]). Also accepted as
+    /// `base`.
+    #[serde(default, alias = "base")]
+    pub extends: Option<String>,
+    /// Image assets by logical name (`[assets.<name>]`).
+    #[serde(default)]
+    pub assets: HashMap<String, SkinAssetDef>,
+}
+
+impl SkinManifest {
+    /// Overlay this manifest on `parent`'s, keeping the parent's value
+    /// for every key not in `set_keys`.
+    ///
+    /// Assets are merged by name, the child's replacing the parent's.
+    pub(crate) fn merge(mut self, parent: Self, set_keys: &[String]) -> Self {
+        let mut assets = parent.assets.clone();
+        assets.extend(std::mem::take(&mut self.assets));
+        self.assets = assets;
+        // Whichever side is picked below, a child without `[assets]`
+        // leaves the parent's unchanged.
+        prefer_set_keys!(self, parent, set_keys;
+            name,
+            version,
+            author,
+            description,
+            screen_width,
+            screen_height,
+            extends,
+            assets,
+        )
+    }
+}
+
+/// Asset name of the nine-patch window frames are drawn with.
+pub const WINDOW_FRAME_ASSET: &str = "window_frame";
+
+/// Asset name of the nine-patch titlebar buttons are drawn with.
+pub const BUTTON_NORMAL_ASSET: &str = "button_normal";
+
+/// Asset name of the nine-patch drawn for the titlebar button under the
+/// pointer, which a click presses.
+pub const BUTTON_PRESSED_ASSET: &str = "button_pressed";
+
+/// An image listed under `[assets]` in `skin.toml`.
+///
+/// Frontends look assets up by logical name (`window_frame`,
+/// `button_normal`, `button_pressed`) and draw them as nine-patches:
+/// the inset borders keep their size and the rest stretches.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct SkinAssetDef {
+    /// Image file (PNG, BMP or GIF), relative to the skin directory.
+    pub image: String,
+    #[serde(default)]
+    pub left: u16,
+    #[serde(default)]
+    pub right: u16,
+    #[serde(default)]
+    pub top: u16,
+    #[serde(default)]
+    pub bottom: u16,
 }
 
 fn default_version() -> String {
@@ -66,6 +128,31 @@ pub struct SkinObjectDef {
     pub stroke_color: Option<String>,
 }
 
+impl SkinObjectDef {
+    /// Overlay this definition on `parent`'s, keeping the parent's value
+    /// for every property left unset here.
+    pub fn merge(self, parent: Self) -> Self {
+        prefer_child!(self, parent;
+            x,
+            y,
+            w,
+            h,
+            color,
+            text,
+            text_color,
+            font_size,
+            alpha,
+            visible,
+            border_radius,
+            gradient_top,
+            gradient_bottom,
+            shadow_level,
+            stroke_width,
+            stroke_color,
+        )
+    }
+}
+
 /// Layout: a named collection of SDI object definitions (`layout.toml`).
 #[derive(Debug, Clone, Deserialize)]
 pub struct SkinLayout {
@@ -73,6 +160,21 @@ pub struct SkinLayout {
     pub objects: HashMap<String, SkinObjectDef>,
 }
 
+impl SkinLayout {
+    /// Add `parent`'s objects to this layout, merging the ones both
+    /// define.
+    pub fn merge(mut self, parent: Self) -> Self {
+        for (name, def) in parent.objects {
+            let merged = match self.objects.remove(&name) {
+                Some(child) => child.merge(def),
+                None => def,
+            };
+            self.objects.insert(name, merged);
+        }
+        self
+    }
+}
+
 /// Feature gates controlling which capabilities a skin exposes.
 #[derive(Debug, Clone, Deserialize)]
 pub struct SkinFeatures {
@@ -181,6 +283,45 @@ impl Default for SkinFeatures {
     }
 }
 
+impl SkinFeatures {
+    /// Overlay these features on `parent`'s, keeping the parent's value
+    /// for every key not in `set_keys`.
+    pub(crate) fn merge(self, parent: Self, set_keys: &[String]) -> Self {
+        prefer_set_keys!(self, parent, set_keys;
+            dashboard,
+            terminal,
+            file_browser,
+            browser,
+            window_manager,
+            dashboard_pages,
+            icons_per_page,
+            grid_cols,
+            grid_rows,
+            command_categories,
+            start_menu,
+            corrupted,
+            show_battery,
+            show_clock,
+            show_version,
+            show_tabs,
+            show_media_tabs,
+            show_page_dots,
+            transition_fade_frames,
+            transition_slide_frames,
+        )
+    }
+}
+
+/// Every file a skin directory is loaded from, required ones first.
+pub(crate) const SKIN_FILES: [&str; 6] = [
+    "skin.toml",
+    "layout.toml",
+    "features.toml",
+    "theme.toml",
+    "strings.toml",
+    "corrupted.toml",
+];
+
 /// A fully loaded skin ready for use.
 #[derive(Debug, Clone)]
 pub struct Skin {
@@ -190,6 +331,18 @@ pub struct Skin {
     pub theme: SkinTheme,
     pub strings: SkinStrings,
     pub corrupted_modifiers: Option<CorruptedModifiers>,
+    /// Directory the skin was loaded from (`None` for skins built from
+    /// strings, such as the built-ins).
+    pub source_dir: Option<PathBuf>,
+    /// Top-level keys set in `strings.toml` (`None` if it was empty or
+    /// absent). Used by [`Skin::validate`] to spot missing strings.
+    pub string_keys: Option<Vec<String>>,
+    /// Undecoded bytes of the `[wallpaper] image` file, read by
+    /// [`Skin::from_directory`].
+    pub wallpaper_image: Option<Vec<u8>>,
+    /// Undecoded bytes of the `[assets]` images by asset name, read by
+    /// [`Skin::from_directory`].
+    pub asset_images: HashMap<String, Vec<u8>>,
 }
 
 impl Skin {
@@ -220,11 +373,16 @@ impl Skin {
                 .map_err(|e| OasisError::Config(format!("theme.toml: {e}")))?
         };
 
-        let strings: SkinStrings = if strings_toml.is_empty() {
-            SkinStrings::default()
+        let (strings, string_keys) = if strings_toml.is_empty() {
+            (SkinStrings::default(), None)
         } else {
-            toml::from_str(strings_toml)
-                .map_err(|e| OasisError::Config(format!("strings.toml: {e}")))?
+            let table: toml::Table = toml::from_str(strings_toml)
+                .map_err(|e| OasisError::Config(format!("strings.toml: {e}")))?;
+            let keys = table.keys().cloned().collect();
+            let strings: SkinStrings = toml::Value::Table(table)
+                .try_into()
+                .map_err(|e| OasisError::Config(format!("strings.toml: {e}")))?;
+            (strings, Some(keys))
         };
 
         let corrupted_modifiers = if features.corrupted {
@@ -240,6 +398,10 @@ impl Skin {
             theme,
             strings,
             corrupted_modifiers,
+            source_dir: None,
+            string_keys,
+            wallpaper_image: None,
+            asset_images: HashMap::new(),
         })
     }
 
@@ -344,7 +506,16 @@ impl Skin {
     ///
     /// Requires `skin.toml`, `layout.toml`, and `features.toml`.
     /// Optional files: `theme.toml`, `strings.toml`, `corrupted.toml`.
+    /// A skin whose manifest `extends` a parent needs only `skin.toml`;
+    /// the parent is loaded first and fills in whatever it leaves unset.
     pub fn from_directory(dir: &Path) -> Result<Self> {
+        Self::from_directory_chain(dir, &mut Vec::new())
+    }
+
+    /// [`Self::from_directory`], with `chain` holding the directories of
+    /// the skins that (transitively) extend this one.
+    pub(crate) fn from_directory_chain(dir: &Path, chain: &mut Vec<PathBuf>) -> Result<Self> {
+        inherit::enter(dir, chain)?;
         let read = |name: &str| -> Result<String> {
             let p = dir.join(name);
             std::fs::read_to_string(&p)
@@ -353,18 +524,80 @@ impl Skin {
         let read_opt =
             |name: &str| -> String { std::fs::read_to_string(dir.join(name)).unwrap_or_default() };
 
-        let manifest = read("skin.toml")?;
-        let layout = read("layout.toml")?;
-        let features = read("features.toml")?;
-        let theme = read_opt("theme.toml");
-        let strings = read_opt("strings.toml");
-        let corrupted = read_opt("corrupted.toml");
+        let manifest = read(SKIN_FILES[0])?;
+        let extends = toml::from_str::<SkinManifest>(&manifest)
+            .map_err(|e| OasisError::Config(format!("skin.toml: {e}")))?
+            .extends;
+        let (layout, features) = if extends.is_some() {
+            (read_opt(SKIN_FILES[1]), read_opt(SKIN_FILES[2]))
+        } else {
+            (read(SKIN_FILES[1])?, read(SKIN_FILES[2])?)
+        };
+        let theme = read_opt(SKIN_FILES[3]);
+        let strings = read_opt(SKIN_FILES[4]);
+        let corrupted = read_opt(SKIN_FILES[5]);
 
-        if corrupted.is_empty() {
+        let mut skin = if corrupted.is_empty() {
             Self::from_toml_full(&manifest, &layout, &features, &theme, &strings)
         } else {
             Self::from_toml_corrupted(&manifest, &layout, &features, &theme, &strings, &corrupted)
+        }?;
+        skin.source_dir = Some(dir.to_path_buf());
+        // A missing image is not fatal; the generated wallpaper is used.
+        if let Some(image) = skin.theme.wallpaper.as_ref().and_then(|w| w.image.as_ref()) {
+            match std::fs::read(dir.join(image)) {
+                Ok(bytes) => skin.wallpaper_image = Some(bytes),
+                Err(e) => log::warn!("skin wallpaper {image}: {e}"),
+            }
+        }
+        // Likewise for assets: whatever uses them falls back to flat colors.
+        skin.read_asset_images(|image| std::fs::read(dir.join(image)));
+        if let Some(parent) = extends {
+            let keys = SetKeys::parse(&manifest, &features, &theme, &corrupted)?;
+            let parent = inherit::load_parent(&skin.manifest.name, &parent, dir, chain)?;
+            skin = skin.inherit(parent, &keys);
         }
+        Ok(skin)
+    }
+
+    /// Read the image of every `[assets]` entry with `read`, given the
+    /// image's path. Images that cannot be read are left out.
+    ///
+    /// [`Self::from_directory`] does this itself; frontends without
+    /// `std::fs` call it after building the skin from its TOML files.
+    pub fn read_asset_images(&mut self, read: impl Fn(&str) -> std::io::Result<Vec<u8>>) {
+        for (name, asset) in &self.manifest.assets {
+            match read(&asset.image) {
+                Ok(bytes) => {
+                    self.asset_images.insert(name.clone(), bytes);
+                },
+                Err(e) => log::warn!("skin asset {name} ({}): {e}", asset.image),
+            }
+        }
+    }
+
+    /// The `[assets]` entry `name` with its image bytes, if the skin has
+    /// one and its image could be read.
+    pub fn asset(&self, name: &str) -> Option<(&SkinAssetDef, &[u8])> {
+        let asset = self.manifest.assets.get(name)?;
+        let image = self.asset_images.get(name)?;
+        Some((asset, image))
+    }
+
+    /// Re-read the skin from its source directory and replace this
+    /// skin's contents with it, returning the previous contents.
+    ///
+    /// On error (a parse failure, or a skin not loaded from a
+    /// directory) the skin is left unchanged.
+    pub fn reload(&mut self) -> Result<Skin> {
+        let Some(dir) = &self.source_dir else {
+            return Err(OasisError::Config(format!(
+                "skin '{}' was not loaded from a directory",
+                self.manifest.name
+            )));
+        };
+        let fresh = Self::from_directory(dir)?;
+        Ok(std::mem::replace(self, fresh))
     }
 
     /// Scan a directory for skin subdirectories (those containing `skin.toml`).
@@ -826,6 +1059,85 @@ h = 272
         assert!(result.is_err());
     }
 
+    fn write_skin_dir(dir: &Path, manifest: &str) {
+        std::fs::write(dir.join("skin.toml"), manifest).unwrap();
+        std::fs::write(dir.join("layout.toml"), LAYOUT).unwrap();
+        std::fs::write(dir.join("features.toml"), FEATURES).unwrap();
+    }
+
+    #[test]
+    fn reload_swaps_contents_and_keeps_old_on_error() {
+        let dir = tempfile::tempdir().unwrap();
+        write_skin_dir(dir.path(), MANIFEST);
+        let mut skin = Skin::from_directory(dir.path()).unwrap();
+        assert_eq!(skin.source_dir.as_deref(), Some(dir.path()));
+
+        write_skin_dir(dir.path(), &MANIFEST.replace("classic", "edited"));
+        let old = skin.reload().unwrap();
+        assert_eq!(old.manifest.name, "classic");
+        assert_eq!(skin.manifest.name, "edited");
+
+        std::fs::write(dir.path().join("layout.toml"), "[[[").unwrap();
+        let err = skin.reload().unwrap_err();
+        assert!(err.to_string().contains("layout.toml"));
+        assert_eq!(skin.manifest.name, "edited");
+        assert_eq!(skin.layout.objects.len(), 2);
+    }
+
+    #[test]
+    fn from_directory_reads_wallpaper_image() {
+        let dir = tempfile::tempdir().unwrap();
+        write_skin_dir(dir.path(), MANIFEST);
+        let theme = "[wallpaper]\nimage = \"bg.png\"\nmode = \"tile\"\n";
+        std::fs::write(dir.path().join("theme.toml"), theme).unwrap();
+        // Missing file: the skin still loads, without an image.
+        let skin = Skin::from_directory(dir.path()).unwrap();
+        assert!(skin.wallpaper_image.is_none());
+
+        std::fs::write(dir.path().join("bg.png"), [1, 2, 3]).unwrap();
+        let skin = Skin::from_directory(dir.path()).unwrap();
+        assert_eq!(skin.wallpaper_image.as_deref(), Some(&[1, 2, 3][..]));
+        let wp = skin.theme.wallpaper.unwrap();
+        assert_eq!(wp.mode.as_deref(), Some("tile"));
+    }
+
+    #[test]
+    fn from_directory_reads_asset_images() {
+        let dir = tempfile::tempdir().unwrap();
+        let manifest =
+            format!("{MANIFEST}[assets.window_frame]\nimage = \"frame.png\"\nleft = 2\ntop = 5\n");
+        write_skin_dir(dir.path(), &manifest);
+        // Missing file: the skin still loads, without the asset.
+        let skin = Skin::from_directory(dir.path()).unwrap();
+        assert_eq!(skin.manifest.assets.len(), 1);
+        assert!(skin.asset(WINDOW_FRAME_ASSET).is_none());
+
+        std::fs::write(dir.path().join("frame.png"), [1, 2, 3]).unwrap();
+        let skin = Skin::from_directory(dir.path()).unwrap();
+        let (asset, image) = skin.asset(WINDOW_FRAME_ASSET).unwrap();
+        assert_eq!(image, &[1, 2, 3][..]);
+        assert_eq!(
+            (asset.left, asset.right, asset.top, asset.bottom),
+            (2, 0, 5, 0)
+        );
+        assert!(skin.asset("button_normal").is_none());
+    }
+
+    #[test]
+    fn embedded_classic_has_its_window_frame() {
+        let skin = crate::classic_skin().unwrap();
+        let (_, image) = skin.asset(WINDOW_FRAME_ASSET).unwrap();
+        assert!(image.starts_with(b"\x89PNG"));
+    }
+
+    #[test]
+    fn reload_without_directory_fails() {
+        let mut skin = Skin::from_toml(MANIFEST, LAYOUT, FEATURES).unwrap();
+        assert!(skin.source_dir.is_none());
+        assert!(skin.reload().is_err());
+        assert_eq!(skin.manifest.name, "classic");
+    }
+
     // -- Robustness / edge cases ----------------------------------------
 
     #[test]
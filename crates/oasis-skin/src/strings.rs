@@ -3,9 +3,18 @@
 //! All displayed text is skin-configurable via `strings.toml`. This enables
 //! different personas (military-style for Tactical, hacker-style for Terminal,
 //! garbled for Corrupted) without code changes.
+//!
+//! Besides the fixed fields, a `[messages]` table holds free-form strings
+//! looked up by key through [`SkinStrings::format`] (with `{name}`
+//! placeholders) and [`SkinStrings::plural`] (`<key>_one` / `<key>_other`
+//! variants chosen by count).
+
+use std::collections::BTreeMap;
 
 use serde::Deserialize;
 
+use crate::inherit::prefer_set_keys;
+
 /// User-facing text strings for a skin.
 #[derive(Debug, Clone, Deserialize)]
 pub struct SkinStrings {
@@ -30,6 +39,10 @@ pub struct SkinStrings {
     /// Welcome message shown after boot.
     #[serde(default = "default_welcome")]
     pub welcome_message: String,
+    /// Further strings by key, for [`SkinStrings::format`] and
+    /// [`SkinStrings::plural`].
+    #[serde(default)]
+    pub messages: BTreeMap<String, String>,
 }
 
 fn default_prompt() -> String {
@@ -51,6 +64,29 @@ fn default_welcome() -> String {
     "Welcome to OASIS_OS.".to_string()
 }
 
+impl SkinStrings {
+    /// Overlay these strings on `parent`'s, keeping the parent's value
+    /// for every key not in `set_keys`.
+    ///
+    /// Messages are merged key by key, the child's winning.
+    pub(crate) fn merge(mut self, mut parent: Self, set_keys: &[String]) -> Self {
+        let mut messages = std::mem::take(&mut parent.messages);
+        messages.append(&mut self.messages);
+        let mut merged = prefer_set_keys!(self, parent, set_keys;
+            boot_text,
+            prompt_format,
+            title,
+            home_label,
+            error_prefix,
+            shutdown_message,
+            welcome_message,
+            messages,
+        );
+        merged.messages = messages;
+        merged
+    }
+}
+
 impl Default for SkinStrings {
     fn default() -> Self {
         Self {
@@ -61,15 +97,107 @@ impl Default for SkinStrings {
             error_prefix: default_error_prefix(),
             shutdown_message: default_shutdown(),
             welcome_message: default_welcome(),
+            messages: BTreeMap::new(),
         }
     }
 }
 
 impl SkinStrings {
+    /// Every key `strings.toml` can set.
+    pub const KEYS: [&'static str; 8] = [
+        "boot_text",
+        "prompt_format",
+        "title",
+        "home_label",
+        "error_prefix",
+        "shutdown_message",
+        "welcome_message",
+        "messages",
+    ];
+
     /// Format the prompt with the current working directory substituted.
     pub fn format_prompt(&self, cwd: &str) -> String {
         self.prompt_format.replace("{cwd}", cwd)
     }
+
+    /// Look up a string: one of the text fields by name, or an entry of
+    /// `[messages]`.
+    pub fn get(&self, key: &str) -> Option<&str> {
+        let field = match key {
+            "prompt_format" => &self.prompt_format,
+            "title" => &self.title,
+            "home_label" => &self.home_label,
+            "error_prefix" => &self.error_prefix,
+            "shutdown_message" => &self.shutdown_message,
+            "welcome_message" => &self.welcome_message,
+            _ => return self.messages.get(key).map(String::as_str),
+        };
+        Some(field)
+    }
+
+    /// The string `key` with `{name}` placeholders replaced from `args`.
+    ///
+    /// Substituted values are not scanned again, placeholders without an
+    /// argument are kept as written, and `{{` / `}}` stand for literal
+    /// braces. A missing key yields `[key]`, so it shows up on screen.
+    pub fn format(&self, key: &str, args: &[(&str, &str)]) -> String {
+        match self.get(key) {
+            Some(template) => substitute(template, args),
+            None => format!("[{key}]"),
+        }
+    }
+
+    /// The singular (`<key>_one`) or plural (`<key>_other`) variant of
+    /// `key` for `n`, with `{n}` replaced by the count. A variant that is
+    /// not defined falls back to `key` itself, then to `[key]`.
+    pub fn plural(&self, key: &str, n: u64) -> String {
+        let variant = if n == 1 {
+            format!("{key}_one")
+        } else {
+            format!("{key}_other")
+        };
+        let count = n.to_string();
+        let args = [("n", count.as_str())];
+        match self.get(&variant).or_else(|| self.get(key)) {
+            Some(template) => substitute(template, &args),
+            None => format!("[{key}]"),
+        }
+    }
+}
+
+/// Replace `{name}` placeholders in one left-to-right pass.
+fn substitute(template: &str, args: &[(&str, &str)]) -> String {
+    let mut out = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(i) = rest.find(['{', '}']) {
+        out.push_str(&rest[..i]);
+        let tail = &rest[i..];
+        if tail.starts_with("{{") || tail.starts_with("}}") {
+            out.push_str(&tail[..1]);
+            rest = &tail[2..];
+            continue;
+        }
+        let value = tail
+            .strip_prefix('{')
+            .and_then(|t| t.find('}').map(|end| &t[..end]))
+            .and_then(|name| {
+                args.iter()
+                    .find(|(k, _)| *k == name)
+                    .map(|(k, v)| (k.len(), v))
+            });
+        match value {
+            Some((len, value)) => {
+                out.push_str(value);
+                rest = &tail[len + 2..];
+            },
+            None => {
+                out.push_str(&tail[..1]);
+                rest = &tail[1..];
+            },
+        }
+    }
+    out.push_str(rest);
+    out
 }
 
 #[cfg(test)]
@@ -118,4 +246,73 @@ welcome_message = "TACTICAL SYSTEM ONLINE"
         // Defaults for unspecified fields.
         assert_eq!(s.error_prefix, "error: ");
     }
+
+    fn messages() -> SkinStrings {
+        let toml = r#"
+title = "TACTICAL"
+[messages]
+greeting = "Hello, {user}! Welcome to {title}."
+literal = "{{user}} is {user}"
+files_one = "{n} file"
+files_other = "{n} files"
+items = "{n} item(s)"
+"#;
+        toml::from_str(toml).unwrap()
+    }
+
+    #[test]
+    fn format_substitutes_placeholders() {
+        let s = messages();
+        assert_eq!(
+            s.format("greeting", &[("user", "ada"), ("title", "OASIS")]),
+            "Hello, ada! Welcome to OASIS."
+        );
+        assert_eq!(s.format("title", &[]), "TACTICAL");
+        // Unknown placeholders are kept.
+        assert_eq!(
+            s.format("greeting", &[("user", "ada")]),
+            "Hello, ada! Welcome to {title}."
+        );
+        assert_eq!(s.format("literal", &[("user", "ada")]), "{user} is ada");
+    }
+
+    #[test]
+    fn format_does_not_expand_nested_placeholders() {
+        let s = messages();
+        // A value that itself looks like a placeholder is inserted as is.
+        assert_eq!(
+            s.format("greeting", &[("user", "{title}"), ("title", "OASIS")]),
+            "Hello, {title}! Welcome to OASIS."
+        );
+        // Braces around a placeholder survive the substitution.
+        assert_eq!(substitute("{{{user}}}", &[("user", "ada")]), "{ada}");
+        assert_eq!(substitute("{a{user}}", &[("user", "ada")]), "{aada}");
+    }
+
+    #[test]
+    fn plural_selects_variant_by_count() {
+        let s = messages();
+        assert_eq!(s.plural("files", 1), "1 file");
+        assert_eq!(s.plural("files", 0), "0 files");
+        assert_eq!(s.plural("files", 12), "12 files");
+        assert_eq!(s.plural("items", 3), "3 item(s)");
+    }
+
+    #[test]
+    fn unknown_keys_are_bracketed() {
+        let s = messages();
+        assert_eq!(s.format("nope", &[("user", "ada")]), "[nope]");
+        assert_eq!(s.plural("nope", 2), "[nope]");
+        assert_eq!(s.get("boot_text"), None);
+    }
+
+    #[test]
+    fn merge_combines_messages() {
+        let parent = messages();
+        let child: SkinStrings = toml::from_str("[messages]\nfiles_one = \"one file\"\n").unwrap();
+        let merged = child.merge(parent, &["messages".to_string()]);
+        assert_eq!(merged.plural("files", 1), "one file");
+        assert_eq!(merged.plural("files", 2), "2 files");
+        assert_eq!(merged.title, "TACTICAL");
+    }
 }
@@ -3,7 +3,7 @@
 //! The theme defines the color palette and optional WM visual overrides
 //! for a skin. Loaded from `theme.toml`.
 
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 
 use oasis_types::backend::Color;
 use oasis_types::color::{darken, lighten, with_alpha};
@@ -11,8 +11,10 @@ use oasis_types::shadow::Shadow;
 use oasis_ui::theme::Theme;
 use oasis_wm::WmTheme;
 
+use crate::inherit::{is_set, merge_nested, prefer_child};
+
 /// Color scheme for a skin.
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct SkinTheme {
     /// Main background color.
     #[serde(default = "default_bg")]
@@ -79,6 +81,10 @@ pub struct SkinTheme {
     #[serde(default)]
     pub start_menu_overrides: Option<StartMenuOverrides>,
 
+    /// Colors and placement of notification toasts.
+    #[serde(default)]
+    pub notification_overrides: Option<NotificationOverrides>,
+
     /// Wallpaper generation configuration.
     #[serde(default)]
     pub wallpaper: Option<WallpaperConfig>,
@@ -89,7 +95,7 @@ pub struct SkinTheme {
 }
 
 /// Optional overrides for the window manager theme.
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct WmThemeOverrides {
     pub titlebar_height: Option<u32>,
     pub border_width: Option<u32>,
@@ -166,10 +172,16 @@ pub struct WmThemeOverrides {
     pub maximize_top_inset: Option<u32>,
     #[serde(default)]
     pub maximize_bottom_inset: Option<u32>,
+    #[serde(default)]
+    pub snap_threshold: Option<u32>,
+    #[serde(default)]
+    pub animate_minimize: Option<bool>,
+    #[serde(default)]
+    pub keyboard_step: Option<u32>,
 }
 
 /// Per-element overrides for status bar and bottom bar colors.
-#[derive(Debug, Clone, Default, Deserialize)]
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
 pub struct BarOverrides {
     pub bar_bg: Option<String>,
     pub statusbar_bg: Option<String>,
@@ -196,7 +208,7 @@ pub struct BarOverrides {
 }
 
 /// Per-element overrides for dashboard icon rendering.
-#[derive(Debug, Clone, Default, Deserialize)]
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
 pub struct IconOverrides {
     pub body_color: Option<String>,
     pub fold_color: Option<String>,
@@ -216,7 +228,7 @@ pub struct IconOverrides {
 }
 
 /// Wallpaper generation configuration.
-#[derive(Debug, Clone, Default, Deserialize)]
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
 pub struct WallpaperConfig {
     /// Style: "gradient" (default), "solid", or "none".
     pub style: Option<String>,
@@ -228,10 +240,18 @@ pub struct WallpaperConfig {
     pub wave_intensity: Option<f32>,
     /// Gradient angle in degrees: 0=horizontal, 90=vertical (default 0).
     pub angle: Option<f32>,
+    /// PNG, BMP or GIF image (or JPEG, on the PSP), relative to the skin
+    /// directory. Drawn in place of the generated wallpaper once loaded.
+    pub image: Option<String>,
+    /// How the image fills the screen: "stretch" (default), "tile", or
+    /// "center".
+    pub mode: Option<String>,
+    /// Hex color behind a centered image, or while it loads.
+    pub fallback_color: Option<String>,
 }
 
 /// Geometry overrides for bar heights, icon sizes, and font sizes.
-#[derive(Debug, Clone, Default, Deserialize)]
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
 pub struct GeometryOverrides {
     pub statusbar_height: Option<u32>,
     pub bottombar_height: Option<u32>,
@@ -242,7 +262,7 @@ pub struct GeometryOverrides {
 }
 
 /// Per-element overrides for the start menu popup and button.
-#[derive(Debug, Clone, Default, Deserialize)]
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
 pub struct StartMenuOverrides {
     pub panel_bg: Option<String>,
     pub panel_gradient_top: Option<String>,
@@ -301,7 +321,7 @@ pub struct StartMenuOverrides {
 }
 
 /// Per-element overrides for browser chrome colors.
-#[derive(Debug, Clone, Default, Deserialize)]
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
 pub struct BrowserOverrides {
     pub chrome_bg: Option<String>,
     pub chrome_text: Option<String>,
@@ -313,6 +333,225 @@ pub struct BrowserOverrides {
     pub link_color: Option<String>,
 }
 
+/// Per-element overrides for notification toasts.
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct NotificationOverrides {
+    pub bg: Option<String>,
+    pub border: Option<String>,
+    pub title_text: Option<String>,
+    pub body_text: Option<String>,
+    /// Accent stripe of normal and low urgency toasts.
+    pub accent: Option<String>,
+    /// Accent stripe of critical toasts.
+    pub critical_accent: Option<String>,
+    pub border_radius: Option<u16>,
+    pub shadow_level: Option<u8>,
+    pub width: Option<u32>,
+    /// Most toasts shown at once; the rest wait their turn.
+    pub max_visible: Option<usize>,
+    /// Screen corner: "top_right" (default), "top_left", "bottom_right"
+    /// or "bottom_left".
+    pub corner: Option<String>,
+}
+
+impl WmThemeOverrides {
+    pub fn merge(self, parent: Self) -> Self {
+        prefer_child!(self, parent;
+            titlebar_height,
+            border_width,
+            titlebar_active,
+            titlebar_inactive,
+            titlebar_text,
+            frame_color,
+            content_bg,
+            btn_close,
+            btn_minimize,
+            btn_maximize,
+            button_size,
+            resize_handle_size,
+            titlebar_font_size,
+            titlebar_radius,
+            titlebar_gradient,
+            titlebar_gradient_top,
+            titlebar_gradient_bottom,
+            titlebar_inactive_gradient_top,
+            titlebar_inactive_gradient_bottom,
+            frame_shadow_level,
+            frame_border_radius,
+            button_radius,
+            button_side,
+            glyph_close,
+            glyph_minimize,
+            glyph_maximize,
+            title_align,
+            separator_enabled,
+            separator_color,
+            glyph_close_color,
+            glyph_minimize_color,
+            glyph_maximize_color,
+            button_spacing,
+            btn_close_hover,
+            btn_minimize_hover,
+            btn_maximize_hover,
+            title_text_shadow,
+            title_text_shadow_color,
+            content_stroke_width,
+            content_stroke_color,
+            maximize_top_inset,
+            maximize_bottom_inset,
+            snap_threshold,
+            animate_minimize,
+            keyboard_step,
+        )
+    }
+}
+
+impl BarOverrides {
+    pub fn merge(self, parent: Self) -> Self {
+        prefer_child!(self, parent;
+            bar_bg,
+            statusbar_bg,
+            separator_color,
+            battery_color,
+            version_color,
+            clock_color,
+            url_color,
+            usb_color,
+            tab_active_fill,
+            tab_active_alpha,
+            tab_inactive_alpha,
+            media_tab_active,
+            media_tab_inactive,
+            pipe_color,
+            r_hint_color,
+            category_label_color,
+            page_dot_active,
+            page_dot_inactive,
+            statusbar_gradient_top,
+            statusbar_gradient_bottom,
+            bar_gradient_top,
+            bar_gradient_bottom,
+        )
+    }
+}
+
+impl IconOverrides {
+    pub fn merge(self, parent: Self) -> Self {
+        prefer_child!(self, parent;
+            body_color,
+            fold_color,
+            outline_color,
+            shadow_color,
+            label_color,
+            cursor_color,
+            icon_border_radius,
+            cursor_border_radius,
+            cursor_stroke_width,
+            icon_style,
+            cursor_style,
+        )
+    }
+}
+
+impl WallpaperConfig {
+    pub fn merge(self, parent: Self) -> Self {
+        prefer_child!(self, parent;
+            style,
+            color_stops,
+            wave_enabled,
+            wave_intensity,
+            angle,
+            image,
+            mode,
+            fallback_color,
+        )
+    }
+}
+
+impl GeometryOverrides {
+    pub fn merge(self, parent: Self) -> Self {
+        prefer_child!(self, parent;
+            statusbar_height,
+            bottombar_height,
+            tab_row_height,
+            icon_width,
+            icon_height,
+            font_small,
+        )
+    }
+}
+
+impl StartMenuOverrides {
+    pub fn merge(self, parent: Self) -> Self {
+        prefer_child!(self, parent;
+            panel_bg,
+            panel_gradient_top,
+            panel_gradient_bottom,
+            panel_border,
+            item_text,
+            item_text_active,
+            highlight_color,
+            button_bg,
+            button_text,
+            panel_border_radius,
+            panel_shadow_level,
+            layout_mode,
+            button_label,
+            button_width,
+            button_height,
+            button_shape,
+            panel_width,
+            columns,
+            header_text,
+            header_bg,
+            header_text_color,
+            header_height,
+            footer_enabled,
+            footer_bg,
+            footer_text_color,
+            footer_height,
+            item_icon_size,
+            item_row_height,
+            button_gradient,
+            button_gradient_top,
+            button_gradient_bottom,
+        )
+    }
+}
+
+impl BrowserOverrides {
+    pub fn merge(self, parent: Self) -> Self {
+        prefer_child!(self, parent;
+            chrome_bg,
+            chrome_text,
+            chrome_button_bg,
+            url_bar_bg,
+            url_bar_text,
+            status_bar_bg,
+            status_bar_text,
+            link_color,
+        )
+    }
+}
+
+impl NotificationOverrides {
+    pub fn merge(self, parent: Self) -> Self {
+        prefer_child!(self, parent;
+            bg,
+            border,
+            title_text,
+            body_text,
+            accent,
+            critical_accent,
+            border_radius,
+            shadow_level,
+            width,
+            max_visible,
+            corner,
+        )
+    }
+}
+
 fn default_bg() -> String {
     "#1A1A2D".to_string()
 }
@@ -363,6 +602,7 @@ impl Default for SkinTheme {
             icon_overrides: None,
             browser_overrides: None,
             start_menu_overrides: None,
+            notification_overrides: None,
             wallpaper: None,
             geometry: None,
         }
@@ -370,6 +610,59 @@ impl Default for SkinTheme {
 }
 
 impl SkinTheme {
+    /// Overlay this theme on `parent`'s. `set_keys` are the top-level
+    /// keys of this theme's TOML: colors not among them come from the
+    /// parent, as does every unset optional value.
+    pub fn merge(self, parent: Self, set_keys: &[String]) -> Self {
+        let pick = |key: &str, child: String, parent: String| {
+            if is_set(set_keys, key) { child } else { parent }
+        };
+        Self {
+            background: pick("background", self.background, parent.background),
+            primary: pick("primary", self.primary, parent.primary),
+            secondary: pick("secondary", self.secondary, parent.secondary),
+            text: pick("text", self.text, parent.text),
+            dim_text: pick("dim_text", self.dim_text, parent.dim_text),
+            status_bar: pick("status_bar", self.status_bar, parent.status_bar),
+            prompt: pick("prompt", self.prompt, parent.prompt),
+            output: pick("output", self.output, parent.output),
+            error: pick("error", self.error, parent.error),
+            surface: self.surface.or(parent.surface),
+            accent_hover: self.accent_hover.or(parent.accent_hover),
+            border_radius: self.border_radius.or(parent.border_radius),
+            shadow_intensity: self.shadow_intensity.or(parent.shadow_intensity),
+            gradient_enabled: self.gradient_enabled.or(parent.gradient_enabled),
+            wm_theme: merge_nested(self.wm_theme, parent.wm_theme, WmThemeOverrides::merge),
+            bar_overrides: merge_nested(
+                self.bar_overrides,
+                parent.bar_overrides,
+                BarOverrides::merge,
+            ),
+            icon_overrides: merge_nested(
+                self.icon_overrides,
+                parent.icon_overrides,
+                IconOverrides::merge,
+            ),
+            browser_overrides: merge_nested(
+                self.browser_overrides,
+                parent.browser_overrides,
+                BrowserOverrides::merge,
+            ),
+            start_menu_overrides: merge_nested(
+                self.start_menu_overrides,
+                parent.start_menu_overrides,
+                StartMenuOverrides::merge,
+            ),
+            notification_overrides: merge_nested(
+                self.notification_overrides,
+                parent.notification_overrides,
+                NotificationOverrides::merge,
+            ),
+            wallpaper: merge_nested(self.wallpaper, parent.wallpaper, WallpaperConfig::merge),
+            geometry: merge_nested(self.geometry, parent.geometry, GeometryOverrides::merge),
+        }
+    }
+
     /// Parse the background color string to a `Color`.
     pub fn background_color(&self) -> Color {
         parse_hex_color(&self.background).unwrap_or(Color::BLACK)
@@ -482,6 +775,8 @@ impl SkinTheme {
             scrollbar_thumb_hover: Color::rgba(255, 255, 255, 80),
             tooltip_bg: lighten(bg, 0.15),
             tooltip_text: text,
+            progress_track: Color::rgba(255, 255, 255, 10),
+            progress_fill: accent,
 
             font_size_xs: 8,
             font_size_sm: 8,
@@ -684,6 +979,15 @@ impl SkinTheme {
             if let Some(v) = ov.maximize_bottom_inset {
                 theme.maximize_bottom_inset = v;
             }
+            if let Some(v) = ov.snap_threshold {
+                theme.snap_threshold = v;
+            }
+            if let Some(v) = ov.animate_minimize {
+                theme.animate_minimize = v;
+            }
+            if let Some(v) = ov.keyboard_step {
+                theme.keyboard_step = v;
+            }
         }
         // Default glyph colors to titlebar_text_color if not explicitly set.
         if self
@@ -758,6 +1062,15 @@ pub fn parse_hex_color(s: &str) -> Option<Color> {
     }
 }
 
+/// Format a `Color` as "#RRGGBB", or "#RRGGBBAA" when not opaque.
+pub fn format_hex_color(c: Color) -> String {
+    if c.a == 255 {
+        format!("#{:02X}{:02X}{:02X}", c.r, c.g, c.b)
+    } else {
+        format!("#{:02X}{:02X}{:02X}{:02X}", c.r, c.g, c.b, c.a)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -778,6 +1091,8 @@ mod tests {
         );
         assert_eq!(parse_hex_color("invalid"), None);
         assert_eq!(parse_hex_color("#GG0000"), None);
+        assert_eq!(format_hex_color(Color::rgb(0x20, 0x46, 0x82)), "#204682");
+        assert_eq!(format_hex_color(Color::rgba(0, 255, 0, 128)), "#00FF0080");
     }
 
     #[test]
@@ -0,0 +1,399 @@
+//! Skin validation -- find mistakes a skin author would want to fix.
+//!
+//! Loading is forgiving: bad colors fall back to defaults and missing
+//! strings or layout objects are filled in. [`Skin::validate`] reports
+//! those silent fallbacks, each with the file and TOML key to look at.
+
+use std::fmt;
+use std::path::Path;
+
+use crate::Skin;
+use crate::strings::SkinStrings;
+use crate::theme::parse_hex_color;
+
+/// How serious a validation issue is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum IssueSeverity {
+    /// The skin works, but probably not as the author intended.
+    Warning,
+    /// A value is unusable and a default is used in its place.
+    Error,
+}
+
+impl fmt::Display for IssueSeverity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Warning => f.write_str("warning"),
+            Self::Error => f.write_str("error"),
+        }
+    }
+}
+
+/// A single problem found by [`Skin::validate`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SkinValidationIssue {
+    pub severity: IssueSeverity,
+    /// File the value comes from, e.g. `theme.toml`.
+    pub file: &'static str,
+    /// Dotted TOML key within `file`, e.g. `wm_theme.titlebar_active`.
+    pub path: String,
+    pub message: String,
+}
+
+impl fmt::Display for SkinValidationIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}: {} {}: {}",
+            self.severity, self.file, self.path, self.message
+        )
+    }
+}
+
+/// String-valued theme keys that are not colors.
+const NON_COLOR_KEYS: &[&str] = &[
+    "button_label",
+    "button_shape",
+    "button_side",
+    "cursor_style",
+    "glyph_close",
+    "glyph_maximize",
+    "glyph_minimize",
+    "header_text",
+    "icon_style",
+    "image",
+    "layout_mode",
+    "mode",
+    "style",
+    "title_align",
+];
+
+/// Layout object keys holding colors.
+const LAYOUT_COLOR_KEYS: [&str; 5] = [
+    "color",
+    "text_color",
+    "gradient_top",
+    "gradient_bottom",
+    "stroke_color",
+];
+
+/// Extensions of files a skin value can refer to.
+const ASSET_EXTENSIONS: &[&str] = &[".png", ".bmp", ".jpg", ".jpeg", ".ttf", ".wav", ".mp3"];
+
+/// Layout objects the terminal positions its text with when it is shown
+/// full screen (no dashboard or window manager).
+const TERMINAL_OBJECTS: [&str; 2] = ["terminal_output", "terminal_prompt"];
+
+impl Skin {
+    /// Check the skin for values that loading silently replaced or
+    /// ignored. An empty list means nothing was found.
+    pub fn validate(&self) -> Vec<SkinValidationIssue> {
+        let mut v = Validator {
+            issues: Vec::new(),
+            dir: self.source_dir.as_deref(),
+        };
+        v.check_theme(self);
+        v.check_assets(self);
+        v.check_layout(self);
+        v.check_strings(self);
+        v.check_corrupted(self);
+        v.issues
+    }
+}
+
+struct Validator<'a> {
+    issues: Vec<SkinValidationIssue>,
+    dir: Option<&'a Path>,
+}
+
+impl Validator<'_> {
+    fn push(&mut self, severity: IssueSeverity, file: &'static str, path: &str, message: String) {
+        self.issues.push(SkinValidationIssue {
+            severity,
+            file,
+            path: path.to_string(),
+            message,
+        });
+    }
+
+    fn check_color(&mut self, file: &'static str, path: &str, value: &str) {
+        if parse_hex_color(value).is_none() {
+            self.push(
+                IssueSeverity::Error,
+                file,
+                path,
+                format!("invalid color '{value}' (expected #RRGGBB or #RRGGBBAA)"),
+            );
+        }
+    }
+
+    /// Report a value naming a file (by its extension) that is missing
+    /// from the skin directory. Skins without a directory have nothing
+    /// to check.
+    fn check_asset(&mut self, file: &'static str, path: &str, value: &str) {
+        let lower = value.to_ascii_lowercase();
+        if !ASSET_EXTENSIONS.iter().any(|ext| lower.ends_with(ext)) {
+            return;
+        }
+        if let Some(dir) = self.dir
+            && !dir.join(value).is_file()
+        {
+            self.push(
+                IssueSeverity::Error,
+                file,
+                path,
+                format!("asset '{value}' not found in {}", dir.display()),
+            );
+        }
+    }
+
+    fn check_theme(&mut self, skin: &Skin) {
+        match toml::Value::try_from(&skin.theme) {
+            Ok(value) => self.check_theme_value("", "", &value),
+            Err(e) => log::warn!("cannot inspect theme of skin '{}': {e}", skin.manifest.name),
+        }
+    }
+
+    fn check_theme_value(&mut self, path: &str, key: &str, value: &toml::Value) {
+        match value {
+            toml::Value::String(s) => {
+                if NON_COLOR_KEYS.contains(&key) {
+                    self.check_asset("theme.toml", path, s);
+                } else {
+                    self.check_color("theme.toml", path, s);
+                }
+            },
+            toml::Value::Array(items) => {
+                for (i, item) in items.iter().enumerate() {
+                    self.check_theme_value(&format!("{path}[{i}]"), key, item);
+                }
+            },
+            toml::Value::Table(table) => {
+                for (k, v) in table {
+                    let child = if path.is_empty() {
+                        k.clone()
+                    } else {
+                        format!("{path}.{k}")
+                    };
+                    self.check_theme_value(&child, k, v);
+                }
+            },
+            _ => {},
+        }
+    }
+
+    fn check_assets(&mut self, skin: &Skin) {
+        for (name, asset) in &skin.manifest.assets {
+            self.check_asset("skin.toml", &format!("assets.{name}.image"), &asset.image);
+        }
+    }
+
+    fn check_layout(&mut self, skin: &Skin) {
+        let mut names: Vec<&String> = skin.layout.objects.keys().collect();
+        names.sort();
+        for name in names {
+            let def = &skin.layout.objects[name];
+            let colors = [
+                &def.color,
+                &def.text_color,
+                &def.gradient_top,
+                &def.gradient_bottom,
+                &def.stroke_color,
+            ];
+            for (key, value) in LAYOUT_COLOR_KEYS.iter().zip(colors) {
+                if let Some(value) = value {
+                    self.check_color("layout.toml", &format!("{name}.{key}"), value);
+                }
+            }
+        }
+
+        let features = &skin.features;
+        if features.terminal && !features.dashboard && !features.window_manager {
+            for object in TERMINAL_OBJECTS {
+                if !skin.layout.objects.contains_key(object) {
+                    self.push(
+                        IssueSeverity::Warning,
+                        "layout.toml",
+                        object,
+                        "not defined; the full-screen terminal (features.toml) uses \
+                         default placement"
+                            .to_string(),
+                    );
+                }
+            }
+        }
+    }
+
+    fn check_strings(&mut self, skin: &Skin) {
+        let Some(keys) = &skin.string_keys else {
+            return;
+        };
+        for key in keys {
+            if !SkinStrings::KEYS.contains(&key.as_str()) {
+                self.push(
+                    IssueSeverity::Warning,
+                    "strings.toml",
+                    key,
+                    "unknown key; it is ignored".to_string(),
+                );
+            }
+        }
+        // An empty `[messages]` table is fine.
+        for key in SkinStrings::KEYS.iter().filter(|k| **k != "messages") {
+            if !keys.iter().any(|k| k == key) {
+                self.push(
+                    IssueSeverity::Warning,
+                    "strings.toml",
+                    key,
+                    "missing; the default text is used".to_string(),
+                );
+            }
+        }
+    }
+
+    fn check_corrupted(&mut self, skin: &Skin) {
+        let Some(m) = &skin.corrupted_modifiers else {
+            return;
+        };
+        let amounts = [
+            ("position_jitter", m.position_jitter as f32),
+            ("scanline_shift", m.scanline_shift as f32),
+            ("ramp_seconds", m.ramp_seconds),
+            ("burst_every_seconds", m.burst_every_seconds),
+            ("pulse_seconds", m.pulse_seconds),
+        ];
+        for (key, value) in amounts {
+            if value < 0.0 {
+                self.push(
+                    IssueSeverity::Error,
+                    "corrupted.toml",
+                    key,
+                    format!("{value} is negative"),
+                );
+            }
+        }
+        let fractions = [
+            ("alpha_flicker_chance", m.alpha_flicker_chance),
+            ("text_garble_chance", m.text_garble_chance),
+            ("channel_swap_chance", m.channel_swap_chance),
+            ("scanline_chance", m.scanline_chance),
+            ("pulse_depth", m.pulse_depth),
+            ("intensity", m.intensity),
+        ];
+        for (key, value) in fractions {
+            if !(0.0..=1.0).contains(&value) {
+                self.push(
+                    IssueSeverity::Error,
+                    "corrupted.toml",
+                    key,
+                    format!("{value} is outside 0.0-1.0"),
+                );
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::builtin;
+
+    const MANIFEST: &str = "name = \"test\"\n";
+    const FEATURES: &str = "dashboard = false\nterminal = true\n";
+
+    fn paths(issues: &[SkinValidationIssue]) -> Vec<(&str, &str)> {
+        issues.iter().map(|i| (i.file, i.path.as_str())).collect()
+    }
+
+    #[test]
+    fn builtin_skins_have_no_errors() {
+        for name in builtin::builtin_names() {
+            let skin = builtin::load_builtin(name).unwrap();
+            let errors: Vec<_> = skin
+                .validate()
+                .into_iter()
+                .filter(|i| i.severity == IssueSeverity::Error)
+                .collect();
+            assert!(errors.is_empty(), "{name}: {errors:?}");
+        }
+    }
+
+    #[test]
+    fn reports_bad_colors_with_toml_paths() {
+        let layout = "[terminal_output]\ncolor = \"#GGGGGG\"\n\n\
+                      [terminal_prompt]\ntext_color = \"#FFFFFF\"\n";
+        let theme = "background = \"blue\"\n\
+                     [wm_theme]\ntitlebar_active = \"#12345\"\nbutton_side = \"left\"\n\
+                     [wallpaper]\ncolor_stops = [\"#000000\", \"#XYZ000\"]\n";
+        let skin = Skin::from_toml_full(MANIFEST, layout, FEATURES, theme, "").unwrap();
+        let issues = skin.validate();
+        assert!(issues.iter().all(|i| i.severity == IssueSeverity::Error));
+        assert_eq!(
+            paths(&issues),
+            vec![
+                ("theme.toml", "background"),
+                ("theme.toml", "wallpaper.color_stops[1]"),
+                ("theme.toml", "wm_theme.titlebar_active"),
+                ("layout.toml", "terminal_output.color"),
+            ]
+        );
+        assert!(issues[3].to_string().contains("'#GGGGGG'"));
+    }
+
+    #[test]
+    fn reports_missing_layout_objects_and_strings() {
+        let strings = "title = \"T\"\nwelcom_message = \"hi\"\n";
+        let skin = Skin::from_toml_full(MANIFEST, "", FEATURES, "", strings).unwrap();
+        let issues = skin.validate();
+        let found = paths(&issues);
+        assert!(found.contains(&("layout.toml", "terminal_output")));
+        assert!(found.contains(&("layout.toml", "terminal_prompt")));
+        assert!(found.contains(&("strings.toml", "welcom_message")));
+        assert!(found.contains(&("strings.toml", "welcome_message")));
+        assert!(!found.contains(&("strings.toml", "title")));
+        assert!(issues.iter().all(|i| i.severity == IssueSeverity::Warning));
+    }
+
+    #[test]
+    fn reports_out_of_range_corruption_and_missing_assets() {
+        let skin = Skin::from_toml_corrupted(
+            MANIFEST,
+            "[terminal_output]\n[terminal_prompt]\n",
+            FEATURES,
+            "",
+            "",
+            "position_jitter = -1\nintensity = 1.5\nramp_seconds = -2.0\n\
+             scanline_chance = 2.0\n",
+        )
+        .unwrap();
+        let found: Vec<_> = skin.validate().into_iter().map(|i| i.path).collect();
+        assert_eq!(
+            found,
+            vec![
+                "position_jitter",
+                "ramp_seconds",
+                "scanline_chance",
+                "intensity"
+            ]
+        );
+
+        let dir = tempfile::tempdir().unwrap();
+        let manifest = format!("{MANIFEST}\n[assets.window_frame]\nimage = \"frame.png\"\n");
+        std::fs::write(dir.path().join("skin.toml"), manifest).unwrap();
+        std::fs::write(dir.path().join("features.toml"), "").unwrap();
+        std::fs::write(dir.path().join("layout.toml"), "").unwrap();
+        std::fs::write(dir.path().join("start.png"), "").unwrap();
+        let theme = "[start_menu_overrides]\nbutton_label = \"start.png\"\n\
+                     [wallpaper]\nimage = \"Images/Sky.PNG\"\nmode = \"tile\"\n";
+        std::fs::write(dir.path().join("theme.toml"), theme).unwrap();
+        let skin = Skin::from_directory(dir.path()).unwrap();
+        let issues = skin.validate();
+        assert_eq!(
+            paths(&issues),
+            vec![
+                ("theme.toml", "wallpaper.image"),
+                ("skin.toml", "assets.window_frame.image"),
+            ]
+        );
+    }
+}
@@ -0,0 +1,234 @@
+//! Change detection for skins loaded from a directory.
+//!
+//! `SkinWatcher` opens the skin directory as a [`RealVfs`] and reads its
+//! change journal, which is fed by comparing the modification time and
+//! size of every file in the directory. Besides the TOML files, the
+//! wallpaper and asset images the skin names are watched. That is cheap
+//! enough to poll about once a second from the main loop, e.g. through
+//! [`Skin::reload_if_changed`].
+
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use oasis_types::error::Result;
+use oasis_vfs::{RealVfs, Vfs};
+
+use crate::Skin;
+use crate::loader::SKIN_FILES;
+
+/// Watches a skin directory for edits.
+#[derive(Debug)]
+pub struct SkinWatcher {
+    dir: PathBuf,
+    /// The skin directory, or `None` if it could not be opened.
+    vfs: Option<RealVfs>,
+    /// Journal generation the skin files were last loaded at.
+    generation: u64,
+    /// Images the skin names, as journal paths.
+    images: Vec<String>,
+}
+
+impl SkinWatcher {
+    /// Start watching the directory `skin` was loaded from. Returns
+    /// `None` for skins that did not come from a directory.
+    pub fn new(skin: &Skin) -> Option<Self> {
+        let mut watcher = Self::for_dir(skin.source_dir.as_deref()?);
+        watcher.images = image_paths(skin);
+        Some(watcher)
+    }
+
+    /// Start watching the skin files in `dir`.
+    pub fn for_dir(dir: &Path) -> Self {
+        let mut watcher = Self {
+            dir: dir.to_path_buf(),
+            vfs: RealVfs::new(dir).ok(),
+            generation: 0,
+            images: Vec::new(),
+        };
+        watcher.rearm();
+        watcher
+    }
+
+    /// Rescan the directory at most once per `interval`; see
+    /// [`RealVfs::with_poll_interval`].
+    pub fn with_poll_interval(mut self, interval: Duration) -> Self {
+        self.vfs = self.vfs.map(|vfs| vfs.with_poll_interval(interval));
+        self
+    }
+
+    /// The watched directory.
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Whether any skin file or image was modified, created, or deleted
+    /// since the watcher was created or last rearmed.
+    pub fn poll_changed(&self) -> bool {
+        let Some(vfs) = &self.vfs else {
+            return false;
+        };
+        let changes = vfs.changes_since(self.generation);
+        changes.truncated
+            || changes.changes.iter().any(|(path, _)| {
+                self.images.contains(path)
+                    || path
+                        .strip_prefix('/')
+                        .is_some_and(|name| SKIN_FILES.contains(&name))
+            })
+    }
+
+    /// Record the current state of the files, so only later edits are
+    /// reported. Call after reloading (whether or not it succeeded).
+    pub fn rearm(&mut self) {
+        if let Some(vfs) = &self.vfs {
+            self.generation = vfs.generation();
+        }
+    }
+}
+
+impl Skin {
+    /// Reload this skin if `watcher` saw its files change, and rearm the
+    /// watcher. Returns whether the skin was reloaded.
+    ///
+    /// If the new files fail to load (say a TOML file was caught half
+    /// written) the error is returned and the skin is left unchanged.
+    /// The watcher is rearmed either way, so the next save is picked up.
+    pub fn reload_if_changed(&mut self, watcher: &mut SkinWatcher) -> Result<bool> {
+        if !watcher.poll_changed() {
+            return Ok(false);
+        }
+        watcher.rearm();
+        self.reload()?;
+        // The reloaded skin may name other images.
+        watcher.images = image_paths(self);
+        Ok(true)
+    }
+}
+
+/// Journal paths of the wallpaper and asset images `skin` names.
+fn image_paths(skin: &Skin) -> Vec<String> {
+    let wallpaper = skin.theme.wallpaper.as_ref().and_then(|w| w.image.as_ref());
+    let assets = skin.manifest.assets.values().map(|asset| &asset.image);
+    wallpaper
+        .into_iter()
+        .chain(assets)
+        .map(|image| format!("/{}", image.trim_start_matches("./")))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs::File;
+    use std::time::{Duration, SystemTime};
+
+    fn skin_dir() -> tempfile::TempDir {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("skin.toml"), "name = \"watched\"\n").unwrap();
+        std::fs::write(dir.path().join("layout.toml"), "").unwrap();
+        std::fs::write(dir.path().join("features.toml"), "").unwrap();
+        dir
+    }
+
+    /// Push a file's mtime forward so the change is visible even on
+    /// filesystems with coarse timestamps.
+    fn touch(path: &Path) {
+        let later = SystemTime::now() + Duration::from_secs(5);
+        File::options()
+            .write(true)
+            .open(path)
+            .unwrap()
+            .set_modified(later)
+            .unwrap();
+    }
+
+    #[test]
+    fn detects_edits_and_rearms() {
+        let dir = skin_dir();
+        let skin = Skin::from_directory(dir.path()).unwrap();
+        let mut watcher = SkinWatcher::new(&skin)
+            .unwrap()
+            .with_poll_interval(Duration::ZERO);
+        assert_eq!(watcher.dir(), dir.path());
+        assert!(!watcher.poll_changed());
+
+        touch(&dir.path().join("layout.toml"));
+        assert!(watcher.poll_changed());
+        watcher.rearm();
+        assert!(!watcher.poll_changed());
+    }
+
+    #[test]
+    fn reload_if_changed_keeps_skin_on_bad_toml() {
+        let dir = skin_dir();
+        let mut skin = Skin::from_directory(dir.path()).unwrap();
+        let mut watcher = SkinWatcher::new(&skin)
+            .unwrap()
+            .with_poll_interval(Duration::ZERO);
+        assert!(!skin.reload_if_changed(&mut watcher).unwrap());
+
+        let manifest = dir.path().join("skin.toml");
+        std::fs::write(&manifest, "name = \"edited").unwrap();
+        touch(&manifest);
+        assert!(skin.reload_if_changed(&mut watcher).is_err());
+        assert_eq!(skin.manifest.name, "watched");
+        assert!(!skin.reload_if_changed(&mut watcher).unwrap());
+
+        std::fs::write(&manifest, "name = \"edited\"\n").unwrap();
+        assert!(skin.reload_if_changed(&mut watcher).unwrap());
+        assert_eq!(skin.manifest.name, "edited");
+    }
+
+    #[test]
+    fn detects_optional_files_appearing() {
+        let dir = skin_dir();
+        let watcher = SkinWatcher::for_dir(dir.path()).with_poll_interval(Duration::ZERO);
+        std::fs::write(dir.path().join("theme.toml"), "").unwrap();
+        assert!(watcher.poll_changed());
+    }
+
+    #[test]
+    fn ignores_other_files() {
+        let dir = skin_dir();
+        let watcher = SkinWatcher::for_dir(dir.path()).with_poll_interval(Duration::ZERO);
+        std::fs::create_dir(dir.path().join("assets")).unwrap();
+        std::fs::write(dir.path().join("assets/notes.txt"), "x").unwrap();
+        assert!(!watcher.poll_changed());
+    }
+
+    #[test]
+    fn detects_image_edits() {
+        let dir = skin_dir();
+        std::fs::write(
+            dir.path().join("skin.toml"),
+            "name = \"watched\"\n[assets.window_frame]\nimage = \"img/frame.png\"\n",
+        )
+        .unwrap();
+        std::fs::write(
+            dir.path().join("theme.toml"),
+            "[wallpaper]\nimage = \"./wall.png\"\n",
+        )
+        .unwrap();
+        std::fs::create_dir(dir.path().join("img")).unwrap();
+        std::fs::write(dir.path().join("img/frame.png"), "old").unwrap();
+        std::fs::write(dir.path().join("wall.png"), "old").unwrap();
+        let skin = Skin::from_directory(dir.path()).unwrap();
+        let mut watcher = SkinWatcher::new(&skin)
+            .unwrap()
+            .with_poll_interval(Duration::ZERO);
+
+        for image in ["wall.png", "img/frame.png"] {
+            assert!(!watcher.poll_changed());
+            std::fs::write(dir.path().join(image), "new").unwrap();
+            touch(&dir.path().join(image));
+            assert!(watcher.poll_changed(), "{image}");
+            watcher.rearm();
+        }
+    }
+
+    #[test]
+    fn builtin_skins_are_not_watched() {
+        let skin = crate::builtin::load_builtin("terminal").unwrap();
+        assert!(SkinWatcher::new(&skin).is_none());
+    }
+}
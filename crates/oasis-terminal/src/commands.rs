@@ -1,8 +1,9 @@
 //! Built-in commands for the OASIS_OS terminal.
 
 use oasis_types::error::{OasisError, Result};
-use oasis_vfs::EntryKind;
+use oasis_vfs::{EntryKind, Metadata, VfsEntry};
 
+use crate::file_commands::{format_mode, format_timestamp};
 use crate::interpreter::{Command, CommandOutput, CommandRegistry, Environment};
 
 /// Maximum file size for `cat` display (10 MiB).
@@ -31,6 +32,8 @@ pub fn register_builtins(reg: &mut CommandRegistry) {
     // Phase 4: file browser commands.
     reg.register(Box::new(CpCmd));
     reg.register(Box::new(MvCmd));
+    reg.register(Box::new(LnCmd));
+    reg.register(Box::new(ReadlinkCmd));
     reg.register(Box::new(FindCmd));
     // Phase 4: system commands using platform services.
     reg.register(Box::new(PowerCmd));
@@ -103,21 +106,20 @@ impl Command for LsCmd {
         "List directory contents"
     }
     fn usage(&self) -> &str {
-        "ls [path]"
+        "ls [-l] [--json] [path]"
     }
     fn category(&self) -> &str {
         "filesystem"
     }
     fn execute(&self, args: &[&str], env: &mut Environment<'_>) -> Result<CommandOutput> {
-        let path = if args.is_empty() {
-            env.cwd.clone()
-        } else {
-            resolve_path(&env.cwd, args[0])
-        };
+        let (long, path) = ls_args(args, env)?;
         let entries = env.vfs.readdir(&path)?;
         if entries.is_empty() {
             return Ok(CommandOutput::Text("(empty)".to_string()));
         }
+        if long {
+            return Ok(CommandOutput::Text(ls_long(env, &path, &entries)?));
+        }
         let mut lines = Vec::new();
         for e in &entries {
             let suffix = if e.kind == EntryKind::Directory {
@@ -129,6 +131,85 @@ impl Command for LsCmd {
         }
         Ok(CommandOutput::Text(lines.join("\n")))
     }
+    fn execute_json(&self, args: &[&str], env: &mut Environment<'_>) -> Result<serde_json::Value> {
+        let (long, path) = ls_args(args, env)?;
+        let entries = env.vfs.readdir(&path)?;
+        let mut items = Vec::new();
+        for e in &entries {
+            let mut item = serde_json::json!({
+                "name": e.name,
+                "size": e.size,
+                "is_dir": e.kind == EntryKind::Directory,
+            });
+            if e.kind == EntryKind::Symlink {
+                item["link"] = env.vfs.readlink(&resolve_path(&path, &e.name))?.into();
+            }
+            if long {
+                let meta = entry_metadata(env, &path, e)?;
+                item["mode"] = format!("{:04o}", meta.mode).into();
+                item["owner"] = meta.owner.into();
+                item["modified"] = meta.modified.into();
+            }
+            items.push(item);
+        }
+        Ok(items.into())
+    }
+}
+
+/// Parse `ls` arguments into (long format, directory).
+fn ls_args(args: &[&str], env: &Environment<'_>) -> Result<(bool, String)> {
+    let (flags, rest) = split_flags(args);
+    if let Some(flag) = flags.iter().find(|f| **f != "-l") {
+        return Err(OasisError::Command(format!("ls: unknown option {flag}")));
+    }
+    let path = match rest.first() {
+        Some(arg) => resolve_path(&env.cwd, arg),
+        None => env.cwd.clone(),
+    };
+    Ok((!flags.is_empty(), path))
+}
+
+/// Metadata `ls -l` shows for `entry` in `dir`: a link's own (default)
+/// metadata rather than its target's, which may not exist.
+fn entry_metadata(env: &Environment<'_>, dir: &str, entry: &VfsEntry) -> Result<Metadata> {
+    if entry.kind == EntryKind::Symlink {
+        return Ok(Metadata::new(EntryKind::Symlink, entry.size));
+    }
+    env.vfs.metadata(&resolve_path(dir, &entry.name))
+}
+
+/// `ls -l` lines: mode, owner, size, modification time and name, with
+/// the owner and size columns padded to the widest entry. Links show
+/// their target after `->`.
+fn ls_long(env: &Environment<'_>, dir: &str, entries: &[VfsEntry]) -> Result<String> {
+    let mut rows = Vec::new();
+    for e in entries {
+        let meta = entry_metadata(env, dir, e)?;
+        let suffix = match e.kind {
+            EntryKind::Directory => "/".to_string(),
+            EntryKind::File => String::new(),
+            EntryKind::Symlink => {
+                format!(" -> {}", env.vfs.readlink(&resolve_path(dir, &e.name))?)
+            },
+        };
+        let time = format_timestamp(meta.modified);
+        rows.push((
+            format_mode(meta.kind, meta.mode),
+            meta.owner,
+            meta.size.to_string(),
+            time.get(..16).unwrap_or(&time).to_string(),
+            format!("{}{suffix}", e.name),
+        ));
+    }
+    let owner_w = rows.iter().map(|r| r.1.len()).max().unwrap_or(0);
+    let size_w = rows.iter().map(|r| r.2.len()).max().unwrap_or(0);
+    let lines: Vec<String> = rows
+        .iter()
+        .map(|(mode, owner, size, time, name)| {
+            format!("{mode} {owner:<owner_w$} {size:>size_w$} {time:<16} {name}")
+        })
+        .collect();
+    Ok(lines.join("\n"))
 }
 
 // ---------------------------------------------------------------------------
@@ -201,14 +282,18 @@ impl Command for CatCmd {
         "Display file contents"
     }
     fn usage(&self) -> &str {
-        "cat <file>"
+        "cat [file]"
     }
     fn category(&self) -> &str {
         "filesystem"
     }
     fn execute(&self, args: &[&str], env: &mut Environment<'_>) -> Result<CommandOutput> {
         if args.is_empty() {
-            return Err(OasisError::Command("usage: cat <file>".to_string()));
+            // Pass piped input through (`... | cat`).
+            return match env.stdin.take() {
+                Some(text) => Ok(CommandOutput::Text(text)),
+                None => Err(OasisError::Command("usage: cat [file]".to_string())),
+            };
         }
         let path = resolve_path(&env.cwd, args[0]);
         let meta = env.vfs.stat(&path)?;
@@ -262,24 +347,71 @@ impl Command for RmCmd {
         "rm"
     }
     fn description(&self) -> &str {
-        "Remove a file or empty directory"
+        "Remove files or directories"
     }
     fn usage(&self) -> &str {
-        "rm <path>"
+        "rm [-r] [-f] <path...>"
     }
     fn category(&self) -> &str {
         "filesystem"
     }
     fn execute(&self, args: &[&str], env: &mut Environment<'_>) -> Result<CommandOutput> {
-        if args.is_empty() {
-            return Err(OasisError::Command("usage: rm <path>".to_string()));
+        let (flags, paths) = split_flags(args);
+        let recursive = flags.iter().any(|f| f.contains(['r', 'R']));
+        let force = flags.iter().any(|f| f.contains('f'));
+        if paths.is_empty() {
+            return Err(OasisError::Command(
+                "usage: rm [-r] [-f] <path...>".to_string(),
+            ));
+        }
+        for arg in paths {
+            let path = resolve_path(&env.cwd, arg);
+            if !env.vfs.exists(&path) {
+                if force {
+                    continue;
+                }
+                return Err(OasisError::Command(format!("rm: no such path: {arg}")));
+            }
+            if recursive {
+                env.vfs.remove_dir_all(&path)?;
+            } else if env.vfs.stat(&path)?.kind == EntryKind::Directory
+                && !env.vfs.readdir(&path)?.is_empty()
+            {
+                return Err(OasisError::Command(format!(
+                    "rm: {arg}: directory not empty (use -r)"
+                )));
+            } else {
+                env.vfs.remove(&path)?;
+            }
         }
-        let path = resolve_path(&env.cwd, args[0]);
-        env.vfs.remove(&path)?;
         Ok(CommandOutput::None)
     }
 }
 
+/// Split leading `-x` flags from the other arguments.
+fn split_flags<'a>(args: &[&'a str]) -> (Vec<&'a str>, Vec<&'a str>) {
+    let n = args
+        .iter()
+        .take_while(|a| a.len() > 1 && a.starts_with('-'))
+        .count();
+    (args[..n].to_vec(), args[n..].to_vec())
+}
+
+/// Where copying or moving `src` to `dst` puts it: inside `dst` when that
+/// is an existing directory, otherwise `dst` itself.
+fn transfer_target(env: &Environment<'_>, src: &str, dst: &str) -> String {
+    let is_dir = env
+        .vfs
+        .stat(dst)
+        .is_ok_and(|meta| meta.kind == EntryKind::Directory);
+    let name = src.rsplit('/').next().unwrap_or(src);
+    if is_dir && !name.is_empty() && src != dst {
+        resolve_path(dst, name)
+    } else {
+        dst.to_string()
+    }
+}
+
 // ---------------------------------------------------------------------------
 // echo
 // ---------------------------------------------------------------------------
@@ -388,29 +520,37 @@ impl Command for CpCmd {
         "cp"
     }
     fn description(&self) -> &str {
-        "Copy a file"
+        "Copy files or directories"
     }
     fn usage(&self) -> &str {
-        "cp <src> <dst>"
+        "cp [-r] <src> <dst>"
     }
     fn category(&self) -> &str {
         "filesystem"
     }
     fn execute(&self, args: &[&str], env: &mut Environment<'_>) -> Result<CommandOutput> {
-        if args.len() < 2 {
-            return Err(OasisError::Command("usage: cp <src> <dst>".to_string()));
+        let (flags, paths) = split_flags(args);
+        let recursive = flags.iter().any(|f| f.contains(['r', 'R']));
+        let [src, dst] = paths[..] else {
+            return Err(OasisError::Command(
+                "usage: cp [-r] <src> <dst>".to_string(),
+            ));
+        };
+        let src_path = resolve_path(&env.cwd, src);
+        let meta = env.vfs.stat(&src_path)?;
+        if meta.kind == EntryKind::Directory && !recursive {
+            return Err(OasisError::Command(format!(
+                "cp: {src} is a directory (use -r)"
+            )));
         }
-        let src = resolve_path(&env.cwd, args[0]);
-        let dst = resolve_path(&env.cwd, args[1]);
-        let meta = env.vfs.stat(&src)?;
         if meta.size as usize > COPY_MAX_SIZE {
             return Err(OasisError::Command(format!(
                 "file too large ({} bytes, max {})",
                 meta.size, COPY_MAX_SIZE
             )));
         }
-        let data = env.vfs.read(&src)?;
-        env.vfs.write(&dst, &data)?;
+        let dst_path = transfer_target(env, &src_path, &resolve_path(&env.cwd, dst));
+        env.vfs.copy(&src_path, &dst_path)?;
         Ok(CommandOutput::None)
     }
 }
@@ -425,7 +565,7 @@ impl Command for MvCmd {
         "mv"
     }
     fn description(&self) -> &str {
-        "Move/rename a file"
+        "Move/rename a file or directory"
     }
     fn usage(&self) -> &str {
         "mv <src> <dst>"
@@ -434,25 +574,76 @@ impl Command for MvCmd {
         "filesystem"
     }
     fn execute(&self, args: &[&str], env: &mut Environment<'_>) -> Result<CommandOutput> {
-        if args.len() < 2 {
+        let [src, dst] = args[..] else {
             return Err(OasisError::Command("usage: mv <src> <dst>".to_string()));
-        }
-        let src = resolve_path(&env.cwd, args[0]);
-        let dst = resolve_path(&env.cwd, args[1]);
-        let meta = env.vfs.stat(&src)?;
-        if meta.size as usize > COPY_MAX_SIZE {
-            return Err(OasisError::Command(format!(
-                "file too large ({} bytes, max {})",
-                meta.size, COPY_MAX_SIZE
-            )));
-        }
-        let data = env.vfs.read(&src)?;
-        env.vfs.write(&dst, &data)?;
-        env.vfs.remove(&src)?;
+        };
+        let src_path = resolve_path(&env.cwd, src);
+        let dst_path = transfer_target(env, &src_path, &resolve_path(&env.cwd, dst));
+        env.vfs.rename(&src_path, &dst_path)?;
         Ok(CommandOutput::None)
     }
 }
 
+// ---------------------------------------------------------------------------
+// ln
+// ---------------------------------------------------------------------------
+
+struct LnCmd;
+impl Command for LnCmd {
+    fn name(&self) -> &str {
+        "ln"
+    }
+    fn description(&self) -> &str {
+        "Create a symbolic link"
+    }
+    fn usage(&self) -> &str {
+        "ln -s <target> <link>"
+    }
+    fn category(&self) -> &str {
+        "filesystem"
+    }
+    fn execute(&self, args: &[&str], env: &mut Environment<'_>) -> Result<CommandOutput> {
+        let (flags, paths) = split_flags(args);
+        let (["-s"], [target, link]) = (&flags[..], &paths[..]) else {
+            return Err(OasisError::Command(
+                "usage: ln -s <target> <link> (only symbolic links are supported)".to_string(),
+            ));
+        };
+        // The target is stored as given: a relative one is resolved from
+        // the link's directory when the link is followed.
+        let link_path = transfer_target(env, target, &resolve_path(&env.cwd, link));
+        env.vfs.symlink(target, &link_path)?;
+        Ok(CommandOutput::None)
+    }
+}
+
+// ---------------------------------------------------------------------------
+// readlink
+// ---------------------------------------------------------------------------
+
+struct ReadlinkCmd;
+impl Command for ReadlinkCmd {
+    fn name(&self) -> &str {
+        "readlink"
+    }
+    fn description(&self) -> &str {
+        "Print the target of a symbolic link"
+    }
+    fn usage(&self) -> &str {
+        "readlink <link>"
+    }
+    fn category(&self) -> &str {
+        "filesystem"
+    }
+    fn execute(&self, args: &[&str], env: &mut Environment<'_>) -> Result<CommandOutput> {
+        let [link] = args[..] else {
+            return Err(OasisError::Command("usage: readlink <link>".to_string()));
+        };
+        let path = resolve_path(&env.cwd, link);
+        Ok(CommandOutput::Text(env.vfs.readlink(&path)?))
+    }
+}
+
 // ---------------------------------------------------------------------------
 // find
 // ---------------------------------------------------------------------------
@@ -837,6 +1028,7 @@ mod tests {
             network: None,
             tls: None,
             stdin: None,
+            enforce_permissions: false,
         };
         let result = reg.execute(line, &mut env);
         *cwd = env.cwd;
@@ -873,6 +1065,90 @@ mod tests {
         }
     }
 
+    #[test]
+    fn ls_json_lists_file_records() {
+        let (reg, mut vfs) = setup();
+        let mut cwd = "/home".to_string();
+        match exec(&reg, &mut vfs, &mut cwd, "ls --json user").unwrap() {
+            CommandOutput::Json(v) => {
+                let readme = v
+                    .as_array()
+                    .unwrap()
+                    .iter()
+                    .find(|e| e["name"] == "readme.txt")
+                    .unwrap();
+                assert_eq!(readme["is_dir"], false);
+                assert!(readme["size"].as_u64().unwrap() > 0);
+            },
+            _ => panic!("expected json"),
+        }
+        // Redirected JSON is written pretty-printed.
+        exec(&reg, &mut vfs, &mut cwd, "ls /home --json > /home/ls.json").unwrap();
+        let written = String::from_utf8(vfs.read("/home/ls.json").unwrap()).unwrap();
+        assert!(written.contains("\n  {\n    \"is_dir\": true,"));
+        assert!(exec(&reg, &mut vfs, &mut cwd, "pwd --json").is_err());
+        assert!(exec(&reg, &mut vfs, &mut cwd, "history --json").is_err());
+    }
+
+    #[test]
+    fn ls_long_format_columns() {
+        let (reg, mut vfs) = setup();
+        vfs.mkdir("/home/user/docs").unwrap();
+        let stamp = 19782 * 86400 + 13 * 3600 + 5 * 60 + 9;
+        for (path, owner) in [
+            ("/home/user/readme.txt", "root"),
+            ("/home/user/docs", "oasis"),
+        ] {
+            let mut meta = vfs.metadata(path).unwrap();
+            meta.owner = owner.to_string();
+            meta.modified = stamp;
+            vfs.set_metadata(path, &meta).unwrap();
+        }
+        let mut cwd = "/home/user".to_string();
+        match exec(&reg, &mut vfs, &mut cwd, "ls -l").unwrap() {
+            CommandOutput::Text(s) => assert_eq!(
+                s,
+                "drwxr-xr-x oasis  0 2024-02-29 13:05 docs/\n\
+                 -rw-r--r-- root  11 2024-02-29 13:05 readme.txt"
+            ),
+            _ => panic!("expected text"),
+        }
+        match exec(&reg, &mut vfs, &mut cwd, "ls -l --json").unwrap() {
+            CommandOutput::Json(v) => {
+                assert_eq!(v[1]["mode"], "0644");
+                assert_eq!(v[1]["owner"], "root");
+                assert_eq!(v[1]["modified"], stamp);
+            },
+            _ => panic!("expected json"),
+        }
+        assert!(exec(&reg, &mut vfs, &mut cwd, "ls -x").is_err());
+    }
+
+    #[test]
+    fn ln_readlink_and_ls_show_link_targets() {
+        let (reg, mut vfs) = setup();
+        let mut cwd = "/home/user".to_string();
+        exec(&reg, &mut vfs, &mut cwd, "ln -s readme.txt notes").unwrap();
+        match exec(&reg, &mut vfs, &mut cwd, "readlink notes").unwrap() {
+            CommandOutput::Text(s) => assert_eq!(s, "readme.txt"),
+            _ => panic!("expected text"),
+        }
+        match exec(&reg, &mut vfs, &mut cwd, "cat notes").unwrap() {
+            CommandOutput::Text(s) => assert_eq!(s, "Hello OASIS"),
+            _ => panic!("expected text"),
+        }
+        match exec(&reg, &mut vfs, &mut cwd, "ls -l").unwrap() {
+            CommandOutput::Text(s) => assert!(s.contains(" notes -> readme.txt"), "{s}"),
+            _ => panic!("expected text"),
+        }
+        match exec(&reg, &mut vfs, &mut cwd, "ls -l --json").unwrap() {
+            CommandOutput::Json(v) => assert_eq!(v[0]["link"], "readme.txt"),
+            _ => panic!("expected json"),
+        }
+        assert!(exec(&reg, &mut vfs, &mut cwd, "ln readme.txt hard").is_err());
+        assert!(exec(&reg, &mut vfs, &mut cwd, "readlink readme.txt").is_err());
+    }
+
     #[test]
     fn cd_and_pwd() {
         let (reg, mut vfs) = setup();
@@ -914,6 +1190,38 @@ mod tests {
         assert!(exec(&reg, &mut vfs, &mut cwd, "cat").is_err());
     }
 
+    #[test]
+    fn pipeline_redirects_final_output() {
+        let (reg, mut vfs) = setup();
+        let mut cwd = "/home/user".to_string();
+        vfs.write("/home/user/log.txt", b"foo 2\nbar\nfoo 1\nfood 3")
+            .unwrap();
+        let line = "cat log.txt | grep foo | sort > out.txt";
+        assert!(matches!(
+            exec(&reg, &mut vfs, &mut cwd, line).unwrap(),
+            CommandOutput::None
+        ));
+        assert_eq!(
+            vfs.read("/home/user/out.txt").unwrap(),
+            b"foo 1\nfoo 2\nfood 3"
+        );
+        exec(&reg, &mut vfs, &mut cwd, "echo bar | cat >> out.txt").unwrap();
+        assert_eq!(
+            vfs.read("/home/user/out.txt").unwrap(),
+            b"foo 1\nfoo 2\nfood 3\nbar"
+        );
+    }
+
+    #[test]
+    fn pipeline_error_names_the_stage() {
+        let (reg, mut vfs) = setup();
+        let mut cwd = "/".to_string();
+        let line = "cat /home/user/readme.txt | cat /missing | sort > /home/out.txt";
+        let err = exec(&reg, &mut vfs, &mut cwd, line).unwrap_err();
+        assert!(err.to_string().contains("pipeline stage 2 (cat)"), "{err}");
+        assert!(!vfs.exists("/home/out.txt"));
+    }
+
     #[test]
     fn mkdir_creates_dir() {
         let (reg, mut vfs) = setup();
@@ -1022,6 +1330,55 @@ mod tests {
         assert!(exec(&reg, &mut vfs, &mut cwd, "mv").is_err());
     }
 
+    #[test]
+    fn mv_directory_with_children() {
+        let (reg, mut vfs) = setup();
+        vfs.mkdir("/home/user/docs/deep").unwrap();
+        vfs.write("/home/user/docs/deep/a.txt", b"a").unwrap();
+        let mut cwd = "/home".to_string();
+        exec(&reg, &mut vfs, &mut cwd, "mv user/docs /home/archive").unwrap();
+        assert!(!vfs.exists("/home/user/docs"));
+        assert_eq!(vfs.read("/home/archive/deep/a.txt").unwrap(), b"a");
+        // Moving into an existing directory keeps the name.
+        exec(&reg, &mut vfs, &mut cwd, "mv /home/archive user").unwrap();
+        assert!(vfs.exists("/home/user/archive/deep/a.txt"));
+        assert!(exec(&reg, &mut vfs, &mut cwd, "mv user /home/user/archive").is_err());
+    }
+
+    #[test]
+    fn cp_recursive_and_self_copy() {
+        let (reg, mut vfs) = setup();
+        vfs.mkdir("/home/user/docs").unwrap();
+        vfs.write("/home/user/docs/a.txt", b"a").unwrap();
+        let mut cwd = "/home/user".to_string();
+        let err = exec(&reg, &mut vfs, &mut cwd, "cp docs backup").unwrap_err();
+        assert!(err.to_string().contains("use -r"));
+        exec(&reg, &mut vfs, &mut cwd, "cp -r docs backup").unwrap();
+        assert_eq!(vfs.read("/home/user/backup/a.txt").unwrap(), b"a");
+        assert!(vfs.exists("/home/user/docs/a.txt"));
+        assert!(exec(&reg, &mut vfs, &mut cwd, "cp readme.txt readme.txt").is_err());
+        exec(&reg, &mut vfs, &mut cwd, "cp readme.txt docs").unwrap();
+        assert_eq!(
+            vfs.read("/home/user/docs/readme.txt").unwrap(),
+            b"Hello OASIS"
+        );
+    }
+
+    #[test]
+    fn rm_recursive_and_force() {
+        let (reg, mut vfs) = setup();
+        vfs.mkdir("/home/user/docs/deep").unwrap();
+        vfs.write("/home/user/docs/deep/a.txt", b"a").unwrap();
+        let mut cwd = "/home/user".to_string();
+        let err = exec(&reg, &mut vfs, &mut cwd, "rm docs").unwrap_err();
+        assert!(err.to_string().contains("not empty"));
+        exec(&reg, &mut vfs, &mut cwd, "rm -r docs").unwrap();
+        assert!(!vfs.exists("/home/user/docs"));
+        assert!(exec(&reg, &mut vfs, &mut cwd, "rm missing").is_err());
+        exec(&reg, &mut vfs, &mut cwd, "rm -f missing readme.txt").unwrap();
+        assert!(!vfs.exists("/home/user/readme.txt"));
+    }
+
     #[test]
     fn find_by_name() {
         let (reg, mut vfs) = setup();
@@ -1075,6 +1432,7 @@ mod tests {
             network: None,
             tls: None,
             stdin: None,
+            enforce_permissions: false,
         };
         match reg.execute("power", &mut env).unwrap() {
             CommandOutput::Text(s) => assert!(s.contains("NoBattery")),
@@ -1106,6 +1464,7 @@ mod tests {
             network: None,
             tls: None,
             stdin: None,
+            enforce_permissions: false,
         };
         match reg.execute("clock", &mut env).unwrap() {
             CommandOutput::Text(s) => {
@@ -1150,6 +1509,7 @@ mod tests {
             network: None,
             tls: None,
             stdin: None,
+            enforce_permissions: false,
         };
         match reg.execute("usb", &mut env).unwrap() {
             CommandOutput::Text(s) => assert!(s.contains("unsupported")),
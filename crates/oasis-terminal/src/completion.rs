@@ -0,0 +1,358 @@
+//! Tab completion for command names and VFS paths.
+//!
+//! [`Completer`] completes the word at the end of an input line: the
+//! first word of a command (after `|`, `;`, `&&` or `||` too) against the
+//! registered commands, anything else against the VFS. A single match is
+//! filled in; several fill in their common prefix and are listed, and
+//! further presses cycle through them. Words opened with a quote stay
+//! quoted, and matches containing spaces are quoted.
+
+use oasis_vfs::{EntryKind, Vfs};
+
+use crate::CommandRegistry;
+use crate::interpreter::resolve_path;
+
+/// Result of one completion request.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Completion {
+    /// The input line with the word completed.
+    pub line: String,
+    /// Candidates to show when there is more than one (their last path
+    /// component), or empty.
+    pub candidates: Vec<String>,
+}
+
+/// Complete a path argument: entries of its parent directory whose name
+/// starts with the last component of `prefix`. Directories end in `/`.
+/// Hidden entries are only offered when the component starts with `.`.
+pub fn complete_path(vfs: &dyn Vfs, cwd: &str, prefix: &str) -> Vec<String> {
+    let (dir_part, base) = match prefix.rfind('/') {
+        Some(i) => prefix.split_at(i + 1),
+        None => ("", prefix),
+    };
+    let dir = if dir_part.is_empty() {
+        cwd.to_string()
+    } else {
+        resolve_path(cwd, dir_part)
+    };
+    let Ok(entries) = vfs.readdir(&dir) else {
+        return Vec::new();
+    };
+    let mut matches: Vec<String> = entries
+        .into_iter()
+        .filter(|e| e.name.starts_with(base) && (base.starts_with('.') || !e.name.starts_with('.')))
+        .map(|e| {
+            let slash = if e.kind == EntryKind::Directory {
+                "/"
+            } else {
+                ""
+            };
+            format!("{dir_part}{}{slash}", e.name)
+        })
+        .collect();
+    matches.sort();
+    matches
+}
+
+/// The word being completed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Word {
+    /// Byte offset in the line where the word starts.
+    start: usize,
+    /// Quote character the word was opened with.
+    quote: Option<char>,
+    /// The word with quotes and escapes removed.
+    text: String,
+    /// Whether the word is in command position.
+    is_command: bool,
+}
+
+/// Find the word at the end of `line`, following the interpreter's
+/// quoting rules.
+fn last_word(line: &str) -> Word {
+    let mut start = 0;
+    let mut quote: Option<char> = None;
+    let mut open: Option<char> = None;
+    let mut chars = line.char_indices().peekable();
+    while let Some((i, c)) = chars.next() {
+        match (open, c) {
+            (Some(q), c) if c == q => open = None,
+            (Some('"') | None, '\\') => {
+                chars.next();
+            },
+            (Some(_), _) => {},
+            (None, '"' | '\'') => {
+                open = Some(c);
+                if i == start {
+                    quote = Some(c);
+                }
+            },
+            (None, c) if c.is_whitespace() || matches!(c, '|' | ';' | '&') => {
+                start = i + c.len_utf8();
+                quote = None;
+            },
+            (None, _) => {},
+        }
+    }
+
+    let mut text = String::new();
+    let mut open = None;
+    let mut chars = line[start..].chars();
+    while let Some(c) = chars.next() {
+        match (open, c) {
+            (Some(q), c) if c == q => open = None,
+            (None, '"' | '\'') => open = Some(c),
+            (Some('"') | None, '\\') => text.extend(chars.next()),
+            _ => text.push(c),
+        }
+    }
+
+    let before = line[..start].trim_end();
+    let is_command = before.is_empty() || before.ends_with(['|', ';', '&']);
+    Word {
+        start,
+        quote,
+        text,
+        is_command,
+    }
+}
+
+/// Write `candidate` back as a shell word. `finished` closes the quote
+/// and adds a space, for a match nothing more can be added to.
+fn render_word(candidate: &str, quote: Option<char>, finished: bool) -> String {
+    let quote = quote.or_else(|| candidate.contains(char::is_whitespace).then_some('"'));
+    let mut out = String::new();
+    if let Some(q) = quote {
+        out.push(q);
+    }
+    out.push_str(candidate);
+    if finished {
+        if let Some(q) = quote {
+            out.push(q);
+        }
+        out.push(' ');
+    }
+    out
+}
+
+/// Longest prefix shared by every string in `items`.
+fn common_prefix(items: &[String]) -> &str {
+    let Some(first) = items.first() else {
+        return "";
+    };
+    let mut len = first.len();
+    for item in &items[1..] {
+        len = first
+            .char_indices()
+            .zip(item.chars())
+            .take_while(|((_, a), b)| a == b)
+            .map(|((i, a), _)| i + a.len_utf8())
+            .last()
+            .unwrap_or(0)
+            .min(len);
+    }
+    &first[..len]
+}
+
+/// Name shown for a candidate in the list: its last path component.
+fn display_name(candidate: &str) -> String {
+    let trimmed = candidate.trim_end_matches('/');
+    let name = trimmed.rsplit('/').next().unwrap_or(trimmed);
+    if candidate.ends_with('/') {
+        format!("{name}/")
+    } else {
+        name.to_string()
+    }
+}
+
+/// Candidates being cycled through by repeated presses.
+#[derive(Debug, Clone)]
+struct Cycle {
+    /// The line as this completer last left it.
+    line: String,
+    /// Line text before the word.
+    head: String,
+    quote: Option<char>,
+    candidates: Vec<String>,
+    /// Candidate shown last, or `None` before the first cycle step.
+    index: Option<usize>,
+}
+
+/// Tab completion state for one input line.
+#[derive(Debug, Clone, Default)]
+pub struct Completer {
+    cycle: Option<Cycle>,
+}
+
+impl Completer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Forget the candidates being cycled through. Call when the line is
+    /// edited some other way.
+    pub fn reset(&mut self) {
+        self.cycle = None;
+    }
+
+    /// The candidate list of the completion in progress, if any.
+    pub fn candidates(&self) -> Vec<String> {
+        self.cycle
+            .as_ref()
+            .map(|c| c.candidates.iter().map(|s| display_name(s)).collect())
+            .unwrap_or_default()
+    }
+
+    /// Complete the word at the end of `line`. Pressing again on the line
+    /// this returned steps through the candidates.
+    pub fn complete(
+        &mut self,
+        line: &str,
+        reg: &CommandRegistry,
+        vfs: &dyn Vfs,
+        cwd: &str,
+    ) -> Completion {
+        if let Some(cycle) = self.cycle.as_mut().filter(|c| c.line == line) {
+            let index = cycle.index.map_or(0, |i| (i + 1) % cycle.candidates.len());
+            cycle.index = Some(index);
+            let candidate = &cycle.candidates[index];
+            cycle.line = format!(
+                "{}{}",
+                cycle.head,
+                render_word(candidate, cycle.quote, false)
+            );
+            return Completion {
+                line: cycle.line.clone(),
+                candidates: self.candidates(),
+            };
+        }
+        self.cycle = None;
+
+        let word = last_word(line);
+        let head = &line[..word.start];
+        let candidates = if word.is_command && !word.text.contains('/') {
+            reg.complete_command(&word.text)
+        } else {
+            complete_path(vfs, cwd, &word.text)
+        };
+        match candidates.as_slice() {
+            [] => Completion {
+                line: line.to_string(),
+                candidates: Vec::new(),
+            },
+            [only] => Completion {
+                line: format!(
+                    "{head}{}",
+                    render_word(only, word.quote, !only.ends_with('/'))
+                ),
+                candidates: Vec::new(),
+            },
+            _ => {
+                let prefix = common_prefix(&candidates);
+                let new_line = if prefix.len() > word.text.len() {
+                    format!("{head}{}", render_word(prefix, word.quote, false))
+                } else {
+                    line.to_string()
+                };
+                self.cycle = Some(Cycle {
+                    line: new_line.clone(),
+                    head: head.to_string(),
+                    quote: word.quote,
+                    candidates,
+                    index: None,
+                });
+                Completion {
+                    line: new_line,
+                    candidates: self.candidates(),
+                }
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use oasis_vfs::MemoryVfs;
+
+    fn setup() -> (CommandRegistry, MemoryVfs) {
+        let mut reg = CommandRegistry::new();
+        crate::register_builtins(&mut reg);
+        let mut vfs = MemoryVfs::new();
+        for dir in ["/home", "/home/docs", "/home/my files", "/home/.config"] {
+            vfs.mkdir(dir).unwrap();
+        }
+        vfs.write("/home/notes.txt", b"").unwrap();
+        vfs.write("/home/my files/report one.txt", b"").unwrap();
+        (reg, vfs)
+    }
+
+    fn complete(line: &str) -> Completion {
+        let (reg, vfs) = setup();
+        Completer::new().complete(line, &reg, &vfs, "/home")
+    }
+
+    #[test]
+    fn completes_command_names() {
+        let (reg, _) = setup();
+        assert_eq!(reg.complete_command("hist"), ["history"]);
+        assert!(reg.complete_command("ca").contains(&"cat".to_string()));
+        assert_eq!(complete("hist").line, "history ");
+        assert_eq!(complete("ls | hist").line, "ls | history ");
+        assert_eq!(complete("zzz").line, "zzz");
+    }
+
+    #[test]
+    fn completes_paths_with_directory_slash() {
+        let (_, vfs) = setup();
+        assert_eq!(complete_path(&vfs, "/home", "d"), ["docs/"]);
+        assert_eq!(complete_path(&vfs, "/", "/home/n"), ["/home/notes.txt"]);
+        assert_eq!(complete_path(&vfs, "/home", "."), [".config/"]);
+        assert!(complete_path(&vfs, "/home", "missing/").is_empty());
+        assert_eq!(complete("cat no").line, "cat notes.txt ");
+        assert_eq!(complete("cd d").line, "cd docs/");
+    }
+
+    #[test]
+    fn quotes_paths_with_spaces() {
+        assert_eq!(complete("cd my").line, "cd \"my files/");
+        assert_eq!(
+            complete("cat \"my files/rep").line,
+            "cat \"my files/report one.txt\" "
+        );
+        assert_eq!(
+            complete("cat 'my files/rep").line,
+            "cat 'my files/report one.txt' "
+        );
+        assert_eq!(
+            complete("cat my\\ files/rep").line,
+            "cat \"my files/report one.txt\" "
+        );
+    }
+
+    #[test]
+    fn several_matches_list_then_cycle() {
+        let (reg, mut vfs) = setup();
+        vfs.write("/home/notes.md", b"").unwrap();
+        let mut completer = Completer::new();
+        let first = completer.complete("cat n", &reg, &vfs, "/home");
+        assert_eq!(first.line, "cat notes.");
+        assert_eq!(first.candidates, ["notes.md", "notes.txt"]);
+
+        let mut line = first.line;
+        let mut lines = Vec::new();
+        for _ in 0..3 {
+            line = completer.complete(&line, &reg, &vfs, "/home").line;
+            lines.push(line.clone());
+        }
+        assert_eq!(lines, ["cat notes.md", "cat notes.txt", "cat notes.md"]);
+
+        // An edited line starts over.
+        completer.reset();
+        assert!(completer.candidates().is_empty());
+        assert_eq!(
+            completer.complete("cat notes.t", &reg, &vfs, "/home").line,
+            "cat notes.txt "
+        );
+    }
+}
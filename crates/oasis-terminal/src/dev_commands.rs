@@ -582,7 +582,7 @@ impl Command for TestCmd {
     }
     fn execute(&self, args: &[&str], env: &mut Environment<'_>) -> Result<CommandOutput> {
         if args.is_empty() {
-            return Ok(CommandOutput::Text("false".to_string()));
+            return Ok(test_output(false));
         }
         let result = match args[0] {
             "-f" => {
@@ -643,9 +643,20 @@ impl Command for TestCmd {
                 }
             },
         };
-        Ok(CommandOutput::Text(
-            if result { "true" } else { "false" }.to_string(),
-        ))
+        Ok(test_output(result))
+    }
+}
+
+/// Print the result of `test`, exiting with status 1 when it is false.
+fn test_output(result: bool) -> CommandOutput {
+    let text = CommandOutput::Text(if result { "true" } else { "false" }.to_string());
+    if result {
+        text
+    } else {
+        CommandOutput::Status {
+            code: 1,
+            output: Box::new(text),
+        }
     }
 }
 
@@ -718,6 +729,7 @@ mod tests {
             network: None,
             tls: None,
             stdin: None,
+            enforce_permissions: false,
         };
         reg.execute(line, &mut env)
     }
@@ -76,13 +76,13 @@ const LESSONS: &[(&str, &str)] = &[
     (
         "Files",
         "FILE OPERATIONS\n\n\
-         cat <file>         - Display file contents\n\
+         cat [file]         - Display file contents\n\
          write <file> text  - Write text to a file\n\
          append <file> text - Append text to a file\n\
          mkdir <dir>        - Create a directory\n\
-         cp <src> <dst>     - Copy a file\n\
-         mv <src> <dst>     - Move/rename a file\n\
-         rm <path>          - Delete a file or directory\n\
+         cp [-r] <src> <dst> - Copy a file or directory\n\
+         mv <src> <dst>     - Move/rename a file or directory\n\
+         rm [-r] [-f] <path> - Delete files or directories\n\
          stat <path>        - Show file details\n\n\
          Try it: type 'write /tmp/hello.txt Hello World'",
     ),
@@ -101,10 +101,12 @@ const LESSONS: &[(&str, &str)] = &[
         "Variables & Aliases",
         "VARIABLES & ALIASES\n\n\
          set VAR=value    - Set a variable\n\
-         echo $VAR        - Use a variable\n\
+         export A=1 B=2   - Set several variables\n\
+         echo $VAR        - Use a variable (${VAR} also works)\n\
+         echo '$VAR' \\$VAR - Print a literal $VAR\n\
          unset VAR        - Remove a variable\n\
          env              - List all variables\n\
-         alias ll=ls      - Create an alias\n\
+         alias ll='ls /'  - Create an alias (saved to the profile)\n\
          unalias ll       - Remove an alias\n\n\
          Built-in variables: $CWD, $USER, $SHELL, $HOME\n\n\
          Try it: type 'set NAME=OASIS && echo Hello $NAME'",
@@ -124,7 +126,8 @@ const LESSONS: &[(&str, &str)] = &[
                             do\n\
                               commands\n\
                             done\n\n\
-         Conditions use the 'test' command:\n\
+         A condition holds when its command exits with\n\
+         status 0, as 'test' does when it prints true:\n\
          test -f /path   (file exists)\n\
          test -d /path   (directory exists)\n\
          test a = b      (string equality)",
@@ -135,7 +138,7 @@ const LESSONS: &[(&str, &str)] = &[
          help             - List all commands by category\n\
          help <cmd>       - Show detailed help for a command\n\
          which <cmd>      - Check if a command exists\n\
-         history          - Show command history\n\
+         history [-c]     - Show (or clear) command history\n\
          !!               - Repeat last command\n\
          !n               - Repeat command number n\n\
          cal              - Show a calendar\n\
@@ -306,10 +309,14 @@ pub fn populate_man_pages(vfs: &mut dyn oasis_vfs::Vfs) {
         (
             "ls",
             "NAME\n    ls - list directory contents\n\n\
-             SYNOPSIS\n    ls [path]\n\n\
+             SYNOPSIS\n    ls [-l] [--json] [path]\n\n\
              DESCRIPTION\n    List files and directories at the given path.\n\
-             If no path is given, lists the current directory.\n\n\
-             EXAMPLES\n    ls /home\n    ls\n",
+             If no path is given, lists the current directory.\n\
+             With -l, shows mode, owner, size and modification time,\n\
+             and symbolic links as 'name -> target'.\n\
+             With --json, prints an array of {name, size, is_dir} records\n\
+             (plus mode, owner and modified with -l, and link for links).\n\n\
+             EXAMPLES\n    ls /home\n    ls -l\n    ls --json /home > /tmp/home.json\n",
         ),
         (
             "cd",
@@ -322,8 +329,9 @@ pub fn populate_man_pages(vfs: &mut dyn oasis_vfs::Vfs) {
         (
             "cat",
             "NAME\n    cat - display file contents\n\n\
-             SYNOPSIS\n    cat <file>\n\n\
-             DESCRIPTION\n    Read and display the contents of a file.\n\n\
+             SYNOPSIS\n    cat [file]\n\n\
+             DESCRIPTION\n    Read and display the contents of a file. Without a\n    \
+             file, pass piped input through.\n\n\
              EXAMPLES\n    cat /etc/motd\n    cat /home/notes.txt\n",
         ),
         (
@@ -337,12 +345,13 @@ pub fn populate_man_pages(vfs: &mut dyn oasis_vfs::Vfs) {
         (
             "grep",
             "NAME\n    grep - search text patterns\n\n\
-             SYNOPSIS\n    grep [options] <pattern> <file>\n\n\
+             SYNOPSIS\n    grep [options] <pattern> <file>\n    grep -r [options] <pattern> [dir]\n\n\
              DESCRIPTION\n    Search for lines matching a pattern.\n\n\
              OPTIONS\n    -i  Case insensitive\n    -n  Show line numbers\n\
-             -v  Invert match\n    -c  Count matches\n\n\
+             -v  Invert match\n    -c  Count matches\n\
+             -r  Search every text file under a directory\n\n\
              EXAMPLES\n    grep error /var/log/audit.log\n\
-             cat file.txt | grep -i hello\n",
+             cat file.txt | grep -i hello\n    grep -r -n TODO /home\n",
         ),
         (
             "help",
@@ -355,13 +364,40 @@ pub fn populate_man_pages(vfs: &mut dyn oasis_vfs::Vfs) {
         (
             "run",
             "NAME\n    run - execute a script file\n\n\
-             SYNOPSIS\n    run <path>\n\n\
-             DESCRIPTION\n    Execute commands from a script file.\n\
-             Lines starting with '#' are comments.\n\
-             Supports if/then/else/fi, while/do/done,\n\
-             and for/in/do/done control flow.\n\n\
+             SYNOPSIS\n    run <path> [args...]\n\n\
+             DESCRIPTION\n    Same as sh; see 'man sh'.\n\n\
              EXAMPLES\n    run /home/setup.sh\n",
         ),
+        (
+            "sh",
+            "NAME\n    sh - execute a script file\n\n\
+             SYNOPSIS\n    sh <path> [args...]\n\n\
+             DESCRIPTION\n    Execute commands from a script file.\n\
+             Lines starting with '#' are comments; ';' separates\n\
+             statements. Supports if/then/elif/else/fi,\n\
+             while/do/done and for/in/do/done. Arguments are\n\
+             $1..$9, with $# and $@; $0 is the script path.\n\
+             'exit [n]' ends the script with status n; otherwise\n\
+             the last command's status is used, and a non-zero\n\
+             status makes sh fail. /etc/rc runs at startup.\n\n\
+             EXAMPLES\n    sh /home/setup.sh\n\
+             sh /home/greet.sh world\n",
+        ),
+        (
+            "schedule",
+            "NAME\n    schedule - run commands periodically\n\n\
+             SYNOPSIS\n    schedule add <interval> <command...>\n\
+             schedule [list]\n    schedule remove <id>\n    schedule log\n\n\
+             DESCRIPTION\n    Run a command line every interval: seconds\n\
+             (90 or 90s), 5m, 2h, @minute, @hour or @day. Quote\n\
+             commands containing pipes. Output goes to the log\n\
+             shown by 'schedule log'. The schedule is saved to\n\
+             /home/.schedule. Runs missed while the system was\n\
+             asleep happen once, not once per interval.\n\n\
+             EXAMPLES\n    schedule add 5m 'echo tick >> /tmp/ticks'\n\
+             schedule add 30s 'date >> /tmp/clock.log'\n\
+             schedule remove 1\n",
+        ),
         (
             "tutorial",
             "NAME\n    tutorial - interactive terminal tutorial\n\n\
@@ -394,9 +430,11 @@ pub fn populate_profile(vfs: &mut dyn oasis_vfs::Vfs) {
     let profile = "\
 # OASIS OS shell profile
 # This file is executed on terminal startup.
-set USER=oasis
-set HOME=/home
-set SHELL=oasis-sh
+export USER=oasis
+export HOME=/home
+export SHELL=oasis-sh
+alias l='ls'
+alias ..='cd ..'
 ";
     let _ = vfs.write("/home/.profile", profile.as_bytes());
 }
@@ -417,6 +455,7 @@ mod tests {
             network: None,
             tls: None,
             stdin: None,
+            enforce_permissions: false,
         };
         reg.execute(line, &mut env)
     }
@@ -552,5 +591,6 @@ mod tests {
         let data = vfs.read("/home/.profile").unwrap();
         let text = String::from_utf8_lossy(&data);
         assert!(text.contains("USER=oasis"));
+        assert!(text.contains("alias l='ls'"));
     }
 }
@@ -1,9 +1,15 @@
-//! File and archive utility commands: write, append, tree, du, stat, xxd, checksum.
+//! File and archive utility commands: write, append, tree, du, stat, xxd, checksum,
+//! watchfs.
 
+use std::time::{Duration, Instant};
+
+use oasis_platform::SystemTime;
 use oasis_types::error::{OasisError, Result};
-use oasis_vfs::EntryKind;
+use oasis_vfs::{ChangeKind, Changes, EntryKind, archive, is_within};
 
 use crate::interpreter::{Command, CommandOutput, Environment, resolve_path};
+use crate::jobs::{CancelToken, Job};
+use crate::security_commands::check_writable;
 
 // ---------------------------------------------------------------------------
 // write
@@ -31,6 +37,7 @@ impl Command for WriteCmd {
         }
         let path = resolve_path(&env.cwd, args[0]);
         let text = args[1..].join(" ");
+        check_writable(env, &path)?;
         env.vfs.write(&path, text.as_bytes())?;
         Ok(CommandOutput::Text(format!(
             "Wrote {} bytes to {path}",
@@ -65,6 +72,7 @@ impl Command for AppendCmd {
         }
         let path = resolve_path(&env.cwd, args[0]);
         let text = args[1..].join(" ");
+        check_writable(env, &path)?;
         let mut data = if env.vfs.exists(&path) {
             env.vfs.read(&path)?
         } else {
@@ -245,6 +253,32 @@ fn format_size(bytes: u64) -> String {
     }
 }
 
+/// Mode bits as `ls -l` shows them, e.g. `drwxr-xr-x`.
+pub(crate) fn format_mode(kind: EntryKind, mode: u32) -> String {
+    let mut s = String::with_capacity(10);
+    s.push(match kind {
+        EntryKind::File => '-',
+        EntryKind::Directory => 'd',
+        EntryKind::Symlink => 'l',
+    });
+    for shift in [6, 3, 0] {
+        let bits = mode >> shift;
+        s.push(if bits & 4 != 0 { 'r' } else { '-' });
+        s.push(if bits & 2 != 0 { 'w' } else { '-' });
+        s.push(if bits & 1 != 0 { 'x' } else { '-' });
+    }
+    s
+}
+
+/// A timestamp as `YYYY-MM-DD HH:MM:SS` (UTC), or `-` when unknown.
+pub(crate) fn format_timestamp(secs: u64) -> String {
+    if secs == 0 {
+        "-".to_string()
+    } else {
+        SystemTime::from_unix_secs(secs).to_string()
+    }
+}
+
 // ---------------------------------------------------------------------------
 // stat
 // ---------------------------------------------------------------------------
@@ -258,7 +292,7 @@ impl Command for StatCmd {
         "Show file metadata"
     }
     fn usage(&self) -> &str {
-        "stat <path>"
+        "stat [--json] <path>"
     }
     fn category(&self) -> &str {
         "filesystem"
@@ -268,21 +302,52 @@ impl Command for StatCmd {
             return Err(OasisError::Command("usage: stat <path>".to_string()));
         }
         let path = resolve_path(&env.cwd, args[0]);
-        let meta = env.vfs.stat(&path)?;
+        let meta = env.vfs.metadata(&path)?;
         let kind = match meta.kind {
             EntryKind::File => "regular file",
             EntryKind::Directory => "directory",
+            EntryKind::Symlink => "symbolic link",
         };
         let mut lines = Vec::new();
-        lines.push(format!("  File: {path}"));
-        lines.push(format!("  Type: {kind}"));
+        // Links are followed; the file line names the target too.
+        match env.vfs.readlink(&path) {
+            Ok(target) => lines.push(format!("    File: {path} -> {target}")),
+            Err(_) => lines.push(format!("    File: {path}")),
+        }
+        lines.push(format!("    Type: {kind}"));
         lines.push(format!(
-            "  Size: {} ({})",
+            "    Size: {} ({})",
             meta.size,
             format_size(meta.size)
         ));
+        lines.push(format!(
+            "    Mode: {:04o} ({})",
+            meta.mode,
+            format_mode(meta.kind, meta.mode)
+        ));
+        lines.push(format!("   Owner: {}", meta.owner));
+        lines.push(format!(" Created: {}", format_timestamp(meta.created)));
+        lines.push(format!("Modified: {}", format_timestamp(meta.modified)));
         Ok(CommandOutput::Text(lines.join("\n")))
     }
+    fn execute_json(&self, args: &[&str], env: &mut Environment<'_>) -> Result<serde_json::Value> {
+        if args.is_empty() {
+            return Err(OasisError::Command("usage: stat <path>".to_string()));
+        }
+        let path = resolve_path(&env.cwd, args[0]);
+        let meta = env.vfs.metadata(&path)?;
+        let name = path.rsplit('/').next().unwrap_or_default();
+        Ok(serde_json::json!({
+            "path": path,
+            "name": name,
+            "size": meta.size,
+            "is_dir": meta.kind == EntryKind::Directory,
+            "mode": format!("{:04o}", meta.mode),
+            "owner": meta.owner,
+            "created": meta.created,
+            "modified": meta.modified,
+        }))
+    }
 }
 
 // ---------------------------------------------------------------------------
@@ -383,6 +448,198 @@ impl Command for ChecksumCmd {
     }
 }
 
+// ---------------------------------------------------------------------------
+// tar
+// ---------------------------------------------------------------------------
+
+struct TarCmd;
+impl Command for TarCmd {
+    fn name(&self) -> &str {
+        "tar"
+    }
+    fn description(&self) -> &str {
+        "Pack a directory into an archive, or unpack one"
+    }
+    fn usage(&self) -> &str {
+        "tar -c <archive> <dir> | tar -x <archive> [dest] | tar -t <archive>"
+    }
+    fn category(&self) -> &str {
+        "filesystem"
+    }
+    fn execute(&self, args: &[&str], env: &mut Environment<'_>) -> Result<CommandOutput> {
+        match args {
+            ["-c", archive, dir] => {
+                let archive = resolve_path(&env.cwd, archive);
+                let dir = resolve_path(&env.cwd, dir);
+                let bytes = archive::pack(&*env.vfs, &dir)?;
+                check_writable(env, &archive)?;
+                env.vfs.write(&archive, &bytes)?;
+                let count = archive::list(&bytes)?.len();
+                Ok(CommandOutput::Text(format!(
+                    "Packed {count} entries from {dir} into {archive} ({})",
+                    format_size(bytes.len() as u64)
+                )))
+            },
+            ["-x", archive, rest @ ..] if rest.len() <= 1 => {
+                let archive = resolve_path(&env.cwd, archive);
+                let dest = match rest {
+                    [dest] => resolve_path(&env.cwd, dest),
+                    _ => env.cwd.clone(),
+                };
+                let bytes = env.vfs.read(&archive)?;
+                let count = archive::unpack(env.vfs, &bytes, &dest)?;
+                Ok(CommandOutput::Text(format!(
+                    "Unpacked {count} entries into {dest}"
+                )))
+            },
+            ["-t", archive] => {
+                let bytes = env.vfs.read(&resolve_path(&env.cwd, archive))?;
+                let lines: Vec<String> = archive::list(&bytes)?
+                    .iter()
+                    .map(|entry| {
+                        let slash = if entry.kind == EntryKind::Directory {
+                            "/"
+                        } else {
+                            ""
+                        };
+                        format!(
+                            "{} {:>8} {}{slash}",
+                            format_mode(entry.kind, entry.mode),
+                            entry.size,
+                            entry.path
+                        )
+                    })
+                    .collect();
+                Ok(CommandOutput::Text(lines.join("\n")))
+            },
+            _ => Err(OasisError::Command(
+                "usage: tar -c <archive> <dir> | tar -x <archive> [dest] | tar -t <archive>"
+                    .to_string(),
+            )),
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// watchfs
+// ---------------------------------------------------------------------------
+
+/// How often a background `watchfs` polls the VFS.
+const WATCH_INTERVAL: Duration = Duration::from_millis(500);
+
+struct WatchfsCmd;
+impl Command for WatchfsCmd {
+    fn name(&self) -> &str {
+        "watchfs"
+    }
+    fn description(&self) -> &str {
+        "Print changes to a path"
+    }
+    fn usage(&self) -> &str {
+        "watchfs <path> [generation]"
+    }
+    fn category(&self) -> &str {
+        "filesystem"
+    }
+    /// In the foreground, list the journaled changes since `generation`
+    /// (default: all of them) and the current generation.
+    fn execute(&self, args: &[&str], env: &mut Environment<'_>) -> Result<CommandOutput> {
+        let (path, since) = match args {
+            [path] => (*path, 0),
+            [path, generation] => (
+                *path,
+                generation
+                    .parse()
+                    .map_err(|_| OasisError::Command("invalid generation".to_string()))?,
+            ),
+            _ => {
+                return Err(OasisError::Command(
+                    "usage: watchfs <path> [generation]".to_string(),
+                ));
+            },
+        };
+        let path = resolve_path(&env.cwd, path);
+        let changes = env.vfs.changes_since(since);
+        let mut lines = change_lines(&path, &changes);
+        lines.push(format!("generation {}", changes.generation));
+        Ok(CommandOutput::Text(lines.join("\n")))
+    }
+
+    /// In the background (`watchfs <path> &`) changes are printed as they
+    /// happen, until the job is killed or `fg` prints the rest and ends it.
+    fn spawn(
+        &self,
+        args: &[&str],
+        env: &mut Environment<'_>,
+        _cancel: CancelToken,
+    ) -> Result<Box<dyn Job>> {
+        let [path] = args else {
+            return Err(OasisError::Command("usage: watchfs <path> &".to_string()));
+        };
+        Ok(Box::new(WatchJob {
+            path: resolve_path(&env.cwd, path),
+            generation: env.vfs.generation(),
+            interval: WATCH_INTERVAL,
+            last_poll: Instant::now(),
+        }))
+    }
+}
+
+/// Background `watchfs`: polls the change journal every `interval`.
+struct WatchJob {
+    path: String,
+    generation: u64,
+    interval: Duration,
+    last_poll: Instant,
+}
+
+impl Job for WatchJob {
+    fn step(&mut self, env: &mut Environment<'_>, out: &mut Vec<String>) -> Result<bool> {
+        if self.last_poll.elapsed() >= self.interval {
+            self.poll(env, out);
+        }
+        Ok(false)
+    }
+
+    /// A watch would otherwise only end when killed, which a waiting `fg`
+    /// leaves no chance for: print the changes so far and stop.
+    fn finish(&mut self, env: &mut Environment<'_>, out: &mut Vec<String>) -> Result<()> {
+        self.poll(env, out);
+        Ok(())
+    }
+}
+
+impl WatchJob {
+    /// Print the changes since the last poll.
+    fn poll(&mut self, env: &mut Environment<'_>, out: &mut Vec<String>) {
+        self.last_poll = Instant::now();
+        let changes = env.vfs.changes_since(self.generation);
+        self.generation = changes.generation;
+        out.extend(change_lines(&self.path, &changes));
+    }
+}
+
+/// One line per change affecting `path`: to it, below it, or to a
+/// directory above it.
+fn change_lines(path: &str, changes: &Changes) -> Vec<String> {
+    let mut lines = Vec::new();
+    if changes.truncated {
+        lines.push("journal truncated: some changes were missed".to_string());
+    }
+    for (changed, kind) in &changes.changes {
+        if !is_within(changed, path) && !is_within(path, changed) {
+            continue;
+        }
+        let verb = match kind {
+            ChangeKind::Created => "created",
+            ChangeKind::Modified => "modified",
+            ChangeKind::Removed => "removed",
+        };
+        lines.push(format!("{verb} {changed}"));
+    }
+    lines
+}
+
 /// Register file utility commands.
 pub fn register_file_commands(reg: &mut crate::CommandRegistry) {
     reg.register(Box::new(WriteCmd));
@@ -392,6 +649,8 @@ pub fn register_file_commands(reg: &mut crate::CommandRegistry) {
     reg.register(Box::new(StatCmd));
     reg.register(Box::new(XxdCmd));
     reg.register(Box::new(ChecksumCmd));
+    reg.register(Box::new(WatchfsCmd));
+    reg.register(Box::new(TarCmd));
 }
 
 #[cfg(test)]
@@ -418,6 +677,7 @@ mod tests {
             network: None,
             tls: None,
             stdin: None,
+            enforce_permissions: false,
         };
         reg.execute(line, &mut env)
     }
@@ -441,6 +701,20 @@ mod tests {
         assert!(text.contains("line2"));
     }
 
+    #[test]
+    fn append_updates_modified_time() {
+        let (reg, mut vfs) = setup();
+        vfs.write("/tmp/log.txt", b"line1").unwrap();
+        let mut meta = vfs.metadata("/tmp/log.txt").unwrap();
+        meta.created = 1;
+        meta.modified = 1;
+        vfs.set_metadata("/tmp/log.txt", &meta).unwrap();
+        exec(&reg, &mut vfs, "append /tmp/log.txt line2").unwrap();
+        let meta = vfs.metadata("/tmp/log.txt").unwrap();
+        assert_eq!(meta.created, 1);
+        assert!(meta.modified > 1);
+    }
+
     #[test]
     fn tree_basic() {
         let (reg, mut vfs) = setup();
@@ -482,6 +756,33 @@ mod tests {
         }
     }
 
+    #[test]
+    fn chmod_and_chown_show_in_stat() {
+        let (mut reg, mut vfs) = setup();
+        crate::register_security_commands(&mut reg);
+        vfs.write("/tmp/x.txt", b"hello").unwrap();
+        exec(&reg, &mut vfs, "chmod 600 /tmp/x.txt").unwrap();
+        exec(&reg, &mut vfs, "chmod u+x /tmp/x.txt").unwrap();
+        exec(&reg, &mut vfs, "chown root /tmp/x.txt").unwrap();
+        let mut meta = vfs.metadata("/tmp/x.txt").unwrap();
+        meta.created = 0;
+        meta.modified = 19782 * 86400 + 13 * 3600 + 5 * 60 + 9;
+        vfs.set_metadata("/tmp/x.txt", &meta).unwrap();
+        match exec(&reg, &mut vfs, "stat /tmp/x.txt").unwrap() {
+            CommandOutput::Text(s) => assert_eq!(
+                s,
+                "    File: /tmp/x.txt\n\
+                 \x20   Type: regular file\n\
+                 \x20   Size: 5 (5B)\n\
+                 \x20   Mode: 0700 (-rwx------)\n\
+                 \x20  Owner: root\n\
+                 \x20Created: -\n\
+                 Modified: 2024-02-29 13:05:09"
+            ),
+            _ => panic!("expected text"),
+        }
+    }
+
     #[test]
     fn stat_dir() {
         let (reg, mut vfs) = setup();
@@ -491,6 +792,32 @@ mod tests {
         }
     }
 
+    #[test]
+    fn stat_json() {
+        let (reg, mut vfs) = setup();
+        vfs.write("/tmp/x.txt", b"hello").unwrap();
+        let mut meta = vfs.metadata("/tmp/x.txt").unwrap();
+        meta.created = 100;
+        meta.modified = 200;
+        vfs.set_metadata("/tmp/x.txt", &meta).unwrap();
+        match exec(&reg, &mut vfs, "stat --json /tmp/x.txt").unwrap() {
+            CommandOutput::Json(v) => assert_eq!(
+                v,
+                serde_json::json!({
+                    "path": "/tmp/x.txt",
+                    "name": "x.txt",
+                    "size": 5,
+                    "is_dir": false,
+                    "mode": "0644",
+                    "owner": "oasis",
+                    "created": 100,
+                    "modified": 200,
+                })
+            ),
+            _ => panic!("expected json"),
+        }
+    }
+
     #[test]
     fn xxd_hex_dump() {
         let (reg, mut vfs) = setup();
@@ -518,4 +845,94 @@ mod tests {
             _ => panic!("expected text"),
         }
     }
+
+    #[test]
+    fn tar_packs_lists_and_unpacks() {
+        let (reg, mut vfs) = setup();
+        vfs.mkdir("/sites/home").unwrap();
+        vfs.mkdir("/sites/empty").unwrap();
+        vfs.write("/sites/home/index.html", b"<p>hi</p>").unwrap();
+        match exec(&reg, &mut vfs, "tar -c /tmp/sites.oar /sites").unwrap() {
+            CommandOutput::Text(s) => assert!(s.starts_with("Packed 3 entries from /sites")),
+            _ => panic!("expected text"),
+        }
+        match exec(&reg, &mut vfs, "tar -t /tmp/sites.oar").unwrap() {
+            CommandOutput::Text(s) => assert_eq!(
+                s,
+                "drwxr-xr-x        0 empty/\n\
+                 drwxr-xr-x        0 home/\n\
+                 -rw-r--r--        9 home/index.html"
+            ),
+            _ => panic!("expected text"),
+        }
+        exec(&reg, &mut vfs, "tar -x /tmp/sites.oar /copy").unwrap();
+        assert_eq!(vfs.read("/copy/home/index.html").unwrap(), b"<p>hi</p>");
+        assert!(vfs.readdir("/copy/empty").unwrap().is_empty());
+        assert!(exec(&reg, &mut vfs, "tar -x /tmp/missing.oar").is_err());
+        assert!(exec(&reg, &mut vfs, "tar /tmp/sites.oar").is_err());
+    }
+
+    #[test]
+    fn watchfs_lists_changes_under_path() {
+        let (reg, mut vfs) = setup();
+        vfs.write("/tmp/a.txt", b"a").unwrap();
+        vfs.write("/elsewhere.txt", b"b").unwrap();
+        match exec(&reg, &mut vfs, "watchfs /tmp").unwrap() {
+            CommandOutput::Text(s) => {
+                assert_eq!(s, "created /tmp\ncreated /tmp/a.txt\ngeneration 3");
+            },
+            _ => panic!("expected text"),
+        }
+        match exec(&reg, &mut vfs, "watchfs /tmp/a.txt 2").unwrap() {
+            CommandOutput::Text(s) => assert_eq!(s, "generation 3"),
+            _ => panic!("expected text"),
+        }
+        assert!(exec(&reg, &mut vfs, "watchfs").is_err());
+    }
+
+    #[test]
+    fn watchfs_job_prints_new_changes() {
+        let (_reg, mut vfs) = setup();
+        vfs.write("/tmp/old.txt", b"old").unwrap();
+        let mut job = WatchJob {
+            path: "/tmp".to_string(),
+            generation: vfs.generation(),
+            interval: Duration::ZERO,
+            last_poll: Instant::now(),
+        };
+        let mut out = Vec::new();
+        vfs.write("/tmp/old.txt", b"new").unwrap();
+        vfs.remove("/tmp/old.txt").unwrap();
+        vfs.write("/other.txt", b"x").unwrap();
+        let mut env = Environment {
+            cwd: "/".to_string(),
+            vfs: &mut vfs,
+            power: None,
+            time: None,
+            usb: None,
+            network: None,
+            tls: None,
+            stdin: None,
+            enforce_permissions: false,
+        };
+        assert!(!job.step(&mut env, &mut out).unwrap());
+        assert_eq!(out, ["modified /tmp/old.txt", "removed /tmp/old.txt"]);
+        job.step(&mut env, &mut out).unwrap();
+        assert_eq!(out.len(), 2);
+        env.vfs.remove("/tmp").unwrap();
+        job.step(&mut env, &mut out).unwrap();
+        assert_eq!(out[2], "removed /tmp");
+    }
+
+    #[test]
+    fn fg_on_a_watch_prints_its_changes_and_returns() {
+        let (reg, mut vfs) = setup();
+        exec(&reg, &mut vfs, "watchfs /tmp &").unwrap();
+        vfs.write("/tmp/new.txt", b"x").unwrap();
+        match exec(&reg, &mut vfs, "fg 1").unwrap() {
+            CommandOutput::Text(s) => assert_eq!(s, "created /tmp/new.txt"),
+            _ => panic!("expected text"),
+        }
+        assert!(exec(&reg, &mut vfs, "fg 1").is_err());
+    }
 }
@@ -2,14 +2,21 @@
 //!
 //! Supports quoted arguments, environment variables, command history,
 //! pipes, output redirection, command chaining, and glob expansion.
+//! A `--json` argument anywhere on a command asks it for
+//! [`CommandOutput::Json`] instead of text.
 
 use std::cell::{Cell, RefCell};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet, VecDeque};
 
 use oasis_platform::{NetworkService, PowerService, TimeService, UsbService};
+use oasis_types::backend::Color;
 use oasis_types::error::{OasisError, Result};
 use oasis_vfs::Vfs;
 
+use crate::jobs::{CancelToken, FinishedJob, Job, JobTable};
+use crate::schedule::Scheduler;
+use crate::script::ScriptLimits;
+
 /// Output produced by a command.
 #[derive(Debug, Clone)]
 pub enum CommandOutput {
@@ -20,6 +27,9 @@ pub enum CommandOutput {
         headers: Vec<String>,
         rows: Vec<Vec<String>>,
     },
+    /// Structured output, produced when a command is run with `--json`.
+    /// Rendered with [`format_json`] on text terminals.
+    Json(serde_json::Value),
     /// Command produced no visible output.
     None,
     /// Signal to clear the terminal output buffer.
@@ -40,16 +50,54 @@ pub enum CommandOutput {
         /// `true` = sandbox on (VFS only), `false` = networking enabled.
         enable: bool,
     },
+    /// Signal to the app to report browser resource cache statistics.
+    BrowserCache {
+        /// Drop all cached resources before reporting.
+        clear: bool,
+    },
     /// Signal to the app to swap the active skin.
     SkinSwap {
         /// Skin name or path to load.
         name: String,
     },
+    /// Signal to the app to watch the active skin's directory and reload
+    /// it when its files change.
+    SkinWatch {
+        /// `Some(true)` = start, `Some(false)` = stop, `None` = toggle.
+        enable: Option<bool>,
+    },
+    /// Signal to the app to reload the active skin from its directory now.
+    SkinReload,
+    /// Signal to the app to set the strength of the active skin's glitch
+    /// effect.
+    SkinIntensity {
+        /// Base intensity, 0.0 (off) to 1.0.
+        intensity: f32,
+    },
+    /// Signal to the app to report or change a color of the live theme.
+    ThemeColor {
+        /// Color name, one of `ActiveTheme::COLOR_KEYS` or
+        /// `active_theme::WM_COLOR_KEYS`.
+        key: String,
+        /// New value, or `None` to report the current one.
+        color: Option<Color>,
+    },
     /// Multiple outputs from a chained command (e.g. `skin xp ; echo Done`).
     /// Each inner output is processed in order by the app layer.
     Multi(Vec<CommandOutput>),
+    /// Output of a command that ran but exits with a non-zero status,
+    /// like `test` with a false expression. The registry sets `$?` to
+    /// `code` and passes `output` on in its place.
+    Status {
+        code: i32,
+        output: Box<CommandOutput>,
+    },
 }
 
+/// Default for [`Environment::enforce_permissions`]: on when built with
+/// the `enforce-permissions` feature.
+pub const ENFORCE_PERMISSIONS: bool = cfg!(feature = "enforce-permissions");
+
 /// Shared mutable environment passed to every command.
 pub struct Environment<'a> {
     /// Current working directory (VFS path).
@@ -68,6 +116,9 @@ pub struct Environment<'a> {
     pub tls: Option<&'a dyn oasis_net::tls::TlsProvider>,
     /// Piped input from a previous command in a pipeline.
     pub stdin: Option<String>,
+    /// Refuse to overwrite files whose mode is not writable, instead of
+    /// only recording the mode. See [`ENFORCE_PERMISSIONS`].
+    pub enforce_permissions: bool,
 }
 
 /// A single executable command.
@@ -88,20 +139,196 @@ pub trait Command {
 
     /// Execute the command with the given arguments and environment.
     fn execute(&self, args: &[&str], env: &mut Environment<'_>) -> Result<CommandOutput>;
+
+    /// Start the command in the background (`cmd &`). Commands with
+    /// long-running work override this to return a job that does it in
+    /// steps or on a thread, checking `cancel` for `kill`. The default
+    /// runs [`execute`](Self::execute) to completion right away.
+    fn spawn(
+        &self,
+        args: &[&str],
+        env: &mut Environment<'_>,
+        _cancel: CancelToken,
+    ) -> Result<Box<dyn Job>> {
+        let output = self.execute(args, env)?;
+        Ok(Box::new(FinishedJob::new(output)?))
+    }
+
+    /// Execute the command for `--json` (already removed from `args`),
+    /// returning its result as JSON. Commands without structured output
+    /// keep the default, which rejects the flag.
+    fn execute_json(
+        &self,
+        _args: &[&str],
+        _env: &mut Environment<'_>,
+    ) -> Result<serde_json::Value> {
+        Err(OasisError::Command(format!(
+            "{}: --json is not supported",
+            self.name()
+        )))
+    }
+}
+
+/// Render JSON output for a text terminal.
+pub fn format_json(value: &serde_json::Value) -> String {
+    serde_json::to_string_pretty(value).unwrap_or_else(|_| value.to_string())
 }
 
 /// Maximum number of history entries to retain.
 const MAX_HISTORY: usize = 100;
 
+/// Where [`CommandRegistry::save_history`] keeps the command history.
+pub const HISTORY_PATH: &str = "/home/.history";
+
+/// Shell profile run by [`CommandRegistry::run_profile`] at startup.
+pub const PROFILE_PATH: &str = "/home/.profile";
+
+/// Command history: a bounded list of entered lines, oldest first, with
+/// Up/Down recall.
+///
+/// Recall starts at the newest entry. The line being typed when it
+/// starts is stashed and given back once Down steps past the newest
+/// entry.
+#[derive(Debug, Clone)]
+pub struct History {
+    entries: VecDeque<String>,
+    capacity: usize,
+    /// Index into `entries` of the entry being recalled, or `None` when
+    /// not recalling.
+    cursor: Option<usize>,
+    stash: Option<String>,
+}
+
+impl History {
+    /// Create an empty history keeping at most `capacity` entries.
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            entries: VecDeque::new(),
+            capacity,
+            cursor: None,
+            stash: None,
+        }
+    }
+
+    /// Add a line, dropping the oldest entry when full. Blank lines and
+    /// repeats of the newest entry are not recorded. Ends any recall.
+    pub fn push(&mut self, line: &str) {
+        self.reset_recall();
+        let line = line.trim();
+        if line.is_empty() || self.entries.back().is_some_and(|last| last == line) {
+            return;
+        }
+        self.entries.push_back(line.to_string());
+        while self.entries.len() > self.capacity {
+            self.entries.pop_front();
+        }
+    }
+
+    /// Entries, oldest first.
+    pub fn entries(&self) -> &VecDeque<String> {
+        &self.entries
+    }
+
+    /// Entry `n`, counting from 1 as `history` numbers them.
+    pub fn get(&self, n: usize) -> Option<&str> {
+        self.entries.get(n.checked_sub(1)?).map(String::as_str)
+    }
+
+    /// The newest entry.
+    pub fn last(&self) -> Option<&str> {
+        self.entries.back().map(String::as_str)
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Forget every entry.
+    pub fn clear(&mut self) {
+        self.entries.clear();
+        self.reset_recall();
+    }
+
+    /// Step back to the previous (older) entry, for Up. `current` is the
+    /// line being typed; it is stashed when recall starts. Stays on the
+    /// oldest entry once reached. Returns `None` if the history is empty.
+    pub fn older(&mut self, current: &str) -> Option<String> {
+        let idx = match self.cursor {
+            Some(i) => i.saturating_sub(1),
+            None => {
+                let idx = self.entries.len().checked_sub(1)?;
+                self.stash = Some(current.to_string());
+                idx
+            },
+        };
+        self.cursor = Some(idx);
+        self.entries.get(idx).cloned()
+    }
+
+    /// Step forward to the next (newer) entry, for Down. Past the newest
+    /// entry this returns the stashed line and ends recall; when not
+    /// recalling it returns `None`.
+    pub fn newer(&mut self) -> Option<String> {
+        let idx = self.cursor? + 1;
+        if idx < self.entries.len() {
+            self.cursor = Some(idx);
+            self.entries.get(idx).cloned()
+        } else {
+            let stash = self.stash.take().unwrap_or_default();
+            self.reset_recall();
+            Some(stash)
+        }
+    }
+
+    fn reset_recall(&mut self) {
+        self.cursor = None;
+        self.stash = None;
+    }
+
+    /// Serialize as one entry per line.
+    pub fn to_text(&self) -> String {
+        let mut text = String::new();
+        for entry in &self.entries {
+            text.push_str(entry);
+            text.push('\n');
+        }
+        text
+    }
+
+    /// Append the entries of text written by [`to_text`](Self::to_text).
+    pub fn load_text(&mut self, text: &str) {
+        for line in text.lines() {
+            self.push(line);
+        }
+    }
+}
+
+/// Shell built-ins handled by the registry itself rather than a [`Command`].
+const INTERCEPTED: [&str; 15] = [
+    "help", "run", "sh", "history", "set", "export", "unset", "env", "alias", "unalias", "which",
+    "jobs", "fg", "kill", "schedule",
+];
+
 /// Registry of available commands with dispatch.
 ///
-/// Also holds persistent shell state: variables, aliases, and history.
+/// Also holds persistent shell state: variables, aliases, history,
+/// background jobs, and scheduled commands.
 pub struct CommandRegistry {
     commands: HashMap<String, Box<dyn Command>>,
     variables: RefCell<HashMap<String, String>>,
     aliases: RefCell<HashMap<String, String>>,
-    history: RefCell<Vec<String>>,
+    history: RefCell<History>,
+    jobs: RefCell<JobTable>,
+    schedule: RefCell<Scheduler>,
+    scripts: ScriptLimits,
     last_exit_code: Cell<i32>,
+    /// Exit status of the last single command, from
+    /// [`CommandOutput::Status`].
+    command_status: Cell<i32>,
 }
 
 impl CommandRegistry {
@@ -115,8 +342,12 @@ impl CommandRegistry {
             commands: HashMap::new(),
             variables: RefCell::new(vars),
             aliases: RefCell::new(HashMap::new()),
-            history: RefCell::new(Vec::new()),
+            history: RefCell::new(History::new(MAX_HISTORY)),
+            jobs: RefCell::new(JobTable::default()),
+            schedule: RefCell::new(Scheduler::new()),
+            scripts: ScriptLimits::default(),
             last_exit_code: Cell::new(0),
+            command_status: Cell::new(0),
         }
     }
 
@@ -125,8 +356,45 @@ impl CommandRegistry {
         self.commands.insert(cmd.name().to_string(), cmd);
     }
 
+    /// Command names (registered commands, built-ins and aliases)
+    /// starting with `prefix`, sorted.
+    pub fn complete_command(&self, prefix: &str) -> Vec<String> {
+        let prefix = prefix.to_ascii_lowercase();
+        let mut names: Vec<String> = self
+            .commands
+            .keys()
+            .map(String::as_str)
+            .chain(INTERCEPTED)
+            .map(str::to_string)
+            .chain(self.aliases.borrow().keys().cloned())
+            .filter(|name| name.starts_with(&prefix))
+            .collect();
+        names.sort();
+        names.dedup();
+        names
+    }
+
     // -- Shell variable API --
 
+    /// Exit status of the last command line, as in `$?`.
+    pub(crate) fn exit_code(&self) -> i32 {
+        self.last_exit_code.get()
+    }
+
+    /// Set `$?`.
+    pub(crate) fn set_exit_code(&self, code: i32) {
+        self.last_exit_code.set(code);
+        self.set_variable("?", &code.to_string());
+    }
+
+    pub(crate) fn script_limits(&self) -> &ScriptLimits {
+        &self.scripts
+    }
+
+    pub(crate) fn scheduler(&self) -> &RefCell<Scheduler> {
+        &self.schedule
+    }
+
     /// Set a shell variable.
     pub fn set_variable(&self, name: &str, value: &str) {
         self.variables
@@ -172,26 +440,72 @@ impl CommandRegistry {
 
     /// Get command history.
     pub fn history(&self) -> Vec<String> {
-        self.history.borrow().clone()
+        self.history.borrow().entries().iter().cloned().collect()
     }
 
-    /// Push a command to history.
-    fn push_history(&self, line: &str) {
-        let mut hist = self.history.borrow_mut();
-        // Don't duplicate the last entry.
-        if hist.last().is_none_or(|last| last != line) {
-            hist.push(line.to_string());
-            if hist.len() > MAX_HISTORY {
-                hist.remove(0);
-            }
+    /// Step back to the previous history entry, for recalling commands
+    /// with Up. See [`History::older`].
+    pub fn history_prev(&self, current: &str) -> Option<String> {
+        self.history.borrow_mut().older(current)
+    }
+
+    /// Step forward to the next history entry, for Down. See
+    /// [`History::newer`].
+    pub fn history_next(&self) -> Option<String> {
+        self.history.borrow_mut().newer()
+    }
+
+    /// Record a line in history. [`execute`](Self::execute) does this for
+    /// every line it runs; frontends that run lines some other way can
+    /// call it themselves.
+    pub fn push_history(&self, line: &str) {
+        self.history.borrow_mut().push(line);
+    }
+
+    /// Write the history to [`HISTORY_PATH`], one entry per line.
+    pub fn save_history(&self, vfs: &mut dyn Vfs) -> Result<()> {
+        vfs.write(HISTORY_PATH, self.history.borrow().to_text().as_bytes())
+    }
+
+    /// Add the entries saved at [`HISTORY_PATH`] to the history. A
+    /// missing file is not an error.
+    pub fn load_history(&self, vfs: &dyn Vfs) -> Result<()> {
+        if !vfs.exists(HISTORY_PATH) {
+            return Ok(());
         }
+        let data = vfs.read(HISTORY_PATH)?;
+        self.history
+            .borrow_mut()
+            .load_text(&String::from_utf8_lossy(&data));
+        Ok(())
+    }
+
+    // -- Job API --
+
+    /// Whether any background jobs are being tracked.
+    pub fn has_jobs(&self) -> bool {
+        !self.jobs.borrow().is_empty()
+    }
+
+    /// Advance every running background job once. Frontends call this
+    /// between frames.
+    pub fn poll_jobs(&self, env: &mut Environment<'_>) {
+        self.jobs.borrow_mut().poll(env);
+    }
+
+    /// Background job output produced since the last call, tagged with
+    /// job ids, plus a notice for each job that ended. Frontends print it
+    /// above the prompt.
+    pub fn take_job_output(&self) -> Vec<String> {
+        self.jobs.borrow_mut().take_output()
     }
 
     /// Parse and execute a command line.
     ///
     /// Supports quoting, variable expansion, aliases, command chaining
-    /// (`;`, `&&`, `||`), pipes (`|`), and output redirection (`>`, `>>`).
-    /// Command names are case-insensitive.
+    /// (`;`, `&&`, `||`), background jobs (a trailing `&`), pipes (`|`),
+    /// and output redirection (`>`, `>>`). Command names are
+    /// case-insensitive.
     pub fn execute(&self, line: &str, env: &mut Environment<'_>) -> Result<CommandOutput> {
         let trimmed = line.trim();
         if trimmed.is_empty() {
@@ -208,13 +522,23 @@ impl CommandRegistry {
 
         // Push to history (after history expansion, before execution).
         self.push_history(&line);
+        self.execute_line(&line, env)
+    }
 
-        // Update $CWD before variable expansion.
+    /// Execute a command line without history handling: chains,
+    /// background jobs, pipes and redirection. Sets `$?`.
+    pub(crate) fn execute_line(
+        &self,
+        line: &str,
+        env: &mut Environment<'_>,
+    ) -> Result<CommandOutput> {
+        // Update $CWD / $PWD before variable expansion.
         self.set_variable("CWD", &env.cwd);
+        self.set_variable("PWD", &env.cwd);
         self.last_exit_code.set(self.last_exit_code.get());
 
         // Split into chained segments (;, &&, ||).
-        let segments = split_chains(&line)?;
+        let segments = split_chains(line)?;
         let single_command = segments.len() == 1;
         let mut all_outputs: Vec<CommandOutput> = Vec::new();
 
@@ -229,18 +553,22 @@ impl CommandRegistry {
                 continue;
             }
 
-            match self.execute_pipeline(&segment.command, env) {
+            self.command_status.set(0);
+            let result = if segment.background {
+                self.spawn_job(&segment.command, env)
+            } else {
+                self.execute_pipeline(&segment.command, env)
+            };
+            match result {
                 Ok(output) => {
-                    self.last_exit_code.set(0);
-                    self.set_variable("?", "0");
+                    self.set_exit_code(self.command_status.get());
                     match output {
                         CommandOutput::None => {},
                         other => all_outputs.push(other),
                     }
                 },
                 Err(e) => {
-                    self.last_exit_code.set(1);
-                    self.set_variable("?", "1");
+                    self.set_exit_code(1);
                     // For single commands, propagate errors directly.
                     if single_command {
                         return Err(e);
@@ -279,6 +607,9 @@ impl CommandRegistry {
     }
 
     /// Execute a pipeline: `cmd1 | cmd2 | cmd3`.
+    ///
+    /// Each stage's output is the next stage's `env.stdin`. A failing
+    /// stage stops the pipeline; its error names the stage.
     fn execute_pipeline(
         &self,
         pipeline_str: &str,
@@ -294,13 +625,20 @@ impl CommandRegistry {
         // Pipeline: chain stdout -> stdin.
         let mut stdin: Option<String> = env.stdin.take();
 
-        for segment in &pipe_segments {
+        for (i, segment) in pipe_segments.iter().enumerate() {
             env.stdin = stdin.take();
             // All segments get redirection parsing so `>` / `>>` is
             // stripped instead of being passed as literal arguments.
-            let result = self.execute_with_redirect(segment, env)?;
-
-            stdin = match result {
+            let result = self.execute_with_redirect(segment, env);
+            // Don't let a segment's input leak into later chained
+            // commands (`a | b ; c`), even when it fails.
+            env.stdin = None;
+
+            let output = result.map_err(|e| {
+                let name = segment.split_whitespace().next().unwrap_or_default();
+                OasisError::Command(format!("pipeline stage {} ({name}): {e}", i + 1))
+            })?;
+            stdin = match output {
                 CommandOutput::Text(text) => Some(text),
                 CommandOutput::Table { headers, rows } => {
                     let mut out = headers.join(" | ");
@@ -310,6 +648,7 @@ impl CommandRegistry {
                     }
                     Some(out)
                 },
+                CommandOutput::Json(value) => Some(format_json(&value)),
                 _ => None,
             };
         }
@@ -328,6 +667,11 @@ impl CommandRegistry {
         env: &mut Environment<'_>,
     ) -> Result<CommandOutput> {
         let (cmd_part, redirect) = parse_redirect(cmd_str);
+        // Like a shell, refuse a read-only target before running anything.
+        if let Some(redir) = &redirect {
+            let path = resolve_path(&env.cwd, redir.path.trim());
+            crate::security_commands::check_writable(env, &path)?;
+        }
 
         let result = self.execute_single_cmd(cmd_part.trim(), env)?;
 
@@ -343,6 +687,7 @@ impl CommandRegistry {
                     }
                     out
                 },
+                CommandOutput::Json(value) => format_json(value),
                 _ => String::new(),
             };
 
@@ -369,15 +714,55 @@ impl CommandRegistry {
         }
     }
 
-    /// Execute a single command (after chaining, piping, and redirection).
-    fn execute_single_cmd(
+    /// Start a command line in the background and report its job id.
+    ///
+    /// A plain registered command is started with [`Command::spawn`].
+    /// Pipelines, redirections and built-ins run to completion first and
+    /// are tracked as finished jobs.
+    fn spawn_job(&self, cmd_str: &str, env: &mut Environment<'_>) -> Result<CommandOutput> {
+        let cancel = CancelToken::new();
+        let simple = split_pipes(cmd_str)?.len() == 1 && parse_redirect(cmd_str).1.is_none();
+        let tokens = if simple {
+            self.command_tokens(cmd_str, env)?
+        } else {
+            Vec::new()
+        };
+        let command = tokens.first().and_then(|name| {
+            let name = name.to_ascii_lowercase();
+            if INTERCEPTED.contains(&name.as_str()) || tokens.iter().any(|a| a == "--json") {
+                None
+            } else {
+                self.commands.get(name.as_str())
+            }
+        });
+        let job = match command {
+            Some(cmd) => {
+                let args: Vec<&str> = tokens[1..].iter().map(String::as_str).collect();
+                cmd.spawn(&args, env, cancel.clone())?
+            },
+            None => Box::new(FinishedJob::new(self.execute_pipeline(cmd_str, env)?)?),
+        };
+        let id = self.jobs.borrow_mut().add(cmd_str, job, cancel);
+        Ok(CommandOutput::Text(format!("[{id}] {cmd_str}")))
+    }
+
+    /// Expand variables, quotes and globs in `text`, giving its words.
+    pub(crate) fn expand_words(
         &self,
-        cmd_str: &str,
+        text: &str,
         env: &mut Environment<'_>,
-    ) -> Result<CommandOutput> {
+    ) -> Result<Vec<String>> {
+        let expanded = self.expand_variables(text.trim(), &env.cwd);
+        let tokens = tokenize(&expanded)?;
+        Ok(expand_globs(&tokens, env.vfs, &env.cwd))
+    }
+
+    /// Expand variables, quotes, aliases and globs in a single command,
+    /// giving its name and arguments. Empty for a blank command.
+    fn command_tokens(&self, cmd_str: &str, env: &mut Environment<'_>) -> Result<Vec<String>> {
         let trimmed = cmd_str.trim();
         if trimmed.is_empty() {
-            return Ok(CommandOutput::None);
+            return Ok(Vec::new());
         }
 
         // Expand variables.
@@ -386,33 +771,79 @@ impl CommandRegistry {
         // Tokenize with quote handling.
         let tokens = tokenize(&expanded)?;
         if tokens.is_empty() {
-            return Ok(CommandOutput::None);
+            return Ok(tokens);
         }
 
         // Expand aliases (first token only).
         let tokens = self.expand_alias(tokens);
         if tokens.is_empty() {
-            return Ok(CommandOutput::None);
+            return Ok(tokens);
         }
 
         // Expand globs.
-        let tokens = expand_globs(&tokens, env.vfs, &env.cwd);
+        Ok(expand_globs(&tokens, env.vfs, &env.cwd))
+    }
+
+    /// Execute a single command (after chaining, piping, and redirection),
+    /// noting its exit status.
+    fn execute_single_cmd(
+        &self,
+        cmd_str: &str,
+        env: &mut Environment<'_>,
+    ) -> Result<CommandOutput> {
+        self.command_status.set(0);
+        match self.dispatch(cmd_str, env)? {
+            CommandOutput::Status { code, output } => {
+                self.command_status.set(code);
+                Ok(*output)
+            },
+            output => Ok(output),
+        }
+    }
+
+    /// Run a single command: a built-in or a registered [`Command`].
+    fn dispatch(&self, cmd_str: &str, env: &mut Environment<'_>) -> Result<CommandOutput> {
+        let tokens = self.command_tokens(cmd_str, env)?;
+        if tokens.is_empty() {
+            return Ok(CommandOutput::None);
+        }
 
         let name_lower = tokens[0].to_ascii_lowercase();
         let arg_strings: Vec<String> = tokens[1..].to_vec();
-        let args: Vec<&str> = arg_strings.iter().map(|s| s.as_str()).collect();
+        let json = arg_strings.iter().any(|a| a == "--json");
+        let args: Vec<&str> = arg_strings
+            .iter()
+            .map(|s| s.as_str())
+            .filter(|a| *a != "--json")
+            .collect();
+
+        if json {
+            if INTERCEPTED.contains(&name_lower.as_str()) {
+                return Err(OasisError::Command(format!(
+                    "{name_lower}: --json is not supported"
+                )));
+            }
+            if let Some(cmd) = self.commands.get(name_lower.as_str()) {
+                return cmd.execute_json(&args, env).map(CommandOutput::Json);
+            }
+        }
 
         // Intercept built-in commands that need registry access.
         match name_lower.as_str() {
             "help" => return self.execute_help(&args),
-            "run" => return self.execute_run(&args, env),
+            "run" | "sh" => return self.execute_sh(&name_lower, &args, env),
             "history" => return self.execute_history_cmd(&args),
             "set" => return self.execute_set(&args),
+            "export" => return self.execute_export(&args),
             "unset" => return self.execute_unset(&args),
             "env" => return self.execute_env(),
-            "alias" => return self.execute_alias(&args),
-            "unalias" => return self.execute_unalias(&args),
+            "alias" => return self.execute_alias(&args, env),
+            "unalias" => return self.execute_unalias(&args, env),
             "which" => return self.execute_which(&args),
+            "jobs" => return self.execute_jobs(),
+            "fg" => return self.execute_fg(&args, env),
+            "kill" => return self.execute_kill(&args),
+            "schedule" => return self.execute_schedule(&args, env),
             _ => {},
         }
 
@@ -428,41 +859,62 @@ impl CommandRegistry {
     // -- History expansion --
 
     fn expand_history(&self, input: &str) -> Result<String> {
+        let hist = self.history.borrow();
         if input == "!!" {
-            let hist = self.history.borrow();
             return hist
                 .last()
-                .cloned()
+                .map(str::to_string)
                 .ok_or_else(|| OasisError::Command("!!: no previous command".to_string()));
         }
         if let Some(n_str) = input.strip_prefix('!')
             && let Ok(n) = n_str.parse::<usize>()
         {
-            let hist = self.history.borrow();
-            if n == 0 || n > hist.len() {
-                return Err(OasisError::Command(format!("!{n}: event not found")));
-            }
-            return Ok(hist[n - 1].clone());
+            return hist
+                .get(n)
+                .map(str::to_string)
+                .ok_or_else(|| OasisError::Command(format!("!{n}: event not found")));
         }
         Ok(input.to_string())
     }
 
     // -- Variable expansion --
 
+    /// Expand `$VAR`, `${VAR}`, `$?`, `$#` and `$@`. Text in single quotes and a
+    /// `$` escaped as `\$` are left alone; the backslash and quotes are
+    /// kept for [`tokenize`] to remove.
     fn expand_variables(&self, input: &str, cwd: &str) -> String {
         let vars = self.variables.borrow();
         let mut result = String::with_capacity(input.len());
         let chars: Vec<char> = input.chars().collect();
         let mut i = 0;
+        let mut in_single = false;
+        let mut in_double = false;
 
         while i < chars.len() {
-            if chars[i] == '$' && i + 1 < chars.len() {
+            match chars[i] {
+                '\\' if !in_single => {
+                    result.extend(&chars[i..(i + 2).min(chars.len())]);
+                    i += 2;
+                    continue;
+                },
+                '\'' if !in_double => in_single = !in_single,
+                '"' if !in_single => in_double = !in_double,
+                _ => {},
+            }
+            if chars[i] == '$' && !in_single && i + 1 < chars.len() {
                 // Check for $? (last exit code).
                 if chars[i + 1] == '?' {
                     result.push_str(&self.last_exit_code.get().to_string());
                     i += 2;
                     continue;
                 }
+                // $# and $@ (script argument count and list).
+                if matches!(chars[i + 1], '#' | '@') {
+                    let name = chars[i + 1].to_string();
+                    result.push_str(&self.resolve_var(&name, &vars, cwd));
+                    i += 2;
+                    continue;
+                }
                 // Check for ${VAR} syntax.
                 if chars[i + 1] == '{'
                     && let Some(end) = chars[i + 2..].iter().position(|&c| c == '}')
@@ -498,7 +950,7 @@ impl CommandRegistry {
 
     fn resolve_var(&self, name: &str, vars: &HashMap<String, String>, cwd: &str) -> String {
         match name {
-            "CWD" => cwd.to_string(),
+            "CWD" | "PWD" => cwd.to_string(),
             "?" => self.last_exit_code.get().to_string(),
             _ => vars.get(name).cloned().unwrap_or_default(),
         }
@@ -506,16 +958,18 @@ impl CommandRegistry {
 
     // -- Alias expansion --
 
+    /// Replace the command word with its alias, re-tokenizing the alias
+    /// value. The result is expanded again while it starts with another
+    /// alias, but each alias at most once, so `alias ls='ls -l'` stops.
     fn expand_alias(&self, mut tokens: Vec<String>) -> Vec<String> {
-        if tokens.is_empty() {
-            return tokens;
-        }
         let aliases = self.aliases.borrow();
-        if let Some(expansion) = aliases.get(&tokens[0]) {
-            // Replace the first token with the alias expansion.
-            let expanded_tokens = match tokenize(expansion) {
-                Ok(t) => t,
-                Err(_) => return tokens,
+        let mut seen = HashSet::new();
+        while let Some(first) = tokens.first()
+            && let Some(expansion) = aliases.get(first)
+            && seen.insert(first.clone())
+        {
+            let Ok(expanded_tokens) = tokenize(expansion) else {
+                break;
             };
             tokens.splice(0..1, expanded_tokens);
         }
@@ -524,269 +978,6 @@ impl CommandRegistry {
 
     // -- Intercepted commands --
 
-    /// Built-in `run` implementation that executes scripts through the registry.
-    fn execute_run(&self, args: &[&str], env: &mut Environment<'_>) -> Result<CommandOutput> {
-        let path = args
-            .first()
-            .copied()
-            .ok_or_else(|| OasisError::Command("usage: run <path>".to_string()))?;
-
-        let full_path = resolve_path(&env.cwd, path);
-
-        if !env.vfs.exists(&full_path) {
-            return Err(OasisError::Command(format!(
-                "script not found: {full_path}"
-            )));
-        }
-
-        let data = env.vfs.read(&full_path)?;
-        let source = String::from_utf8_lossy(&data);
-        let lines: Vec<String> = source
-            .lines()
-            .map(|l| l.trim().to_string())
-            .filter(|l| !l.is_empty() && !l.starts_with('#'))
-            .collect();
-
-        if lines.is_empty() {
-            return Ok(CommandOutput::Text("(empty script)".to_string()));
-        }
-
-        let mut output = Vec::new();
-        let mut pos = 0;
-        self.execute_script_block(&lines, &mut pos, env, &mut output);
-
-        if output.is_empty() {
-            Ok(CommandOutput::Text(format!(
-                "Script {full_path}: {} commands executed.",
-                lines.len()
-            )))
-        } else {
-            Ok(CommandOutput::Text(output.join("\n")))
-        }
-    }
-
-    /// Execute a block of script lines with control flow support.
-    ///
-    /// Handles `if`/`then`/`else`/`fi`, `while`/`do`/`done`, and
-    /// `for`/`in`/`do`/`done` constructs.
-    fn execute_script_block(
-        &self,
-        lines: &[String],
-        pos: &mut usize,
-        env: &mut Environment<'_>,
-        output: &mut Vec<String>,
-    ) {
-        const MAX_ITERATIONS: usize = 1000;
-
-        while *pos < lines.len() {
-            let line = &lines[*pos];
-            let first_word = line.split_whitespace().next().unwrap_or("");
-
-            match first_word {
-                "if" => {
-                    let condition = line.strip_prefix("if").unwrap_or("").trim();
-                    *pos += 1;
-                    // Evaluate condition.
-                    let cond_result = self.eval_condition(condition, env);
-
-                    // Find then/else/fi boundaries and collect blocks.
-                    let mut then_block = Vec::new();
-                    let mut else_block = Vec::new();
-                    let mut in_else = false;
-                    let mut depth = 0;
-
-                    while *pos < lines.len() {
-                        let l = &lines[*pos];
-                        let fw = l.split_whitespace().next().unwrap_or("");
-                        if fw == "if" {
-                            depth += 1;
-                        }
-                        if fw == "fi" {
-                            if depth == 0 {
-                                *pos += 1;
-                                break;
-                            }
-                            depth -= 1;
-                        }
-                        if fw == "else" && depth == 0 {
-                            in_else = true;
-                            *pos += 1;
-                            continue;
-                        }
-                        // Skip "then" keyword.
-                        if fw == "then" && depth == 0 {
-                            *pos += 1;
-                            continue;
-                        }
-                        if in_else {
-                            else_block.push(l.clone());
-                        } else {
-                            then_block.push(l.clone());
-                        }
-                        *pos += 1;
-                    }
-
-                    let block = if cond_result {
-                        &then_block
-                    } else {
-                        &else_block
-                    };
-                    if !block.is_empty() {
-                        let mut sub_pos = 0;
-                        self.execute_script_block(block, &mut sub_pos, env, output);
-                    }
-                },
-                "while" => {
-                    let condition = line.strip_prefix("while").unwrap_or("").trim().to_string();
-                    *pos += 1;
-                    // Skip "do" keyword.
-                    if *pos < lines.len() && lines[*pos].split_whitespace().next() == Some("do") {
-                        *pos += 1;
-                    }
-
-                    // Collect loop body until "done".
-                    let body_start = *pos;
-                    let mut depth = 0;
-                    while *pos < lines.len() {
-                        let fw = lines[*pos].split_whitespace().next().unwrap_or("");
-                        if fw == "while" || fw == "for" {
-                            depth += 1;
-                        }
-                        if fw == "done" {
-                            if depth == 0 {
-                                break;
-                            }
-                            depth -= 1;
-                        }
-                        *pos += 1;
-                    }
-                    let body: Vec<String> = lines[body_start..*pos].to_vec();
-                    if *pos < lines.len() {
-                        *pos += 1; // Skip "done".
-                    }
-
-                    // Execute loop.
-                    let mut iterations = 0;
-                    while self.eval_condition(&condition, env) && iterations < MAX_ITERATIONS {
-                        let mut sub_pos = 0;
-                        self.execute_script_block(&body, &mut sub_pos, env, output);
-                        iterations += 1;
-                    }
-                    if iterations >= MAX_ITERATIONS {
-                        output.push(format!(
-                            "warning: while loop terminated after \
-                             {MAX_ITERATIONS} iterations (limit reached)"
-                        ));
-                    }
-                },
-                "for" => {
-                    // Parse: for VAR in ITEM1 ITEM2 ...
-                    let rest = line.strip_prefix("for").unwrap_or("").trim();
-                    let parts: Vec<&str> = rest.splitn(3, ' ').collect();
-                    let var_name = parts.first().copied().unwrap_or("_");
-                    let items_str = if parts.get(1) == Some(&"in") {
-                        parts.get(2).copied().unwrap_or("")
-                    } else {
-                        ""
-                    };
-                    let items: Vec<&str> = items_str.split_whitespace().collect();
-
-                    *pos += 1;
-                    // Skip "do" keyword.
-                    if *pos < lines.len() && lines[*pos].split_whitespace().next() == Some("do") {
-                        *pos += 1;
-                    }
-
-                    // Collect loop body until "done".
-                    let body_start = *pos;
-                    let mut depth = 0;
-                    while *pos < lines.len() {
-                        let fw = lines[*pos].split_whitespace().next().unwrap_or("");
-                        if fw == "while" || fw == "for" {
-                            depth += 1;
-                        }
-                        if fw == "done" {
-                            if depth == 0 {
-                                break;
-                            }
-                            depth -= 1;
-                        }
-                        *pos += 1;
-                    }
-                    let body: Vec<String> = lines[body_start..*pos].to_vec();
-                    if *pos < lines.len() {
-                        *pos += 1; // Skip "done".
-                    }
-
-                    // Execute for each item.
-                    for item in &items {
-                        self.set_variable(var_name, item);
-                        let mut sub_pos = 0;
-                        self.execute_script_block(&body, &mut sub_pos, env, output);
-                    }
-                },
-                // Stop tokens -- return to parent.
-                "fi" | "done" | "else" | "then" => {
-                    *pos += 1;
-                    return;
-                },
-                _ => {
-                    // Regular command.
-                    self.execute_script_line(line, env, output, *pos);
-                    *pos += 1;
-                },
-            }
-        }
-    }
-
-    /// Execute a single script line and collect output.
-    fn execute_script_line(
-        &self,
-        line: &str,
-        env: &mut Environment<'_>,
-        output: &mut Vec<String>,
-        line_num: usize,
-    ) {
-        match self.execute_single_cmd(line, env) {
-            Ok(CommandOutput::Text(text)) => {
-                for l in text.lines() {
-                    output.push(l.to_string());
-                }
-            },
-            Ok(CommandOutput::Table { headers, rows }) => {
-                output.push(headers.join(" | "));
-                for row in &rows {
-                    output.push(row.join(" | "));
-                }
-            },
-            Ok(CommandOutput::Clear) => {
-                output.push("(clear)".to_string());
-            },
-            Ok(CommandOutput::None) => {},
-            Ok(_) => {
-                output.push("(signal command skipped in script)".to_string());
-            },
-            Err(e) => {
-                output.push(format!("error at line {}: {e}", line_num + 1));
-            },
-        }
-    }
-
-    /// Evaluate a condition string for if/while.
-    ///
-    /// Runs the condition as a command. If it succeeds and outputs "true",
-    /// the condition is true. If it errors or outputs "false", it's false.
-    fn eval_condition(&self, condition: &str, env: &mut Environment<'_>) -> bool {
-        match self.execute_single_cmd(condition, env) {
-            Ok(CommandOutput::Text(text)) => {
-                let t = text.trim();
-                t == "true" || t == "0" || (!t.is_empty() && t != "false" && t != "1")
-            },
-            Ok(CommandOutput::None) => true,
-            _ => false,
-        }
-    }
-
     /// Built-in help with access to the registry.
     fn execute_help(&self, args: &[&str]) -> Result<CommandOutput> {
         if let Some(&name) = args.first() {
@@ -808,13 +999,19 @@ impl CommandRegistry {
             for builtin in &[
                 ("help", "general"),
                 ("run", "scripting"),
+                ("sh", "scripting"),
                 ("history", "general"),
                 ("set", "config"),
+                ("export", "config"),
                 ("unset", "config"),
                 ("env", "config"),
                 ("alias", "config"),
                 ("unalias", "config"),
                 ("which", "general"),
+                ("jobs", "general"),
+                ("fg", "general"),
+                ("kill", "general"),
+                ("schedule", "system"),
             ] {
                 categories
                     .entry(builtin.1)
@@ -851,6 +1048,35 @@ impl CommandRegistry {
         }
     }
 
+    /// Built-in `jobs` command.
+    fn execute_jobs(&self) -> Result<CommandOutput> {
+        let rows = self.jobs.borrow_mut().list();
+        if rows.is_empty() {
+            return Ok(CommandOutput::Text("No jobs.".to_string()));
+        }
+        Ok(CommandOutput::Table {
+            headers: ["ID", "STATE", "COMMAND", "OUTPUT"]
+                .map(String::from)
+                .to_vec(),
+            rows,
+        })
+    }
+
+    /// Built-in `fg` command: wait for a job and print the output not yet
+    /// shown.
+    fn execute_fg(&self, args: &[&str], env: &mut Environment<'_>) -> Result<CommandOutput> {
+        let id = parse_job_id("fg", args)?;
+        let text = self.jobs.borrow_mut().wait(id, env)?;
+        Ok(CommandOutput::Text(text))
+    }
+
+    /// Built-in `kill` command.
+    fn execute_kill(&self, args: &[&str]) -> Result<CommandOutput> {
+        let id = parse_job_id("kill", args)?;
+        self.jobs.borrow_mut().kill(id)?;
+        Ok(CommandOutput::None)
+    }
+
     /// Built-in `which` command.
     fn execute_which(&self, args: &[&str]) -> Result<CommandOutput> {
         if args.is_empty() {
@@ -858,10 +1084,7 @@ impl CommandRegistry {
         }
         let name = args[0].to_ascii_lowercase();
         // Check intercepted commands first.
-        let intercepted = [
-            "help", "run", "history", "set", "unset", "env", "alias", "unalias", "which",
-        ];
-        if intercepted.contains(&name.as_str()) {
+        if INTERCEPTED.contains(&name.as_str()) {
             return Ok(CommandOutput::Text(format!("{name}: shell built-in")));
         }
         match self.commands.get(name.as_str()) {
@@ -885,9 +1108,10 @@ impl CommandRegistry {
         }
     }
 
-    /// Built-in `history` command.
+    /// Built-in `history` command: list numbered entries, or clear them
+    /// with `-c`.
     fn execute_history_cmd(&self, args: &[&str]) -> Result<CommandOutput> {
-        if args.first() == Some(&"clear") {
+        if matches!(args.first(), Some(&"-c" | &"clear")) {
             self.history.borrow_mut().clear();
             return Ok(CommandOutput::Text("History cleared.".to_string()));
         }
@@ -896,7 +1120,7 @@ impl CommandRegistry {
             return Ok(CommandOutput::Text("(no history)".to_string()));
         }
         let mut out = String::new();
-        for (i, entry) in hist.iter().enumerate() {
+        for (i, entry) in hist.entries().iter().enumerate() {
             out.push_str(&format!("  {:4}  {entry}\n", i + 1));
         }
         Ok(CommandOutput::Text(out.trim_end().to_string()))
@@ -926,6 +1150,29 @@ impl CommandRegistry {
         }
     }
 
+    /// Built-in `export` command: `export NAME=value...`. Every shell
+    /// variable is visible to later commands, so `export NAME` on its own
+    /// changes nothing.
+    fn execute_export(&self, args: &[&str]) -> Result<CommandOutput> {
+        if args.is_empty() {
+            return self.execute_env();
+        }
+        for arg in args {
+            let (name, value) = arg.split_once('=').unwrap_or((arg, ""));
+            let valid = name.starts_with(|c: char| c.is_alphabetic() || c == '_')
+                && name.chars().all(|c| c.is_alphanumeric() || c == '_');
+            if !valid {
+                return Err(OasisError::Command(format!(
+                    "export: '{arg}': not a valid variable name"
+                )));
+            }
+            if arg.contains('=') {
+                self.set_variable(name, value);
+            }
+        }
+        Ok(CommandOutput::None)
+    }
+
     /// Built-in `unset` command.
     fn execute_unset(&self, args: &[&str]) -> Result<CommandOutput> {
         if args.is_empty() {
@@ -950,8 +1197,9 @@ impl CommandRegistry {
         Ok(CommandOutput::Text(out.trim_end().to_string()))
     }
 
-    /// Built-in `alias` command.
-    fn execute_alias(&self, args: &[&str]) -> Result<CommandOutput> {
+    /// Built-in `alias` command. Definitions made outside scripts are
+    /// saved to the shell profile.
+    fn execute_alias(&self, args: &[&str], env: &mut Environment<'_>) -> Result<CommandOutput> {
         if args.is_empty() {
             // List all aliases.
             let aliases = self.aliases.borrow();
@@ -965,38 +1213,48 @@ impl CommandRegistry {
             entries.sort_by_key(|(k, _)| *k);
             let mut out = String::new();
             for (k, v) in &entries {
-                out.push_str(&format!("alias {k}='{v}'\n"));
+                out.push_str(&alias_line(k, v));
+                out.push('\n');
             }
             return Ok(CommandOutput::Text(out.trim_end().to_string()));
         }
+        // Quotes were removed by the tokenizer, so the value is kept as
+        // written and re-tokenized when the alias is used.
         let assignment = args.join(" ");
         if let Some((name, value)) = assignment.split_once('=') {
             let name = name.trim();
-            let value = value.trim().trim_matches('\'').trim_matches('"');
-            if name.is_empty() {
+            let value = value.trim();
+            if name.is_empty() || name.contains(char::is_whitespace) {
                 return Err(OasisError::Command(
                     "usage: alias <name>=<command>".to_string(),
                 ));
             }
             self.set_alias(name, value);
+            if !self.scripts.running() {
+                save_profile_alias(env, name, Some(value))?;
+            }
             Ok(CommandOutput::None)
         } else {
             // Show alias value.
             let aliases = self.aliases.borrow();
             match aliases.get(args[0]) {
-                Some(val) => Ok(CommandOutput::Text(format!("alias {}='{val}'", args[0]))),
+                Some(val) => Ok(CommandOutput::Text(alias_line(args[0], val))),
                 None => Ok(CommandOutput::Text(format!("{}: not aliased", args[0]))),
             }
         }
     }
 
-    /// Built-in `unalias` command.
-    fn execute_unalias(&self, args: &[&str]) -> Result<CommandOutput> {
+    /// Built-in `unalias` command. Also removes the definitions from the
+    /// shell profile.
+    fn execute_unalias(&self, args: &[&str], env: &mut Environment<'_>) -> Result<CommandOutput> {
         if args.is_empty() {
             return Err(OasisError::Command("usage: unalias <name>".to_string()));
         }
         for name in args {
             self.unset_alias(name);
+            if !self.scripts.running() {
+                save_profile_alias(env, name, None)?;
+            }
         }
         Ok(CommandOutput::None)
     }
@@ -1104,6 +1362,54 @@ pub fn tokenize(input: &str) -> Result<Vec<String>> {
     Ok(tokens)
 }
 
+// ---------------------------------------------------------------------------
+// Alias persistence
+// ---------------------------------------------------------------------------
+
+/// The `alias` command defining `name`, single-quoting the value.
+fn alias_line(name: &str, value: &str) -> String {
+    format!("alias {name}='{}'", value.replace('\'', "'\\''"))
+}
+
+/// The alias a profile line defines, if it is an `alias name=...` line.
+fn profile_alias_name(line: &str) -> Option<&str> {
+    let (name, _) = line.trim().strip_prefix("alias ")?.split_once('=')?;
+    Some(name.trim())
+}
+
+/// Define (`Some`) or remove (`None`) alias `name` in [`PROFILE_PATH`],
+/// replacing an existing definition in place. The profile runs at
+/// startup, which reloads the aliases.
+fn save_profile_alias(env: &mut Environment<'_>, name: &str, value: Option<&str>) -> Result<()> {
+    let profile = if env.vfs.exists(PROFILE_PATH) {
+        String::from_utf8_lossy(&env.vfs.read(PROFILE_PATH)?).into_owned()
+    } else {
+        String::new()
+    };
+    let mut new_line = value.map(|v| alias_line(name, v));
+    let mut lines = Vec::new();
+    for line in profile.lines() {
+        if profile_alias_name(line) == Some(name) {
+            // The first definition is replaced, any later ones dropped.
+            lines.extend(new_line.take());
+        } else {
+            lines.push(line.to_string());
+        }
+    }
+    lines.extend(new_line);
+    let mut updated = lines.join("\n");
+    if !updated.is_empty() {
+        updated.push('\n');
+    }
+    if updated != profile {
+        if let Some((parent, _)) = PROFILE_PATH.rsplit_once('/') {
+            let _ = env.vfs.mkdir(parent);
+        }
+        env.vfs.write(PROFILE_PATH, updated.as_bytes())?;
+    }
+    Ok(())
+}
+
 // ---------------------------------------------------------------------------
 // Chain splitting: ;, &&, ||
 // ---------------------------------------------------------------------------
@@ -1121,9 +1427,21 @@ enum ChainOp {
 struct ChainSegment {
     command: String,
     chain_op: ChainOp,
+    /// Ended by a single `&`: run as a background job.
+    background: bool,
+}
+
+/// Parse the job id argument of `fg` / `kill` (`N` or `%N`).
+fn parse_job_id(cmd: &str, args: &[&str]) -> Result<u32> {
+    let [arg] = args else {
+        return Err(OasisError::Command(format!("usage: {cmd} <job>")));
+    };
+    arg.trim_start_matches('%')
+        .parse()
+        .map_err(|_| OasisError::Command(format!("{cmd}: invalid job id: {arg}")))
 }
 
-/// Split a command line on `;`, `&&`, and `||` (respecting quotes).
+/// Split a command line on `;`, `&`, `&&`, and `||` (respecting quotes).
 fn split_chains(input: &str) -> Result<Vec<ChainSegment>> {
     let mut segments = Vec::new();
     let mut current = String::new();
@@ -1173,6 +1491,7 @@ fn split_chains(input: &str) -> Result<Vec<ChainSegment>> {
                     segments.push(ChainSegment {
                         command: cmd,
                         chain_op,
+                        background: false,
                     });
                 }
                 current.clear();
@@ -1185,11 +1504,27 @@ fn split_chains(input: &str) -> Result<Vec<ChainSegment>> {
                     segments.push(ChainSegment {
                         command: cmd,
                         chain_op,
+                        background: false,
                     });
                 }
                 current.clear();
                 chain_op = ChainOp::And;
             },
+            '&' => {
+                let cmd = current.trim().to_string();
+                if cmd.is_empty() {
+                    return Err(OasisError::Command(
+                        "syntax error near unexpected token '&'".to_string(),
+                    ));
+                }
+                segments.push(ChainSegment {
+                    command: cmd,
+                    chain_op,
+                    background: true,
+                });
+                current.clear();
+                chain_op = ChainOp::Always;
+            },
             '|' if chars.peek() == Some(&'|') => {
                 chars.next(); // consume second |
                 let cmd = current.trim().to_string();
@@ -1197,6 +1532,7 @@ fn split_chains(input: &str) -> Result<Vec<ChainSegment>> {
                     segments.push(ChainSegment {
                         command: cmd,
                         chain_op,
+                        background: false,
                     });
                 }
                 current.clear();
@@ -1211,6 +1547,7 @@ fn split_chains(input: &str) -> Result<Vec<ChainSegment>> {
         segments.push(ChainSegment {
             command: cmd,
             chain_op,
+            background: false,
         });
     }
 
@@ -1518,6 +1855,7 @@ mod tests {
             network: None,
             tls: None,
             stdin: None,
+            enforce_permissions: false,
         }
     }
 
@@ -1725,6 +2063,7 @@ mod tests {
                 psk: Some("key".into()),
             },
             CommandOutput::BrowserSandbox { enable: true },
+            CommandOutput::BrowserCache { clear: false },
             CommandOutput::SkinSwap { name: "xp".into() },
         ];
         for o in &outputs {
@@ -1952,6 +2291,22 @@ mod tests {
         assert_eq!(result, "pwd=/home/user");
     }
 
+    #[test]
+    fn variable_pwd_follows_cwd() {
+        let mut reg = CommandRegistry::new();
+        reg.register(Box::new(EchoCmd));
+        let mut vfs = MemoryVfs::new();
+        let mut env = make_env(&mut vfs);
+        env.cwd = "/home".to_string();
+        match reg.execute("echo $PWD", &mut env).unwrap() {
+            CommandOutput::Text(s) => assert_eq!(s, "/home"),
+            _ => panic!("expected text output"),
+        }
+        env.cwd = "/tmp".to_string();
+        reg.execute("echo", &mut env).unwrap();
+        assert_eq!(reg.get_variable("PWD").as_deref(), Some("/tmp"));
+    }
+
     #[test]
     fn variable_exit_code() {
         let reg = CommandRegistry::new();
@@ -1967,6 +2322,31 @@ mod tests {
         assert_eq!(result, "");
     }
 
+    #[test]
+    fn variable_expansion_in_quotes_and_escapes() {
+        let mut reg = CommandRegistry::new();
+        reg.register(Box::new(EchoCmd));
+        let mut vfs = MemoryVfs::new();
+        let mut env = make_env(&mut vfs);
+        reg.set_variable("NAME", "oasis os");
+        for (line, expected) in [
+            (r#"echo "hi $NAME""#, "hi oasis os"),
+            (r#"echo "${NAME}!""#, "oasis os!"),
+            (r#"echo "[$MISSING]""#, "[]"),
+            ("echo '$NAME'", "$NAME"),
+            (r#"echo "it's $NAME""#, "it's oasis os"),
+            (r"echo \$NAME", "$NAME"),
+            (r#"echo "cost: \$5 for $NAME""#, "cost: $5 for oasis os"),
+            (r#"echo "${NAME}${NAME}/$HOME""#, "oasis osoasis os//home"),
+            (r#"echo "'$NAME'""#, "'oasis os'"),
+        ] {
+            match reg.execute(line, &mut env).unwrap() {
+                CommandOutput::Text(s) => assert_eq!(s, expected, "{line}"),
+                _ => panic!("expected text output"),
+            }
+        }
+    }
+
     // -- History tests --
 
     #[test]
@@ -2021,6 +2401,81 @@ mod tests {
         assert_eq!(reg.history().len(), 1);
     }
 
+    #[test]
+    fn history_recall_steps_through_entries() {
+        let mut reg = CommandRegistry::new();
+        reg.register(Box::new(EchoCmd));
+        let mut vfs = MemoryVfs::new();
+        let mut env = make_env(&mut vfs);
+        assert_eq!(reg.history_prev("typed"), None);
+        reg.execute("echo a", &mut env).unwrap();
+        reg.execute("   ", &mut env).unwrap();
+        reg.execute("echo b", &mut env).unwrap();
+
+        assert_eq!(reg.history_prev("ec").as_deref(), Some("echo b"));
+        assert_eq!(reg.history_prev("echo b").as_deref(), Some("echo a"));
+        assert_eq!(reg.history_prev("echo a").as_deref(), Some("echo a"));
+        assert_eq!(reg.history_next().as_deref(), Some("echo b"));
+        // Past the newest entry, the line typed before recall comes back.
+        assert_eq!(reg.history_next().as_deref(), Some("ec"));
+        assert_eq!(reg.history_next(), None);
+
+        // Executing a command restarts recall from the newest entry.
+        reg.history_prev("");
+        reg.history_prev("");
+        reg.execute("echo c", &mut env).unwrap();
+        assert_eq!(reg.history_prev("").as_deref(), Some("echo c"));
+    }
+
+    #[test]
+    fn history_command_lists_and_clears() {
+        let mut reg = CommandRegistry::new();
+        reg.register(Box::new(EchoCmd));
+        let mut vfs = MemoryVfs::new();
+        let mut env = make_env(&mut vfs);
+        reg.execute("echo a", &mut env).unwrap();
+        match reg.execute("history", &mut env).unwrap() {
+            CommandOutput::Text(s) => assert_eq!(s, "     1  echo a\n     2  history"),
+            other => panic!("expected text output, got {other:?}"),
+        }
+        reg.execute("history -c", &mut env).unwrap();
+        assert!(reg.history().is_empty());
+        assert!(reg.execute("!1", &mut env).is_err());
+    }
+
+    #[test]
+    fn history_saves_and_loads() {
+        let mut reg = CommandRegistry::new();
+        reg.register(Box::new(EchoCmd));
+        let mut vfs = MemoryVfs::new();
+        vfs.mkdir("/home").unwrap();
+        let mut env = make_env(&mut vfs);
+        reg.execute("echo one", &mut env).unwrap();
+        reg.execute("echo two", &mut env).unwrap();
+        reg.save_history(&mut vfs).unwrap();
+        assert_eq!(vfs.read(HISTORY_PATH).unwrap(), b"echo one\necho two\n");
+
+        let reg = CommandRegistry::new();
+        reg.load_history(&MemoryVfs::new()).unwrap();
+        assert!(reg.history().is_empty());
+        reg.load_history(&vfs).unwrap();
+        assert_eq!(reg.history(), ["echo one", "echo two"]);
+    }
+
+    #[test]
+    fn history_is_bounded() {
+        let mut reg = CommandRegistry::new();
+        reg.register(Box::new(EchoCmd));
+        let mut vfs = MemoryVfs::new();
+        let mut env = make_env(&mut vfs);
+        for i in 0..MAX_HISTORY + 5 {
+            reg.execute(&format!("echo {i}"), &mut env).unwrap();
+        }
+        let hist = reg.history();
+        assert_eq!(hist.len(), MAX_HISTORY);
+        assert_eq!(hist[0], "echo 5");
+    }
+
     // -- Pipe tests --
 
     #[test]
@@ -2320,6 +2775,87 @@ mod tests {
         }
     }
 
+    fn text(output: Result<CommandOutput>) -> String {
+        match output.unwrap() {
+            CommandOutput::Text(s) => s,
+            _ => panic!("expected text output"),
+        }
+    }
+
+    #[test]
+    fn alias_definition_is_shown_quoted() {
+        let reg = CommandRegistry::new();
+        let mut vfs = MemoryVfs::new();
+        let mut env = make_env(&mut vfs);
+        reg.execute("alias say='echo it'\\''s'", &mut env).unwrap();
+        assert_eq!(reg.aliases()["say"], "echo it's");
+        assert_eq!(
+            text(reg.execute("alias say", &mut env)),
+            "alias say='echo it'\\''s'"
+        );
+        reg.execute("unalias say", &mut env).unwrap();
+        assert!(reg.aliases().is_empty());
+    }
+
+    #[test]
+    fn alias_value_is_retokenized_with_args_appended() {
+        let mut reg = CommandRegistry::new();
+        reg.register(Box::new(EchoCmd));
+        let mut vfs = MemoryVfs::new();
+        let mut env = make_env(&mut vfs);
+        reg.execute("alias greet='echo \"a  b\"'", &mut env)
+            .unwrap();
+        assert_eq!(text(reg.execute("greet c d", &mut env)), "a  b c d");
+        // Only the command word is expanded.
+        assert_eq!(text(reg.execute("echo greet", &mut env)), "greet");
+    }
+
+    #[test]
+    fn alias_recursion_stops() {
+        let mut reg = CommandRegistry::new();
+        reg.register(Box::new(EchoCmd));
+        let mut vfs = MemoryVfs::new();
+        let mut env = make_env(&mut vfs);
+        reg.execute("alias echo='echo >'", &mut env).unwrap();
+        assert_eq!(text(reg.execute("echo hi", &mut env)), "> hi");
+        // Aliases chain, but a loop ends at the first repeat.
+        reg.execute("alias hi=yo", &mut env).unwrap();
+        reg.execute("alias yo='echo yo'", &mut env).unwrap();
+        assert_eq!(text(reg.execute("hi", &mut env)), "> yo");
+        reg.execute("alias a=b", &mut env).unwrap();
+        reg.execute("alias b=a", &mut env).unwrap();
+        assert!(reg.execute("a", &mut env).is_err());
+    }
+
+    #[test]
+    fn aliases_persist_in_profile() {
+        let mut vfs = MemoryVfs::new();
+        crate::populate_profile(&mut vfs);
+        let mut env = make_env(&mut vfs);
+        let reg = CommandRegistry::new();
+        reg.run_profile(&mut env).unwrap();
+        assert_eq!(reg.aliases()["l"], "ls");
+        // Reloading the profile leaves it as it was.
+        let before = env.vfs.read(PROFILE_PATH).unwrap();
+        reg.run_profile(&mut env).unwrap();
+        assert_eq!(env.vfs.read(PROFILE_PATH).unwrap(), before);
+
+        reg.execute("alias l='ls /home'", &mut env).unwrap();
+        reg.execute("alias lh='ls \"$HOME\"'", &mut env).unwrap();
+        reg.execute("unalias ..", &mut env).unwrap();
+        let profile = String::from_utf8(env.vfs.read(PROFILE_PATH).unwrap()).unwrap();
+        assert!(profile.contains("export SHELL=oasis-sh\nalias l='ls /home'\n"));
+        assert!(profile.ends_with("alias lh='ls \"$HOME\"'\n"));
+        assert!(!profile.contains("cd .."));
+
+        let fresh = CommandRegistry::new();
+        fresh.run_profile(&mut env).unwrap();
+        let aliases = fresh.aliases();
+        assert_eq!(aliases.len(), 2);
+        assert_eq!(aliases["l"], "ls /home");
+        assert_eq!(aliases["lh"], "ls \"$HOME\"");
+    }
+
     // -- Set/env tests --
 
     #[test]
@@ -2335,6 +2871,55 @@ mod tests {
         }
     }
 
+    #[test]
+    fn variable_values_are_not_expanded_again() {
+        let mut reg = CommandRegistry::new();
+        reg.register(Box::new(EchoCmd));
+        let mut vfs = MemoryVfs::new();
+        let mut env = make_env(&mut vfs);
+        reg.execute("export INNER=deep 'OUTER=$INNER'", &mut env)
+            .unwrap();
+        match reg.execute("echo \"$OUTER\"", &mut env).unwrap() {
+            CommandOutput::Text(s) => assert_eq!(s, "$INNER"),
+            _ => panic!("expected text output"),
+        }
+    }
+
+    #[test]
+    fn profile_sets_variables_at_startup() {
+        let reg = CommandRegistry::new();
+        let mut vfs = MemoryVfs::new();
+        let mut env = make_env(&mut vfs);
+        reg.run_profile(&mut env).unwrap();
+        crate::populate_profile(&mut *env.vfs);
+        reg.run_profile(&mut env).unwrap();
+        assert_eq!(reg.get_variable("USER").as_deref(), Some("oasis"));
+        assert_eq!(reg.get_variable("SHELL").as_deref(), Some("oasis-sh"));
+        assert!(reg.history().is_empty());
+    }
+
+    #[test]
+    fn export_sets_variables() {
+        let mut reg = CommandRegistry::new();
+        reg.register(Box::new(EchoCmd));
+        let mut vfs = MemoryVfs::new();
+        let mut env = make_env(&mut vfs);
+        reg.execute(
+            "export CONFIG=/etc/oasis \"MOTD=hello there\" HOME",
+            &mut env,
+        )
+        .unwrap();
+        match reg
+            .execute("echo $CONFIG/file \"$MOTD\" $HOME", &mut env)
+            .unwrap()
+        {
+            CommandOutput::Text(s) => assert_eq!(s, "/etc/oasis/file hello there /home"),
+            _ => panic!("expected text output"),
+        }
+        assert!(reg.execute("export 9LIVES=cat", &mut env).is_err());
+        assert!(reg.execute("export =x", &mut env).is_err());
+    }
+
     #[test]
     fn env_lists_variables() {
         let reg = CommandRegistry::new();
@@ -2428,9 +3013,10 @@ mod tests {
     fn script_if_false_branch() {
         let mut reg = CommandRegistry::new();
         reg.register(Box::new(EchoCmd));
+        crate::register_dev_commands(&mut reg);
         let mut vfs = MemoryVfs::new();
         vfs.mkdir("/tmp").unwrap();
-        let out = run_script(&reg, &mut vfs, "if echo false\nthen\necho yes\nfi");
+        let out = run_script(&reg, &mut vfs, "if test a = b\nthen\necho yes\nfi");
         // false condition: then block skipped, no else block → shows command count
         assert!(out.contains("commands executed"));
     }
@@ -2439,12 +3025,13 @@ mod tests {
     fn script_if_else() {
         let mut reg = CommandRegistry::new();
         reg.register(Box::new(EchoCmd));
+        crate::register_dev_commands(&mut reg);
         let mut vfs = MemoryVfs::new();
         vfs.mkdir("/tmp").unwrap();
         let out = run_script(
             &reg,
             &mut vfs,
-            "if echo false\nthen\necho yes\nelse\necho no\nfi",
+            "if test a = b\nthen\necho yes\nelse\necho no\nfi",
         );
         assert_eq!(out, "no");
     }
@@ -2494,7 +3081,7 @@ mod tests {
         // Use a counter via set/test: set counter, run while counter equals
         // a value, then unset to stop. Simpler: just run a fixed echo loop
         // that terminates immediately because condition is false.
-        let out = run_script(&reg, &mut vfs, "while echo false\ndo\necho body\ndone");
+        let out = run_script(&reg, &mut vfs, "while test a = b\ndo\necho body\ndone");
         // Condition is false from the start, loop never executes.
         assert!(out.contains("commands executed"));
     }
@@ -2545,12 +3132,13 @@ mod tests {
     fn script_nested_if_outer_false() {
         let mut reg = CommandRegistry::new();
         reg.register(Box::new(EchoCmd));
+        crate::register_dev_commands(&mut reg);
         let mut vfs = MemoryVfs::new();
         vfs.mkdir("/tmp").unwrap();
         let out = run_script(
             &reg,
             &mut vfs,
-            "if echo false\nthen\nif echo true\nthen\necho nested\nfi\nfi",
+            "if test a = b\nthen\nif echo true\nthen\necho nested\nfi\nfi",
         );
         // Outer if is false, whole then block (including inner if) skipped
         assert!(out.contains("commands executed"));
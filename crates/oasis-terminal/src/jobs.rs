@@ -0,0 +1,516 @@
+//! Background jobs: `cmd &`, `jobs`, `fg` and `kill`.
+//!
+//! A command line ending in `&` is started through [`Command::spawn`],
+//! which returns a [`Job`]. The frontend calls
+//! [`CommandRegistry::poll_jobs`](crate::CommandRegistry::poll_jobs)
+//! between frames to advance every running job, and prints what
+//! [`CommandRegistry::take_job_output`](crate::CommandRegistry::take_job_output)
+//! returns above the prompt. Output is only kept until it is passed on,
+//! and a job that ended is forgotten once its end has been announced.
+//!
+//! A job either does its work in small slices inside [`Job::step`]
+//! (cooperative, for single-threaded frontends) or hands it to a thread
+//! and only collects the results there ([`ThreadJob`]). Commands without
+//! a background mode run to completion when started and come back as an
+//! already finished job.
+//!
+//! [`Command::spawn`]: crate::Command::spawn
+
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::thread::JoinHandle;
+
+use oasis_types::error::{OasisError, Result};
+
+use crate::interpreter::{CommandOutput, Environment, format_json};
+
+/// Cancellation flag shared between a job and `kill`.
+#[derive(Debug, Clone, Default)]
+pub struct CancelToken(Arc<AtomicBool>);
+
+impl CancelToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Ask the job to stop.
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    /// Whether `kill` was used on the job. Long-running work should check
+    /// this regularly and return early.
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+/// Work started in the background.
+pub trait Job {
+    /// Advance the job, appending any new output lines to `out`. Returns
+    /// `true` once the job has finished.
+    fn step(&mut self, env: &mut Environment<'_>, out: &mut Vec<String>) -> Result<bool>;
+
+    /// Run the job to its end for `fg`, appending its output to `out`.
+    /// The default steps it until it finishes; jobs that wait on
+    /// something should block on it rather than spin.
+    fn finish(&mut self, env: &mut Environment<'_>, out: &mut Vec<String>) -> Result<()> {
+        while !self.step(env, out)? {}
+        Ok(())
+    }
+}
+
+/// A job whose output was produced when it was started.
+pub struct FinishedJob {
+    lines: Vec<String>,
+}
+
+impl FinishedJob {
+    /// Wrap a command's output. Signals to the app (skin swaps and the
+    /// like) cannot be delivered from the background and are rejected.
+    pub fn new(output: CommandOutput) -> Result<Self> {
+        let text = match output {
+            CommandOutput::Text(text) => text,
+            CommandOutput::Table { headers, rows } => {
+                let mut out = headers.join(" | ");
+                for row in &rows {
+                    out.push('\n');
+                    out.push_str(&row.join(" | "));
+                }
+                out
+            },
+            CommandOutput::Json(value) => format_json(&value),
+            CommandOutput::None => String::new(),
+            CommandOutput::Status { output, .. } => return Self::new(*output),
+            _ => {
+                return Err(OasisError::Command(
+                    "this command cannot run in the background".to_string(),
+                ));
+            },
+        };
+        Ok(Self {
+            lines: text.lines().map(str::to_string).collect(),
+        })
+    }
+}
+
+impl Job for FinishedJob {
+    fn step(&mut self, _env: &mut Environment<'_>, out: &mut Vec<String>) -> Result<bool> {
+        out.append(&mut self.lines);
+        Ok(true)
+    }
+}
+
+/// A job running on its own thread. Lines the thread sends become the
+/// job's output.
+pub struct ThreadJob {
+    lines: Receiver<String>,
+    handle: Option<JoinHandle<Result<()>>>,
+}
+
+impl ThreadJob {
+    /// Run `work` on a new thread.
+    pub fn spawn<F>(work: F) -> Self
+    where
+        F: FnOnce(&Sender<String>) -> Result<()> + Send + 'static,
+    {
+        let (tx, rx) = mpsc::channel();
+        let handle = std::thread::spawn(move || work(&tx));
+        Self {
+            lines: rx,
+            handle: Some(handle),
+        }
+    }
+}
+
+impl Job for ThreadJob {
+    fn step(&mut self, _env: &mut Environment<'_>, out: &mut Vec<String>) -> Result<bool> {
+        out.extend(self.lines.try_iter());
+        let Some(handle) = self.handle.take_if(|h| h.is_finished()) else {
+            return Ok(self.handle.is_none());
+        };
+        let result = handle
+            .join()
+            .unwrap_or_else(|_| Err(OasisError::Command("job panicked".to_string())));
+        out.extend(self.lines.try_iter());
+        result.map(|()| true)
+    }
+
+    fn finish(&mut self, _env: &mut Environment<'_>, out: &mut Vec<String>) -> Result<()> {
+        // Lines arrive until the thread ends and drops its sender.
+        out.extend(self.lines.iter());
+        let Some(handle) = self.handle.take() else {
+            return Ok(());
+        };
+        handle
+            .join()
+            .unwrap_or_else(|_| Err(OasisError::Command("job panicked".to_string())))
+    }
+}
+
+/// Where a job is in its life.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum JobState {
+    Running,
+    Done,
+    /// Finished with an error.
+    Failed(String),
+    /// Stopped by `kill`.
+    Killed,
+}
+
+impl JobState {
+    fn label(&self) -> &str {
+        match self {
+            Self::Running => "Running",
+            Self::Done => "Done",
+            Self::Failed(_) => "Failed",
+            Self::Killed => "Killed",
+        }
+    }
+}
+
+struct JobEntry {
+    id: u32,
+    command: String,
+    job: Box<dyn Job>,
+    cancel: CancelToken,
+    state: JobState,
+    /// Output [`JobTable::take_output`] has not passed on yet.
+    output: Vec<String>,
+    /// The first line the job printed, for `jobs`.
+    first_line: Option<String>,
+}
+
+impl JobEntry {
+    fn step(&mut self, env: &mut Environment<'_>) {
+        if self.state != JobState::Running {
+            return;
+        }
+        if self.cancel.is_cancelled() {
+            self.state = JobState::Killed;
+            return;
+        }
+        match self.job.step(env, &mut self.output) {
+            Ok(false) => {},
+            Ok(true) => self.state = JobState::Done,
+            Err(e) => self.state = JobState::Failed(e.to_string()),
+        }
+        self.note_first_line();
+    }
+
+    /// Run the job to its end, unless it was killed.
+    fn finish(&mut self, env: &mut Environment<'_>) {
+        if self.state != JobState::Running {
+            return;
+        }
+        if self.cancel.is_cancelled() {
+            self.state = JobState::Killed;
+            return;
+        }
+        self.state = match self.job.finish(env, &mut self.output) {
+            Ok(()) => JobState::Done,
+            Err(e) => JobState::Failed(e.to_string()),
+        };
+        self.note_first_line();
+    }
+
+    fn note_first_line(&mut self) {
+        if self.first_line.is_none() {
+            self.first_line = self.output.first().cloned();
+        }
+    }
+}
+
+/// The jobs of one shell.
+#[derive(Default)]
+pub(crate) struct JobTable {
+    next_id: u32,
+    jobs: Vec<JobEntry>,
+}
+
+impl JobTable {
+    /// Track a new job. Returns its id.
+    pub(crate) fn add(&mut self, command: &str, job: Box<dyn Job>, cancel: CancelToken) -> u32 {
+        self.next_id += 1;
+        self.jobs.push(JobEntry {
+            id: self.next_id,
+            command: command.to_string(),
+            job,
+            cancel,
+            state: JobState::Running,
+            output: Vec::new(),
+            first_line: None,
+        });
+        self.next_id
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.jobs.is_empty()
+    }
+
+    /// Advance every running job once.
+    pub(crate) fn poll(&mut self, env: &mut Environment<'_>) {
+        for entry in &mut self.jobs {
+            entry.step(env);
+        }
+    }
+
+    /// Output printed since the last call, each line tagged with its job
+    /// id, followed by a notice for every job that ended. Ended jobs are
+    /// forgotten once announced.
+    pub(crate) fn take_output(&mut self) -> Vec<String> {
+        let mut lines = Vec::new();
+        for entry in &mut self.jobs {
+            for line in entry.output.drain(..) {
+                lines.push(format!("[{}] {line}", entry.id));
+            }
+            if entry.state != JobState::Running {
+                lines.push(end_notice(entry));
+            }
+        }
+        self.jobs.retain(|e| e.state == JobState::Running);
+        lines
+    }
+
+    /// One row per job (id, state, command, first output line). Ended
+    /// jobs are forgotten once listed.
+    pub(crate) fn list(&mut self) -> Vec<Vec<String>> {
+        let rows = self
+            .jobs
+            .iter()
+            .map(|e| {
+                vec![
+                    e.id.to_string(),
+                    e.state.label().to_string(),
+                    e.command.clone(),
+                    e.first_line.clone().unwrap_or_default(),
+                ]
+            })
+            .collect();
+        self.jobs.retain(|e| e.state == JobState::Running);
+        rows
+    }
+
+    /// Cancel job `id`.
+    pub(crate) fn kill(&mut self, id: u32) -> Result<()> {
+        let entry = self.get_mut(id)?;
+        if entry.state != JobState::Running {
+            return Err(OasisError::Command(format!("kill: job {id} has ended")));
+        }
+        entry.cancel.cancel();
+        Ok(())
+    }
+
+    /// Wait for job `id` to end and return the output not passed on yet.
+    /// The job is forgotten afterwards.
+    pub(crate) fn wait(&mut self, id: u32, env: &mut Environment<'_>) -> Result<String> {
+        let entry = self.get_mut(id)?;
+        entry.finish(env);
+        let mut text = entry.output.join("\n");
+        if entry.state != JobState::Done {
+            if !text.is_empty() {
+                text.push('\n');
+            }
+            text.push_str(&end_notice(entry));
+        }
+        self.jobs.retain(|e| e.id != id);
+        Ok(text)
+    }
+
+    fn get_mut(&mut self, id: u32) -> Result<&mut JobEntry> {
+        self.jobs
+            .iter_mut()
+            .find(|e| e.id == id)
+            .ok_or_else(|| OasisError::Command(format!("no such job: {id}")))
+    }
+}
+
+fn end_notice(entry: &JobEntry) -> String {
+    match &entry.state {
+        JobState::Failed(e) => format!("[{}] Failed ({e}): {}", entry.id, entry.command),
+        state => format!("[{}] {}: {}", entry.id, state.label(), entry.command),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Command, CommandRegistry};
+    use oasis_vfs::MemoryVfs;
+
+    /// `count N`: a cooperative job printing 1..=N, one line per step.
+    struct CountCmd;
+    impl Command for CountCmd {
+        fn name(&self) -> &str {
+            "count"
+        }
+        fn description(&self) -> &str {
+            "Count up"
+        }
+        fn usage(&self) -> &str {
+            "count <n>"
+        }
+        fn execute(&self, _args: &[&str], _env: &mut Environment<'_>) -> Result<CommandOutput> {
+            Ok(CommandOutput::Text("counted".to_string()))
+        }
+        fn spawn(
+            &self,
+            args: &[&str],
+            _env: &mut Environment<'_>,
+            _cancel: CancelToken,
+        ) -> Result<Box<dyn Job>> {
+            let n = args[0].parse().unwrap();
+            Ok(Box::new(CountJob { next: 1, n }))
+        }
+    }
+
+    struct CountJob {
+        next: u32,
+        n: u32,
+    }
+    impl Job for CountJob {
+        fn step(&mut self, _env: &mut Environment<'_>, out: &mut Vec<String>) -> Result<bool> {
+            out.push(self.next.to_string());
+            self.next += 1;
+            Ok(self.next > self.n)
+        }
+    }
+
+    fn setup() -> (CommandRegistry, MemoryVfs) {
+        let mut reg = CommandRegistry::new();
+        crate::register_builtins(&mut reg);
+        reg.register(Box::new(CountCmd));
+        (reg, MemoryVfs::new())
+    }
+
+    fn env(vfs: &mut MemoryVfs) -> Environment<'_> {
+        Environment {
+            cwd: "/".to_string(),
+            vfs,
+            power: None,
+            time: None,
+            usb: None,
+            network: None,
+            tls: None,
+            stdin: None,
+            enforce_permissions: false,
+        }
+    }
+
+    fn text(output: CommandOutput) -> String {
+        match output {
+            CommandOutput::Text(s) => s,
+            other => panic!("expected text, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn background_output_is_queued_until_flushed() {
+        let (reg, mut vfs) = setup();
+        let mut env = env(&mut vfs);
+        let started = reg.execute("count 2 &", &mut env).unwrap();
+        assert_eq!(text(started), "[1] count 2");
+        assert!(reg.take_job_output().is_empty());
+
+        reg.poll_jobs(&mut env);
+        assert_eq!(reg.take_job_output(), ["[1] 1"]);
+        reg.poll_jobs(&mut env);
+        reg.poll_jobs(&mut env);
+        assert_eq!(reg.take_job_output(), ["[1] 2", "[1] Done: count 2"]);
+        assert!(reg.take_job_output().is_empty());
+        // Announced jobs are forgotten.
+        assert!(!reg.has_jobs());
+    }
+
+    #[test]
+    fn fg_returns_only_output_not_yet_shown() {
+        let (reg, mut vfs) = setup();
+        let mut env = env(&mut vfs);
+        reg.execute("count 3 &", &mut env).unwrap();
+        reg.poll_jobs(&mut env);
+        assert_eq!(reg.take_job_output(), ["[1] 1"]);
+        match reg.execute("jobs", &mut env).unwrap() {
+            CommandOutput::Table { rows, .. } => {
+                assert_eq!(rows, [["1", "Running", "count 3", "1"]]);
+            },
+            other => panic!("expected table, got {other:?}"),
+        }
+        assert_eq!(text(reg.execute("fg 1", &mut env).unwrap()), "2\n3");
+    }
+
+    #[test]
+    fn jobs_lists_and_fg_waits() {
+        let (reg, mut vfs) = setup();
+        let mut env = env(&mut vfs);
+        reg.execute("count 3 &", &mut env).unwrap();
+        reg.poll_jobs(&mut env);
+        match reg.execute("jobs", &mut env).unwrap() {
+            CommandOutput::Table { rows, .. } => {
+                assert_eq!(rows, [["1", "Running", "count 3", "1"]]);
+            },
+            other => panic!("expected table, got {other:?}"),
+        }
+        assert_eq!(text(reg.execute("fg %1", &mut env).unwrap()), "1\n2\n3");
+        assert_eq!(text(reg.execute("jobs", &mut env).unwrap()), "No jobs.");
+        assert!(!reg.has_jobs());
+        assert!(reg.execute("fg 1", &mut env).is_err());
+    }
+
+    #[test]
+    fn kill_cancels_a_job() {
+        let (reg, mut vfs) = setup();
+        let mut env = env(&mut vfs);
+        reg.execute("count 100 &", &mut env).unwrap();
+        reg.execute("kill 1", &mut env).unwrap();
+        reg.poll_jobs(&mut env);
+        assert_eq!(reg.take_job_output(), ["[1] Killed: count 100"]);
+        assert!(reg.execute("kill 1", &mut env).is_err());
+        assert!(reg.execute("kill 7", &mut env).is_err());
+        assert!(reg.execute("kill", &mut env).is_err());
+    }
+
+    #[test]
+    fn thread_jobs_run_and_can_be_killed() {
+        let (reg, mut vfs) = setup();
+        let mut env = env(&mut vfs);
+        reg.execute("sleep 0.1 &", &mut env).unwrap();
+        assert_eq!(text(reg.execute("fg 1", &mut env).unwrap()), "slept 0.1s");
+
+        reg.execute("sleep 60 &", &mut env).unwrap();
+        reg.execute("kill 2", &mut env).unwrap();
+        assert_eq!(
+            text(reg.execute("fg 2", &mut env).unwrap()),
+            "[2] Killed: sleep 60"
+        );
+    }
+
+    #[test]
+    fn single_ampersand_separates_commands() {
+        let (reg, mut vfs) = setup();
+        let mut env = env(&mut vfs);
+        // Commands without a background mode finish when started.
+        let out = text(reg.execute("echo a & echo b", &mut env).unwrap());
+        assert_eq!(out, "[1] echo a\nb");
+        reg.poll_jobs(&mut env);
+        assert_eq!(reg.take_job_output(), ["[1] a", "[1] Done: echo a"]);
+
+        // Pipelines and built-ins are tracked as finished jobs too.
+        reg.execute("echo x | cat &", &mut env).unwrap();
+        assert_eq!(text(reg.execute("fg 2", &mut env).unwrap()), "x");
+        assert!(reg.execute("& echo", &mut env).is_err());
+        assert_eq!(
+            text(reg.execute("echo a && echo b", &mut env).unwrap()),
+            "a\nb"
+        );
+    }
+
+    #[test]
+    fn signals_cannot_run_in_the_background() {
+        let (reg, mut vfs) = setup();
+        let mut env = env(&mut vfs);
+        assert!(reg.execute("clear &", &mut env).is_err());
+        assert!(!reg.has_jobs());
+    }
+}
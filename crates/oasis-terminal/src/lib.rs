@@ -6,12 +6,16 @@
 
 pub mod audio_commands;
 mod commands;
+pub mod completion;
 pub mod dev_commands;
 pub mod doc_commands;
 pub mod file_commands;
 pub mod fun_commands;
 mod interpreter;
+pub mod jobs;
 pub mod network_commands;
+pub mod schedule;
+mod script;
 pub mod security_commands;
 pub mod skin_commands;
 pub mod system_commands;
@@ -22,6 +26,8 @@ pub mod ui_commands;
 pub use audio_commands::register_audio_commands;
 /// Register all built-in commands (fs, system, network, audio, skin) into a registry.
 pub use commands::register_builtins;
+/// Tab completion state for the terminal input line.
+pub use completion::Completer;
 /// Register developer tool commands (base64, json, uuid, seq, expr, test, xargs).
 pub use dev_commands::register_dev_commands;
 /// Populate default man pages in the VFS.
@@ -42,10 +48,27 @@ pub use interpreter::Command;
 pub use interpreter::CommandOutput;
 /// Registry of available commands with dispatch.
 pub use interpreter::CommandRegistry;
+/// Whether the `enforce-permissions` feature is enabled.
+pub use interpreter::ENFORCE_PERMISSIONS;
 /// Shared mutable environment passed to every command.
 pub use interpreter::Environment;
+/// VFS file the command history is saved to.
+pub use interpreter::HISTORY_PATH;
+/// Bounded command history with Up/Down recall.
+pub use interpreter::History;
+/// VFS file run as the shell profile at startup.
+pub use interpreter::PROFILE_PATH;
+pub use interpreter::format_json;
+/// Resolve a path argument against the working directory.
+pub use interpreter::resolve_path;
 /// Register network commands (wifi, ping, http) into a registry.
 pub use network_commands::register_network_commands;
+/// VFS file scheduled commands are saved to.
+pub use schedule::SCHEDULE_PATH;
+/// Script run at startup, after the shell profile.
+pub use script::RC_PATH;
+/// What a script run produced (output, exit status).
+pub use script::ScriptOutcome;
 /// Register security commands (chmod, chown, passwd, audit).
 pub use security_commands::register_security_commands;
 /// Register skin management commands (skin list/switch) into a registry.
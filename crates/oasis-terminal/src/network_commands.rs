@@ -1,5 +1,6 @@
 //! WiFi / network terminal commands.
 
+use oasis_platform::HttpResponse;
 use oasis_types::error::{OasisError, Result};
 
 use crate::{Command, CommandOutput, CommandRegistry, Environment};
@@ -8,7 +9,7 @@ use crate::{Command, CommandOutput, CommandRegistry, Environment};
 pub fn register_network_commands(reg: &mut CommandRegistry) {
     reg.register(Box::new(WifiCmd));
     reg.register(Box::new(PingCmd));
-    reg.register(Box::new(HttpCmd));
+    reg.register(Box::new(HttpCmd::new(platform_fetch)));
 }
 
 // ---------------------------------------------------------------------------
@@ -121,64 +122,91 @@ impl Command for PingCmd {
 }
 
 // ---------------------------------------------------------------------------
-// http (HTTP GET via platform network service)
+// http
 // ---------------------------------------------------------------------------
 
-/// Terminal command for performing HTTP GET requests via platform network service.
-struct HttpCmd;
+/// How `http` makes a request: GET `url`, or with `head` only the status
+/// and headers are wanted (a body is ignored).
+pub type HttpFetch = fn(url: &str, head: bool, env: &mut Environment<'_>) -> Result<HttpResponse>;
+
+/// `http <url>` and `http head <url>`. Registered over the platform
+/// network service; a frontend with its own HTTP client registers it
+/// again with a different [`HttpFetch`].
+pub struct HttpCmd {
+    fetch: HttpFetch,
+}
+
+impl HttpCmd {
+    pub fn new(fetch: HttpFetch) -> Self {
+        Self { fetch }
+    }
+}
+
 impl Command for HttpCmd {
     fn name(&self) -> &str {
         "http"
     }
     fn description(&self) -> &str {
-        "HTTP GET request"
+        "HTTP GET request, or show the response headers"
     }
     fn usage(&self) -> &str {
-        "http <url>"
+        "http <url> | http head <url>"
     }
     fn category(&self) -> &str {
         "network"
     }
     fn execute(&self, args: &[&str], env: &mut Environment<'_>) -> Result<CommandOutput> {
-        if args.is_empty() {
-            return Err(OasisError::Command("usage: http <url>".to_string()));
-        }
-        let Some(net) = env.network else {
-            return Ok(CommandOutput::Text(
-                "http: no network service available".to_string(),
-            ));
+        let (head, url) = match args {
+            ["head", url] => (true, *url),
+            [url] if *url != "head" => (false, *url),
+            _ => return Err(OasisError::Command(format!("usage: {}", self.usage()))),
         };
-        let url = args[0];
-        match net.http_get(url) {
-            Ok(resp) => {
-                let body_text = String::from_utf8_lossy(&resp.body);
-                // Truncate long responses for terminal display.
-                let truncated = if body_text.len() > 2048 {
-                    let end = body_text.floor_char_boundary(2048);
-                    format!(
-                        "{}...\n(truncated, {} bytes total)",
-                        &body_text[..end],
-                        resp.body.len()
-                    )
-                } else {
-                    body_text.into_owned()
-                };
-                Ok(CommandOutput::Text(format!(
-                    "HTTP {} ({})\n{}",
-                    resp.status_code,
-                    resp.body.len(),
-                    truncated,
-                )))
-            },
-            Err(e) => Ok(CommandOutput::Text(format!("http: {e}"))),
+        let resp = (self.fetch)(url, head, env)?;
+        if head {
+            let mut lines = vec![format!("HTTP {}", resp.status_code)];
+            lines.extend(
+                resp.headers
+                    .iter()
+                    .map(|(name, value)| format!("{name}: {value}")),
+            );
+            return Ok(CommandOutput::Text(lines.join("\n")));
         }
+        let body_text = String::from_utf8_lossy(&resp.body);
+        // Truncate long responses for terminal display.
+        let truncated = if body_text.len() > 2048 {
+            let end = body_text.floor_char_boundary(2048);
+            format!(
+                "{}...\n(truncated, {} bytes total)",
+                &body_text[..end],
+                resp.body.len()
+            )
+        } else {
+            body_text.into_owned()
+        };
+        Ok(CommandOutput::Text(format!(
+            "HTTP {} ({})\n{}",
+            resp.status_code,
+            resp.body.len(),
+            truncated,
+        )))
     }
 }
 
+/// [`HttpFetch`] through the platform network service.
+fn platform_fetch(url: &str, _head: bool, env: &mut Environment<'_>) -> Result<HttpResponse> {
+    let Some(net) = env.network else {
+        return Err(OasisError::Command(
+            "http: no network service available".to_string(),
+        ));
+    };
+    net.http_get(url)
+        .map_err(|e| OasisError::Command(format!("http: {url}: {e}")))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use oasis_platform::DesktopPlatform;
+    use oasis_platform::{DesktopPlatform, NetworkService, WifiInfo};
 
     use crate::Environment;
     use oasis_vfs::MemoryVfs;
@@ -197,6 +225,7 @@ mod tests {
             network: None,
             tls: None,
             stdin: None,
+            enforce_permissions: false,
         };
         match reg.execute("wifi", &mut env).unwrap() {
             CommandOutput::Text(s) => assert!(s.contains("no network service")),
@@ -219,6 +248,7 @@ mod tests {
             network: Some(&platform),
             tls: None,
             stdin: None,
+            enforce_permissions: false,
         };
         match reg.execute("wifi", &mut env).unwrap() {
             CommandOutput::Text(s) => {
@@ -243,7 +273,55 @@ mod tests {
             network: None,
             tls: None,
             stdin: None,
+            enforce_permissions: false,
         };
         assert!(reg.execute("ping", &mut env).is_err());
     }
+
+    /// Answers every request with a fixed response.
+    struct FixedHttp;
+
+    impl NetworkService for FixedHttp {
+        fn wifi_info(&self) -> Result<WifiInfo> {
+            Err(OasisError::Platform("no WiFi".to_string()))
+        }
+        fn http_get(&self, _url: &str) -> Result<HttpResponse> {
+            Ok(HttpResponse {
+                status_code: 200,
+                headers: vec![("Content-Type".to_string(), "text/plain".to_string())],
+                body: b"hello".to_vec(),
+            })
+        }
+    }
+
+    #[test]
+    fn http_get_and_head() {
+        let mut reg = CommandRegistry::new();
+        register_network_commands(&mut reg);
+        let mut vfs = MemoryVfs::new();
+        let mut env = Environment {
+            cwd: "/".to_string(),
+            vfs: &mut vfs,
+            power: None,
+            time: None,
+            usb: None,
+            network: None,
+            tls: None,
+            stdin: None,
+            enforce_permissions: false,
+        };
+        let err = reg.execute("http http://a/", &mut env).unwrap_err();
+        assert!(err.to_string().contains("no network service"), "{err}");
+
+        env.network = Some(&FixedHttp);
+        match reg.execute("http http://a/", &mut env).unwrap() {
+            CommandOutput::Text(s) => assert_eq!(s, "HTTP 200 (5)\nhello"),
+            _ => panic!("expected text"),
+        }
+        match reg.execute("http head http://a/", &mut env).unwrap() {
+            CommandOutput::Text(s) => assert_eq!(s, "HTTP 200\nContent-Type: text/plain"),
+            _ => panic!("expected text"),
+        }
+        assert!(reg.execute("http head", &mut env).is_err());
+    }
 }
@@ -0,0 +1,509 @@
+//! Periodic commands: `schedule add/list/remove/log`.
+//!
+//! Each entry runs a command every N seconds: a single argument is a
+//! command line (quote it to use pipes or chains), several are a command
+//! and its arguments, passed on exactly as given. The frontend calls
+//! [`CommandRegistry::run_schedule`] between frames with a monotonic
+//! clock; due commands run through the registry and their output goes to
+//! a bounded log that `schedule log` prints. Entries are saved to
+//! [`SCHEDULE_PATH`] and read back by [`CommandRegistry::load_schedule`].
+//!
+//! A command that fell due several times while the clock was not ticked
+//! (for example while the PSP was asleep) runs once, and keeps its
+//! original cadence afterwards.
+
+use std::collections::VecDeque;
+
+use oasis_types::error::{OasisError, Result};
+use oasis_vfs::Vfs;
+
+use crate::interpreter::{CommandOutput, CommandRegistry, Environment, tokenize};
+use crate::script::collect_output;
+
+/// VFS file the schedule is saved to, one `<interval> <args...>` per
+/// line, with the arguments quoted as needed.
+pub const SCHEDULE_PATH: &str = "/home/.schedule";
+
+/// Lines kept in the schedule log.
+pub const MAX_SCHEDULE_LOG: usize = 200;
+
+/// Scheduled commands allowed at once.
+pub const MAX_SCHEDULE_ENTRIES: usize = 32;
+
+const USAGE: &str = "usage: schedule [add <interval> <command...> | list | remove <id> | log]";
+
+/// Parse an interval: seconds (`90`, `90s`), minutes (`5m`), hours
+/// (`2h`), `@minute`, `@hour` or `@day`.
+pub fn parse_interval(spec: &str) -> Result<u64> {
+    let secs = match spec {
+        "@minute" => Some(60),
+        "@hour" => Some(3_600),
+        "@day" | "@daily" => Some(86_400),
+        _ => {
+            let (digits, unit) = match spec.as_bytes().last() {
+                Some(b's') => (&spec[..spec.len() - 1], 1),
+                Some(b'm') => (&spec[..spec.len() - 1], 60),
+                Some(b'h') => (&spec[..spec.len() - 1], 3_600),
+                _ => (spec, 1),
+            };
+            digits
+                .parse::<u64>()
+                .ok()
+                .filter(|&n| n > 0)
+                .and_then(|n| n.checked_mul(unit))
+        },
+    };
+    secs.ok_or_else(|| {
+        OasisError::Command(format!(
+            "schedule: invalid interval '{spec}' (use 90, 30s, 5m, 2h, @minute or @hour)"
+        ))
+    })
+}
+
+/// One scheduled command.
+#[derive(Debug, Clone)]
+pub struct ScheduleEntry {
+    pub id: u32,
+    /// The interval as written.
+    pub spec: String,
+    pub interval_secs: u64,
+    /// The command as given to `schedule add`.
+    pub args: Vec<String>,
+    /// Times the command has run since startup.
+    pub runs: u32,
+    /// Clock reading the command is next due at; unset until the first
+    /// tick after the entry was added or loaded.
+    next_due_ms: Option<u64>,
+}
+
+impl ScheduleEntry {
+    /// The command line run: a lone argument as is, or the arguments
+    /// quoted so they reach the command unchanged.
+    pub fn command_line(&self) -> String {
+        match self.args.as_slice() {
+            [line] => line.clone(),
+            args => quote_args(args),
+        }
+    }
+}
+
+/// Join `args` into a line that [`tokenize`] splits back into them.
+fn quote_args(args: &[String]) -> String {
+    let quoted: Vec<String> = args.iter().map(|arg| quote_word(arg)).collect();
+    quoted.join(" ")
+}
+
+/// Quote `word` unless it is made only of characters the shell leaves
+/// alone.
+fn quote_word(word: &str) -> String {
+    let plain = |c: char| c.is_alphanumeric() || "-_./:=@%+,".contains(c);
+    if !word.is_empty() && word.chars().all(plain) {
+        word.to_string()
+    } else if !word.contains('\'') {
+        format!("'{word}'")
+    } else {
+        let mut quoted = String::from('"');
+        for c in word.chars() {
+            if matches!(c, '"' | '\\' | '$') {
+                quoted.push('\\');
+            }
+            quoted.push(c);
+        }
+        quoted.push('"');
+        quoted
+    }
+}
+
+/// The scheduled commands and their output log.
+#[derive(Debug, Default)]
+pub struct Scheduler {
+    entries: Vec<ScheduleEntry>,
+    next_id: u32,
+    log: VecDeque<String>,
+}
+
+impl Scheduler {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn entries(&self) -> &[ScheduleEntry] {
+        &self.entries
+    }
+
+    /// Add a command to run every `spec`. Returns its id.
+    pub fn add(&mut self, spec: &str, args: &[&str]) -> Result<u32> {
+        let interval_secs = parse_interval(spec)?;
+        if args.iter().all(|arg| arg.trim().is_empty()) {
+            return Err(OasisError::Command(USAGE.to_string()));
+        }
+        if self.entries.len() >= MAX_SCHEDULE_ENTRIES {
+            return Err(OasisError::Command(format!(
+                "schedule: at most {MAX_SCHEDULE_ENTRIES} commands can be scheduled"
+            )));
+        }
+        self.next_id += 1;
+        self.entries.push(ScheduleEntry {
+            id: self.next_id,
+            spec: spec.to_string(),
+            interval_secs,
+            args: args.iter().map(|arg| arg.to_string()).collect(),
+            runs: 0,
+            next_due_ms: None,
+        });
+        Ok(self.next_id)
+    }
+
+    /// Remove entry `id`. Returns whether it existed.
+    pub fn remove(&mut self, id: u32) -> bool {
+        let before = self.entries.len();
+        self.entries.retain(|e| e.id != id);
+        self.entries.len() != before
+    }
+
+    /// The `(id, command)` of every entry due at `now_ms`, scheduling
+    /// each for its next run.
+    ///
+    /// An entry's first run is one interval after the first tick that
+    /// sees it. An entry overdue by several intervals is returned once
+    /// and moved to its next slot after `now_ms`.
+    pub fn take_due(&mut self, now_ms: u64) -> Vec<(u32, String)> {
+        let mut due = Vec::new();
+        for entry in &mut self.entries {
+            let interval_ms = entry.interval_secs.saturating_mul(1000);
+            match entry.next_due_ms {
+                None => entry.next_due_ms = Some(now_ms.saturating_add(interval_ms)),
+                Some(next) if now_ms >= next => {
+                    let missed = (now_ms - next) / interval_ms;
+                    let slots = (missed + 1).saturating_mul(interval_ms);
+                    entry.next_due_ms = Some(next.saturating_add(slots));
+                    entry.runs += 1;
+                    due.push((entry.id, entry.command_line()));
+                },
+                Some(_) => {},
+            }
+        }
+        due
+    }
+
+    /// Append a line to the log, dropping the oldest past
+    /// [`MAX_SCHEDULE_LOG`].
+    pub fn push_log(&mut self, line: String) {
+        if self.log.len() >= MAX_SCHEDULE_LOG {
+            self.log.pop_front();
+        }
+        self.log.push_back(line);
+    }
+
+    /// Log lines, oldest first.
+    pub fn log(&self) -> impl Iterator<Item = &str> {
+        self.log.iter().map(String::as_str)
+    }
+
+    /// The entries as saved to [`SCHEDULE_PATH`].
+    pub fn to_text(&self) -> String {
+        self.entries
+            .iter()
+            .map(|e| format!("{} {}\n", e.spec, quote_args(&e.args)))
+            .collect()
+    }
+
+    /// Add the entries of a saved schedule. Blank lines and `#` comments
+    /// are skipped; invalid lines are reported in the log.
+    pub fn load_text(&mut self, text: &str) {
+        for (n, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let (spec, command) = line.split_once(char::is_whitespace).unwrap_or((line, ""));
+            let added = tokenize(command).and_then(|args| {
+                let args: Vec<&str> = args.iter().map(String::as_str).collect();
+                self.add(spec, &args)
+            });
+            if let Err(e) = added {
+                self.push_log(format!("{SCHEDULE_PATH}:{}: {e}", n + 1));
+            }
+        }
+    }
+}
+
+impl CommandRegistry {
+    /// Whether any commands are scheduled.
+    pub fn has_schedule(&self) -> bool {
+        !self.scheduler().borrow().is_empty()
+    }
+
+    /// Add the entries saved at [`SCHEDULE_PATH`]. A missing file is not
+    /// an error.
+    pub fn load_schedule(&self, vfs: &dyn Vfs) -> Result<()> {
+        if !vfs.exists(SCHEDULE_PATH) {
+            return Ok(());
+        }
+        let data = vfs.read(SCHEDULE_PATH)?;
+        self.scheduler()
+            .borrow_mut()
+            .load_text(&String::from_utf8_lossy(&data));
+        Ok(())
+    }
+
+    /// Run the scheduled commands due at `now_ms`, a monotonic clock in
+    /// milliseconds. Frontends call this between frames.
+    pub fn run_schedule(&self, now_ms: u64, env: &mut Environment<'_>) {
+        let due = self.scheduler().borrow_mut().take_due(now_ms);
+        for (id, command) in due {
+            let mut lines = Vec::new();
+            match self.execute_line(&command, env) {
+                Ok(output) => collect_output(output, &mut lines),
+                Err(e) => lines.push(format!("error: {e}")),
+            }
+            let mut scheduler = self.scheduler().borrow_mut();
+            scheduler.push_log(format!("[{}s] #{id} {command}", now_ms / 1000));
+            for line in lines {
+                scheduler.push_log(format!("  {line}"));
+            }
+        }
+    }
+
+    /// Built-in `schedule`.
+    pub(crate) fn execute_schedule(
+        &self,
+        args: &[&str],
+        env: &mut Environment<'_>,
+    ) -> Result<CommandOutput> {
+        match args {
+            [] | ["list"] => {
+                let scheduler = self.scheduler().borrow();
+                if scheduler.is_empty() {
+                    return Ok(CommandOutput::Text("No scheduled commands.".to_string()));
+                }
+                let rows = scheduler
+                    .entries()
+                    .iter()
+                    .map(|e| {
+                        vec![
+                            e.id.to_string(),
+                            e.spec.clone(),
+                            e.runs.to_string(),
+                            e.command_line(),
+                        ]
+                    })
+                    .collect();
+                Ok(CommandOutput::Table {
+                    headers: ["ID", "EVERY", "RUNS", "COMMAND"]
+                        .map(String::from)
+                        .to_vec(),
+                    rows,
+                })
+            },
+            ["add", spec, args @ ..] if !args.is_empty() => {
+                let id = self.scheduler().borrow_mut().add(spec, args)?;
+                self.save_schedule(env.vfs)?;
+                Ok(CommandOutput::Text(format!(
+                    "Scheduled #{id} every {spec}."
+                )))
+            },
+            ["remove", id] => {
+                let id: u32 = id
+                    .trim_start_matches('#')
+                    .parse()
+                    .map_err(|_| OasisError::Command(format!("schedule: invalid id: {id}")))?;
+                if !self.scheduler().borrow_mut().remove(id) {
+                    return Err(OasisError::Command(format!("schedule: no entry #{id}")));
+                }
+                self.save_schedule(env.vfs)?;
+                Ok(CommandOutput::None)
+            },
+            ["log"] => {
+                let scheduler = self.scheduler().borrow();
+                let text: Vec<&str> = scheduler.log().collect();
+                if text.is_empty() {
+                    return Ok(CommandOutput::Text("(schedule log is empty)".to_string()));
+                }
+                Ok(CommandOutput::Text(text.join("\n")))
+            },
+            _ => Err(OasisError::Command(USAGE.to_string())),
+        }
+    }
+
+    /// Write the schedule to [`SCHEDULE_PATH`].
+    fn save_schedule(&self, vfs: &mut dyn Vfs) -> Result<()> {
+        if let Some((parent, _)) = SCHEDULE_PATH.rsplit_once('/') {
+            let _ = vfs.mkdir(parent);
+        }
+        let text = self.scheduler().borrow().to_text();
+        vfs.write(SCHEDULE_PATH, text.as_bytes())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use oasis_vfs::MemoryVfs;
+
+    fn setup() -> (CommandRegistry, MemoryVfs) {
+        let mut reg = CommandRegistry::new();
+        crate::register_builtins(&mut reg);
+        (reg, MemoryVfs::new())
+    }
+
+    fn env(vfs: &mut MemoryVfs) -> Environment<'_> {
+        Environment {
+            cwd: "/".to_string(),
+            vfs,
+            power: None,
+            time: None,
+            usb: None,
+            network: None,
+            tls: None,
+            stdin: None,
+            enforce_permissions: false,
+        }
+    }
+
+    fn text(output: Result<CommandOutput>) -> String {
+        match output.unwrap() {
+            CommandOutput::Text(s) => s,
+            _ => panic!("expected text output"),
+        }
+    }
+
+    #[test]
+    fn intervals() {
+        assert_eq!(parse_interval("90").unwrap(), 90);
+        assert_eq!(parse_interval("30s").unwrap(), 30);
+        assert_eq!(parse_interval("5m").unwrap(), 300);
+        assert_eq!(parse_interval("2h").unwrap(), 7_200);
+        assert_eq!(parse_interval("@minute").unwrap(), 60);
+        assert_eq!(parse_interval("@hour").unwrap(), 3_600);
+        for bad in ["0", "-5", "m", "5x", "@week", ""] {
+            assert!(parse_interval(bad).is_err(), "{bad}");
+        }
+    }
+
+    #[test]
+    fn due_commands_run_once_per_interval() {
+        let mut s = Scheduler::new();
+        s.add("10", &["echo a"]).unwrap();
+        s.add("@minute", &["echo", "b"]).unwrap();
+        // The first tick starts the clocks.
+        assert!(s.take_due(5_000).is_empty());
+        assert!(s.take_due(14_999).is_empty());
+        assert_eq!(s.take_due(15_000), [(1, "echo a".to_string())]);
+        assert!(s.take_due(20_000).is_empty());
+        assert_eq!(s.take_due(25_500), [(1, "echo a".to_string())]);
+        assert_eq!(s.take_due(65_000).len(), 2);
+        assert_eq!(s.entries()[0].runs, 3);
+    }
+
+    #[test]
+    fn missed_runs_are_collapsed() {
+        let mut s = Scheduler::new();
+        s.add("10", &["echo a"]).unwrap();
+        s.take_due(0);
+        // Asleep for an hour: one run, then the regular cadence again.
+        assert_eq!(s.take_due(3_600_005).len(), 1);
+        assert!(s.take_due(3_609_999).is_empty());
+        assert_eq!(s.take_due(3_610_000).len(), 1);
+        assert_eq!(s.entries()[0].runs, 2);
+    }
+
+    #[test]
+    fn schedule_commands_and_log() {
+        let (reg, mut vfs) = setup();
+        let mut env = env(&mut vfs);
+        assert_eq!(
+            text(reg.execute("schedule add 5s echo tick", &mut env)),
+            "Scheduled #1 every 5s."
+        );
+        reg.execute("schedule add @hour nosuchcmd", &mut env)
+            .unwrap();
+        assert!(reg.execute("schedule add 5q echo", &mut env).is_err());
+        assert!(reg.execute("schedule add 5s", &mut env).is_err());
+        match reg.execute("schedule", &mut env).unwrap() {
+            CommandOutput::Table { rows, .. } => {
+                assert_eq!(rows.len(), 2);
+                assert_eq!(rows[0][3], "echo tick");
+            },
+            _ => panic!("expected table output"),
+        }
+
+        reg.run_schedule(0, &mut env);
+        reg.run_schedule(5_000, &mut env);
+        reg.run_schedule(3_600_000, &mut env);
+        let log = text(reg.execute("schedule log", &mut env));
+        let lines: Vec<&str> = log.lines().collect();
+        assert_eq!(lines[0], "[5s] #1 echo tick");
+        assert_eq!(lines[1], "  tick");
+        assert_eq!(lines[2], "[3600s] #1 echo tick");
+        assert_eq!(lines[4], "[3600s] #2 nosuchcmd");
+        assert!(lines[5].starts_with("  error:"));
+
+        reg.execute("schedule remove 1", &mut env).unwrap();
+        assert!(reg.execute("schedule remove 1", &mut env).is_err());
+        assert_eq!(reg.scheduler().borrow().entries().len(), 1);
+    }
+
+    #[test]
+    fn schedule_is_saved_and_loaded() {
+        let (reg, mut vfs) = setup();
+        {
+            let mut env = env(&mut vfs);
+            reg.execute("schedule add 5m 'echo a | wc -w'", &mut env)
+                .unwrap();
+            reg.execute("schedule add @hour echo b", &mut env).unwrap();
+            reg.execute("schedule add 10 echo c", &mut env).unwrap();
+            reg.execute("schedule remove 2", &mut env).unwrap();
+        }
+        assert_eq!(
+            vfs.read(SCHEDULE_PATH).unwrap(),
+            b"5m 'echo a | wc -w'\n10 echo c\n"
+        );
+
+        let (fresh, _) = setup();
+        fresh.load_schedule(&MemoryVfs::new()).unwrap();
+        assert!(!fresh.has_schedule());
+        let mut saved = vfs.read(SCHEDULE_PATH).unwrap();
+        saved.extend_from_slice(b"# comment\nbogus echo\n");
+        vfs.write(SCHEDULE_PATH, &saved).unwrap();
+        fresh.load_schedule(&vfs).unwrap();
+        let scheduler = fresh.scheduler().borrow();
+        let commands: Vec<String> = scheduler
+            .entries()
+            .iter()
+            .map(ScheduleEntry::command_line)
+            .collect();
+        assert_eq!(commands, ["echo a | wc -w", "echo c"]);
+        assert!(scheduler.log().next().unwrap().contains(":4:"));
+    }
+
+    #[test]
+    fn arguments_keep_their_quoting() {
+        let (reg, mut vfs) = setup();
+        {
+            let mut env = env(&mut vfs);
+            reg.execute(
+                r#"schedule add 5s echo "a  b" 'it'"'"'s' '$HOME'"#,
+                &mut env,
+            )
+            .unwrap();
+            reg.run_schedule(0, &mut env);
+            reg.run_schedule(5_000, &mut env);
+            let log = text(reg.execute("schedule log", &mut env));
+            assert_eq!(log.lines().nth(1), Some("  a  b it's $HOME"));
+        }
+
+        // The saved arguments load back unchanged.
+        let (fresh, _) = setup();
+        fresh.load_schedule(&vfs).unwrap();
+        let scheduler = fresh.scheduler().borrow();
+        assert_eq!(
+            scheduler.entries()[0].args,
+            ["echo", "a  b", "it's", "$HOME"]
+        );
+    }
+}
@@ -0,0 +1,879 @@
+//! Shell scripts: `sh <path> [args...]` (also `run`), the shell profile
+//! and `/etc/rc`.
+//!
+//! A script is parsed as a whole before it runs, so a missing `fi` or
+//! `done` is reported without running anything. Scripts have `#`
+//! comments, `;` between statements, `if`/`elif`/`else`/`fi`,
+//! `while`/`do`/`done`, `for x in a b c`/`do`/`done` (`for x` alone
+//! walks the arguments), positional arguments `$0`..`$9`, `$#` and `$@`,
+//! and `exit [n]`. Every other statement is a command line run through
+//! the registry, pipes, chains and redirection included. A failing
+//! command is reported with its line number and the script goes on.
+//!
+//! A condition holds when its command exits with status 0; `test` exits
+//! with status 1 when its expression is false.
+//!
+//! All scripts of one run, nested `sh` calls included, share a budget of
+//! [`MAX_SCRIPT_STEPS`] commands and nest at most [`MAX_SCRIPT_DEPTH`]
+//! deep, so a runaway script cannot hang the device.
+
+use std::cell::Cell;
+
+use oasis_types::error::{OasisError, Result};
+
+use crate::interpreter::{
+    CommandOutput, CommandRegistry, Environment, PROFILE_PATH, format_json, resolve_path,
+};
+
+/// Script run at startup, after the shell profile.
+pub const RC_PATH: &str = "/etc/rc";
+
+/// Commands (conditions and `for` word lists included) one script run
+/// may execute.
+pub const MAX_SCRIPT_STEPS: usize = 10_000;
+
+/// Iterations after which a `while` loop is stopped.
+pub const MAX_LOOP_ITERATIONS: usize = 1000;
+
+/// How deep scripts may call each other with `sh`.
+pub const MAX_SCRIPT_DEPTH: usize = 8;
+
+/// Variables holding the positional arguments while a script runs.
+const POSITIONAL: [&str; 12] = ["0", "1", "2", "3", "4", "5", "6", "7", "8", "9", "#", "@"];
+
+/// What a script run produced.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScriptOutcome {
+    /// Output of the script's commands, line by line.
+    pub output: Vec<String>,
+    /// Exit status: given to `exit`, or that of the last command.
+    pub status: i32,
+    /// Commands executed, conditions and `for` word lists included.
+    pub steps: usize,
+}
+
+/// Nesting depth and commands used by the script run in progress.
+#[derive(Debug, Default)]
+pub(crate) struct ScriptLimits {
+    depth: Cell<usize>,
+    steps: Cell<usize>,
+}
+
+impl ScriptLimits {
+    /// Whether a script is running.
+    pub(crate) fn running(&self) -> bool {
+        self.depth.get() > 0
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Parsing
+// ---------------------------------------------------------------------------
+
+/// A statement and the script line it starts on.
+#[derive(Debug, Clone)]
+struct Line {
+    number: usize,
+    text: String,
+}
+
+impl Line {
+    fn keyword(&self) -> &str {
+        self.text.split_whitespace().next().unwrap_or("")
+    }
+
+    /// The text after the keyword.
+    fn rest(&self) -> &str {
+        self.text[self.keyword().len()..].trim()
+    }
+}
+
+#[derive(Debug, Clone)]
+enum Stmt {
+    Command(Line),
+    If {
+        /// Conditions with the block run when they hold, `if` then each
+        /// `elif`.
+        branches: Vec<(Line, Vec<Stmt>)>,
+        otherwise: Vec<Stmt>,
+    },
+    While {
+        condition: Line,
+        body: Vec<Stmt>,
+    },
+    For {
+        var: String,
+        /// The words after `in`, unexpanded; `None` walks the arguments.
+        words: Option<Line>,
+        body: Vec<Stmt>,
+    },
+}
+
+/// Split a script into statements: comments and blank lines dropped,
+/// lines split at `;` outside quotes, and a command following `then`,
+/// `do` or `else` on the same line split off.
+fn split_statements(source: &str) -> Vec<Line> {
+    let mut lines = Vec::new();
+    for (i, raw) in source.lines().enumerate() {
+        let trimmed = raw.trim();
+        if trimmed.is_empty() || trimmed.starts_with('#') {
+            continue;
+        }
+        for piece in split_semicolons(trimmed) {
+            let mut text = piece.trim();
+            while !text.is_empty() {
+                let (word, rest) = text.split_once(char::is_whitespace).unwrap_or((text, ""));
+                let rest = rest.trim();
+                let split = matches!(word, "then" | "do" | "else") && !rest.is_empty();
+                lines.push(Line {
+                    number: i + 1,
+                    text: if split { word } else { text }.to_string(),
+                });
+                text = if split { rest } else { "" };
+            }
+        }
+    }
+    lines
+}
+
+/// The characters of `line` outside quotes and escapes, with their
+/// byte offsets.
+fn unquoted(line: &str) -> Vec<(usize, char)> {
+    let mut found = Vec::new();
+    let mut quote: Option<char> = None;
+    let mut chars = line.char_indices();
+    while let Some((i, c)) = chars.next() {
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (Some('"') | None, '\\') => {
+                chars.next();
+            },
+            (None, '"' | '\'') => quote = Some(c),
+            (None, c) => found.push((i, c)),
+            _ => {},
+        }
+    }
+    found
+}
+
+/// Split a line at each `;` outside quotes.
+fn split_semicolons(line: &str) -> Vec<&str> {
+    let mut pieces = Vec::new();
+    let mut start = 0;
+    for (i, _) in unquoted(line).into_iter().filter(|&(_, c)| c == ';') {
+        pieces.push(&line[start..i]);
+        start = i + 1;
+    }
+    pieces.push(&line[start..]);
+    pieces
+}
+
+fn syntax_error(line: &Line, message: &str) -> OasisError {
+    OasisError::Command(format!("line {}: {message}", line.number))
+}
+
+struct Parser {
+    lines: std::vec::IntoIter<Line>,
+}
+
+impl Parser {
+    /// Parse statements up to a line starting with one of `ends`, which
+    /// is returned too. `None` when the script ended first.
+    fn block(&mut self, ends: &[&str]) -> Result<(Vec<Stmt>, Option<Line>)> {
+        let mut stmts = Vec::new();
+        while let Some(line) = self.lines.next() {
+            let keyword = line.keyword();
+            if ends.contains(&keyword) {
+                return Ok((stmts, Some(line)));
+            }
+            let stmt = match keyword {
+                "if" => self.if_stmt(line)?,
+                "while" => {
+                    let opener = line.clone();
+                    let condition = Self::condition(line)?;
+                    self.expect("do", &opener)?;
+                    let body = self.body(&["done"], &opener)?.0;
+                    Stmt::While { condition, body }
+                },
+                "for" => self.for_stmt(line)?,
+                "then" | "do" | "else" | "elif" | "fi" | "done" => {
+                    return Err(syntax_error(&line, &format!("unexpected '{keyword}'")));
+                },
+                _ => Stmt::Command(line),
+            };
+            stmts.push(stmt);
+        }
+        Ok((stmts, None))
+    }
+
+    /// A block that must be closed by one of `ends`.
+    fn body(&mut self, ends: &[&str], opener: &Line) -> Result<(Vec<Stmt>, Line)> {
+        match self.block(ends)? {
+            (stmts, Some(end)) => Ok((stmts, end)),
+            (_, None) => Err(syntax_error(
+                opener,
+                &format!("'{}' without '{}'", opener.keyword(), ends[ends.len() - 1]),
+            )),
+        }
+    }
+
+    fn expect(&mut self, keyword: &str, after: &Line) -> Result<()> {
+        match self.lines.next() {
+            Some(line) if line.text == keyword => Ok(()),
+            _ => Err(syntax_error(after, &format!("expected '{keyword}'"))),
+        }
+    }
+
+    /// The condition of an `if`, `elif` or `while` line.
+    fn condition(line: Line) -> Result<Line> {
+        if line.rest().is_empty() {
+            return Err(syntax_error(
+                &line,
+                &format!("'{}' needs a condition", line.keyword()),
+            ));
+        }
+        Ok(Line {
+            number: line.number,
+            text: line.rest().to_string(),
+        })
+    }
+
+    fn if_stmt(&mut self, line: Line) -> Result<Stmt> {
+        let opener = line.clone();
+        let mut branches = Vec::new();
+        let mut condition = Self::condition(line)?;
+        loop {
+            self.expect("then", &condition)?;
+            let (block, end) = self.body(&["elif", "else", "fi"], &opener)?;
+            branches.push((condition, block));
+            match end.keyword() {
+                "elif" => condition = Self::condition(end)?,
+                "else" => {
+                    let otherwise = self.body(&["fi"], &opener)?.0;
+                    return Ok(Stmt::If {
+                        branches,
+                        otherwise,
+                    });
+                },
+                _ => {
+                    return Ok(Stmt::If {
+                        branches,
+                        otherwise: Vec::new(),
+                    });
+                },
+            }
+        }
+    }
+
+    fn for_stmt(&mut self, line: Line) -> Result<Stmt> {
+        let rest = line.rest();
+        let (var, tail) = rest.split_once(char::is_whitespace).unwrap_or((rest, ""));
+        if var.is_empty() || !var.chars().all(|c| c.is_alphanumeric() || c == '_') {
+            return Err(syntax_error(&line, "'for' needs a variable name"));
+        }
+        let tail = tail.trim();
+        let words = if tail.is_empty() {
+            None
+        } else if tail == "in" || tail.starts_with("in ") {
+            Some(Line {
+                number: line.number,
+                text: tail[2..].trim().to_string(),
+            })
+        } else {
+            return Err(syntax_error(&line, "expected 'in'"));
+        };
+        self.expect("do", &line)?;
+        let body = self.body(&["done"], &line)?.0;
+        Ok(Stmt::For {
+            var: var.to_string(),
+            words,
+            body,
+        })
+    }
+}
+
+/// Parse a script into statements.
+fn parse(source: &str) -> Result<Vec<Stmt>> {
+    let mut parser = Parser {
+        lines: split_statements(source).into_iter(),
+    };
+    Ok(parser.block(&[])?.0)
+}
+
+// ---------------------------------------------------------------------------
+// Execution
+// ---------------------------------------------------------------------------
+
+/// Why a script stopped early.
+enum Stop {
+    Exit(i32),
+    OutOfSteps,
+}
+
+type Flow = std::result::Result<(), Stop>;
+
+/// One script being run.
+struct Run<'a> {
+    reg: &'a CommandRegistry,
+    args: &'a [String],
+    output: Vec<String>,
+    status: i32,
+}
+
+impl Run<'_> {
+    fn block(&mut self, stmts: &[Stmt], env: &mut Environment<'_>) -> Flow {
+        for stmt in stmts {
+            match stmt {
+                Stmt::Command(line) => self.command(line, env)?,
+                Stmt::If {
+                    branches,
+                    otherwise,
+                } => {
+                    let mut taken = None;
+                    for (condition, block) in branches {
+                        if self.condition(condition, env)? {
+                            taken = Some(block);
+                            break;
+                        }
+                    }
+                    self.status = 0;
+                    self.block(taken.unwrap_or(otherwise), env)?;
+                },
+                Stmt::While { condition, body } => {
+                    let mut status = 0;
+                    let mut iterations = 0;
+                    while self.condition(condition, env)? {
+                        if iterations == MAX_LOOP_ITERATIONS {
+                            self.output.push(format!(
+                                "warning: while loop terminated after \
+                                 {MAX_LOOP_ITERATIONS} iterations (limit reached)"
+                            ));
+                            break;
+                        }
+                        iterations += 1;
+                        self.block(body, env)?;
+                        status = self.status;
+                    }
+                    self.status = status;
+                },
+                Stmt::For { var, words, body } => {
+                    self.take_step()?;
+                    let items = match words {
+                        Some(line) => match self.reg.expand_words(&line.text, env) {
+                            Ok(items) => items,
+                            Err(e) => {
+                                self.fail(line, &e);
+                                continue;
+                            },
+                        },
+                        None => self.args.to_vec(),
+                    };
+                    self.status = 0;
+                    for item in items {
+                        self.reg.set_variable(var, &item);
+                        self.block(body, env)?;
+                    }
+                },
+            }
+        }
+        Ok(())
+    }
+
+    /// Count a command against the budget of the whole run.
+    fn take_step(&self) -> Flow {
+        let limits = self.reg.script_limits();
+        if limits.steps.get() >= MAX_SCRIPT_STEPS {
+            return Err(Stop::OutOfSteps);
+        }
+        limits.steps.set(limits.steps.get() + 1);
+        Ok(())
+    }
+
+    fn fail(&mut self, line: &Line, e: &OasisError) {
+        self.output
+            .push(format!("error at line {}: {e}", line.number));
+        self.status = 1;
+        self.reg.set_exit_code(1);
+    }
+
+    fn command(&mut self, line: &Line, env: &mut Environment<'_>) -> Flow {
+        self.take_step()?;
+        if line.keyword() == "exit" {
+            let code = match self.reg.expand_words(line.rest(), env) {
+                Ok(words) => match words.as_slice() {
+                    [] => self.status,
+                    [code] => code.parse().unwrap_or(2),
+                    _ => 2,
+                },
+                Err(_) => 2,
+            };
+            return Err(Stop::Exit(code));
+        }
+        if matches!(line.keyword(), "sh" | "run")
+            && !unquoted(&line.text)
+                .iter()
+                .any(|&(_, c)| matches!(c, '|' | '&' | '>' | '<'))
+        {
+            return self.nested_script(line, env);
+        }
+        match self.reg.execute_line(&line.text, env) {
+            Ok(output) => {
+                collect_output(output, &mut self.output);
+                self.status = self.reg.exit_code();
+            },
+            Err(e) => self.fail(line, &e),
+        }
+        Ok(())
+    }
+
+    /// Run a plain `sh <path> [args...]` line in place, so the nested
+    /// script's output and status pass through unwrapped.
+    fn nested_script(&mut self, line: &Line, env: &mut Environment<'_>) -> Flow {
+        let outcome = self.reg.expand_words(line.rest(), env).and_then(|words| {
+            let Some((path, args)) = words.split_first() else {
+                return Err(OasisError::Command(format!(
+                    "usage: {} <path> [args...]",
+                    line.keyword()
+                )));
+            };
+            let args: Vec<&str> = args.iter().map(String::as_str).collect();
+            self.reg.run_script(path, &args, env)
+        });
+        match outcome {
+            Ok(outcome) => {
+                self.output.extend(outcome.output);
+                self.status = outcome.status;
+            },
+            Err(e) => self.fail(line, &e),
+        }
+        Ok(())
+    }
+
+    /// Run a condition. Its output is discarded.
+    fn condition(
+        &mut self,
+        line: &Line,
+        env: &mut Environment<'_>,
+    ) -> std::result::Result<bool, Stop> {
+        self.take_step()?;
+        let holds = self.reg.execute_line(&line.text, env).is_ok() && self.reg.exit_code() == 0;
+        self.status = if holds { 0 } else { 1 };
+        self.reg.set_exit_code(self.status);
+        Ok(holds)
+    }
+}
+
+/// Append a command's output to the script output.
+pub(crate) fn collect_output(output: CommandOutput, lines: &mut Vec<String>) {
+    match output {
+        CommandOutput::Text(text) => lines.extend(text.lines().map(str::to_string)),
+        CommandOutput::Table { headers, rows } => {
+            lines.push(headers.join(" | "));
+            lines.extend(rows.iter().map(|row| row.join(" | ")));
+        },
+        CommandOutput::Json(value) => {
+            lines.extend(format_json(&value).lines().map(str::to_string));
+        },
+        CommandOutput::Multi(outputs) => {
+            for output in outputs {
+                collect_output(output, lines);
+            }
+        },
+        CommandOutput::Clear => lines.push("(clear)".to_string()),
+        CommandOutput::None => {},
+        _ => lines.push("(signal command skipped in script)".to_string()),
+    }
+}
+
+impl CommandRegistry {
+    /// Run the script at `path` (relative to the working directory) with
+    /// positional arguments `args`.
+    ///
+    /// Fails only if the script cannot be read or parsed, or nests too
+    /// deep; errors of its commands are part of the output.
+    pub fn run_script(
+        &self,
+        path: &str,
+        args: &[&str],
+        env: &mut Environment<'_>,
+    ) -> Result<ScriptOutcome> {
+        let full_path = resolve_path(&env.cwd, path);
+        if !env.vfs.exists(&full_path) {
+            return Err(OasisError::Command(format!(
+                "script not found: {full_path}"
+            )));
+        }
+        let data = env.vfs.read(&full_path)?;
+        let stmts = parse(&String::from_utf8_lossy(&data))
+            .map_err(|e| OasisError::Command(format!("{full_path}: {e}")))?;
+
+        let limits = self.script_limits();
+        let depth = limits.depth.get();
+        if depth >= MAX_SCRIPT_DEPTH {
+            return Err(OasisError::Command(format!(
+                "{full_path}: scripts nested more than {MAX_SCRIPT_DEPTH} deep"
+            )));
+        }
+        if depth == 0 {
+            limits.steps.set(0);
+        }
+        let steps_before = limits.steps.get();
+        limits.depth.set(depth + 1);
+
+        let saved = self.set_positional(path, args);
+        let args: Vec<String> = args.iter().map(|a| a.to_string()).collect();
+        let mut run = Run {
+            reg: self,
+            args: &args,
+            output: Vec::new(),
+            status: 0,
+        };
+        let flow = run.block(&stmts, env);
+        let mut output = run.output;
+        let status = match flow {
+            Ok(()) => run.status,
+            Err(Stop::Exit(code)) => code,
+            Err(Stop::OutOfSteps) => {
+                if depth == 0 {
+                    output.push(format!(
+                        "error: script stopped after {MAX_SCRIPT_STEPS} commands"
+                    ));
+                }
+                1
+            },
+        };
+        self.restore_positional(saved);
+        limits.depth.set(depth);
+        self.set_exit_code(status);
+
+        Ok(ScriptOutcome {
+            output,
+            status,
+            steps: limits.steps.get() - steps_before,
+        })
+    }
+
+    /// Run [`PROFILE_PATH`] as a script, so it can set variables and
+    /// aliases at startup. A missing profile is not an error.
+    pub fn run_profile(&self, env: &mut Environment<'_>) -> Result<()> {
+        if env.vfs.exists(PROFILE_PATH) {
+            self.run_script(PROFILE_PATH, &[], env)?;
+        }
+        Ok(())
+    }
+
+    /// Run [`RC_PATH`] if it exists, returning its output for the
+    /// terminal.
+    pub fn run_rc(&self, env: &mut Environment<'_>) -> Result<Vec<String>> {
+        if !env.vfs.exists(RC_PATH) {
+            return Ok(Vec::new());
+        }
+        let outcome = self.run_script(RC_PATH, &[], env)?;
+        let mut output = outcome.output;
+        if outcome.status != 0 {
+            output.push(format!("{RC_PATH}: exit status {}", outcome.status));
+        }
+        Ok(output)
+    }
+
+    /// Built-in `sh` (and `run`): run a script and print its output. A
+    /// non-zero exit status makes the command fail.
+    pub(crate) fn execute_sh(
+        &self,
+        name: &str,
+        args: &[&str],
+        env: &mut Environment<'_>,
+    ) -> Result<CommandOutput> {
+        let Some((&path, script_args)) = args.split_first() else {
+            return Err(OasisError::Command(format!(
+                "usage: {name} <path> [args...]"
+            )));
+        };
+        let outcome = self.run_script(path, script_args, env)?;
+        let mut text = outcome.output.join("\n");
+        if outcome.status != 0 {
+            if !text.is_empty() {
+                text.push('\n');
+            }
+            text.push_str(&format!("{path}: exit status {}", outcome.status));
+            return Ok(CommandOutput::Status {
+                code: outcome.status,
+                output: Box::new(CommandOutput::Text(text)),
+            });
+        }
+        if text.is_empty() {
+            text = if outcome.steps == 0 {
+                "(empty script)".to_string()
+            } else {
+                let full_path = resolve_path(&env.cwd, path);
+                format!("Script {full_path}: {} commands executed.", outcome.steps)
+            };
+        }
+        Ok(CommandOutput::Text(text))
+    }
+
+    /// Set `$0`..`$9`, `$#` and `$@`, returning the previous values.
+    fn set_positional(&self, path: &str, args: &[&str]) -> Vec<Option<String>> {
+        let saved = POSITIONAL.map(|name| self.get_variable(name)).to_vec();
+        self.set_variable("0", path);
+        for (i, name) in POSITIONAL[1..10].iter().enumerate() {
+            match args.get(i) {
+                Some(arg) => self.set_variable(name, arg),
+                None => self.unset_variable(name),
+            }
+        }
+        self.set_variable("#", &args.len().to_string());
+        self.set_variable("@", &args.join(" "));
+        saved
+    }
+
+    fn restore_positional(&self, saved: Vec<Option<String>>) {
+        for (name, value) in POSITIONAL.iter().zip(saved) {
+            match value {
+                Some(value) => self.set_variable(name, &value),
+                None => self.unset_variable(name),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use oasis_vfs::{MemoryVfs, Vfs};
+
+    fn setup() -> (CommandRegistry, MemoryVfs) {
+        let mut reg = CommandRegistry::new();
+        crate::register_builtins(&mut reg);
+        let mut vfs = MemoryVfs::new();
+        vfs.mkdir("/etc").unwrap();
+        vfs.mkdir("/tmp").unwrap();
+        (reg, vfs)
+    }
+
+    fn env(vfs: &mut MemoryVfs) -> Environment<'_> {
+        Environment {
+            cwd: "/".to_string(),
+            vfs,
+            power: None,
+            time: None,
+            usb: None,
+            network: None,
+            tls: None,
+            stdin: None,
+            enforce_permissions: false,
+        }
+    }
+
+    /// Write `source` to /tmp/t.sh and run it with `args`.
+    fn run(source: &str, args: &[&str]) -> ScriptOutcome {
+        let (reg, mut vfs) = setup();
+        vfs.write("/tmp/t.sh", source.as_bytes()).unwrap();
+        reg.run_script("/tmp/t.sh", args, &mut env(&mut vfs))
+            .unwrap()
+    }
+
+    #[test]
+    fn statements_split_at_semicolons_and_keywords() {
+        let lines: Vec<String> = split_statements(
+            "# setup\nif test -f x; then echo 'a;b'; fi\n\nfor i in 1 2; do echo $i; done",
+        )
+        .into_iter()
+        .map(|l| format!("{}:{}", l.number, l.text))
+        .collect();
+        assert_eq!(
+            lines,
+            [
+                "2:if test -f x",
+                "2:then",
+                "2:echo 'a;b'",
+                "2:fi",
+                "4:for i in 1 2",
+                "4:do",
+                "4:echo $i",
+                "4:done",
+            ]
+        );
+    }
+
+    #[test]
+    fn nested_if_and_for() {
+        let out = run(
+            "for x in a b c; do\n\
+               if test $x = b; then\n\
+                 for y in 1 2; do echo $x$y; done\n\
+               elif test $x = c; then\n\
+                 echo last\n\
+               else\n\
+                 if test -n $x; then echo first $x; fi\n\
+               fi\n\
+             done",
+            &[],
+        );
+        assert_eq!(out.output, ["first a", "b1", "b2", "last"]);
+        assert_eq!(out.status, 0);
+    }
+
+    #[test]
+    fn positional_arguments() {
+        let out = run(
+            "echo $# args: $@\necho first=$1 second=$2 third=$3\nfor a; do echo arg $a; done",
+            &["one", "two"],
+        );
+        assert_eq!(
+            out.output,
+            [
+                "2 args: one two",
+                "first=one second=two third=",
+                "arg one",
+                "arg two",
+            ]
+        );
+    }
+
+    #[test]
+    fn arguments_are_restored_after_a_nested_script() {
+        let (reg, mut vfs) = setup();
+        vfs.write("/tmp/inner.sh", b"echo inner $1").unwrap();
+        vfs.write("/tmp/outer.sh", b"sh /tmp/inner.sh x\necho outer $1 $#")
+            .unwrap();
+        let mut env = env(&mut vfs);
+        let out = reg.run_script("/tmp/outer.sh", &["y"], &mut env).unwrap();
+        assert_eq!(out.output, ["inner x", "outer y 1"]);
+        assert_eq!(reg.get_variable("1"), None);
+    }
+
+    #[test]
+    fn exit_status() {
+        let out = run("echo before\nexit 3\necho after", &[]);
+        assert_eq!(out.output, ["before"]);
+        assert_eq!(out.status, 3);
+
+        // Without `exit`, the last command decides.
+        assert_eq!(run("echo ok\nnosuchcommand", &[]).status, 1);
+        assert_eq!(run("nosuchcommand\necho ok", &[]).status, 0);
+        let out = run("nosuchcommand\nexit", &[]);
+        assert_eq!(out.status, 1);
+        assert_eq!(
+            out.output,
+            ["error at line 1: command error: unknown command: nosuchcommand"]
+        );
+        // A condition that fails without a branch to run is not a failure.
+        assert_eq!(run("if test a = b; then echo no; fi", &[]).status, 0);
+    }
+
+    #[test]
+    fn conditions_use_the_exit_status() {
+        // What a condition prints does not matter.
+        let out = run(
+            "if echo false; then echo ran; fi\n\
+             if test a = b; then echo no; else echo status $?; fi\n\
+             test -d /tmp && echo dir; test -f /tmp || echo not a file",
+            &[],
+        );
+        assert_eq!(
+            out.output,
+            ["ran", "status 1", "true", "dir", "false", "not a file"]
+        );
+    }
+
+    #[test]
+    fn sh_command_reports_exit_status() {
+        let (reg, mut vfs) = setup();
+        vfs.write("/tmp/ok.sh", b"echo hi $1").unwrap();
+        vfs.write("/tmp/fail.sh", b"echo partial\nexit 2").unwrap();
+        let mut env = env(&mut vfs);
+        match reg.execute("sh /tmp/ok.sh there", &mut env).unwrap() {
+            CommandOutput::Text(s) => assert_eq!(s, "hi there"),
+            other => panic!("expected text, got {other:?}"),
+        }
+        match reg.execute("sh /tmp/fail.sh", &mut env).unwrap() {
+            CommandOutput::Text(s) => assert_eq!(s, "partial\n/tmp/fail.sh: exit status 2"),
+            other => panic!("expected text, got {other:?}"),
+        }
+        assert_eq!(reg.get_variable("?").as_deref(), Some("2"));
+        match reg.execute("sh /tmp/fail.sh ; echo $?", &mut env).unwrap() {
+            CommandOutput::Text(s) => assert!(s.ends_with("exit status 2\n2")),
+            other => panic!("expected text, got {other:?}"),
+        }
+        assert!(reg.execute("sh", &mut env).is_err());
+        match reg
+            .execute("sh /tmp/fail.sh || echo recovered", &mut env)
+            .unwrap()
+        {
+            CommandOutput::Text(s) => assert!(s.ends_with("recovered")),
+            other => panic!("expected text, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn syntax_errors_run_nothing() {
+        let (reg, mut vfs) = setup();
+        let mut env = env(&mut vfs);
+        for (source, message) in [
+            (
+                "echo x > /tmp/ran\nif test a = a\nthen\necho y",
+                "line 2: 'if' without 'fi'",
+            ),
+            (
+                "echo x > /tmp/ran\nfor i in 1 2\necho $i\ndone",
+                "line 2: expected 'do'",
+            ),
+            ("echo x > /tmp/ran\ndone", "line 2: unexpected 'done'"),
+            (
+                "echo x > /tmp/ran\nwhile\ndo\ndone",
+                "line 2: 'while' needs a condition",
+            ),
+            (
+                "echo x > /tmp/ran\nif true; echo y; fi",
+                "line 2: expected 'then'",
+            ),
+        ] {
+            env.vfs.write("/tmp/bad.sh", source.as_bytes()).unwrap();
+            let err = reg.run_script("/tmp/bad.sh", &[], &mut env).unwrap_err();
+            assert!(err.to_string().contains(message), "{err} for {source:?}");
+            assert!(!env.vfs.exists("/tmp/ran"));
+        }
+    }
+
+    #[test]
+    fn runaway_scripts_are_stopped() {
+        let out = run("while echo true; do echo spin; done", &[]);
+        assert_eq!(out.output.len(), MAX_LOOP_ITERATIONS + 1);
+        assert!(out.output[MAX_LOOP_ITERATIONS].contains("limit reached"));
+
+        let words = "x ".repeat(200);
+        let out = run(
+            &format!("for a in {words}; do for b in {words}; do echo $a$b; done; done"),
+            &[],
+        );
+        assert_eq!(
+            out.output.last().unwrap(),
+            &format!("error: script stopped after {MAX_SCRIPT_STEPS} commands")
+        );
+        assert_eq!(out.status, 1);
+        assert_eq!(out.steps, MAX_SCRIPT_STEPS);
+
+        // A script calling itself stops at the depth limit.
+        let out = run("echo level $#\nsh /tmp/t.sh", &[]);
+        assert_eq!(out.output.len(), MAX_SCRIPT_DEPTH + 1);
+        assert!(out.output[MAX_SCRIPT_DEPTH].contains("nested more than 8 deep"));
+    }
+
+    #[test]
+    fn rc_runs_when_present() {
+        let (reg, mut vfs) = setup();
+        let mut env = env(&mut vfs);
+        assert!(reg.run_rc(&mut env).unwrap().is_empty());
+        env.vfs
+            .write(
+                RC_PATH,
+                b"# boot\nmkdir /home\nset GREETING=hello\necho $GREETING\nexit 4",
+            )
+            .unwrap();
+        let out = reg.run_rc(&mut env).unwrap();
+        assert_eq!(out, ["hello", "/etc/rc: exit status 4"]);
+        assert!(env.vfs.exists("/home"));
+        assert_eq!(reg.get_variable("GREETING").as_deref(), Some("hello"));
+    }
+}
@@ -2,8 +2,93 @@
 
 use oasis_types::error::{OasisError, Result};
 
+use crate::file_commands::format_mode;
 use crate::interpreter::{Command, CommandOutput, Environment, resolve_path};
 
+/// Log of security events, shown by `audit`.
+pub const AUDIT_LOG_PATH: &str = "/var/log/audit.log";
+
+/// Refuse to overwrite `path` if the environment enforces permissions
+/// and the file is read-only. Denials are logged to [`AUDIT_LOG_PATH`].
+/// Paths that do not exist yet are always writable.
+pub(crate) fn check_writable(env: &mut Environment<'_>, path: &str) -> Result<()> {
+    if !env.enforce_permissions {
+        return Ok(());
+    }
+    match env.vfs.metadata(path) {
+        Ok(meta) if !meta.is_writable() => {
+            audit(env, &format!("denied write {path}"));
+            Err(OasisError::Command(format!("permission denied: {path}")))
+        },
+        _ => Ok(()),
+    }
+}
+
+/// Append an event to the audit log, stamped with the time if known.
+/// Best effort: failing to log never fails the command.
+fn audit(env: &mut Environment<'_>, event: &str) {
+    let line = match env.time.and_then(|t| t.now().ok()) {
+        Some(now) => format!("{now} {event}"),
+        None => event.to_string(),
+    };
+    let mut data = env.vfs.read(AUDIT_LOG_PATH).unwrap_or_default();
+    if !data.is_empty() && !data.ends_with(b"\n") {
+        data.push(b'\n');
+    }
+    data.extend_from_slice(line.as_bytes());
+    data.push(b'\n');
+    if !env.vfs.exists("/var/log") {
+        let _ = env.vfs.mkdir("/var");
+        let _ = env.vfs.mkdir("/var/log");
+    }
+    if let Err(e) = env.vfs.write(AUDIT_LOG_PATH, &data) {
+        log::warn!("Failed to write audit log: {e}");
+    }
+}
+
+/// Apply a chmod mode to `current`: octal (`755`) or symbolic clauses
+/// like `u+x`, `go-w` or `a=r`, separated by commas.
+fn parse_mode(spec: &str, current: u32) -> Result<u32> {
+    let invalid = || OasisError::Command(format!("invalid mode: {spec}"));
+    if !spec.is_empty() && spec.len() <= 4 && spec.bytes().all(|b| (b'0'..=b'7').contains(&b)) {
+        return u32::from_str_radix(spec, 8).map_err(|_| invalid());
+    }
+    let mut mode = current;
+    for clause in spec.split(',') {
+        let op_at = clause.find(['+', '-', '=']).ok_or_else(invalid)?;
+        let (who, rest) = clause.split_at(op_at);
+        let mut who_mask = 0;
+        for c in who.chars() {
+            who_mask |= match c {
+                'u' => 0o700,
+                'g' => 0o070,
+                'o' => 0o007,
+                'a' => 0o777,
+                _ => return Err(invalid()),
+            };
+        }
+        if who.is_empty() {
+            who_mask = 0o777;
+        }
+        let mut perms = 0;
+        for c in rest[1..].chars() {
+            perms |= match c {
+                'r' => 0o444,
+                'w' => 0o222,
+                'x' => 0o111,
+                _ => return Err(invalid()),
+            };
+        }
+        let bits = perms & who_mask;
+        mode = match &rest[..1] {
+            "+" => mode | bits,
+            "-" => mode & !bits,
+            _ => (mode & !who_mask) | bits,
+        };
+    }
+    Ok(mode)
+}
+
 // ---------------------------------------------------------------------------
 // chmod
 // ---------------------------------------------------------------------------
@@ -14,7 +99,7 @@ impl Command for ChmodCmd {
         "chmod"
     }
     fn description(&self) -> &str {
-        "Set file permissions (VFS metadata)"
+        "Set file permissions"
     }
     fn usage(&self) -> &str {
         "chmod <mode> <file>"
@@ -28,13 +113,14 @@ impl Command for ChmodCmd {
                 "usage: chmod <mode> <file>".to_string(),
             ));
         }
-        let mode = args[0];
         let path = resolve_path(&env.cwd, args[1]);
-        // VFS doesn't have real permissions; store as metadata.
-        let meta_path = format!("{path}.__perms__");
-        env.vfs.write(&meta_path, mode.as_bytes())?;
+        let mut meta = env.vfs.metadata(&path)?;
+        meta.mode = parse_mode(args[0], meta.mode)?;
+        env.vfs.set_metadata(&path, &meta)?;
         Ok(CommandOutput::Text(format!(
-            "Set permissions on {path}: {mode}"
+            "Set permissions on {path}: {:04o} ({})",
+            meta.mode,
+            format_mode(meta.kind, meta.mode)
         )))
     }
 }
@@ -49,7 +135,7 @@ impl Command for ChownCmd {
         "chown"
     }
     fn description(&self) -> &str {
-        "Set file owner (VFS metadata)"
+        "Set file owner"
     }
     fn usage(&self) -> &str {
         "chown <owner> <file>"
@@ -65,8 +151,9 @@ impl Command for ChownCmd {
         }
         let owner = args[0];
         let path = resolve_path(&env.cwd, args[1]);
-        let meta_path = format!("{path}.__owner__");
-        env.vfs.write(&meta_path, owner.as_bytes())?;
+        let mut meta = env.vfs.metadata(&path)?;
+        meta.owner = owner.to_string();
+        env.vfs.set_metadata(&path, &meta)?;
         Ok(CommandOutput::Text(format!("Set owner of {path}: {owner}")))
     }
 }
@@ -118,7 +205,7 @@ impl Command for AuditCmd {
     }
     fn execute(&self, args: &[&str], env: &mut Environment<'_>) -> Result<CommandOutput> {
         let subcmd = args.first().copied().unwrap_or("show");
-        let log_path = "/var/log/audit.log";
+        let log_path = AUDIT_LOG_PATH;
 
         match subcmd {
             "show" => {
@@ -171,6 +258,7 @@ mod tests {
             network: None,
             tls: None,
             stdin: None,
+            enforce_permissions: false,
         };
         reg.execute(line, &mut env)
     }
@@ -185,8 +273,22 @@ mod tests {
             CommandOutput::Text(s) => assert!(s.contains("755")),
             _ => panic!("expected text"),
         }
-        let perms = vfs.read("/test.txt.__perms__").unwrap();
-        assert_eq!(perms, b"755");
+        assert_eq!(vfs.metadata("/test.txt").unwrap().mode, 0o755);
+        assert!(!vfs.exists("/test.txt.__perms__"));
+    }
+
+    #[test]
+    fn chmod_symbolic_modes() {
+        assert_eq!(parse_mode("u+x", 0o644).unwrap(), 0o744);
+        assert_eq!(parse_mode("go-r", 0o644).unwrap(), 0o600);
+        assert_eq!(parse_mode("a=r,u+w", 0o777).unwrap(), 0o644);
+        assert_eq!(parse_mode("-w", 0o666).unwrap(), 0o444);
+        assert!(parse_mode("9", 0).is_err());
+        assert!(parse_mode("u*x", 0).is_err());
+        let mut reg = CommandRegistry::new();
+        register_security_commands(&mut reg);
+        let mut vfs = MemoryVfs::new();
+        assert!(exec(&reg, &mut vfs, "chmod 644 /missing").is_err());
     }
 
     #[test]
@@ -199,6 +301,39 @@ mod tests {
             CommandOutput::Text(s) => assert!(s.contains("root")),
             _ => panic!("expected text"),
         }
+        assert_eq!(vfs.metadata("/test.txt").unwrap().owner, "root");
+    }
+
+    #[test]
+    fn enforced_permissions_block_writes_to_read_only_files() {
+        let mut reg = CommandRegistry::new();
+        register_security_commands(&mut reg);
+        crate::register_file_commands(&mut reg);
+        let mut vfs = MemoryVfs::new();
+        vfs.write("/ro.txt", b"keep").unwrap();
+        exec(&reg, &mut vfs, "chmod 444 /ro.txt").unwrap();
+        let mut env = Environment {
+            cwd: "/".to_string(),
+            vfs: &mut vfs,
+            power: None,
+            time: None,
+            usb: None,
+            network: None,
+            tls: None,
+            stdin: None,
+            enforce_permissions: true,
+        };
+        let err = reg.execute("write /ro.txt gone", &mut env).unwrap_err();
+        assert!(err.to_string().contains("permission denied"));
+        assert!(reg.execute("passwd > /ro.txt", &mut env).is_err());
+        reg.execute("write /new.txt fine", &mut env).unwrap();
+        assert_eq!(vfs.read("/ro.txt").unwrap(), b"keep");
+        let log = vfs.read(AUDIT_LOG_PATH).unwrap();
+        assert!(String::from_utf8_lossy(&log).contains("denied write /ro.txt"));
+
+        // Without enforcement the mode is only recorded.
+        exec(&reg, &mut vfs, "write /ro.txt gone").unwrap();
+        assert_eq!(vfs.read("/ro.txt").unwrap(), b"gone");
     }
 
     #[test]
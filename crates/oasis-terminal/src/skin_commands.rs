@@ -2,7 +2,7 @@
 
 use oasis_skin::Skin;
 use oasis_skin::builtin;
-use oasis_types::error::Result;
+use oasis_types::error::{OasisError, Result};
 
 use crate::{Command, CommandOutput, CommandRegistry, Environment};
 
@@ -20,11 +20,11 @@ impl Command for SkinCmd {
     }
 
     fn description(&self) -> &str {
-        "List, show, or switch skins"
+        "List, show, validate, or switch skins"
     }
 
     fn usage(&self) -> &str {
-        "skin [list|current|<name>]"
+        "skin [list|current|reload|watch [on|off]|validate <name>|intensity <0.0-1.0>|<name>]"
     }
 
     fn category(&self) -> &str {
@@ -52,6 +52,58 @@ impl Command for SkinCmd {
             Some("current") => Ok(CommandOutput::Text(
                 "Use 'skin <name>' to switch skins.".to_string(),
             )),
+            Some("reload") => Ok(CommandOutput::SkinReload),
+            Some("watch") => {
+                let enable = match args.get(1).copied() {
+                    None => None,
+                    Some("on") => Some(true),
+                    Some("off") => Some(false),
+                    Some(other) => {
+                        return Err(OasisError::Command(format!(
+                            "skin watch: expected 'on' or 'off', got '{other}'"
+                        )));
+                    },
+                };
+                Ok(CommandOutput::SkinWatch { enable })
+            },
+            Some("intensity") => {
+                let intensity = args
+                    .get(1)
+                    .and_then(|v| v.parse::<f32>().ok())
+                    .filter(|v| (0.0..=1.0).contains(v))
+                    .ok_or_else(|| {
+                        OasisError::Command("usage: skin intensity <0.0-1.0>".to_string())
+                    })?;
+                Ok(CommandOutput::SkinIntensity { intensity })
+            },
+            Some("validate") => {
+                let name = args.get(1).ok_or_else(|| {
+                    OasisError::Command("usage: skin validate <name>".to_string())
+                })?;
+                let issues = oasis_skin::load_skin(name)?.validate();
+                if issues.is_empty() {
+                    return Ok(CommandOutput::Text(format!(
+                        "Skin '{name}': no problems found."
+                    )));
+                }
+                let rows = issues
+                    .into_iter()
+                    .map(|i| {
+                        vec![
+                            i.severity.to_string(),
+                            i.file.to_string(),
+                            i.path,
+                            i.message,
+                        ]
+                    })
+                    .collect();
+                Ok(CommandOutput::Table {
+                    headers: ["severity", "file", "key", "problem"]
+                        .map(String::from)
+                        .to_vec(),
+                    rows,
+                })
+            },
             Some(name) => Ok(CommandOutput::SkinSwap {
                 name: name.to_string(),
             }),
@@ -74,6 +126,7 @@ mod tests {
             network: None,
             tls: None,
             stdin: None,
+            enforce_permissions: false,
         }
     }
 
@@ -101,6 +154,84 @@ mod tests {
         assert!(matches!(out, CommandOutput::Text(_)));
     }
 
+    #[test]
+    fn skin_watch_emits_signal() {
+        let cmd = SkinCmd;
+        let mut vfs = MemoryVfs::new();
+        let mut env = make_env(&mut vfs);
+        for (args, expected) in [
+            (&["watch"][..], None),
+            (&["watch", "on"][..], Some(true)),
+            (&["watch", "off"][..], Some(false)),
+        ] {
+            match cmd.execute(args, &mut env).unwrap() {
+                CommandOutput::SkinWatch { enable } => assert_eq!(enable, expected),
+                _ => panic!("expected SkinWatch"),
+            }
+        }
+        assert!(cmd.execute(&["watch", "maybe"], &mut env).is_err());
+        assert!(matches!(
+            cmd.execute(&["reload"], &mut env).unwrap(),
+            CommandOutput::SkinReload
+        ));
+    }
+
+    #[test]
+    fn skin_intensity_emits_signal() {
+        let cmd = SkinCmd;
+        let mut vfs = MemoryVfs::new();
+        let mut env = make_env(&mut vfs);
+        match cmd.execute(&["intensity", "0.25"], &mut env).unwrap() {
+            CommandOutput::SkinIntensity { intensity } => assert_eq!(intensity, 0.25),
+            _ => panic!("expected SkinIntensity"),
+        }
+        for bad in [
+            &["intensity"][..],
+            &["intensity", "1.5"],
+            &["intensity", "lots"],
+        ] {
+            assert!(cmd.execute(bad, &mut env).is_err());
+        }
+    }
+
+    #[test]
+    fn skin_validate_reports_issues() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("skin.toml"), "name = \"broken\"\n").unwrap();
+        std::fs::write(dir.path().join("layout.toml"), "").unwrap();
+        std::fs::write(dir.path().join("features.toml"), "").unwrap();
+        std::fs::write(dir.path().join("theme.toml"), "background = \"navy\"\n").unwrap();
+        let path = dir.path().to_str().unwrap();
+
+        let cmd = SkinCmd;
+        let mut vfs = MemoryVfs::new();
+        let mut env = make_env(&mut vfs);
+        match cmd.execute(&["validate", path], &mut env).unwrap() {
+            CommandOutput::Table { headers, rows } => {
+                assert_eq!(headers.len(), 4);
+                assert_eq!(
+                    rows,
+                    vec![vec![
+                        "error".to_string(),
+                        "theme.toml".to_string(),
+                        "background".to_string(),
+                        "invalid color 'navy' (expected #RRGGBB or #RRGGBBAA)".to_string(),
+                    ]]
+                );
+            },
+            _ => panic!("expected Table"),
+        }
+        assert!(matches!(
+            cmd.execute(&["validate", "modern"], &mut env).unwrap(),
+            CommandOutput::Text(s) if s.contains("no problems")
+        ));
+        assert!(cmd.execute(&["validate"], &mut env).is_err());
+        assert!(
+            cmd.execute(&["validate", "no-such-skin"], &mut env)
+                .is_err()
+        );
+    }
+
     #[test]
     fn skin_swap_emits_signal() {
         let cmd = SkinCmd;
@@ -1,8 +1,12 @@
-//! System and process commands: uptime, df, whoami, hostname, date, sleep.
+//! System and process commands: uptime, df, mount, whoami, hostname, date,
+//! sleep.
+
+use std::time::{Duration, Instant};
 
 use oasis_types::error::{OasisError, Result};
 
 use crate::interpreter::{Command, CommandOutput, Environment};
+use crate::jobs::{CancelToken, Job, ThreadJob};
 
 // ---------------------------------------------------------------------------
 // uptime
@@ -59,7 +63,7 @@ impl Command for DfCmd {
         "Show VFS filesystem usage"
     }
     fn usage(&self) -> &str {
-        "df"
+        "df [--json]"
     }
     fn category(&self) -> &str {
         "system"
@@ -74,6 +78,15 @@ impl Command for DfCmd {
         ));
         Ok(CommandOutput::Text(lines.join("\n")))
     }
+    fn execute_json(&self, _args: &[&str], env: &mut Environment<'_>) -> Result<serde_json::Value> {
+        let (dirs, files, total_bytes) = count_vfs_recursive(env, "/", 0)?;
+        Ok(serde_json::json!([{
+            "filesystem": "vfs",
+            "files": files,
+            "dirs": dirs,
+            "size": total_bytes,
+        }]))
+    }
 }
 
 /// Maximum recursion depth for VFS traversal to prevent stack overflow.
@@ -111,6 +124,46 @@ fn count_vfs_recursive(
     Ok((dirs, files, bytes))
 }
 
+// ---------------------------------------------------------------------------
+// mount
+// ---------------------------------------------------------------------------
+
+struct MountCmd;
+impl Command for MountCmd {
+    fn name(&self) -> &str {
+        "mount"
+    }
+    fn description(&self) -> &str {
+        "List mounted filesystems"
+    }
+    fn usage(&self) -> &str {
+        "mount [--json]"
+    }
+    fn category(&self) -> &str {
+        "system"
+    }
+    fn execute(&self, _args: &[&str], env: &mut Environment<'_>) -> Result<CommandOutput> {
+        let lines: Vec<String> = mount_points(env)
+            .iter()
+            .map(|prefix| format!("vfs on {prefix}"))
+            .collect();
+        Ok(CommandOutput::Text(lines.join("\n")))
+    }
+    fn execute_json(&self, _args: &[&str], env: &mut Environment<'_>) -> Result<serde_json::Value> {
+        Ok(serde_json::json!(mount_points(env)))
+    }
+}
+
+/// Mount points of the VFS; a single filesystem counts as mounted at `/`.
+fn mount_points(env: &Environment<'_>) -> Vec<String> {
+    let mounts = env.vfs.mounts();
+    if mounts.is_empty() {
+        vec!["/".to_string()]
+    } else {
+        mounts
+    }
+}
+
 // ---------------------------------------------------------------------------
 // whoami
 // ---------------------------------------------------------------------------
@@ -206,23 +259,50 @@ impl Command for SleepCmd {
         "system"
     }
     fn execute(&self, args: &[&str], _env: &mut Environment<'_>) -> Result<CommandOutput> {
-        if args.is_empty() {
-            return Err(OasisError::Command("usage: sleep <seconds>".to_string()));
-        }
-        let secs: f64 = args[0]
-            .parse()
-            .map_err(|_| OasisError::Command("invalid number".to_string()))?;
-        // In a real system this would block; in VFS-only mode we just report.
+        let secs = parse_seconds(args)?;
+        // Blocking would freeze the UI; in the foreground we just report.
         Ok(CommandOutput::Text(format!(
             "(slept {secs:.1}s -- simulated)"
         )))
     }
+
+    /// In the background (`sleep N &`) the wait is real, on a thread.
+    fn spawn(
+        &self,
+        args: &[&str],
+        _env: &mut Environment<'_>,
+        cancel: CancelToken,
+    ) -> Result<Box<dyn Job>> {
+        let secs = parse_seconds(args)?;
+        let until = Instant::now() + Duration::from_secs_f64(secs);
+        Ok(Box::new(ThreadJob::spawn(move |out| {
+            while Instant::now() < until {
+                if cancel.is_cancelled() {
+                    return Ok(());
+                }
+                std::thread::sleep(Duration::from_millis(20));
+            }
+            let _ = out.send(format!("slept {secs:.1}s"));
+            Ok(())
+        })))
+    }
+}
+
+fn parse_seconds(args: &[&str]) -> Result<f64> {
+    let Some(arg) = args.first() else {
+        return Err(OasisError::Command("usage: sleep <seconds>".to_string()));
+    };
+    match arg.parse::<f64>() {
+        Ok(secs) if secs.is_finite() && secs >= 0.0 => Ok(secs),
+        _ => Err(OasisError::Command("invalid number".to_string())),
+    }
 }
 
 /// Register system commands.
 pub fn register_system_commands(reg: &mut crate::CommandRegistry) {
     reg.register(Box::new(UptimeCmd));
     reg.register(Box::new(DfCmd));
+    reg.register(Box::new(MountCmd));
     reg.register(Box::new(WhoamiCmd));
     reg.register(Box::new(HostnameCmd));
     reg.register(Box::new(DateCmd));
@@ -233,9 +313,9 @@ pub fn register_system_commands(reg: &mut crate::CommandRegistry) {
 mod tests {
     use super::*;
     use crate::{CommandOutput, CommandRegistry, Environment};
-    use oasis_vfs::MemoryVfs;
+    use oasis_vfs::{MemoryVfs, MountVfs, Vfs};
 
-    fn exec(reg: &CommandRegistry, vfs: &mut MemoryVfs, line: &str) -> Result<CommandOutput> {
+    fn exec(reg: &CommandRegistry, vfs: &mut dyn Vfs, line: &str) -> Result<CommandOutput> {
         let mut env = Environment {
             cwd: "/".to_string(),
             vfs,
@@ -245,6 +325,7 @@ mod tests {
             network: None,
             tls: None,
             stdin: None,
+            enforce_permissions: false,
         };
         reg.execute(line, &mut env)
     }
@@ -296,6 +377,44 @@ mod tests {
         }
     }
 
+    #[test]
+    fn df_json() {
+        let mut reg = CommandRegistry::new();
+        register_system_commands(&mut reg);
+        let mut vfs = MemoryVfs::new();
+        vfs.mkdir("/docs").unwrap();
+        vfs.write("/docs/a.txt", b"abc").unwrap();
+        match exec(&reg, &mut vfs, "df --json").unwrap() {
+            CommandOutput::Json(v) => assert_eq!(
+                v,
+                serde_json::json!([{"filesystem": "vfs", "files": 1, "dirs": 1, "size": 3}])
+            ),
+            _ => panic!("expected json"),
+        }
+    }
+
+    #[test]
+    fn mount_lists_mount_points() {
+        let mut reg = CommandRegistry::new();
+        register_system_commands(&mut reg);
+        let mut vfs = MemoryVfs::new();
+        match exec(&reg, &mut vfs, "mount").unwrap() {
+            CommandOutput::Text(s) => assert_eq!(s, "vfs on /"),
+            _ => panic!("expected text"),
+        }
+        let mut mounted = MountVfs::new();
+        mounted.mount("/", Box::new(MemoryVfs::new())).unwrap();
+        mounted.mount("/ms0", Box::new(MemoryVfs::new())).unwrap();
+        match exec(&reg, &mut mounted, "mount").unwrap() {
+            CommandOutput::Text(s) => assert_eq!(s, "vfs on /\nvfs on /ms0"),
+            _ => panic!("expected text"),
+        }
+        match exec(&reg, &mut mounted, "mount --json").unwrap() {
+            CommandOutput::Json(v) => assert_eq!(v, serde_json::json!(["/", "/ms0"])),
+            _ => panic!("expected json"),
+        }
+    }
+
     #[test]
     fn date_no_service() {
         let mut reg = CommandRegistry::new();
@@ -1,6 +1,7 @@
 //! Text processing commands: head, tail, wc, grep, sort, uniq, tee, tr, cut, diff.
 
 use oasis_types::error::{OasisError, Result};
+use oasis_vfs::{EntryKind, Vfs};
 
 use crate::interpreter::{Command, CommandOutput, Environment, resolve_path};
 
@@ -114,7 +115,7 @@ impl Command for GrepCmd {
         "Search for pattern in text"
     }
     fn usage(&self) -> &str {
-        "grep [-i] [-n] [-v] [-c] <pattern> [file]"
+        "grep [-i] [-n] [-v] [-c] [-r] <pattern> [file|dir]"
     }
     fn category(&self) -> &str {
         "text"
@@ -124,6 +125,7 @@ impl Command for GrepCmd {
         let mut show_numbers = false;
         let mut invert = false;
         let mut count_only = false;
+        let mut recursive = false;
         let mut positional = Vec::new();
 
         for &arg in args {
@@ -132,32 +134,76 @@ impl Command for GrepCmd {
                 "-n" => show_numbers = true,
                 "-v" => invert = true,
                 "-c" => count_only = true,
+                "-r" => recursive = true,
                 _ => positional.push(arg),
             }
         }
         if positional.is_empty() {
             return Err(OasisError::Command(
-                "usage: grep [-i] [-n] [-v] [-c] <pattern> [file]".to_string(),
+                "usage: grep [-i] [-n] [-v] [-c] [-r] <pattern> [file|dir]".to_string(),
             ));
         }
         let pattern = positional[0];
-        let text = read_text_input(positional.get(1).copied(), env)?;
-
         let pat = if case_insensitive {
             pattern.to_ascii_lowercase()
         } else {
             pattern.to_string()
         };
+        let is_match = |line: &str| {
+            let found = if case_insensitive {
+                line.to_ascii_lowercase().contains(&pat)
+            } else {
+                line.contains(&pat)
+            };
+            found != invert
+        };
 
-        let mut matches = Vec::new();
-        for (i, line) in text.lines().enumerate() {
-            let haystack = if case_insensitive {
-                line.to_ascii_lowercase()
+        if recursive {
+            let root = resolve_path(&env.cwd, positional.get(1).copied().unwrap_or("."));
+            let mut files = Vec::new();
+            collect_files(env.vfs, &root, &mut files)?;
+
+            let mut rows = Vec::new();
+            for path in files {
+                let data = env.vfs.read(&path)?;
+                // Skip binary-looking files.
+                if data.contains(&0) {
+                    continue;
+                }
+                let text = String::from_utf8_lossy(&data);
+                for (i, line) in text.lines().enumerate() {
+                    if is_match(line) {
+                        let mut row = vec![path.clone()];
+                        if show_numbers {
+                            row.push((i + 1).to_string());
+                        }
+                        row.push(line.to_string());
+                        rows.push(row);
+                    }
+                }
+            }
+
+            return if count_only {
+                Ok(CommandOutput::Text(format!("{}", rows.len())))
+            } else if rows.is_empty() {
+                Ok(CommandOutput::Text("(no matches)".to_string()))
             } else {
-                line.to_string()
+                let headers: &[&str] = if show_numbers {
+                    &["path", "line", "text"]
+                } else {
+                    &["path", "text"]
+                };
+                Ok(CommandOutput::Table {
+                    headers: headers.iter().map(|h| h.to_string()).collect(),
+                    rows,
+                })
             };
-            let found = haystack.contains(&pat);
-            if found != invert {
+        }
+
+        let text = read_text_input(positional.get(1).copied(), env)?;
+        let mut matches = Vec::new();
+        for (i, line) in text.lines().enumerate() {
+            if is_match(line) {
                 if show_numbers {
                     matches.push(format!("{}:{line}", i + 1));
                 } else {
@@ -580,6 +626,27 @@ fn parse_n_flag<'a>(args: &[&'a str], default: usize) -> Result<(usize, Option<&
     Ok((n, file))
 }
 
+/// Recursively collect the paths of all regular files under `dir`, in
+/// name order.
+fn collect_files(vfs: &mut dyn Vfs, dir: &str, files: &mut Vec<String>) -> Result<()> {
+    let mut entries = vfs.readdir(dir)?;
+    entries.sort_by(|a, b| a.name.cmp(&b.name));
+    for entry in &entries {
+        let full = if dir == "/" {
+            format!("/{}", entry.name)
+        } else {
+            format!("{}/{}", dir, entry.name)
+        };
+        match entry.kind {
+            EntryKind::File => files.push(full),
+            EntryKind::Directory => collect_files(vfs, &full, files)?,
+            // Not followed, so link loops can't recurse forever.
+            EntryKind::Symlink => {},
+        }
+    }
+    Ok(())
+}
+
 /// Read text from a file path or stdin.
 fn read_text_input(file: Option<&str>, env: &mut Environment<'_>) -> Result<String> {
     if let Some(path) = file {
@@ -633,10 +700,52 @@ mod tests {
             network: None,
             tls: None,
             stdin: None,
+            enforce_permissions: false,
         };
         reg.execute(line, &mut env)
     }
 
+    #[test]
+    fn pipeline_through_text_commands() {
+        let (reg, mut vfs) = setup();
+        vfs.write("/tmp/words.txt", b"pear\napple\npear\nfig\napple\npear")
+            .unwrap();
+        let line = "sort /tmp/words.txt | uniq | grep -v fig | head -n 1";
+        match exec(&reg, &mut vfs, line).unwrap() {
+            CommandOutput::Text(s) => assert_eq!(s, "apple"),
+            _ => panic!("expected text"),
+        }
+        match exec(&reg, &mut vfs, "tail -n 2 /tmp/test.txt | wc -l").unwrap() {
+            CommandOutput::Text(s) => assert_eq!(s.trim(), "2"),
+            _ => panic!("expected text"),
+        }
+    }
+
+    #[test]
+    fn quoted_pipe_is_not_a_separator() {
+        let (reg, mut vfs) = setup();
+        vfs.write("/tmp/pipes.txt", b"a | b\nab\n'|'").unwrap();
+        match exec(&reg, &mut vfs, "grep \"a | b\" /tmp/pipes.txt").unwrap() {
+            CommandOutput::Text(s) => assert_eq!(s, "a | b"),
+            _ => panic!("expected text"),
+        }
+        match exec(&reg, &mut vfs, "grep -c '|' /tmp/pipes.txt | head").unwrap() {
+            CommandOutput::Text(s) => assert_eq!(s, "2"),
+            _ => panic!("expected text"),
+        }
+    }
+
+    #[test]
+    fn piped_stdin_does_not_reach_later_commands() {
+        let (reg, mut vfs) = setup();
+        match exec(&reg, &mut vfs, "head -n 1 /tmp/test.txt | wc -l ; wc -l").unwrap() {
+            CommandOutput::Text(s) => {
+                assert_eq!(s.lines().map(str::trim).collect::<Vec<_>>(), ["1", "0"])
+            },
+            _ => panic!("expected text"),
+        }
+    }
+
     #[test]
     fn head_default() {
         let (reg, mut vfs) = setup();
@@ -736,6 +845,36 @@ mod tests {
         }
     }
 
+    #[test]
+    fn grep_recursive_walks_directories() {
+        let (reg, mut vfs) = setup();
+        vfs.mkdir("/tmp/sub").unwrap();
+        vfs.write("/tmp/sub/notes.txt", b"one\nAlphabet soup")
+            .unwrap();
+        vfs.write("/tmp/sub/blob.bin", b"alpha\0beta").unwrap();
+        match exec(&reg, &mut vfs, "grep -r -i -n alpha /tmp").unwrap() {
+            CommandOutput::Table { headers, rows } => {
+                assert_eq!(headers, ["path", "line", "text"]);
+                assert_eq!(
+                    rows,
+                    [
+                        ["/tmp/sub/notes.txt", "2", "Alphabet soup"],
+                        ["/tmp/test.txt", "1", "alpha"],
+                    ]
+                );
+            },
+            _ => panic!("expected table"),
+        }
+        match exec(&reg, &mut vfs, "grep -r Alpha /tmp").unwrap() {
+            CommandOutput::Table { headers, rows } => {
+                assert_eq!(headers, ["path", "text"]);
+                assert_eq!(rows, [["/tmp/sub/notes.txt", "Alphabet soup"]]);
+            },
+            _ => panic!("expected table"),
+        }
+        assert!(exec(&reg, &mut vfs, "grep -r alpha /missing").is_err());
+    }
+
     #[test]
     fn sort_basic() {
         let (reg, mut vfs) = setup();
@@ -856,6 +995,7 @@ mod tests {
             network: None,
             tls: None,
             stdin: Some("hello".to_string()),
+            enforce_permissions: false,
         };
         match reg.execute("tr elo ELO", &mut env).unwrap() {
             CommandOutput::Text(s) => assert_eq!(s, "hELLO"),
@@ -877,6 +1017,7 @@ mod tests {
             network: None,
             tls: None,
             stdin: Some("hello world".to_string()),
+            enforce_permissions: false,
         };
         match reg.execute("tr -d lo", &mut env).unwrap() {
             CommandOutput::Text(s) => assert_eq!(s, "he wrd"),
@@ -1,6 +1,10 @@
 //! Window & UI control commands: wm, sdi, theme, notify, screenshot.
 
+use oasis_skin::ActiveTheme;
+use oasis_skin::active_theme::WM_COLOR_KEYS;
+use oasis_skin::theme::parse_hex_color;
 use oasis_types::error::{OasisError, Result};
+use oasis_wm::SnapRegion;
 
 use crate::interpreter::{Command, CommandOutput, Environment};
 
@@ -8,6 +12,15 @@ use crate::interpreter::{Command, CommandOutput, Environment};
 // wm
 // ---------------------------------------------------------------------------
 
+/// File the `wm` command leaves window requests in for the frontend.
+const WM_REQUEST_PATH: &str = "/var/wm/request";
+
+/// The region names `wm snap` accepts, for its error messages.
+fn snap_region_names() -> String {
+    let names: Vec<&str> = SnapRegion::ALL.iter().map(|r| r.name()).collect();
+    names.join(", ")
+}
+
 struct WmCmd;
 impl Command for WmCmd {
     fn name(&self) -> &str {
@@ -17,7 +30,7 @@ impl Command for WmCmd {
         "Window manager control"
     }
     fn usage(&self) -> &str {
-        "wm [list|close <id>|focus <id>|minimize <id>]"
+        "wm [list|close|focus|minimize|maximize|restore|move <id>|snap <id> <region>]"
     }
     fn category(&self) -> &str {
         "ui"
@@ -39,7 +52,24 @@ impl Command for WmCmd {
                     ))
                 }
             },
-            "close" | "focus" | "minimize" | "maximize" => {
+            "snap" => {
+                let (Some(id), Some(region)) = (args.get(1), args.get(2)) else {
+                    return Err(OasisError::Command(format!(
+                        "usage: wm snap <window-id> <region>\nregions: {}",
+                        snap_region_names()
+                    )));
+                };
+                if SnapRegion::from_name(region).is_none() {
+                    return Err(OasisError::Command(format!(
+                        "unknown region: {region}\nregions: {}",
+                        snap_region_names()
+                    )));
+                }
+                let request = format!("snap {id} {region}");
+                env.vfs.write(WM_REQUEST_PATH, request.as_bytes())?;
+                Ok(CommandOutput::Text(format!("WM request: {request}")))
+            },
+            "close" | "focus" | "minimize" | "maximize" | "restore" | "move" => {
                 let id = args.get(1).copied().unwrap_or("");
                 if id.is_empty() {
                     return Err(OasisError::Command(format!(
@@ -47,9 +77,14 @@ impl Command for WmCmd {
                     )));
                 }
                 let request = format!("{subcmd} {id}");
-                let req_path = "/var/wm/request";
-                env.vfs.write(req_path, request.as_bytes())?;
-                Ok(CommandOutput::Text(format!("WM request: {subcmd} {id}")))
+                env.vfs.write(WM_REQUEST_PATH, request.as_bytes())?;
+                let mut text = format!("WM request: {subcmd} {id}");
+                if subcmd == "move" {
+                    text.push_str(
+                        "\narrows move, hold L/R to resize, Confirm applies, Cancel reverts",
+                    );
+                }
+                Ok(CommandOutput::Text(text))
             },
             _ => Err(OasisError::Command(format!(
                 "unknown subcommand: {subcmd}\nusage: {}",
@@ -120,7 +155,7 @@ impl Command for ThemeCmd {
         "Show or modify current theme"
     }
     fn usage(&self) -> &str {
-        "theme [show|colors]"
+        "theme [show|colors|keys] | theme get <key> | theme set <key> <#RRGGBB[AA]>"
     }
     fn category(&self) -> &str {
         "ui"
@@ -141,15 +176,69 @@ impl Command for ThemeCmd {
                     ))
                 }
             },
+            "keys" => Ok(CommandOutput::Text(theme_color_keys().join("\n"))),
+            "get" => {
+                let [_, key] = args else {
+                    return Err(OasisError::Command("usage: theme get <key>".to_string()));
+                };
+                check_theme_key(key)?;
+                Ok(CommandOutput::ThemeColor {
+                    key: key.to_string(),
+                    color: None,
+                })
+            },
+            "set" => {
+                let [_, key, value] = args else {
+                    return Err(OasisError::Command(
+                        "usage: theme set <key> <#RRGGBB[AA]>".to_string(),
+                    ));
+                };
+                check_theme_key(key)?;
+                let color = parse_hex_color(value).ok_or_else(|| {
+                    OasisError::Command(format!(
+                        "invalid color '{value}' (expected #RRGGBB or #RRGGBBAA)"
+                    ))
+                })?;
+                Ok(CommandOutput::ThemeColor {
+                    key: key.to_string(),
+                    color: Some(color),
+                })
+            },
             _ => Err(OasisError::Command(format!("unknown subcommand: {subcmd}"))),
         }
     }
 }
 
+/// Every color name `theme get/set` accepts.
+fn theme_color_keys() -> Vec<&'static str> {
+    let mut keys: Vec<&str> = ActiveTheme::COLOR_KEYS
+        .iter()
+        .chain(WM_COLOR_KEYS)
+        .copied()
+        .collect();
+    keys.sort_unstable();
+    keys
+}
+
+fn check_theme_key(key: &str) -> Result<()> {
+    let keys = theme_color_keys();
+    if keys.contains(&key) {
+        Ok(())
+    } else {
+        Err(OasisError::Command(format!(
+            "unknown theme color '{key}'; valid keys: {}",
+            keys.join(", ")
+        )))
+    }
+}
+
 // ---------------------------------------------------------------------------
 // notify
 // ---------------------------------------------------------------------------
 
+/// Most notifications `notify --history` prints.
+const NOTIFY_HISTORY_LEN: usize = 50;
+
 struct NotifyCmd;
 impl Command for NotifyCmd {
     fn name(&self) -> &str {
@@ -159,14 +248,27 @@ impl Command for NotifyCmd {
         "Show a notification message"
     }
     fn usage(&self) -> &str {
-        "notify <message>"
+        "notify <message> | notify --history"
     }
     fn category(&self) -> &str {
         "ui"
     }
     fn execute(&self, args: &[&str], env: &mut Environment<'_>) -> Result<CommandOutput> {
         if args.is_empty() {
-            return Err(OasisError::Command("usage: notify <message>".to_string()));
+            return Err(OasisError::Command(
+                "usage: notify <message> | notify --history".to_string(),
+            ));
+        }
+        if args == ["--history"] {
+            // The UI layer keeps the last notifications posted here.
+            let history = env.vfs.read("/var/notify/history").unwrap_or_default();
+            let history = String::from_utf8_lossy(&history);
+            let lines: Vec<&str> = history.lines().collect();
+            if lines.is_empty() {
+                return Ok(CommandOutput::Text("No notifications.".to_string()));
+            }
+            let start = lines.len().saturating_sub(NOTIFY_HISTORY_LEN);
+            return Ok(CommandOutput::Text(lines[start..].join("\n")));
         }
         let message = args.join(" ");
         // Write notification to VFS for the UI layer to pick up.
@@ -219,6 +321,7 @@ pub fn register_ui_commands(reg: &mut crate::CommandRegistry) {
 mod tests {
     use super::*;
     use crate::{CommandOutput, CommandRegistry, Environment};
+    use oasis_types::backend::Color;
     use oasis_vfs::{MemoryVfs, Vfs};
 
     fn exec(reg: &CommandRegistry, vfs: &mut MemoryVfs, line: &str) -> Result<CommandOutput> {
@@ -231,6 +334,7 @@ mod tests {
             network: None,
             tls: None,
             stdin: None,
+            enforce_permissions: false,
         };
         reg.execute(line, &mut env)
     }
@@ -276,6 +380,30 @@ mod tests {
         assert_eq!(data, b"close browser");
     }
 
+    #[test]
+    fn wm_snap_queues_request() {
+        let (reg, mut vfs) = setup();
+        exec(&reg, &mut vfs, "wm snap terminal left").unwrap();
+        assert_eq!(vfs.read("/var/wm/request").unwrap(), b"snap terminal left");
+        assert!(exec(&reg, &mut vfs, "wm snap terminal").is_err());
+        // Both names the window manager takes for the whole work area.
+        exec(&reg, &mut vfs, "wm snap terminal max").unwrap();
+        exec(&reg, &mut vfs, "wm snap terminal maximized").unwrap();
+        let err = exec(&reg, &mut vfs, "wm snap terminal sideways").unwrap_err();
+        assert!(err.to_string().contains("top-left"));
+    }
+
+    #[test]
+    fn wm_move_queues_request() {
+        let (reg, mut vfs) = setup();
+        match exec(&reg, &mut vfs, "wm move terminal").unwrap() {
+            CommandOutput::Text(s) => assert!(s.contains("Confirm applies")),
+            _ => panic!("expected text"),
+        }
+        assert_eq!(vfs.read("/var/wm/request").unwrap(), b"move terminal");
+        assert!(exec(&reg, &mut vfs, "wm move").is_err());
+    }
+
     #[test]
     fn sdi_list_no_status() {
         let mut reg = CommandRegistry::new();
@@ -298,6 +426,39 @@ mod tests {
         }
     }
 
+    #[test]
+    fn theme_get_and_set_signal_the_app() {
+        let (reg, mut vfs) = setup();
+        match exec(&reg, &mut vfs, "theme get titlebar_active").unwrap() {
+            CommandOutput::ThemeColor { key, color } => {
+                assert_eq!(key, "titlebar_active");
+                assert_eq!(color, None);
+            },
+            _ => panic!("expected ThemeColor"),
+        }
+        match exec(&reg, &mut vfs, "theme set clock_color #204682").unwrap() {
+            CommandOutput::ThemeColor { key, color } => {
+                assert_eq!(key, "clock_color");
+                assert_eq!(color, Some(Color::rgb(0x20, 0x46, 0x82)));
+            },
+            _ => panic!("expected ThemeColor"),
+        }
+        assert!(exec(&reg, &mut vfs, "theme set clock_color blue").is_err());
+        assert!(exec(&reg, &mut vfs, "theme get").is_err());
+    }
+
+    #[test]
+    fn theme_unknown_key_lists_valid_keys() {
+        let (reg, mut vfs) = setup();
+        let err = exec(&reg, &mut vfs, "theme get titlebar").unwrap_err();
+        let msg = err.to_string();
+        assert!(msg.contains("titlebar_active") && msg.contains("sm_panel_bg"));
+        match exec(&reg, &mut vfs, "theme keys").unwrap() {
+            CommandOutput::Text(s) => assert!(s.lines().any(|l| l == "wallpaper_fallback")),
+            _ => panic!("expected text"),
+        }
+    }
+
     #[test]
     fn notify_queues_message() {
         let (reg, mut vfs) = setup();
@@ -309,6 +470,25 @@ mod tests {
         assert_eq!(data, b"Hello World");
     }
 
+    #[test]
+    fn notify_history_prints_the_last_fifty() {
+        let (reg, mut vfs) = setup();
+        match exec(&reg, &mut vfs, "notify --history").unwrap() {
+            CommandOutput::Text(s) => assert_eq!(s, "No notifications."),
+            _ => panic!("expected text"),
+        }
+        let history: Vec<String> = (0..60).map(|i| format!("Terminal: note {i}")).collect();
+        vfs.write("/var/notify/history", history.join("\n").as_bytes())
+            .unwrap();
+        match exec(&reg, &mut vfs, "notify --history").unwrap() {
+            CommandOutput::Text(s) => {
+                assert_eq!(s.lines().count(), 50);
+                assert_eq!(s.lines().next(), Some("Terminal: note 10"));
+            },
+            _ => panic!("expected text"),
+        }
+    }
+
     #[test]
     fn screenshot_queues_request() {
         let (reg, mut vfs) = setup();
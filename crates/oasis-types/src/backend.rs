@@ -8,7 +8,7 @@
 //! the "Extended Primitives" section for shape, gradient, text, texture, clip,
 //! and batch methods that backends can progressively override.
 
-use crate::error::Result;
+use crate::error::{OasisError, Result};
 use crate::input::InputEvent;
 
 /// Width of a single glyph in the bitmap font system.
@@ -23,13 +23,22 @@ pub const BITMAP_GLYPH_HEIGHT: u32 = 8;
 /// then scales by the font-size multiplier. This produces tighter text than
 /// the old fixed `8 * len` calculation.
 pub fn bitmap_measure_text(text: &str, font_size: u16) -> u32 {
-    let scale = if font_size >= BITMAP_GLYPH_WIDTH as u16 {
+    let width: u32 = text.chars().map(crate::bitmap_font::glyph_advance).sum();
+    width * bitmap_scale(font_size)
+}
+
+/// Advance width of one bitmap font glyph at the given font size.
+pub fn bitmap_glyph_advance(ch: char, font_size: u16) -> u32 {
+    crate::bitmap_font::glyph_advance(ch) * bitmap_scale(font_size)
+}
+
+/// Integer scale the bitmap font is drawn at for `font_size`.
+fn bitmap_scale(font_size: u16) -> u32 {
+    if font_size >= BITMAP_GLYPH_WIDTH as u16 {
         (font_size / BITMAP_GLYPH_WIDTH as u16) as u32
     } else {
         1
-    };
-    let width: u32 = text.chars().map(crate::bitmap_font::glyph_advance).sum();
-    width * scale
+    }
 }
 
 /// A color in RGBA format (0-255 per channel).
@@ -462,6 +471,14 @@ pub trait SdiBackend {
         (font_size as f32 * 1.2) as u32
     }
 
+    /// Advance width of a single glyph: how far the pen moves after
+    /// drawing `ch`. `measure_text` of a string is the sum of its glyph
+    /// advances, so layout that breaks text glyph by glyph can use this
+    /// to match what `draw_text` produces. Defaults to the bitmap font.
+    fn glyph_advance(&self, ch: char, font_size: u16) -> u32 {
+        bitmap_glyph_advance(ch, font_size)
+    }
+
     /// Measure both width and height of a text string.
     fn measure_text_extents(&self, text: &str, font_size: u16) -> (u32, u32) {
         (
@@ -642,6 +659,55 @@ pub trait SdiBackend {
         self.blit(tex, x, y, w, h)
     }
 
+    /// Blit a texture rotated by `angle_degrees` clockwise.
+    ///
+    /// The texture is scaled to `w`x`h` at (`x`, `y`) and then rotated
+    /// about `center`, given relative to that destination rectangle
+    /// (`None` rotates about its middle). The default implementation
+    /// draws nothing; backends that can rotate sprites override it.
+    fn blit_rotated(
+        &mut self,
+        tex: TextureId,
+        x: i32,
+        y: i32,
+        w: u32,
+        h: u32,
+        angle_degrees: f32,
+        center: Option<(i32, i32)>,
+    ) -> Result<()> {
+        let _ = (tex, x, y, w, h, angle_degrees, center);
+        Ok(())
+    }
+
+    // -----------------------------------------------------------------------
+    // Extended: Render Targets
+    // -----------------------------------------------------------------------
+
+    /// Create a `w`x`h` offscreen texture that drawing can be redirected
+    /// into with [`set_render_target`](Self::set_render_target). It is
+    /// blitted and destroyed like any other texture.
+    ///
+    /// The default fails: the backend cannot draw offscreen.
+    fn create_render_target(&mut self, w: u32, h: u32) -> Result<TextureId> {
+        let _ = (w, h);
+        Err(OasisError::Backend(
+            "render targets are not supported by this backend".to_string(),
+        ))
+    }
+
+    /// Draw into `target`, a texture from
+    /// [`create_render_target`](Self::create_render_target), instead of
+    /// the screen; `None` goes back to the screen. Coordinates are the
+    /// target's own, starting unclipped.
+    fn set_render_target(&mut self, target: Option<TextureId>) -> Result<()> {
+        match target {
+            None => Ok(()),
+            Some(_) => Err(OasisError::Backend(
+                "render targets are not supported by this backend".to_string(),
+            )),
+        }
+    }
+
     // -----------------------------------------------------------------------
     // Extended: Clip and Transform Stack (Phase 5)
     // -----------------------------------------------------------------------
@@ -712,6 +778,17 @@ pub trait SdiBackend {
 pub trait InputBackend {
     /// Poll for pending input events.
     fn poll_events(&mut self) -> Vec<InputEvent>;
+
+    /// Text on the system clipboard, if the platform has one.
+    fn clipboard_text(&self) -> Option<String> {
+        None
+    }
+
+    /// Replace the system clipboard contents. A no-op on platforms
+    /// without a clipboard.
+    fn set_clipboard_text(&mut self, _text: &str) -> Result<()> {
+        Ok(())
+    }
 }
 
 /// Network backend trait.
@@ -935,6 +1012,14 @@ mod tests {
 
     // -- Default: fill_rounded_rect falls back to fill_rect --
 
+    #[test]
+    fn render_targets_are_unsupported_by_default() {
+        let mut backend = RecordingBackend::new();
+        assert!(backend.create_render_target(16, 16).is_err());
+        assert!(backend.set_render_target(Some(TextureId(1))).is_err());
+        backend.set_render_target(None).unwrap();
+    }
+
     #[test]
     fn fill_rounded_rect_defaults_to_fill_rect() {
         let mut b = RecordingBackend::new();
@@ -1115,6 +1200,17 @@ mod tests {
         assert_eq!(b.measure_text_height(10), 12);
     }
 
+    #[test]
+    fn glyph_advance_default_matches_measure_text() {
+        let b = RecordingBackend::new();
+        for size in [6, 8, 12, 16, 24] {
+            let sum: u32 = "Wim, 0!".chars().map(|c| b.glyph_advance(c, size)).sum();
+            assert_eq!(sum, b.measure_text("Wim, 0!", size));
+        }
+        assert_eq!(b.glyph_advance('A', 8), 7);
+        assert_eq!(b.glyph_advance('A', 16), 14);
+    }
+
     #[test]
     fn measure_text_extents_default() {
         let b = RecordingBackend::new();
@@ -1225,6 +1321,14 @@ mod tests {
         assert!(calls[0].starts_with("blit(7,"));
     }
 
+    #[test]
+    fn blit_rotated_defaults_to_noop() {
+        let mut b = RecordingBackend::new();
+        b.blit_rotated(TextureId(7), 0, 0, 16, 16, 45.0, None)
+            .unwrap();
+        assert!(b.calls().is_empty());
+    }
+
     // -- Clip/transform stack defaults --
 
     #[test]
@@ -1,5 +1,6 @@
 //! Configuration types for OASIS_OS instances.
 
+use std::collections::BTreeMap;
 use std::path::{Path, PathBuf};
 
 use serde::{Deserialize, Serialize};
@@ -15,8 +16,23 @@ pub struct OasisConfig {
     pub screen_height: u32,
     /// Window title (desktop only).
     pub window_title: String,
+    /// Integer factor the desktop window is enlarged by. Drawing still
+    /// uses `screen_width` x `screen_height` coordinates.
+    #[serde(default = "default_window_scale")]
+    pub window_scale: u32,
+    /// How the virtual screen follows the desktop window when it is
+    /// resized.
+    #[serde(default)]
+    pub window_scaling: WindowScaling,
+    /// Keep window content in offscreen textures, repainting a window
+    /// only when it changes. Turn off if a GPU driver draws it wrong.
+    #[serde(default = "default_cache_window_content")]
+    pub cache_window_content: bool,
     /// Remote terminal listen port (0 = disabled).
     pub terminal_port: u16,
+    /// Game controller stick tuning and button remapping (desktop only).
+    #[serde(default)]
+    pub gamepad: GamepadConfig,
 }
 
 impl Default for OasisConfig {
@@ -26,11 +42,96 @@ impl Default for OasisConfig {
             screen_width: 480,
             screen_height: 272,
             window_title: String::from("OASIS_OS"),
+            window_scale: default_window_scale(),
+            window_scaling: WindowScaling::default(),
+            cache_window_content: default_cache_window_content(),
             terminal_port: 0,
+            gamepad: GamepadConfig::default(),
         }
     }
 }
 
+fn default_window_scale() -> u32 {
+    2
+}
+
+fn default_cache_window_content() -> bool {
+    true
+}
+
+/// How a resizable desktop window shows the virtual screen.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum WindowScaling {
+    /// Enlarge the virtual screen by whole multiples and letterbox the
+    /// rest, keeping pixels crisp.
+    #[default]
+    Integer,
+    /// Enlarge the virtual screen as far as the window allows, keeping
+    /// its aspect ratio, with filtered scaling.
+    Smooth,
+    /// Resize the virtual screen with the window, at `window_scale`
+    /// window pixels per pixel. The backend reports each new size with
+    /// [`InputEvent::Resized`](crate::input::InputEvent::Resized).
+    Native,
+}
+
+/// Game controller settings, the `[gamepad]` table of the config file.
+///
+/// ```toml
+/// [gamepad]
+/// deadzone = 6000
+/// cursor_speed = 8
+///
+/// [gamepad.buttons]
+/// a = "cancel"
+/// b = "confirm"
+/// guide = "start"
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GamepadConfig {
+    /// Left stick deflection (out of 32767) below which it is ignored.
+    pub deadzone: u16,
+    /// Cursor speed in pixels per frame at full stick deflection.
+    pub cursor_speed: u32,
+    /// Buttons mapped differently from the PSP-style default layout,
+    /// keyed by SDL game controller button name (`a`, `b`, `x`, `y`,
+    /// `back`, `guide`, `start`, `leftshoulder`, `rightshoulder`,
+    /// `dpup`, `dpdown`, `dpleft`, `dpright`, ...).
+    pub buttons: BTreeMap<String, PadAction>,
+}
+
+impl Default for GamepadConfig {
+    fn default() -> Self {
+        Self {
+            deadzone: 8000,
+            cursor_speed: 6,
+            buttons: BTreeMap::new(),
+        }
+    }
+}
+
+/// What a game controller button does.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PadAction {
+    Up,
+    Down,
+    Left,
+    Right,
+    Confirm,
+    Cancel,
+    Triangle,
+    Square,
+    Start,
+    Select,
+    LeftTrigger,
+    RightTrigger,
+    /// Ignore the button.
+    None,
+}
+
 impl OasisConfig {
     /// Load configuration from a TOML file, falling back to defaults on error.
     pub fn load(path: &Path) -> Self {
@@ -77,5 +178,49 @@ mod tests {
         let back: OasisConfig = toml::from_str(&toml_str).unwrap();
         assert_eq!(back.screen_width, cfg.screen_width);
         assert_eq!(back.screen_height, cfg.screen_height);
+        assert_eq!(back.window_scale, cfg.window_scale);
+    }
+
+    #[test]
+    fn window_scale_defaults_when_missing() {
+        let cfg: OasisConfig = toml::from_str(
+            "skin_path = \"skins/classic\"\nscreen_width = 480\nscreen_height = 272\n\
+             window_title = \"OASIS_OS\"\nterminal_port = 0\n",
+        )
+        .unwrap();
+        assert_eq!(cfg.window_scale, 2);
+        assert_eq!(cfg.window_scaling, WindowScaling::Integer);
+        assert!(cfg.cache_window_content);
+    }
+
+    #[test]
+    fn window_scaling_reads_lowercase_names() {
+        let cfg: OasisConfig = toml::from_str(
+            "skin_path = \"skins/classic\"\nscreen_width = 480\nscreen_height = 272\n\
+             window_title = \"OASIS_OS\"\nterminal_port = 0\nwindow_scaling = \"native\"\n",
+        )
+        .unwrap();
+        assert_eq!(cfg.window_scaling, WindowScaling::Native);
+    }
+
+    #[test]
+    fn gamepad_table_overrides_tuning_and_buttons() {
+        let base = "skin_path = \"skins/classic\"\nscreen_width = 480\nscreen_height = 272\n\
+                    window_title = \"OASIS_OS\"\nterminal_port = 0\n";
+        let cfg: OasisConfig = toml::from_str(base).unwrap();
+        assert_eq!(cfg.gamepad, GamepadConfig::default());
+
+        let cfg: OasisConfig = toml::from_str(&format!(
+            "{base}[gamepad]\ncursor_speed = 9\n\
+             [gamepad.buttons]\na = \"cancel\"\nguide = \"left_trigger\"\nx = \"none\"\n"
+        ))
+        .unwrap();
+        assert_eq!(cfg.gamepad.deadzone, 8000);
+        assert_eq!(cfg.gamepad.cursor_speed, 9);
+        assert_eq!(cfg.gamepad.buttons["a"], PadAction::Cancel);
+        assert_eq!(cfg.gamepad.buttons["guide"], PadAction::LeftTrigger);
+        assert_eq!(cfg.gamepad.buttons["x"], PadAction::None);
+        let back: OasisConfig = toml::from_str(&toml::to_string(&cfg).unwrap()).unwrap();
+        assert_eq!(back.gamepad, cfg.gamepad);
     }
 }
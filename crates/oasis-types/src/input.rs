@@ -22,6 +22,19 @@ pub enum InputEvent {
     TextInput(char),
     /// Backspace / delete-left.
     Backspace,
+    /// Tab key (completion in the terminal).
+    Tab,
+    /// Text pasted from the system clipboard.
+    Paste(String),
+    /// A text-editing key from a physical keyboard, with the modifiers
+    /// held. Plain arrows stay [`ButtonPress`](Self::ButtonPress).
+    EditKey {
+        key: EditKey,
+        /// Shift: extend the selection.
+        shift: bool,
+        /// Ctrl: move by word.
+        ctrl: bool,
+    },
     /// Pointer click at absolute position (mouse or touch).
     PointerClick { x: i32, y: i32 },
     /// Pointer released.
@@ -30,6 +43,9 @@ pub enum InputEvent {
     FocusGained,
     /// The OS instance lost focus.
     FocusLost,
+    /// The virtual screen changed size (a desktop window resized with
+    /// [`WindowScaling::Native`](crate::config::WindowScaling::Native)).
+    Resized { w: u32, h: u32 },
     /// User requested quit (window close, etc.).
     Quit,
 }
@@ -49,6 +65,20 @@ pub enum Button {
     Select,
 }
 
+/// Keys for editing text beyond the shared button set.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum EditKey {
+    Left,
+    Right,
+    Home,
+    End,
+    /// Delete the character after the cursor.
+    Delete,
+    SelectAll,
+    Copy,
+    Cut,
+}
+
 /// Shoulder / trigger buttons.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum Trigger {
@@ -247,10 +277,18 @@ mod tests {
             InputEvent::TriggerRelease(Trigger::Left),
             InputEvent::TextInput('x'),
             InputEvent::Backspace,
+            InputEvent::Tab,
+            InputEvent::Paste("x".to_string()),
+            InputEvent::EditKey {
+                key: EditKey::Home,
+                shift: false,
+                ctrl: false,
+            },
             InputEvent::PointerClick { x: 0, y: 0 },
             InputEvent::PointerRelease { x: 0, y: 0 },
             InputEvent::FocusGained,
             InputEvent::FocusLost,
+            InputEvent::Resized { w: 0, h: 0 },
             InputEvent::Quit,
         ];
         for (i, a) in events.iter().enumerate() {
@@ -10,6 +10,7 @@ pub mod color;
 pub mod config;
 pub mod error;
 pub mod input;
+pub mod nine_patch;
 pub mod pbp;
 pub mod shadow;
 pub mod tls;
@@ -0,0 +1,290 @@
+//! Nine-patch (9-slice) rendering for scalable themed borders.
+
+use crate::backend::{SdiBackend, TextureId};
+use crate::error::Result;
+
+/// Nine-patch definition for a texture.
+///
+/// The texture is divided into a 3x3 grid by four independent insets.
+/// Corners render at fixed size, edges stretch in one dimension, and the
+/// center stretches in both. When the destination is smaller than two
+/// opposite corners together, the corners are clipped (keeping their
+/// outer parts) and the edges and center between them are not drawn.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NinePatch {
+    /// Source texture.
+    pub texture: TextureId,
+    /// Total texture width in pixels.
+    pub tex_width: u32,
+    /// Total texture height in pixels.
+    pub tex_height: u32,
+    /// Left edge margin in pixels.
+    pub left: u16,
+    /// Right edge margin in pixels.
+    pub right: u16,
+    /// Top edge margin in pixels.
+    pub top: u16,
+    /// Bottom edge margin in pixels.
+    pub bottom: u16,
+}
+
+/// One column (or row) of the grid: source offset and size, destination
+/// offset and size.
+type Span = (u32, u32, i32, u32);
+
+/// Split `size` destination pixels between a start and an end inset.
+///
+/// Insets that fit are kept. Otherwise `size` is shared between them in
+/// proportion to their sizes, so the two never overlap.
+fn clip_insets(start: u32, end: u32, size: u32) -> (u32, u32) {
+    if start + end <= size {
+        return (start, end);
+    }
+    let clipped = (start as u64 * size as u64 / (start + end) as u64) as u32;
+    (clipped, size - clipped)
+}
+
+/// The three spans along one axis of a texture `tex` pixels long drawn
+/// `size` pixels long at `pos`.
+fn spans(start: u32, end: u32, tex: u32, pos: i32, size: u32) -> [Span; 3] {
+    let (dst_start, dst_end) = clip_insets(start, end, size);
+    let dst_mid = size - dst_start - dst_end;
+    [
+        (0, dst_start, pos, dst_start),
+        (start, tex - start - end, pos + dst_start as i32, dst_mid),
+        (
+            tex - dst_end,
+            dst_end,
+            pos + (size - dst_end) as i32,
+            dst_end,
+        ),
+    ]
+}
+
+impl NinePatch {
+    /// Draw the nine-patch at the given screen position and size.
+    ///
+    /// Nothing is drawn if the insets do not fit in the texture.
+    pub fn draw(&self, backend: &mut dyn SdiBackend, x: i32, y: i32, w: u32, h: u32) -> Result<()> {
+        let l = self.left as u32;
+        let r = self.right as u32;
+        let t = self.top as u32;
+        let b = self.bottom as u32;
+        if l + r > self.tex_width || t + b > self.tex_height {
+            return Ok(());
+        }
+
+        let columns = spans(l, r, self.tex_width, x, w);
+        let rows = spans(t, b, self.tex_height, y, h);
+        for &(src_y, src_h, dst_y, dst_h) in &rows {
+            for &(src_x, src_w, dst_x, dst_w) in &columns {
+                if src_w == 0 || src_h == 0 || dst_w == 0 || dst_h == 0 {
+                    continue;
+                }
+                backend.blit_sub(
+                    self.texture,
+                    src_x,
+                    src_y,
+                    src_w,
+                    src_h,
+                    dst_x,
+                    dst_y,
+                    dst_w,
+                    dst_h,
+                )?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::Color;
+
+    fn sample() -> NinePatch {
+        NinePatch {
+            texture: TextureId(1),
+            tex_width: 64,
+            tex_height: 64,
+            left: 8,
+            right: 8,
+            top: 8,
+            bottom: 8,
+        }
+    }
+
+    type Rects = ((u32, u32, u32, u32), (i32, i32, u32, u32));
+
+    /// Backend recording the `(src, dst)` rects of every `blit_sub`
+    /// call; anything else drawn is a test failure.
+    #[derive(Default)]
+    struct BlitRecorder {
+        blits: Vec<Rects>,
+    }
+
+    impl SdiBackend for BlitRecorder {
+        fn init(&mut self, _w: u32, _h: u32) -> Result<()> {
+            Ok(())
+        }
+        fn clear(&mut self, _color: Color) -> Result<()> {
+            unreachable!("nine-patches only blit")
+        }
+        fn blit(&mut self, _tex: TextureId, _x: i32, _y: i32, _w: u32, _h: u32) -> Result<()> {
+            unreachable!("nine-patches only blit sub-rects")
+        }
+        fn fill_rect(&mut self, _x: i32, _y: i32, _w: u32, _h: u32, _c: Color) -> Result<()> {
+            unreachable!("nine-patches only blit")
+        }
+        fn draw_text(&mut self, _t: &str, _x: i32, _y: i32, _s: u16, _c: Color) -> Result<()> {
+            unreachable!("nine-patches only blit")
+        }
+        fn swap_buffers(&mut self) -> Result<()> {
+            Ok(())
+        }
+        fn load_texture(&mut self, _w: u32, _h: u32, _data: &[u8]) -> Result<TextureId> {
+            Ok(TextureId(0))
+        }
+        fn destroy_texture(&mut self, _tex: TextureId) -> Result<()> {
+            Ok(())
+        }
+        fn set_clip_rect(&mut self, _x: i32, _y: i32, _w: u32, _h: u32) -> Result<()> {
+            Ok(())
+        }
+        fn reset_clip_rect(&mut self) -> Result<()> {
+            Ok(())
+        }
+        fn measure_text(&self, _text: &str, _font_size: u16) -> u32 {
+            0
+        }
+        fn read_pixels(&self, _x: i32, _y: i32, _w: u32, _h: u32) -> Result<Vec<u8>> {
+            Ok(Vec::new())
+        }
+        fn shutdown(&mut self) -> Result<()> {
+            Ok(())
+        }
+        fn blit_sub(
+            &mut self,
+            _tex: TextureId,
+            src_x: u32,
+            src_y: u32,
+            src_w: u32,
+            src_h: u32,
+            dst_x: i32,
+            dst_y: i32,
+            dst_w: u32,
+            dst_h: u32,
+        ) -> Result<()> {
+            self.blits
+                .push(((src_x, src_y, src_w, src_h), (dst_x, dst_y, dst_w, dst_h)));
+            Ok(())
+        }
+    }
+
+    /// `(src, dst)` rects of every `blit_sub` call drawing `np`.
+    fn blits(np: &NinePatch, x: i32, y: i32, w: u32, h: u32) -> Vec<Rects> {
+        let mut backend = BlitRecorder::default();
+        np.draw(&mut backend, x, y, w, h).unwrap();
+        backend.blits
+    }
+
+    #[test]
+    fn fields_accessible() {
+        let np = sample();
+        assert_eq!(np.texture, TextureId(1));
+        assert_eq!(np.tex_width, 64);
+        assert_eq!(np.tex_height, 64);
+        assert_eq!(np.left, 8);
+        assert_eq!(np.right, 8);
+        assert_eq!(np.top, 8);
+        assert_eq!(np.bottom, 8);
+    }
+
+    #[test]
+    fn asymmetric_margins() {
+        let np = NinePatch {
+            texture: TextureId(2),
+            tex_width: 100,
+            tex_height: 80,
+            left: 10,
+            right: 20,
+            top: 5,
+            bottom: 15,
+        };
+        assert_eq!(np.left, 10);
+        assert_eq!(np.right, 20);
+        assert_eq!(np.top, 5);
+        assert_eq!(np.bottom, 15);
+    }
+
+    #[test]
+    fn zero_margins() {
+        let np = NinePatch {
+            texture: TextureId(3),
+            tex_width: 32,
+            tex_height: 32,
+            left: 0,
+            right: 0,
+            top: 0,
+            bottom: 0,
+        };
+        assert_eq!(np.left + np.right, 0);
+        assert_eq!(np.top + np.bottom, 0);
+    }
+
+    #[test]
+    fn asymmetric_insets_stretch_center_and_edges() {
+        let np = NinePatch {
+            texture: TextureId(2),
+            tex_width: 100,
+            tex_height: 80,
+            left: 10,
+            right: 20,
+            top: 5,
+            bottom: 15,
+        };
+        let blits = blits(&np, 4, 6, 200, 120);
+        assert_eq!(blits.len(), 9);
+        // Top-left and bottom-right corners at texture size.
+        assert_eq!(blits[0], ((0, 0, 10, 5), (4, 6, 10, 5)));
+        assert_eq!(blits[8], ((80, 65, 20, 15), (184, 111, 20, 15)));
+        // Top edge stretches horizontally only.
+        assert_eq!(blits[1], ((10, 0, 70, 5), (14, 6, 170, 5)));
+        // Center stretches both ways.
+        assert_eq!(blits[4], ((10, 5, 70, 60), (14, 11, 170, 100)));
+    }
+
+    #[test]
+    fn small_destination_clips_corners() {
+        let np = NinePatch {
+            texture: TextureId(2),
+            tex_width: 100,
+            tex_height: 80,
+            left: 10,
+            right: 30,
+            top: 5,
+            bottom: 15,
+        };
+        let blits = blits(&np, 0, 0, 20, 40);
+        // No room for the top/bottom edges or the center columns.
+        assert!(blits.iter().all(|(src, _)| src.0 != 10));
+        let top: Vec<_> = blits.iter().filter(|(_, dst)| dst.1 == 0).collect();
+        assert_eq!(top.len(), 2);
+        // Corners share the 20px 1:3 and keep their outer pixels.
+        assert_eq!(*top[0], ((0, 0, 5, 5), (0, 0, 5, 5)));
+        assert_eq!(*top[1], ((85, 0, 15, 5), (5, 0, 15, 5)));
+        // Rows still fit, so the side edges stretch between them.
+        assert!(blits.contains(&((0, 5, 5, 60), (0, 5, 5, 20))));
+    }
+
+    #[test]
+    fn oversized_insets_draw_nothing() {
+        let np = NinePatch {
+            left: 40,
+            right: 40,
+            ..sample()
+        };
+        assert!(blits(&np, 0, 0, 200, 200).is_empty());
+    }
+}
@@ -7,6 +7,17 @@
 use crate::backend::NetworkStream;
 use crate::error::Result;
 
+/// The certificate a server presented during the TLS handshake.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PeerCertificate {
+    /// SHA-256 digest of the DER-encoded leaf certificate, as lowercase
+    /// hex.
+    pub fingerprint: String,
+    /// End of the certificate's validity period (`notAfter`), in
+    /// seconds since the Unix epoch, if the provider could read it.
+    pub not_after: Option<u64>,
+}
+
 /// Provides TLS client connections.
 ///
 /// Each platform backend implements this with its preferred TLS library
@@ -20,6 +31,111 @@ pub trait TlsProvider: Send + Sync {
         stream: Box<dyn NetworkStream>,
         server_name: &str,
     ) -> Result<Box<dyn NetworkStream>>;
+
+    /// Like [`connect_tls`](Self::connect_tls), but also report the
+    /// certificate the server presented, for trust-on-first-use
+    /// pinning.
+    ///
+    /// The default implementation reports no certificate; providers
+    /// that can inspect the handshake should override it.
+    fn connect_tls_peer(
+        &self,
+        stream: Box<dyn NetworkStream>,
+        server_name: &str,
+    ) -> Result<(Box<dyn NetworkStream>, Option<PeerCertificate>)> {
+        Ok((self.connect_tls(stream, server_name)?, None))
+    }
+}
+
+/// Read the `notAfter` time of a DER-encoded X.509 certificate, in
+/// seconds since the Unix epoch.
+///
+/// Only walks as far as the validity field; returns `None` if the
+/// encoding is not what RFC 5280 describes.
+pub fn x509_not_after(der: &[u8]) -> Option<u64> {
+    let (tag, cert, _) = der_tlv(der)?;
+    if tag != 0x30 {
+        return None;
+    }
+    let (tag, tbs, _) = der_tlv(cert)?;
+    if tag != 0x30 {
+        return None;
+    }
+    let mut rest = tbs;
+    // Optional explicit version [0].
+    if rest.first() == Some(&0xA0) {
+        rest = der_tlv(rest)?.2;
+    }
+    // serialNumber, signature, issuer.
+    for _ in 0..3 {
+        rest = der_tlv(rest)?.2;
+    }
+    let (tag, validity, _) = der_tlv(rest)?;
+    if tag != 0x30 {
+        return None;
+    }
+    let (_, _, rest) = der_tlv(validity)?;
+    let (tag, not_after, _) = der_tlv(rest)?;
+    der_time(tag, not_after)
+}
+
+/// Split one DER tag-length-value off the front of `data`, returning
+/// the tag, the value, and the remaining bytes.
+fn der_tlv(data: &[u8]) -> Option<(u8, &[u8], &[u8])> {
+    let (&tag, rest) = data.split_first()?;
+    let (&first, mut rest) = rest.split_first()?;
+    let len = if first < 0x80 {
+        usize::from(first)
+    } else {
+        let count = usize::from(first & 0x7F);
+        if count == 0 || count > 4 || rest.len() < count {
+            return None;
+        }
+        let (bytes, tail) = rest.split_at(count);
+        rest = tail;
+        bytes.iter().fold(0usize, |n, &b| (n << 8) | usize::from(b))
+    };
+    if rest.len() < len {
+        return None;
+    }
+    let (value, rest) = rest.split_at(len);
+    Some((tag, value, rest))
+}
+
+/// Convert a DER `UTCTime` (tag 0x17) or `GeneralizedTime` (tag 0x18)
+/// in UTC to seconds since the Unix epoch.
+fn der_time(tag: u8, value: &[u8]) -> Option<u64> {
+    let text = std::str::from_utf8(value).ok()?.strip_suffix('Z')?;
+    let (year, rest) = match tag {
+        0x17 => {
+            let yy: u64 = text.get(..2)?.parse().ok()?;
+            (if yy >= 50 { 1900 + yy } else { 2000 + yy }, &text[2..])
+        },
+        0x18 => (text.get(..4)?.parse().ok()?, &text[4..]),
+        _ => return None,
+    };
+    if rest.len() != 10 || !rest.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    let field = |i: usize| rest[i..i + 2].parse::<u64>().ok();
+    let (month, day) = (field(0)?, field(2)?);
+    let (hour, minute, second) = (field(4)?, field(6)?, field(8)?);
+    if !(1..=12).contains(&month) || !(1..=31).contains(&day) || year < 1970 {
+        return None;
+    }
+
+    // Days since 1970-01-01 in the proleptic Gregorian calendar.
+    let (y, m) = if month <= 2 {
+        (year - 1, month + 9)
+    } else {
+        (year, month - 3)
+    };
+    let era = y / 400;
+    let yoe = y % 400;
+    let doy = (153 * m + 2) / 5 + day - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    let days = (era * 146_097 + doe).checked_sub(719_468)?;
+    Some(days * 86_400 + hour * 3_600 + minute * 60 + second)
 }
 
 #[cfg(test)]
@@ -52,4 +168,65 @@ mod tests {
         let provider = MockTlsProvider;
         let _: &dyn TlsProvider = &provider;
     }
+
+    #[test]
+    fn default_connect_tls_peer_reports_no_certificate() {
+        struct NullStream;
+        impl NetworkStream for NullStream {
+            fn read(&mut self, _buf: &mut [u8]) -> Result<usize> {
+                Ok(0)
+            }
+            fn write(&mut self, data: &[u8]) -> Result<usize> {
+                Ok(data.len())
+            }
+            fn close(&mut self) -> Result<()> {
+                Ok(())
+            }
+        }
+
+        let (_, cert) = MockTlsProvider
+            .connect_tls_peer(Box::new(NullStream), "example.com")
+            .unwrap();
+        assert_eq!(cert, None);
+    }
+
+    #[test]
+    fn der_time_formats() {
+        // UTCTime: two-digit years below 50 are 20xx.
+        assert_eq!(der_time(0x17, b"700101000000Z"), Some(0));
+        assert_eq!(der_time(0x17, b"240229123000Z"), Some(1_709_209_800));
+        // GeneralizedTime.
+        assert_eq!(der_time(0x18, b"20380119031408Z"), Some(2_147_483_648));
+        assert_eq!(der_time(0x18, b"20380119031408"), None);
+        assert_eq!(der_time(0x02, b"700101000000Z"), None);
+    }
+
+    #[test]
+    fn x509_not_after_walks_to_validity() {
+        // Hand-built skeleton: version, serial, signature, issuer,
+        // then validity with notBefore/notAfter as UTCTime.
+        let validity: Vec<u8> = [
+            &[0x30, 30][..],
+            &[0x17, 13],
+            b"700101000000Z",
+            &[0x17, 13],
+            b"300101000000Z",
+        ]
+        .concat();
+        let tbs_body: Vec<u8> = [
+            &[0xA0, 3, 0x02, 1, 2][..],
+            &[0x02, 1, 7],
+            &[0x30, 0],
+            &[0x30, 0],
+            &validity,
+        ]
+        .concat();
+        let mut tbs = vec![0x30, tbs_body.len() as u8];
+        tbs.extend_from_slice(&tbs_body);
+        let mut cert = vec![0x30, 0x81, tbs.len() as u8];
+        cert.extend_from_slice(&tbs);
+
+        assert_eq!(x509_not_after(&cert), Some(1_893_456_000));
+        assert_eq!(x509_not_after(&cert[..cert.len() - 1]), None);
+    }
 }
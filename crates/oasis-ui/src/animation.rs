@@ -1,8 +1,11 @@
 //! Animation primitives: easing functions and tweens.
 
-use crate::color::lerp_color;
 use oasis_types::backend::Color;
 
+/// Nominal frame length in milliseconds, for animations driven by a frame
+/// counter instead of elapsed time.
+pub const FRAME_MS: u32 = 16;
+
 /// Standard easing functions.
 ///
 /// Input `t` is clamped to `[0.0, 1.0]`. Output is the eased value.
@@ -61,6 +64,14 @@ pub mod easing {
         (2.0_f32.powf(-10.0 * t) * ((t - p / 4.0) * (2.0 * core::f32::consts::PI / p)).sin()) + 1.0
     }
 
+    /// Back ease-out (overshoots the target slightly, then settles).
+    pub fn ease_out_back(t: f32) -> f32 {
+        const C1: f32 = 1.70158;
+        const C3: f32 = C1 + 1.0;
+        let t1 = t.clamp(0.0, 1.0) - 1.0;
+        1.0 + C3 * t1 * t1 * t1 + C1 * t1 * t1
+    }
+
     /// Bounce ease-out (bounces multiple times before settling).
     pub fn ease_out_bounce(t: f32) -> f32 {
         let t = t.clamp(0.0, 1.0);
@@ -79,23 +90,107 @@ pub mod easing {
     }
 }
 
+/// An easing curve, selectable at runtime.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Easing {
+    /// See [`easing::linear`].
+    #[default]
+    Linear,
+    /// See [`easing::ease_in_quad`].
+    EaseInQuad,
+    /// See [`easing::ease_out_quad`].
+    EaseOutQuad,
+    /// See [`easing::ease_in_out_quad`].
+    EaseInOutQuad,
+    /// See [`easing::ease_out_cubic`].
+    EaseOutCubic,
+    /// See [`easing::ease_in_out_cubic`].
+    EaseInOutCubic,
+    /// See [`easing::ease_out_back`].
+    EaseOutBack,
+    /// See [`easing::ease_out_elastic`].
+    EaseOutElastic,
+    /// See [`easing::ease_out_bounce`].
+    EaseOutBounce,
+}
+
+impl Easing {
+    /// Apply the curve to `t` (clamped to `[0.0, 1.0]`).
+    pub fn apply(self, t: f32) -> f32 {
+        match self {
+            Self::Linear => easing::linear(t),
+            Self::EaseInQuad => easing::ease_in_quad(t),
+            Self::EaseOutQuad => easing::ease_out_quad(t),
+            Self::EaseInOutQuad => easing::ease_in_out_quad(t),
+            Self::EaseOutCubic => easing::ease_out_cubic(t),
+            Self::EaseInOutCubic => easing::ease_in_out_cubic(t),
+            Self::EaseOutBack => easing::ease_out_back(t),
+            Self::EaseOutElastic => easing::ease_out_elastic(t),
+            Self::EaseOutBounce => easing::ease_out_bounce(t),
+        }
+    }
+}
+
+/// Values a [`Tween`] can interpolate.
+///
+/// `t` may fall outside `[0.0, 1.0]` for overshooting easings such as
+/// [`Easing::EaseOutBack`].
+pub trait Lerp: Copy {
+    /// The value `t` of the way from `a` to `b`.
+    fn lerp(a: Self, b: Self, t: f32) -> Self;
+}
+
+impl Lerp for f32 {
+    fn lerp(a: Self, b: Self, t: f32) -> Self {
+        a + (b - a) * t
+    }
+}
+
+/// Interpolates each channel (including alpha) on its stored value, with
+/// no gamma correction. Channels are rounded and saturate at 0 and 255.
+impl Lerp for Color {
+    fn lerp(a: Self, b: Self, t: f32) -> Self {
+        let channel = |a: u8, b: u8| (a as f32 + (b as f32 - a as f32) * t).round() as u8;
+        Color::rgba(
+            channel(a.r, b.r),
+            channel(a.g, b.g),
+            channel(a.b, b.b),
+            channel(a.a, b.a),
+        )
+    }
+}
+
+/// Screen positions, rounded to the nearest pixel.
+impl Lerp for (i32, i32) {
+    fn lerp(a: Self, b: Self, t: f32) -> Self {
+        let axis = |a: i32, b: i32| (a as f32 + (b - a) as f32 * t).round() as i32;
+        (axis(a.0, b.0), axis(a.1, b.1))
+    }
+}
+
+impl Lerp for (f32, f32) {
+    fn lerp(a: Self, b: Self, t: f32) -> Self {
+        (f32::lerp(a.0, b.0, t), f32::lerp(a.1, b.1, t))
+    }
+}
+
 /// A running animation that interpolates between two values.
-pub struct Tween {
+pub struct Tween<T = f32> {
     /// Starting value.
-    pub start: f32,
+    pub start: T,
     /// Target value.
-    pub end: f32,
+    pub end: T,
     /// Total duration in milliseconds.
     pub duration_ms: u32,
     /// Elapsed time in milliseconds.
     pub elapsed_ms: u32,
-    /// Easing function to apply.
-    pub easing: fn(f32) -> f32,
+    /// Easing curve to apply.
+    pub easing: Easing,
 }
 
-impl Tween {
+impl<T: Lerp> Tween<T> {
     /// Create a new tween animation.
-    pub fn new(start: f32, end: f32, duration_ms: u32, easing: fn(f32) -> f32) -> Self {
+    pub fn new(start: T, end: T, duration_ms: u32, easing: Easing) -> Self {
         Self {
             start,
             end,
@@ -106,15 +201,9 @@ impl Tween {
     }
 
     /// Advance by `dt_ms` and return the current interpolated value.
-    pub fn tick(&mut self, dt_ms: u32) -> f32 {
-        self.elapsed_ms = (self.elapsed_ms + dt_ms).min(self.duration_ms);
-        let t = if self.duration_ms > 0 {
-            self.elapsed_ms as f32 / self.duration_ms as f32
-        } else {
-            1.0
-        };
-        let eased = (self.easing)(t);
-        self.start + (self.end - self.start) * eased
+    pub fn tick(&mut self, dt_ms: u32) -> T {
+        self.elapsed_ms = self.elapsed_ms.saturating_add(dt_ms).min(self.duration_ms);
+        self.value()
     }
 
     /// Check if the animation has completed.
@@ -123,47 +212,18 @@ impl Tween {
     }
 
     /// Current value without advancing time.
-    pub fn value(&self) -> f32 {
+    pub fn value(&self) -> T {
         let t = if self.duration_ms > 0 {
             self.elapsed_ms as f32 / self.duration_ms as f32
         } else {
             1.0
         };
-        let eased = (self.easing)(t);
-        self.start + (self.end - self.start) * eased
+        T::lerp(self.start, self.end, self.easing.apply(t))
     }
 }
 
 /// Tween between two colors over time.
-pub struct ColorTween {
-    /// Starting color.
-    pub start: Color,
-    /// Target color.
-    pub end: Color,
-    tween: Tween,
-}
-
-impl ColorTween {
-    /// Create a new color tween animation.
-    pub fn new(start: Color, end: Color, duration_ms: u32, easing: fn(f32) -> f32) -> Self {
-        Self {
-            start,
-            end,
-            tween: Tween::new(0.0, 1.0, duration_ms, easing),
-        }
-    }
-
-    /// Advance by `dt_ms` and return the current interpolated color.
-    pub fn tick(&mut self, dt_ms: u32) -> Color {
-        let t = self.tween.tick(dt_ms);
-        lerp_color(self.start, self.end, t)
-    }
-
-    /// Check if the animation has completed.
-    pub fn is_finished(&self) -> bool {
-        self.tween.is_finished()
-    }
-}
+pub type ColorTween = Tween<Color>;
 
 #[cfg(test)]
 mod tests {
@@ -171,7 +231,7 @@ mod tests {
 
     #[test]
     fn tween_linear() {
-        let mut tw = Tween::new(0.0, 100.0, 100, easing::linear);
+        let mut tw = Tween::new(0.0, 100.0, 100, Easing::Linear);
         assert_eq!(tw.tick(0), 0.0);
         assert_eq!(tw.tick(50), 50.0);
         assert_eq!(tw.tick(50), 100.0);
@@ -180,7 +240,7 @@ mod tests {
 
     #[test]
     fn tween_eased() {
-        let mut tw = Tween::new(0.0, 100.0, 100, easing::ease_in_quad);
+        let mut tw = Tween::new(0.0, 100.0, 100, Easing::EaseInQuad);
         let v = tw.tick(50);
         // ease_in_quad at t=0.5 is 0.25, so value should be 25.
         assert!((v - 25.0).abs() < 0.01);
@@ -202,11 +262,51 @@ mod tests {
             Color::rgb(0, 0, 0),
             Color::rgb(200, 100, 50),
             100,
-            easing::linear,
+            Easing::Linear,
         );
         let c = ct.tick(50);
         assert_eq!(c.r, 100);
         assert_eq!(c.g, 50);
         assert_eq!(c.b, 25);
     }
+
+    #[test]
+    fn ease_out_back_overshoots() {
+        assert_eq!(easing::ease_out_back(0.0), 0.0);
+        assert!((easing::ease_out_back(1.0) - 1.0).abs() < 1e-6);
+        let peak = (1..10)
+            .map(|i| easing::ease_out_back(i as f32 / 10.0))
+            .fold(0.0_f32, f32::max);
+        assert!(peak > 1.05);
+        assert_eq!(Easing::EaseOutBack.apply(0.5), easing::ease_out_back(0.5));
+        assert_eq!(Easing::default(), Easing::Linear);
+    }
+
+    #[test]
+    fn tween_positions_and_zero_duration() {
+        let mut tw = Tween::new((0, 100), (50, 0), 200, Easing::EaseInOutCubic);
+        assert_eq!(tw.value(), (0, 100));
+        assert_eq!(tw.tick(100), (25, 50));
+        assert!(!tw.is_finished());
+        assert_eq!(tw.tick(500), (50, 0));
+        assert!(tw.is_finished());
+
+        let instant = Tween::new((1.0, 2.0), (3.0, 4.0), 0, Easing::EaseOutQuad);
+        assert!(instant.is_finished());
+        assert_eq!(instant.value(), (3.0, 4.0));
+    }
+
+    #[test]
+    fn color_lerp_rounds_and_saturates_per_channel() {
+        let a = Color::rgba(0, 255, 10, 0);
+        let b = Color::rgba(255, 0, 11, 255);
+        let mid = Color::lerp(a, b, 0.5);
+        assert_eq!((mid.r, mid.g, mid.b, mid.a), (128, 128, 11, 128));
+        assert_eq!(Color::lerp(a, b, 1.0), b);
+        // EaseOutBack overshoots past the end color; channels clamp.
+        let over = Color::lerp(a, b, 1.1);
+        assert_eq!((over.r, over.g, over.a), (255, 0, 255));
+        let mut ct = ColorTween::new(a, b, 100, Easing::EaseOutBack);
+        assert_eq!(ct.tick(100), b);
+    }
 }
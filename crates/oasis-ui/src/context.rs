@@ -3,12 +3,50 @@
 //! All oasis-ui widgets render through `DrawContext`, which wraps a
 //! `&mut dyn SdiBackend` and provides access to the active theme.
 
-use crate::layout::Padding;
+use crate::layout::{self, HAlign, Padding, VAlign};
 use crate::shadow::Shadow;
 use crate::theme::Theme;
 use oasis_types::backend::{Color, SdiBackend, TextureId};
 use oasis_types::error::Result;
 
+/// Appended to the last line when `max_lines` cuts wrapped text short.
+pub const ELLIPSIS: &str = "...";
+
+/// How [`DrawContext::draw_text_wrapped`] places text in its rect.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TextLayout {
+    /// Horizontal alignment of each line.
+    pub h_align: HAlign,
+    /// Vertical alignment of the block of lines.
+    pub v_align: VAlign,
+    /// Maximum lines; surplus text is replaced by an ellipsis.
+    pub max_lines: Option<u32>,
+    /// Line height in pixels (0 = use font height).
+    pub line_height: u32,
+}
+
+impl Default for TextLayout {
+    fn default() -> Self {
+        Self {
+            h_align: HAlign::Left,
+            v_align: VAlign::Top,
+            max_lines: None,
+            line_height: 0,
+        }
+    }
+}
+
+impl TextLayout {
+    /// Lines aligned `h_align`, the block aligned `v_align`.
+    pub fn aligned(h_align: HAlign, v_align: VAlign) -> Self {
+        Self {
+            h_align,
+            v_align,
+            ..Self::default()
+        }
+    }
+}
+
 /// Drawing context wrapping a backend and theme.
 pub struct DrawContext<'a> {
     /// Backend for rendering operations.
@@ -127,6 +165,55 @@ impl<'a> DrawContext<'a> {
         self.backend.measure_text_extents(text, font_size)
     }
 
+    /// Break `text` into lines no wider than `width`, as drawn at
+    /// `font_size`. See [`wrap_text_with`].
+    pub fn wrap_text(
+        &self,
+        text: &str,
+        width: u32,
+        font_size: u16,
+        max_lines: Option<u32>,
+    ) -> Vec<String> {
+        wrap_text_with(text, width, max_lines, |s| {
+            self.backend.measure_text(s, font_size)
+        })
+    }
+
+    /// Word-wrap `text` to the width of `rect` (`x, y, w, h`) and draw it
+    /// aligned within the rect as `layout` says.
+    ///
+    /// Returns the height of the drawn lines, so callers can stack more
+    /// content below. Lines are not clipped to the rect's height; limit
+    /// them with [`TextLayout::max_lines`].
+    pub fn draw_text_wrapped(
+        &mut self,
+        text: &str,
+        rect: (i32, i32, u32, u32),
+        font_size: u16,
+        color: Color,
+        layout: TextLayout,
+    ) -> Result<u32> {
+        let (x, y, w, h) = rect;
+        let lh = if layout.line_height > 0 {
+            layout.line_height
+        } else {
+            self.backend.measure_text_height(font_size)
+        };
+        let lines = self.wrap_text(text, w, font_size, layout.max_lines);
+        let height = lines.len() as u32 * lh;
+        let top = y + layout::align_y(h, height, layout.v_align);
+        for (i, line) in lines.iter().enumerate() {
+            if line.is_empty() {
+                continue;
+            }
+            let lw = self.backend.measure_text(line, font_size);
+            let lx = x + layout::align_x(w, lw, layout.h_align);
+            let ly = top + (i as u32 * lh) as i32;
+            self.backend.draw_text(line, lx, ly, font_size, color)?;
+        }
+        Ok(height)
+    }
+
     /// Inner rect after applying padding.
     pub fn padded_rect(
         &self,
@@ -170,3 +257,162 @@ impl Drop for Region<'_> {
         let _ = self.backend.pop_region();
     }
 }
+
+/// Break `text` into lines no wider than `width`, measuring text with
+/// `measure`.
+///
+/// Lines break between words; a word wider than `width` on its own is
+/// split between characters. Explicit newlines are kept. With
+/// `max_lines` set, surplus lines are dropped and the last kept line
+/// ends in an [`ELLIPSIS`].
+pub fn wrap_text_with(
+    text: &str,
+    width: u32,
+    max_lines: Option<u32>,
+    measure: impl Fn(&str) -> u32,
+) -> Vec<String> {
+    let mut lines = Vec::new();
+    for paragraph in text.split('\n') {
+        let mut current = String::new();
+        for word in paragraph.split_whitespace() {
+            let candidate = if current.is_empty() {
+                word.to_string()
+            } else {
+                format!("{current} {word}")
+            };
+            if measure(&candidate) <= width {
+                current = candidate;
+                continue;
+            }
+            if !current.is_empty() {
+                lines.push(std::mem::take(&mut current));
+            }
+            if measure(word) <= width {
+                current = word.to_string();
+                continue;
+            }
+            // Hard-break a word that does not fit on a line of its own,
+            // keeping at least one character per line.
+            for ch in word.chars() {
+                current.push(ch);
+                if measure(&current) > width && current.chars().count() > 1 {
+                    current.pop();
+                    lines.push(std::mem::take(&mut current));
+                    current.push(ch);
+                }
+            }
+        }
+        lines.push(current);
+    }
+
+    if let Some(max) = max_lines
+        && lines.len() > max as usize
+    {
+        lines.truncate(max as usize);
+        if let Some(last) = lines.last_mut() {
+            let mut kept = std::mem::take(last);
+            *last = loop {
+                let line = format!("{}{ELLIPSIS}", kept.trim_end());
+                if kept.is_empty() || measure(&line) <= width {
+                    break line;
+                }
+                kept.pop();
+            };
+        }
+    }
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::MockBackend;
+
+    const TEXT: &str = "The quick brown fox jumps over the lazy dog";
+
+    #[test]
+    fn wrapped_text_breaks_at_known_positions() {
+        let theme = Theme::dark();
+        let mut backend = MockBackend::new();
+        let width = backend.measure_text("The quick brown", 8);
+        let height = {
+            let mut ctx = DrawContext::new(&mut backend, &theme);
+            ctx.draw_text_wrapped(
+                TEXT,
+                (0, 0, width, 100),
+                8,
+                Color::WHITE,
+                TextLayout::default(),
+            )
+            .unwrap()
+        };
+        let lh = backend.measure_text_height(8);
+        let lines: Vec<_> = backend
+            .text_positions()
+            .into_iter()
+            .map(|(text, x, y, _)| (text.to_string(), x, y))
+            .collect();
+        assert_eq!(
+            lines,
+            [
+                ("The quick brown".to_string(), 0, 0),
+                ("fox jumps over".to_string(), 0, lh as i32),
+                ("the lazy dog".to_string(), 0, 2 * lh as i32),
+            ]
+        );
+        assert_eq!(height, 3 * lh);
+    }
+
+    #[test]
+    fn wrapped_text_aligns_within_the_rect() {
+        let theme = Theme::dark();
+        let mut backend = MockBackend::new();
+        let (w, lw) = (200, backend.measure_text("one", 8));
+        let lh = backend.measure_text_height(8);
+        {
+            let mut ctx = DrawContext::new(&mut backend, &theme);
+            let centred = TextLayout::aligned(HAlign::Center, VAlign::Center);
+            ctx.draw_text_wrapped("one", (10, 20, w, 100), 8, Color::WHITE, centred)
+                .unwrap();
+            let right = TextLayout::aligned(HAlign::Right, VAlign::Top);
+            ctx.draw_text_wrapped("one\none", (10, 20, w, 100), 8, Color::WHITE, right)
+                .unwrap();
+        }
+        let positions = backend.text_positions();
+        assert!(positions.contains(&(
+            "one",
+            10 + layout::center(w, lw),
+            20 + layout::center(100, lh),
+            8
+        )));
+        assert!(positions.contains(&("one", 10 + (w - lw) as i32, 20, 8)));
+        assert!(positions.contains(&("one", 10 + (w - lw) as i32, 20 + lh as i32, 8)));
+    }
+
+    #[test]
+    fn wrapped_text_ends_in_an_ellipsis_at_max_lines() {
+        let theme = Theme::dark();
+        let mut backend = MockBackend::new();
+        let width = backend.measure_text("The quick brown", 8);
+        let layout = TextLayout {
+            max_lines: Some(2),
+            line_height: 10,
+            ..TextLayout::default()
+        };
+        let height = {
+            let mut ctx = DrawContext::new(&mut backend, &theme);
+            ctx.draw_text_wrapped(TEXT, (0, 0, width, 100), 8, Color::WHITE, layout)
+                .unwrap()
+        };
+        assert_eq!(height, 20);
+        let positions = backend.text_positions();
+        assert_eq!(positions.len(), 2);
+        let (last, _, y, _) = positions[1];
+        assert_eq!(y, 10);
+        assert!(
+            last.starts_with("fox") && last.ends_with(ELLIPSIS),
+            "{last}"
+        );
+        assert!(backend.measure_text(last, 8) <= width);
+    }
+}
@@ -0,0 +1,364 @@
+//! Dialog widget: modal message box over a dimmed screen.
+//!
+//! A [`Dialog`] draws itself as a centered panel with a title, or just its
+//! body -- the message above a row of buttons -- into a rectangle the
+//! caller owns, as the window manager does for its dialog windows.
+
+use crate::button::{Button, ButtonState};
+use crate::context::{DrawContext, TextLayout, wrap_text_with};
+use crate::layout;
+use crate::theme::Theme;
+use crate::widget::Widget;
+use oasis_types::backend::{BITMAP_GLYPH_HEIGHT, BITMAP_GLYPH_WIDTH, bitmap_measure_text};
+use oasis_types::error::Result;
+use oasis_types::input::{Button as InputButton, InputEvent};
+
+/// Widest a dialog panel gets, in pixels.
+const MAX_WIDTH: u32 = 320;
+
+/// Narrowest a dialog button gets, in pixels.
+const MIN_BUTTON_WIDTH: u32 = 64;
+
+/// How a dialog was closed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DialogResult {
+    /// The button at this index was chosen.
+    Button(usize),
+    /// Closed with the Cancel button without choosing one.
+    Dismissed,
+}
+
+/// A modal dialog: a title, a message and a row of buttons.
+///
+/// While a dialog is open the caller should pass every input event to
+/// [`Dialog::handle_input`] and nothing else. Left/Right (or Up/Down)
+/// move the focus between the buttons, Confirm chooses the focused one
+/// and Cancel dismisses the dialog.
+///
+/// Its size is measured with the bitmap font metrics, so a window can be
+/// sized for a dialog before anything is drawn.
+pub struct Dialog {
+    /// Title text.
+    pub title: String,
+    /// Message text, wrapped to the dialog width.
+    pub message: String,
+    /// Button labels, left to right. There must be at least one.
+    pub buttons: Vec<String>,
+    /// Focused button, chosen by Confirm.
+    pub focused: usize,
+    /// Button under the pointer.
+    pub hovered: Option<usize>,
+    /// Button that dismissing the dialog stands for, for callers that
+    /// want an index either way (see [`Dialog::chosen_index`]).
+    pub cancel: usize,
+    /// Alpha of the overlay dimming the screen.
+    pub dim_alpha: u8,
+}
+
+impl Dialog {
+    /// A dialog with the given buttons, focusing the first. Dismissing it
+    /// stands for the last.
+    pub fn new(title: impl Into<String>, message: impl Into<String>, buttons: &[&str]) -> Self {
+        Self {
+            title: title.into(),
+            message: message.into(),
+            buttons: buttons.iter().map(|b| b.to_string()).collect(),
+            focused: 0,
+            hovered: None,
+            cancel: buttons.len().saturating_sub(1),
+            dim_alpha: 160,
+        }
+    }
+
+    /// A dialog asking to confirm, with OK and Cancel buttons.
+    pub fn confirm(title: impl Into<String>, message: impl Into<String>) -> Self {
+        Self::new(title, message, &["OK", "Cancel"])
+    }
+
+    /// A dialog showing a message, with a single OK button.
+    pub fn alert(title: impl Into<String>, message: impl Into<String>) -> Self {
+        Self::new(title, message, &["OK"])
+    }
+
+    /// Number of buttons.
+    pub fn button_count(&self) -> usize {
+        self.buttons.len()
+    }
+
+    /// The button `result` chose, counting a dismissal as the cancel
+    /// button.
+    pub fn chosen_index(&self, result: DialogResult) -> usize {
+        match result {
+            DialogResult::Button(index) => index,
+            DialogResult::Dismissed => self.cancel,
+        }
+    }
+
+    /// Handle an input event. Returns the result once the dialog closes.
+    ///
+    /// Every event is consumed, so nothing reaches the UI behind the
+    /// dialog. Pointer input needs the dialog's position; hosts that
+    /// support it use [`Dialog::button_at`].
+    pub fn handle_input(&mut self, event: &InputEvent) -> Option<DialogResult> {
+        let InputEvent::ButtonPress(button) = event else {
+            return None;
+        };
+        let last = self.button_count().saturating_sub(1);
+        match button {
+            InputButton::Left | InputButton::Up => self.focused = self.focused.saturating_sub(1),
+            InputButton::Right | InputButton::Down => self.focused = (self.focused + 1).min(last),
+            InputButton::Confirm => return Some(DialogResult::Button(self.focused.min(last))),
+            InputButton::Cancel => return Some(DialogResult::Dismissed),
+            _ => {},
+        }
+        None
+    }
+
+    /// Size of the body -- padding, the wrapped message and the button
+    /// row -- in a dialog at most `max_w` wide.
+    pub fn body_size(&self, theme: &Theme, max_w: u32) -> (u32, u32) {
+        let pad = theme.spacing_md as u32;
+        let gap = theme.spacing_sm as u32;
+        let buttons_w = self.button_widths(theme).iter().sum::<u32>()
+            + gap * (self.button_count() as u32).saturating_sub(1);
+        let w = MAX_WIDTH.min(max_w).max(buttons_w + 2 * pad);
+        let lines = wrap_text_with(&self.message, w - 2 * pad, None, |s| {
+            bitmap_measure_text(s, theme.font_size_md)
+        })
+        .len() as u32;
+        let text_h = text_height(theme.font_size_md);
+        (
+            w,
+            pad + lines * text_h + 2 * gap + button_height(theme) + pad,
+        )
+    }
+
+    /// Rectangles of the buttons in the body rectangle `(x, y, w, h)`,
+    /// right-aligned along its bottom edge.
+    pub fn button_rects(
+        &self,
+        theme: &Theme,
+        (x, y, w, h): (i32, i32, u32, u32),
+    ) -> Vec<(i32, i32, u32, u32)> {
+        let pad = theme.spacing_md as i32;
+        let gap = theme.spacing_sm as i32;
+        let bh = button_height(theme);
+        let widths = self.button_widths(theme);
+        let total: i32 = widths.iter().map(|bw| *bw as i32 + gap).sum::<i32>() - gap;
+        let mut bx = x + w as i32 - pad - total;
+        let by = y + h as i32 - pad - bh as i32;
+        widths
+            .into_iter()
+            .map(|bw| {
+                let rect = (bx, by, bw, bh);
+                bx += bw as i32 + gap;
+                rect
+            })
+            .collect()
+    }
+
+    /// Index of the button at `(x, y)` in the body rectangle `body`.
+    pub fn button_at(
+        &self,
+        theme: &Theme,
+        body: (i32, i32, u32, u32),
+        x: i32,
+        y: i32,
+    ) -> Option<usize> {
+        self.button_rects(theme, body)
+            .iter()
+            .position(|&(bx, by, bw, bh)| {
+                x >= bx && y >= by && x < bx + bw as i32 && y < by + bh as i32
+            })
+    }
+
+    /// Draw the body -- the message and the buttons -- in `body`.
+    pub fn draw_body(&self, ctx: &mut DrawContext<'_>, body: (i32, i32, u32, u32)) -> Result<()> {
+        let pad = ctx.theme.spacing_md as u32;
+        let (x, y, w, h) = body;
+        let inner_w = w.saturating_sub(2 * pad);
+        let message = (x + pad as i32, y + pad as i32, inner_w, h);
+        let font = ctx.theme.font_size_md;
+        let layout = TextLayout {
+            line_height: text_height(font),
+            ..TextLayout::default()
+        };
+        let color = ctx.theme.text_secondary;
+        ctx.draw_text_wrapped(&self.message, message, font, color, layout)?;
+
+        let rects = self.button_rects(ctx.theme, body);
+        for (i, (label, (bx, by, bw, bh))) in self.buttons.iter().zip(rects).enumerate() {
+            let mut button = if i == self.focused {
+                Button::primary(label.as_str())
+            } else {
+                Button::new(label.as_str())
+            };
+            if self.hovered == Some(i) {
+                button.state = ButtonState::Hover;
+            }
+            button.draw(ctx, bx, by, bw, bh)?;
+        }
+        Ok(())
+    }
+
+    /// Widths of the buttons.
+    fn button_widths(&self, theme: &Theme) -> Vec<u32> {
+        self.buttons
+            .iter()
+            .map(|label| {
+                (bitmap_measure_text(label, theme.font_size_md) + 2 * theme.spacing_md as u32)
+                    .max(MIN_BUTTON_WIDTH)
+            })
+            .collect()
+    }
+}
+
+fn button_height(theme: &Theme) -> u32 {
+    text_height(theme.font_size_md) + 2 * theme.spacing_sm as u32
+}
+
+/// Height of a line of bitmap font text.
+fn text_height(font_size: u16) -> u32 {
+    BITMAP_GLYPH_HEIGHT * (font_size as u32 / BITMAP_GLYPH_WIDTH).max(1)
+}
+
+impl Widget for Dialog {
+    /// A dialog covers the whole area it is given.
+    fn measure(&self, _ctx: &DrawContext<'_>, available_w: u32, available_h: u32) -> (u32, u32) {
+        (available_w, available_h)
+    }
+
+    /// Dim the screen and draw the panel centered in the given area.
+    fn draw(&self, ctx: &mut DrawContext<'_>, x: i32, y: i32, w: u32, h: u32) -> Result<()> {
+        ctx.backend.dim_screen(self.dim_alpha)?;
+
+        let pad = ctx.theme.spacing_md as u32;
+        let title_h = text_height(ctx.theme.font_size_lg);
+        let (body_w, body_h) = self.body_size(ctx.theme, w.saturating_sub(2 * pad));
+        let panel_h = pad + title_h + body_h;
+        let px = x + layout::center(w, body_w);
+        let py = y + layout::center(h, panel_h);
+        ctx.panel(px, py, body_w, panel_h, 3)?;
+        ctx.label_styled(
+            &self.title,
+            px + pad as i32,
+            py + pad as i32,
+            ctx.theme.font_size_lg,
+            ctx.theme.text_primary,
+        )?;
+        let body_y = py + (pad + title_h) as i32;
+        self.draw_body(ctx, (px, body_y, body_w, body_h))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{DrawCall, MockBackend};
+    use crate::theme::Theme;
+
+    fn press(dialog: &mut Dialog, button: InputButton) -> Option<DialogResult> {
+        dialog.handle_input(&InputEvent::ButtonPress(button))
+    }
+
+    #[test]
+    fn constructors() {
+        let d = Dialog::confirm("Delete", "Delete file?");
+        assert_eq!(d.title, "Delete");
+        assert_eq!(d.button_count(), 2);
+        assert_eq!(d.focused, 0);
+        assert_eq!(d.cancel, 1);
+        let a = Dialog::alert("Done", "Saved.");
+        assert_eq!(a.button_count(), 1);
+        assert_eq!(a.chosen_index(DialogResult::Dismissed), 0);
+    }
+
+    #[test]
+    fn arrows_move_focus_and_confirm_resolves() {
+        let mut d = Dialog::confirm("t", "m");
+        assert_eq!(press(&mut d, InputButton::Right), None);
+        assert_eq!(d.focused, 1);
+        press(&mut d, InputButton::Down);
+        assert_eq!(d.focused, 1);
+        assert_eq!(
+            press(&mut d, InputButton::Confirm),
+            Some(DialogResult::Button(1))
+        );
+        press(&mut d, InputButton::Left);
+        press(&mut d, InputButton::Up);
+        assert_eq!(d.focused, 0);
+        assert_eq!(
+            press(&mut d, InputButton::Confirm),
+            Some(DialogResult::Button(0))
+        );
+        assert_eq!(
+            press(&mut d, InputButton::Cancel),
+            Some(DialogResult::Dismissed)
+        );
+    }
+
+    #[test]
+    fn alert_has_one_button_and_swallows_other_input() {
+        let mut a = Dialog::alert("t", "m");
+        press(&mut a, InputButton::Right);
+        assert_eq!(a.focused, 0);
+        assert_eq!(press(&mut a, InputButton::Triangle), None);
+        assert_eq!(a.handle_input(&InputEvent::TextInput('y')), None);
+        assert_eq!(
+            a.handle_input(&InputEvent::PointerClick { x: 0, y: 0 }),
+            None
+        );
+        assert_eq!(
+            press(&mut a, InputButton::Confirm),
+            Some(DialogResult::Button(0))
+        );
+    }
+
+    #[test]
+    fn draw_dims_screen_and_centers_panel() {
+        let theme = Theme::dark();
+        let mut backend = MockBackend::new();
+        {
+            let mut ctx = DrawContext::new(&mut backend, &theme);
+            let d = Dialog::confirm("Delete", "Delete this file from the memory stick?");
+            d.draw(&mut ctx, 0, 0, 480, 272).unwrap();
+        }
+        match &backend.calls[0] {
+            DrawCall::FillRect { w, h, color, .. } => {
+                assert_eq!((*w, *h), (480, 272));
+                assert_eq!(color.a, 160);
+            },
+            other => panic!("expected the dim overlay first, got {other:?}"),
+        }
+        let texts = backend.text_positions();
+        let (_, title_x, _, _) = texts.iter().find(|t| t.0 == "Delete").unwrap();
+        // The 320px panel is centered, so its content starts past x = 80.
+        assert!(*title_x > 80 && *title_x < 120);
+        assert!(backend.has_text("memory"));
+        assert!(backend.has_text("OK"));
+        assert!(backend.has_text("Cancel"));
+    }
+
+    #[test]
+    fn body_lays_out_buttons_right_aligned() {
+        let theme = Theme::dark();
+        let mut d = Dialog::new("Save", "Save changes?", &["Save", "Discard", "Cancel"]);
+        let (w, h) = d.body_size(&theme, 480);
+        assert_eq!(w, MAX_WIDTH);
+        let body = (100, 50, w, h);
+        let rects = d.button_rects(&theme, body);
+        assert_eq!(rects.len(), 3);
+        let (lx, ly, lw, lh) = rects[2];
+        assert_eq!(lx + lw as i32, 100 + (w - theme.spacing_md as u32) as i32);
+        assert_eq!(ly + lh as i32, 50 + (h - theme.spacing_md as u32) as i32);
+        assert!(rects[0].0 + (rects[0].2 as i32) < rects[1].0);
+
+        let (bx, by, _, _) = rects[1];
+        assert_eq!(d.button_at(&theme, body, bx + 1, by + 1), Some(1));
+        assert_eq!(d.button_at(&theme, body, 101, 51), None);
+
+        // A long message wraps onto more lines and a taller body.
+        d.message = "word ".repeat(60);
+        assert!(d.body_size(&theme, 480).1 > h);
+    }
+}
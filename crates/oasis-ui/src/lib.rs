@@ -11,19 +11,24 @@ pub mod button;
 pub mod card;
 pub use oasis_types::color;
 pub mod context;
+pub mod dialog;
 pub mod divider;
 pub mod flex;
 pub mod icon;
 pub mod input_field;
 pub mod layout;
 pub mod list_view;
-pub mod nine_patch;
+pub mod momentum;
+pub use oasis_types::nine_patch;
 pub mod panel;
 pub mod progress_bar;
 pub mod scroll_view;
 pub use oasis_types::shadow;
+pub mod spinner;
 pub mod tab_bar;
+pub mod table_view;
 pub mod text_block;
+pub mod text_input;
 pub mod theme;
 pub mod toggle;
 pub mod widget;
@@ -31,7 +36,7 @@ pub mod widget;
 #[cfg(test)]
 pub(crate) mod test_utils;
 
-pub use context::DrawContext;
+pub use context::{DrawContext, TextLayout};
 pub use layout::Padding;
 pub use theme::Theme;
 pub use widget::Widget;
@@ -1,13 +1,29 @@
 //! ListView widget: scrollable list with virtualized item rendering.
 
 use crate::context::DrawContext;
+use crate::momentum::{FRAME_MS, Momentum};
 use crate::widget::Widget;
 use oasis_types::error::Result;
 
+/// Furthest the list can be pulled past either end, in pixels.
+pub const MAX_OVERSCROLL: i32 = 48;
+
+/// Overscroll left after each frame of the bounce back.
+const BOUNCE: f32 = 0.7;
+
+/// Share of the pointer movement applied while pulled past an end.
+const OVERSCROLL_RESISTANCE: f32 = 0.5;
+
 /// A scrollable list with virtualized rendering.
 ///
 /// Only items visible within the viewport are drawn. The `render_item`
 /// callback draws each visible item.
+///
+/// Pointer drags scroll the list with momentum: feed them to
+/// [`begin_drag`](Self::begin_drag), [`drag`](Self::drag) and
+/// [`end_drag`](Self::end_drag), and call [`tick`](Self::tick) every
+/// frame. The list can be pulled up to [`MAX_OVERSCROLL`] pixels past
+/// either end and springs back once released.
 pub struct ListView<T> {
     /// List items.
     pub items: Vec<T>,
@@ -19,6 +35,23 @@ pub struct ListView<T> {
     pub selected: Option<usize>,
     /// Callback to render a single item.
     pub render_item: fn(&T, &mut DrawContext<'_>, i32, i32, u32, u32, bool) -> Result<()>,
+    /// Fling momentum once a drag is released.
+    momentum: Momentum,
+    /// Sub-pixel part of the scroll position.
+    carry: f32,
+    /// The drag in progress.
+    drag: Option<Drag>,
+}
+
+/// A pointer drag on a [`ListView`].
+#[derive(Debug, Clone, Copy)]
+struct Drag {
+    /// Pointer y when the drag started.
+    start_y: i32,
+    /// Scroll position when the drag started.
+    start_pos: f32,
+    /// Pointer y at the previous move.
+    last_y: i32,
 }
 
 impl<T> ListView<T> {
@@ -34,6 +67,9 @@ impl<T> ListView<T> {
             item_height: item_height.max(1),
             selected: None,
             render_item,
+            momentum: Momentum::default(),
+            carry: 0.0,
+            drag: None,
         }
     }
 
@@ -52,6 +88,99 @@ impl<T> ListView<T> {
         }
     }
 
+    /// Largest in-bounds scroll offset for a viewport `viewport_h` tall.
+    pub fn max_scroll(&self, viewport_h: u32) -> i32 {
+        (self.content_height() as i32 - viewport_h as i32).max(0)
+    }
+
+    /// Jump to `offset`, clamped to the content, stopping any momentum.
+    pub fn set_scroll_offset(&mut self, offset: i32, viewport_h: u32) {
+        self.scroll_offset = offset.clamp(0, self.max_scroll(viewport_h));
+        self.momentum.stop();
+        self.carry = 0.0;
+    }
+
+    /// Start a drag with the pointer at `pointer_y`. Stops any momentum.
+    pub fn begin_drag(&mut self, pointer_y: i32) {
+        self.drag = Some(Drag {
+            start_y: pointer_y,
+            start_pos: self.position(),
+            last_y: pointer_y,
+        });
+        self.momentum.stop();
+    }
+
+    /// Move the drag to `pointer_y`, `dt_ms` after the previous pointer
+    /// position. The content follows the pointer, at half speed past the
+    /// ends, and the pointer speed is blended into the fling velocity.
+    pub fn drag(&mut self, pointer_y: i32, dt_ms: u32, viewport_h: u32) {
+        let max = self.max_scroll(viewport_h) as f32;
+        let Some(drag) = self.drag.as_mut() else {
+            return;
+        };
+        let delta = (drag.last_y - pointer_y) as f32;
+        drag.last_y = pointer_y;
+        let (start_y, start_pos) = (drag.start_y, drag.start_pos);
+
+        // Where the pointer would put the content without resistance.
+        let bound = start_pos.clamp(0.0, max);
+        let start = bound + (start_pos - bound) / OVERSCROLL_RESISTANCE;
+        let free = start + (start_y - pointer_y) as f32;
+        let bound = free.clamp(0.0, max);
+        let overscroll = MAX_OVERSCROLL as f32;
+        let excess = ((free - bound) * OVERSCROLL_RESISTANCE).clamp(-overscroll, overscroll);
+        self.set_position(bound + excess);
+
+        self.momentum.track(delta, dt_ms);
+    }
+
+    /// Release the drag, leaving the list to coast on its velocity.
+    pub fn end_drag(&mut self) {
+        self.drag = None;
+    }
+
+    /// Whether a drag is in progress.
+    pub fn is_dragging(&self) -> bool {
+        self.drag.is_some()
+    }
+
+    /// Advance the momentum and the bounce back from past an end by
+    /// `dt_ms`. Returns whether the list is still moving.
+    ///
+    /// Once it stops, the offset is back within `0..=max_scroll`.
+    pub fn tick(&mut self, dt_ms: u32, viewport_h: u32) -> bool {
+        if self.is_dragging() {
+            return false;
+        }
+        let frames = dt_ms as f32 / FRAME_MS;
+        let max = self.max_scroll(viewport_h) as f32;
+        let mut pos = self.position() + self.momentum.step(dt_ms);
+        let bound = pos.clamp(0.0, max);
+        if pos != bound {
+            // Past an end: drop the momentum and spring back.
+            self.momentum.stop();
+            let overscroll = MAX_OVERSCROLL as f32;
+            let excess = (pos - bound).clamp(-overscroll, overscroll) * BOUNCE.powf(frames);
+            pos = if excess.abs() < 0.5 {
+                bound
+            } else {
+                bound + excess
+            };
+        }
+        self.set_position(pos);
+        self.momentum.is_moving() || pos != bound
+    }
+
+    /// Scroll position including the sub-pixel part.
+    fn position(&self) -> f32 {
+        self.scroll_offset as f32 + self.carry
+    }
+
+    fn set_position(&mut self, pos: f32) {
+        self.scroll_offset = pos.round() as i32;
+        self.carry = pos - self.scroll_offset as f32;
+    }
+
     /// Draw the list view at the given position.
     pub fn draw_at(&self, ctx: &mut DrawContext<'_>, x: i32, y: i32, w: u32, h: u32) -> Result<()> {
         ctx.backend.push_clip_rect(x, y, w, h)?;
@@ -175,6 +304,95 @@ mod tests {
         assert_eq!(lv.selected, Some(2));
     }
 
+    // -- Kinetic scrolling --
+
+    fn long_list() -> ListView<String> {
+        let items: Vec<String> = (0..50).map(|i| i.to_string()).collect();
+        // 1000px of content; with a 200px viewport the offset ends at 800.
+        ListView::new(items, 20, dummy_render)
+    }
+
+    /// Tick at 60 fps until the list stops, returning the frames taken.
+    fn settle(lv: &mut ListView<String>) -> usize {
+        (1..1000)
+            .find(|_| !lv.tick(16, 200))
+            .expect("list never settled")
+    }
+
+    #[test]
+    fn drag_follows_pointer_and_coasts() {
+        let mut lv = long_list();
+        lv.begin_drag(150);
+        lv.drag(130, 16, 200);
+        lv.drag(110, 16, 200);
+        assert_eq!(lv.scroll_offset, 40);
+        assert!(!lv.tick(16, 200), "no momentum while dragging");
+        lv.end_drag();
+
+        assert!(lv.tick(16, 200));
+        let after_one = lv.scroll_offset;
+        assert!(after_one > 40);
+        settle(&mut lv);
+        assert!(lv.scroll_offset > after_one);
+        assert!(lv.scroll_offset < lv.max_scroll(200));
+        // Settled lists stay put.
+        let rest = lv.scroll_offset;
+        assert!(!lv.tick(16, 200));
+        assert_eq!(lv.scroll_offset, rest);
+    }
+
+    #[test]
+    fn fling_past_the_end_bounces_back() {
+        let mut lv = long_list();
+        lv.set_scroll_offset(780, 200);
+        lv.begin_drag(100);
+        lv.drag(40, 16, 200);
+        lv.end_drag();
+        let mut furthest = lv.scroll_offset;
+        while lv.tick(16, 200) {
+            furthest = furthest.max(lv.scroll_offset);
+        }
+        assert!(furthest > 800 && furthest <= 800 + MAX_OVERSCROLL);
+        assert_eq!(lv.scroll_offset, 800);
+    }
+
+    #[test]
+    fn pulling_past_the_top_resists_and_settles() {
+        let mut lv = long_list();
+        lv.begin_drag(0);
+        lv.drag(40, 16, 200);
+        assert_eq!(lv.scroll_offset, -20);
+        lv.drag(400, 16, 200);
+        assert_eq!(lv.scroll_offset, -MAX_OVERSCROLL);
+        lv.end_drag();
+        lv.tick(16, 200);
+        // Catching the list mid-bounce does not make it jump.
+        let caught = lv.scroll_offset;
+        lv.begin_drag(50);
+        lv.drag(50, 16, 200);
+        assert_eq!(lv.scroll_offset, caught);
+        lv.end_drag();
+        settle(&mut lv);
+        assert_eq!(lv.scroll_offset, 0);
+    }
+
+    #[test]
+    fn set_scroll_offset_clamps_and_stops() {
+        let mut lv = long_list();
+        lv.begin_drag(100);
+        lv.drag(0, 16, 200);
+        lv.end_drag();
+        lv.set_scroll_offset(5000, 200);
+        assert_eq!(lv.scroll_offset, 800);
+        assert!(!lv.tick(16, 200));
+        lv.set_scroll_offset(-5, 200);
+        assert_eq!(lv.scroll_offset, 0);
+        // A list shorter than its viewport cannot scroll.
+        let mut short = ListView::new(vec!["a".to_string()], 20, dummy_render);
+        short.set_scroll_offset(10, 200);
+        assert_eq!(short.scroll_offset, 0);
+    }
+
     // -- Draw / measure tests using MockBackend --
 
     use crate::context::DrawContext;
@@ -0,0 +1,146 @@
+//! Momentum for kinetic scrolling.
+//!
+//! Shared by the scrollable widgets and the browser viewport so that
+//! everything coasts and slows down alike: feed pointer movement to
+//! [`Momentum::track`] (or impulses to [`Momentum::push`]) and move the
+//! content by what [`Momentum::step`] returns every frame.
+
+/// Frame length the per-frame friction is given for.
+pub const FRAME_MS: f32 = crate::animation::FRAME_MS as f32;
+
+/// Default share of the velocity kept after each frame.
+pub const DEFAULT_FRICTION: f32 = 0.95;
+
+/// Default fastest speed, in pixels per millisecond.
+pub const DEFAULT_MAX_VELOCITY: f32 = 4.0;
+
+/// Speeds (pixels per millisecond) below this stop the momentum.
+const VELOCITY_EPSILON: f32 = 0.01;
+
+/// A scroll velocity that decays with friction.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Momentum {
+    /// Share of the velocity kept after each [`FRAME_MS`] frame, in
+    /// `0.0..1.0`. Higher values coast further.
+    pub friction: f32,
+    /// Fastest speed, in pixels per millisecond.
+    pub max_velocity: f32,
+    /// Current speed in pixels per millisecond (positive scrolls down).
+    velocity: f32,
+}
+
+impl Default for Momentum {
+    fn default() -> Self {
+        Self::new(DEFAULT_FRICTION, DEFAULT_MAX_VELOCITY)
+    }
+}
+
+impl Momentum {
+    /// Create a momentum at rest.
+    pub fn new(friction: f32, max_velocity: f32) -> Self {
+        Self {
+            friction,
+            max_velocity,
+            velocity: 0.0,
+        }
+    }
+
+    /// Current speed in pixels per millisecond.
+    pub fn velocity(&self) -> f32 {
+        self.velocity
+    }
+
+    /// Whether there is any speed left.
+    pub fn is_moving(&self) -> bool {
+        self.velocity != 0.0
+    }
+
+    /// Stop dead.
+    pub fn stop(&mut self) {
+        self.velocity = 0.0;
+    }
+
+    /// Blend the speed of a movement of `delta` pixels over `dt_ms` into
+    /// the velocity, favouring the latest movement.
+    pub fn track(&mut self, delta: f32, dt_ms: u32) {
+        if dt_ms > 0 {
+            let speed = delta / dt_ms as f32;
+            self.set(self.velocity * 0.2 + speed * 0.8);
+        }
+    }
+
+    /// Add `dv` pixels per millisecond (a stick flick, say).
+    pub fn push(&mut self, dv: f32) {
+        self.set(self.velocity + dv);
+    }
+
+    /// Add the speed that coasts about `distance` pixels before friction
+    /// stops it.
+    pub fn push_distance(&mut self, distance: f32) {
+        self.push(distance * (1.0 - self.friction) / FRAME_MS);
+    }
+
+    /// Advance by `dt_ms`, returning the distance travelled.
+    pub fn step(&mut self, dt_ms: u32) -> f32 {
+        if self.velocity == 0.0 {
+            return 0.0;
+        }
+        let distance = self.velocity * dt_ms as f32;
+        self.velocity *= self.friction.powf(dt_ms as f32 / FRAME_MS);
+        if self.velocity.abs() < VELOCITY_EPSILON {
+            self.velocity = 0.0;
+        }
+        distance
+    }
+
+    fn set(&mut self, velocity: f32) {
+        self.velocity = velocity.clamp(-self.max_velocity, self.max_velocity);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn coast(momentum: &mut Momentum) -> f32 {
+        let mut travelled = 0.0;
+        while momentum.is_moving() {
+            travelled += momentum.step(16);
+        }
+        travelled
+    }
+
+    #[test]
+    fn push_distance_coasts_about_that_far() {
+        for friction in [0.85, 0.95] {
+            let mut momentum = Momentum::new(friction, DEFAULT_MAX_VELOCITY);
+            momentum.push_distance(-120.0);
+            let travelled = coast(&mut momentum);
+            assert!((-120.0..-115.0).contains(&travelled), "{travelled}");
+        }
+    }
+
+    #[test]
+    fn tracking_favours_recent_movement_and_is_capped() {
+        let mut momentum = Momentum::default();
+        momentum.track(32.0, 16);
+        assert!((momentum.velocity() - 1.6).abs() < 1e-5);
+        momentum.track(1000.0, 16);
+        assert_eq!(momentum.velocity(), DEFAULT_MAX_VELOCITY);
+        // No time passed: nothing to learn from.
+        momentum.track(-1000.0, 0);
+        assert_eq!(momentum.velocity(), DEFAULT_MAX_VELOCITY);
+    }
+
+    #[test]
+    fn friction_does_not_depend_on_the_frame_rate() {
+        let mut a = Momentum::default();
+        let mut b = Momentum::default();
+        a.push(2.0);
+        b.push(2.0);
+        let one = a.step(16);
+        let two = b.step(8) + b.step(8);
+        assert!((a.velocity() - b.velocity()).abs() < 1e-5);
+        assert!((one - two).abs() < 1.0);
+    }
+}
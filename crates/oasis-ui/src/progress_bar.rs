@@ -1,10 +1,15 @@
 //! ProgressBar widget.
 
+use crate::animation::{Easing, FRAME_MS};
 use crate::context::DrawContext;
 use crate::layout;
 use crate::widget::Widget;
 use oasis_types::error::Result;
 
+/// Time the indeterminate highlight takes to cross the track, in
+/// milliseconds.
+const INDETERMINATE_CYCLE_MS: u32 = 1200;
+
 /// Progress bar visual style.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum ProgressStyle {
@@ -24,6 +29,8 @@ pub struct ProgressBar {
     pub style: ProgressStyle,
     /// Whether to show percentage label.
     pub show_label: bool,
+    /// Time elapsed in the indeterminate animation, in milliseconds.
+    elapsed_ms: u32,
 }
 
 impl ProgressBar {
@@ -33,8 +40,40 @@ impl ProgressBar {
             value: value.clamp(0.0, 1.0),
             style: ProgressStyle::Bar,
             show_label: false,
+            elapsed_ms: 0,
+        }
+    }
+
+    /// Create an indeterminate progress bar.
+    pub fn indeterminate() -> Self {
+        Self {
+            style: ProgressStyle::Indeterminate,
+            ..Self::new(0.0)
         }
     }
+
+    /// Advance the indeterminate animation by `dt_ms`.
+    pub fn tick(&mut self, dt_ms: u32) {
+        self.elapsed_ms = self.elapsed_ms.wrapping_add(dt_ms);
+    }
+
+    /// Jump to frame `frame` of an external frame counter, assuming
+    /// [`FRAME_MS`] per frame.
+    pub fn set_frame(&mut self, frame: u32) {
+        self.elapsed_ms = frame.wrapping_mul(FRAME_MS);
+    }
+
+    /// Horizontal span `(offset, width)` of the indeterminate highlight
+    /// within a track `w` wide, clipped to the track.
+    fn highlight_span(&self, w: u32) -> (u32, u32) {
+        let hw = (w / 3).max(1) as f32;
+        let t = (self.elapsed_ms % INDETERMINATE_CYCLE_MS) as f32 / INDETERMINATE_CYCLE_MS as f32;
+        // The highlight enters from the left edge and leaves at the right.
+        let left = -hw + Easing::EaseInOutQuad.apply(t) * (w as f32 + hw);
+        let start = left.max(0.0);
+        let end = (left + hw).min(w as f32);
+        (start as u32, (end - start).max(0.0) as u32)
+    }
 }
 
 #[cfg(test)]
@@ -87,7 +126,7 @@ mod tests {
     // -- Draw / measure tests using MockBackend --
 
     use crate::context::DrawContext;
-    use crate::test_utils::MockBackend;
+    use crate::test_utils::{DrawCall, MockBackend};
     use crate::theme::Theme;
     use crate::widget::Widget;
 
@@ -182,6 +221,54 @@ mod tests {
         // stroke_circle falls back to fill_circle then fill_rect in mock.
         assert!(backend.fill_rect_count() >= 1);
     }
+
+    fn highlight(p: &ProgressBar, theme: &Theme) -> Option<(i32, u32)> {
+        let mut backend = MockBackend::new();
+        {
+            let mut ctx = DrawContext::new(&mut backend, theme);
+            p.draw(&mut ctx, 0, 0, 90, 8).unwrap();
+        }
+        backend.calls.iter().find_map(|c| match c {
+            DrawCall::FillRect { x, w, color, .. } if *color == theme.progress_fill => {
+                Some((*x, *w))
+            },
+            _ => None,
+        })
+    }
+
+    #[test]
+    fn indeterminate_highlight_slides_across_frames() {
+        let theme = Theme::dark();
+        let mut p = ProgressBar::indeterminate();
+        // The highlight starts just off the left edge.
+        assert_eq!(highlight(&p, &theme), None);
+        p.tick(300);
+        let early = highlight(&p, &theme).unwrap();
+        p.set_frame(600 / FRAME_MS);
+        let middle = highlight(&p, &theme).unwrap();
+        assert!(middle.0 > early.0, "{early:?} {middle:?}");
+        // Clipped to the track at every step.
+        for frame in 0..80 {
+            p.set_frame(frame);
+            if let Some((x, w)) = highlight(&p, &theme) {
+                assert!(x >= 0 && x as u32 + w <= 90, "frame {frame}: {x} {w}");
+            }
+        }
+    }
+
+    #[test]
+    fn indeterminate_ignores_the_value_and_label() {
+        let theme = Theme::dark();
+        let mut p = ProgressBar::indeterminate();
+        p.value = 0.5;
+        p.show_label = true;
+        let mut backend = MockBackend::new();
+        {
+            let mut ctx = DrawContext::new(&mut backend, &theme);
+            p.draw(&mut ctx, 0, 0, 90, 8).unwrap();
+        }
+        assert_eq!(backend.draw_text_count(), 0);
+    }
 }
 
 impl Widget for ProgressBar {
@@ -194,16 +281,22 @@ impl Widget for ProgressBar {
 
     fn draw(&self, ctx: &mut DrawContext<'_>, x: i32, y: i32, w: u32, h: u32) -> Result<()> {
         match self.style {
-            ProgressStyle::Bar | ProgressStyle::Indeterminate => {
+            ProgressStyle::Bar => {
                 let radius = h as u16 / 2;
                 // Track.
                 ctx.backend
-                    .fill_rounded_rect(x, y, w, h, radius, ctx.theme.scrollbar_track)?;
+                    .fill_rounded_rect(x, y, w, h, radius, ctx.theme.progress_track)?;
                 // Fill.
                 let fill_w = (w as f32 * self.value) as u32;
                 if fill_w > 0 {
-                    ctx.backend
-                        .fill_rounded_rect(x, y, fill_w, h, radius, ctx.theme.accent)?;
+                    ctx.backend.fill_rounded_rect(
+                        x,
+                        y,
+                        fill_w,
+                        h,
+                        radius,
+                        ctx.theme.progress_fill,
+                    )?;
                 }
                 // Label.
                 if self.show_label {
@@ -217,17 +310,34 @@ impl Widget for ProgressBar {
                         .draw_text(&pct, tx, ty, fs, ctx.theme.text_primary)?;
                 }
             },
+            ProgressStyle::Indeterminate => {
+                let radius = h as u16 / 2;
+                ctx.backend
+                    .fill_rounded_rect(x, y, w, h, radius, ctx.theme.progress_track)?;
+                let (offset, hw) = self.highlight_span(w);
+                if hw > 0 {
+                    ctx.backend.fill_rounded_rect(
+                        x + offset as i32,
+                        y,
+                        hw,
+                        h,
+                        radius.min(hw as u16 / 2),
+                        ctx.theme.progress_fill,
+                    )?;
+                }
+            },
             ProgressStyle::Circular => {
                 let r = (h.min(w) / 2) as u16;
                 let cx = x + r as i32;
                 let cy = y + r as i32;
                 // Background circle.
                 ctx.backend
-                    .stroke_circle(cx, cy, r, 2, ctx.theme.scrollbar_track)?;
+                    .stroke_circle(cx, cy, r, 2, ctx.theme.progress_track)?;
                 // Progress arc approximated as a partial circle overlay.
                 // Full circle at 100%.
                 if self.value >= 0.99 {
-                    ctx.backend.stroke_circle(cx, cy, r, 2, ctx.theme.accent)?;
+                    ctx.backend
+                        .stroke_circle(cx, cy, r, 2, ctx.theme.progress_fill)?;
                 }
             },
         }
@@ -1,9 +1,18 @@
 //! ScrollView widget: scrollable content region with scrollbar.
 
+use crate::animation::{Easing, Tween};
 use crate::context::DrawContext;
+use crate::momentum::Momentum;
 use crate::widget::Widget;
 use oasis_types::error::Result;
 
+/// Duration of an animated [`ScrollView::scroll_to`].
+pub const SCROLL_ANIMATION_MS: u32 = 250;
+
+/// Width of the strip along the right edge that grabs the scrollbar, so a
+/// thin bar is still easy to hit with a mouse.
+const SCROLLBAR_HIT_W: u32 = 10;
+
 /// Scrollbar visual style.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum ScrollbarStyle {
@@ -16,6 +25,13 @@ pub enum ScrollbarStyle {
 }
 
 /// A scrollable content region with a scrollbar.
+///
+/// Besides direct offset changes, the view scrolls kinetically: content
+/// drags ([`begin_drag`](Self::begin_drag), [`drag`](Self::drag),
+/// [`end_drag`](Self::end_drag)) and stick flicks ([`flick`](Self::flick))
+/// build up [`momentum`](Self::momentum) that [`tick`](Self::tick) plays
+/// out every frame. [`pointer_down`](Self::pointer_down) also grabs the
+/// scrollbar thumb. The offset never leaves `0..=max_scroll`.
 pub struct ScrollView {
     /// Total height of scrollable content.
     pub content_height: u32,
@@ -25,6 +41,28 @@ pub struct ScrollView {
     pub viewport_height: u32,
     /// Scrollbar visual style.
     pub scrollbar_style: ScrollbarStyle,
+    /// Fling momentum; set its friction to tune how far flicks coast.
+    pub momentum: Momentum,
+    /// Sub-pixel part of the scroll position.
+    carry: f32,
+    /// The drag in progress.
+    drag: Option<Drag>,
+    /// Animated [`scroll_to`](Self::scroll_to) in progress.
+    animation: Option<Tween<f32>>,
+}
+
+/// A pointer drag on a [`ScrollView`].
+#[derive(Debug, Clone, Copy)]
+struct Drag {
+    /// Pointer y when the drag started.
+    start_y: i32,
+    /// Scroll position when the drag started.
+    start_pos: f32,
+    /// Pointer y at the previous move.
+    last_y: i32,
+    /// Content pixels per pointer pixel when dragging the thumb; `None`
+    /// when dragging the content itself.
+    thumb_scale: Option<f32>,
 }
 
 impl ScrollView {
@@ -35,36 +73,226 @@ impl ScrollView {
             scroll_y: 0,
             viewport_height,
             scrollbar_style: ScrollbarStyle::Thin,
+            momentum: Momentum::default(),
+            carry: 0.0,
+            drag: None,
+            animation: None,
         }
     }
 
+    /// Largest scroll position.
+    pub fn max_scroll(&self) -> i32 {
+        (self.content_height as i32 - self.viewport_height as i32).max(0)
+    }
+
     /// Clamp scroll position to valid range.
     pub fn clamp_scroll(&mut self) {
-        let max_scroll = (self.content_height as i32 - self.viewport_height as i32).max(0);
-        self.scroll_y = self.scroll_y.clamp(0, max_scroll);
+        self.scroll_y = self.scroll_y.clamp(0, self.max_scroll());
+        self.carry = 0.0;
     }
 
     /// Scroll by a delta amount.
     pub fn scroll_by(&mut self, delta: i32) {
+        self.animation = None;
         self.scroll_y += delta;
         self.clamp_scroll();
     }
 
+    /// Scroll to `offset` (clamped to the content), stopping any momentum.
+    /// With `animated`, glide there over [`SCROLL_ANIMATION_MS`] as
+    /// [`tick`](Self::tick) is called.
+    pub fn scroll_to(&mut self, offset: i32, animated: bool) {
+        self.momentum.stop();
+        let target = offset.clamp(0, self.max_scroll());
+        if animated {
+            self.animation = Some(Tween::new(
+                self.position(),
+                target as f32,
+                SCROLL_ANIMATION_MS,
+                Easing::EaseOutCubic,
+            ));
+        } else {
+            self.animation = None;
+            self.scroll_y = target;
+            self.carry = 0.0;
+        }
+    }
+
+    /// Add `velocity` pixels per millisecond to the momentum, as an analog
+    /// stick flick does. Repeated flicks build up to the momentum's top
+    /// speed.
+    pub fn flick(&mut self, velocity: f32) {
+        self.animation = None;
+        self.momentum.push(velocity);
+    }
+
     /// Whether the scrollbar should be visible.
     pub fn needs_scrollbar(&self) -> bool {
         self.content_height > self.viewport_height && self.scrollbar_style != ScrollbarStyle::Hidden
     }
 
-    /// Draw the scrollbar.
-    pub fn draw_scrollbar(&self, ctx: &mut DrawContext<'_>, x: i32, y: i32, h: u32) -> Result<()> {
+    /// Width of the visible scrollbar (0 if there is none).
+    fn bar_width(&self) -> u32 {
         if !self.needs_scrollbar() {
-            return Ok(());
+            return 0;
         }
-        let bar_w = match self.scrollbar_style {
-            ScrollbarStyle::Thin => 3u32,
+        match self.scrollbar_style {
+            ScrollbarStyle::Thin => 3,
             ScrollbarStyle::Wide => 6,
-            ScrollbarStyle::Hidden => return Ok(()),
+            ScrollbarStyle::Hidden => 0,
+        }
+    }
+
+    /// Offset and height of the thumb in a track `h` tall.
+    fn thumb(&self, h: u32) -> (i32, u32) {
+        let bar_w = self.bar_width();
+        let ratio = self.viewport_height as f32 / self.content_height.max(1) as f32;
+        let thumb_h = ((h as f32 * ratio).max(bar_w as f32) as u32).min(h);
+        let scroll_range = self.max_scroll();
+        let thumb_y = if scroll_range > 0 {
+            ((h - thumb_h) as f32 * self.scroll_y.clamp(0, scroll_range) as f32
+                / scroll_range as f32) as i32
+        } else {
+            0
+        };
+        (thumb_y, thumb_h)
+    }
+
+    /// Whether `(px, py)` is on the scrollbar of the view drawn at
+    /// `(x, y, w, h)`.
+    pub fn scrollbar_hit(&self, px: i32, py: i32, x: i32, y: i32, w: u32, h: u32) -> bool {
+        if self.bar_width() == 0 {
+            return false;
+        }
+        let right = x + w as i32;
+        let left = right - SCROLLBAR_HIT_W.min(w) as i32;
+        (left..right).contains(&px) && (y..y + h as i32).contains(&py)
+    }
+
+    /// Handle a pointer press at `(px, py)` on the view drawn at
+    /// `(x, y, w, h)`. On the scrollbar, this grabs the thumb (a press on
+    /// the track first centers the thumb under the pointer); elsewhere in
+    /// the view it starts a content drag. Returns whether the press was in
+    /// the view.
+    pub fn pointer_down(&mut self, px: i32, py: i32, x: i32, y: i32, w: u32, h: u32) -> bool {
+        if self.scrollbar_hit(px, py, x, y, w, h) {
+            let (thumb_y, thumb_h) = self.thumb(h);
+            let track_range = h.saturating_sub(thumb_h);
+            let scale = if track_range > 0 {
+                self.max_scroll() as f32 / track_range as f32
+            } else {
+                0.0
+            };
+            let on_thumb = (y + thumb_y..y + thumb_y + thumb_h as i32).contains(&py);
+            if !on_thumb {
+                let thumb_top = py - y - thumb_h as i32 / 2;
+                self.scroll_to((thumb_top as f32 * scale).round() as i32, false);
+            }
+            self.start_drag(py, Some(scale));
+            return true;
+        }
+        let inside = (x..x + w as i32).contains(&px) && (y..y + h as i32).contains(&py);
+        if inside {
+            self.begin_drag(py);
+        }
+        inside
+    }
+
+    /// Start dragging the content with the pointer at `pointer_y`. Stops
+    /// any momentum.
+    pub fn begin_drag(&mut self, pointer_y: i32) {
+        self.start_drag(pointer_y, None);
+    }
+
+    fn start_drag(&mut self, pointer_y: i32, thumb_scale: Option<f32>) {
+        self.drag = Some(Drag {
+            start_y: pointer_y,
+            start_pos: self.position(),
+            last_y: pointer_y,
+            thumb_scale,
+        });
+        self.momentum.stop();
+        self.animation = None;
+    }
+
+    /// Move the drag to `pointer_y`, `dt_ms` after the previous pointer
+    /// position. A dragged thumb maps pointer movement onto the content
+    /// range; dragged content follows the pointer and picks up its speed
+    /// as momentum for when it is released.
+    pub fn drag(&mut self, pointer_y: i32, dt_ms: u32) {
+        let Some(drag) = self.drag.as_mut() else {
+            return;
         };
+        let delta = (drag.last_y - pointer_y) as f32;
+        drag.last_y = pointer_y;
+        let moved = (pointer_y - drag.start_y) as f32;
+        let pos = match drag.thumb_scale {
+            Some(scale) => drag.start_pos + moved * scale,
+            None => {
+                let pos = drag.start_pos - moved;
+                self.momentum.track(delta, dt_ms);
+                pos
+            },
+        };
+        self.set_position(pos.clamp(0.0, self.max_scroll() as f32));
+    }
+
+    /// Release the drag. Released content coasts on its momentum.
+    pub fn end_drag(&mut self) {
+        self.drag = None;
+    }
+
+    /// Whether a drag is in progress.
+    pub fn is_dragging(&self) -> bool {
+        self.drag.is_some()
+    }
+
+    /// Advance the momentum or an animated [`scroll_to`](Self::scroll_to)
+    /// by `dt_ms`. Returns whether the view is still moving.
+    ///
+    /// Momentum that carries the view into either end stops there.
+    pub fn tick(&mut self, dt_ms: u32) -> bool {
+        if self.is_dragging() {
+            return false;
+        }
+        if let Some(tween) = self.animation.as_mut() {
+            let pos = tween.tick(dt_ms);
+            let finished = tween.is_finished();
+            self.set_position(pos.clamp(0.0, self.max_scroll() as f32));
+            if finished {
+                self.animation = None;
+            }
+            return !finished;
+        }
+        if !self.momentum.is_moving() {
+            return false;
+        }
+        let max = self.max_scroll() as f32;
+        let pos = self.position() + self.momentum.step(dt_ms);
+        let bound = pos.clamp(0.0, max);
+        if pos != bound {
+            self.momentum.stop();
+        }
+        self.set_position(bound);
+        self.momentum.is_moving()
+    }
+
+    /// Scroll position including the sub-pixel part.
+    fn position(&self) -> f32 {
+        self.scroll_y as f32 + self.carry
+    }
+
+    fn set_position(&mut self, pos: f32) {
+        self.scroll_y = pos.round() as i32;
+        self.carry = pos - self.scroll_y as f32;
+    }
+
+    /// Draw the scrollbar.
+    pub fn draw_scrollbar(&self, ctx: &mut DrawContext<'_>, x: i32, y: i32, h: u32) -> Result<()> {
+        let bar_w = self.bar_width();
+        if bar_w == 0 {
+            return Ok(());
+        }
 
         // Track.
         ctx.backend.fill_rounded_rect(
@@ -77,14 +305,7 @@ impl ScrollView {
         )?;
 
         // Thumb.
-        let ratio = self.viewport_height as f32 / self.content_height as f32;
-        let thumb_h = ((h as f32 * ratio).max(bar_w as f32)) as u32;
-        let scroll_range = self.content_height - self.viewport_height;
-        let thumb_y = if scroll_range > 0 {
-            ((h - thumb_h) as f32 * self.scroll_y as f32 / scroll_range as f32) as i32
-        } else {
-            0
-        };
+        let (thumb_y, thumb_h) = self.thumb(h);
         ctx.backend.fill_rounded_rect(
             x,
             y + thumb_y,
@@ -279,6 +500,140 @@ mod tests {
         // content_height (0) <= viewport_height (200), so no scrollbar.
         assert_eq!(backend.fill_rect_count(), 0);
     }
+
+    // -- Kinetic scrolling and the scrollbar --
+
+    /// 2000px of content in a 200px viewport: offsets run 0..=1800.
+    fn long_view() -> ScrollView {
+        ScrollView::new(2000, 200)
+    }
+
+    /// Tick at 60 fps until the view stops, checking it stays in bounds.
+    fn settle(sv: &mut ScrollView) -> usize {
+        (1..1000)
+            .find(|_| {
+                let moving = sv.tick(16);
+                assert!(
+                    (0..=sv.max_scroll()).contains(&sv.scroll_y),
+                    "offset {} out of bounds",
+                    sv.scroll_y
+                );
+                !moving
+            })
+            .expect("view never settled")
+    }
+
+    #[test]
+    fn flick_coasts_and_converges_in_bounds() {
+        let mut sv = long_view();
+        sv.begin_drag(150);
+        sv.drag(120, 16);
+        sv.drag(90, 16);
+        assert_eq!(sv.scroll_y, 60);
+        assert!(!sv.tick(16), "no momentum while dragging");
+        sv.end_drag();
+
+        assert!(sv.tick(16));
+        let after_one = sv.scroll_y;
+        assert!(after_one > 60);
+        settle(&mut sv);
+        assert!(sv.scroll_y > after_one && sv.scroll_y < sv.max_scroll());
+        // A settled view stays put.
+        let rest = sv.scroll_y;
+        assert!(!sv.tick(16));
+        assert_eq!(sv.scroll_y, rest);
+    }
+
+    #[test]
+    fn momentum_stops_at_the_ends() {
+        let mut sv = long_view();
+        sv.scroll_to(1700, false);
+        for _ in 0..10 {
+            sv.flick(1.0);
+        }
+        assert_eq!(sv.momentum.velocity(), sv.momentum.max_velocity);
+        settle(&mut sv);
+        assert_eq!(sv.scroll_y, 1800);
+        assert!(!sv.momentum.is_moving());
+
+        sv.scroll_to(300, false);
+        sv.flick(-4.0);
+        settle(&mut sv);
+        assert_eq!(sv.scroll_y, 0);
+        // Dragging past the top does not overscroll either.
+        sv.begin_drag(0);
+        sv.drag(100, 16);
+        assert_eq!(sv.scroll_y, 0);
+    }
+
+    #[test]
+    fn friction_sets_how_far_a_flick_coasts() {
+        let coast = |friction: f32| {
+            let mut sv = long_view();
+            sv.momentum.friction = friction;
+            sv.flick(1.0);
+            settle(&mut sv);
+            sv.scroll_y
+        };
+        assert!(coast(0.9) < coast(0.97));
+    }
+
+    #[test]
+    fn dragging_the_thumb_maps_to_the_content_range() {
+        // Drawn at (0, 0, 200, 200): a 20px thumb on the right edge with
+        // 180px of travel for 1800px of content.
+        let mut sv = long_view();
+        assert!(sv.scrollbar_hit(195, 5, 0, 0, 200, 200));
+        assert!(!sv.scrollbar_hit(100, 5, 0, 0, 200, 200));
+        assert!(sv.pointer_down(198, 10, 0, 0, 200, 200));
+        assert!(sv.is_dragging());
+        sv.drag(40, 16);
+        assert_eq!(sv.scroll_y, 300);
+        sv.drag(500, 16);
+        assert_eq!(sv.scroll_y, 1800);
+        sv.end_drag();
+        // Thumb drags leave no momentum behind.
+        assert!(!sv.tick(16));
+        assert_eq!(sv.scroll_y, 1800);
+    }
+
+    #[test]
+    fn pressing_the_track_jumps_the_thumb_under_the_pointer() {
+        let mut sv = long_view();
+        assert!(sv.pointer_down(199, 110, 0, 0, 200, 200));
+        // The thumb (20px) is centered on y=110: its top at 100 of 180.
+        assert_eq!(sv.scroll_y, 1000);
+        sv.drag(120, 16);
+        assert_eq!(sv.scroll_y, 1100);
+        sv.end_drag();
+        // Presses in the content start a content drag instead.
+        assert!(sv.pointer_down(50, 50, 0, 0, 200, 200));
+        sv.drag(40, 16);
+        assert_eq!(sv.scroll_y, 1110);
+        assert!(!sv.pointer_down(50, 250, 0, 0, 200, 200));
+    }
+
+    #[test]
+    fn animated_scroll_to_eases_to_the_target() {
+        let mut sv = long_view();
+        sv.scroll_to(5000, true);
+        assert_eq!(sv.scroll_y, 0);
+        assert!(sv.tick(16));
+        let first = sv.scroll_y;
+        // Ease-out: the first frame covers more than its share.
+        let linear_share = 1800 * 16 / SCROLL_ANIMATION_MS as i32;
+        assert!(first > linear_share, "{first}");
+        let mut last = first;
+        while sv.tick(16) {
+            assert!(sv.scroll_y >= last && sv.scroll_y <= 1800);
+            last = sv.scroll_y;
+        }
+        assert_eq!(sv.scroll_y, 1800);
+
+        sv.scroll_to(100, false);
+        assert_eq!(sv.scroll_y, 100);
+        assert!(!sv.tick(16));
+    }
 }
 
 impl Widget for ScrollView {
@@ -287,15 +642,7 @@ impl Widget for ScrollView {
     }
 
     fn draw(&self, ctx: &mut DrawContext<'_>, x: i32, y: i32, w: u32, h: u32) -> Result<()> {
-        let bar_w = if self.needs_scrollbar() {
-            match self.scrollbar_style {
-                ScrollbarStyle::Thin => 3u32,
-                ScrollbarStyle::Wide => 6,
-                ScrollbarStyle::Hidden => 0,
-            }
-        } else {
-            0
-        };
+        let bar_w = self.bar_width();
         let content_w = w.saturating_sub(bar_w + 2);
 
         // Draw scrollbar.
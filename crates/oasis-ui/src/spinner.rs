@@ -0,0 +1,166 @@
+//! Spinner widget: a ring of dots with a highlight chasing around it.
+//!
+//! The spinner keeps its own clock. Advance it with [`Spinner::tick`] when
+//! the frame time is known, or with [`Spinner::set_frame`] from a frame
+//! counter when it is not.
+
+use crate::animation::{Easing, FRAME_MS, Lerp};
+use crate::context::DrawContext;
+use crate::widget::Widget;
+use oasis_types::backend::Color;
+use oasis_types::error::Result;
+
+/// An indeterminate busy indicator.
+pub struct Spinner {
+    /// Number of dots around the ring.
+    pub segments: u8,
+    /// Time the highlight stays on each dot, in milliseconds.
+    pub step_ms: u32,
+    /// Highlight color; the theme's `progress_fill` if `None`.
+    pub color: Option<Color>,
+    /// Color of the idle dots; the theme's `progress_track` if `None`.
+    pub track_color: Option<Color>,
+    /// Time elapsed since the spinner started, in milliseconds.
+    elapsed_ms: u32,
+}
+
+impl Default for Spinner {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Spinner {
+    /// Create a spinner with eight dots.
+    pub fn new() -> Self {
+        Self {
+            segments: 8,
+            step_ms: 80,
+            color: None,
+            track_color: None,
+            elapsed_ms: 0,
+        }
+    }
+
+    /// Advance the animation by `dt_ms`.
+    pub fn tick(&mut self, dt_ms: u32) {
+        self.elapsed_ms = self.elapsed_ms.wrapping_add(dt_ms);
+    }
+
+    /// Jump to frame `frame` of an external frame counter, assuming
+    /// [`FRAME_MS`] per frame.
+    pub fn set_frame(&mut self, frame: u32) {
+        self.elapsed_ms = frame.wrapping_mul(FRAME_MS);
+    }
+
+    /// Builder form of [`Spinner::set_frame`].
+    pub fn with_frame(mut self, frame: u32) -> Self {
+        self.set_frame(frame);
+        self
+    }
+
+    /// Index of the highlighted dot, counting clockwise from the top.
+    pub fn head(&self) -> usize {
+        let segments = self.segments.max(1) as u32;
+        (self.elapsed_ms / self.step_ms.max(1) % segments) as usize
+    }
+}
+
+impl Widget for Spinner {
+    fn measure(&self, _ctx: &DrawContext<'_>, _available_w: u32, _available_h: u32) -> (u32, u32) {
+        (16, 16)
+    }
+
+    fn draw(&self, ctx: &mut DrawContext<'_>, x: i32, y: i32, w: u32, h: u32) -> Result<()> {
+        let segments = self.segments.max(1) as usize;
+        let fill = self.color.unwrap_or(ctx.theme.progress_fill);
+        let track = self.track_color.unwrap_or(ctx.theme.progress_track);
+        let r = (w.min(h) / 2) as f32;
+        let dot = (r / 4.0).max(1.0);
+        let orbit = r - dot;
+        let cx = x as f32 + w as f32 / 2.0;
+        let cy = y as f32 + h as f32 / 2.0;
+        let head = self.head();
+        for i in 0..segments {
+            // Dots behind the head fade back to the track color.
+            let behind = (head + segments - i) % segments;
+            let t = Easing::EaseOutQuad.apply(behind as f32 / segments as f32);
+            let color = Color::lerp(fill, track, t);
+            let angle = i as f32 / segments as f32 * core::f32::consts::TAU;
+            let dx = orbit * angle.sin();
+            let dy = -orbit * angle.cos();
+            ctx.backend.fill_circle(
+                (cx + dx).round() as i32,
+                (cy + dy).round() as i32,
+                dot as u16,
+                color,
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{DrawCall, MockBackend};
+    use crate::theme::Theme;
+
+    fn dots(spinner: &Spinner, theme: &Theme) -> Vec<(i32, i32, Color)> {
+        let mut backend = MockBackend::new();
+        {
+            let mut ctx = DrawContext::new(&mut backend, theme);
+            spinner.draw(&mut ctx, 0, 0, 16, 16).unwrap();
+        }
+        backend
+            .calls
+            .iter()
+            .filter_map(|c| match c {
+                DrawCall::FillRect { x, y, color, .. } => Some((*x, *y, *color)),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn draws_one_dot_per_segment_with_the_head_in_the_fill_color() {
+        let theme = Theme::dark();
+        let spinner = Spinner::new();
+        let dots = dots(&spinner, &theme);
+        assert_eq!(dots.len(), 8);
+        assert_eq!(dots[0].2, theme.progress_fill);
+        assert!(dots[1..].iter().all(|d| d.2 != theme.progress_fill));
+    }
+
+    #[test]
+    fn the_highlight_moves_between_frames() {
+        let theme = Theme::dark();
+        let mut spinner = Spinner::new();
+        let first = dots(&spinner, &theme);
+        spinner.tick(spinner.step_ms);
+        let second = dots(&spinner, &theme);
+        assert_eq!(spinner.head(), 1);
+        // The dots stay put; only their colors move round.
+        assert!(
+            first
+                .iter()
+                .zip(&second)
+                .all(|(a, b)| (a.0, a.1) == (b.0, b.1))
+        );
+        assert_ne!(first, second);
+        assert_eq!(second[1].2, theme.progress_fill);
+    }
+
+    #[test]
+    fn frame_counter_drives_the_animation_and_colors_override_the_theme() {
+        let theme = Theme::dark();
+        let red = Color::rgb(255, 0, 0);
+        let mut spinner = Spinner::new().with_frame(10);
+        spinner.color = Some(red);
+        // 10 frames of 16 ms at 80 ms a dot.
+        assert_eq!(spinner.head(), 2);
+        assert_eq!(dots(&spinner, &theme)[2].2, red);
+        spinner.set_frame(40);
+        assert_eq!(spinner.head(), 0);
+    }
+}
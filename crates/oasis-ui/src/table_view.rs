@@ -0,0 +1,534 @@
+//! TableView widget: a list with columns and sortable headers.
+
+use crate::context::DrawContext;
+use crate::layout::{HAlign, align_x};
+use crate::list_view::ListView;
+use crate::widget::Widget;
+use oasis_types::backend::Color;
+use oasis_types::error::Result;
+
+/// How wide a column is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColumnWidth {
+    /// A fixed number of pixels.
+    Fixed(u32),
+    /// A share of the width the fixed columns leave, in proportion to the
+    /// other weighted columns.
+    Weight(u32),
+}
+
+/// Value a column sorts its rows by.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub enum SortKey {
+    /// Sizes, dates, counts.
+    Number(i64),
+    /// Names and the like, compared byte-wise.
+    Text(String),
+}
+
+/// A column of a [`TableView`].
+pub struct Column<T> {
+    /// Header text.
+    pub title: String,
+    /// Column width.
+    pub width: ColumnWidth,
+    /// Alignment of the header and cells.
+    pub align: HAlign,
+    /// Text of a row's cell.
+    pub text: fn(&T) -> String,
+    /// Color of a row's cell (default: the theme's primary text color).
+    pub color: Option<fn(&T) -> Color>,
+    /// Key to sort rows by; the table cannot be sorted by a column
+    /// without one.
+    pub sort_key: Option<fn(&T) -> SortKey>,
+}
+
+impl<T> Column<T> {
+    /// Create a left-aligned, unsortable column.
+    pub fn new(title: impl Into<String>, width: ColumnWidth, text: fn(&T) -> String) -> Self {
+        Self {
+            title: title.into(),
+            width,
+            align: HAlign::Left,
+            text,
+            color: None,
+            sort_key: None,
+        }
+    }
+
+    /// Set the alignment.
+    pub fn with_align(mut self, align: HAlign) -> Self {
+        self.align = align;
+        self
+    }
+
+    /// Set the cell color.
+    pub fn with_color(mut self, color: fn(&T) -> Color) -> Self {
+        self.color = Some(color);
+        self
+    }
+
+    /// Make the column sortable by `sort_key`.
+    pub fn with_sort_key(mut self, sort_key: fn(&T) -> SortKey) -> Self {
+        self.sort_key = Some(sort_key);
+        self
+    }
+}
+
+/// A scrollable table: a header row above a [`ListView`] of rows drawn
+/// as cells.
+///
+/// Rows, selection and scrolling (including the kinetic drag) live in
+/// [`list`](Self::list) and behave exactly as in a plain list view; the
+/// header is as tall as a row and sits above the scrolled area.
+pub struct TableView<T> {
+    /// Columns, left to right.
+    pub columns: Vec<Column<T>>,
+    /// The rows.
+    pub list: ListView<T>,
+    /// Column and direction (`true` = ascending) the rows are sorted by.
+    sort: Option<(usize, bool)>,
+}
+
+/// Rows are drawn by the table itself.
+fn no_render<T>(
+    _row: &T,
+    _ctx: &mut DrawContext<'_>,
+    _x: i32,
+    _y: i32,
+    _w: u32,
+    _h: u32,
+    _selected: bool,
+) -> Result<()> {
+    Ok(())
+}
+
+impl<T> TableView<T> {
+    /// Create a table, unsorted and with nothing selected.
+    pub fn new(columns: Vec<Column<T>>, rows: Vec<T>, row_height: u32) -> Self {
+        Self {
+            columns,
+            list: ListView::new(rows, row_height, no_render::<T>),
+            sort: None,
+        }
+    }
+
+    /// Height of the header row.
+    pub fn header_height(&self) -> u32 {
+        self.list.item_height
+    }
+
+    /// Height left for rows in a table `h` tall.
+    pub fn rows_height(&self, h: u32) -> u32 {
+        h.saturating_sub(self.header_height())
+    }
+
+    /// Replace the rows, keeping the current sort. Clears the selection
+    /// and scrolls back to the top.
+    pub fn set_rows(&mut self, rows: Vec<T>) {
+        self.list.items = rows;
+        self.list.selected = None;
+        self.list.set_scroll_offset(0, 0);
+        if let Some((column, ascending)) = self.sort {
+            self.set_sort(column, ascending);
+        }
+    }
+
+    /// Column and direction (`true` = ascending) the rows are sorted by.
+    pub fn sort(&self) -> Option<(usize, bool)> {
+        self.sort
+    }
+
+    /// Stably sort the rows by `column`, keeping the selected row
+    /// selected. Does nothing if the column has no sort key.
+    pub fn set_sort(&mut self, column: usize, ascending: bool) {
+        let Some(sort_key) = self.columns.get(column).and_then(|c| c.sort_key) else {
+            return;
+        };
+        let mut rows: Vec<(SortKey, usize, T)> = std::mem::take(&mut self.list.items)
+            .into_iter()
+            .enumerate()
+            .map(|(i, row)| (sort_key(&row), i, row))
+            .collect();
+        // `sort_by` is stable, and so is the reversed comparison.
+        rows.sort_by(|a, b| {
+            let order = a.0.cmp(&b.0);
+            if ascending { order } else { order.reverse() }
+        });
+        self.list.selected = self
+            .list
+            .selected
+            .and_then(|old| rows.iter().position(|(_, i, _)| *i == old));
+        self.list.items = rows.into_iter().map(|(_, _, row)| row).collect();
+        self.sort = Some((column, ascending));
+    }
+
+    /// Sort by `column` as a click on its header does: ascending at first,
+    /// then flipping the direction on each further click.
+    pub fn toggle_sort(&mut self, column: usize) {
+        let ascending = match self.sort {
+            Some((current, ascending)) if current == column => !ascending,
+            _ => true,
+        };
+        self.set_sort(column, ascending);
+    }
+
+    /// Pixel widths of the columns in a table `w` wide.
+    pub fn column_widths(&self, w: u32) -> Vec<u32> {
+        let fixed: u32 = self
+            .columns
+            .iter()
+            .map(|c| match c.width {
+                ColumnWidth::Fixed(px) => px,
+                ColumnWidth::Weight(_) => 0,
+            })
+            .sum();
+        let total_weight: u32 = self
+            .columns
+            .iter()
+            .map(|c| match c.width {
+                ColumnWidth::Fixed(_) => 0,
+                ColumnWidth::Weight(weight) => weight,
+            })
+            .sum();
+        let spare = w.saturating_sub(fixed);
+        let last_weighted = self
+            .columns
+            .iter()
+            .rposition(|c| matches!(c.width, ColumnWidth::Weight(_)));
+        let mut given = 0;
+        self.columns
+            .iter()
+            .enumerate()
+            .map(|(i, c)| match c.width {
+                ColumnWidth::Fixed(px) => px,
+                // The last weighted column takes the rounding leftovers.
+                ColumnWidth::Weight(_) if Some(i) == last_weighted => spare - given,
+                ColumnWidth::Weight(weight) => {
+                    let share = (spare as u64 * weight as u64 / total_weight.max(1) as u64) as u32;
+                    given += share;
+                    share
+                },
+            })
+            .collect()
+    }
+
+    /// Index of the column at `x`, relative to the left of a table `w`
+    /// wide.
+    pub fn column_at(&self, x: i32, w: u32) -> Option<usize> {
+        if x < 0 {
+            return None;
+        }
+        let mut right = 0;
+        self.column_widths(w).into_iter().position(|cw| {
+            right += cw as i32;
+            x < right
+        })
+    }
+
+    /// Index of the row at `y`, relative to the top of the table
+    /// (header included).
+    pub fn row_at(&self, y: i32) -> Option<usize> {
+        let rows_y = y - self.header_height() as i32;
+        let content_y = rows_y + self.list.scroll_offset;
+        if rows_y < 0 || content_y < 0 {
+            return None;
+        }
+        let index = (content_y / self.list.item_height as i32) as usize;
+        (index < self.list.items.len()).then_some(index)
+    }
+
+    /// Handle a click at `(x, y)`, relative to the top left of a table `w`
+    /// wide: a click on a sortable header toggles the sort. Returns the
+    /// column sorted by.
+    pub fn click_header(&mut self, x: i32, y: i32, w: u32) -> Option<usize> {
+        if y < 0 || y >= self.header_height() as i32 {
+            return None;
+        }
+        let column = self.column_at(x, w)?;
+        self.columns[column].sort_key?;
+        self.toggle_sort(column);
+        Some(column)
+    }
+
+    /// Scroll to make the given row visible in a table `h` tall.
+    pub fn scroll_to(&mut self, index: usize, h: u32) {
+        self.list.scroll_to(index, self.rows_height(h));
+    }
+
+    /// Draw the table at the given position.
+    pub fn draw_at(&self, ctx: &mut DrawContext<'_>, x: i32, y: i32, w: u32, h: u32) -> Result<()> {
+        let widths = self.column_widths(w);
+        let header_h = self.header_height().min(h);
+        let font_size = ctx.theme.font_size_sm;
+        let text_dy = (self.list.item_height as i32
+            - ctx.backend.measure_text_height(font_size) as i32)
+            .max(0)
+            / 2;
+
+        // Header: titles, the sort direction and a divider underneath.
+        let mut cx = x;
+        for (i, (column, &cw)) in self.columns.iter().zip(&widths).enumerate() {
+            let title = match self.sort {
+                Some((sorted, true)) if sorted == i => format!("{} ^", column.title),
+                Some((sorted, false)) if sorted == i => format!("{} v", column.title),
+                _ => column.title.clone(),
+            };
+            let color = ctx.theme.text_secondary;
+            draw_cell(ctx, &title, column.align, cx, y + text_dy, cw, color)?;
+            cx += cw as i32;
+        }
+        let divider_y = y + header_h as i32 - 1;
+        ctx.backend
+            .fill_rect(x, divider_y, w, 1, ctx.theme.border_subtle)?;
+
+        // Rows, clipped below the header.
+        let rows_y = y + header_h as i32;
+        let rows_h = h - header_h;
+        ctx.backend.push_clip_rect(x, rows_y, w, rows_h)?;
+        let result = (|| {
+            let row_h = self.list.item_height;
+            let first = (self.list.scroll_offset / row_h as i32).max(0) as usize;
+            let visible = (rows_h / row_h + 2) as usize;
+            let last = (first + visible).min(self.list.items.len());
+
+            for i in first..last {
+                let row = &self.list.items[i];
+                let row_y = rows_y + (i as i32 * row_h as i32) - self.list.scroll_offset;
+                if self.list.selected == Some(i) {
+                    ctx.backend
+                        .fill_rect(x, row_y, w, row_h, ctx.theme.accent_subtle)?;
+                }
+                let mut cx = x;
+                for (column, &cw) in self.columns.iter().zip(&widths) {
+                    let text = (column.text)(row);
+                    let color = column.color.map_or(ctx.theme.text_primary, |c| c(row));
+                    draw_cell(ctx, &text, column.align, cx, row_y + text_dy, cw, color)?;
+                    cx += cw as i32;
+                }
+            }
+            Ok(())
+        })();
+        ctx.backend.pop_clip_rect()?;
+        result
+    }
+}
+
+/// Draw `text` aligned in a cell `w` wide, cut short with an ellipsis if it
+/// does not fit.
+fn draw_cell(
+    ctx: &mut DrawContext<'_>,
+    text: &str,
+    align: HAlign,
+    x: i32,
+    y: i32,
+    w: u32,
+    color: Color,
+) -> Result<()> {
+    let font_size = ctx.theme.font_size_sm;
+    let pad = ctx.theme.spacing_xs as u32;
+    let inner_w = w.saturating_sub(2 * pad);
+    if text.is_empty() || inner_w == 0 {
+        return Ok(());
+    }
+    let text_w = ctx.backend.measure_text(text, font_size);
+    let tx = x + pad as i32 + align_x(inner_w, text_w, align);
+    ctx.backend
+        .draw_text_ellipsis(text, tx, y, font_size, color, inner_w)?;
+    Ok(())
+}
+
+impl<T> Widget for TableView<T> {
+    fn measure(&self, _ctx: &DrawContext<'_>, available_w: u32, _available_h: u32) -> (u32, u32) {
+        (
+            available_w,
+            self.header_height() + self.list.content_height(),
+        )
+    }
+
+    fn draw(&self, ctx: &mut DrawContext<'_>, x: i32, y: i32, w: u32, h: u32) -> Result<()> {
+        self.draw_at(ctx, x, y, w, h)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{DrawCall, MockBackend};
+    use crate::theme::Theme;
+
+    #[derive(Debug, Clone, PartialEq)]
+    struct File {
+        name: &'static str,
+        size: i64,
+    }
+
+    fn file(name: &'static str, size: i64) -> File {
+        File { name, size }
+    }
+
+    fn files() -> TableView<File> {
+        let columns = vec![
+            Column::new("Name", ColumnWidth::Weight(1), |f: &File| {
+                f.name.to_string()
+            })
+            .with_sort_key(|f| SortKey::Text(f.name.to_string())),
+            Column::new("Size", ColumnWidth::Fixed(60), |f: &File| {
+                f.size.to_string()
+            })
+            .with_align(HAlign::Right)
+            .with_sort_key(|f| SortKey::Number(f.size)),
+            Column::new("Kind", ColumnWidth::Weight(1), |_: &File| {
+                "file".to_string()
+            }),
+        ];
+        let rows = vec![
+            file("c.txt", 30),
+            file("a.txt", 10),
+            file("d.txt", 10),
+            file("b.txt", 20),
+        ];
+        TableView::new(columns, rows, 10)
+    }
+
+    fn names(table: &TableView<File>) -> Vec<&'static str> {
+        table.list.items.iter().map(|f| f.name).collect()
+    }
+
+    #[test]
+    fn column_widths_share_the_space_left_by_fixed_columns() {
+        let table = files();
+        assert_eq!(table.column_widths(260), vec![100, 60, 100]);
+        // The last weighted column takes the odd pixel.
+        assert_eq!(table.column_widths(161), vec![50, 60, 51]);
+        assert_eq!(table.column_widths(40), vec![0, 60, 0]);
+        assert_eq!(table.column_at(99, 260), Some(0));
+        assert_eq!(table.column_at(100, 260), Some(1));
+        assert_eq!(table.column_at(259, 260), Some(2));
+        assert_eq!(table.column_at(260, 260), None);
+        assert_eq!(table.column_at(-1, 260), None);
+    }
+
+    #[test]
+    fn sorting_is_stable_and_keeps_the_selection() {
+        let mut table = files();
+        table.list.selected = Some(2); // d.txt
+        table.set_sort(1, true);
+        assert_eq!(names(&table), ["a.txt", "d.txt", "b.txt", "c.txt"]);
+        assert_eq!(table.list.selected, Some(1));
+        // Descending keeps equal sizes in their existing order too.
+        table.set_sort(1, false);
+        assert_eq!(names(&table), ["c.txt", "b.txt", "a.txt", "d.txt"]);
+        assert_eq!(table.list.selected, Some(3));
+        assert_eq!(table.sort(), Some((1, false)));
+        // Columns without a sort key are left alone.
+        table.set_sort(2, true);
+        assert_eq!(table.sort(), Some((1, false)));
+        assert_eq!(names(&table), ["c.txt", "b.txt", "a.txt", "d.txt"]);
+    }
+
+    #[test]
+    fn header_clicks_toggle_the_sort() {
+        let mut table = files();
+        assert_eq!(table.click_header(10, 5, 260), Some(0));
+        assert_eq!(names(&table), ["a.txt", "b.txt", "c.txt", "d.txt"]);
+        assert_eq!(table.click_header(10, 5, 260), Some(0));
+        assert_eq!(names(&table), ["d.txt", "c.txt", "b.txt", "a.txt"]);
+        // Another column starts ascending.
+        assert_eq!(table.click_header(120, 5, 260), Some(1));
+        assert_eq!(table.sort(), Some((1, true)));
+        // Unsortable columns and clicks below the header do nothing.
+        assert_eq!(table.click_header(200, 5, 260), None);
+        assert_eq!(table.click_header(10, 15, 260), None);
+        assert_eq!(table.sort(), Some((1, true)));
+    }
+
+    #[test]
+    fn set_rows_keeps_the_sort() {
+        let mut table = files();
+        table.list.selected = Some(1);
+        table.set_sort(0, false);
+        table.set_rows(vec![file("x", 1), file("z", 2), file("y", 3)]);
+        assert_eq!(names(&table), ["z", "y", "x"]);
+        assert_eq!(table.list.selected, None);
+    }
+
+    #[test]
+    fn row_at_accounts_for_header_and_scroll() {
+        let mut table = files();
+        assert_eq!(table.row_at(5), None);
+        assert_eq!(table.row_at(10), Some(0));
+        assert_eq!(table.row_at(29), Some(1));
+        assert_eq!(table.row_at(50), None);
+        table.list.scroll_offset = 15;
+        assert_eq!(table.row_at(10), Some(1));
+        assert_eq!(table.row_at(20), Some(2));
+    }
+
+    #[test]
+    fn scroll_to_leaves_room_for_the_header() {
+        let mut table = files();
+        // 30px tall: the header and two rows.
+        table.scroll_to(3, 30);
+        assert_eq!(table.list.scroll_offset, 20);
+        table.scroll_to(0, 30);
+        assert_eq!(table.list.scroll_offset, 0);
+    }
+
+    #[test]
+    fn draw_renders_header_cells_and_selection() {
+        let theme = Theme::dark();
+        let mut backend = MockBackend::new();
+        let mut table = files();
+        table.set_sort(1, true);
+        table.list.selected = Some(0);
+        {
+            let mut ctx = DrawContext::new(&mut backend, &theme);
+            table.draw(&mut ctx, 0, 0, 260, 100).unwrap();
+        }
+        assert!(backend.has_text("Name"));
+        assert!(backend.has_text("Size ^"));
+        assert!(backend.has_text("Kind"));
+        assert!(backend.has_text("c.txt"));
+        // Four rows of three cells plus three titles.
+        assert_eq!(backend.draw_text_count(), 15);
+        // The header divider and the selected row.
+        let fills: Vec<_> = backend
+            .calls
+            .iter()
+            .filter_map(|c| match c {
+                DrawCall::FillRect { y, h, color, .. } => Some((*y, *h, *color)),
+                _ => None,
+            })
+            .collect();
+        assert!(fills.contains(&(9, 1, theme.border_subtle)));
+        assert!(fills.contains(&(10, 10, theme.accent_subtle)));
+    }
+
+    #[test]
+    fn numbers_align_right_and_long_text_is_truncated() {
+        let theme = Theme::dark();
+        let mut backend = MockBackend::new();
+        let columns = vec![
+            Column::new("Name", ColumnWidth::Fixed(40), |f: &File| {
+                f.name.to_string()
+            }),
+            Column::new("Size", ColumnWidth::Fixed(60), |f: &File| {
+                f.size.to_string()
+            })
+            .with_align(HAlign::Right),
+        ];
+        let table = TableView::new(columns, vec![file("a-very-long-name.txt", 7)], 10);
+        {
+            let mut ctx = DrawContext::new(&mut backend, &theme);
+            table.draw(&mut ctx, 0, 0, 100, 20).unwrap();
+        }
+        let pad = theme.spacing_xs as i32;
+        let seven_w = oasis_types::backend::bitmap_measure_text("7", theme.font_size_sm) as i32;
+        let texts = backend.text_positions();
+        let size = texts.iter().find(|t| t.0 == "7").unwrap();
+        assert_eq!(size.1, 100 - pad - seven_w);
+        assert!(!backend.has_text("a-very-long-name.txt"));
+        assert!(backend.has_text("..."));
+    }
+}
@@ -30,6 +30,14 @@ pub enum DrawCall {
         w: u32,
         h: u32,
     },
+    BlitSub {
+        tex: TextureId,
+        src: (u32, u32, u32, u32),
+        x: i32,
+        y: i32,
+        w: u32,
+        h: u32,
+    },
 }
 
 /// A mock backend that records all draw calls for test assertions.
@@ -118,6 +126,29 @@ impl SdiBackend for MockBackend {
         Ok(())
     }
 
+    fn blit_sub(
+        &mut self,
+        tex: TextureId,
+        src_x: u32,
+        src_y: u32,
+        src_w: u32,
+        src_h: u32,
+        x: i32,
+        y: i32,
+        w: u32,
+        h: u32,
+    ) -> Result<()> {
+        self.calls.push(DrawCall::BlitSub {
+            tex,
+            src: (src_x, src_y, src_w, src_h),
+            x,
+            y,
+            w,
+            h,
+        });
+        Ok(())
+    }
+
     fn fill_rect(&mut self, x: i32, y: i32, w: u32, h: u32, color: Color) -> Result<()> {
         self.calls.push(DrawCall::FillRect { x, y, w, h, color });
         Ok(())
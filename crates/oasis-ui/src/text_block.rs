@@ -1,7 +1,7 @@
 //! Text block widget: multiline text with wrapping, truncation, alignment.
 
-use crate::context::DrawContext;
-use crate::layout::HAlign;
+use crate::context::{DrawContext, TextLayout};
+use crate::layout::{HAlign, VAlign};
 use crate::widget::Widget;
 use oasis_types::backend::Color;
 use oasis_types::error::Result;
@@ -42,61 +42,149 @@ impl TextBlock {
             ctx.theme.font_size_md
         }
     }
-}
 
-impl Widget for TextBlock {
-    fn measure(&self, ctx: &DrawContext<'_>, available_w: u32, _available_h: u32) -> (u32, u32) {
-        let fs = self.effective_font_size(ctx);
-        let lh = if self.line_height > 0 {
+    fn effective_line_height(&self, ctx: &DrawContext<'_>) -> u32 {
+        if self.line_height > 0 {
             self.line_height
         } else {
-            ctx.backend.measure_text_height(fs)
-        };
-
-        let mut lines = 0u32;
-        let mut max_w = 0u32;
-        for line in self.text.split('\n') {
-            let words: Vec<&str> = line.split_whitespace().collect();
-            if words.is_empty() {
-                lines += 1;
-                continue;
-            }
-            let mut current = String::new();
-            for word in words {
-                let test = if current.is_empty() {
-                    word.to_string()
-                } else {
-                    format!("{current} {word}")
-                };
-                if ctx.backend.measure_text(&test, fs) > available_w && !current.is_empty() {
-                    max_w = max_w.max(ctx.backend.measure_text(&current, fs));
-                    lines += 1;
-                    current = word.to_string();
-                } else {
-                    current = test;
-                }
-            }
-            if !current.is_empty() {
-                max_w = max_w.max(ctx.backend.measure_text(&current, fs));
-                lines += 1;
-            }
+            ctx.backend
+                .measure_text_height(self.effective_font_size(ctx))
         }
-        if let Some(ml) = self.max_lines {
-            lines = lines.min(ml);
+    }
+
+    fn layout(&self) -> TextLayout {
+        TextLayout {
+            h_align: self.align,
+            v_align: VAlign::Top,
+            max_lines: self.max_lines,
+            line_height: self.line_height,
         }
-        (max_w, lines * lh)
     }
 
-    fn draw(&self, ctx: &mut DrawContext<'_>, x: i32, y: i32, w: u32, _h: u32) -> Result<()> {
+    /// Break the text into lines no wider than `width`, as drawn.
+    ///
+    /// See [`DrawContext::wrap_text`].
+    pub fn wrap_lines(&self, ctx: &DrawContext<'_>, width: u32) -> Vec<String> {
+        ctx.wrap_text(
+            &self.text,
+            width,
+            self.effective_font_size(ctx),
+            self.max_lines,
+        )
+    }
+}
+
+impl Widget for TextBlock {
+    fn measure(&self, ctx: &DrawContext<'_>, available_w: u32, _available_h: u32) -> (u32, u32) {
+        let fs = self.effective_font_size(ctx);
+        let lines = self.wrap_lines(ctx, available_w);
+        let max_w = lines
+            .iter()
+            .map(|line| ctx.backend.measure_text(line, fs))
+            .max()
+            .unwrap_or(0);
+        (max_w, lines.len() as u32 * self.effective_line_height(ctx))
+    }
+
+    fn draw(&self, ctx: &mut DrawContext<'_>, x: i32, y: i32, w: u32, h: u32) -> Result<()> {
         let fs = self.effective_font_size(ctx);
         let color = self.color.unwrap_or(ctx.theme.text_primary);
-        let lh = if self.line_height > 0 {
-            self.line_height
-        } else {
-            ctx.backend.measure_text_height(fs)
-        };
-        ctx.backend
-            .draw_text_wrapped(&self.text, x, y, fs, color, w, lh)?;
+        ctx.draw_text_wrapped(&self.text, (x, y, w, h), fs, color, self.layout())?;
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::context::ELLIPSIS;
+    use crate::test_utils::MockBackend;
+    use crate::theme::Theme;
+    use oasis_types::backend::SdiBackend;
+
+    fn block(text: &str) -> TextBlock {
+        let mut tb = TextBlock::new(text);
+        tb.font_size = 8;
+        tb
+    }
+
+    #[test]
+    fn wraps_on_word_boundaries() {
+        let theme = Theme::dark();
+        let mut backend = MockBackend::new();
+        let ctx = DrawContext::new(&mut backend, &theme);
+        let width = ctx.backend.measure_text("abcd abcd", 8);
+        let tb = block("abcd abcd abcd  abcd");
+        assert_eq!(tb.wrap_lines(&ctx, width), ["abcd abcd", "abcd abcd"]);
+        assert_eq!(tb.wrap_lines(&ctx, width * 3), ["abcd abcd abcd abcd"]);
+    }
+
+    #[test]
+    fn hard_breaks_text_without_spaces() {
+        let theme = Theme::dark();
+        let mut backend = MockBackend::new();
+        let ctx = DrawContext::new(&mut backend, &theme);
+        let width = ctx.backend.measure_text("mmmmm", 8);
+        let tb = block("mmmmmmmmmmmm");
+        assert_eq!(tb.wrap_lines(&ctx, width), ["mmmmm", "mmmmm", "mm"]);
+        // Even narrower than one glyph, every line keeps a character.
+        assert_eq!(tb.wrap_lines(&ctx, 0).len(), 12);
+
+        let lh = ctx.backend.measure_text_height(8);
+        assert_eq!(tb.measure(&ctx, width, 1000), (width, 3 * lh));
+    }
+
+    #[test]
+    fn keeps_newlines_and_blank_lines() {
+        let theme = Theme::dark();
+        let mut backend = MockBackend::new();
+        let ctx = DrawContext::new(&mut backend, &theme);
+        let tb = block("one\n\ntwo");
+        assert_eq!(tb.wrap_lines(&ctx, 200), ["one", "", "two"]);
+        let lh = ctx.backend.measure_text_height(8);
+        assert_eq!(tb.measure(&ctx, 200, 1000).1, 3 * lh);
+    }
+
+    #[test]
+    fn max_lines_ends_with_ellipsis() {
+        let theme = Theme::dark();
+        let mut backend = MockBackend::new();
+        let ctx = DrawContext::new(&mut backend, &theme);
+        let width = ctx.backend.measure_text("abcd abcd", 8);
+        let mut tb = block("abcd abcd abcd abcd abcd");
+        tb.max_lines = Some(2);
+        let lines = tb.wrap_lines(&ctx, width);
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0], "abcd abcd");
+        assert!(lines[1].starts_with("abcd") && lines[1].ends_with(ELLIPSIS));
+        assert!(ctx.backend.measure_text(&lines[1], 8) <= width);
+
+        let lh = ctx.backend.measure_text_height(8);
+        assert_eq!(tb.measure(&ctx, width, 1000).1, 2 * lh);
+
+        // Text that fits is left alone.
+        tb.max_lines = Some(3);
+        assert_eq!(tb.wrap_lines(&ctx, width * 3), ["abcd abcd abcd abcd abcd"]);
+    }
+
+    #[test]
+    fn draw_aligns_each_line() {
+        let theme = Theme::dark();
+        let mut backend = MockBackend::new();
+        let (short, long) = (
+            backend.measure_text("ab", 8) as i32,
+            backend.measure_text("abcd", 8) as i32,
+        );
+        {
+            let mut ctx = DrawContext::new(&mut backend, &theme);
+            let mut tb = block("ab abcd");
+            tb.align = HAlign::Right;
+            tb.line_height = 10;
+            tb.draw(&mut ctx, 5, 0, long as u32, 100).unwrap();
+        }
+        assert_eq!(
+            backend.text_positions(),
+            [("ab", 5 + long - short, 0, 8), ("abcd", 5, 10, 8)]
+        );
+    }
+}
@@ -0,0 +1,599 @@
+//! TextInput widget: a single-line text editor with selection.
+//!
+//! Unlike [`InputField`](crate::input_field::InputField), a `TextInput`
+//! handles [`InputEvent`]s itself: cursor movement by character and by
+//! word, Shift selection, cut/copy/paste, and a view that scrolls
+//! horizontally to keep the cursor visible instead of truncating long text.
+
+use std::borrow::Cow;
+use std::cell::Cell;
+use std::ops::Range;
+
+use crate::context::DrawContext;
+use crate::layout;
+use crate::widget::Widget;
+use oasis_types::error::Result;
+use oasis_types::input::{Button, EditKey, InputEvent};
+
+/// What [`TextInput::handle_input`] did with an event.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TextInputResponse {
+    /// Not an editing event; left for the caller.
+    Ignored,
+    /// Consumed without changing the text (cursor and selection moves).
+    Handled,
+    /// The text changed.
+    Changed,
+    /// Copy: the selected text, for the caller to put on the clipboard.
+    Copied(String),
+    /// Cut: the text removed, for the caller to put on the clipboard.
+    Cut(String),
+}
+
+impl TextInputResponse {
+    /// Whether the event was consumed.
+    pub fn consumed(&self) -> bool {
+        !matches!(self, Self::Ignored)
+    }
+}
+
+/// An editable single line of text with a cursor and a selection.
+///
+/// The clipboard belongs to the caller: copy and cut hand the text back
+/// in the [`TextInputResponse`], and pasting arrives as
+/// [`InputEvent::Paste`].
+#[derive(Debug, Clone, Default)]
+pub struct TextInput {
+    /// Current contents.
+    pub text: String,
+    /// Cursor position within `text` (byte offset, on a char boundary).
+    pub cursor: usize,
+    /// Text shown while the input is empty.
+    pub placeholder: String,
+    /// Whether the caret is drawn.
+    pub focused: bool,
+    /// Show every character as `*` and refuse to copy or cut (passwords).
+    pub masked: bool,
+    /// Other end of the selection; the selection spans from here to the
+    /// cursor.
+    anchor: Option<usize>,
+    /// Pixels the text is scrolled left by. Updated while drawing, since
+    /// that is when the visible width is known.
+    scroll_x: Cell<u32>,
+}
+
+impl TextInput {
+    /// Create an input holding `text` with the cursor at the end.
+    pub fn new(text: &str) -> Self {
+        Self {
+            text: text.to_string(),
+            cursor: text.len(),
+            ..Self::default()
+        }
+    }
+
+    /// Set the placeholder text.
+    pub fn with_placeholder(mut self, placeholder: impl Into<String>) -> Self {
+        self.placeholder = placeholder.into();
+        self
+    }
+
+    /// Replace the contents, move the cursor to the end and clear the
+    /// selection.
+    pub fn set(&mut self, text: &str) {
+        self.text = text.to_string();
+        self.cursor = self.text.len();
+        self.anchor = None;
+    }
+
+    /// Empty the input.
+    pub fn clear(&mut self) {
+        self.set("");
+    }
+
+    /// Whether the input is empty.
+    pub fn is_empty(&self) -> bool {
+        self.text.is_empty()
+    }
+
+    /// Number of characters before the cursor.
+    pub fn cursor_chars(&self) -> usize {
+        self.text[..self.cursor].chars().count()
+    }
+
+    /// The selected byte range, if anything is selected.
+    pub fn selection(&self) -> Option<Range<usize>> {
+        let anchor = self.anchor?.min(self.text.len());
+        if anchor == self.cursor || !self.text.is_char_boundary(anchor) {
+            return None;
+        }
+        Some(anchor.min(self.cursor)..anchor.max(self.cursor))
+    }
+
+    /// The selected text, if anything is selected.
+    pub fn selected_text(&self) -> Option<&str> {
+        self.selection().map(|sel| &self.text[sel])
+    }
+
+    /// Select the whole text, leaving the cursor at the end.
+    pub fn select_all(&mut self) {
+        self.anchor = Some(0);
+        self.cursor = self.text.len();
+    }
+
+    /// Pixels the text is currently scrolled left by.
+    pub fn scroll_offset(&self) -> u32 {
+        self.scroll_x.get()
+    }
+
+    /// Insert `text` at the cursor, replacing the selection.
+    pub fn insert_str(&mut self, text: &str) {
+        self.delete_selection();
+        self.text.insert_str(self.cursor, text);
+        self.cursor += text.len();
+    }
+
+    /// Apply an input event.
+    pub fn handle_input(&mut self, event: &InputEvent) -> TextInputResponse {
+        match event {
+            InputEvent::TextInput(ch) => {
+                self.insert_str(ch.encode_utf8(&mut [0; 4]));
+                TextInputResponse::Changed
+            },
+            InputEvent::Paste(text) => {
+                // Single-line input: drop newlines and other controls.
+                let text: String = text.chars().filter(|c| !c.is_control()).collect();
+                self.insert_str(&text);
+                TextInputResponse::Changed
+            },
+            InputEvent::Backspace => {
+                if !self.delete_selection() {
+                    let prev = self.prev_char(self.cursor);
+                    self.text.replace_range(prev..self.cursor, "");
+                    self.cursor = prev;
+                }
+                TextInputResponse::Changed
+            },
+            InputEvent::ButtonPress(Button::Left) => self.edit_key(EditKey::Left, false, false),
+            InputEvent::ButtonPress(Button::Right) => self.edit_key(EditKey::Right, false, false),
+            InputEvent::EditKey { key, shift, ctrl } => self.edit_key(*key, *shift, *ctrl),
+            _ => TextInputResponse::Ignored,
+        }
+    }
+
+    fn edit_key(&mut self, key: EditKey, shift: bool, ctrl: bool) -> TextInputResponse {
+        let target = match key {
+            EditKey::Left if ctrl => self.prev_word(self.cursor),
+            EditKey::Right if ctrl => self.next_word(self.cursor),
+            // Without Shift, an arrow first collapses the selection.
+            EditKey::Left => match self.selection() {
+                Some(sel) if !shift => sel.start,
+                _ => self.prev_char(self.cursor),
+            },
+            EditKey::Right => match self.selection() {
+                Some(sel) if !shift => sel.end,
+                _ => self.next_char(self.cursor),
+            },
+            EditKey::Home => 0,
+            EditKey::End => self.text.len(),
+            EditKey::Delete => {
+                if !self.delete_selection() {
+                    let end = if ctrl {
+                        self.next_word(self.cursor)
+                    } else {
+                        self.next_char(self.cursor)
+                    };
+                    self.text.replace_range(self.cursor..end, "");
+                }
+                return TextInputResponse::Changed;
+            },
+            EditKey::SelectAll => {
+                self.select_all();
+                return TextInputResponse::Handled;
+            },
+            EditKey::Copy | EditKey::Cut if self.masked => {
+                return TextInputResponse::Handled;
+            },
+            EditKey::Copy => {
+                return self
+                    .selected_text()
+                    .map_or(TextInputResponse::Handled, |text| {
+                        TextInputResponse::Copied(text.to_string())
+                    });
+            },
+            EditKey::Cut => {
+                let Some(text) = self.selected_text().map(str::to_string) else {
+                    return TextInputResponse::Handled;
+                };
+                self.delete_selection();
+                return TextInputResponse::Cut(text);
+            },
+        };
+        if shift {
+            self.anchor.get_or_insert(self.cursor);
+        } else {
+            self.anchor = None;
+        }
+        self.cursor = target;
+        TextInputResponse::Handled
+    }
+
+    /// Delete the selected text, if any. Always clears the selection.
+    fn delete_selection(&mut self) -> bool {
+        let selection = self.selection();
+        self.anchor = None;
+        let Some(sel) = selection else {
+            return false;
+        };
+        self.text.replace_range(sel.clone(), "");
+        self.cursor = sel.start;
+        true
+    }
+
+    /// Byte offset of the character before `pos` (or `pos` at the start).
+    fn prev_char(&self, pos: usize) -> usize {
+        self.text[..pos]
+            .char_indices()
+            .next_back()
+            .map_or(pos, |(i, _)| i)
+    }
+
+    /// Byte offset just past the character at `pos` (or `pos` at the end).
+    fn next_char(&self, pos: usize) -> usize {
+        self.text[pos..]
+            .chars()
+            .next()
+            .map_or(pos, |c| pos + c.len_utf8())
+    }
+
+    /// Start of the word before `pos`, skipping any separators first.
+    fn prev_word(&self, pos: usize) -> usize {
+        self.text[..pos]
+            .trim_end_matches(|c| !is_word_char(c))
+            .trim_end_matches(is_word_char)
+            .len()
+    }
+
+    /// End of the word after `pos`, skipping any separators first.
+    fn next_word(&self, pos: usize) -> usize {
+        let rest = self.text[pos..]
+            .trim_start_matches(|c| !is_word_char(c))
+            .trim_start_matches(is_word_char);
+        self.text.len() - rest.len()
+    }
+
+    /// The text as drawn.
+    fn display_text(&self) -> Cow<'_, str> {
+        if self.masked {
+            Cow::Owned("*".repeat(self.text.chars().count()))
+        } else {
+            Cow::Borrowed(&self.text)
+        }
+    }
+
+    /// Byte offset in [`display_text`](Self::display_text) matching `pos`
+    /// in the text.
+    fn display_offset(&self, pos: usize) -> usize {
+        if self.masked {
+            self.text[..pos].chars().count()
+        } else {
+            pos
+        }
+    }
+
+    /// Draw the text, selection and caret (no background or border) in a
+    /// box `w` wide, scrolling it horizontally to keep the cursor in view.
+    pub fn draw_text_at(
+        &self,
+        ctx: &mut DrawContext<'_>,
+        x: i32,
+        y: i32,
+        w: u32,
+        h: u32,
+    ) -> Result<()> {
+        let fs = ctx.theme.font_size_md;
+        let text_h = ctx.backend.measure_text_height(fs);
+        let ty = y + layout::center(h, text_h);
+        if w == 0 {
+            return Ok(());
+        }
+
+        if self.text.is_empty() {
+            self.scroll_x.set(0);
+            if !self.placeholder.is_empty() {
+                ctx.backend.draw_text_ellipsis(
+                    &self.placeholder,
+                    x,
+                    ty,
+                    fs,
+                    ctx.theme.text_disabled,
+                    w,
+                )?;
+            }
+            if self.focused {
+                ctx.backend
+                    .fill_rect(x, ty, 1, text_h, ctx.theme.text_primary)?;
+            }
+            return Ok(());
+        }
+
+        let display = self.display_text();
+        let px = |pos: usize| {
+            ctx.backend
+                .measure_text(&display[..self.display_offset(pos)], fs)
+        };
+        let caret = px(self.cursor);
+        let selection = self.selection().map(|sel| (px(sel.start), px(sel.end)));
+        let text_w = ctx.backend.measure_text(&display, fs);
+
+        // Scroll no further than needed to show the end of the text (and
+        // the caret after it), then just enough to bring the caret in.
+        let mut scroll = self.scroll_x.get().min((text_w + 1).saturating_sub(w));
+        scroll = scroll.min(caret);
+        if caret + 1 > scroll + w {
+            scroll = caret + 1 - w;
+        }
+        self.scroll_x.set(scroll);
+        let origin = x - scroll as i32;
+
+        ctx.backend.push_clip_rect(x, y, w, h)?;
+        let result = (|| {
+            if let Some((start, end)) = selection {
+                ctx.backend.fill_rect(
+                    origin + start as i32,
+                    ty,
+                    end - start,
+                    text_h,
+                    ctx.theme.accent_subtle,
+                )?;
+            }
+            ctx.backend
+                .draw_text(&display, origin, ty, fs, ctx.theme.text_primary)?;
+            if self.focused {
+                ctx.backend.fill_rect(
+                    origin + caret as i32,
+                    ty,
+                    1,
+                    text_h,
+                    ctx.theme.text_primary,
+                )?;
+            }
+            Ok(())
+        })();
+        ctx.backend.pop_clip_rect()?;
+        result
+    }
+}
+
+/// Characters that make up words for Ctrl+arrow movement.
+fn is_word_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
+impl Widget for TextInput {
+    fn measure(&self, ctx: &DrawContext<'_>, available_w: u32, _available_h: u32) -> (u32, u32) {
+        let h = ctx.backend.measure_text_height(ctx.theme.font_size_md) + 8;
+        (available_w, h)
+    }
+
+    fn draw(&self, ctx: &mut DrawContext<'_>, x: i32, y: i32, w: u32, h: u32) -> Result<()> {
+        let radius = ctx.theme.border_radius_md;
+        ctx.backend
+            .fill_rounded_rect(x, y, w, h, radius, ctx.theme.input_bg)?;
+        let bc = if self.focused {
+            ctx.theme.input_border_focus
+        } else {
+            ctx.theme.input_border
+        };
+        ctx.backend.stroke_rounded_rect(x, y, w, h, radius, 1, bc)?;
+        self.draw_text_at(ctx, x + 4, y, w.saturating_sub(8), h)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{DrawCall, MockBackend};
+    use crate::theme::Theme;
+
+    fn key(key: EditKey, shift: bool, ctrl: bool) -> InputEvent {
+        InputEvent::EditKey { key, shift, ctrl }
+    }
+
+    fn left() -> InputEvent {
+        InputEvent::ButtonPress(Button::Left)
+    }
+
+    #[test]
+    fn cursor_moves_by_character_over_multibyte_text() {
+        let mut input = TextInput::default();
+        for ch in "héllo".chars() {
+            assert_eq!(
+                input.handle_input(&InputEvent::TextInput(ch)),
+                TextInputResponse::Changed
+            );
+        }
+        for _ in 0..3 {
+            input.handle_input(&left());
+        }
+        assert_eq!(input.cursor_chars(), 2);
+        input.handle_input(&InputEvent::Backspace);
+        assert_eq!(input.text, "hllo");
+        assert_eq!(input.cursor, 1);
+        input.handle_input(&key(EditKey::Delete, false, false));
+        assert_eq!(input.text, "hlo");
+        // Both ends stay in bounds.
+        input.handle_input(&key(EditKey::Home, false, false));
+        input.handle_input(&left());
+        input.handle_input(&InputEvent::Backspace);
+        assert_eq!((input.text.as_str(), input.cursor), ("hlo", 0));
+        input.handle_input(&key(EditKey::End, false, false));
+        input.handle_input(&key(EditKey::Delete, false, false));
+        assert_eq!((input.text.as_str(), input.cursor), ("hlo", 3));
+    }
+
+    #[test]
+    fn ctrl_arrows_move_by_word() {
+        let mut input = TextInput::new("vfs://sites/héllo wörld");
+        input.handle_input(&key(EditKey::Left, false, true));
+        assert_eq!(&input.text[input.cursor..], "wörld");
+        input.handle_input(&key(EditKey::Left, false, true));
+        assert_eq!(&input.text[input.cursor..], "héllo wörld");
+        input.handle_input(&key(EditKey::Home, false, false));
+        input.handle_input(&key(EditKey::Right, false, true));
+        assert_eq!(&input.text[..input.cursor], "vfs");
+        input.handle_input(&key(EditKey::Right, false, true));
+        assert_eq!(&input.text[..input.cursor], "vfs://sites");
+        input.handle_input(&key(EditKey::Delete, false, true));
+        assert_eq!(input.text, "vfs://sites wörld");
+    }
+
+    #[test]
+    fn shift_extends_the_selection_and_typing_replaces_it() {
+        let mut input = TextInput::new("hello world");
+        input.handle_input(&key(EditKey::Left, true, true));
+        input.handle_input(&key(EditKey::Left, true, false));
+        assert_eq!(input.selected_text(), Some(" world"));
+        // A plain arrow collapses the selection to its start.
+        input.handle_input(&left());
+        assert_eq!(input.selection(), None);
+        assert_eq!(input.cursor, 5);
+        input.handle_input(&key(EditKey::End, true, false));
+        input.handle_input(&InputEvent::TextInput('!'));
+        assert_eq!(input.text, "hello!");
+        assert_eq!(input.selection(), None);
+        input.handle_input(&key(EditKey::SelectAll, false, false));
+        input.handle_input(&InputEvent::Backspace);
+        assert!(input.is_empty());
+    }
+
+    #[test]
+    fn copy_cut_and_paste() {
+        let mut input = TextInput::new("vfs://index.html");
+        assert_eq!(
+            input.handle_input(&key(EditKey::Copy, false, false)),
+            TextInputResponse::Handled
+        );
+        input.handle_input(&key(EditKey::Left, true, true));
+        input.handle_input(&key(EditKey::Left, true, true));
+        assert_eq!(
+            input.handle_input(&key(EditKey::Copy, false, false)),
+            TextInputResponse::Copied("index.html".into())
+        );
+        assert_eq!(
+            input.handle_input(&key(EditKey::Cut, false, false)),
+            TextInputResponse::Cut("index.html".into())
+        );
+        assert_eq!(input.text, "vfs://");
+        input.handle_input(&InputEvent::Paste("sites/\r\nhomé/".into()));
+        assert_eq!(input.text, "vfs://sites/homé/");
+        assert_eq!(input.cursor, input.text.len());
+    }
+
+    #[test]
+    fn masked_input_hides_text_and_refuses_to_copy() {
+        let theme = Theme::dark();
+        let mut backend = MockBackend::new();
+        let mut input = TextInput::new("sécret");
+        input.masked = true;
+        input.select_all();
+        assert_eq!(
+            input.handle_input(&key(EditKey::Copy, false, false)),
+            TextInputResponse::Handled
+        );
+        assert_eq!(
+            input.handle_input(&key(EditKey::Cut, false, false)),
+            TextInputResponse::Handled
+        );
+        assert_eq!(input.text, "sécret");
+        {
+            let mut ctx = DrawContext::new(&mut backend, &theme);
+            input.draw(&mut ctx, 0, 0, 200, 24).unwrap();
+        }
+        assert!(backend.has_text("******"));
+        assert!(!backend.has_text("sécret"));
+    }
+
+    #[test]
+    fn long_text_scrolls_to_keep_the_caret_visible() {
+        let theme = Theme::dark();
+        let mut backend = MockBackend::new();
+        let mut input = TextInput::new(&"abcdefg ".repeat(10));
+        input.focused = true;
+        let text_w = oasis_types::backend::bitmap_measure_text(&input.text, theme.font_size_md);
+        let caret_x = |backend: &MockBackend| {
+            backend
+                .calls
+                .iter()
+                .rev()
+                .find_map(|c| match c {
+                    DrawCall::FillRect { x, w: 1, .. } => Some(*x),
+                    _ => None,
+                })
+                .unwrap()
+        };
+
+        {
+            let mut ctx = DrawContext::new(&mut backend, &theme);
+            input.draw_text_at(&mut ctx, 10, 0, 100, 20).unwrap();
+        }
+        assert_eq!(input.scroll_offset(), text_w + 1 - 100);
+        assert_eq!(caret_x(&backend), 10 + 99);
+        let texts = backend.text_positions();
+        assert_eq!(texts[0].1, 10 - input.scroll_offset() as i32);
+
+        // Moving left inside the view does not scroll...
+        input.handle_input(&key(EditKey::Left, false, true));
+        backend.calls.clear();
+        {
+            let mut ctx = DrawContext::new(&mut backend, &theme);
+            input.draw_text_at(&mut ctx, 10, 0, 100, 20).unwrap();
+        }
+        assert_eq!(input.scroll_offset(), text_w + 1 - 100);
+        // ...but Home scrolls back to the start.
+        input.handle_input(&key(EditKey::Home, false, false));
+        backend.calls.clear();
+        {
+            let mut ctx = DrawContext::new(&mut backend, &theme);
+            input.draw_text_at(&mut ctx, 10, 0, 100, 20).unwrap();
+        }
+        assert_eq!(input.scroll_offset(), 0);
+        assert_eq!(caret_x(&backend), 10);
+    }
+
+    #[test]
+    fn draw_shows_placeholder_and_selection() {
+        let theme = Theme::dark();
+        let mut backend = MockBackend::new();
+        let mut input = TextInput::default().with_placeholder("Search");
+        {
+            let mut ctx = DrawContext::new(&mut backend, &theme);
+            input.draw(&mut ctx, 0, 0, 200, 24).unwrap();
+        }
+        assert!(backend.has_text("Search"));
+
+        let mut backend = MockBackend::new();
+        input.set("hello");
+        input.select_all();
+        {
+            let mut ctx = DrawContext::new(&mut backend, &theme);
+            input.draw(&mut ctx, 0, 0, 200, 24).unwrap();
+        }
+        assert!(backend.has_text("hello"));
+        let hello_w = oasis_types::backend::bitmap_measure_text("hello", theme.font_size_md);
+        assert!(backend.calls.iter().any(|c| matches!(
+            c,
+            DrawCall::FillRect { x: 4, w, color, .. }
+                if *w == hello_w && *color == theme.accent_subtle
+        )));
+    }
+
+    #[test]
+    fn non_edit_events_are_ignored() {
+        let mut input = TextInput::new("x");
+        let response = input.handle_input(&InputEvent::ButtonPress(Button::Confirm));
+        assert_eq!(response, TextInputResponse::Ignored);
+        assert!(!response.consumed());
+        assert_eq!(input.text, "x");
+    }
+}
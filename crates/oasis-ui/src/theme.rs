@@ -72,6 +72,10 @@ pub struct Theme {
     pub tooltip_bg: Color,
     /// Tooltip text color.
     pub tooltip_text: Color,
+    /// Progress bar and spinner track.
+    pub progress_track: Color,
+    /// Progress bar fill and spinner highlight.
+    pub progress_fill: Color,
 
     /// Extra-small font size.
     pub font_size_xs: u16,
@@ -156,6 +160,8 @@ impl Theme {
             scrollbar_thumb_hover: Color::rgba(255, 255, 255, 80),
             tooltip_bg: Color::rgb(50, 50, 65),
             tooltip_text: Color::rgb(220, 220, 230),
+            progress_track: Color::rgba(255, 255, 255, 10),
+            progress_fill: Color::rgb(80, 160, 255),
 
             font_size_xs: 8,
             font_size_sm: 8,
@@ -221,6 +227,8 @@ impl Theme {
             scrollbar_thumb_hover: Color::rgba(0, 0, 0, 60),
             tooltip_bg: Color::rgb(40, 40, 50),
             tooltip_text: Color::rgb(240, 240, 245),
+            progress_track: Color::rgba(0, 0, 0, 10),
+            progress_fill: Color::rgb(50, 120, 220),
 
             font_size_xs: 8,
             font_size_sm: 8,
@@ -254,6 +262,7 @@ impl Theme {
         theme.accent_hover = Color::rgb(255, 165, 60);
         theme.accent_pressed = Color::rgb(220, 120, 20);
         theme.accent_subtle = Color::rgba(255, 140, 30, 30);
+        theme.progress_fill = Color::rgb(255, 140, 30);
         theme.border_strong = Color::rgb(255, 140, 30);
         theme.success = Color::rgb(100, 220, 80);
         theme
@@ -303,6 +312,8 @@ impl Theme {
             scrollbar_thumb_hover: Color::rgba(255, 255, 255, 200),
             tooltip_bg: Color::rgb(0, 0, 0),
             tooltip_text: Color::rgb(255, 255, 255),
+            progress_track: Color::rgba(255, 255, 255, 30),
+            progress_fill: Color::rgb(0, 255, 255),
 
             font_size_xs: 8,
             font_size_sm: 8,
@@ -0,0 +1,327 @@
+//! Archives: a directory tree packed into one byte buffer.
+//!
+//! The format is a simple uncompressed tar-like stream. It starts with
+//! [`MAGIC`], then each entry is a header followed by its data:
+//!
+//! | field | size |
+//! |-------|------|
+//! | kind: `d`, `f` or `l` | 1 byte |
+//! | mode | 4 bytes, little-endian |
+//! | path length | 2 bytes, little-endian |
+//! | data size | 8 bytes, little-endian |
+//! | path, relative to the packed directory | path length |
+//! | file contents or link target | data size |
+//!
+//! A zero kind byte ends the archive. Directories come before what is in
+//! them, and empty directories are kept.
+
+use oasis_types::error::{OasisError, Result};
+
+use crate::{EntryKind, SYMLINK_MODE, Vfs, join};
+
+/// First bytes of every archive.
+pub const MAGIC: &[u8; 8] = b"OASISAR1";
+
+/// Kind byte that ends an archive.
+const END: u8 = 0;
+
+/// Size of an entry header, before the path.
+const HEADER_LEN: usize = 1 + 4 + 2 + 8;
+
+/// An entry listed in an archive.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ArchiveEntry {
+    /// Path relative to the packed directory, `/`-separated.
+    pub path: String,
+    pub kind: EntryKind,
+    /// Unix-style permission bits.
+    pub mode: u32,
+    /// Size of the file contents or link target.
+    pub size: u64,
+}
+
+/// Pack the directory `root` and everything in it. Links are stored as
+/// links, not followed.
+pub fn pack(vfs: &dyn Vfs, root: &str) -> Result<Vec<u8>> {
+    if vfs.stat(root)?.kind != EntryKind::Directory {
+        return Err(OasisError::Vfs(format!("not a directory: {root}")));
+    }
+    let mut out = MAGIC.to_vec();
+    pack_dir(vfs, root, "", &mut out)?;
+    out.push(END);
+    Ok(out)
+}
+
+fn pack_dir(vfs: &dyn Vfs, dir: &str, prefix: &str, out: &mut Vec<u8>) -> Result<()> {
+    let mut entries = vfs.readdir(dir)?;
+    entries.sort_by(|a, b| a.name.cmp(&b.name));
+    for entry in entries {
+        let path = join(dir, &entry.name);
+        let rel = if prefix.is_empty() {
+            entry.name.clone()
+        } else {
+            format!("{prefix}/{}", entry.name)
+        };
+        let (mode, data) = match entry.kind {
+            EntryKind::Directory => (vfs.metadata(&path)?.mode, Vec::new()),
+            EntryKind::File => (vfs.metadata(&path)?.mode, vfs.read(&path)?),
+            EntryKind::Symlink => (SYMLINK_MODE, vfs.readlink(&path)?.into_bytes()),
+        };
+        write_header(out, entry.kind, mode, &rel, &data)?;
+        if entry.kind == EntryKind::Directory {
+            pack_dir(vfs, &path, &rel, out)?;
+        }
+    }
+    Ok(())
+}
+
+fn write_header(
+    out: &mut Vec<u8>,
+    kind: EntryKind,
+    mode: u32,
+    path: &str,
+    data: &[u8],
+) -> Result<()> {
+    let path_len = u16::try_from(path.len())
+        .map_err(|_| OasisError::Vfs(format!("path too long to archive: {path}")))?;
+    out.push(match kind {
+        EntryKind::Directory => b'd',
+        EntryKind::File => b'f',
+        EntryKind::Symlink => b'l',
+    });
+    out.extend_from_slice(&mode.to_le_bytes());
+    out.extend_from_slice(&path_len.to_le_bytes());
+    out.extend_from_slice(&(data.len() as u64).to_le_bytes());
+    out.extend_from_slice(path.as_bytes());
+    out.extend_from_slice(data);
+    Ok(())
+}
+
+/// List the entries of an archive, checking it the way [`unpack`] does.
+pub fn list(bytes: &[u8]) -> Result<Vec<ArchiveEntry>> {
+    Ok(parse(bytes)?.into_iter().map(|(entry, _)| entry).collect())
+}
+
+/// Unpack an archive into `dest`, creating it if needed, and return the
+/// number of entries written.
+///
+/// The whole archive is checked first, so nothing is written when it is
+/// malformed or has an entry that would land outside `dest`: an absolute
+/// path, a `.` or `..` component, or a path through a link it contains.
+pub fn unpack(vfs: &mut dyn Vfs, bytes: &[u8], dest: &str) -> Result<usize> {
+    let entries = parse(bytes)?;
+    vfs.mkdir(dest)?;
+    for (entry, data) in &entries {
+        let path = join(dest, &entry.path);
+        match entry.kind {
+            EntryKind::Directory => vfs.mkdir(&path)?,
+            EntryKind::File => vfs.write(&path, data)?,
+            EntryKind::Symlink => {
+                let target = std::str::from_utf8(data).map_err(|_| {
+                    OasisError::Vfs(format!("bad link target in archive: {}", entry.path))
+                })?;
+                vfs.symlink(target, &path)?;
+                continue;
+            },
+        }
+        // Modes are kept where the filesystem allows it.
+        if let Ok(mut meta) = vfs.metadata(&path)
+            && meta.mode != entry.mode
+        {
+            meta.mode = entry.mode;
+            let _ = vfs.set_metadata(&path, &meta);
+        }
+    }
+    Ok(entries.len())
+}
+
+/// Read and check every entry, with its data.
+fn parse(bytes: &[u8]) -> Result<Vec<(ArchiveEntry, &[u8])>> {
+    let bad = |what: &str| OasisError::Vfs(format!("bad archive: {what}"));
+    let mut rest = bytes
+        .strip_prefix(MAGIC.as_slice())
+        .ok_or_else(|| bad("missing header"))?;
+    let mut entries: Vec<(ArchiveEntry, &[u8])> = Vec::new();
+    loop {
+        let Some((&kind, _)) = rest.split_first() else {
+            return Err(bad("truncated"));
+        };
+        if kind == END {
+            break;
+        }
+        if rest.len() < HEADER_LEN {
+            return Err(bad("truncated"));
+        }
+        let kind = match kind {
+            b'd' => EntryKind::Directory,
+            b'f' => EntryKind::File,
+            b'l' => EntryKind::Symlink,
+            _ => return Err(bad("unknown entry kind")),
+        };
+        let mode = u32::from_le_bytes(rest[1..5].try_into().unwrap());
+        let path_len = u16::from_le_bytes(rest[5..7].try_into().unwrap()) as usize;
+        let size = u64::from_le_bytes(rest[7..15].try_into().unwrap());
+        rest = &rest[HEADER_LEN..];
+        let data_len = usize::try_from(size).map_err(|_| bad("entry too large"))?;
+        if rest.len() < path_len || rest.len() - path_len < data_len {
+            return Err(bad("truncated"));
+        }
+        let path = std::str::from_utf8(&rest[..path_len]).map_err(|_| bad("path is not UTF-8"))?;
+        let data = &rest[path_len..path_len + data_len];
+        rest = &rest[path_len + data_len..];
+
+        check_path(path)?;
+        // A link must not lead a later entry (or a write to the link
+        // itself) outside the destination.
+        if let Some((link, _)) = entries.iter().find(|(e, _)| {
+            e.kind == EntryKind::Symlink
+                && path
+                    .strip_prefix(e.path.as_str())
+                    .is_some_and(|r| r.is_empty() || r.starts_with('/'))
+        }) {
+            return Err(OasisError::Vfs(format!(
+                "archive entry {path} goes through the link {}",
+                link.path
+            )));
+        }
+        entries.push((
+            ArchiveEntry {
+                path: path.to_string(),
+                kind,
+                mode,
+                size,
+            },
+            data,
+        ));
+    }
+    Ok(entries)
+}
+
+/// Refuse paths that could land outside the destination.
+fn check_path(path: &str) -> Result<()> {
+    let unsafe_path = path.is_empty()
+        || path.starts_with('/')
+        || path.contains('\\')
+        || path
+            .split('/')
+            .any(|part| part.is_empty() || part == "." || part == "..");
+    if unsafe_path {
+        return Err(OasisError::Vfs(format!("unsafe path in archive: {path:?}")));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::MemoryVfs;
+
+    fn site_vfs() -> MemoryVfs {
+        let mut vfs = MemoryVfs::new();
+        vfs.mkdir("/sites/home/img").unwrap();
+        vfs.mkdir("/sites/empty").unwrap();
+        vfs.write("/sites/home/index.html", b"<h1>Hi</h1>").unwrap();
+        vfs.write("/sites/home/img/logo.png", &[0, 1, 2, 255])
+            .unwrap();
+        vfs.symlink("home/index.html", "/sites/index.html").unwrap();
+        let mut meta = vfs.metadata("/sites/home/index.html").unwrap();
+        meta.mode = 0o600;
+        vfs.set_metadata("/sites/home/index.html", &meta).unwrap();
+        vfs
+    }
+
+    /// An archive holding one entry with the given header fields.
+    fn single_entry(kind: u8, path: &str, data: &[u8]) -> Vec<u8> {
+        let mut bytes = MAGIC.to_vec();
+        bytes.push(kind);
+        bytes.extend_from_slice(&0o644u32.to_le_bytes());
+        bytes.extend_from_slice(&(path.len() as u16).to_le_bytes());
+        bytes.extend_from_slice(&(data.len() as u64).to_le_bytes());
+        bytes.extend_from_slice(path.as_bytes());
+        bytes.extend_from_slice(data);
+        bytes.push(END);
+        bytes
+    }
+
+    #[test]
+    fn round_trip_keeps_tree_modes_and_links() {
+        let vfs = site_vfs();
+        let bytes = pack(&vfs, "/sites").unwrap();
+        let paths: Vec<String> = list(&bytes).unwrap().into_iter().map(|e| e.path).collect();
+        assert_eq!(
+            paths,
+            [
+                "empty",
+                "home",
+                "home/img",
+                "home/img/logo.png",
+                "home/index.html",
+                "index.html"
+            ]
+        );
+
+        let mut out = MemoryVfs::new();
+        assert_eq!(unpack(&mut out, &bytes, "/restore").unwrap(), 6);
+        assert_eq!(
+            out.read("/restore/home/img/logo.png").unwrap(),
+            [0, 1, 2, 255]
+        );
+        assert_eq!(
+            out.stat("/restore/empty").unwrap().kind,
+            EntryKind::Directory
+        );
+        assert!(out.readdir("/restore/empty").unwrap().is_empty());
+        assert_eq!(
+            out.metadata("/restore/home/index.html").unwrap().mode,
+            0o600
+        );
+        assert_eq!(
+            out.readlink("/restore/index.html").unwrap(),
+            "home/index.html"
+        );
+        assert_eq!(out.read("/restore/index.html").unwrap(), b"<h1>Hi</h1>");
+    }
+
+    #[test]
+    fn pack_needs_a_directory() {
+        let vfs = site_vfs();
+        assert!(pack(&vfs, "/sites/home/index.html").is_err());
+        assert!(pack(&vfs, "/missing").is_err());
+    }
+
+    #[test]
+    fn unpack_rejects_paths_leaving_the_destination() {
+        for path in ["../evil", "a/../../evil", "/etc/passwd", "./a", "a//b", ""] {
+            let mut vfs = MemoryVfs::new();
+            let err = unpack(&mut vfs, &single_entry(b'f', path, b"x"), "/dest").unwrap_err();
+            assert!(err.to_string().contains("unsafe path"), "{path}: {err}");
+            assert!(!vfs.exists("/dest"));
+        }
+
+        // A link followed by an entry written through it.
+        let mut bytes = single_entry(b'l', "out", b"/etc");
+        bytes.pop();
+        bytes.extend_from_slice(&single_entry(b'f', "out/passwd", b"x")[MAGIC.len()..]);
+        let mut vfs = MemoryVfs::new();
+        vfs.mkdir("/etc").unwrap();
+        assert!(unpack(&mut vfs, &bytes, "/dest").is_err());
+        assert!(!vfs.exists("/etc/passwd"));
+
+        // A link overwritten by a file of the same name.
+        let mut bytes = single_entry(b'l', "out", b"/etc/passwd");
+        bytes.pop();
+        bytes.extend_from_slice(&single_entry(b'f', "out", b"x")[MAGIC.len()..]);
+        assert!(unpack(&mut vfs, &bytes, "/dest").is_err());
+        assert!(!vfs.exists("/etc/passwd"));
+    }
+
+    #[test]
+    fn malformed_archives_are_errors() {
+        let bytes = pack(&site_vfs(), "/sites").unwrap();
+        assert!(list(&bytes[..bytes.len() - 1]).is_err());
+        assert!(list(&bytes[..20]).is_err());
+        assert!(list(b"not an archive").is_err());
+        assert!(list(&single_entry(b'x', "a", b"")).is_err());
+        assert_eq!(list(&[MAGIC.as_slice(), &[END]].concat()).unwrap(), []);
+    }
+}
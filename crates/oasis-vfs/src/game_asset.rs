@@ -8,7 +8,8 @@ use std::collections::{HashMap, HashSet};
 
 use oasis_types::error::{OasisError, Result};
 
-use crate::{EntryKind, Vfs, VfsEntry, VfsMetadata};
+use crate::journal::ChangeJournal;
+use crate::{ChangeKind, Changes, EntryKind, Vfs, VfsEntry, VfsMetadata, slice_range};
 
 /// An entry in the game asset VFS.
 #[derive(Debug, Clone)]
@@ -34,6 +35,8 @@ pub struct GameAssetVfs {
     overlay: HashMap<String, Node>,
     /// Paths marked as deleted (hides base entries).
     deleted: HashSet<String>,
+    /// Player changes made so far (base content is not journaled).
+    journal: ChangeJournal,
 }
 
 impl GameAssetVfs {
@@ -45,6 +48,7 @@ impl GameAssetVfs {
             base,
             overlay: HashMap::new(),
             deleted: HashSet::new(),
+            journal: ChangeJournal::new(),
         }
     }
 
@@ -155,6 +159,15 @@ impl Vfs for GameAssetVfs {
         }
     }
 
+    fn read_range(&self, path: &str, offset: u64, len: usize) -> Result<Vec<u8>> {
+        let path = normalize(path);
+        match self.effective_entry(&path) {
+            Some(Node::File(data)) => Ok(slice_range(data, offset, len).to_vec()),
+            Some(Node::Dir) => Err(OasisError::Vfs(format!("is a directory: {path}"))),
+            None => Err(OasisError::Vfs(format!("no such file: {path}"))),
+        }
+    }
+
     fn write(&mut self, path: &str, data: &[u8]) -> Result<()> {
         let path = normalize(path);
         let par = parent(&path);
@@ -163,6 +176,12 @@ impl Vfs for GameAssetVfs {
                 "parent directory does not exist: {par}"
             )));
         }
+        let kind = if self.effective_entry(&path).is_some() {
+            ChangeKind::Modified
+        } else {
+            ChangeKind::Created
+        };
+        self.journal.record(&path, kind);
         // Un-delete if it was previously deleted.
         self.deleted.remove(&path);
         self.overlay.insert(path, Node::File(data.to_vec()));
@@ -194,6 +213,7 @@ impl Vfs for GameAssetVfs {
         if par != path && !self.effective_dir_exists(&par) {
             self.mkdir(&par)?;
         }
+        self.journal.record(&path, ChangeKind::Created);
         self.deleted.remove(&path);
         self.overlay.insert(path, Node::Dir);
         Ok(())
@@ -223,6 +243,7 @@ impl Vfs for GameAssetVfs {
                 return Err(OasisError::Vfs(format!("no such path: {path}")));
             },
         }
+        self.journal.record(&path, ChangeKind::Removed);
         self.overlay.remove(&path);
         self.deleted.insert(path);
         Ok(())
@@ -232,6 +253,14 @@ impl Vfs for GameAssetVfs {
         let path = normalize(path);
         self.effective_entry(&path).is_some()
     }
+
+    fn generation(&self) -> u64 {
+        self.journal.generation()
+    }
+
+    fn changes_since(&self, generation: u64) -> Changes {
+        self.journal.since(generation)
+    }
 }
 
 /// Normalize a path: ensure leading `/`, collapse `//`, strip trailing `/`.
@@ -465,4 +494,42 @@ mod tests {
         assert!(vfs.exists("/a/b/c"));
         assert_eq!(vfs.read("/a/b/c/file.txt").unwrap(), b"deep");
     }
+
+    #[test]
+    fn default_rename_moves_base_tree() {
+        let mut vfs = GameAssetVfs::new();
+        vfs.add_base_file("/assets/maps/one.map", b"1");
+        vfs.add_base_file("/assets/readme", b"r");
+        vfs.rename("/assets", "/moved").unwrap();
+        assert!(!vfs.exists("/assets"));
+        assert!(!vfs.exists("/assets/maps/one.map"));
+        assert_eq!(vfs.read("/moved/maps/one.map").unwrap(), b"1");
+        assert_eq!(vfs.read("/moved/readme").unwrap(), b"r");
+        assert!(vfs.copy("/moved/readme", "/moved/readme").is_err());
+        assert!(vfs.rename("/moved", "/moved/maps/x").is_err());
+    }
+
+    #[test]
+    fn journal_tracks_player_changes_only() {
+        let mut vfs = GameAssetVfs::new();
+        vfs.add_base_file("/lore/intro.txt", b"base");
+        assert_eq!(vfs.generation(), 0);
+        vfs.write("/lore/intro.txt", b"edited").unwrap();
+        vfs.write("/lore/notes.txt", b"new").unwrap();
+        vfs.remove("/lore/intro.txt").unwrap();
+        let kinds: Vec<_> = vfs
+            .changes_since(0)
+            .changes
+            .into_iter()
+            .map(|(_, k)| k)
+            .collect();
+        assert_eq!(
+            kinds,
+            [
+                ChangeKind::Modified,
+                ChangeKind::Created,
+                ChangeKind::Removed
+            ]
+        );
+    }
 }
@@ -0,0 +1,132 @@
+//! Change journal: which paths changed since a given generation.
+//!
+//! Every change a VFS makes bumps its generation counter and is logged
+//! with the new generation. Callers remember the generation they last
+//! saw and ask for [`Vfs::changes_since`](crate::Vfs::changes_since) it.
+//! The log is bounded; once older entries are dropped, callers further
+//! behind get [`Changes::truncated`] and should rescan what they track.
+
+use std::collections::VecDeque;
+
+/// Number of changes a journal keeps before dropping the oldest.
+pub const JOURNAL_CAPACITY: usize = 256;
+
+/// What happened to a path.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChangeKind {
+    /// A file or directory was created.
+    Created,
+    /// A file was overwritten.
+    Modified,
+    /// A file or directory (with everything in it) was removed.
+    Removed,
+}
+
+/// Result of [`Vfs::changes_since`](crate::Vfs::changes_since).
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Changes {
+    /// Current generation; pass it to the next call.
+    pub generation: u64,
+    /// Changed paths, oldest first. A change to a directory covers
+    /// everything below it: moving or removing a tree is one entry.
+    pub changes: Vec<(String, ChangeKind)>,
+    /// Some changes after the requested generation are no longer in the
+    /// journal. `changes` lists the rest; callers should rescan.
+    pub truncated: bool,
+}
+
+/// A bounded log of changes, shared by the VFS implementations.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct ChangeJournal {
+    generation: u64,
+    /// Callers at a generation below this missed dropped changes.
+    floor: u64,
+    entries: VecDeque<(u64, String, ChangeKind)>,
+}
+
+impl ChangeJournal {
+    pub(crate) fn new() -> Self {
+        Self::default()
+    }
+
+    /// Log a change to `path`.
+    pub(crate) fn record(&mut self, path: &str, kind: ChangeKind) {
+        self.generation += 1;
+        if self.entries.len() == JOURNAL_CAPACITY
+            && let Some((generation, _, _)) = self.entries.pop_front()
+        {
+            self.floor = generation;
+        }
+        self.entries
+            .push_back((self.generation, path.to_string(), kind));
+    }
+
+    /// Drop every entry, so callers at an older generation rescan.
+    pub(crate) fn truncate(&mut self) {
+        self.generation += 1;
+        self.floor = self.generation;
+        self.entries.clear();
+    }
+
+    pub(crate) fn generation(&self) -> u64 {
+        self.generation
+    }
+
+    /// Changes logged after `generation`. A generation this journal has
+    /// not reached yet (say, from another VFS) counts as truncated.
+    pub(crate) fn since(&self, generation: u64) -> Changes {
+        Changes {
+            generation: self.generation,
+            changes: self
+                .entries
+                .iter()
+                .filter(|(g, _, _)| *g > generation)
+                .map(|(_, path, kind)| (path.clone(), *kind))
+                .collect(),
+            truncated: generation < self.floor || generation > self.generation,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn since_lists_newer_changes() {
+        let mut journal = ChangeJournal::new();
+        journal.record("/a", ChangeKind::Created);
+        let seen = journal.generation();
+        journal.record("/a", ChangeKind::Modified);
+        journal.record("/b", ChangeKind::Removed);
+        let changes = journal.since(seen);
+        assert_eq!(changes.generation, 3);
+        assert!(!changes.truncated);
+        assert_eq!(
+            changes.changes,
+            [
+                ("/a".to_string(), ChangeKind::Modified),
+                ("/b".to_string(), ChangeKind::Removed)
+            ]
+        );
+        assert!(journal.since(3).changes.is_empty());
+        assert!(journal.since(9).truncated);
+    }
+
+    #[test]
+    fn dropping_entries_truncates_old_callers() {
+        let mut journal = ChangeJournal::new();
+        for i in 0..JOURNAL_CAPACITY + 2 {
+            journal.record(&format!("/f{i}"), ChangeKind::Created);
+        }
+        let old = journal.since(1);
+        assert!(old.truncated);
+        assert_eq!(old.changes.len(), JOURNAL_CAPACITY);
+        assert!(!journal.since(2).truncated);
+
+        let seen = journal.generation();
+        journal.truncate();
+        assert!(journal.since(seen).truncated);
+        assert!(!journal.since(journal.generation()).truncated);
+    }
+}
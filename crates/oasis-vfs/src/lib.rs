@@ -4,16 +4,26 @@
 //! storage backends. On PSP, `ls` lists Memory Stick contents. On Pi, it
 //! lists real Linux directories. In UE5, it lists game-authored content.
 //! In tests, MemoryVfs provides a fully in-memory tree.
+//!
+//! Every implementation keeps a change journal (see [`Vfs::changes_since`])
+//! so features like skin hot-reload can tell when a path changed.
 
+pub mod archive;
 mod game_asset;
+mod journal;
 mod memory;
+mod mount;
+mod overlay;
 mod real;
 
 pub use game_asset::GameAssetVfs;
+pub use journal::{ChangeKind, Changes, JOURNAL_CAPACITY};
 pub use memory::MemoryVfs;
+pub use mount::MountVfs;
+pub use overlay::{OverlayVfs, WHITEOUT_PREFIX};
 pub use real::RealVfs;
 
-use oasis_types::error::Result;
+use oasis_types::error::{OasisError, Result};
 
 /// Type of a VFS entry.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -22,6 +32,9 @@ pub enum EntryKind {
     File,
     /// Directory.
     Directory,
+    /// Symbolic link. Only [`Vfs::lstat`] and [`Vfs::readdir`] report
+    /// links; everything else follows them.
+    Symlink,
 }
 
 /// A single entry returned by `readdir`.
@@ -29,9 +42,9 @@ pub enum EntryKind {
 pub struct VfsEntry {
     /// Name of the file or directory (basename, not full path).
     pub name: String,
-    /// Whether this entry is a file or directory.
+    /// Whether this entry is a file, directory or symbolic link.
     pub kind: EntryKind,
-    /// Size in bytes (0 for directories).
+    /// Size in bytes (0 for directories, the target length for links).
     pub size: u64,
 }
 
@@ -44,10 +57,75 @@ pub struct VfsMetadata {
     pub size: u64,
 }
 
+/// Permission bits of new files.
+pub const DEFAULT_FILE_MODE: u32 = 0o644;
+
+/// Permission bits of new directories.
+pub const DEFAULT_DIR_MODE: u32 = 0o755;
+
+/// Permission bits of symbolic links.
+pub const SYMLINK_MODE: u32 = 0o777;
+
+/// Owner of new files and directories.
+pub const DEFAULT_OWNER: &str = "oasis";
+
+/// Most symbolic links followed while resolving one path. Deeper chains
+/// (and cycles) fail with "too many levels of symbolic links".
+pub const MAX_SYMLINK_DEPTH: usize = 16;
+
+/// Full metadata of a file or directory: what [`Vfs::stat`] reports plus
+/// permissions, ownership and timestamps.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Metadata {
+    /// Whether this is a file or directory.
+    pub kind: EntryKind,
+    /// Size in bytes (0 for directories).
+    pub size: u64,
+    /// Unix-style permission bits, e.g. `0o644`.
+    pub mode: u32,
+    /// Owner name.
+    pub owner: String,
+    /// Creation time in seconds since the Unix epoch, 0 if unknown.
+    pub created: u64,
+    /// Last modification time in seconds since the Unix epoch, 0 if
+    /// unknown.
+    pub modified: u64,
+}
+
+impl Metadata {
+    /// Default metadata for an entry of `kind` and `size` with unknown
+    /// timestamps.
+    pub fn new(kind: EntryKind, size: u64) -> Self {
+        Self {
+            kind,
+            size,
+            mode: match kind {
+                EntryKind::File => DEFAULT_FILE_MODE,
+                EntryKind::Directory => DEFAULT_DIR_MODE,
+                EntryKind::Symlink => SYMLINK_MODE,
+            },
+            owner: DEFAULT_OWNER.to_string(),
+            created: 0,
+            modified: 0,
+        }
+    }
+
+    /// Whether the owner may write to the entry.
+    pub fn is_writable(&self) -> bool {
+        self.mode & 0o200 != 0
+    }
+}
+
 /// The virtual file system trait.
 ///
 /// All file operations in the command interpreter go through this trait.
 /// Paths are always forward-slash separated, absolute (starting with `/`).
+///
+/// Symbolic links are followed, except by [`lstat`](Vfs::lstat),
+/// [`readlink`](Vfs::readlink), [`remove`](Vfs::remove),
+/// [`remove_dir_all`](Vfs::remove_dir_all), [`rename`](Vfs::rename) and
+/// [`copy`](Vfs::copy), which act on (or copy) the link itself. Links in
+/// the middle of a path are always followed.
 pub trait Vfs {
     /// List entries in a directory.
     fn readdir(&self, path: &str) -> Result<Vec<VfsEntry>>;
@@ -55,6 +133,14 @@ pub trait Vfs {
     /// Read entire file contents.
     fn read(&self, path: &str) -> Result<Vec<u8>>;
 
+    /// Read up to `len` bytes of a file starting at byte `offset`, for
+    /// streaming files too big to hold at once. Fewer bytes come back near
+    /// the end of the file, and none (not an error) from past it. The
+    /// default reads the whole file and slices it.
+    fn read_range(&self, path: &str, offset: u64, len: usize) -> Result<Vec<u8>> {
+        Ok(slice_range(&self.read(path)?, offset, len).to_vec())
+    }
+
     /// Write data to a file, creating or overwriting it.
     fn write(&mut self, path: &str, data: &[u8]) -> Result<()>;
 
@@ -69,4 +155,176 @@ pub trait Vfs {
 
     /// Check whether a path exists.
     fn exists(&self, path: &str) -> bool;
+
+    /// Move a file or a directory with its contents to `to`.
+    ///
+    /// The parent of `to` must exist. An existing file at `to` is replaced
+    /// when moving a file; any other existing destination is an error, as
+    /// is moving a directory into itself. The default copies and then
+    /// removes the source.
+    fn rename(&mut self, from: &str, to: &str) -> Result<()> {
+        let kind = check_transfer(self, from, to, "move")?;
+        copy_tree(self, from, to, kind)?;
+        self.remove_dir_all(from)
+    }
+
+    /// Copy a file or a directory with its contents to `to`, under the
+    /// same rules as [`rename`](Vfs::rename).
+    fn copy(&mut self, from: &str, to: &str) -> Result<()> {
+        let kind = check_transfer(self, from, to, "copy")?;
+        copy_tree(self, from, to, kind)
+    }
+
+    /// Remove a file, or a directory and everything in it. Links inside
+    /// are removed, not followed.
+    fn remove_dir_all(&mut self, path: &str) -> Result<()> {
+        if self.lstat(path)?.kind == EntryKind::Directory {
+            for entry in self.readdir(path)? {
+                self.remove_dir_all(&join(path, &entry.name))?;
+            }
+        }
+        self.remove(path)
+    }
+
+    /// Permissions, ownership and timestamps of a path. The default
+    /// reports [`Metadata::new`] for what [`stat`](Vfs::stat) returns.
+    fn metadata(&self, path: &str) -> Result<Metadata> {
+        let meta = self.stat(path)?;
+        Ok(Metadata::new(meta.kind, meta.size))
+    }
+
+    /// Set the mode, owner and timestamps of a path from `meta`; its
+    /// kind and size are ignored. The default refuses.
+    fn set_metadata(&mut self, path: &str, meta: &Metadata) -> Result<()> {
+        let _ = meta;
+        self.stat(path)?;
+        Err(OasisError::Vfs(format!(
+            "cannot change metadata on this filesystem: {path}"
+        )))
+    }
+
+    /// Create a symbolic link at `link` pointing to `target`. The target
+    /// need not exist; a relative target is resolved from the directory
+    /// holding the link. The default refuses.
+    fn symlink(&mut self, target: &str, link: &str) -> Result<()> {
+        let _ = target;
+        Err(OasisError::Vfs(format!(
+            "symbolic links are not supported on this filesystem: {link}"
+        )))
+    }
+
+    /// Target of the symbolic link at `path`, as it was given to
+    /// [`symlink`](Vfs::symlink).
+    fn readlink(&self, path: &str) -> Result<String> {
+        self.lstat(path)?;
+        Err(OasisError::Vfs(format!("not a symbolic link: {path}")))
+    }
+
+    /// Like [`stat`](Vfs::stat), but reports a symbolic link itself
+    /// instead of what it points to. The default is `stat`, for
+    /// filesystems without links.
+    fn lstat(&self, path: &str) -> Result<VfsMetadata> {
+        self.stat(path)
+    }
+
+    /// Current change generation: grows with every write, removal, new
+    /// directory or move. Always 0 for a VFS that does not track changes.
+    fn generation(&self) -> u64 {
+        0
+    }
+
+    /// Changes made after `generation` (see [`Changes`]). A VFS that does
+    /// not track changes reports none.
+    fn changes_since(&self, generation: u64) -> Changes {
+        let _ = generation;
+        Changes::default()
+    }
+
+    /// Mount points of the filesystems combined in this VFS, sorted.
+    /// Empty for a VFS backed by a single filesystem.
+    fn mounts(&self) -> Vec<String> {
+        Vec::new()
+    }
+}
+
+/// The part of `data` that [`Vfs::read_range`] returns.
+fn slice_range(data: &[u8], offset: u64, len: usize) -> &[u8] {
+    let start = usize::try_from(offset).map_or(data.len(), |o| o.min(data.len()));
+    &data[start..data.len().min(start.saturating_add(len))]
+}
+
+/// Join a directory path and an entry name.
+fn join(dir: &str, name: &str) -> String {
+    format!("{}/{name}", dir.trim_end_matches('/'))
+}
+
+/// Whether `path` is `dir` or inside it. Both must be normalized.
+pub fn is_within(path: &str, dir: &str) -> bool {
+    dir == "/" || path == dir || path.strip_prefix(dir).is_some_and(|r| r.starts_with('/'))
+}
+
+/// Check that `from` can be copied or moved (`verb`) to `to` under the
+/// rules of [`Vfs::rename`], returning the kind of `from`.
+fn check_transfer<V: Vfs + ?Sized>(vfs: &V, from: &str, to: &str, verb: &str) -> Result<EntryKind> {
+    let kind = vfs.lstat(from)?.kind;
+    let (src, dst) = (trim_path(from), trim_path(to));
+    if src == dst {
+        return Err(OasisError::Vfs(format!(
+            "{from} and {to} are the same file"
+        )));
+    }
+    if kind == EntryKind::Directory && is_within(dst, src) {
+        return Err(OasisError::Vfs(format!("cannot {verb} {from} into itself")));
+    }
+    if let Ok(meta) = vfs.stat(to)
+        && (meta.kind == EntryKind::Directory || kind == EntryKind::Directory)
+    {
+        return Err(OasisError::Vfs(format!("destination exists: {to}")));
+    }
+    let parent = match dst.rfind('/') {
+        Some(0) | None => "/",
+        Some(i) => &dst[..i],
+    };
+    if !vfs
+        .stat(parent)
+        .is_ok_and(|meta| meta.kind == EntryKind::Directory)
+    {
+        return Err(OasisError::Vfs(format!("no such directory: {parent}")));
+    }
+    Ok(kind)
+}
+
+/// `path` without trailing slashes (except for the root).
+fn trim_path(path: &str) -> &str {
+    match path.trim_end_matches('/') {
+        "" => "/",
+        trimmed => trimmed,
+    }
+}
+
+/// Copy `from` (of kind `kind`) to `to` through the basic operations.
+/// Links are copied as links.
+fn copy_tree<V: Vfs + ?Sized>(vfs: &mut V, from: &str, to: &str, kind: EntryKind) -> Result<()> {
+    match kind {
+        EntryKind::File => {
+            let data = vfs.read(from)?;
+            vfs.write(to, &data)
+        },
+        EntryKind::Directory => {
+            vfs.mkdir(to)?;
+            for entry in vfs.readdir(from)? {
+                copy_tree(
+                    vfs,
+                    &join(from, &entry.name),
+                    &join(to, &entry.name),
+                    entry.kind,
+                )?;
+            }
+            Ok(())
+        },
+        EntryKind::Symlink => {
+            let target = vfs.readlink(from)?;
+            vfs.symlink(&target, to)
+        },
+    }
 }
@@ -5,15 +5,73 @@
 
 use std::borrow::Cow;
 use std::collections::BTreeMap;
+use std::time::{SystemTime, UNIX_EPOCH};
 
 use oasis_types::error::{OasisError, Result};
 
-use crate::{EntryKind, Vfs, VfsEntry, VfsMetadata};
+use crate::journal::ChangeJournal;
+use crate::{
+    ChangeKind, Changes, EntryKind, MAX_SYMLINK_DEPTH, Metadata, Vfs, VfsEntry, VfsMetadata,
+    check_transfer, slice_range,
+};
 
 #[derive(Debug, Clone)]
 enum Node {
     File(Vec<u8>),
     Dir,
+    /// Symbolic link, holding its target as given.
+    Link(String),
+}
+
+impl Node {
+    fn kind(&self) -> EntryKind {
+        match self {
+            Node::File(_) => EntryKind::File,
+            Node::Dir => EntryKind::Directory,
+            Node::Link(_) => EntryKind::Symlink,
+        }
+    }
+
+    /// Size as reported by `stat`: file length, 0 for directories and
+    /// the target length for links.
+    fn size(&self) -> u64 {
+        match self {
+            Node::File(data) => data.len() as u64,
+            Node::Dir => 0,
+            Node::Link(target) => target.len() as u64,
+        }
+    }
+}
+
+/// Mode, owner and timestamps of a node.
+#[derive(Debug, Clone)]
+struct Attrs {
+    mode: u32,
+    owner: String,
+    created: u64,
+    modified: u64,
+}
+
+impl Attrs {
+    /// Attributes of a node of `kind` created now.
+    fn new(kind: EntryKind) -> Self {
+        let meta = Metadata::new(kind, 0);
+        let now = now_secs();
+        Self {
+            mode: meta.mode,
+            owner: meta.owner,
+            created: now,
+            modified: now,
+        }
+    }
+}
+
+/// Seconds since the Unix epoch.
+fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
 }
 
 /// A fully in-memory virtual file system.
@@ -21,6 +79,12 @@ enum Node {
 pub struct MemoryVfs {
     /// Map of normalized paths to file/directory nodes.
     nodes: BTreeMap<String, Node>,
+    /// Attributes of the nodes, by the same keys.
+    attrs: BTreeMap<String, Attrs>,
+    /// Changes made so far.
+    journal: ChangeJournal,
+    /// Number of `Node::Link`s; paths are resolved only when nonzero.
+    links: usize,
 }
 
 impl MemoryVfs {
@@ -28,7 +92,93 @@ impl MemoryVfs {
     pub fn new() -> Self {
         let mut nodes = BTreeMap::new();
         nodes.insert("/".to_string(), Node::Dir);
-        Self { nodes }
+        let mut attrs = BTreeMap::new();
+        attrs.insert("/".to_string(), Attrs::new(EntryKind::Directory));
+        Self {
+            nodes,
+            attrs,
+            journal: ChangeJournal::new(),
+            links: 0,
+        }
+    }
+}
+
+impl MemoryVfs {
+    /// Insert a node, keeping the link count.
+    fn insert_node(&mut self, key: String, node: Node) {
+        if matches!(node, Node::Link(_)) {
+            self.links += 1;
+        }
+        if let Some(Node::Link(_)) = self.nodes.insert(key, node) {
+            self.links -= 1;
+        }
+    }
+
+    /// Remove a node, keeping the link count.
+    fn remove_node(&mut self, key: &str) -> Option<Node> {
+        let node = self.nodes.remove(key);
+        if let Some(Node::Link(_)) = node {
+            self.links -= 1;
+        }
+        node
+    }
+
+    /// The key of the node `path` names, with symbolic links along the way
+    /// followed. The last component is followed only if `follow_last` is
+    /// set. The node itself need not exist.
+    fn resolve<'a>(&self, path: &'a str, follow_last: bool) -> Result<Cow<'a, str>> {
+        let path = normalize(path);
+        if self.links == 0 {
+            return Ok(path);
+        }
+        // Components still to resolve, next one last.
+        let mut pending: Vec<String> = Vec::new();
+        push_components(&mut pending, &path);
+        let mut resolved = String::new();
+        let mut followed = 0;
+        while let Some(name) = pending.pop() {
+            if name == ".." {
+                let end = resolved.rfind('/').unwrap_or(0);
+                resolved.truncate(end);
+                continue;
+            }
+            let candidate = format!("{resolved}/{name}");
+            if let Some(Node::Link(target)) = self.nodes.get(&candidate)
+                && (follow_last || !pending.is_empty())
+            {
+                followed += 1;
+                if followed > MAX_SYMLINK_DEPTH {
+                    return Err(OasisError::Vfs(format!(
+                        "too many levels of symbolic links: {path}"
+                    )));
+                }
+                if target.starts_with('/') {
+                    resolved.clear();
+                }
+                push_components(&mut pending, target);
+                continue;
+            }
+            resolved = candidate;
+        }
+        if resolved.is_empty() {
+            resolved.push('/');
+        }
+        Ok(Cow::Owned(resolved))
+    }
+
+    /// Keys of `path` and everything below it.
+    fn subtree_keys(&self, path: &str) -> Vec<String> {
+        let prefix = format!("{}/", path.trim_end_matches('/'));
+        std::iter::once(path.to_string())
+            .chain(
+                self.nodes
+                    .range(prefix.clone()..)
+                    .map(|(k, _)| k)
+                    .take_while(|k| k.starts_with(&prefix))
+                    .cloned(),
+            )
+            .filter(|k| self.nodes.contains_key(k))
+            .collect()
     }
 }
 
@@ -53,7 +203,7 @@ fn is_normalized(path: &str) -> bool {
 /// Normalize a path: ensure leading `/`, collapse `//`, strip trailing `/`
 /// (except for root). Returns the input unchanged (zero-alloc) when already
 /// in normal form.
-fn normalize(path: &str) -> Cow<'_, str> {
+pub(crate) fn normalize(path: &str) -> Cow<'_, str> {
     if is_normalized(path) {
         return Cow::Borrowed(path);
     }
@@ -83,6 +233,17 @@ fn normalize(path: &str) -> Cow<'_, str> {
     Cow::Owned(result)
 }
 
+/// Push the components of `path` onto `stack` so they pop in order,
+/// skipping empty ones and `.`.
+fn push_components(stack: &mut Vec<String>, path: &str) {
+    stack.extend(
+        path.split('/')
+            .rev()
+            .filter(|c| !c.is_empty() && *c != ".")
+            .map(String::from),
+    );
+}
+
 /// Return the parent of a normalized path.
 fn parent(path: &str) -> &str {
     if path == "/" {
@@ -97,13 +258,13 @@ fn parent(path: &str) -> &str {
 
 impl Vfs for MemoryVfs {
     fn readdir(&self, path: &str) -> Result<Vec<VfsEntry>> {
-        let path = normalize(path);
+        let path = self.resolve(path, true)?;
         match self.nodes.get(path.as_ref()) {
             Some(Node::Dir) => {},
             Some(Node::File(_)) => {
                 return Err(OasisError::Vfs(format!("not a directory: {path}")));
             },
-            None => {
+            Some(Node::Link(_)) | None => {
                 return Err(OasisError::Vfs(format!("no such directory: {path}")));
             },
         }
@@ -128,14 +289,8 @@ impl Vfs for MemoryVfs {
             if !rest.is_empty() && !rest.contains('/') {
                 entries.push(VfsEntry {
                     name: rest.to_string(),
-                    kind: match node {
-                        Node::Dir => EntryKind::Directory,
-                        Node::File(_) => EntryKind::File,
-                    },
-                    size: match node {
-                        Node::File(data) => data.len() as u64,
-                        Node::Dir => 0,
-                    },
+                    kind: node.kind(),
+                    size: node.size(),
                 });
             }
         }
@@ -145,16 +300,26 @@ impl Vfs for MemoryVfs {
     }
 
     fn read(&self, path: &str) -> Result<Vec<u8>> {
-        let path = normalize(path);
+        let path = self.resolve(path, true)?;
         match self.nodes.get(path.as_ref()) {
             Some(Node::File(data)) => Ok(data.clone()),
             Some(Node::Dir) => Err(OasisError::Vfs(format!("is a directory: {path}"))),
-            None => Err(OasisError::Vfs(format!("no such file: {path}"))),
+            Some(Node::Link(_)) | None => Err(OasisError::Vfs(format!("no such file: {path}"))),
+        }
+    }
+
+    fn read_range(&self, path: &str, offset: u64, len: usize) -> Result<Vec<u8>> {
+        let path = self.resolve(path, true)?;
+        match self.nodes.get(path.as_ref()) {
+            Some(Node::File(data)) => Ok(slice_range(data, offset, len).to_vec()),
+            Some(Node::Dir) => Err(OasisError::Vfs(format!("is a directory: {path}"))),
+            Some(Node::Link(_)) | None => Err(OasisError::Vfs(format!("no such file: {path}"))),
         }
     }
 
+    /// Writing through a dangling link creates its target.
     fn write(&mut self, path: &str, data: &[u8]) -> Result<()> {
-        let path = normalize(path);
+        let path = self.resolve(path, true)?.into_owned();
         // Ensure parent directory exists.
         let par = parent(&path);
         if !self.nodes.contains_key(par) {
@@ -162,46 +327,56 @@ impl Vfs for MemoryVfs {
                 "parent directory does not exist: {par}"
             )));
         }
-        self.nodes
-            .insert(path.into_owned(), Node::File(data.to_vec()));
+        let kind = if self.nodes.contains_key(&path) {
+            ChangeKind::Modified
+        } else {
+            ChangeKind::Created
+        };
+        self.journal.record(&path, kind);
+        match self.attrs.get_mut(&path) {
+            Some(attrs) if kind == ChangeKind::Modified => attrs.modified = now_secs(),
+            _ => {
+                self.attrs.insert(path.clone(), Attrs::new(EntryKind::File));
+            },
+        }
+        self.insert_node(path, Node::File(data.to_vec()));
         Ok(())
     }
 
     fn stat(&self, path: &str) -> Result<VfsMetadata> {
-        let path = normalize(path);
+        let path = self.resolve(path, true)?;
         match self.nodes.get(path.as_ref()) {
-            Some(Node::File(data)) => Ok(VfsMetadata {
-                kind: EntryKind::File,
-                size: data.len() as u64,
-            }),
-            Some(Node::Dir) => Ok(VfsMetadata {
-                kind: EntryKind::Directory,
-                size: 0,
+            Some(node @ (Node::File(_) | Node::Dir)) => Ok(VfsMetadata {
+                kind: node.kind(),
+                size: node.size(),
             }),
-            None => Err(OasisError::Vfs(format!("no such path: {path}"))),
+            Some(Node::Link(_)) | None => Err(OasisError::Vfs(format!("no such path: {path}"))),
         }
     }
 
     fn mkdir(&mut self, path: &str) -> Result<()> {
-        let path = normalize(path);
-        if self.nodes.contains_key(path.as_ref()) {
+        let path = self.resolve(path, true)?.into_owned();
+        if self.nodes.contains_key(&path) {
             return Ok(()); // Already exists, no error.
         }
         // Ensure parent exists (create parents recursively).
         let par = parent(&path).to_string();
-        if par != path.as_ref() && !self.nodes.contains_key(&par) {
+        if par != path && !self.nodes.contains_key(&par) {
             self.mkdir(&par)?;
         }
-        self.nodes.insert(path.into_owned(), Node::Dir);
+        self.journal.record(&path, ChangeKind::Created);
+        self.attrs
+            .insert(path.clone(), Attrs::new(EntryKind::Directory));
+        self.insert_node(path, Node::Dir);
         Ok(())
     }
 
     fn remove(&mut self, path: &str) -> Result<()> {
-        let path = normalize(path);
-        if path.as_ref() == "/" {
+        let path = self.resolve(path, false)?.into_owned();
+        if path == "/" {
             return Err(OasisError::Vfs("cannot remove root".to_string()));
         }
-        match self.nodes.get(path.as_ref()) {
+        match self.nodes.get(&path) {
             Some(Node::Dir) => {
                 // Check that directory is empty using BTreeMap range scan.
                 let prefix = format!("{path}/");
@@ -214,18 +389,162 @@ impl Vfs for MemoryVfs {
                     return Err(OasisError::Vfs(format!("directory not empty: {path}")));
                 }
             },
-            Some(Node::File(_)) => {},
+            Some(Node::File(_) | Node::Link(_)) => {},
             None => {
                 return Err(OasisError::Vfs(format!("no such path: {path}")));
             },
         }
-        self.nodes.remove(path.as_ref());
+        self.remove_node(&path);
+        self.attrs.remove(&path);
+        self.journal.record(&path, ChangeKind::Removed);
         Ok(())
     }
 
     fn exists(&self, path: &str) -> bool {
-        let path = normalize(path);
-        self.nodes.contains_key(path.as_ref())
+        self.resolve(path, true)
+            .is_ok_and(|path| self.nodes.contains_key(path.as_ref()))
+    }
+
+    /// Moves the nodes to their new keys without copying file data.
+    fn rename(&mut self, from: &str, to: &str) -> Result<()> {
+        let from = self.resolve(from, false)?.into_owned();
+        let to = self.resolve(to, false)?.into_owned();
+        check_transfer(self, &from, &to, "move")?;
+        for key in self.subtree_keys(&from) {
+            if let Some(node) = self.remove_node(&key) {
+                let new_key = format!("{to}{}", &key[from.len()..]);
+                if let Some(attrs) = self.attrs.remove(&key) {
+                    self.attrs.insert(new_key.clone(), attrs);
+                }
+                self.insert_node(new_key, node);
+            }
+        }
+        self.journal.record(&from, ChangeKind::Removed);
+        self.journal.record(&to, ChangeKind::Created);
+        Ok(())
+    }
+
+    fn copy(&mut self, from: &str, to: &str) -> Result<()> {
+        let from = self.resolve(from, false)?.into_owned();
+        let to = self.resolve(to, true)?.into_owned();
+        check_transfer(self, &from, &to, "copy")?;
+        let kind = if self.nodes.contains_key(&to) {
+            ChangeKind::Modified
+        } else {
+            ChangeKind::Created
+        };
+        for key in self.subtree_keys(&from) {
+            if let Some(node) = self.nodes.get(&key).cloned() {
+                let new_key = format!("{to}{}", &key[from.len()..]);
+                // Copies keep the mode and owner but are new files.
+                let now = now_secs();
+                if let Some(attrs) = self.attrs.get(&key).cloned() {
+                    self.attrs.insert(
+                        new_key.clone(),
+                        Attrs {
+                            created: now,
+                            modified: now,
+                            ..attrs
+                        },
+                    );
+                }
+                self.insert_node(new_key, node);
+            }
+        }
+        self.journal.record(&to, kind);
+        Ok(())
+    }
+
+    fn remove_dir_all(&mut self, path: &str) -> Result<()> {
+        let path = self.resolve(path, false)?.into_owned();
+        if path == "/" {
+            return Err(OasisError::Vfs("cannot remove root".to_string()));
+        }
+        if !self.nodes.contains_key(&path) {
+            return Err(OasisError::Vfs(format!("no such path: {path}")));
+        }
+        for key in self.subtree_keys(&path) {
+            self.remove_node(&key);
+            self.attrs.remove(&key);
+        }
+        self.journal.record(&path, ChangeKind::Removed);
+        Ok(())
+    }
+
+    fn metadata(&self, path: &str) -> Result<Metadata> {
+        let path = self.resolve(path, true)?;
+        let stat = self.stat(&path)?;
+        let mut meta = Metadata::new(stat.kind, stat.size);
+        if let Some(attrs) = self.attrs.get(path.as_ref()) {
+            meta.mode = attrs.mode;
+            meta.owner.clone_from(&attrs.owner);
+            meta.created = attrs.created;
+            meta.modified = attrs.modified;
+        }
+        Ok(meta)
+    }
+
+    fn set_metadata(&mut self, path: &str, meta: &Metadata) -> Result<()> {
+        let path = self.resolve(path, true)?.into_owned();
+        if !self.nodes.contains_key(&path) {
+            return Err(OasisError::Vfs(format!("no such path: {path}")));
+        }
+        self.attrs.insert(
+            path,
+            Attrs {
+                mode: meta.mode & 0o7777,
+                owner: meta.owner.clone(),
+                created: meta.created,
+                modified: meta.modified,
+            },
+        );
+        Ok(())
+    }
+
+    fn symlink(&mut self, target: &str, link: &str) -> Result<()> {
+        let link = self.resolve(link, false)?.into_owned();
+        if self.nodes.contains_key(&link) {
+            return Err(OasisError::Vfs(format!("file exists: {link}")));
+        }
+        let par = parent(&link);
+        if !matches!(self.nodes.get(par), Some(Node::Dir)) {
+            return Err(OasisError::Vfs(format!(
+                "parent directory does not exist: {par}"
+            )));
+        }
+        self.journal.record(&link, ChangeKind::Created);
+        self.attrs
+            .insert(link.clone(), Attrs::new(EntryKind::Symlink));
+        self.insert_node(link, Node::Link(target.to_string()));
+        Ok(())
+    }
+
+    fn readlink(&self, path: &str) -> Result<String> {
+        let path = self.resolve(path, false)?;
+        match self.nodes.get(path.as_ref()) {
+            Some(Node::Link(target)) => Ok(target.clone()),
+            Some(_) => Err(OasisError::Vfs(format!("not a symbolic link: {path}"))),
+            None => Err(OasisError::Vfs(format!("no such path: {path}"))),
+        }
+    }
+
+    fn lstat(&self, path: &str) -> Result<VfsMetadata> {
+        let path = self.resolve(path, false)?;
+        match self.nodes.get(path.as_ref()) {
+            Some(node) => Ok(VfsMetadata {
+                kind: node.kind(),
+                size: node.size(),
+            }),
+            None => Err(OasisError::Vfs(format!("no such path: {path}"))),
+        }
+    }
+
+    fn generation(&self) -> u64 {
+        self.journal.generation()
+    }
+
+    fn changes_since(&self, generation: u64) -> Changes {
+        self.journal.since(generation)
     }
 }
 
@@ -239,6 +558,104 @@ mod tests {
         assert!(vfs.exists("/"));
     }
 
+    /// `/data/file.txt`, `/data/sub/` and a few links to them.
+    fn linked_vfs() -> MemoryVfs {
+        let mut vfs = MemoryVfs::new();
+        vfs.mkdir("/data/sub").unwrap();
+        vfs.write("/data/file.txt", b"hello").unwrap();
+        vfs.mkdir("/links").unwrap();
+        vfs.symlink("/data/file.txt", "/links/abs").unwrap();
+        vfs.symlink("../data/sub", "/links/rel").unwrap();
+        vfs.symlink("abs", "/links/chain").unwrap();
+        vfs
+    }
+
+    #[test]
+    fn symlinks_are_followed() {
+        let mut vfs = linked_vfs();
+        assert_eq!(vfs.read("/links/abs").unwrap(), b"hello");
+        assert_eq!(vfs.read("/links/chain").unwrap(), b"hello");
+        assert_eq!(vfs.stat("/links/chain").unwrap().kind, EntryKind::File);
+        assert_eq!(vfs.stat("/links/rel").unwrap().kind, EntryKind::Directory);
+
+        // Links in the middle of a path, and writes through them.
+        vfs.write("/links/rel/new.txt", b"x").unwrap();
+        assert!(vfs.exists("/data/sub/new.txt"));
+        assert_eq!(vfs.readdir("/links/rel").unwrap()[0].name, "new.txt");
+        vfs.write("/links/chain", b"bye").unwrap();
+        assert_eq!(vfs.read("/data/file.txt").unwrap(), b"bye");
+        assert_eq!(vfs.lstat("/links/chain").unwrap().kind, EntryKind::Symlink);
+    }
+
+    #[test]
+    fn lstat_readlink_and_readdir_show_links() {
+        let vfs = linked_vfs();
+        let meta = vfs.lstat("/links/rel").unwrap();
+        assert_eq!(meta.kind, EntryKind::Symlink);
+        assert_eq!(meta.size, "../data/sub".len() as u64);
+        assert_eq!(vfs.readlink("/links/rel").unwrap(), "../data/sub");
+        assert!(vfs.readlink("/data/file.txt").is_err());
+        assert!(vfs.readlink("/nope").is_err());
+        let kinds: Vec<_> = vfs
+            .readdir("/links")
+            .unwrap()
+            .into_iter()
+            .map(|e| (e.name, e.kind))
+            .collect();
+        assert_eq!(kinds[0], ("abs".to_string(), EntryKind::Symlink));
+        assert_eq!(kinds.len(), 3);
+        assert_eq!(vfs.metadata("/links/abs").unwrap().size, 5);
+    }
+
+    #[test]
+    fn symlink_cycles_fail() {
+        let mut vfs = MemoryVfs::new();
+        vfs.symlink("/b", "/a").unwrap();
+        vfs.symlink("/a", "/b").unwrap();
+        vfs.symlink("self", "/self").unwrap();
+        for path in ["/a", "/self", "/a/x"] {
+            let err = vfs.read(path).unwrap_err();
+            assert!(err.to_string().contains("too many levels"), "{path}: {err}");
+        }
+        assert!(!vfs.exists("/a"));
+        assert!(vfs.write("/self", b"x").is_err());
+        // The links themselves are still there.
+        assert_eq!(vfs.readlink("/a").unwrap(), "/b");
+        vfs.remove("/a").unwrap();
+        assert!(vfs.lstat("/a").is_err());
+    }
+
+    #[test]
+    fn dangling_links_and_removal() {
+        let mut vfs = linked_vfs();
+        vfs.symlink("/data/later.txt", "/links/dangling").unwrap();
+        assert!(!vfs.exists("/links/dangling"));
+        assert!(vfs.lstat("/links/dangling").is_ok());
+        vfs.write("/links/dangling", b"now").unwrap();
+        assert_eq!(vfs.read("/data/later.txt").unwrap(), b"now");
+        assert!(vfs.symlink("/x", "/links/abs").is_err());
+        assert!(vfs.symlink("/x", "/missing/link").is_err());
+
+        // Removing a link leaves its target, even for directories.
+        vfs.remove("/links/abs").unwrap();
+        assert!(vfs.exists("/data/file.txt"));
+        vfs.remove_dir_all("/links/rel").unwrap();
+        assert!(vfs.exists("/data/sub"));
+
+        // Moves and copies take the link along, not the target.
+        vfs.rename("/links/chain", "/moved").unwrap();
+        assert_eq!(vfs.readlink("/moved").unwrap(), "abs");
+        vfs.copy("/links", "/links2").unwrap();
+        assert_eq!(
+            vfs.lstat("/links2/dangling").unwrap().kind,
+            EntryKind::Symlink
+        );
+        vfs.remove_dir_all("/links").unwrap();
+        vfs.remove_dir_all("/links2").unwrap();
+        vfs.remove("/moved").unwrap();
+        assert_eq!(vfs.links, 0);
+    }
+
     #[test]
     fn mkdir_and_readdir() {
         let mut vfs = MemoryVfs::new();
@@ -259,6 +676,23 @@ mod tests {
         assert_eq!(data, b"hello world");
     }
 
+    #[test]
+    fn read_range_slices_and_stops_at_eof() {
+        let mut vfs = MemoryVfs::new();
+        vfs.mkdir("/tmp").unwrap();
+        vfs.write("/tmp/test.txt", b"hello world").unwrap();
+        assert_eq!(vfs.read_range("/tmp/test.txt", 0, 5).unwrap(), b"hello");
+        assert_eq!(vfs.read_range("/tmp/test.txt", 6, 100).unwrap(), b"world");
+        assert!(vfs.read_range("/tmp/test.txt", 11, 4).unwrap().is_empty());
+        assert!(
+            vfs.read_range("/tmp/test.txt", u64::MAX, 4)
+                .unwrap()
+                .is_empty()
+        );
+        assert!(vfs.read_range("/tmp/missing", 0, 4).is_err());
+        assert!(vfs.read_range("/tmp", 0, 4).is_err());
+    }
+
     #[test]
     fn stat_file() {
         let mut vfs = MemoryVfs::new();
@@ -514,6 +948,144 @@ mod tests {
         assert_eq!(entries.len(), 200);
     }
 
+    fn tree() -> MemoryVfs {
+        let mut vfs = MemoryVfs::new();
+        vfs.mkdir("/src/sub/deep").unwrap();
+        vfs.write("/src/a.txt", b"a").unwrap();
+        vfs.write("/src/sub/b.txt", b"b").unwrap();
+        vfs.write("/src/sub/deep/c.txt", b"c").unwrap();
+        // Shares a prefix with /src but is not inside it.
+        vfs.write("/src2", b"other").unwrap();
+        vfs
+    }
+
+    #[test]
+    fn rename_directory_with_nested_children() {
+        let mut vfs = tree();
+        vfs.mkdir("/dst").unwrap();
+        vfs.rename("/src", "/dst/moved").unwrap();
+        assert!(!vfs.exists("/src"));
+        assert!(!vfs.exists("/src/sub/deep/c.txt"));
+        assert_eq!(vfs.read("/dst/moved/a.txt").unwrap(), b"a");
+        assert_eq!(vfs.read("/dst/moved/sub/b.txt").unwrap(), b"b");
+        assert_eq!(vfs.read("/dst/moved/sub/deep/c.txt").unwrap(), b"c");
+        assert_eq!(vfs.readdir("/dst/moved/sub").unwrap().len(), 2);
+        assert_eq!(vfs.read("/src2").unwrap(), b"other");
+    }
+
+    #[test]
+    fn rename_and_copy_checks() {
+        let mut vfs = tree();
+        let err = |r: Result<()>| r.unwrap_err().to_string();
+        assert!(err(vfs.rename("/src", "/src/sub/x")).contains("into itself"));
+        assert!(err(vfs.copy("/src", "/src/x")).contains("into itself"));
+        assert!(err(vfs.rename("/src", "/src2")).contains("destination exists"));
+        assert!(err(vfs.rename("/src/a.txt", "/src/sub")).contains("destination exists"));
+        assert!(err(vfs.rename("/nope", "/x")).contains("no such path"));
+        assert!(err(vfs.rename("/src2", "/missing/x")).contains("no such directory"));
+        // A file replaces a file.
+        vfs.rename("/src/a.txt", "/src2").unwrap();
+        assert_eq!(vfs.read("/src2").unwrap(), b"a");
+        // Failed operations changed nothing.
+        assert_eq!(vfs.read("/src/sub/deep/c.txt").unwrap(), b"c");
+    }
+
+    #[test]
+    fn copy_file_onto_itself_fails() {
+        let mut vfs = tree();
+        let e = vfs.copy("/src/a.txt", "/src//a.txt").unwrap_err();
+        assert!(e.to_string().contains("same file"));
+        assert_eq!(vfs.read("/src/a.txt").unwrap(), b"a");
+    }
+
+    #[test]
+    fn copy_directory_tree() {
+        let mut vfs = tree();
+        vfs.copy("/src", "/copy").unwrap();
+        assert_eq!(vfs.read("/copy/sub/deep/c.txt").unwrap(), b"c");
+        assert_eq!(vfs.read("/src/sub/deep/c.txt").unwrap(), b"c");
+        vfs.write("/copy/a.txt", b"changed").unwrap();
+        assert_eq!(vfs.read("/src/a.txt").unwrap(), b"a");
+    }
+
+    #[test]
+    fn remove_dir_all_removes_subtree() {
+        let mut vfs = tree();
+        vfs.remove_dir_all("/src").unwrap();
+        assert!(!vfs.exists("/src"));
+        assert!(!vfs.exists("/src/sub/deep"));
+        assert!(vfs.exists("/src2"));
+        vfs.remove_dir_all("/src2").unwrap();
+        assert!(vfs.remove_dir_all("/src2").is_err());
+        assert!(vfs.remove_dir_all("/").is_err());
+        assert_eq!(vfs.readdir("/").unwrap().len(), 0);
+    }
+
+    #[test]
+    fn metadata_round_trip_and_timestamps() {
+        let mut vfs = MemoryVfs::new();
+        vfs.mkdir("/d").unwrap();
+        vfs.write("/d/f", b"abc").unwrap();
+        let mut meta = vfs.metadata("/d/f").unwrap();
+        assert_eq!((meta.mode, meta.size), (0o644, 3));
+        assert_eq!(meta.owner, "oasis");
+        assert!(meta.created > 0 && meta.modified >= meta.created);
+        assert_eq!(vfs.metadata("/d").unwrap().mode, 0o755);
+
+        meta.mode = 0o400;
+        meta.owner = "root".to_string();
+        meta.modified = 0;
+        vfs.set_metadata("/d/f", &meta).unwrap();
+        let got = vfs.metadata("/d/f").unwrap();
+        assert_eq!(
+            (got.mode, got.owner.as_str(), got.modified),
+            (0o400, "root", 0)
+        );
+        assert!(!got.is_writable());
+
+        // Writing bumps the modification time and keeps the rest.
+        vfs.write("/d/f", b"abcd").unwrap();
+        let got = vfs.metadata("/d/f").unwrap();
+        assert!(got.modified > 0);
+        assert_eq!((got.mode, got.created), (0o400, meta.created));
+
+        // Metadata follows moves.
+        vfs.rename("/d", "/e").unwrap();
+        assert_eq!(vfs.metadata("/e/f").unwrap().owner, "root");
+        assert!(vfs.set_metadata("/d/f", &meta).is_err());
+    }
+
+    #[test]
+    fn journal_records_changes() {
+        let mut vfs = MemoryVfs::new();
+        assert_eq!(vfs.generation(), 0);
+        vfs.mkdir("/a/b").unwrap();
+        let seen = vfs.generation();
+        vfs.write("/a/b/f", b"1").unwrap();
+        vfs.write("/a/b/f", b"2").unwrap();
+        vfs.rename("/a/b", "/a/c").unwrap();
+        vfs.remove_dir_all("/a").unwrap();
+        // Failed operations change nothing.
+        assert!(vfs.remove("/a").is_err());
+        let changes = vfs.changes_since(seen);
+        assert_eq!(changes.generation, vfs.generation());
+        assert!(!changes.truncated);
+        let expected = [
+            ("/a/b/f", ChangeKind::Created),
+            ("/a/b/f", ChangeKind::Modified),
+            ("/a/b", ChangeKind::Removed),
+            ("/a/c", ChangeKind::Created),
+            ("/a", ChangeKind::Removed),
+        ];
+        let got: Vec<_> = changes
+            .changes
+            .iter()
+            .map(|(p, k)| (p.as_str(), *k))
+            .collect();
+        assert_eq!(got, expected);
+        assert_eq!(vfs.changes_since(0).changes.len(), 7);
+    }
+
     mod prop {
         use super::*;
         use proptest::prelude::*;
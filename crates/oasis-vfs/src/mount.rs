@@ -0,0 +1,494 @@
+//! Mount-point virtual file system.
+//!
+//! Overlays several backends under one namespace, e.g. the Memory Stick
+//! at `/ms0`, a host directory at `/host` and a MemoryVfs at `/`, so code
+//! handed a single `&dyn Vfs` can reach all of them.
+
+use std::cell::{Cell, RefCell};
+
+use oasis_types::error::{OasisError, Result};
+
+use crate::journal::ChangeJournal;
+use crate::memory::normalize;
+use crate::{
+    ChangeKind, Changes, EntryKind, Metadata, Vfs, VfsEntry, VfsMetadata, check_transfer,
+    copy_tree, is_within,
+};
+
+/// A filesystem mounted at a path prefix.
+struct Mount {
+    /// Normalized mount point.
+    prefix: String,
+    /// The mounted filesystem; its `/` is the mount point.
+    vfs: Box<dyn Vfs>,
+    /// Generation of `vfs` whose changes are already in our journal.
+    seen: Cell<u64>,
+}
+
+/// A VFS that routes each path to the filesystem mounted at its longest
+/// matching prefix.
+///
+/// Directories above mount points (such as `/` when only `/ms0` and
+/// `/host` are mounted) exist implicitly, and directory listings include
+/// the mount points below them. Moving between mounts is refused; copying
+/// between mounts goes through read and write.
+///
+/// The change journal collects the journals of the mounted filesystems
+/// when it is read, so changes made to them directly are seen too. Changes
+/// are in order per mount, but not across mounts.
+pub struct MountVfs {
+    /// Mounted filesystems, longest prefix first.
+    mounts: Vec<Mount>,
+    journal: RefCell<ChangeJournal>,
+}
+
+impl MountVfs {
+    /// Create a VFS with nothing mounted.
+    pub fn new() -> Self {
+        Self {
+            mounts: Vec::new(),
+            journal: RefCell::default(),
+        }
+    }
+
+    /// Mount `vfs` at `prefix`. Fails if something is already mounted there.
+    pub fn mount(&mut self, prefix: &str, vfs: Box<dyn Vfs>) -> Result<()> {
+        let prefix = normalize(prefix).into_owned();
+        if self.mounts.iter().any(|m| m.prefix == prefix) {
+            return Err(OasisError::Vfs(format!("already mounted: {prefix}")));
+        }
+        let at = self
+            .mounts
+            .iter()
+            .position(|m| m.prefix.len() < prefix.len())
+            .unwrap_or(self.mounts.len());
+        self.pull_changes();
+        self.journal
+            .borrow_mut()
+            .record(&prefix, ChangeKind::Created);
+        let seen = Cell::new(vfs.generation());
+        self.mounts.insert(at, Mount { prefix, vfs, seen });
+        Ok(())
+    }
+
+    /// Unmount the filesystem at `prefix` and hand it back.
+    pub fn unmount(&mut self, prefix: &str) -> Result<Box<dyn Vfs>> {
+        let prefix = normalize(prefix);
+        let index = self
+            .mounts
+            .iter()
+            .position(|m| m.prefix == prefix)
+            .ok_or_else(|| OasisError::Vfs(format!("not mounted: {prefix}")))?;
+        self.pull_changes();
+        self.journal
+            .borrow_mut()
+            .record(&prefix, ChangeKind::Removed);
+        Ok(self.mounts.remove(index).vfs)
+    }
+
+    /// Copy new changes from the mounted filesystems into the journal.
+    /// Changes hidden by a longer mount prefix are left out.
+    fn pull_changes(&self) {
+        let mut journal = self.journal.borrow_mut();
+        for (index, mount) in self.mounts.iter().enumerate() {
+            let changes = mount.vfs.changes_since(mount.seen.get());
+            mount.seen.set(changes.generation);
+            if changes.truncated {
+                journal.truncate();
+            }
+            for (inner, kind) in changes.changes {
+                let path = match (mount.prefix.as_str(), inner.as_str()) {
+                    ("/", _) => inner,
+                    (prefix, "/") => prefix.to_string(),
+                    (prefix, _) => format!("{prefix}{inner}"),
+                };
+                if self.route(&path).is_some_and(|(i, _)| i == index) {
+                    journal.record(&path, kind);
+                }
+            }
+        }
+    }
+
+    /// Index of the mount holding `path` (normalized) and the path inside it.
+    fn route(&self, path: &str) -> Option<(usize, String)> {
+        let index = self
+            .mounts
+            .iter()
+            .position(|m| is_within(path, &m.prefix))?;
+        let prefix = &self.mounts[index].prefix;
+        let inner = match prefix.as_str() {
+            "/" => path,
+            _ => &path[prefix.len()..],
+        };
+        let inner = if inner.is_empty() { "/" } else { inner };
+        Some((index, inner.to_string()))
+    }
+
+    fn route_mut(&mut self, path: &str) -> Result<(&mut dyn Vfs, String)> {
+        let (index, inner) = self
+            .route(path)
+            .ok_or_else(|| OasisError::Vfs(format!("no filesystem mounted at {path}")))?;
+        Ok((self.mounts[index].vfs.as_mut(), inner))
+    }
+
+    /// Whether `path` (normalized) is a mount point or a directory above one.
+    fn is_mount_dir(&self, path: &str) -> bool {
+        self.mounts.iter().any(|m| is_within(&m.prefix, path))
+    }
+
+    /// Names of the mount points (or directories leading to them) directly
+    /// inside `dir` (normalized).
+    fn mount_children(&self, dir: &str) -> Vec<String> {
+        let mut names: Vec<String> = Vec::new();
+        for m in &self.mounts {
+            if m.prefix == dir || !is_within(&m.prefix, dir) {
+                continue;
+            }
+            let rest = m.prefix[dir.len()..].trim_start_matches('/');
+            let name = rest.split('/').next().unwrap_or(rest);
+            if !names.iter().any(|n| n == name) {
+                names.push(name.to_string());
+            }
+        }
+        names
+    }
+
+    /// Refuse to change `path` when it is a mount point or above one.
+    fn check_not_mount_dir(&self, path: &str, verb: &str) -> Result<()> {
+        if self.is_mount_dir(path) {
+            return Err(OasisError::Vfs(format!(
+                "cannot {verb} mount point: {path}"
+            )));
+        }
+        Ok(())
+    }
+}
+
+impl Default for MountVfs {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Vfs for MountVfs {
+    fn readdir(&self, path: &str) -> Result<Vec<VfsEntry>> {
+        let path = normalize(path);
+        let children = self.mount_children(&path);
+        let mut entries = match self.route(&path) {
+            Some((index, inner)) => match self.mounts[index].vfs.readdir(&inner) {
+                Ok(entries) => entries,
+                Err(_) if !children.is_empty() => Vec::new(),
+                Err(e) => return Err(e),
+            },
+            None if !children.is_empty() => Vec::new(),
+            None => return Err(OasisError::Vfs(format!("no such directory: {path}"))),
+        };
+        if !children.is_empty() {
+            entries.retain(|e| !children.contains(&e.name));
+            entries.extend(children.into_iter().map(|name| VfsEntry {
+                name,
+                kind: EntryKind::Directory,
+                size: 0,
+            }));
+            entries.sort_by(|a, b| a.name.cmp(&b.name));
+        }
+        Ok(entries)
+    }
+
+    fn read(&self, path: &str) -> Result<Vec<u8>> {
+        let path = normalize(path);
+        match self.route(&path) {
+            Some((index, inner)) => self.mounts[index].vfs.read(&inner),
+            None if self.is_mount_dir(&path) => {
+                Err(OasisError::Vfs(format!("is a directory: {path}")))
+            },
+            None => Err(OasisError::Vfs(format!("no such file: {path}"))),
+        }
+    }
+
+    fn read_range(&self, path: &str, offset: u64, len: usize) -> Result<Vec<u8>> {
+        let path = normalize(path);
+        match self.route(&path) {
+            Some((index, inner)) => self.mounts[index].vfs.read_range(&inner, offset, len),
+            None if self.is_mount_dir(&path) => {
+                Err(OasisError::Vfs(format!("is a directory: {path}")))
+            },
+            None => Err(OasisError::Vfs(format!("no such file: {path}"))),
+        }
+    }
+
+    fn write(&mut self, path: &str, data: &[u8]) -> Result<()> {
+        let path = normalize(path);
+        self.check_not_mount_dir(&path, "write")?;
+        let (vfs, inner) = self.route_mut(&path)?;
+        vfs.write(&inner, data)
+    }
+
+    fn stat(&self, path: &str) -> Result<VfsMetadata> {
+        let path = normalize(path);
+        if self.is_mount_dir(&path) {
+            return Ok(VfsMetadata {
+                kind: EntryKind::Directory,
+                size: 0,
+            });
+        }
+        match self.route(&path) {
+            Some((index, inner)) => self.mounts[index].vfs.stat(&inner),
+            None => Err(OasisError::Vfs(format!("no such path: {path}"))),
+        }
+    }
+
+    fn mkdir(&mut self, path: &str) -> Result<()> {
+        let path = normalize(path);
+        if self.is_mount_dir(&path) {
+            return Ok(());
+        }
+        let (vfs, inner) = self.route_mut(&path)?;
+        vfs.mkdir(&inner)
+    }
+
+    fn remove(&mut self, path: &str) -> Result<()> {
+        let path = normalize(path);
+        self.check_not_mount_dir(&path, "remove")?;
+        let (vfs, inner) = self.route_mut(&path)?;
+        vfs.remove(&inner)
+    }
+
+    fn exists(&self, path: &str) -> bool {
+        let path = normalize(path);
+        self.is_mount_dir(&path)
+            || self
+                .route(&path)
+                .is_some_and(|(index, inner)| self.mounts[index].vfs.exists(&inner))
+    }
+
+    fn rename(&mut self, from: &str, to: &str) -> Result<()> {
+        let (from, to) = (normalize(from), normalize(to));
+        self.check_not_mount_dir(&from, "move")?;
+        let (src, inner_from) = self
+            .route(&from)
+            .ok_or_else(|| OasisError::Vfs(format!("no such path: {from}")))?;
+        match self.route(&to) {
+            Some((dst, inner_to)) if dst == src && !self.is_mount_dir(&to) => {
+                self.mounts[src].vfs.rename(&inner_from, &inner_to)
+            },
+            _ => Err(OasisError::Vfs(format!(
+                "cannot move {from} to {to}: not on the same mount"
+            ))),
+        }
+    }
+
+    fn copy(&mut self, from: &str, to: &str) -> Result<()> {
+        let (from, to) = (normalize(from), normalize(to));
+        if !self.is_mount_dir(&from)
+            && !self.is_mount_dir(&to)
+            && let (Some((src, inner_from)), Some((dst, inner_to))) =
+                (self.route(&from), self.route(&to))
+            && src == dst
+        {
+            return self.mounts[src].vfs.copy(&inner_from, &inner_to);
+        }
+        let kind = check_transfer(self, &from, &to, "copy")?;
+        copy_tree(self, &from, &to, kind)
+    }
+
+    fn remove_dir_all(&mut self, path: &str) -> Result<()> {
+        let path = normalize(path);
+        self.check_not_mount_dir(&path, "remove")?;
+        let (vfs, inner) = self.route_mut(&path)?;
+        vfs.remove_dir_all(&inner)
+    }
+
+    fn metadata(&self, path: &str) -> Result<Metadata> {
+        let path = normalize(path);
+        if self.is_mount_dir(&path) {
+            return Ok(Metadata::new(EntryKind::Directory, 0));
+        }
+        match self.route(&path) {
+            Some((index, inner)) => self.mounts[index].vfs.metadata(&inner),
+            None => Err(OasisError::Vfs(format!("no such path: {path}"))),
+        }
+    }
+
+    fn set_metadata(&mut self, path: &str, meta: &Metadata) -> Result<()> {
+        let path = normalize(path);
+        self.check_not_mount_dir(&path, "change")?;
+        let (vfs, inner) = self.route_mut(&path)?;
+        vfs.set_metadata(&inner, meta)
+    }
+
+    /// The mounted filesystem holding the link resolves its target, so
+    /// an absolute target is taken from that filesystem's own root.
+    fn symlink(&mut self, target: &str, link: &str) -> Result<()> {
+        let link = normalize(link);
+        self.check_not_mount_dir(&link, "replace")?;
+        let (vfs, inner) = self.route_mut(&link)?;
+        vfs.symlink(target, &inner)
+    }
+
+    fn readlink(&self, path: &str) -> Result<String> {
+        let path = normalize(path);
+        if self.is_mount_dir(&path) {
+            return Err(OasisError::Vfs(format!("not a symbolic link: {path}")));
+        }
+        match self.route(&path) {
+            Some((index, inner)) => self.mounts[index].vfs.readlink(&inner),
+            None => Err(OasisError::Vfs(format!("no such path: {path}"))),
+        }
+    }
+
+    fn lstat(&self, path: &str) -> Result<VfsMetadata> {
+        let path = normalize(path);
+        if self.is_mount_dir(&path) {
+            return self.stat(&path);
+        }
+        match self.route(&path) {
+            Some((index, inner)) => self.mounts[index].vfs.lstat(&inner),
+            None => Err(OasisError::Vfs(format!("no such path: {path}"))),
+        }
+    }
+
+    fn generation(&self) -> u64 {
+        self.pull_changes();
+        self.journal.borrow().generation()
+    }
+
+    fn changes_since(&self, generation: u64) -> Changes {
+        self.pull_changes();
+        self.journal.borrow().since(generation)
+    }
+
+    fn mounts(&self) -> Vec<String> {
+        let mut prefixes: Vec<String> = self.mounts.iter().map(|m| m.prefix.clone()).collect();
+        prefixes.sort();
+        prefixes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::MemoryVfs;
+
+    fn names(vfs: &dyn Vfs, path: &str) -> Vec<String> {
+        vfs.readdir(path)
+            .unwrap()
+            .into_iter()
+            .map(|e| e.name)
+            .collect()
+    }
+
+    fn sample() -> MountVfs {
+        let mut root = MemoryVfs::new();
+        root.mkdir("/home").unwrap();
+        let mut ms0 = MemoryVfs::new();
+        ms0.write("/save.dat", b"ms").unwrap();
+        let mut vfs = MountVfs::new();
+        vfs.mount("/", Box::new(root)).unwrap();
+        vfs.mount("/ms0", Box::new(ms0)).unwrap();
+        vfs.mount("/mnt/usb", Box::new(MemoryVfs::new())).unwrap();
+        vfs
+    }
+
+    #[test]
+    fn root_lists_mount_points() {
+        let vfs = sample();
+        assert_eq!(names(&vfs, "/"), ["home", "mnt", "ms0"]);
+        assert_eq!(names(&vfs, "/mnt"), ["usb"]);
+        assert_eq!(names(&vfs, "/ms0"), ["save.dat"]);
+        assert_eq!(vfs.stat("/mnt").unwrap().kind, EntryKind::Directory);
+        assert!(vfs.exists("/mnt/usb"));
+        assert_eq!(vfs.mounts(), ["/", "/mnt/usb", "/ms0"]);
+
+        let mut bare = MountVfs::new();
+        bare.mount("/host", Box::new(MemoryVfs::new())).unwrap();
+        assert_eq!(names(&bare, "/"), ["host"]);
+        assert!(bare.write("/elsewhere.txt", b"x").is_err());
+    }
+
+    #[test]
+    fn writes_land_in_the_longest_matching_mount() {
+        let mut vfs = sample();
+        vfs.write("/ms0/new.txt", b"stick").unwrap();
+        vfs.write("/home/notes.txt", b"root").unwrap();
+        vfs.mkdir("/mnt/usb/photos").unwrap();
+        assert_eq!(vfs.read("/ms0/new.txt").unwrap(), b"stick");
+
+        let usb = vfs.unmount("/mnt/usb").unwrap();
+        assert!(usb.exists("/photos"));
+        let ms0 = vfs.unmount("/ms0").unwrap();
+        assert_eq!(ms0.read("/new.txt").unwrap(), b"stick");
+        assert!(!ms0.exists("/home"));
+        let root = vfs.unmount("/").unwrap();
+        assert_eq!(root.read("/home/notes.txt").unwrap(), b"root");
+        assert!(!root.exists("/ms0/new.txt"));
+        assert!(vfs.unmount("/").is_err());
+    }
+
+    #[test]
+    fn mount_errors_and_mount_point_protection() {
+        let mut vfs = sample();
+        assert!(vfs.mount("/ms0/", Box::new(MemoryVfs::new())).is_err());
+        assert!(vfs.remove("/ms0").is_err());
+        assert!(vfs.remove_dir_all("/mnt").is_err());
+        assert!(vfs.write("/mnt", b"x").is_err());
+        assert!(vfs.mkdir("/mnt").is_ok());
+    }
+
+    #[test]
+    fn rename_stays_within_a_mount() {
+        let mut vfs = sample();
+        vfs.rename("/ms0/save.dat", "/ms0/backup.dat").unwrap();
+        assert!(vfs.exists("/ms0/backup.dat"));
+        let err = vfs
+            .rename("/ms0/backup.dat", "/home/backup.dat")
+            .unwrap_err();
+        assert!(err.to_string().contains("same mount"));
+        assert!(vfs.rename("/ms0", "/home/ms0").is_err());
+        // Copying across mounts works through read and write.
+        vfs.copy("/ms0/backup.dat", "/home/backup.dat").unwrap();
+        assert_eq!(vfs.read("/home/backup.dat").unwrap(), b"ms");
+        assert!(vfs.exists("/ms0/backup.dat"));
+    }
+
+    #[test]
+    fn journal_merges_mounted_journals() {
+        let mut vfs = sample();
+        let seen = vfs.generation();
+        vfs.write("/ms0/new.txt", b"x").unwrap();
+        vfs.write("/home/a.txt", b"y").unwrap();
+        vfs.unmount("/mnt/usb").unwrap();
+        let changes = vfs.changes_since(seen);
+        assert!(!changes.truncated);
+        let mut got: Vec<_> = changes
+            .changes
+            .iter()
+            .map(|(p, k)| (p.as_str(), *k))
+            .collect();
+        got.sort_by_key(|(p, _)| *p);
+        assert_eq!(
+            got,
+            [
+                ("/home/a.txt", ChangeKind::Created),
+                ("/mnt/usb", ChangeKind::Removed),
+                ("/ms0/new.txt", ChangeKind::Created),
+            ]
+        );
+        assert!(vfs.changes_since(changes.generation).changes.is_empty());
+    }
+
+    #[test]
+    fn symlinks_resolve_inside_their_mount() {
+        let mut vfs = sample();
+        let mut mem = MemoryVfs::new();
+        mem.write("/f", b"inner").unwrap();
+        vfs.mount("/mem", Box::new(mem)).unwrap();
+        vfs.symlink("/f", "/mem/link").unwrap();
+        assert_eq!(vfs.read("/mem/link").unwrap(), b"inner");
+        assert_eq!(vfs.readlink("/mem/link").unwrap(), "/f");
+        assert_eq!(vfs.lstat("/mem/link").unwrap().kind, EntryKind::Symlink);
+        assert_eq!(vfs.lstat("/mem").unwrap().kind, EntryKind::Directory);
+        assert!(vfs.readlink("/mem").is_err());
+        assert!(vfs.symlink("/f", "/mem").is_err());
+    }
+}
@@ -0,0 +1,470 @@
+//! Copy-on-write overlay virtual file system.
+//!
+//! Stacks a writable upper filesystem over a read-only lower one, e.g. a
+//! user directory over the skins and sites embedded in the binary, so
+//! edits never touch the shipped content.
+
+use std::collections::BTreeMap;
+
+use oasis_types::error::{OasisError, Result};
+
+use crate::journal::ChangeJournal;
+use crate::memory::normalize;
+use crate::{ChangeKind, Changes, EntryKind, Metadata, Vfs, VfsEntry, VfsMetadata, join};
+
+/// Prefix of the marker files that hide lower entries.
+pub const WHITEOUT_PREFIX: &str = ".wh.";
+
+/// Marker file that hides every lower entry inside its directory.
+const OPAQUE_MARKER: &str = ".wh..wh..opq";
+
+/// A VFS that reads through a writable upper layer to a read-only lower
+/// one.
+///
+/// Lookups try the upper layer first, then the lower one. Writes, new
+/// directories and metadata changes go to the upper layer, copying up
+/// the directories above them (and, for metadata, the entry itself).
+/// Directory listings are the union of both layers.
+///
+/// Removing an entry that exists in the lower layer leaves a whiteout
+/// marker, an empty `.wh.<name>` file next to where it was in the upper
+/// layer, so the removal survives with the upper layer (say, on disk).
+/// A directory created over a removed one gets a `.wh..wh..opq` marker
+/// so the old lower contents stay hidden. Markers never show up in
+/// listings, and names starting with `.wh.` cannot be created.
+///
+/// Links are resolved by the layer holding them.
+pub struct OverlayVfs {
+    /// Shipped content; never written to.
+    lower: Box<dyn Vfs>,
+    /// User changes and whiteout markers.
+    upper: Box<dyn Vfs>,
+    journal: ChangeJournal,
+}
+
+impl OverlayVfs {
+    /// Stack `upper` over `lower`.
+    pub fn new(lower: Box<dyn Vfs>, upper: Box<dyn Vfs>) -> Self {
+        Self {
+            lower,
+            upper,
+            journal: ChangeJournal::new(),
+        }
+    }
+
+    /// Take the overlay apart into its lower and upper layers.
+    pub fn into_layers(self) -> (Box<dyn Vfs>, Box<dyn Vfs>) {
+        (self.lower, self.upper)
+    }
+
+    /// Whether the lower entry at `path` (normalized) is hidden: removed,
+    /// inside an opaque directory, or below something in the upper layer
+    /// that is not a directory.
+    fn lower_hidden(&self, path: &str) -> bool {
+        let mut dir = "/".to_string();
+        for name in path.split('/').filter(|c| !c.is_empty()) {
+            if self.upper.lstat(&join(&dir, OPAQUE_MARKER)).is_ok()
+                || self.upper.lstat(&whiteout(&dir, name)).is_ok()
+            {
+                return true;
+            }
+            dir = join(&dir, name);
+            if dir.len() < path.len()
+                && self
+                    .upper
+                    .lstat(&dir)
+                    .is_ok_and(|meta| meta.kind != EntryKind::Directory)
+            {
+                return true;
+            }
+        }
+        false
+    }
+
+    /// The layer whose entry is seen at `path` (normalized), if any.
+    fn layer(&self, path: &str) -> Option<&dyn Vfs> {
+        if self.upper.lstat(path).is_ok() {
+            Some(self.upper.as_ref())
+        } else if self.lower.lstat(path).is_ok() && !self.lower_hidden(path) {
+            Some(self.lower.as_ref())
+        } else {
+            None
+        }
+    }
+
+    /// Whether `path` (normalized) exists only in the lower layer.
+    fn in_lower_only(&self, path: &str) -> bool {
+        self.upper.lstat(path).is_err()
+            && self.lower.lstat(path).is_ok()
+            && !self.lower_hidden(path)
+    }
+
+    /// Make sure `dir` (normalized) and the directories above it exist in
+    /// the upper layer, copying their metadata up from the lower one.
+    fn copy_up_dir(&mut self, dir: &str) -> Result<()> {
+        let mut path = "/".to_string();
+        for name in dir.split('/').filter(|c| !c.is_empty()) {
+            let parent = path;
+            path = join(&parent, name);
+            if self.upper.lstat(&path).is_ok() {
+                continue;
+            }
+            let meta = if self.in_lower_only(&path) {
+                Some(self.lower.metadata(&path)?)
+            } else {
+                None
+            };
+            let marker = whiteout(&parent, name);
+            let removed = self.upper.lstat(&marker).is_ok();
+            self.upper.mkdir(&path)?;
+            if let Some(meta) = meta {
+                // Best effort: the upper layer may not keep modes.
+                let _ = self.upper.set_metadata(&path, &meta);
+            }
+            if removed {
+                self.upper.remove(&marker)?;
+                self.upper.write(&join(&path, OPAQUE_MARKER), b"")?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Prepare to create `path` (normalized) in the upper layer: check the
+    /// name, copy up its parent directory and drop its whiteout marker.
+    fn prepare_create(&mut self, path: &str) -> Result<()> {
+        let (parent, name) = split(path);
+        if name.starts_with(WHITEOUT_PREFIX) {
+            return Err(OasisError::Vfs(format!("reserved name: {path}")));
+        }
+        if !self
+            .stat(parent)
+            .is_ok_and(|meta| meta.kind == EntryKind::Directory)
+        {
+            return Err(OasisError::Vfs(format!(
+                "parent directory does not exist: {parent}"
+            )));
+        }
+        self.copy_up_dir(parent)?;
+        let marker = whiteout(parent, name);
+        if self.upper.lstat(&marker).is_ok() {
+            self.upper.remove(&marker)?;
+        }
+        Ok(())
+    }
+}
+
+impl Vfs for OverlayVfs {
+    fn readdir(&self, path: &str) -> Result<Vec<VfsEntry>> {
+        let path = normalize(path);
+        if self.stat(&path)?.kind != EntryKind::Directory {
+            return Err(OasisError::Vfs(format!("not a directory: {path}")));
+        }
+        let mut entries: BTreeMap<String, VfsEntry> = BTreeMap::new();
+        let upper = self.upper.readdir(&path).unwrap_or_default();
+        let hidden = |name: &str| {
+            upper
+                .iter()
+                .any(|e| e.name == OPAQUE_MARKER || e.name == format!("{WHITEOUT_PREFIX}{name}"))
+        };
+        if !self.lower_hidden(&path) {
+            for entry in self.lower.readdir(&path).unwrap_or_default() {
+                if !hidden(&entry.name) {
+                    entries.insert(entry.name.clone(), entry);
+                }
+            }
+        }
+        for entry in upper {
+            if !entry.name.starts_with(WHITEOUT_PREFIX) {
+                entries.insert(entry.name.clone(), entry);
+            }
+        }
+        Ok(entries.into_values().collect())
+    }
+
+    fn read(&self, path: &str) -> Result<Vec<u8>> {
+        let path = normalize(path);
+        match self.layer(&path) {
+            Some(layer) => layer.read(&path),
+            None => Err(OasisError::Vfs(format!("no such file: {path}"))),
+        }
+    }
+
+    fn read_range(&self, path: &str, offset: u64, len: usize) -> Result<Vec<u8>> {
+        let path = normalize(path);
+        match self.layer(&path) {
+            Some(layer) => layer.read_range(&path, offset, len),
+            None => Err(OasisError::Vfs(format!("no such file: {path}"))),
+        }
+    }
+
+    fn write(&mut self, path: &str, data: &[u8]) -> Result<()> {
+        let path = normalize(path).into_owned();
+        if self
+            .stat(&path)
+            .is_ok_and(|m| m.kind == EntryKind::Directory)
+        {
+            return Err(OasisError::Vfs(format!("is a directory: {path}")));
+        }
+        let kind = match self.layer(&path) {
+            Some(_) => ChangeKind::Modified,
+            None => ChangeKind::Created,
+        };
+        self.prepare_create(&path)?;
+        self.upper.write(&path, data)?;
+        self.journal.record(&path, kind);
+        Ok(())
+    }
+
+    fn stat(&self, path: &str) -> Result<VfsMetadata> {
+        let path = normalize(path);
+        match self.layer(&path) {
+            Some(layer) => layer.stat(&path),
+            None => Err(OasisError::Vfs(format!("no such path: {path}"))),
+        }
+    }
+
+    fn mkdir(&mut self, path: &str) -> Result<()> {
+        let path = normalize(path).into_owned();
+        if let Ok(meta) = self.stat(&path) {
+            return match meta.kind {
+                EntryKind::Directory => Ok(()),
+                _ => Err(OasisError::Vfs(format!("not a directory: {path}"))),
+            };
+        }
+        let (parent, name) = split(&path);
+        if name.starts_with(WHITEOUT_PREFIX) {
+            return Err(OasisError::Vfs(format!("reserved name: {path}")));
+        }
+        if parent != path {
+            self.mkdir(parent)?;
+        }
+        self.copy_up_dir(&path)?;
+        self.journal.record(&path, ChangeKind::Created);
+        Ok(())
+    }
+
+    fn remove(&mut self, path: &str) -> Result<()> {
+        let path = normalize(path).into_owned();
+        if path == "/" {
+            return Err(OasisError::Vfs("cannot remove root".to_string()));
+        }
+        let Some(layer) = self.layer(&path) else {
+            return Err(OasisError::Vfs(format!("no such path: {path}")));
+        };
+        if layer.lstat(&path)?.kind == EntryKind::Directory && !self.readdir(&path)?.is_empty() {
+            return Err(OasisError::Vfs(format!("directory not empty: {path}")));
+        }
+        let in_lower = self.lower.lstat(&path).is_ok() && !self.lower_hidden(&path);
+        if self.upper.lstat(&path).is_ok() {
+            if self.upper.lstat(&path)?.kind == EntryKind::Directory {
+                // Only markers are left inside.
+                for entry in self.upper.readdir(&path)? {
+                    self.upper.remove(&join(&path, &entry.name))?;
+                }
+            }
+            self.upper.remove(&path)?;
+        }
+        if in_lower {
+            let (parent, name) = split(&path);
+            self.copy_up_dir(parent)?;
+            self.upper.write(&whiteout(parent, name), b"")?;
+        }
+        self.journal.record(&path, ChangeKind::Removed);
+        Ok(())
+    }
+
+    fn exists(&self, path: &str) -> bool {
+        let path = normalize(path);
+        self.layer(&path).is_some_and(|layer| layer.exists(&path))
+    }
+
+    fn metadata(&self, path: &str) -> Result<Metadata> {
+        let path = normalize(path);
+        match self.layer(&path) {
+            Some(layer) => layer.metadata(&path),
+            None => Err(OasisError::Vfs(format!("no such path: {path}"))),
+        }
+    }
+
+    /// Copies a lower entry up first; a file is copied whole.
+    fn set_metadata(&mut self, path: &str, meta: &Metadata) -> Result<()> {
+        let path = normalize(path).into_owned();
+        if self.in_lower_only(&path) {
+            match self.lower.lstat(&path)?.kind {
+                EntryKind::Directory => self.copy_up_dir(&path)?,
+                EntryKind::File => {
+                    let data = self.lower.read(&path)?;
+                    self.copy_up_dir(split(&path).0)?;
+                    self.upper.write(&path, &data)?;
+                },
+                EntryKind::Symlink => {
+                    let target = self.lower.readlink(&path)?;
+                    self.copy_up_dir(split(&path).0)?;
+                    self.upper.symlink(&target, &path)?;
+                },
+            }
+        }
+        self.upper.set_metadata(&path, meta)
+    }
+
+    fn symlink(&mut self, target: &str, link: &str) -> Result<()> {
+        let link = normalize(link).into_owned();
+        if self.layer(&link).is_some() {
+            return Err(OasisError::Vfs(format!("file exists: {link}")));
+        }
+        self.prepare_create(&link)?;
+        self.upper.symlink(target, &link)?;
+        self.journal.record(&link, ChangeKind::Created);
+        Ok(())
+    }
+
+    fn readlink(&self, path: &str) -> Result<String> {
+        let path = normalize(path);
+        match self.layer(&path) {
+            Some(layer) => layer.readlink(&path),
+            None => Err(OasisError::Vfs(format!("no such path: {path}"))),
+        }
+    }
+
+    fn lstat(&self, path: &str) -> Result<VfsMetadata> {
+        let path = normalize(path);
+        match self.layer(&path) {
+            Some(layer) => layer.lstat(&path),
+            None => Err(OasisError::Vfs(format!("no such path: {path}"))),
+        }
+    }
+
+    fn generation(&self) -> u64 {
+        self.journal.generation()
+    }
+
+    fn changes_since(&self, generation: u64) -> Changes {
+        self.journal.since(generation)
+    }
+}
+
+/// Parent directory and name of a normalized path.
+fn split(path: &str) -> (&str, &str) {
+    match path.rfind('/') {
+        Some(0) => ("/", &path[1..]),
+        Some(i) => (&path[..i], &path[i + 1..]),
+        None => ("/", path),
+    }
+}
+
+/// Path of the whiteout marker for `name` inside `dir`.
+fn whiteout(dir: &str, name: &str) -> String {
+    join(dir, &format!("{WHITEOUT_PREFIX}{name}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::MemoryVfs;
+
+    fn names(vfs: &dyn Vfs, path: &str) -> Vec<String> {
+        vfs.readdir(path)
+            .unwrap()
+            .into_iter()
+            .map(|e| e.name)
+            .collect()
+    }
+
+    fn sample() -> OverlayVfs {
+        let mut base = MemoryVfs::new();
+        base.mkdir("/skins/classic").unwrap();
+        base.write("/skins/classic/skin.toml", b"name = \"classic\"")
+            .unwrap();
+        base.write("/skins/classic/theme.toml", b"base").unwrap();
+        base.mkdir("/sites/home").unwrap();
+        base.write("/sites/home/index.html", b"<p>home</p>")
+            .unwrap();
+        OverlayVfs::new(Box::new(base), Box::new(MemoryVfs::new()))
+    }
+
+    #[test]
+    fn reads_fall_through_and_writes_stay_on_top() {
+        let mut vfs = sample();
+        assert_eq!(vfs.read("/skins/classic/theme.toml").unwrap(), b"base");
+        vfs.write("/skins/classic/theme.toml", b"mine").unwrap();
+        vfs.write("/skins/classic/extra.toml", b"new").unwrap();
+        assert_eq!(vfs.read("/skins/classic/theme.toml").unwrap(), b"mine");
+        assert_eq!(
+            names(&vfs, "/skins/classic"),
+            ["extra.toml", "skin.toml", "theme.toml"]
+        );
+        assert_eq!(names(&vfs, "/"), ["sites", "skins"]);
+
+        let (lower, upper) = vfs.into_layers();
+        assert_eq!(lower.read("/skins/classic/theme.toml").unwrap(), b"base");
+        assert!(!lower.exists("/skins/classic/extra.toml"));
+        assert_eq!(upper.read("/skins/classic/theme.toml").unwrap(), b"mine");
+        assert!(!upper.exists("/sites"));
+    }
+
+    #[test]
+    fn removal_leaves_whiteouts_that_hide_the_base() {
+        let mut vfs = sample();
+        vfs.remove("/skins/classic/theme.toml").unwrap();
+        assert!(!vfs.exists("/skins/classic/theme.toml"));
+        assert!(vfs.read("/skins/classic/theme.toml").is_err());
+        assert_eq!(names(&vfs, "/skins/classic"), ["skin.toml"]);
+        assert!(vfs.remove("/skins/classic/theme.toml").is_err());
+
+        // Writing it again brings it back with the new contents.
+        vfs.write("/skins/classic/theme.toml", b"again").unwrap();
+        assert_eq!(vfs.read("/skins/classic/theme.toml").unwrap(), b"again");
+        assert!(vfs.write("/skins/.wh.classic", b"").is_err());
+
+        vfs.remove_dir_all("/sites").unwrap();
+        assert!(!vfs.exists("/sites/home/index.html"));
+        assert_eq!(names(&vfs, "/"), ["skins"]);
+        let (lower, upper) = vfs.into_layers();
+        assert!(lower.exists("/sites/home/index.html"));
+        assert!(upper.exists("/.wh.sites"));
+    }
+
+    #[test]
+    fn recreated_directories_are_opaque() {
+        let mut vfs = sample();
+        vfs.remove_dir_all("/sites/home").unwrap();
+        vfs.mkdir("/sites/home").unwrap();
+        assert!(names(&vfs, "/sites/home").is_empty());
+        assert!(!vfs.exists("/sites/home/index.html"));
+        vfs.write("/sites/home/index.html", b"<p>mine</p>").unwrap();
+        assert_eq!(names(&vfs, "/sites/home"), ["index.html"]);
+        vfs.remove("/sites/home/index.html").unwrap();
+        vfs.remove("/sites/home").unwrap();
+        assert_eq!(names(&vfs, "/sites"), Vec::<String>::new());
+    }
+
+    #[test]
+    fn changes_are_journaled_and_metadata_copies_up() {
+        let mut vfs = sample();
+        let seen = vfs.generation();
+        vfs.write("/skins/classic/theme.toml", b"mine").unwrap();
+        vfs.remove("/sites/home/index.html").unwrap();
+        assert_eq!(
+            vfs.changes_since(seen).changes,
+            [
+                (
+                    "/skins/classic/theme.toml".to_string(),
+                    ChangeKind::Modified
+                ),
+                ("/sites/home/index.html".to_string(), ChangeKind::Removed)
+            ]
+        );
+
+        let mut meta = vfs.metadata("/skins/classic/skin.toml").unwrap();
+        meta.mode = 0o600;
+        vfs.set_metadata("/skins/classic/skin.toml", &meta).unwrap();
+        assert_eq!(
+            vfs.metadata("/skins/classic/skin.toml").unwrap().mode,
+            0o600
+        );
+        assert_eq!(
+            vfs.read("/skins/classic/skin.toml").unwrap(),
+            b"name = \"classic\""
+        );
+    }
+}
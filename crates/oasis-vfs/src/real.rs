@@ -3,19 +3,60 @@
 //! Wraps `std::fs` operations behind the `Vfs` trait. A configurable root
 //! directory is prepended to all paths, providing sandboxing -- the VFS
 //! cannot escape its root.
+//!
+//! Other programs can change the directory too, so the change journal is
+//! fed by polling: [`Vfs::generation`] and [`Vfs::changes_since`] compare
+//! the modification time and size of every file under the root with the
+//! previous scan. A scan is reused for the poll interval, so reading both
+//! costs one walk of the tree.
 
+use std::cell::RefCell;
+use std::collections::BTreeMap;
 use std::fs;
-use std::path::PathBuf;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 
 use oasis_types::error::{OasisError, Result};
 
-use crate::{EntryKind, Vfs, VfsEntry, VfsMetadata};
+use crate::journal::ChangeJournal;
+use crate::memory::normalize;
+use crate::{
+    ChangeKind, Changes, DEFAULT_OWNER, EntryKind, Metadata, Vfs, VfsEntry, VfsMetadata,
+    check_transfer, is_within, join,
+};
+
+/// Most paths a poll looks at; anything past them is not watched.
+const MAX_WATCHED: usize = 4096;
+
+/// How long a scan is reused before the tree is walked again.
+const DEFAULT_POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Kind, modification time and size of a path. Directories are stamped
+/// without a time, so adding to one is reported for the new entry only.
+type Stamp = (EntryKind, Option<SystemTime>, u64);
+
+/// Change tracking state.
+#[derive(Debug, Default)]
+struct Watch {
+    journal: ChangeJournal,
+    /// Stamps of every path under the root at the last poll, by VFS
+    /// path. `None` until the first poll.
+    stamps: Option<BTreeMap<String, Stamp>>,
+    /// When the tree was last scanned.
+    polled_at: Option<Instant>,
+    /// Whether the last scan stopped at [`MAX_WATCHED`] paths.
+    truncated: bool,
+}
 
 /// A VFS backed by the real filesystem, rooted at a configurable directory.
 #[derive(Debug)]
 pub struct RealVfs {
     /// Canonical absolute path to the VFS root on the real filesystem.
     root: PathBuf,
+    /// How long a scan is reused.
+    poll_interval: Duration,
+    watch: RefCell<Watch>,
 }
 
 impl RealVfs {
@@ -33,9 +74,91 @@ impl RealVfs {
             root: root
                 .canonicalize()
                 .map_err(|e| OasisError::Vfs(format!("cannot canonicalize root: {e}")))?,
+            poll_interval: DEFAULT_POLL_INTERVAL,
+            watch: RefCell::default(),
         })
     }
 
+    /// Rescan for outside changes at most once per `interval` (default
+    /// 250 ms). Changes made through this VFS are journaled at once
+    /// either way.
+    pub fn with_poll_interval(mut self, interval: Duration) -> Self {
+        self.poll_interval = interval;
+        self
+    }
+
+    /// Compare the tree with the last poll and journal the differences,
+    /// unless the last poll is more recent than the poll interval.
+    fn poll(&self) {
+        let now = Instant::now();
+        if self
+            .watch
+            .borrow()
+            .polled_at
+            .is_some_and(|at| now.duration_since(at) < self.poll_interval)
+        {
+            return;
+        }
+        let mut current = BTreeMap::new();
+        let truncated = scan(&self.root, "/", &mut current);
+        let mut watch = self.watch.borrow_mut();
+        watch.polled_at = Some(now);
+        if truncated && !watch.truncated {
+            log::warn!(
+                "{} has more than {MAX_WATCHED} entries -- changes past them are not noticed",
+                self.root.display()
+            );
+        }
+        watch.truncated = truncated;
+        let Some(old) = watch.stamps.replace(current) else {
+            return;
+        };
+        let current = watch.stamps.as_ref().expect("just stored");
+        let mut changes: Vec<(String, ChangeKind)> = Vec::new();
+        for (path, stamp) in current {
+            let kind = match old.get(path) {
+                None => ChangeKind::Created,
+                Some(before) if before.0 != stamp.0 => ChangeKind::Created,
+                Some(before) if before != stamp => ChangeKind::Modified,
+                Some(_) => continue,
+            };
+            changes.push((path.clone(), kind));
+        }
+        for path in old.keys().filter(|p| !current.contains_key(*p)) {
+            changes.push((path.clone(), ChangeKind::Removed));
+        }
+        // A created or removed tree is reported for its root only.
+        let mut roots: Vec<&str> = Vec::new();
+        for (path, kind) in &changes {
+            if *kind != ChangeKind::Modified {
+                if roots.iter().any(|root| is_within(path, root)) {
+                    continue;
+                }
+                roots.push(path);
+            }
+            watch.journal.record(path, *kind);
+        }
+    }
+
+    /// Journal a change made through this VFS and refresh the stamps
+    /// below `path`, so the next poll does not report it again.
+    fn note(&self, path: &str, kind: ChangeKind) {
+        let path = normalize(path);
+        let mut watch = self.watch.borrow_mut();
+        watch.journal.record(&path, kind);
+        if let Some(stamps) = watch.stamps.as_mut() {
+            stamps.retain(|key, _| !is_within(key, &path));
+            if let Ok(real_path) = self.resolve_link(&path)
+                && let Some(stamp) = stamp_of(&real_path)
+            {
+                stamps.insert(path.to_string(), stamp);
+                if stamp.0 == EntryKind::Directory {
+                    scan(&real_path, &path, stamps);
+                }
+            }
+        }
+    }
+
     /// Resolve a VFS path to a real filesystem path, ensuring it stays
     /// within the root directory.
     fn resolve(&self, vfs_path: &str) -> Result<PathBuf> {
@@ -69,6 +192,19 @@ impl RealVfs {
         }
         Ok(resolved)
     }
+
+    /// Like [`resolve`](Self::resolve), but a symbolic link at the end of
+    /// the path is not followed.
+    fn resolve_link(&self, vfs_path: &str) -> Result<PathBuf> {
+        let path = normalize(vfs_path);
+        let (dir, name) = path.rsplit_once('/').unwrap_or(("", &path));
+        if matches!(name, "" | "." | "..") {
+            return self.resolve(&path);
+        }
+        Ok(self
+            .resolve(if dir.is_empty() { "/" } else { dir })?
+            .join(name))
+    }
 }
 
 impl Vfs for RealVfs {
@@ -80,14 +216,11 @@ impl Vfs for RealVfs {
         let mut entries = Vec::new();
         for entry in fs::read_dir(&real_path)? {
             let entry = entry?;
-            let meta = entry.metadata()?;
+            // Not followed, so links are listed as links.
+            let meta = fs::symlink_metadata(entry.path())?;
             entries.push(VfsEntry {
                 name: entry.file_name().to_string_lossy().into_owned(),
-                kind: if meta.is_dir() {
-                    EntryKind::Directory
-                } else {
-                    EntryKind::File
-                },
+                kind: entry_kind(&meta),
                 size: meta.len(),
             });
         }
@@ -103,9 +236,27 @@ impl Vfs for RealVfs {
         Ok(fs::read(&real_path)?)
     }
 
+    fn read_range(&self, path: &str, offset: u64, len: usize) -> Result<Vec<u8>> {
+        let real_path = self.resolve(path)?;
+        if real_path.is_dir() {
+            return Err(OasisError::Vfs(format!("is a directory: {path}")));
+        }
+        let mut file = fs::File::open(&real_path)?;
+        file.seek(SeekFrom::Start(offset))?;
+        let mut data = Vec::new();
+        file.take(len as u64).read_to_end(&mut data)?;
+        Ok(data)
+    }
+
     fn write(&mut self, path: &str, data: &[u8]) -> Result<()> {
         let real_path = self.resolve(path)?;
+        let kind = if real_path.exists() {
+            ChangeKind::Modified
+        } else {
+            ChangeKind::Created
+        };
         fs::write(&real_path, data)?;
+        self.note(path, kind);
         Ok(())
     }
 
@@ -124,26 +275,208 @@ impl Vfs for RealVfs {
 
     fn mkdir(&mut self, path: &str) -> Result<()> {
         let real_path = self.resolve(path)?;
+        // Journal the topmost directory this creates.
+        let created = real_path
+            .ancestors()
+            .take_while(|p| !p.exists())
+            .last()
+            .and_then(|p| p.strip_prefix(&self.root).ok())
+            .map(|p| format!("/{}", p.to_string_lossy()));
         fs::create_dir_all(&real_path)?;
+        if let Some(created) = created {
+            self.note(&created, ChangeKind::Created);
+        }
         Ok(())
     }
 
     fn remove(&mut self, path: &str) -> Result<()> {
-        let real_path = self.resolve(path)?;
+        let real_path = self.resolve_link(path)?;
         if real_path == self.root {
             return Err(OasisError::Vfs("cannot remove VFS root".to_string()));
         }
-        if real_path.is_dir() {
+        if fs::symlink_metadata(&real_path)?.is_dir() {
             fs::remove_dir(&real_path)?;
         } else {
             fs::remove_file(&real_path)?;
         }
+        self.note(path, ChangeKind::Removed);
         Ok(())
     }
 
     fn exists(&self, path: &str) -> bool {
         self.resolve(path).map(|p| p.exists()).unwrap_or(false)
     }
+
+    fn rename(&mut self, from: &str, to: &str) -> Result<()> {
+        check_transfer(self, from, to, "move")?;
+        let (src, dst) = (self.resolve_link(from)?, self.resolve_link(to)?);
+        fs::rename(&src, &dst)?;
+        self.note(from, ChangeKind::Removed);
+        self.note(to, ChangeKind::Created);
+        Ok(())
+    }
+
+    fn remove_dir_all(&mut self, path: &str) -> Result<()> {
+        let real_path = self.resolve_link(path)?;
+        if real_path == self.root {
+            return Err(OasisError::Vfs("cannot remove VFS root".to_string()));
+        }
+        if fs::symlink_metadata(&real_path)?.is_dir() {
+            fs::remove_dir_all(&real_path)?;
+        } else {
+            fs::remove_file(&real_path)?;
+        }
+        self.note(path, ChangeKind::Removed);
+        Ok(())
+    }
+
+    /// Mode bits and times come from the host; the owner is always
+    /// [`DEFAULT_OWNER`].
+    fn metadata(&self, path: &str) -> Result<Metadata> {
+        let real_path = self.resolve(path)?;
+        let host = fs::metadata(&real_path)?;
+        let kind = if host.is_dir() {
+            EntryKind::Directory
+        } else {
+            EntryKind::File
+        };
+        let mut meta = Metadata::new(kind, if host.is_dir() { 0 } else { host.len() });
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            meta.mode = host.permissions().mode() & 0o7777;
+        }
+        #[cfg(not(unix))]
+        if host.permissions().readonly() {
+            meta.mode &= !0o222;
+        }
+        meta.created = host.created().map(unix_secs).unwrap_or(0);
+        meta.modified = host.modified().map(unix_secs).unwrap_or(0);
+        Ok(meta)
+    }
+
+    /// Sets the host mode bits and modification time. The owner cannot
+    /// be changed and the creation time is left alone.
+    fn set_metadata(&mut self, path: &str, meta: &Metadata) -> Result<()> {
+        let real_path = self.resolve(path)?;
+        if meta.owner != DEFAULT_OWNER {
+            return Err(OasisError::Vfs(format!(
+                "cannot change owner on the host filesystem: {path}"
+            )));
+        }
+        if meta.modified > 0 {
+            fs::File::open(&real_path)?
+                .set_modified(UNIX_EPOCH + Duration::from_secs(meta.modified))?;
+        }
+        let mut permissions = fs::metadata(&real_path)?.permissions();
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            permissions.set_mode(meta.mode & 0o7777);
+        }
+        #[cfg(not(unix))]
+        permissions.set_readonly(!meta.is_writable());
+        fs::set_permissions(&real_path, permissions)?;
+        Ok(())
+    }
+
+    /// An absolute target is taken from the VFS root; links that lead
+    /// out of the root cannot be followed.
+    #[cfg(unix)]
+    fn symlink(&mut self, target: &str, link: &str) -> Result<()> {
+        let real_link = self.resolve_link(link)?;
+        let host_target = match target.strip_prefix('/') {
+            Some(rest) => self.root.join(rest),
+            None => PathBuf::from(target),
+        };
+        std::os::unix::fs::symlink(host_target, &real_link)?;
+        self.note(link, ChangeKind::Created);
+        Ok(())
+    }
+
+    fn readlink(&self, path: &str) -> Result<String> {
+        let real_path = self.resolve_link(path)?;
+        if !fs::symlink_metadata(&real_path)?.is_symlink() {
+            return Err(OasisError::Vfs(format!("not a symbolic link: {path}")));
+        }
+        let target = fs::read_link(&real_path)?;
+        Ok(match target.strip_prefix(&self.root) {
+            Ok(inside) => format!("/{}", inside.to_string_lossy()),
+            Err(_) => target.to_string_lossy().into_owned(),
+        })
+    }
+
+    fn lstat(&self, path: &str) -> Result<VfsMetadata> {
+        let meta = fs::symlink_metadata(self.resolve_link(path)?)?;
+        Ok(VfsMetadata {
+            kind: entry_kind(&meta),
+            size: if meta.is_dir() { 0 } else { meta.len() },
+        })
+    }
+
+    fn generation(&self) -> u64 {
+        self.poll();
+        self.watch.borrow().journal.generation()
+    }
+
+    fn changes_since(&self, generation: u64) -> Changes {
+        self.poll();
+        self.watch.borrow().journal.since(generation)
+    }
+}
+
+/// Seconds since the Unix epoch of a host timestamp.
+fn unix_secs(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// Kind of a host entry from metadata read without following links.
+fn entry_kind(meta: &fs::Metadata) -> EntryKind {
+    if meta.is_symlink() {
+        EntryKind::Symlink
+    } else if meta.is_dir() {
+        EntryKind::Directory
+    } else {
+        EntryKind::File
+    }
+}
+
+/// Stamp of a real path, or `None` if it is missing. Links are stamped
+/// themselves, so scans do not follow them into loops.
+fn stamp_of(path: &Path) -> Option<Stamp> {
+    let meta = fs::symlink_metadata(path).ok()?;
+    Some(match entry_kind(&meta) {
+        EntryKind::Directory => (EntryKind::Directory, None, 0),
+        kind => (kind, meta.modified().ok(), meta.len()),
+    })
+}
+
+/// Stamp everything below the real directory `dir` (VFS path `vfs_dir`)
+/// into `stamps`, in name order, up to [`MAX_WATCHED`] paths. Returns
+/// whether it stopped at that limit.
+fn scan(dir: &Path, vfs_dir: &str, stamps: &mut BTreeMap<String, Stamp>) -> bool {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return false;
+    };
+    let mut names: Vec<_> = entries.flatten().map(|e| e.file_name()).collect();
+    names.sort();
+    for name in names {
+        if stamps.len() >= MAX_WATCHED {
+            return true;
+        }
+        let real_path = dir.join(&name);
+        let Some(stamp) = stamp_of(&real_path) else {
+            continue;
+        };
+        let vfs_path = join(vfs_dir, &name.to_string_lossy());
+        stamps.insert(vfs_path.clone(), stamp);
+        if stamp.0 == EntryKind::Directory && scan(&real_path, &vfs_path, stamps) {
+            return true;
+        }
+    }
+    false
 }
 
 #[cfg(test)]
@@ -152,7 +485,9 @@ mod tests {
 
     fn temp_vfs() -> (tempfile::TempDir, RealVfs) {
         let dir = tempfile::tempdir().unwrap();
-        let vfs = RealVfs::new(dir.path()).unwrap();
+        let vfs = RealVfs::new(dir.path())
+            .unwrap()
+            .with_poll_interval(Duration::ZERO);
         (dir, vfs)
     }
 
@@ -170,6 +505,15 @@ mod tests {
         assert_eq!(data, b"world");
     }
 
+    #[test]
+    fn read_range_seeks() {
+        let (_dir, mut vfs) = temp_vfs();
+        vfs.write("/hello.txt", b"hello world").unwrap();
+        assert_eq!(vfs.read_range("/hello.txt", 6, 3).unwrap(), b"wor");
+        assert_eq!(vfs.read_range("/hello.txt", 9, 100).unwrap(), b"ld");
+        assert!(vfs.read_range("/hello.txt", 50, 4).unwrap().is_empty());
+    }
+
     #[test]
     fn mkdir_and_readdir() {
         let (_dir, mut vfs) = temp_vfs();
@@ -203,6 +547,22 @@ mod tests {
         assert!(!vfs.exists("/d"));
     }
 
+    #[test]
+    fn rename_and_remove_dir_all() {
+        let (_dir, mut vfs) = temp_vfs();
+        vfs.mkdir("/d/sub").unwrap();
+        vfs.write("/d/sub/f", b"x").unwrap();
+        assert!(vfs.rename("/d", "/d/sub/inner").is_err());
+        vfs.rename("/d", "/e").unwrap();
+        assert!(!vfs.exists("/d"));
+        assert_eq!(vfs.read("/e/sub/f").unwrap(), b"x");
+        vfs.copy("/e", "/f").unwrap();
+        assert_eq!(vfs.read("/f/sub/f").unwrap(), b"x");
+        vfs.remove_dir_all("/e").unwrap();
+        assert!(!vfs.exists("/e"));
+        assert!(vfs.remove_dir_all("/").is_err());
+    }
+
     #[test]
     fn path_traversal_blocked() {
         let (_dir, vfs) = temp_vfs();
@@ -214,4 +574,123 @@ mod tests {
         let result = RealVfs::new(std::path::Path::new("/nonexistent_oasis_test_dir"));
         assert!(result.is_err());
     }
+
+    #[test]
+    fn journal_polls_external_changes() {
+        let (dir, mut vfs) = temp_vfs();
+        vfs.write("/kept.txt", b"a").unwrap();
+        let seen = vfs.generation();
+        assert_eq!(seen, 1);
+
+        // Changes made by other programs show up at the next poll.
+        std::fs::create_dir_all(dir.path().join("ext/deep")).unwrap();
+        std::fs::write(dir.path().join("ext/deep/f.txt"), b"x").unwrap();
+        std::fs::write(dir.path().join("kept.txt"), b"longer").unwrap();
+        let changes = vfs.changes_since(seen);
+        assert_eq!(
+            changes.changes,
+            [
+                ("/ext".to_string(), ChangeKind::Created),
+                ("/kept.txt".to_string(), ChangeKind::Modified),
+            ]
+        );
+
+        // Changes made through the VFS are journaled once.
+        let seen = changes.generation;
+        vfs.mkdir("/made/here").unwrap();
+        vfs.remove_dir_all("/ext").unwrap();
+        let changes = vfs.changes_since(seen);
+        assert_eq!(
+            changes.changes,
+            [
+                ("/made".to_string(), ChangeKind::Created),
+                ("/ext".to_string(), ChangeKind::Removed),
+            ]
+        );
+        assert!(vfs.changes_since(changes.generation).changes.is_empty());
+    }
+
+    #[test]
+    fn scans_are_reused_for_the_poll_interval() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut vfs = RealVfs::new(dir.path())
+            .unwrap()
+            .with_poll_interval(Duration::from_secs(3600));
+        let seen = vfs.generation();
+
+        // Outside changes wait for the next scan; our own do not.
+        std::fs::write(dir.path().join("outside.txt"), b"x").unwrap();
+        vfs.write("/inside.txt", b"y").unwrap();
+        let changes = vfs.changes_since(seen);
+        assert_eq!(
+            changes.changes,
+            [("/inside.txt".to_string(), ChangeKind::Created)]
+        );
+    }
+
+    #[test]
+    fn scan_stops_at_the_watch_limit() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut stamps = BTreeMap::new();
+        assert!(!scan(dir.path(), "/", &mut stamps));
+        for i in 0..=MAX_WATCHED {
+            std::fs::write(dir.path().join(format!("{i:05}")), b"").unwrap();
+        }
+        assert!(scan(dir.path(), "/", &mut stamps));
+        assert_eq!(stamps.len(), MAX_WATCHED);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn symlinks_stay_inside_the_root() {
+        let (dir, mut vfs) = temp_vfs();
+        vfs.mkdir("/data").unwrap();
+        vfs.write("/data/f.txt", b"abc").unwrap();
+        vfs.symlink("/data/f.txt", "/abs").unwrap();
+        vfs.symlink("data", "/rel").unwrap();
+        assert_eq!(vfs.read("/abs").unwrap(), b"abc");
+        assert_eq!(vfs.readlink("/abs").unwrap(), "/data/f.txt");
+        assert_eq!(vfs.readlink("/rel").unwrap(), "data");
+        assert_eq!(vfs.read("/rel/f.txt").unwrap(), b"abc");
+        assert_eq!(vfs.lstat("/rel").unwrap().kind, EntryKind::Symlink);
+        assert_eq!(vfs.stat("/rel").unwrap().kind, EntryKind::Directory);
+        let kinds: Vec<_> = vfs
+            .readdir("/")
+            .unwrap()
+            .into_iter()
+            .map(|e| e.kind)
+            .collect();
+        assert!(kinds.contains(&EntryKind::Symlink));
+
+        // Loops and escapes are errors; removing a link keeps the target.
+        vfs.symlink("loop", "/loop").unwrap();
+        assert!(vfs.read("/loop").is_err());
+        vfs.symlink("../..", "/out").unwrap();
+        assert!(vfs.readdir("/out").is_err());
+        vfs.remove_dir_all("/rel").unwrap();
+        assert!(dir.path().join("data/f.txt").exists());
+    }
+
+    #[test]
+    fn metadata_reads_and_sets_host_attributes() {
+        let (_dir, mut vfs) = temp_vfs();
+        vfs.write("/f.txt", b"abc").unwrap();
+        let mut meta = vfs.metadata("/f.txt").unwrap();
+        assert_eq!(meta.size, 3);
+        assert_eq!(meta.owner, DEFAULT_OWNER);
+        assert!(meta.modified > 0);
+
+        meta.mode = 0o444;
+        meta.modified = 1_000_000_000;
+        vfs.set_metadata("/f.txt", &meta).unwrap();
+        let got = vfs.metadata("/f.txt").unwrap();
+        assert!(!got.is_writable());
+        assert_eq!(got.modified, 1_000_000_000);
+
+        meta.owner = "root".to_string();
+        assert!(vfs.set_metadata("/f.txt", &meta).is_err());
+        meta.owner = DEFAULT_OWNER.to_string();
+        meta.mode = 0o644;
+        vfs.set_metadata("/f.txt", &meta).unwrap();
+    }
 }
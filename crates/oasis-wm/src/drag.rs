@@ -0,0 +1,75 @@
+//! Dragging items (files and the like) between windows.
+//!
+//! The frontend decides what can be dragged: when the pointer is pressed
+//! on an item in a window's content and moves past [`DRAG_THRESHOLD`], it
+//! calls [`WindowManager::begin_drag`](crate::WindowManager::begin_drag).
+//! From then on the WM follows the cursor, highlights the window under it
+//! and draws a ghost label, and releasing the pointer produces a
+//! [`WmEvent::Drop`](crate::WmEvent::Drop) on the target window.
+
+use crate::window::WindowId;
+
+/// Distance in pixels the pointer must travel with the button held before
+/// a press on a draggable item becomes a drag.
+pub const DRAG_THRESHOLD: i32 = 6;
+
+/// What is being dragged.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DragPayload {
+    /// A file or directory, by its VFS path.
+    VfsPath(String),
+}
+
+impl DragPayload {
+    /// Text of the ghost label following the cursor.
+    pub fn label(&self) -> &str {
+        match self {
+            Self::VfsPath(path) => path
+                .rsplit(['/', ':'])
+                .find(|part| !part.is_empty())
+                .unwrap_or(path),
+        }
+    }
+}
+
+/// An item drag in progress (see
+/// [`WindowManager::item_drag`](crate::WindowManager::item_drag)).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ItemDrag {
+    /// What is being dragged.
+    pub payload: DragPayload,
+    /// The window the drag started in.
+    pub origin: WindowId,
+    /// Cursor position, in screen coordinates.
+    pub x: i32,
+    pub y: i32,
+    /// The window a release would drop on: the one under the cursor,
+    /// unless that is the origin.
+    pub target: Option<WindowId>,
+}
+
+/// Whether the pointer moved from `start` to `now` far enough to start a
+/// drag.
+pub fn past_threshold(start: (i32, i32), now: (i32, i32)) -> bool {
+    (now.0 - start.0).abs() > DRAG_THRESHOLD || (now.1 - start.1).abs() > DRAG_THRESHOLD
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn label_is_the_file_name() {
+        let payload = DragPayload::VfsPath("ms0:/MUSIC/song.mp3".into());
+        assert_eq!(payload.label(), "song.mp3");
+        assert_eq!(DragPayload::VfsPath("/home/docs/".into()).label(), "docs");
+        assert_eq!(DragPayload::VfsPath("ms0:/".into()).label(), "ms0");
+    }
+
+    #[test]
+    fn threshold() {
+        assert!(!past_threshold((10, 10), (16, 4)));
+        assert!(past_threshold((10, 10), (17, 10)));
+        assert!(past_threshold((10, 10), (10, 3)));
+    }
+}
@@ -0,0 +1,97 @@
+//! Saved window layouts.
+//!
+//! [`WindowManager::serialize_layout`](crate::WindowManager::serialize_layout)
+//! writes the open windows as TOML, back to front:
+//!
+//! ```toml
+//! [[window]]
+//! id = "browser"
+//! x = 24
+//! y = 48
+//! width = 382
+//! height = 240
+//! state = "left"
+//!
+//! [window.saved]
+//! x = 24
+//! y = 48
+//! w = 382
+//! h = 240
+//! ```
+//!
+//! `width` and `height` are the outer size. `state` is `normal`,
+//! `minimized`, `maximized` or a snap region name as taken by `wm snap`;
+//! a minimized window also has `minimized_from`, and a maximized or
+//! snapped one the `saved` geometry it restores to.
+
+use serde::{Deserialize, Serialize};
+
+use crate::window::{Geometry, SnapRegion, Window, WindowState};
+
+/// A whole layout file.
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub(crate) struct Layout {
+    /// Windows from back to front.
+    #[serde(default)]
+    pub window: Vec<LayoutWindow>,
+}
+
+/// One window of a layout.
+#[derive(Debug, Deserialize, Serialize)]
+pub(crate) struct LayoutWindow {
+    pub id: String,
+    pub x: i32,
+    pub y: i32,
+    pub width: u32,
+    pub height: u32,
+    #[serde(default = "normal")]
+    pub state: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub minimized_from: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub saved: Option<Geometry>,
+}
+
+impl LayoutWindow {
+    /// Capture `window`.
+    pub fn from_window(window: &Window) -> Self {
+        Self {
+            id: window.id.clone(),
+            x: window.x,
+            y: window.y,
+            width: window.outer_w,
+            height: window.outer_h,
+            state: state_name(window.state).to_string(),
+            minimized_from: window.minimized_from.map(|s| state_name(s).to_string()),
+            saved: window.saved_geometry,
+        }
+    }
+}
+
+fn normal() -> String {
+    "normal".to_string()
+}
+
+/// Name of a window state in a layout.
+pub(crate) fn state_name(state: WindowState) -> &'static str {
+    match state {
+        WindowState::Normal => "normal",
+        WindowState::Minimized => "minimized",
+        WindowState::Maximized => "maximized",
+        WindowState::Snapped(region) => region.name(),
+    }
+}
+
+/// Parse a window state name from a layout. Unknown names give `None`.
+pub(crate) fn parse_state(name: &str) -> Option<WindowState> {
+    match name {
+        "normal" => Some(WindowState::Normal),
+        "minimized" => Some(WindowState::Minimized),
+        "maximized" => Some(WindowState::Maximized),
+        _ => match SnapRegion::from_name(name)? {
+            SnapRegion::Restore => None,
+            SnapRegion::Maximized => Some(WindowState::Maximized),
+            region => Some(WindowState::Snapped(region)),
+        },
+    }
+}
@@ -5,10 +5,18 @@
 //! and manipulates groups of SDI objects to simulate windowed interfaces.
 //! SDI remains a flat, dumb scene graph; the WM is the smart layer on top.
 
+mod drag;
 pub mod hit_test;
+mod layout;
 pub mod manager;
+pub mod taskbar;
 pub mod window;
 
+pub use drag::{DRAG_THRESHOLD, DragPayload, ItemDrag, past_threshold};
 pub use hit_test::{ButtonKind, HitRegion, ResizeEdge};
-pub use manager::{WindowManager, WmEvent};
-pub use window::{Geometry, Window, WindowConfig, WindowId, WindowState, WindowType, WmTheme};
+pub use manager::{CURSOR_Z, DIALOG_Z, KeyboardGrab, NOTIFICATION_Z, WindowManager, WmEvent};
+pub use oasis_ui::dialog::{Dialog, DialogResult};
+pub use taskbar::TaskbarLayout;
+pub use window::{
+    Geometry, SnapRegion, Window, WindowConfig, WindowId, WindowState, WindowType, WmTheme,
+};
@@ -4,13 +4,29 @@
 //! interfaces. It is a consumer of the SDI API -- SDI remains a flat scene
 //! graph with no concept of grouping or hierarchy.
 
+use std::collections::HashMap;
+
 use oasis_sdi::SdiRegistry;
-use oasis_types::backend::SdiBackend;
+use oasis_types::backend::{Color, SdiBackend, TextureId};
 use oasis_types::error::{OasisError, Result};
-use oasis_types::input::InputEvent;
+use oasis_types::input::{Button, InputEvent};
+use oasis_ui::animation::{Easing, Tween};
+use oasis_ui::dialog::{Dialog, DialogResult};
+use oasis_ui::{DrawContext, Theme};
 
+use super::drag::{DragPayload, ItemDrag};
 use super::hit_test::{ButtonKind, HitRegion, ResizeEdge, hit_test};
-use super::window::{Geometry, Window, WindowConfig, WindowId, WindowState, WmTheme};
+use super::layout::{Layout, LayoutWindow, parse_state};
+use super::taskbar::TaskbarLayout;
+use super::window::{
+    Geometry, SnapRegion, Window, WindowConfig, WindowId, WindowState, WindowType, WmTheme,
+};
+
+/// An open modal dialog and the window hosting it.
+struct ModalDialog {
+    window_id: WindowId,
+    dialog: Dialog,
+}
 
 /// Events produced by the WM in response to input.
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -27,8 +43,27 @@ pub enum WmEvent {
     WindowMinimized(WindowId),
     /// A window was maximized.
     WindowMaximized(WindowId),
-    /// A window was restored from minimized or maximized.
+    /// A window was restored from minimized, maximized or snapped.
     WindowRestored(WindowId),
+    /// A window was snapped to part of the screen.
+    WindowSnapped(WindowId, SnapRegion),
+    /// A window's taskbar entry was clicked. The frontend decides what
+    /// that does, typically [`WindowManager::toggle_minimize`] or focus.
+    TaskbarClick(WindowId),
+    /// A modal dialog closed with the button at this index chosen (or its
+    /// [`Dialog::cancel`] index when dismissed).
+    DialogResult(WindowId, usize),
+    /// A dragged item was released over a window (see
+    /// [`WindowManager::begin_drag`]). Coordinates are content-local.
+    Drop {
+        target_window: WindowId,
+        payload: DragPayload,
+        x: i32,
+        y: i32,
+    },
+    /// An item drag ended without a drop: it was released over the
+    /// desktop or its own window, or cancelled.
+    DragCancelled,
     /// Content area was clicked (coordinates are content-local).
     ContentClick(WindowId, i32, i32),
     /// Desktop background was clicked.
@@ -60,12 +95,74 @@ enum DragState {
     },
 }
 
+/// Keyboard move/resize of one window (see
+/// [`WindowManager::begin_keyboard_grab`]).
+///
+/// Nothing moves until the grab is committed; meanwhile
+/// [`WindowManager::draw_with_clips`] draws an outline at
+/// [`KeyboardGrab::pending`] (frontends drawing their own can read it).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeyboardGrab {
+    /// The window being moved or resized.
+    pub window_id: WindowId,
+    /// Geometry the window gets if the grab is committed.
+    pub pending: Geometry,
+    /// Whether the arrows resize (a trigger is held) instead of move.
+    pub resizing: bool,
+}
+
+/// A window outline moving between the window and its taskbar slot:
+/// shrinking into the slot on minimize, growing out of it on restore.
+struct TaskbarAnim {
+    window_id: WindowId,
+    pos: Tween<(i32, i32)>,
+    size: Tween<(i32, i32)>,
+}
+
+impl TaskbarAnim {
+    fn ghost_name(&self) -> String {
+        format!("{}.taskbar_ghost", self.window_id)
+    }
+}
+
+/// How long a window takes to shrink into (or grow out of) its taskbar
+/// slot.
+const TASKBAR_ANIM_MS: u32 = 200;
+
 /// Minimum window content size during resize.
 const MIN_WINDOW_SIZE: u32 = 40;
 
+/// Offset of a dragged item's ghost label from the cursor.
+const GHOST_OFFSET: i32 = 10;
+
+/// Z-order of the lowest modal dialog. Dialogs are drawn in the overlay
+/// layer from here up, each over its own dimmed backdrop.
+pub const DIALOG_Z: i32 = 8000;
+
+/// Z-order band of one dialog's SDI objects.
+const DIALOG_Z_STEP: i32 = 16;
+
+/// Alpha of the overlay dimming everything behind a dialog.
+const DIALOG_DIM_ALPHA: u8 = 160;
+
+/// Z-order reserved for overlays drawn above every window (notification
+/// toasts). SDI overlay objects at or above it are drawn after the
+/// overlay by [`WindowManager::draw_with_overlay`].
+pub const NOTIFICATION_Z: i32 = 9000;
+
+/// Z-order of the mouse cursor, above [`NOTIFICATION_Z`].
+pub const CURSOR_Z: i32 = 10000;
+
 /// Cascade offset between newly created windows.
 const CASCADE_OFFSET: i32 = 24;
 
+/// A window's content painted into an offscreen render target.
+struct CachedContent {
+    texture: TextureId,
+    /// Content rectangle the texture was painted for.
+    rect: (i32, i32, u32, u32),
+}
+
 /// The window manager.
 ///
 /// Manages a list of windows ordered by z-depth (last = topmost).
@@ -87,6 +184,30 @@ pub struct WindowManager {
     drag: Option<DragState>,
     /// Currently hovered window button (for hover color feedback).
     hover_button: Option<(WindowId, ButtonKind)>,
+    /// Window ids in the order they were opened (taskbar order).
+    open_order: Vec<WindowId>,
+    /// Where the frontend draws the taskbar, if it has one.
+    taskbar: Option<TaskbarLayout>,
+    /// Running minimize/restore animations.
+    taskbar_anims: Vec<TaskbarAnim>,
+    /// Keyboard move/resize in progress.
+    keyboard_grab: Option<KeyboardGrab>,
+    /// Open modal dialogs, the topmost last.
+    dialogs: Vec<ModalDialog>,
+    /// Number used for the next dialog's window id.
+    next_dialog: u32,
+    /// Theme dialog content is drawn with.
+    dialog_theme: Theme,
+    /// Item being dragged between windows.
+    item_drag: Option<ItemDrag>,
+    /// Last pointer position seen.
+    cursor: (i32, i32),
+    /// Whether window content is kept in render targets between frames.
+    cache_content: bool,
+    /// Cached content of each window that has been drawn.
+    content_cache: HashMap<WindowId, CachedContent>,
+    /// Render targets no longer needed, freed at the next draw.
+    released_targets: Vec<TextureId>,
 }
 
 impl WindowManager {
@@ -102,6 +223,18 @@ impl WindowManager {
             active_window: None,
             drag: None,
             hover_button: None,
+            open_order: Vec::new(),
+            taskbar: None,
+            taskbar_anims: Vec::new(),
+            keyboard_grab: None,
+            dialogs: Vec::new(),
+            next_dialog: 0,
+            dialog_theme: Theme::dark(),
+            item_drag: None,
+            cursor: (0, 0),
+            cache_content: false,
+            content_cache: HashMap::new(),
+            released_targets: Vec::new(),
         }
     }
 
@@ -121,6 +254,46 @@ impl WindowManager {
     /// Replace the visual theme at runtime.
     pub fn set_theme(&mut self, theme: WmTheme) {
         self.theme = theme;
+        self.mark_all_dirty();
+    }
+
+    /// Keep each window's content in an offscreen render target and
+    /// only call the content callback of
+    /// [`draw_with_overlay`](Self::draw_with_overlay) for windows that
+    /// are dirty (see [`mark_dirty`](Self::mark_dirty)), were resized or
+    /// moved. Off by default; it also switches itself off if the backend
+    /// cannot create render targets.
+    pub fn set_content_caching(&mut self, enabled: bool) {
+        self.cache_content = enabled;
+        if !enabled {
+            self.release_content_cache();
+        }
+    }
+
+    /// Whether window content is cached (see
+    /// [`set_content_caching`](Self::set_content_caching)).
+    pub fn content_caching(&self) -> bool {
+        self.cache_content
+    }
+
+    /// Repaint the content of window `id` at the next draw.
+    pub fn mark_dirty(&mut self, id: &str) {
+        if let Some(window) = self.windows.iter_mut().find(|w| w.id == id) {
+            window.content_dirty = true;
+        }
+    }
+
+    /// Repaint the content of every window at the next draw.
+    pub fn mark_all_dirty(&mut self) {
+        for window in &mut self.windows {
+            window.content_dirty = true;
+        }
+    }
+
+    /// Replace the oasis-ui theme dialog messages and buttons are drawn
+    /// with.
+    pub fn set_dialog_theme(&mut self, theme: Theme) {
+        self.dialog_theme = theme;
     }
 
     /// Get the number of open windows.
@@ -169,6 +342,7 @@ impl WindowManager {
 
         let id = window.id.clone();
         self.windows.push(window);
+        self.open_order.push(id.clone());
 
         // Focus the new window.
         self.focus_window_internal(&id, sdi);
@@ -187,6 +361,19 @@ impl WindowManager {
         let window = &self.windows[idx];
         self.destroy_sdi_objects(window, sdi);
         self.windows.remove(idx);
+        if let Some(cached) = self.content_cache.remove(id) {
+            self.released_targets.push(cached.texture);
+        }
+        self.open_order.retain(|w| w != id);
+        self.dialogs.retain(|d| d.window_id != id);
+        if self
+            .keyboard_grab
+            .as_ref()
+            .is_some_and(|g| g.window_id == id)
+        {
+            self.keyboard_grab = None;
+        }
+        self.cancel_taskbar_anim(id, sdi);
 
         // Cancel any drag on this window.
         if let Some(ref drag) = self.drag {
@@ -263,6 +450,54 @@ impl WindowManager {
         Ok(())
     }
 
+    /// Window ids from back to front (the last one is topmost).
+    pub fn window_ids_in_z_order(&self) -> Vec<&str> {
+        self.windows.iter().map(|w| w.id.as_str()).collect()
+    }
+
+    /// Alt-tab style focus cycling. Forward sends the topmost window to the
+    /// back and focuses the one below it; backward raises the bottom window.
+    /// Either way repeated calls visit every window and wrap around.
+    ///
+    /// Minimized windows are skipped unless every window is minimized, in
+    /// which case the chosen one is restored. Returns the newly focused
+    /// window, or `None` if there are no windows. While a dialog is open
+    /// focus stays on it.
+    pub fn cycle_focus(&mut self, forward: bool, sdi: &mut SdiRegistry) -> Option<WindowId> {
+        if let Some(id) = self.modal_dialog() {
+            return Some(id.to_string());
+        }
+        let visible: Vec<WindowId> = self
+            .windows
+            .iter()
+            .filter(|w| w.state != WindowState::Minimized)
+            .map(|w| w.id.clone())
+            .collect();
+        let mut ids = if visible.is_empty() {
+            self.windows.iter().map(|w| w.id.clone()).collect()
+        } else {
+            visible
+        };
+        if ids.is_empty() {
+            return None;
+        }
+        if forward {
+            ids.rotate_right(1);
+        } else {
+            ids.rotate_left(1);
+        }
+        // Raising every candidate in the new order keeps the SDI stacking
+        // in step; minimized windows stay underneath.
+        for id in &ids {
+            self.focus_window_internal(id, sdi);
+        }
+        let target = ids.pop()?;
+        if self.get_window(&target)?.state == WindowState::Minimized {
+            let _ = self.restore_window(&target, sdi);
+        }
+        Some(target)
+    }
+
     /// Minimize a window (hide all SDI objects).
     pub fn minimize_window(&mut self, id: &str, sdi: &mut SdiRegistry) -> Result<()> {
         let window = self
@@ -277,14 +512,12 @@ impl WindowManager {
             )));
         }
 
-        if window.state == WindowState::Normal {
-            window.saved_geometry = Some(Geometry {
-                x: window.x,
-                y: window.y,
-                w: window.outer_w,
-                h: window.outer_h,
-            });
+        if window.state == WindowState::Minimized {
+            return Ok(());
         }
+        // The geometry is left alone, so restoring only has to bring back
+        // the state (normal, maximized or snapped).
+        window.minimized_from = Some(window.state);
         window.state = WindowState::Minimized;
 
         // Hide all SDI objects.
@@ -304,9 +537,24 @@ impl WindowManager {
             .map(|w| w.id.clone());
         self.active_window = new_active;
 
+        self.start_taskbar_anim(id, true, sdi);
         Ok(())
     }
 
+    /// Minimize a window, or restore and focus it if it is minimized.
+    pub fn toggle_minimize(&mut self, id: &str, sdi: &mut SdiRegistry) -> Result<()> {
+        let window = self
+            .get_window(id)
+            .ok_or_else(|| OasisError::Wm(format!("window not found: {id}")))?;
+        if window.state == WindowState::Minimized {
+            self.restore_window(id, sdi)?;
+            self.focus_window_internal(id, sdi);
+            Ok(())
+        } else {
+            self.minimize_window(id, sdi)
+        }
+    }
+
     /// Maximize a window to fill the screen.
     pub fn maximize_window(&mut self, id: &str, sdi: &mut SdiRegistry) -> Result<()> {
         let window = self
@@ -321,13 +569,16 @@ impl WindowManager {
             )));
         }
 
-        // Save geometry for restore.
-        window.saved_geometry = Some(Geometry {
-            x: window.x,
-            y: window.y,
-            w: window.outer_w,
-            h: window.outer_h,
-        });
+        // Save geometry for restore. A snapped window keeps the geometry
+        // it had before it was snapped.
+        if window.state == WindowState::Normal {
+            window.saved_geometry = Some(Geometry {
+                x: window.x,
+                y: window.y,
+                w: window.outer_w,
+                h: window.outer_h,
+            });
+        }
 
         window.x = 0;
         window.y = self.theme.maximize_top_inset as i32;
@@ -351,13 +602,19 @@ impl WindowManager {
 
         let was_minimized = window.state == WindowState::Minimized;
 
-        if let Some(geom) = window.saved_geometry.take() {
-            window.x = geom.x;
-            window.y = geom.y;
-            window.outer_w = geom.w;
-            window.outer_h = geom.h;
+        if was_minimized {
+            // Back to how it was before minimizing; a maximized or snapped
+            // window stays so and keeps its saved geometry.
+            window.state = window.minimized_from.take().unwrap_or(WindowState::Normal);
+        } else {
+            if let Some(geom) = window.saved_geometry.take() {
+                window.x = geom.x;
+                window.y = geom.y;
+                window.outer_w = geom.w;
+                window.outer_h = geom.h;
+            }
+            window.state = WindowState::Normal;
         }
-        window.state = WindowState::Normal;
 
         if was_minimized {
             // Show all SDI objects.
@@ -369,17 +626,453 @@ impl WindowManager {
             }
         }
 
+        if was_minimized {
+            self.start_taskbar_anim(id, false, sdi);
+        } else {
+            self.cancel_taskbar_anim(id, sdi);
+        }
         self.update_sdi_positions(id.to_string(), sdi);
 
         Ok(())
     }
 
+    /// Start moving window `id` with the keyboard, focusing it.
+    ///
+    /// While the grab lasts, [`handle_input`](Self::handle_input) takes
+    /// every event: the arrows move the pending geometry by
+    /// [`WmTheme::keyboard_step`] pixels, or resize it while a trigger is
+    /// held; Confirm applies it and Cancel drops it.
+    pub fn begin_keyboard_grab(&mut self, id: &str, sdi: &mut SdiRegistry) -> Result<()> {
+        let window = self
+            .get_window(id)
+            .ok_or_else(|| OasisError::Wm(format!("window not found: {id}")))?;
+        if !window.is_draggable() {
+            return Err(OasisError::Wm(format!("window cannot be moved: {id}")));
+        }
+        if !self.dialogs.is_empty() {
+            return Err(OasisError::Wm("a dialog is open".to_string()));
+        }
+        if window.state == WindowState::Minimized {
+            self.restore_window(id, sdi)?;
+        }
+        let pending = self
+            .get_window(id)
+            .map(|w| Geometry {
+                x: w.x,
+                y: w.y,
+                w: w.outer_w,
+                h: w.outer_h,
+            })
+            .ok_or_else(|| OasisError::Wm(format!("window not found: {id}")))?;
+        self.drag = None;
+        self.focus_window_internal(id, sdi);
+        self.keyboard_grab = Some(KeyboardGrab {
+            window_id: id.to_string(),
+            pending,
+            resizing: false,
+        });
+        Ok(())
+    }
+
+    /// The keyboard move/resize in progress, if any.
+    pub fn keyboard_grab(&self) -> Option<&KeyboardGrab> {
+        self.keyboard_grab.as_ref()
+    }
+
+    /// Drop the keyboard grab without moving the window.
+    pub fn cancel_keyboard_grab(&mut self) {
+        self.keyboard_grab = None;
+    }
+
+    /// Open a modal dialog centered on the screen, above any other dialog.
+    ///
+    /// Until it closes, [`handle_input`](Self::handle_input) sends every
+    /// event to it: clicks elsewhere are swallowed, Left/Right move the
+    /// button focus and Confirm or a click activates a button, while
+    /// Cancel or the close button dismiss it. Either way it closes with a
+    /// [`WmEvent::DialogResult`].
+    pub fn show_dialog(&mut self, dialog: Dialog, sdi: &mut SdiRegistry) -> Result<WindowId> {
+        let count = dialog.button_count();
+        if count == 0 {
+            return Err(OasisError::Wm("dialog has no buttons".to_string()));
+        }
+        if dialog.focused >= count || dialog.cancel >= count {
+            return Err(OasisError::Wm(format!(
+                "dialog button index out of range: {count} buttons"
+            )));
+        }
+        let id = loop {
+            let id = format!("dialog_{}", self.next_dialog);
+            self.next_dialog += 1;
+            if self.get_window(&id).is_none() {
+                break id;
+            }
+        };
+        let pad = self.dialog_theme.spacing_md as u32;
+        let max_w = self.screen_w.saturating_sub(4 * pad);
+        let (width, height) = dialog.body_size(&self.dialog_theme, max_w);
+        let border = self.theme.border_width;
+        let outer_w = width + 2 * border;
+        let outer_h = height + self.theme.titlebar_height + 2 * border;
+        let window = WindowConfig {
+            id: id.clone(),
+            title: dialog.title.clone(),
+            x: Some((self.screen_w as i32 - outer_w as i32) / 2),
+            y: Some((self.screen_h as i32 - outer_h as i32) / 2),
+            width,
+            height,
+            window_type: WindowType::Dialog,
+        };
+        self.create_window(&window, sdi)?;
+        self.drag = None;
+        self.keyboard_grab = None;
+        self.item_drag = None;
+        self.dialogs.push(ModalDialog {
+            window_id: id.clone(),
+            dialog,
+        });
+        self.focus_window_internal(&id, sdi);
+        Ok(id)
+    }
+
+    /// Start dragging `payload` out of window `origin_window` from the
+    /// current pointer position. Frontends call this once the pointer,
+    /// pressed on a draggable item, has moved past
+    /// [`DRAG_THRESHOLD`](crate::DRAG_THRESHOLD).
+    ///
+    /// Until the pointer is released, [`handle_input`](Self::handle_input)
+    /// follows it and highlights the window it would drop on. Releasing
+    /// over another window gives [`WmEvent::Drop`]; releasing anywhere
+    /// else, or Cancel, gives [`WmEvent::DragCancelled`].
+    pub fn begin_drag(&mut self, payload: DragPayload, origin_window: &str) -> Result<()> {
+        if self.get_window(origin_window).is_none() {
+            return Err(OasisError::Wm(format!("window not found: {origin_window}")));
+        }
+        if !self.dialogs.is_empty() {
+            return Err(OasisError::Wm("a dialog is open".to_string()));
+        }
+        self.drag = None;
+        self.keyboard_grab = None;
+        let (x, y) = self.cursor;
+        self.item_drag = Some(ItemDrag {
+            payload,
+            origin: origin_window.to_string(),
+            x,
+            y,
+            target: None,
+        });
+        self.update_drop_target();
+        Ok(())
+    }
+
+    /// The item drag in progress, if any.
+    pub fn item_drag(&self) -> Option<&ItemDrag> {
+        self.item_drag.as_ref()
+    }
+
+    /// Id of the topmost modal dialog, if one is open. Frontends should
+    /// route all input to the WM while this is `Some`.
+    pub fn modal_dialog(&self) -> Option<&str> {
+        self.dialogs.last().map(|d| d.window_id.as_str())
+    }
+
+    /// Tell the WM where the frontend draws its taskbar entries, so clicks
+    /// on them produce [`WmEvent::TaskbarClick`] and minimized windows
+    /// shrink toward them. `None` means there is no taskbar.
+    pub fn set_taskbar_layout(&mut self, layout: Option<TaskbarLayout>) {
+        self.taskbar = layout;
+    }
+
+    /// Windows that get a taskbar entry (those that can be minimized), in
+    /// the order they were opened. Entry `i` goes in slot `i`.
+    pub fn taskbar_windows(&self) -> Vec<&Window> {
+        self.open_order
+            .iter()
+            .filter_map(|id| self.get_window(id))
+            .filter(|w| w.has_minimize_button())
+            .collect()
+    }
+
+    /// The window whose taskbar entry in `layout` contains (`x`, `y`).
+    pub fn taskbar_hit_test(&self, x: i32, y: i32, layout: &TaskbarLayout) -> Option<WindowId> {
+        let slot = layout.slot_at(x, y)?;
+        self.taskbar_windows().get(slot).map(|w| w.id.clone())
+    }
+
+    /// Advance animations by `dt_ms`. Call once per frame.
+    pub fn tick(&mut self, dt_ms: u32, sdi: &mut SdiRegistry) {
+        self.taskbar_anims.retain_mut(|anim| {
+            let (x, y) = anim.pos.tick(dt_ms);
+            let (w, h) = anim.size.tick(dt_ms);
+            let name = anim.ghost_name();
+            if anim.pos.is_finished() {
+                let _ = sdi.destroy(&name);
+                return false;
+            }
+            if let Ok(obj) = sdi.get_mut(&name) {
+                obj.x = x;
+                obj.y = y;
+                obj.w = w.max(1) as u32;
+                obj.h = h.max(1) as u32;
+            }
+            true
+        });
+    }
+
+    /// Whether any animation is running.
+    pub fn is_animating(&self) -> bool {
+        !self.taskbar_anims.is_empty()
+    }
+
+    /// Snap a window to part of the work area (the screen minus the
+    /// theme's maximize insets), or put it back with
+    /// [`SnapRegion::Restore`]. The geometry from before the first snap is
+    /// kept, so restoring after several snaps returns to it.
+    pub fn snap_window(
+        &mut self,
+        id: &str,
+        region: SnapRegion,
+        sdi: &mut SdiRegistry,
+    ) -> Result<()> {
+        let window = self
+            .windows
+            .iter()
+            .find(|w| w.id == id)
+            .ok_or_else(|| OasisError::Wm(format!("window not found: {id}")))?;
+        if !window.is_draggable() {
+            return Err(OasisError::Wm(format!(
+                "window type does not support snapping: {id}"
+            )));
+        }
+        match (region, window.state) {
+            (SnapRegion::Maximized, _) => return self.maximize_window(id, sdi),
+            (SnapRegion::Restore, WindowState::Snapped(_) | WindowState::Maximized) => {
+                return self.restore_window(id, sdi);
+            },
+            (SnapRegion::Restore, _) => return Ok(()),
+            (_, WindowState::Minimized) => self.restore_window(id, sdi)?,
+            _ => {},
+        }
+
+        let (x, y, w, h) = self.snap_rect(region);
+        let Some(window) = self.windows.iter_mut().find(|w| w.id == id) else {
+            return Ok(());
+        };
+        if window.state == WindowState::Normal {
+            window.saved_geometry = Some(Geometry {
+                x: window.x,
+                y: window.y,
+                w: window.outer_w,
+                h: window.outer_h,
+            });
+        }
+        window.x = x;
+        window.y = y;
+        window.outer_w = w;
+        window.outer_h = h;
+        window.state = WindowState::Snapped(region);
+        self.update_sdi_positions(id.to_string(), sdi);
+        Ok(())
+    }
+
+    /// Change the screen size (the host window was resized). Maximized
+    /// and snapped windows, minimized ones included, are refitted to the
+    /// new work area; other windows that now reach past the right or
+    /// bottom edge are moved back onto the screen where they fit.
+    pub fn set_screen_size(&mut self, w: u32, h: u32, sdi: &mut SdiRegistry) {
+        if (w, h) == (self.screen_w, self.screen_h) {
+            return;
+        }
+        self.screen_w = w;
+        self.screen_h = h;
+        let top = self.theme.maximize_top_inset as i32;
+        let ids: Vec<WindowId> = self.windows.iter().map(|w| w.id.clone()).collect();
+        for id in ids {
+            let Some(window) = self.windows.iter().find(|w| w.id == id) else {
+                continue;
+            };
+            let state = match window.state {
+                WindowState::Minimized => window.minimized_from.unwrap_or(WindowState::Normal),
+                state => state,
+            };
+            let fitted = match state {
+                WindowState::Maximized => Some(self.snap_rect(SnapRegion::Maximized)),
+                WindowState::Snapped(region) => Some(self.snap_rect(region)),
+                WindowState::Normal | WindowState::Minimized => None,
+            };
+            let Some(window) = self.windows.iter_mut().find(|w| w.id == id) else {
+                continue;
+            };
+            if let Some((x, y, w, h)) = fitted {
+                window.x = x;
+                window.y = y;
+                window.outer_w = w;
+                window.outer_h = h;
+            } else {
+                if window.x + window.outer_w as i32 > self.screen_w as i32 {
+                    window.x = (self.screen_w as i32 - window.outer_w as i32).max(0);
+                }
+                if window.y + window.outer_h as i32 > self.screen_h as i32 {
+                    window.y = (self.screen_h as i32 - window.outer_h as i32).max(top);
+                }
+            }
+            self.update_sdi_positions(id, sdi);
+        }
+    }
+
+    /// The open windows as a TOML layout (see [`restore_layout`]): their
+    /// ids, geometry, state and z-order.
+    ///
+    /// [`restore_layout`]: Self::restore_layout
+    pub fn serialize_layout(&self) -> String {
+        let layout = Layout {
+            window: self
+                .windows
+                .iter()
+                .filter(|w| !self.dialogs.iter().any(|d| d.window_id == w.id))
+                .map(LayoutWindow::from_window)
+                .collect(),
+        };
+        toml::to_string(&layout).unwrap_or_default()
+    }
+
+    /// Reopen the windows of a layout from [`serialize_layout`], back to
+    /// front, and put them where they were. Returns how many windows were
+    /// restored.
+    ///
+    /// The WM cannot build app content, so for each window not already
+    /// open it calls `opener` with the window id. The frontend starts the
+    /// app and returns its window config, or `None` for an id it does not
+    /// know; such windows are skipped with a warning. Windows already
+    /// open are only moved.
+    ///
+    /// [`serialize_layout`]: Self::serialize_layout
+    pub fn restore_layout(
+        &mut self,
+        layout: &str,
+        sdi: &mut SdiRegistry,
+        mut opener: impl FnMut(&str) -> Option<WindowConfig>,
+    ) -> Result<usize> {
+        let layout: Layout = toml::from_str(layout)
+            .map_err(|e| OasisError::Config(format!("window layout: {e}")))?;
+        let mut restored = 0;
+        for entry in layout.window {
+            if self.get_window(&entry.id).is_none() {
+                let Some(mut config) = opener(&entry.id) else {
+                    log::warn!("window layout: skipping unknown app {}", entry.id);
+                    continue;
+                };
+                config.id = entry.id.clone();
+                config.x = Some(entry.x);
+                config.y = Some(entry.y);
+                if let Err(e) = self.create_window(&config, sdi) {
+                    log::warn!("window layout: cannot open {}: {e}", entry.id);
+                    continue;
+                }
+            }
+            let Some(window) = self.windows.iter_mut().find(|w| w.id == entry.id) else {
+                continue;
+            };
+            let state = |name: &str| {
+                parse_state(name).unwrap_or_else(|| {
+                    log::warn!("window layout: unknown state {name} for {}", entry.id);
+                    WindowState::Normal
+                })
+            };
+            window.x = entry.x;
+            window.y = entry.y;
+            window.outer_w = entry.width;
+            window.outer_h = entry.height;
+            window.state = state(&entry.state);
+            window.minimized_from = entry.minimized_from.as_deref().map(state);
+            window.saved_geometry = entry.saved;
+            if window.state == WindowState::Minimized && !window.has_minimize_button() {
+                window.state = window.minimized_from.take().unwrap_or(WindowState::Normal);
+            }
+            let visible = window.state != WindowState::Minimized;
+            for suffix in window.sdi_suffixes() {
+                if let Ok(obj) = sdi.get_mut(&window.sdi_name(suffix)) {
+                    obj.visible = visible;
+                }
+            }
+            self.cancel_taskbar_anim(&entry.id, sdi);
+            self.update_sdi_positions(entry.id.clone(), sdi);
+            self.focus_window_internal(&entry.id, sdi);
+            restored += 1;
+        }
+        self.active_window = self
+            .windows
+            .iter()
+            .rev()
+            .find(|w| w.state != WindowState::Minimized)
+            .map(|w| w.id.clone());
+        Ok(restored)
+    }
+
+    /// Carry out a request written by the `wm` terminal command, such as
+    /// `focus terminal` or `snap terminal left`. Returns what happened.
+    pub fn apply_request(&mut self, request: &str, sdi: &mut SdiRegistry) -> Result<WmEvent> {
+        let parts: Vec<&str> = request.split_whitespace().collect();
+        let event = match parts.as_slice() {
+            ["close", id] => {
+                self.close_window(id, sdi)?;
+                WmEvent::WindowClosed(id.to_string())
+            },
+            ["focus", id] => {
+                self.focus_window(id, sdi)?;
+                WmEvent::WindowFocused(id.to_string())
+            },
+            ["minimize", id] => {
+                self.minimize_window(id, sdi)?;
+                WmEvent::WindowMinimized(id.to_string())
+            },
+            ["maximize", id] => {
+                self.maximize_window(id, sdi)?;
+                WmEvent::WindowMaximized(id.to_string())
+            },
+            ["restore", id] => {
+                self.restore_window(id, sdi)?;
+                WmEvent::WindowRestored(id.to_string())
+            },
+            ["move", id] => {
+                self.begin_keyboard_grab(id, sdi)?;
+                WmEvent::WindowFocused(id.to_string())
+            },
+            ["snap", id, name] => {
+                let region = SnapRegion::from_name(name)
+                    .ok_or_else(|| OasisError::Wm(format!("unknown snap region: {name}")))?;
+                self.snap_window(id, region, sdi)?;
+                snap_event(id, region)
+            },
+            _ => return Err(OasisError::Wm(format!("unknown request: {request}"))),
+        };
+        Ok(event)
+    }
+
     /// Process an input event through the WM. Returns what happened.
+    ///
+    /// While a modal dialog is open every event goes to it; otherwise,
+    /// during an item drag or a keyboard grab every event goes to that.
     pub fn handle_input(&mut self, event: &InputEvent, sdi: &mut SdiRegistry) -> WmEvent {
+        if let InputEvent::PointerClick { x, y }
+        | InputEvent::CursorMove { x, y }
+        | InputEvent::PointerRelease { x, y } = event
+        {
+            self.cursor = (*x, *y);
+        }
+        if !self.dialogs.is_empty() {
+            return self.handle_dialog_input(event, sdi);
+        }
+        if self.item_drag.is_some() {
+            return self.handle_item_drag_input(event, sdi);
+        }
+        if self.keyboard_grab.is_some() {
+            return self.handle_grab_input(event, sdi);
+        }
         match event {
             InputEvent::PointerClick { x, y } => self.handle_click(*x, *y, sdi),
             InputEvent::CursorMove { x, y } => self.handle_cursor_move(*x, *y, sdi),
-            InputEvent::PointerRelease { .. } => self.handle_release(),
+            InputEvent::PointerRelease { x, y } => self.handle_release(*x, *y, sdi),
             _ => WmEvent::None,
         }
     }
@@ -388,45 +1081,317 @@ impl WindowManager {
     /// for each window's content. The WM sets up clip rects before each call
     /// and resets them after.
     pub fn draw_with_clips<F>(
-        &self,
+        &mut self,
+        sdi: &mut SdiRegistry,
+        backend: &mut dyn SdiBackend,
+        draw_content: F,
+    ) -> Result<()>
+    where
+        F: FnMut(&str, i32, i32, u32, u32, &mut dyn SdiBackend) -> Result<()>,
+    {
+        self.draw_with_overlay(sdi, backend, draw_content, |_| Ok(()))
+    }
+
+    /// Like [`draw_with_clips`](Self::draw_with_clips), then call
+    /// `draw_overlay` in the [`NOTIFICATION_Z`] layer: above the windows
+    /// and their content, below the SDI overlay objects at or above it
+    /// (the cursor).
+    ///
+    /// With [content caching](Self::set_content_caching) on,
+    /// `draw_content` draws into a render target at the same coordinates,
+    /// and only for windows whose cached content is stale.
+    pub fn draw_with_overlay<F, G>(
+        &mut self,
         sdi: &mut SdiRegistry,
         backend: &mut dyn SdiBackend,
         mut draw_content: F,
+        draw_overlay: G,
     ) -> Result<()>
     where
         F: FnMut(&str, i32, i32, u32, u32, &mut dyn SdiBackend) -> Result<()>,
+        G: FnOnce(&mut dyn SdiBackend) -> Result<()>,
     {
-        // First draw all SDI objects (frames, titlebars, etc.).
-        sdi.draw(backend)?;
+        for texture in self.released_targets.drain(..) {
+            backend.destroy_texture(texture)?;
+        }
+
+        // First draw the SDI objects below the overlay (frames, titlebars, etc.).
+        sdi.draw_below(backend, DIALOG_Z)?;
 
         // Then draw clipped content for each visible window.
-        for window in &self.windows {
-            if window.state == WindowState::Minimized {
+        for index in 0..self.windows.len() {
+            let window = &self.windows[index];
+            if window.state == WindowState::Minimized || self.dialog(&window.id).is_some() {
                 continue;
             }
             let (cx, cy, cw, ch) = window.content_rect(&self.theme);
             if cw == 0 || ch == 0 {
                 continue;
             }
+            if self.cache_content && self.draw_cached(index, backend, &mut draw_content)? {
+                continue;
+            }
+            let window = &self.windows[index];
             backend.set_clip_rect(cx, cy, cw, ch)?;
             draw_content(&window.id, cx, cy, cw, ch, backend)?;
             backend.reset_clip_rect()?;
         }
 
-        Ok(())
+        // Outline of a pending keyboard move/resize, on top of the windows.
+        if let Some(grab) = &self.keyboard_grab {
+            let Geometry { x, y, w, h } = grab.pending;
+            backend.stroke_rect(x, y, w, h, 2, self.theme.titlebar_active_color)?;
+        }
+
+        // Drop target highlight and the dragged item's ghost label.
+        if let Some(drag) = &self.item_drag {
+            let accent = self.theme.titlebar_active_color;
+            if let Some(target) = drag.target.as_deref().and_then(|id| self.get_window(id)) {
+                let (x, y, w, h) = (target.x, target.y, target.outer_w, target.outer_h);
+                backend.stroke_rect(x, y, w, h, 2, accent)?;
+            }
+            let label = drag.payload.label();
+            let size = self.theme.titlebar_font_size;
+            let (x, y) = (drag.x + GHOST_OFFSET, drag.y + GHOST_OFFSET);
+            let w = backend.measure_text(label, size) + 6;
+            let h = u32::from(size) + 4;
+            backend.fill_rect(x, y, w, h, Color::rgba(accent.r, accent.g, accent.b, 200))?;
+            backend.draw_text(label, x + 3, y + 2, size, self.theme.titlebar_text_color)?;
+        }
+
+        // Each dialog dims everything beneath it, including lower dialogs.
+        let mut z = DIALOG_Z;
+        for dialog in &self.dialogs {
+            backend.dim_screen(DIALOG_DIM_ALPHA)?;
+            sdi.draw_overlay_range(backend, z..z + DIALOG_Z_STEP)?;
+            z += DIALOG_Z_STEP;
+            let Some(window) = self.get_window(&dialog.window_id) else {
+                continue;
+            };
+            let content = window.content_rect(&self.theme);
+            let (cx, cy, cw, ch) = content;
+            backend.set_clip_rect(cx, cy, cw, ch)?;
+            let mut ctx = DrawContext::new(backend, &self.dialog_theme);
+            dialog.dialog.draw_body(&mut ctx, content)?;
+            backend.reset_clip_rect()?;
+        }
+        sdi.draw_overlay_range(backend, z..NOTIFICATION_Z)?;
+
+        draw_overlay(backend)?;
+        sdi.draw_overlay_from(backend, NOTIFICATION_Z)
     }
 
     // -- Internal methods --
 
-    fn handle_click(&mut self, x: i32, y: i32, sdi: &mut SdiRegistry) -> WmEvent {
-        let region = hit_test(&self.windows, x, y, &self.theme);
+    /// Blit window `index`'s cached content, first repainting it with
+    /// `draw_content` if the window is dirty or its content rectangle
+    /// changed. Returns `false`, with caching switched off, if the
+    /// backend has no render targets.
+    fn draw_cached<F>(
+        &mut self,
+        index: usize,
+        backend: &mut dyn SdiBackend,
+        draw_content: &mut F,
+    ) -> Result<bool>
+    where
+        F: FnMut(&str, i32, i32, u32, u32, &mut dyn SdiBackend) -> Result<()>,
+    {
+        let window = &self.windows[index];
+        let rect = window.content_rect(&self.theme);
+        let (cx, cy, cw, ch) = rect;
+        let cached = self.content_cache.get(&window.id);
+        if window.content_dirty || cached.is_none_or(|c| c.rect != rect) {
+            let texture = match self.content_cache.remove(&window.id) {
+                Some(cached) if (cached.rect.2, cached.rect.3) == (cw, ch) => cached.texture,
+                stale => {
+                    if let Some(cached) = stale {
+                        backend.destroy_texture(cached.texture)?;
+                    }
+                    match backend.create_render_target(cw, ch) {
+                        Ok(texture) => texture,
+                        Err(e) => {
+                            log::warn!("Window content caching disabled: {e}");
+                            self.set_content_caching(false);
+                            return Ok(false);
+                        },
+                    }
+                },
+            };
+            let id = window.id.clone();
+            self.content_cache
+                .insert(id.clone(), CachedContent { texture, rect });
+
+            // The content draws at its on-screen position, shifted into
+            // the target.
+            backend.set_render_target(Some(texture))?;
+            let painted = backend
+                .clear(self.theme.content_bg_color)
+                .and_then(|()| backend.push_translate(-cx, -cy))
+                .and_then(|()| {
+                    let painted = draw_content(&id, cx, cy, cw, ch, backend);
+                    backend.pop_translate()?;
+                    painted
+                });
+            backend.reset_clip_rect()?;
+            backend.set_render_target(None)?;
+            painted?;
+            self.windows[index].content_dirty = false;
+        }
+        let texture = self.content_cache[&self.windows[index].id].texture;
+        backend.blit(texture, cx, cy, cw, ch)?;
+        Ok(true)
+    }
 
-        match region {
-            HitRegion::TitlebarButton(id, ButtonKind::Close) => {
-                let _ = self.close_window(&id, sdi);
-                WmEvent::WindowClosed(id)
-            },
-            HitRegion::TitlebarButton(id, ButtonKind::Minimize) => {
+    /// Queue every cached content texture to be freed.
+    fn release_content_cache(&mut self) {
+        self.released_targets
+            .extend(self.content_cache.drain().map(|(_, cached)| cached.texture));
+    }
+
+    fn dialog(&self, id: &str) -> Option<&ModalDialog> {
+        self.dialogs.iter().find(|d| d.window_id == id)
+    }
+
+    fn handle_dialog_input(&mut self, event: &InputEvent, sdi: &mut SdiRegistry) -> WmEvent {
+        let Some(dialog) = self.dialogs.last_mut() else {
+            return WmEvent::None;
+        };
+        let Some(window) = self.windows.iter().find(|w| w.id == dialog.window_id) else {
+            return WmEvent::None;
+        };
+        let content = window.content_rect(&self.theme);
+        let dialog = &mut dialog.dialog;
+        let result = match event {
+            InputEvent::PointerClick { x, y } => {
+                match hit_test(std::slice::from_ref(window), *x, *y, &self.theme) {
+                    HitRegion::TitlebarButton(_, ButtonKind::Close) => {
+                        Some(DialogResult::Dismissed)
+                    },
+                    _ => dialog
+                        .button_at(&self.dialog_theme, content, *x, *y)
+                        .map(DialogResult::Button),
+                }
+            },
+            InputEvent::CursorMove { x, y } => {
+                dialog.hovered = dialog.button_at(&self.dialog_theme, content, *x, *y);
+                None
+            },
+            _ => dialog.handle_input(event),
+        };
+        match result {
+            Some(result) => {
+                let index = dialog.chosen_index(result);
+                self.close_dialog(index, sdi)
+            },
+            None => WmEvent::None,
+        }
+    }
+
+    fn handle_item_drag_input(&mut self, event: &InputEvent, sdi: &mut SdiRegistry) -> WmEvent {
+        match event {
+            InputEvent::CursorMove { .. } => {
+                self.update_drop_target();
+                WmEvent::None
+            },
+            InputEvent::PointerRelease { .. } => {
+                self.update_drop_target();
+                let Some(ItemDrag {
+                    payload, target, ..
+                }) = self.item_drag.take()
+                else {
+                    return WmEvent::None;
+                };
+                let Some(target_window) = target else {
+                    return WmEvent::DragCancelled;
+                };
+                let Some((cx, cy, _, _)) = self
+                    .get_window(&target_window)
+                    .map(|w| w.content_rect(&self.theme))
+                else {
+                    return WmEvent::DragCancelled;
+                };
+                self.focus_window_internal(&target_window, sdi);
+                let (x, y) = self.cursor;
+                WmEvent::Drop {
+                    target_window,
+                    payload,
+                    x: x - cx,
+                    y: y - cy,
+                }
+            },
+            InputEvent::ButtonPress(Button::Cancel) => {
+                self.item_drag = None;
+                WmEvent::DragCancelled
+            },
+            _ => WmEvent::None,
+        }
+    }
+
+    /// Move the item drag to the cursor and find the window it would drop
+    /// on.
+    fn update_drop_target(&mut self) {
+        let (x, y) = self.cursor;
+        let target = match hit_test(&self.windows, x, y, &self.theme) {
+            HitRegion::TitlebarButton(id, _)
+            | HitRegion::Titlebar(id)
+            | HitRegion::ResizeHandle(id, _)
+            | HitRegion::Content(id, ..) => Some(id),
+            HitRegion::Desktop => None,
+        };
+        if let Some(drag) = &mut self.item_drag {
+            drag.x = x;
+            drag.y = y;
+            drag.target = target.filter(|id| *id != drag.origin);
+        }
+    }
+
+    /// Close the topmost dialog, choosing button `index`, and focus the
+    /// window now on top.
+    fn close_dialog(&mut self, index: usize, sdi: &mut SdiRegistry) -> WmEvent {
+        let Some(id) = self.modal_dialog().map(str::to_string) else {
+            return WmEvent::None;
+        };
+        let _ = self.close_window(&id, sdi);
+        if let Some(top) = self.windows.last().map(|w| w.id.clone()) {
+            self.focus_window_internal(&top, sdi);
+        }
+        WmEvent::DialogResult(id, index)
+    }
+
+    /// Keep open dialogs above every other window, in stacking order, and
+    /// give each its own band of the overlay layer from [`DIALOG_Z`] up.
+    fn raise_dialogs(&mut self, sdi: &mut SdiRegistry) {
+        let mut z = DIALOG_Z;
+        for dialog in &self.dialogs {
+            if let Some(idx) = self.windows.iter().position(|w| w.id == dialog.window_id) {
+                let window = self.windows.remove(idx);
+                for (k, suffix) in window.sdi_suffixes().into_iter().enumerate() {
+                    let name = window.sdi_name(suffix);
+                    if let Ok(obj) = sdi.get_mut(&name) {
+                        obj.overlay = true;
+                    }
+                    let _ = sdi.set_z(&name, z + k as i32);
+                }
+                self.windows.push(window);
+            }
+            z += DIALOG_Z_STEP;
+        }
+    }
+
+    fn handle_click(&mut self, x: i32, y: i32, sdi: &mut SdiRegistry) -> WmEvent {
+        if let Some(layout) = self.taskbar
+            && let Some(id) = self.taskbar_hit_test(x, y, &layout)
+        {
+            return WmEvent::TaskbarClick(id);
+        }
+        let region = hit_test(&self.windows, x, y, &self.theme);
+
+        match region {
+            HitRegion::TitlebarButton(id, ButtonKind::Close) => {
+                let _ = self.close_window(&id, sdi);
+                WmEvent::WindowClosed(id)
+            },
+            HitRegion::TitlebarButton(id, ButtonKind::Minimize) => {
                 let _ = self.minimize_window(&id, sdi);
                 WmEvent::WindowMinimized(id)
             },
@@ -493,6 +1458,72 @@ impl WindowManager {
         }
     }
 
+    fn handle_grab_input(&mut self, event: &InputEvent, sdi: &mut SdiRegistry) -> WmEvent {
+        let Some(grab) = self.keyboard_grab.as_mut() else {
+            return WmEvent::None;
+        };
+        let step = self.theme.keyboard_step as i32;
+        let (dx, dy) = match event {
+            InputEvent::ButtonPress(Button::Left) => (-step, 0),
+            InputEvent::ButtonPress(Button::Right) => (step, 0),
+            InputEvent::ButtonPress(Button::Up) => (0, -step),
+            InputEvent::ButtonPress(Button::Down) => (0, step),
+            InputEvent::TriggerPress(_) => {
+                grab.resizing = true;
+                return WmEvent::None;
+            },
+            InputEvent::TriggerRelease(_) => {
+                grab.resizing = false;
+                return WmEvent::None;
+            },
+            InputEvent::ButtonPress(Button::Confirm) => return self.commit_keyboard_grab(sdi),
+            InputEvent::ButtonPress(Button::Cancel) => {
+                self.keyboard_grab = None;
+                return WmEvent::None;
+            },
+            _ => return WmEvent::None,
+        };
+        if grab.resizing {
+            let (x, y, w, h) =
+                compute_resize(grab.pending, ResizeEdge::SouthEast, dx, dy, &self.theme);
+            grab.pending = Geometry { x, y, w, h };
+        } else {
+            grab.pending.x += dx;
+            grab.pending.y += dy;
+        }
+        WmEvent::None
+    }
+
+    /// Give the grabbed window its pending geometry. A maximized or
+    /// snapped window becomes a normal one.
+    fn commit_keyboard_grab(&mut self, sdi: &mut SdiRegistry) -> WmEvent {
+        let Some(KeyboardGrab {
+            window_id, pending, ..
+        }) = self.keyboard_grab.take()
+        else {
+            return WmEvent::None;
+        };
+        let Some(window) = self.windows.iter_mut().find(|w| w.id == window_id) else {
+            return WmEvent::None;
+        };
+        let resized = (window.outer_w, window.outer_h) != (pending.w, pending.h);
+        if (window.x, window.y) == (pending.x, pending.y) && !resized {
+            return WmEvent::None;
+        }
+        window.x = pending.x;
+        window.y = pending.y;
+        window.outer_w = pending.w;
+        window.outer_h = pending.h;
+        window.state = WindowState::Normal;
+        window.saved_geometry = None;
+        self.update_sdi_positions(window_id.clone(), sdi);
+        if resized {
+            WmEvent::WindowResized(window_id)
+        } else {
+            WmEvent::WindowMoved(window_id)
+        }
+    }
+
     fn handle_cursor_move(&mut self, x: i32, y: i32, sdi: &mut SdiRegistry) -> WmEvent {
         let drag = match self.drag.clone() {
             Some(d) => d,
@@ -556,16 +1587,176 @@ impl WindowManager {
         }
     }
 
-    fn handle_release(&mut self) -> WmEvent {
+    fn handle_release(&mut self, x: i32, y: i32, sdi: &mut SdiRegistry) -> WmEvent {
         self.hover_button = None;
-        if let Some(drag) = self.drag.take() {
-            let id = match drag {
-                DragState::Moving { window_id, .. } => window_id,
-                DragState::Resizing { window_id, .. } => window_id,
-            };
-            return WmEvent::WindowMoved(id);
+        match self.drag.take() {
+            Some(DragState::Moving {
+                window_id,
+                start_cursor_x,
+                start_cursor_y,
+                start_win_x,
+                start_win_y,
+            }) => {
+                if (x, y) == (start_cursor_x, start_cursor_y) {
+                    return WmEvent::WindowMoved(window_id);
+                }
+                match self.snap_at(x, y) {
+                    Some(region) => {
+                        self.snap_dragged(&window_id, region, (start_win_x, start_win_y), sdi)
+                    },
+                    None => self.unsnap_dragged(&window_id, sdi),
+                }
+            },
+            Some(DragState::Resizing { window_id, .. }) => WmEvent::WindowMoved(window_id),
+            None => WmEvent::None,
+        }
+    }
+
+    /// The region a drag released at (`x`, `y`) snaps to, if any. Edges
+    /// are those of the work area: the screen minus the reserved bar insets.
+    fn snap_at(&self, x: i32, y: i32) -> Option<SnapRegion> {
+        let threshold = self.theme.snap_threshold as i32;
+        if threshold == 0 {
+            return None;
+        }
+        let top = self.theme.maximize_top_inset as i32;
+        let bottom = self
+            .screen_h
+            .saturating_sub(self.theme.maximize_bottom_inset) as i32;
+        let left = x < threshold;
+        let right = x >= self.screen_w as i32 - threshold;
+        let at_top = y < top + threshold;
+        let at_bottom = y >= bottom - threshold;
+        match (left, right, at_top, at_bottom) {
+            (true, _, true, _) => Some(SnapRegion::TopLeft),
+            (true, _, _, true) => Some(SnapRegion::BottomLeft),
+            (_, true, true, _) => Some(SnapRegion::TopRight),
+            (_, true, _, true) => Some(SnapRegion::BottomRight),
+            (true, ..) => Some(SnapRegion::Left),
+            (_, true, ..) => Some(SnapRegion::Right),
+            (_, _, true, _) => Some(SnapRegion::Maximized),
+            _ => None,
         }
-        WmEvent::None
+    }
+
+    /// Outer geometry of a window snapped to `region`.
+    fn snap_rect(&self, region: SnapRegion) -> (i32, i32, u32, u32) {
+        let top = self.theme.maximize_top_inset;
+        let work_h = self
+            .screen_h
+            .saturating_sub(top + self.theme.maximize_bottom_inset);
+        let (left_w, right_w) = (self.screen_w / 2, self.screen_w - self.screen_w / 2);
+        let (top_h, bottom_h) = (work_h / 2, work_h - work_h / 2);
+        let mid_x = left_w as i32;
+        let mid_y = (top + top_h) as i32;
+        let top = top as i32;
+        match region {
+            SnapRegion::Left => (0, top, left_w, work_h),
+            SnapRegion::Right => (mid_x, top, right_w, work_h),
+            SnapRegion::Top => (0, top, self.screen_w, top_h),
+            SnapRegion::Bottom => (0, mid_y, self.screen_w, bottom_h),
+            SnapRegion::TopLeft => (0, top, left_w, top_h),
+            SnapRegion::TopRight => (mid_x, top, right_w, top_h),
+            SnapRegion::BottomLeft => (0, mid_y, left_w, bottom_h),
+            SnapRegion::BottomRight => (mid_x, mid_y, right_w, bottom_h),
+            SnapRegion::Maximized | SnapRegion::Restore => (0, top, self.screen_w, work_h),
+        }
+    }
+
+    /// Snap a window dropped near an edge. A window that was not snapped
+    /// before remembers where the drag started, so restoring puts it back
+    /// there rather than where it was dropped.
+    fn snap_dragged(
+        &mut self,
+        id: &str,
+        region: SnapRegion,
+        origin: (i32, i32),
+        sdi: &mut SdiRegistry,
+    ) -> WmEvent {
+        let was_normal = self
+            .get_window(id)
+            .is_some_and(|w| w.state == WindowState::Normal);
+        if self.snap_window(id, region, sdi).is_err() {
+            return WmEvent::WindowMoved(id.to_string());
+        }
+        if was_normal
+            && let Some(window) = self.windows.iter_mut().find(|w| w.id == id)
+            && let Some(geom) = window.saved_geometry.as_mut()
+        {
+            (geom.x, geom.y) = origin;
+        }
+        snap_event(id, region)
+    }
+
+    /// A snapped window dragged away from the edges gets its size from
+    /// before the snap back, where it was dropped.
+    fn unsnap_dragged(&mut self, id: &str, sdi: &mut SdiRegistry) -> WmEvent {
+        let Some(window) = self.windows.iter_mut().find(|w| w.id == id) else {
+            return WmEvent::None;
+        };
+        if !matches!(window.state, WindowState::Snapped(_)) {
+            return WmEvent::WindowMoved(id.to_string());
+        }
+        if let Some(geom) = window.saved_geometry.take() {
+            window.outer_w = geom.w;
+            window.outer_h = geom.h;
+        }
+        window.state = WindowState::Normal;
+        self.update_sdi_positions(id.to_string(), sdi);
+        WmEvent::WindowRestored(id.to_string())
+    }
+
+    /// Animate an outline of a window from its rectangle into its taskbar
+    /// slot (`into_slot`) or back out. Any earlier animation of the window
+    /// is dropped. Does nothing without a taskbar or with
+    /// [`WmTheme::animate_minimize`] off.
+    fn start_taskbar_anim(&mut self, id: &str, into_slot: bool, sdi: &mut SdiRegistry) {
+        self.cancel_taskbar_anim(id, sdi);
+        let Some(layout) = self.taskbar.filter(|_| self.theme.animate_minimize) else {
+            return;
+        };
+        let Some(slot) = self.taskbar_windows().iter().position(|w| w.id == id) else {
+            return;
+        };
+        let Some((x, y, w, h)) = self
+            .get_window(id)
+            .map(|w| (w.x, w.y, w.outer_w, w.outer_h))
+        else {
+            return;
+        };
+        let (sx, sy, sw, sh) = layout.slot_rect(slot);
+        let window = ((x, y), (w as i32, h as i32));
+        let slot = ((sx, sy), (sw as i32, sh as i32));
+        let (from, to, easing) = if into_slot {
+            (window, slot, Easing::EaseInQuad)
+        } else {
+            (slot, window, Easing::EaseOutQuad)
+        };
+        let anim = TaskbarAnim {
+            window_id: id.to_string(),
+            pos: Tween::new(from.0, to.0, TASKBAR_ANIM_MS, easing),
+            size: Tween::new(from.1, to.1, TASKBAR_ANIM_MS, easing),
+        };
+        let ghost = sdi.create(anim.ghost_name());
+        ghost.x = from.0.0;
+        ghost.y = from.0.1;
+        ghost.w = from.1.0 as u32;
+        ghost.h = from.1.1 as u32;
+        ghost.color = self.theme.frame_color.with_alpha(96);
+        ghost.stroke_width = Some(1);
+        ghost.stroke_color = Some(self.theme.titlebar_active_color);
+        self.taskbar_anims.push(anim);
+    }
+
+    /// Stop a window's minimize/restore animation, if one is running.
+    fn cancel_taskbar_anim(&mut self, id: &str, sdi: &mut SdiRegistry) {
+        self.taskbar_anims.retain(|anim| {
+            if anim.window_id != id {
+                return true;
+            }
+            let _ = sdi.destroy(&anim.ghost_name());
+            false
+        });
     }
 
     /// Move a window to the top of the z-order list and update SDI z-ordering.
@@ -582,6 +1773,7 @@ impl WindowManager {
                 let _ = sdi.move_to_top(&name);
             }
         }
+        self.raise_dialogs(sdi);
 
         // Update titlebar colors for all windows.
         for (i, window) in self.windows.iter().enumerate() {
@@ -624,7 +1816,7 @@ impl WindowManager {
             }
         }
 
-        self.active_window = Some(id.to_string());
+        self.active_window = self.windows.last().map(|w| w.id.clone());
     }
 
     /// Create all SDI objects for a window.
@@ -651,6 +1843,7 @@ impl WindowManager {
                 obj.stroke_width = Some(theme.border_width as u16);
                 obj.stroke_color = Some(theme.frame_color);
             }
+            obj.nine_patch = theme.frame_patch;
         }
 
         // Titlebar.
@@ -734,6 +1927,7 @@ impl WindowManager {
             if theme.button_radius > 0 {
                 obj.border_radius = Some(theme.button_radius);
             }
+            obj.nine_patch = theme.button_patch;
             let gobj = sdi.create(window.sdi_name("btn_close_glyph"));
             gobj.x = bx + (bw as i32 - glyph_font_size as i32) / 2;
             gobj.y = by + (bh as i32 - glyph_font_size as i32) / 2;
@@ -756,6 +1950,7 @@ impl WindowManager {
             if theme.button_radius > 0 {
                 obj.border_radius = Some(theme.button_radius);
             }
+            obj.nine_patch = theme.button_patch;
             let gobj = sdi.create(window.sdi_name("btn_minimize_glyph"));
             gobj.x = bx + (bw as i32 - glyph_font_size as i32) / 2;
             gobj.y = by + (bh as i32 - glyph_font_size as i32) / 2;
@@ -778,6 +1973,7 @@ impl WindowManager {
             if theme.button_radius > 0 {
                 obj.border_radius = Some(theme.button_radius);
             }
+            obj.nine_patch = theme.button_patch;
             let gobj = sdi.create(window.sdi_name("btn_maximize_glyph"));
             gobj.x = bx + (bw as i32 - glyph_font_size as i32) / 2;
             gobj.y = by + (bh as i32 - glyph_font_size as i32) / 2;
@@ -949,6 +2145,7 @@ impl WindowManager {
                 let name = format!("{old_id}.{suffix}");
                 if let Ok(obj) = sdi.get_mut(&name) {
                     obj.color = base_color;
+                    obj.nine_patch = self.theme.button_patch;
                 }
             }
             // Apply new hover color.
@@ -961,6 +2158,7 @@ impl WindowManager {
                 let name = format!("{new_id}.{suffix}");
                 if let Ok(obj) = sdi.get_mut(&name) {
                     obj.color = hover_color;
+                    obj.nine_patch = self.theme.button_pressed_patch.or(self.theme.button_patch);
                 }
             }
             self.hover_button = new_hover;
@@ -982,6 +2180,15 @@ impl WindowManager {
     }
 }
 
+/// The event reporting a snap to `region`.
+fn snap_event(id: &str, region: SnapRegion) -> WmEvent {
+    match region {
+        SnapRegion::Maximized => WmEvent::WindowMaximized(id.to_string()),
+        SnapRegion::Restore => WmEvent::WindowRestored(id.to_string()),
+        region => WmEvent::WindowSnapped(id.to_string(), region),
+    }
+}
+
 /// Compute new geometry after a resize drag.
 fn compute_resize(
     start: Geometry,
@@ -1044,12 +2251,12 @@ fn compute_resize(
     (x, y, w, h)
 }
 
-use oasis_types::backend::Color;
-
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::window::WindowType;
+    use oasis_types::backend::TextureId;
+    use oasis_types::input::Trigger;
+    use oasis_ui::nine_patch::NinePatch;
 
     fn app_config(id: &str) -> WindowConfig {
         WindowConfig {
@@ -1296,6 +2503,238 @@ mod tests {
         assert!(wm.drag.is_none());
     }
 
+    /// Drag window `id` by its titlebar and release with the cursor at
+    /// (`x`, `y`).
+    fn drag_titlebar_to(
+        wm: &mut WindowManager,
+        sdi: &mut SdiRegistry,
+        id: &str,
+        x: i32,
+        y: i32,
+    ) -> WmEvent {
+        let (tx, ty, _, th) = wm.get_window(id).unwrap().titlebar_rect(&wm.theme).unwrap();
+        let start = (tx + 5, ty + th as i32 / 2);
+        wm.handle_input(
+            &InputEvent::PointerClick {
+                x: start.0,
+                y: start.1,
+            },
+            sdi,
+        );
+        wm.handle_input(&InputEvent::CursorMove { x, y }, sdi);
+        wm.handle_input(&InputEvent::PointerRelease { x, y }, sdi)
+    }
+
+    /// A 480x272 WM with a 24px status bar and a 24px bottom bar.
+    fn psp_wm() -> WindowManager {
+        let theme = WmTheme {
+            maximize_top_inset: 24,
+            maximize_bottom_inset: 24,
+            ..WmTheme::default()
+        };
+        WindowManager::with_theme(480, 272, theme)
+    }
+
+    fn outer_rect(wm: &WindowManager, id: &str) -> (i32, i32, u32, u32) {
+        let w = wm.get_window(id).unwrap();
+        (w.x, w.y, w.outer_w, w.outer_h)
+    }
+
+    #[test]
+    fn drag_to_side_edges_tiles_half_screen() {
+        let mut sdi = SdiRegistry::new();
+        let mut wm = psp_wm();
+        wm.create_window(&app_config("w"), &mut sdi).unwrap();
+
+        let before = outer_rect(&wm, "w");
+        let event = drag_titlebar_to(&mut wm, &mut sdi, "w", 2, 120);
+        assert_eq!(event, WmEvent::WindowSnapped("w".into(), SnapRegion::Left));
+        assert_eq!(outer_rect(&wm, "w"), (0, 24, 240, 224));
+
+        drag_titlebar_to(&mut wm, &mut sdi, "w", 475, 120);
+        assert_eq!(outer_rect(&wm, "w"), (240, 24, 240, 224));
+        let content = sdi.get("w.content").unwrap();
+        assert!(content.x >= 240 && content.y > 24);
+
+        // Un-snapping returns to the geometry from before the first snap.
+        wm.restore_window("w", &mut sdi).unwrap();
+        assert_eq!(outer_rect(&wm, "w"), before);
+    }
+
+    #[test]
+    fn dragging_a_snapped_window_away_restores_its_size() {
+        let mut sdi = SdiRegistry::new();
+        let mut wm = psp_wm();
+        wm.create_window(&app_config("w"), &mut sdi).unwrap();
+        let (_, _, w, h) = outer_rect(&wm, "w");
+        wm.snap_window("w", SnapRegion::Right, &mut sdi).unwrap();
+        let event = drag_titlebar_to(&mut wm, &mut sdi, "w", 200, 120);
+        assert_eq!(event, WmEvent::WindowRestored("w".into()));
+        let win = wm.get_window("w").unwrap();
+        assert_eq!((win.outer_w, win.outer_h), (w, h));
+        assert_eq!(win.state, WindowState::Normal);
+    }
+
+    #[test]
+    fn snap_window_regions_and_restore() {
+        let mut sdi = SdiRegistry::new();
+        let mut wm = psp_wm();
+        wm.create_window(&app_config("w"), &mut sdi).unwrap();
+        wm.create_window(&app_config("other"), &mut sdi).unwrap();
+        let before = outer_rect(&wm, "w");
+
+        wm.snap_window("w", SnapRegion::Top, &mut sdi).unwrap();
+        assert_eq!(outer_rect(&wm, "w"), (0, 24, 480, 112));
+        wm.snap_window("w", SnapRegion::Bottom, &mut sdi).unwrap();
+        assert_eq!(outer_rect(&wm, "w"), (0, 136, 480, 112));
+        assert_eq!(
+            wm.get_window("w").unwrap().state,
+            WindowState::Snapped(SnapRegion::Bottom)
+        );
+
+        // Focus changes keep the snap.
+        wm.focus_window("other", &mut sdi).unwrap();
+        wm.focus_window("w", &mut sdi).unwrap();
+        assert_eq!(
+            wm.get_window("w").unwrap().state,
+            WindowState::Snapped(SnapRegion::Bottom)
+        );
+
+        // Maximizing a snapped window still restores to the original.
+        wm.snap_window("w", SnapRegion::Maximized, &mut sdi)
+            .unwrap();
+        assert_eq!(outer_rect(&wm, "w"), (0, 24, 480, 224));
+        wm.snap_window("w", SnapRegion::Restore, &mut sdi).unwrap();
+        assert_eq!(outer_rect(&wm, "w"), before);
+        assert_eq!(wm.get_window("w").unwrap().state, WindowState::Normal);
+
+        // Restoring an unsnapped window is a no-op; dialogs don't snap.
+        wm.snap_window("w", SnapRegion::Restore, &mut sdi).unwrap();
+        assert_eq!(outer_rect(&wm, "w"), before);
+        wm.create_window(&dialog_config("dlg"), &mut sdi).unwrap();
+        assert!(wm.snap_window("dlg", SnapRegion::Left, &mut sdi).is_err());
+        assert!(wm.snap_window("nope", SnapRegion::Left, &mut sdi).is_err());
+    }
+
+    #[test]
+    fn screen_resize_refits_maximized_and_snapped_windows() {
+        let mut sdi = SdiRegistry::new();
+        let mut wm = psp_wm();
+        for id in ["max", "snap", "min", "free"] {
+            wm.create_window(&app_config(id), &mut sdi).unwrap();
+        }
+        wm.maximize_window("max", &mut sdi).unwrap();
+        wm.snap_window("snap", SnapRegion::Right, &mut sdi).unwrap();
+        wm.maximize_window("min", &mut sdi).unwrap();
+        wm.minimize_window("min", &mut sdi).unwrap();
+        wm.move_window("free", 290, 90, &mut sdi).unwrap();
+
+        wm.set_screen_size(800, 600, &mut sdi);
+        assert_eq!(outer_rect(&wm, "max"), (0, 24, 800, 552));
+        assert_eq!(outer_rect(&wm, "snap"), (400, 24, 400, 552));
+        assert_eq!(sdi.get("snap.frame").unwrap().w, 400);
+        wm.restore_window("min", &mut sdi).unwrap();
+        assert_eq!(outer_rect(&wm, "min"), (0, 24, 800, 552));
+        // Fits on the bigger screen: left alone.
+        assert_eq!(outer_rect(&wm, "free").0, 300);
+        assert_eq!(outer_rect(&wm, "free").1, 100);
+
+        // Shrinking pulls it back on screen.
+        let (_, _, w, h) = outer_rect(&wm, "free");
+        wm.set_screen_size(400, 240, &mut sdi);
+        assert_eq!(
+            outer_rect(&wm, "free"),
+            (400 - w as i32, 240 - h as i32, w, h)
+        );
+    }
+
+    #[test]
+    fn apply_request_runs_wm_commands() {
+        let mut sdi = SdiRegistry::new();
+        let mut wm = psp_wm();
+        wm.create_window(&app_config("terminal"), &mut sdi).unwrap();
+        assert_eq!(
+            wm.apply_request("snap terminal left", &mut sdi).unwrap(),
+            WmEvent::WindowSnapped("terminal".into(), SnapRegion::Left)
+        );
+        assert_eq!(outer_rect(&wm, "terminal"), (0, 24, 240, 224));
+        assert_eq!(
+            wm.apply_request("snap terminal restore", &mut sdi).unwrap(),
+            WmEvent::WindowRestored("terminal".into())
+        );
+        assert_eq!(
+            wm.apply_request("minimize terminal", &mut sdi).unwrap(),
+            WmEvent::WindowMinimized("terminal".into())
+        );
+        assert!(
+            wm.apply_request("snap terminal sideways", &mut sdi)
+                .is_err()
+        );
+        assert!(wm.apply_request("explode terminal", &mut sdi).is_err());
+        assert_eq!(
+            wm.apply_request("close terminal", &mut sdi).unwrap(),
+            WmEvent::WindowClosed("terminal".into())
+        );
+        assert_eq!(wm.window_count(), 0);
+    }
+
+    #[test]
+    fn drag_to_top_edge_maximizes_below_status_bar() {
+        let mut sdi = SdiRegistry::new();
+        let mut wm = psp_wm();
+        wm.create_window(&app_config("w"), &mut sdi).unwrap();
+        let before = outer_rect(&wm, "w");
+
+        // The status bar counts as the top edge.
+        let event = drag_titlebar_to(&mut wm, &mut sdi, "w", 200, 10);
+        assert_eq!(event, WmEvent::WindowMaximized("w".into()));
+        assert_eq!(outer_rect(&wm, "w"), (0, 24, 480, 224));
+
+        // Restoring returns to where the drag started.
+        wm.restore_window("w", &mut sdi).unwrap();
+        assert_eq!(outer_rect(&wm, "w"), before);
+    }
+
+    #[test]
+    fn drag_to_corners_tiles_quarters() {
+        let mut sdi = SdiRegistry::new();
+        let mut wm = psp_wm();
+        wm.create_window(&app_config("w"), &mut sdi).unwrap();
+        drag_titlebar_to(&mut wm, &mut sdi, "w", 479, 30);
+        assert_eq!(outer_rect(&wm, "w"), (240, 24, 240, 112));
+        drag_titlebar_to(&mut wm, &mut sdi, "w", 0, 250);
+        assert_eq!(outer_rect(&wm, "w"), (0, 136, 240, 112));
+    }
+
+    #[test]
+    fn no_snap_away_from_edges_or_when_disabled() {
+        let mut sdi = SdiRegistry::new();
+        let mut wm = psp_wm();
+        wm.create_window(&app_config("w"), &mut sdi).unwrap();
+        let size = outer_rect(&wm, "w").2;
+        let event = drag_titlebar_to(&mut wm, &mut sdi, "w", 200, 120);
+        assert_eq!(event, WmEvent::WindowMoved("w".into()));
+        assert_eq!(outer_rect(&wm, "w").2, size);
+
+        let mut theme = wm.theme().clone();
+        theme.snap_threshold = 0;
+        wm.set_theme(theme);
+        drag_titlebar_to(&mut wm, &mut sdi, "w", 0, 120);
+        let win = wm.get_window("w").unwrap();
+        assert_eq!(win.state, WindowState::Normal);
+        assert_ne!(win.outer_h, 224);
+    }
+
+    #[test]
+    fn dialogs_do_not_snap() {
+        let mut sdi = SdiRegistry::new();
+        let mut wm = psp_wm();
+        wm.create_window(&dialog_config("dlg"), &mut sdi).unwrap();
+        let before = outer_rect(&wm, "dlg");
+        drag_titlebar_to(&mut wm, &mut sdi, "dlg", 0, 10);
+        assert_eq!(outer_rect(&wm, "dlg"), before);
+    }
+
     #[test]
     fn resize_east() {
         let mut sdi = SdiRegistry::new();
@@ -1380,6 +2819,313 @@ mod tests {
         assert!(wm.maximize_window("dlg", &mut sdi).is_err());
     }
 
+    #[test]
+    fn cycle_focus_visits_every_window_and_wraps() {
+        let mut sdi = SdiRegistry::new();
+        let mut wm = WindowManager::new(480, 272);
+        for id in ["a", "b", "c"] {
+            wm.create_window(&app_config(id), &mut sdi).unwrap();
+        }
+        assert_eq!(wm.window_ids_in_z_order(), ["a", "b", "c"]);
+
+        let mut seen = Vec::new();
+        for _ in 0..3 {
+            seen.push(wm.cycle_focus(true, &mut sdi).unwrap());
+        }
+        assert_eq!(seen, ["b", "a", "c"]);
+        assert_eq!(wm.window_ids_in_z_order(), ["a", "b", "c"]);
+
+        assert_eq!(wm.cycle_focus(false, &mut sdi).as_deref(), Some("a"));
+        assert_eq!(wm.window_ids_in_z_order(), ["b", "c", "a"]);
+        assert_eq!(wm.active_window(), Some("a"));
+        // The raised window gets the active titlebar color.
+        let titlebar = sdi.get("a.titlebar").unwrap();
+        assert_eq!(titlebar.color, wm.theme().titlebar_active_color);
+        assert_eq!(wm.cycle_focus(true, &mut sdi).as_deref(), Some("c"));
+    }
+
+    #[test]
+    fn cycle_focus_skips_minimized_unless_all_are() {
+        let mut sdi = SdiRegistry::new();
+        let mut wm = WindowManager::new(480, 272);
+        assert_eq!(wm.cycle_focus(true, &mut sdi), None);
+        for id in ["a", "b", "c"] {
+            wm.create_window(&app_config(id), &mut sdi).unwrap();
+        }
+        wm.minimize_window("b", &mut sdi).unwrap();
+        assert_eq!(wm.cycle_focus(true, &mut sdi).as_deref(), Some("a"));
+        assert_eq!(wm.cycle_focus(true, &mut sdi).as_deref(), Some("c"));
+        assert_eq!(wm.get_window("b").unwrap().state, WindowState::Minimized);
+
+        wm.minimize_window("a", &mut sdi).unwrap();
+        wm.minimize_window("c", &mut sdi).unwrap();
+        let focused = wm.cycle_focus(false, &mut sdi).unwrap();
+        let win = wm.get_window(&focused).unwrap();
+        assert_eq!(win.state, WindowState::Normal);
+        assert!(sdi.get(&win.sdi_name("content")).unwrap().visible);
+        assert_eq!(wm.active_window(), Some(focused.as_str()));
+    }
+
+    /// Taskbar along the bottom bar of [`psp_wm`].
+    const PSP_TASKBAR: TaskbarLayout = TaskbarLayout {
+        x: 24,
+        y: 254,
+        slot_w: 76,
+        slot_h: 12,
+        gap: 4,
+    };
+
+    #[test]
+    fn minimize_restore_keeps_maximized_and_snapped_geometry() {
+        let mut sdi = SdiRegistry::new();
+        let mut wm = psp_wm();
+        wm.create_window(&app_config("a"), &mut sdi).unwrap();
+        wm.create_window(&app_config("b"), &mut sdi).unwrap();
+        let before = outer_rect(&wm, "a");
+
+        wm.maximize_window("a", &mut sdi).unwrap();
+        wm.toggle_minimize("a", &mut sdi).unwrap();
+        assert_eq!(wm.get_window("a").unwrap().state, WindowState::Minimized);
+        wm.toggle_minimize("a", &mut sdi).unwrap();
+        assert_eq!(wm.get_window("a").unwrap().state, WindowState::Maximized);
+        assert_eq!(outer_rect(&wm, "a"), (0, 24, 480, 224));
+        assert_eq!(wm.active_window(), Some("a"));
+        // The pre-maximize geometry survived the round trip.
+        wm.restore_window("a", &mut sdi).unwrap();
+        assert_eq!(outer_rect(&wm, "a"), before);
+
+        wm.snap_window("b", SnapRegion::Left, &mut sdi).unwrap();
+        wm.minimize_window("b", &mut sdi).unwrap();
+        wm.minimize_window("b", &mut sdi).unwrap();
+        wm.restore_window("b", &mut sdi).unwrap();
+        assert_eq!(
+            wm.get_window("b").unwrap().state,
+            WindowState::Snapped(SnapRegion::Left)
+        );
+        assert_eq!(outer_rect(&wm, "b"), (0, 24, 240, 224));
+        assert!(sdi.get("b.content").unwrap().visible);
+        assert!(wm.toggle_minimize("nope", &mut sdi).is_err());
+    }
+
+    #[test]
+    fn taskbar_hit_test_and_click() {
+        let mut sdi = SdiRegistry::new();
+        let mut wm = psp_wm();
+        for id in ["a", "b", "c"] {
+            wm.create_window(&app_config(id), &mut sdi).unwrap();
+        }
+        wm.create_window(&dialog_config("dlg"), &mut sdi).unwrap();
+        // Focus changes don't reorder the taskbar; dialogs get no entry.
+        wm.focus_window("a", &mut sdi).unwrap();
+        let ids: Vec<&str> = wm.taskbar_windows().iter().map(|w| w.id.as_str()).collect();
+        assert_eq!(ids, ["a", "b", "c"]);
+
+        let layout = PSP_TASKBAR;
+        assert_eq!(wm.taskbar_hit_test(30, 258, &layout).as_deref(), Some("a"));
+        assert_eq!(wm.taskbar_hit_test(110, 258, &layout).as_deref(), Some("b"));
+        assert_eq!(wm.taskbar_hit_test(102, 258, &layout), None);
+        assert_eq!(wm.taskbar_hit_test(270, 258, &layout), None);
+
+        // Clicks only hit the taskbar once the WM knows about it.
+        let click = InputEvent::PointerClick { x: 190, y: 258 };
+        assert_ne!(
+            wm.handle_input(&click, &mut sdi),
+            WmEvent::TaskbarClick("c".into())
+        );
+        wm.set_taskbar_layout(Some(layout));
+        assert_eq!(
+            wm.handle_input(&click, &mut sdi),
+            WmEvent::TaskbarClick("c".into())
+        );
+        wm.close_window("b", &mut sdi).unwrap();
+        assert_eq!(wm.taskbar_hit_test(110, 258, &layout).as_deref(), Some("c"));
+    }
+
+    #[test]
+    fn minimize_animates_toward_taskbar_slot() {
+        let mut sdi = SdiRegistry::new();
+        let mut wm = psp_wm();
+        wm.create_window(&app_config("a"), &mut sdi).unwrap();
+        wm.create_window(&app_config("b"), &mut sdi).unwrap();
+
+        // No taskbar, no animation.
+        wm.minimize_window("a", &mut sdi).unwrap();
+        assert!(!wm.is_animating());
+        wm.restore_window("a", &mut sdi).unwrap();
+
+        wm.set_taskbar_layout(Some(PSP_TASKBAR));
+        let (x, y, w, h) = outer_rect(&wm, "b");
+        wm.minimize_window("b", &mut sdi).unwrap();
+        assert!(wm.is_animating());
+        let ghost = sdi.get("b.taskbar_ghost").unwrap();
+        assert_eq!((ghost.x, ghost.y, ghost.w, ghost.h), (x, y, w, h));
+
+        wm.tick(TASKBAR_ANIM_MS / 2, &mut sdi);
+        let ghost = sdi.get("b.taskbar_ghost").unwrap();
+        assert!(ghost.w < w && ghost.w > 76);
+        assert!(ghost.y > y && ghost.y < 254);
+
+        wm.tick(TASKBAR_ANIM_MS, &mut sdi);
+        assert!(!wm.is_animating());
+        assert!(sdi.get("b.taskbar_ghost").is_err());
+
+        // Restoring grows the outline back out of the slot, replacing a
+        // minimize still in progress.
+        wm.restore_window("b", &mut sdi).unwrap();
+        wm.minimize_window("b", &mut sdi).unwrap();
+        wm.tick(TASKBAR_ANIM_MS / 2, &mut sdi);
+        wm.restore_window("b", &mut sdi).unwrap();
+        let ghost = sdi.get("b.taskbar_ghost").unwrap();
+        assert_eq!((ghost.x, ghost.y, ghost.w, ghost.h), (104, 254, 76, 12));
+        wm.tick(TASKBAR_ANIM_MS, &mut sdi);
+        assert!(!wm.is_animating());
+        assert!(sdi.get("b.taskbar_ghost").is_err());
+        assert_eq!(outer_rect(&wm, "b"), (x, y, w, h));
+
+        // Closing a window drops its animation.
+        wm.minimize_window("b", &mut sdi).unwrap();
+        wm.close_window("b", &mut sdi).unwrap();
+        assert!(!wm.is_animating());
+        assert!(sdi.get("b.taskbar_ghost").is_err());
+    }
+
+    #[test]
+    fn animate_minimize_off_skips_animation() {
+        let mut sdi = SdiRegistry::new();
+        let mut wm = psp_wm();
+        wm.theme.animate_minimize = false;
+        wm.set_taskbar_layout(Some(PSP_TASKBAR));
+        wm.create_window(&app_config("a"), &mut sdi).unwrap();
+        wm.minimize_window("a", &mut sdi).unwrap();
+        assert!(!wm.is_animating());
+        wm.restore_window("a", &mut sdi).unwrap();
+        assert!(!wm.is_animating());
+        assert!(sdi.get("a.taskbar_ghost").is_err());
+    }
+
+    #[test]
+    fn minimize_button_and_taskbar_restore_round_trip() {
+        let mut sdi = SdiRegistry::new();
+        let mut wm = psp_wm();
+        wm.set_taskbar_layout(Some(PSP_TASKBAR));
+        wm.create_window(&app_config("a"), &mut sdi).unwrap();
+        wm.create_window(&app_config("b"), &mut sdi).unwrap();
+        let (bx, by, bw, bh) = wm
+            .get_window("b")
+            .unwrap()
+            .minimize_btn_rect(&wm.theme)
+            .unwrap();
+
+        let click = |x, y| InputEvent::PointerClick { x, y };
+        assert_eq!(
+            wm.handle_input(&click(bx + bw as i32 / 2, by + bh as i32 / 2), &mut sdi),
+            WmEvent::WindowMinimized("b".into())
+        );
+        let win = wm.get_window("b").unwrap();
+        assert_eq!(win.state, WindowState::Minimized);
+        // SDI objects are kept, only hidden.
+        assert!(!sdi.get("b.content").unwrap().visible);
+        assert_eq!(wm.active_window(), Some("a"));
+        assert_eq!(wm.cycle_focus(true, &mut sdi).as_deref(), Some("a"));
+
+        // The taskbar entry for "b" is the second slot.
+        let event = wm.handle_input(&click(110, 258), &mut sdi);
+        assert_eq!(event, WmEvent::TaskbarClick("b".into()));
+        wm.toggle_minimize("b", &mut sdi).unwrap();
+        assert_eq!(wm.get_window("b").unwrap().state, WindowState::Normal);
+        assert!(sdi.get("b.content").unwrap().visible);
+        assert_eq!(wm.active_window(), Some("b"));
+    }
+
+    #[test]
+    fn keyboard_grab_moves_and_resizes_on_commit() {
+        let mut sdi = SdiRegistry::new();
+        let mut wm = psp_wm();
+        wm.create_window(&app_config("a"), &mut sdi).unwrap();
+        wm.create_window(&app_config("b"), &mut sdi).unwrap();
+        let (x, y, w, h) = outer_rect(&wm, "a");
+        let press = |b| InputEvent::ButtonPress(b);
+
+        wm.begin_keyboard_grab("a", &mut sdi).unwrap();
+        assert_eq!(wm.active_window(), Some("a"));
+        wm.handle_input(&press(Button::Right), &mut sdi);
+        wm.handle_input(&press(Button::Right), &mut sdi);
+        wm.handle_input(&press(Button::Up), &mut sdi);
+        wm.handle_input(&InputEvent::TriggerPress(Trigger::Left), &mut sdi);
+        wm.handle_input(&press(Button::Down), &mut sdi);
+        wm.handle_input(&InputEvent::TriggerRelease(Trigger::Left), &mut sdi);
+
+        // Only the outline has moved so far.
+        let grab = wm.keyboard_grab().unwrap();
+        assert_eq!(
+            grab.pending,
+            Geometry {
+                x: x + 16,
+                y: y - 8,
+                w,
+                h: h + 8
+            }
+        );
+        assert!(!grab.resizing);
+        assert_eq!(outer_rect(&wm, "a"), (x, y, w, h));
+        // Pointer input doesn't reach the windows meanwhile.
+        assert_eq!(
+            wm.handle_input(&InputEvent::PointerClick { x: 0, y: 0 }, &mut sdi),
+            WmEvent::None
+        );
+
+        assert_eq!(
+            wm.handle_input(&press(Button::Confirm), &mut sdi),
+            WmEvent::WindowResized("a".into())
+        );
+        assert!(wm.keyboard_grab().is_none());
+        assert_eq!(outer_rect(&wm, "a"), (x + 16, y - 8, w, h + 8));
+        let (tx, ty, _, _) = wm
+            .get_window("a")
+            .unwrap()
+            .titlebar_rect(&wm.theme)
+            .unwrap();
+        let titlebar = sdi.get("a.titlebar").unwrap();
+        assert_eq!((titlebar.x, titlebar.y), (tx, ty));
+        assert!(tx >= x + 16);
+    }
+
+    #[test]
+    fn keyboard_grab_cancel_and_limits() {
+        let mut sdi = SdiRegistry::new();
+        let mut wm = psp_wm();
+        wm.create_window(&app_config("a"), &mut sdi).unwrap();
+        wm.create_window(&dialog_config("dlg"), &mut sdi).unwrap();
+        let before = outer_rect(&wm, "a");
+
+        assert_eq!(
+            wm.apply_request("move a", &mut sdi).unwrap(),
+            WmEvent::WindowFocused("a".into())
+        );
+        wm.handle_input(&InputEvent::ButtonPress(Button::Left), &mut sdi);
+        assert_eq!(
+            wm.handle_input(&InputEvent::ButtonPress(Button::Cancel), &mut sdi),
+            WmEvent::None
+        );
+        assert!(wm.keyboard_grab().is_none());
+        assert_eq!(outer_rect(&wm, "a"), before);
+
+        // Resizing stops at the minimum size.
+        wm.begin_keyboard_grab("a", &mut sdi).unwrap();
+        wm.handle_input(&InputEvent::TriggerPress(Trigger::Right), &mut sdi);
+        for _ in 0..100 {
+            wm.handle_input(&InputEvent::ButtonPress(Button::Left), &mut sdi);
+        }
+        let min_w = MIN_WINDOW_SIZE + wm.theme.border_width * 2;
+        assert_eq!(wm.keyboard_grab().unwrap().pending.w, min_w);
+        // Closing the window ends the grab.
+        wm.close_window("a", &mut sdi).unwrap();
+        assert!(wm.keyboard_grab().is_none());
+
+        assert!(wm.begin_keyboard_grab("dlg", &mut sdi).is_err());
+        assert!(wm.begin_keyboard_grab("nope", &mut sdi).is_err());
+    }
+
     #[test]
     fn titlebar_active_inactive_colors() {
         let mut sdi = SdiRegistry::new();
@@ -1649,4 +3395,531 @@ mod tests {
         assert_eq!(sdi.get("w.frame").unwrap().w, resized_w);
         assert_eq!(sdi.get("w.frame").unwrap().h, resized_h);
     }
+
+    #[test]
+    fn layout_round_trip_restores_geometry_state_and_order() {
+        let mut sdi = SdiRegistry::new();
+        let mut wm = WindowManager::new(800, 600);
+        for id in ["files", "ghost", "browser", "music"] {
+            wm.create_window(&app_config(id), &mut sdi).unwrap();
+        }
+        wm.move_window("files", 40, 30, &mut sdi).unwrap();
+        wm.snap_window("browser", SnapRegion::Left, &mut sdi)
+            .unwrap();
+        wm.minimize_window("music", &mut sdi).unwrap();
+        let layout = wm.serialize_layout();
+        assert!(layout.contains("state = \"left\""), "{layout}");
+
+        let mut sdi = SdiRegistry::new();
+        let mut restored = WindowManager::new(800, 600);
+        let mut opened = Vec::new();
+        let count = restored
+            .restore_layout(&layout, &mut sdi, |id| {
+                opened.push(id.to_string());
+                (id != "ghost").then(|| WindowConfig {
+                    x: None,
+                    y: None,
+                    ..app_config(id)
+                })
+            })
+            .unwrap();
+        assert_eq!(count, 3);
+        assert_eq!(opened, ["files", "ghost", "browser", "music"]);
+        assert_eq!(
+            restored.window_ids_in_z_order(),
+            ["files", "browser", "music"]
+        );
+        assert_eq!(restored.active_window(), Some("browser"));
+
+        let files = restored.get_window("files").unwrap();
+        assert_eq!((files.x, files.y), (50, 40));
+        assert_eq!(sdi.get("files.frame").unwrap().x, 50);
+        let browser = restored.get_window("browser").unwrap();
+        let original = wm.get_window("browser").unwrap();
+        assert_eq!(browser.state, WindowState::Snapped(SnapRegion::Left));
+        assert_eq!(browser.outer_w, original.outer_w);
+        assert_eq!(browser.saved_geometry, original.saved_geometry);
+        let music = restored.get_window("music").unwrap();
+        assert_eq!(music.state, WindowState::Minimized);
+        assert_eq!(music.minimized_from, Some(WindowState::Normal));
+        assert!(!sdi.get("music.frame").unwrap().visible);
+
+        // Restoring the browser goes back to its pre-snap geometry.
+        restored.restore_window("browser", &mut sdi).unwrap();
+        assert_eq!(restored.get_window("browser").unwrap().x, 10);
+        assert!(
+            restored
+                .restore_layout("window = 3", &mut sdi, |_| None)
+                .is_err()
+        );
+    }
+
+    fn dialog_button_center(wm: &WindowManager, id: &str, index: usize) -> (i32, i32) {
+        let dialog = &wm.dialog(id).unwrap().dialog;
+        let content = wm.get_window(id).unwrap().content_rect(&wm.theme);
+        let (x, y, w, h) = dialog.button_rects(&wm.dialog_theme, content)[index];
+        (x + w as i32 / 2, y + h as i32 / 2)
+    }
+
+    #[test]
+    fn dialog_is_centered_and_captures_input() {
+        let mut sdi = SdiRegistry::new();
+        let mut wm = WindowManager::new(800, 600);
+        wm.create_window(&app_config("app"), &mut sdi).unwrap();
+        let id = wm
+            .show_dialog(
+                Dialog::new("Quit", "Really quit?", &["Yes", "No"]),
+                &mut sdi,
+            )
+            .unwrap();
+        assert_eq!(wm.modal_dialog(), Some(id.as_str()));
+        assert_eq!(wm.active_window(), Some(id.as_str()));
+        let (x, y, w, h) = outer_rect(&wm, &id);
+        assert_eq!(x, (800 - w as i32) / 2);
+        assert_eq!(y, (600 - h as i32) / 2);
+        assert!(sdi.get(&format!("{id}.frame")).unwrap().overlay);
+
+        // A click on the app window underneath is swallowed.
+        let ev = wm.handle_input(&InputEvent::PointerClick { x: 20, y: 40 }, &mut sdi);
+        assert_eq!(ev, WmEvent::None);
+        assert_eq!(wm.active_window(), Some(id.as_str()));
+        // So is focus cycling.
+        assert_eq!(wm.cycle_focus(true, &mut sdi), Some(id.clone()));
+        assert!(wm.begin_keyboard_grab("app", &mut sdi).is_err());
+
+        let (bx, by) = dialog_button_center(&wm, &id, 1);
+        let ev = wm.handle_input(&InputEvent::PointerClick { x: bx, y: by }, &mut sdi);
+        assert_eq!(ev, WmEvent::DialogResult(id.clone(), 1));
+        assert!(wm.get_window(&id).is_none());
+        assert!(sdi.get(&format!("{id}.frame")).is_err());
+        assert_eq!(wm.modal_dialog(), None);
+        assert_eq!(wm.active_window(), Some("app"));
+    }
+
+    #[test]
+    fn dialog_keyboard_focus_confirm_and_cancel() {
+        let mut sdi = SdiRegistry::new();
+        let mut wm = WindowManager::new(480, 272);
+        let dialog = || {
+            let mut dialog = Dialog::new("Save", "Save changes?", &["Save", "Discard", "Cancel"]);
+            dialog.focused = 1;
+            dialog
+        };
+        let id = wm.show_dialog(dialog(), &mut sdi).unwrap();
+        let press = |b| InputEvent::ButtonPress(b);
+        assert_eq!(
+            wm.handle_input(&press(Button::Right), &mut sdi),
+            WmEvent::None
+        );
+        // Focus stops at the last button.
+        wm.handle_input(&press(Button::Right), &mut sdi);
+        wm.handle_input(&press(Button::Left), &mut sdi);
+        assert_eq!(
+            wm.handle_input(&press(Button::Confirm), &mut sdi),
+            WmEvent::DialogResult(id, 1)
+        );
+
+        let id = wm.show_dialog(dialog(), &mut sdi).unwrap();
+        assert_eq!(
+            wm.handle_input(&press(Button::Cancel), &mut sdi),
+            WmEvent::DialogResult(id, 2)
+        );
+        assert_eq!(wm.window_count(), 0);
+    }
+
+    #[test]
+    fn dialogs_stack() {
+        let mut sdi = SdiRegistry::new();
+        let mut wm = WindowManager::new(800, 600);
+        let outer = wm
+            .show_dialog(Dialog::new("A", "First", &["OK"]), &mut sdi)
+            .unwrap();
+        let inner = wm
+            .show_dialog(Dialog::new("B", "Second", &["OK", "Cancel"]), &mut sdi)
+            .unwrap();
+        assert_ne!(outer, inner);
+        assert_eq!(wm.modal_dialog(), Some(inner.as_str()));
+        assert!(
+            sdi.get(&format!("{inner}.frame")).unwrap().z
+                > sdi.get(&format!("{outer}.content")).unwrap().z
+        );
+
+        // A window opened behind the dialogs stays below them.
+        wm.create_window(&app_config("app"), &mut sdi).unwrap();
+        assert_eq!(
+            wm.window_ids_in_z_order(),
+            vec!["app", outer.as_str(), inner.as_str()]
+        );
+        assert_eq!(wm.active_window(), Some(inner.as_str()));
+
+        let press = InputEvent::ButtonPress(Button::Cancel);
+        assert_eq!(
+            wm.handle_input(&press, &mut sdi),
+            WmEvent::DialogResult(inner, 1)
+        );
+        assert_eq!(wm.modal_dialog(), Some(outer.as_str()));
+        assert_eq!(wm.active_window(), Some(outer.as_str()));
+        assert!(!wm.serialize_layout().contains(&outer));
+        assert_eq!(
+            wm.handle_input(&press, &mut sdi),
+            WmEvent::DialogResult(outer, 0)
+        );
+        assert_eq!(wm.active_window(), Some("app"));
+    }
+
+    #[test]
+    fn dialog_rejects_bad_buttons() {
+        let mut sdi = SdiRegistry::new();
+        let mut wm = WindowManager::new(800, 600);
+        assert!(
+            wm.show_dialog(Dialog::new("A", "B", &[]), &mut sdi)
+                .is_err()
+        );
+        let mut dialog = Dialog::new("A", "B", &["OK"]);
+        dialog.cancel = 1;
+        assert!(wm.show_dialog(dialog, &mut sdi).is_err());
+        let mut dialog = Dialog::new("A", "B", &["OK"]);
+        dialog.focused = 1;
+        assert!(wm.show_dialog(dialog, &mut sdi).is_err());
+        assert_eq!(wm.window_count(), 0);
+    }
+
+    #[test]
+    fn item_drag_drops_on_the_window_under_the_cursor() {
+        let mut sdi = SdiRegistry::new();
+        let mut wm = WindowManager::new(800, 600);
+        wm.create_window(&app_config("files"), &mut sdi).unwrap();
+        let mut music = app_config("music");
+        music.x = Some(300);
+        wm.create_window(&music, &mut sdi).unwrap();
+        let payload = DragPayload::VfsPath("/music/song.mp3".into());
+
+        // Press in the file window's content, then drag.
+        let (fx, fy, _, _) = wm.get_window("files").unwrap().content_rect(&wm.theme);
+        let ev = wm.handle_input(
+            &InputEvent::PointerClick {
+                x: fx + 5,
+                y: fy + 5,
+            },
+            &mut sdi,
+        );
+        assert_eq!(ev, WmEvent::ContentClick("files".into(), 5, 5));
+        assert!(wm.begin_drag(payload.clone(), "nope").is_err());
+        wm.begin_drag(payload.clone(), "files").unwrap();
+        assert_eq!(wm.item_drag().unwrap().target, None, "not over itself");
+
+        let (mx, my, _, _) = wm.get_window("music").unwrap().content_rect(&wm.theme);
+        let over_music = InputEvent::CursorMove {
+            x: mx + 7,
+            y: my + 9,
+        };
+        assert_eq!(wm.handle_input(&over_music, &mut sdi), WmEvent::None);
+        assert_eq!(wm.item_drag().unwrap().target.as_deref(), Some("music"));
+        // No window drag started underneath.
+        assert_eq!(outer_rect(&wm, "files").0, 10);
+
+        let ev = wm.handle_input(
+            &InputEvent::PointerRelease {
+                x: mx + 7,
+                y: my + 9,
+            },
+            &mut sdi,
+        );
+        assert_eq!(
+            ev,
+            WmEvent::Drop {
+                target_window: "music".into(),
+                payload: payload.clone(),
+                x: 7,
+                y: 9,
+            }
+        );
+        assert!(wm.item_drag().is_none());
+        assert_eq!(wm.active_window(), Some("music"));
+
+        // Released over the desktop or cancelled, nothing is dropped.
+        wm.begin_drag(payload.clone(), "files").unwrap();
+        let desktop = InputEvent::PointerRelease { x: 700, y: 500 };
+        assert_eq!(wm.handle_input(&desktop, &mut sdi), WmEvent::DragCancelled);
+        wm.begin_drag(payload, "files").unwrap();
+        let cancel = InputEvent::ButtonPress(Button::Cancel);
+        assert_eq!(wm.handle_input(&cancel, &mut sdi), WmEvent::DragCancelled);
+        assert!(wm.item_drag().is_none());
+    }
+
+    /// Records where `blit_sub` draws and how many rects are filled,
+    /// and logs texture, render target and translate calls.
+    #[derive(Default)]
+    struct BlitRecorder {
+        blit_subs: Vec<(i32, i32, u32, u32)>,
+        fills: usize,
+        /// Whether `create_render_target` succeeds.
+        render_targets: bool,
+        next_texture: u64,
+        log: Vec<String>,
+    }
+
+    impl BlitRecorder {
+        fn with_render_targets() -> Self {
+            Self {
+                render_targets: true,
+                ..Self::default()
+            }
+        }
+
+        /// Log entries starting with `prefix`, then clear the log.
+        fn take(&mut self, prefix: &str) -> Vec<String> {
+            let log = std::mem::take(&mut self.log);
+            log.into_iter().filter(|e| e.starts_with(prefix)).collect()
+        }
+    }
+
+    impl SdiBackend for BlitRecorder {
+        fn init(&mut self, _w: u32, _h: u32) -> Result<()> {
+            Ok(())
+        }
+        fn clear(&mut self, _color: Color) -> Result<()> {
+            Ok(())
+        }
+        fn blit(&mut self, tex: TextureId, x: i32, y: i32, w: u32, h: u32) -> Result<()> {
+            self.log.push(format!("blit {} {x},{y} {w}x{h}", tex.0));
+            Ok(())
+        }
+        fn blit_sub(
+            &mut self,
+            _tex: TextureId,
+            _src_x: u32,
+            _src_y: u32,
+            _src_w: u32,
+            _src_h: u32,
+            x: i32,
+            y: i32,
+            w: u32,
+            h: u32,
+        ) -> Result<()> {
+            self.blit_subs.push((x, y, w, h));
+            Ok(())
+        }
+        fn fill_rect(&mut self, _x: i32, _y: i32, _w: u32, _h: u32, _color: Color) -> Result<()> {
+            self.fills += 1;
+            Ok(())
+        }
+        fn draw_text(&mut self, _t: &str, _x: i32, _y: i32, _s: u16, _c: Color) -> Result<()> {
+            Ok(())
+        }
+        fn swap_buffers(&mut self) -> Result<()> {
+            Ok(())
+        }
+        fn load_texture(&mut self, _w: u32, _h: u32, _data: &[u8]) -> Result<TextureId> {
+            Ok(TextureId(0))
+        }
+        fn destroy_texture(&mut self, tex: TextureId) -> Result<()> {
+            self.log.push(format!("destroy {}", tex.0));
+            Ok(())
+        }
+        fn set_clip_rect(&mut self, _x: i32, _y: i32, _w: u32, _h: u32) -> Result<()> {
+            Ok(())
+        }
+        fn reset_clip_rect(&mut self) -> Result<()> {
+            Ok(())
+        }
+        fn measure_text(&self, text: &str, font_size: u16) -> u32 {
+            text.len() as u32 * u32::from(font_size) / 2
+        }
+        fn read_pixels(&self, _x: i32, _y: i32, w: u32, h: u32) -> Result<Vec<u8>> {
+            Ok(vec![0; (w * h * 4) as usize])
+        }
+        fn shutdown(&mut self) -> Result<()> {
+            Ok(())
+        }
+        fn create_render_target(&mut self, w: u32, h: u32) -> Result<TextureId> {
+            if !self.render_targets {
+                return Err(OasisError::Backend("no render targets".to_string()));
+            }
+            self.next_texture += 1;
+            self.log
+                .push(format!("create {} {w}x{h}", self.next_texture));
+            Ok(TextureId(self.next_texture))
+        }
+        fn set_render_target(&mut self, target: Option<TextureId>) -> Result<()> {
+            self.log.push(format!("target {:?}", target.map(|t| t.0)));
+            Ok(())
+        }
+        fn push_translate(&mut self, dx: i32, dy: i32) -> Result<()> {
+            self.log.push(format!("translate {dx},{dy}"));
+            Ok(())
+        }
+    }
+
+    /// Draw only the `frame` object of window `id`.
+    fn draw_frame(sdi: &mut SdiRegistry, id: &str) -> BlitRecorder {
+        let frame = format!("{id}.frame");
+        let names: Vec<String> = sdi.names().map(str::to_string).collect();
+        for name in names.iter().filter(|n| **n != frame) {
+            sdi.get_mut(name).unwrap().visible = false;
+        }
+        let mut backend = BlitRecorder::default();
+        sdi.draw(&mut backend).unwrap();
+        backend
+    }
+
+    #[test]
+    fn frame_patch_draws_the_frame_in_nine_blits() {
+        let patch = NinePatch {
+            texture: TextureId(7),
+            tex_width: 24,
+            tex_height: 24,
+            left: 4,
+            right: 4,
+            top: 6,
+            bottom: 4,
+        };
+        let theme = WmTheme {
+            frame_patch: Some(patch),
+            ..WmTheme::default()
+        };
+        let mut sdi = SdiRegistry::new();
+        let mut wm = WindowManager::with_theme(800, 600, theme);
+        wm.create_window(&app_config("w1"), &mut sdi).unwrap();
+        let (x, y, w, h) = outer_rect(&wm, "w1");
+
+        let drawn = draw_frame(&mut sdi, "w1");
+        assert_eq!(drawn.fills, 0);
+        assert_eq!(drawn.blit_subs.len(), 9);
+        // Corners keep their size at the frame's corners.
+        assert_eq!(drawn.blit_subs[0], (x, y, 4, 6));
+        assert_eq!(
+            drawn.blit_subs[8],
+            (x + w as i32 - 4, y + h as i32 - 4, 4, 4)
+        );
+    }
+
+    #[test]
+    fn button_patches_follow_the_pointer() {
+        let patch = |id| NinePatch {
+            texture: TextureId(id),
+            tex_width: 8,
+            tex_height: 8,
+            left: 2,
+            right: 2,
+            top: 2,
+            bottom: 2,
+        };
+        let theme = WmTheme {
+            button_patch: Some(patch(1)),
+            button_pressed_patch: Some(patch(2)),
+            ..WmTheme::default()
+        };
+        let mut sdi = SdiRegistry::new();
+        let mut wm = WindowManager::with_theme(800, 600, theme);
+        wm.create_window(&app_config("w1"), &mut sdi).unwrap();
+        let texture =
+            |sdi: &SdiRegistry, name: &str| sdi.get(name).unwrap().nine_patch.map(|p| p.texture);
+        assert_eq!(texture(&sdi, "w1.btn_close"), Some(TextureId(1)));
+        assert_eq!(texture(&sdi, "w1.btn_minimize"), Some(TextureId(1)));
+
+        let (bx, by, bw, bh) = wm
+            .get_window("w1")
+            .unwrap()
+            .close_btn_rect(wm.theme())
+            .unwrap();
+        let (cx, cy) = (bx + bw as i32 / 2, by + bh as i32 / 2);
+        wm.handle_input(&InputEvent::CursorMove { x: cx, y: cy }, &mut sdi);
+        assert_eq!(texture(&sdi, "w1.btn_close"), Some(TextureId(2)));
+        wm.handle_input(&InputEvent::CursorMove { x: 0, y: 0 }, &mut sdi);
+        assert_eq!(texture(&sdi, "w1.btn_close"), Some(TextureId(1)));
+    }
+
+    #[test]
+    fn frames_without_a_patch_stay_flat() {
+        let mut sdi = SdiRegistry::new();
+        let mut wm = WindowManager::new(800, 600);
+        wm.create_window(&app_config("w1"), &mut sdi).unwrap();
+        let drawn = draw_frame(&mut sdi, "w1");
+        assert!(drawn.blit_subs.is_empty());
+        assert!(drawn.fills > 0);
+    }
+
+    /// Draw `wm` and return the windows whose content callback ran.
+    fn draw_content(
+        wm: &mut WindowManager,
+        sdi: &mut SdiRegistry,
+        backend: &mut BlitRecorder,
+    ) -> Vec<String> {
+        let mut painted = Vec::new();
+        wm.draw_with_clips(sdi, backend, |id, _, _, _, _, _| {
+            painted.push(id.to_string());
+            Ok(())
+        })
+        .unwrap();
+        painted
+    }
+
+    #[test]
+    fn cached_content_repaints_only_when_dirty_or_moved() {
+        let mut sdi = SdiRegistry::new();
+        let mut wm = WindowManager::new(800, 600);
+        wm.set_content_caching(true);
+        wm.create_window(&app_config("w1"), &mut sdi).unwrap();
+        let (cx, cy, cw, ch) = wm.get_window("w1").unwrap().content_rect(wm.theme());
+        let mut backend = BlitRecorder::with_render_targets();
+
+        // The first draw paints into a new target, shifted into it.
+        assert_eq!(draw_content(&mut wm, &mut sdi, &mut backend), ["w1"]);
+        assert!(!wm.get_window("w1").unwrap().content_dirty);
+        assert_eq!(
+            backend.take(""),
+            [
+                format!("create 1 {cw}x{ch}"),
+                "target Some(1)".to_string(),
+                format!("translate {},{}", -cx, -cy),
+                "target None".to_string(),
+                format!("blit 1 {cx},{cy} {cw}x{ch}"),
+            ]
+        );
+
+        // Clean frames only composite the cached texture.
+        assert!(draw_content(&mut wm, &mut sdi, &mut backend).is_empty());
+        assert_eq!(
+            backend.take("blit 1"),
+            [format!("blit 1 {cx},{cy} {cw}x{ch}")]
+        );
+
+        wm.mark_dirty("w1");
+        assert_eq!(draw_content(&mut wm, &mut sdi, &mut backend), ["w1"]);
+
+        // Moving repaints into the same target; resizing needs a new one.
+        wm.move_window("w1", 10, 10, &mut sdi).unwrap();
+        assert_eq!(draw_content(&mut wm, &mut sdi, &mut backend), ["w1"]);
+        assert!(backend.take("create").is_empty());
+        wm.resize_window("w1", 300, 200, &mut sdi).unwrap();
+        assert_eq!(draw_content(&mut wm, &mut sdi, &mut backend), ["w1"]);
+        let log = backend.take("");
+        assert!(log.contains(&"destroy 1".to_string()), "{log:?}");
+        assert!(log.iter().any(|e| e.starts_with("create 2 ")), "{log:?}");
+
+        // Closing the window frees its target at the next draw.
+        wm.close_window("w1", &mut sdi).unwrap();
+        draw_content(&mut wm, &mut sdi, &mut backend);
+        assert_eq!(backend.take("destroy"), ["destroy 2"]);
+    }
+
+    #[test]
+    fn content_caching_falls_back_without_render_targets() {
+        let mut sdi = SdiRegistry::new();
+        let mut wm = WindowManager::new(800, 600);
+        wm.create_window(&app_config("w1"), &mut sdi).unwrap();
+        let mut backend = BlitRecorder::default();
+
+        // Off by default: content is drawn every frame.
+        assert!(!wm.content_caching());
+        assert_eq!(draw_content(&mut wm, &mut sdi, &mut backend), ["w1"]);
+        assert_eq!(draw_content(&mut wm, &mut sdi, &mut backend), ["w1"]);
+
+        wm.set_content_caching(true);
+        assert_eq!(draw_content(&mut wm, &mut sdi, &mut backend), ["w1"]);
+        assert!(!wm.content_caching());
+        assert_eq!(draw_content(&mut wm, &mut sdi, &mut backend), ["w1"]);
+        assert!(backend.take("target").is_empty());
+    }
 }
@@ -0,0 +1,66 @@
+//! Taskbar slot layout.
+//!
+//! The WM does not draw a taskbar; frontends do, in whatever bar their
+//! skin has. They describe where the entries go with a [`TaskbarLayout`]
+//! so the WM can hit-test clicks on them and animate minimized windows
+//! toward their entry.
+
+/// Where a taskbar draws its entries: one fixed-size slot per window,
+/// left to right from (`x`, `y`), in the order the windows were opened.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TaskbarLayout {
+    /// Left edge of the first slot.
+    pub x: i32,
+    /// Top edge of every slot.
+    pub y: i32,
+    /// Width of each slot.
+    pub slot_w: u32,
+    /// Height of each slot.
+    pub slot_h: u32,
+    /// Horizontal gap between slots.
+    pub gap: u32,
+}
+
+impl TaskbarLayout {
+    /// Rectangle of the slot at `index`.
+    pub fn slot_rect(&self, index: usize) -> (i32, i32, u32, u32) {
+        let x = self.x + index as i32 * (self.slot_w + self.gap) as i32;
+        (x, self.y, self.slot_w, self.slot_h)
+    }
+
+    /// Index of the slot containing (`px`, `py`), if any. Gaps between
+    /// slots belong to no slot.
+    pub fn slot_at(&self, px: i32, py: i32) -> Option<usize> {
+        if py < self.y || py >= self.y + self.slot_h as i32 || px < self.x {
+            return None;
+        }
+        let stride = (self.slot_w + self.gap).max(1) as i32;
+        let offset = px - self.x;
+        (offset % stride < self.slot_w as i32).then_some((offset / stride) as usize)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn slots_and_gaps() {
+        let layout = TaskbarLayout {
+            x: 24,
+            y: 250,
+            slot_w: 60,
+            slot_h: 12,
+            gap: 4,
+        };
+        assert_eq!(layout.slot_rect(0), (24, 250, 60, 12));
+        assert_eq!(layout.slot_rect(2), (152, 250, 60, 12));
+        assert_eq!(layout.slot_at(24, 250), Some(0));
+        assert_eq!(layout.slot_at(83, 261), Some(0));
+        assert_eq!(layout.slot_at(85, 255), None);
+        assert_eq!(layout.slot_at(88, 255), Some(1));
+        assert_eq!(layout.slot_at(23, 255), None);
+        assert_eq!(layout.slot_at(30, 262), None);
+        assert_eq!(layout.slot_at(30, 249), None);
+    }
+}
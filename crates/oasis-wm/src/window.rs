@@ -5,6 +5,8 @@
 //! etc. The WM handles behavior; the skin handles appearance.
 
 use oasis_types::backend::Color;
+use oasis_ui::nine_patch::NinePatch;
+use serde::{Deserialize, Serialize};
 
 /// Unique window identifier (also the SDI object name prefix).
 pub type WindowId = String;
@@ -30,6 +32,85 @@ pub enum WindowState {
     Normal,
     Minimized,
     Maximized,
+    /// Tiled to part of the screen by snapping.
+    Snapped(SnapRegion),
+}
+
+/// Where a window can be snapped. Halves and quarters are of the work
+/// area: the screen minus the theme's maximize insets (status and bottom
+/// bars).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SnapRegion {
+    /// Left half.
+    Left,
+    /// Right half.
+    Right,
+    /// Top half.
+    Top,
+    /// Bottom half.
+    Bottom,
+    /// Top-left quarter.
+    TopLeft,
+    /// Top-right quarter.
+    TopRight,
+    /// Bottom-left quarter.
+    BottomLeft,
+    /// Bottom-right quarter.
+    BottomRight,
+    /// The whole work area, i.e. maximized.
+    Maximized,
+    /// Back to the geometry the window had before it was snapped.
+    Restore,
+}
+
+impl SnapRegion {
+    /// Every region, in the order `wm snap` lists them.
+    pub const ALL: [Self; 10] = [
+        Self::Left,
+        Self::Right,
+        Self::Top,
+        Self::Bottom,
+        Self::TopLeft,
+        Self::TopRight,
+        Self::BottomLeft,
+        Self::BottomRight,
+        Self::Maximized,
+        Self::Restore,
+    ];
+
+    /// Parse a region name as typed in `wm snap`. Unknown names give
+    /// `None`.
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "left" => Some(Self::Left),
+            "right" => Some(Self::Right),
+            "top" => Some(Self::Top),
+            "bottom" => Some(Self::Bottom),
+            "top-left" => Some(Self::TopLeft),
+            "top-right" => Some(Self::TopRight),
+            "bottom-left" => Some(Self::BottomLeft),
+            "bottom-right" => Some(Self::BottomRight),
+            "max" | "maximized" => Some(Self::Maximized),
+            "restore" => Some(Self::Restore),
+            _ => None,
+        }
+    }
+
+    /// The name [`from_name`](Self::from_name) takes for this region.
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Left => "left",
+            Self::Right => "right",
+            Self::Top => "top",
+            Self::Bottom => "bottom",
+            Self::TopLeft => "top-left",
+            Self::TopRight => "top-right",
+            Self::BottomLeft => "bottom-left",
+            Self::BottomRight => "bottom-right",
+            Self::Maximized => "maximized",
+            Self::Restore => "restore",
+        }
+    }
 }
 
 /// Configuration for creating a new window.
@@ -149,6 +230,26 @@ pub struct WmTheme {
     pub maximize_top_inset: u32,
     /// Bottom inset when maximized (for bottom bar awareness).
     pub maximize_bottom_inset: u32,
+    /// Distance in pixels from a screen edge within which a dragged window
+    /// snaps when released (0 disables snapping).
+    pub snap_threshold: u32,
+    /// Animate windows shrinking into their taskbar entry on minimize and
+    /// growing back out on restore (needs a taskbar layout on the WM).
+    pub animate_minimize: bool,
+    /// Pixels a keyboard move/resize step moves or resizes a window by.
+    pub keyboard_step: u32,
+    /// Nine-patch drawn for window frames instead of the flat
+    /// `frame_color` fill and stroke. Set from a skin's `window_frame`
+    /// asset once its texture is loaded.
+    pub frame_patch: Option<NinePatch>,
+    /// Nine-patch drawn for titlebar buttons instead of their flat
+    /// colors; the glyphs stay on top. Set from a skin's `button_normal`
+    /// asset.
+    pub button_patch: Option<NinePatch>,
+    /// Nine-patch drawn for the titlebar button under the pointer. Set
+    /// from a skin's `button_pressed` asset; without it that button
+    /// keeps `button_patch` and only changes color.
+    pub button_pressed_patch: Option<NinePatch>,
 }
 
 impl Default for WmTheme {
@@ -196,12 +297,18 @@ impl Default for WmTheme {
             content_stroke_color: Color::rgba(255, 255, 255, 20),
             maximize_top_inset: 0,
             maximize_bottom_inset: 0,
+            snap_threshold: 8,
+            animate_minimize: true,
+            keyboard_step: 8,
+            frame_patch: None,
+            button_patch: None,
+            button_pressed_patch: None,
         }
     }
 }
 
 /// Stored geometry for restore-from-maximize.
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
 pub struct Geometry {
     pub x: i32,
     pub y: i32,
@@ -233,6 +340,11 @@ pub struct Window {
     pub outer_h: u32,
     /// Saved geometry for restoring from maximized state.
     pub saved_geometry: Option<Geometry>,
+    /// State to return to when restored from minimized.
+    pub minimized_from: Option<WindowState>,
+    /// Whether the content has changed since it was last cached (see
+    /// [`WindowManager::mark_dirty`](crate::WindowManager::mark_dirty)).
+    pub content_dirty: bool,
 }
 
 impl Window {
@@ -264,6 +376,8 @@ impl Window {
             outer_w,
             outer_h,
             saved_geometry: None,
+            minimized_from: None,
+            content_dirty: true,
         }
     }
 